@@ -0,0 +1,55 @@
+//! Loop route cache key derivation, run once per loop route request before
+//! the cache lookup. Measures building the preferences hash and the key for
+//! a plain request and one setting most preferences.
+//!
+//! Run with `cargo bench --bench cache_key`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use easyroute::cache::{loop_route_cache_key, CacheKeyPrecision, RoutePreferencesHash};
+use easyroute::models::{Coordinates, MustInclude, PoiCategory, SurfacePreference};
+use std::hint::black_box;
+
+fn bench_cache_key(c: &mut Criterion) {
+    let start = Coordinates::new(48.8566, 2.3522).unwrap();
+    let categories = [
+        PoiCategory::Park,
+        PoiCategory::Museum,
+        PoiCategory::Viewpoint,
+    ];
+    let pins = [
+        MustInclude::Point(Coordinates::new(48.86, 2.34).unwrap()),
+        MustInclude::Point(Coordinates::new(48.85, 2.36).unwrap()),
+    ];
+
+    c.bench_function("loop_route_cache_key/plain", |b| {
+        b.iter(|| {
+            let prefs = RoutePreferencesHash::new(None, false);
+            loop_route_cache_key(
+                black_box(&start),
+                black_box(5.0),
+                "walk",
+                &prefs,
+                &CacheKeyPrecision::DEFAULT,
+            )
+        })
+    });
+    c.bench_function("loop_route_cache_key/tuned", |b| {
+        b.iter(|| {
+            let prefs = RoutePreferencesHash::new(Some(black_box(&categories)), true)
+                .with_excluded_categories(Some(&[PoiCategory::Cafe]))
+                .with_pace_min_per_km(Some(5.5))
+                .with_surface(SurfacePreference::Trail)
+                .with_must_include(black_box(&pins));
+            loop_route_cache_key(
+                black_box(&start),
+                black_box(5.0),
+                "run",
+                &prefs,
+                &CacheKeyPrecision::DEFAULT,
+            )
+        })
+    });
+}
+
+criterion_group!(benches, bench_cache_key);
+criterion_main!(benches);
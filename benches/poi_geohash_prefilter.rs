@@ -0,0 +1,83 @@
+//! Radius searches on a dense POI grid: plain `ST_DWithin` vs the geohash
+//! range pre-filter (`POI_GEOHASH_PREFILTER`).
+//!
+//! Needs a migrated PostGIS database whose `pois` table may be wiped:
+//!
+//!   BENCH_DATABASE_URL=postgres://.../easyroute_test cargo bench --bench poi_geohash_prefilter
+//!
+//! Without `BENCH_DATABASE_URL` the benchmark is skipped.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use easyroute::db::{PgPoiRepository, PoiRepository};
+use easyroute::models::Coordinates;
+use sqlx::PgPool;
+
+/// Dense city core: GRID_SIZE x GRID_SIZE POIs, ~55 m apart
+const GRID_SIZE: i32 = 400;
+const GRID_STEP_DEG: f64 = 0.0005;
+const GRID_ORIGIN: (f64, f64) = (48.80, 2.25);
+const RADII_M: [f64; 3] = [300.0, 1000.0, 3000.0];
+
+async fn seed_dense_grid(pool: &PgPool) {
+    sqlx::migrate!("./migrations").run(pool).await.unwrap();
+    sqlx::query("TRUNCATE TABLE pois CASCADE")
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO pois (name, category, location, popularity_score)
+         SELECT 'bench ' || i,
+                (ARRAY['park', 'monument', 'cafe', 'museum'])[1 + i % 4],
+                ST_SetSRID(ST_MakePoint($2 + (i % $3) * $4, $1 + (i / $3) * $4), 4326)::geography,
+                50.0
+         FROM generate_series(0, $3 * $3 - 1) AS i",
+    )
+    .bind(GRID_ORIGIN.0)
+    .bind(GRID_ORIGIN.1)
+    .bind(GRID_SIZE)
+    .bind(GRID_STEP_DEG)
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query("ANALYZE pois").execute(pool).await.unwrap();
+}
+
+fn bench_geohash_prefilter(c: &mut Criterion) {
+    let Ok(database_url) = std::env::var("BENCH_DATABASE_URL") else {
+        eprintln!("BENCH_DATABASE_URL not set, skipping poi_geohash_prefilter");
+        return;
+    };
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let pool = runtime.block_on(async {
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        seed_dense_grid(&pool).await;
+        pool
+    });
+
+    let offset = GRID_SIZE as f64 * GRID_STEP_DEG / 2.0;
+    let center = Coordinates::new(GRID_ORIGIN.0 + offset, GRID_ORIGIN.1 + offset).unwrap();
+    let repos = [
+        ("st_dwithin", PgPoiRepository::new(pool.clone())),
+        (
+            "geohash_prefilter",
+            PgPoiRepository::new(pool.clone()).with_geohash_prefilter(true),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("poi_radius_search");
+    for radius_m in RADII_M {
+        for (label, repo) in &repos {
+            group.bench_with_input(BenchmarkId::new(*label, radius_m), &radius_m, |b, &r| {
+                b.to_async(&runtime).iter(|| async {
+                    repo.find_within_radius(&center, r, None, 200)
+                        .await
+                        .unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_geohash_prefilter);
+criterion_main!(benches);
@@ -0,0 +1,87 @@
+//! Waypoint candidate scoring on large candidate sets (long routes keep up to
+//! 300 candidates after discovery). Measures building the per-request
+//! `CandidateIndex` and a full four-step selection pass with the Advanced strategy.
+//!
+//! Run with `cargo bench --bench poi_scoring`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use easyroute::config::RouteGeneratorConfig;
+use easyroute::models::{Coordinates, Poi, PoiCategory, RoutePreferences};
+use easyroute::services::route_generator::candidates::CandidateIndex;
+use easyroute::services::route_generator::scoring_strategy::{
+    AdvancedStrategy, PoiScoringStrategy, ScoringContext,
+};
+use easyroute::services::route_generator::variation;
+use std::hint::black_box;
+
+const CANDIDATE_COUNTS: [usize; 3] = [100, 300, 1000];
+const WAYPOINTS: usize = 4;
+
+fn candidates(n: usize) -> Vec<Poi> {
+    (0..n)
+        .map(|i| {
+            let angle = i as f64 * 2.399;
+            let radius_deg = 0.01 + 0.04 * (i % 7) as f64 / 6.0;
+            Poi::new(
+                format!("POI {i}"),
+                PoiCategory::Park,
+                Coordinates::new(
+                    48.8566 + radius_deg * angle.sin(),
+                    2.3522 + radius_deg * angle.cos(),
+                )
+                .unwrap(),
+                (i % 100) as f32,
+            )
+        })
+        .collect()
+}
+
+/// Greedy selection: score the remaining candidates, take the best, repeat
+fn select(strategy: &AdvancedStrategy, set: &CandidateIndex, preferences: &RoutePreferences) {
+    let mut selected = Vec::with_capacity(WAYPOINTS);
+    let mut remaining: Vec<usize> = (0..set.len()).collect();
+    let mut rng = variation::seeded_rng(None, 0);
+    for _ in 0..WAYPOINTS {
+        let mut context = ScoringContext {
+            target_waypoint_distance: 4.0,
+            target_distance_km: 15.0,
+            rng: &mut rng,
+            preferences,
+            already_selected: &selected,
+        };
+        let scored = strategy.score_pois(set, &remaining, &mut context);
+        let Some(&(_, best)) = scored
+            .iter()
+            .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+        else {
+            break;
+        };
+        remaining.retain(|&i| i != best);
+        selected.push(best);
+    }
+    black_box(selected);
+}
+
+fn bench_scoring(c: &mut Criterion) {
+    let start = Coordinates::new(48.8566, 2.3522).unwrap();
+    let preferences = RoutePreferences::default();
+    let strategy = AdvancedStrategy::new(RouteGeneratorConfig::default());
+
+    let mut group = c.benchmark_group("poi_scoring");
+    for n in CANDIDATE_COUNTS {
+        let pois = candidates(n);
+        group.bench_with_input(
+            BenchmarkId::new("build_candidate_set", n),
+            &pois,
+            |b, pois| b.iter(|| CandidateIndex::new(&start, black_box(pois), &preferences)),
+        );
+        let set = CandidateIndex::new(&start, &pois, &preferences);
+        group.bench_with_input(BenchmarkId::new("advanced_selection", n), &set, |b, set| {
+            b.iter(|| select(&strategy, set, &preferences))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_scoring);
+criterion_main!(benches);
@@ -0,0 +1,116 @@
+//! Concurrent POI lookups against a region database: a single shared
+//! connection vs the WAL read pool used by the on-device server.
+//!
+//! Run with `cargo bench --bench sqlite_read_pool --features sqlite`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use easyroute::db::{PoiRepository, SqlitePoiRepository, SqliteReadPoolConfig};
+use easyroute::models::{Coordinates, Poi, PoiCategory};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Grid of POIs roughly covering central Paris
+const GRID_SIZE: usize = 100;
+const GRID_STEP_DEG: f64 = 0.001;
+const QUERY_RADIUS_M: f64 = 1500.0;
+const CONCURRENT_QUERIES: [usize; 3] = [1, 8, 32];
+
+async fn build_region_db() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("easyroute-bench-{}.db", Uuid::new_v4()));
+    let opts = SqliteConnectOptions::from_str(&format!("sqlite:{}", path.display()))
+        .unwrap()
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await
+        .unwrap();
+    SqlitePoiRepository::create_schema(&pool).await.unwrap();
+
+    let categories = [PoiCategory::Park, PoiCategory::Monument, PoiCategory::Cafe];
+    let pois: Vec<Poi> = (0..GRID_SIZE * GRID_SIZE)
+        .map(|i| Poi {
+            id: Uuid::new_v4(),
+            name: format!("POI {i}"),
+            category: categories[i % categories.len()].clone(),
+            coordinates: Coordinates::new(
+                48.82 + (i / GRID_SIZE) as f64 * GRID_STEP_DEG,
+                2.30 + (i % GRID_SIZE) as f64 * GRID_STEP_DEG,
+            )
+            .unwrap(),
+            popularity_score: (i % 100) as f32,
+            description: None,
+            estimated_visit_duration_minutes: Some(15),
+            osm_id: None,
+        })
+        .collect();
+    SqlitePoiRepository::new(pool.clone())
+        .insert_batch(&pois)
+        .await
+        .unwrap();
+    pool.close().await;
+    path
+}
+
+async fn query_concurrently(repo: &Arc<SqlitePoiRepository>, queries: usize) {
+    let handles: Vec<_> = (0..queries)
+        .map(|i| {
+            let repo = repo.clone();
+            let center = Coordinates::new(
+                48.84 + (i % 10) as f64 * 0.005,
+                2.32 + (i / 10 % 10) as f64 * 0.005,
+            )
+            .unwrap();
+            tokio::spawn(async move {
+                repo.find_within_radius(&center, QUERY_RADIUS_M, None, 100)
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap();
+    }
+}
+
+fn bench_read_pool(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let path = runtime.block_on(build_region_db());
+    let path_str = path.to_str().unwrap().to_string();
+
+    let single = SqliteReadPoolConfig {
+        max_connections: 1,
+        ..SqliteReadPoolConfig::server()
+    };
+    let pools = [
+        ("single_connection", single),
+        ("server_read_pool", SqliteReadPoolConfig::server()),
+    ];
+
+    let mut group = c.benchmark_group("find_within_radius");
+    for (label, config) in &pools {
+        let pool = runtime
+            .block_on(SqlitePoiRepository::open_read_pool(&path_str, config))
+            .unwrap();
+        let repo = Arc::new(SqlitePoiRepository::new(pool));
+        for queries in CONCURRENT_QUERIES {
+            group.bench_with_input(BenchmarkId::new(*label, queries), &queries, |b, &n| {
+                b.to_async(&runtime).iter(|| query_concurrently(&repo, n))
+            });
+        }
+    }
+    group.finish();
+
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+criterion_group!(benches, bench_read_pool);
+criterion_main!(benches);
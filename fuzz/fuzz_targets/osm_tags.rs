@@ -0,0 +1,27 @@
+//! OSM tags straight from a PBF extract: classification, popularity,
+//! duration, naming and descriptions must not panic, and popularity stays
+//! within 0–100.
+
+#![no_main]
+
+use easyroute::osm;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    // One `key=value` tag per line
+    let tags = osm::collect_tags(text.lines().filter_map(|line| line.split_once('=')));
+
+    let popularity = osm::calculate_popularity(&tags);
+    assert!(
+        (0.0..=100.0).contains(&popularity),
+        "popularity {popularity} for {tags:?}"
+    );
+    if let Some(category) = osm::determine_category(&tags) {
+        osm::estimate_duration(&tags, &category);
+        osm::poi_name(&tags, &category);
+    }
+    osm::build_description(&tags);
+});
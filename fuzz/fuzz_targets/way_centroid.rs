@@ -0,0 +1,34 @@
+//! Way centroids from arbitrary node coordinates: never a panic or an
+//! out-of-range coordinate, and valid nodes always give a centroid inside
+//! their bounding box.
+
+#![no_main]
+
+use easyroute::models::Coordinates;
+use easyroute::osm;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|nodes: Vec<(f64, f64)>| {
+    let centroid = osm::way_centroid(&nodes);
+    if let Some(c) = centroid {
+        assert!(Coordinates::new(c.lat, c.lng).is_ok(), "{c:?}");
+    }
+
+    let valid = !nodes.is_empty()
+        && nodes
+            .iter()
+            .all(|&(lat, lon)| Coordinates::new(lat, lon).is_ok());
+    if valid {
+        let c = centroid.expect("valid nodes have a centroid");
+        let (min_lat, max_lat) = bounds(nodes.iter().map(|n| n.0));
+        let (min_lon, max_lon) = bounds(nodes.iter().map(|n| n.1));
+        assert!(c.lat >= min_lat - 1e-9 && c.lat <= max_lat + 1e-9, "{c:?}");
+        assert!(c.lng >= min_lon - 1e-9 && c.lng <= max_lon + 1e-9, "{c:?}");
+    }
+});
+
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    })
+}
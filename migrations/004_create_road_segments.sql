@@ -0,0 +1,12 @@
+-- Road segments with surface data, imported by osm/osm_poi_style.lua
+-- Used to estimate how much of a generated route runs on paved surfaces
+
+CREATE TABLE IF NOT EXISTS road_segments (
+    osm_id BIGINT PRIMARY KEY,
+    highway TEXT NOT NULL,
+    surface TEXT,  -- Raw OSM surface tag; NULL when untagged
+    geom GEOGRAPHY(LINESTRING, 4326) NOT NULL
+);
+
+-- Spatial index for path proximity queries
+CREATE INDEX IF NOT EXISTS idx_road_segments_geom ON road_segments USING GIST(geom);
@@ -0,0 +1,4 @@
+-- Incline (absolute grade in percent) for step-free / stroller routing
+-- NULL when the way has no numeric incline tag
+
+ALTER TABLE road_segments ADD COLUMN IF NOT EXISTS incline_pct REAL;
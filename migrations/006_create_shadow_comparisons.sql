@@ -0,0 +1,26 @@
+-- Shadow-mode evaluation: a sample of live requests also runs a candidate
+-- strategy in the background. Both best routes are stored in evaluated_routes;
+-- this table pairs them with candidate-minus-primary metric deltas.
+CREATE TABLE shadow_comparisons (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    primary_route_id UUID NOT NULL REFERENCES evaluated_routes(id) ON DELETE CASCADE,
+    -- NULL when the candidate produced no route
+    candidate_route_id UUID REFERENCES evaluated_routes(id) ON DELETE CASCADE,
+    primary_strategy VARCHAR(20) NOT NULL,
+    candidate_strategy VARCHAR(20) NOT NULL,
+    candidate_error TEXT,
+    -- Candidate minus primary (NULL when either side lacks the metric)
+    score_delta REAL,
+    distance_error_delta REAL,
+    circularity_delta REAL,
+    convexity_delta REAL,
+    path_overlap_pct_delta REAL,
+    poi_density_per_km_delta REAL,
+    category_entropy_delta REAL,
+    landmark_coverage_delta REAL,
+    latency_ms_delta INTEGER,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_shadow_comparisons_created_at ON shadow_comparisons(created_at DESC);
+CREATE INDEX idx_shadow_comparisons_strategies ON shadow_comparisons(primary_strategy, candidate_strategy);
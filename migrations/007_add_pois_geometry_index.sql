@@ -0,0 +1,4 @@
+-- Planar index for bounding-box queries (find_pois_in_bbox).
+-- The geography index on `location` only serves distance predicates; a bbox
+-- filter on lat/lng needs the geometry cast to avoid a sequential scan.
+CREATE INDEX IF NOT EXISTS idx_pois_location_geometry ON pois USING GIST((location::geometry));
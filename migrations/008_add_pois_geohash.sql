@@ -0,0 +1,8 @@
+-- Geohash of each POI for prefix range scans (POI_GEOHASH_PREFILTER).
+-- "C" collation so that prefix ranges ('u09t' <= geohash < 'u09u') follow
+-- byte order. Precision must match POI_GEOHASH_PRECISION in src/constants.rs.
+ALTER TABLE pois
+    ADD COLUMN IF NOT EXISTS geohash TEXT COLLATE "C"
+    GENERATED ALWAYS AS (ST_GeoHash(location::geometry, 9)) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_pois_geohash ON pois(geohash);
@@ -0,0 +1,14 @@
+-- Run history of scheduled tasks (src/scheduler/). One row per run on the
+-- instance that held the task's advisory lock.
+CREATE TABLE scheduled_task_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    task VARCHAR(64) NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ,
+    -- running | succeeded | failed
+    status VARCHAR(16) NOT NULL DEFAULT 'running',
+    -- Task summary on success, error message on failure
+    detail TEXT
+);
+
+CREATE INDEX idx_scheduled_task_runs_task ON scheduled_task_runs(task, started_at DESC);
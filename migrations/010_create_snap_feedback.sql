@@ -0,0 +1,27 @@
+-- Client telemetry on snapped POIs (POST /telemetry/snaps): one row per
+-- snapped POI shown to a user, with whether it was tapped or kept on the
+-- route. Feeds the snap radius tuning task (src/services/snap_tuning.rs).
+CREATE TABLE snap_feedback (
+    id BIGSERIAL PRIMARY KEY,
+    transport_mode VARCHAR(16) NOT NULL,
+    distance_from_path_m REAL NOT NULL,
+    tapped BOOLEAN NOT NULL DEFAULT FALSE,
+    kept BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_snap_feedback_mode_created ON snap_feedback(transport_mode, created_at);
+
+-- Latest suggestion per transport mode, written by the snap_radius_tuning
+-- task and shown in GET /debug/snap-radius. Advisory only: SNAP_RADIUS_M
+-- still decides the radius in use.
+CREATE TABLE snap_radius_recommendations (
+    transport_mode VARCHAR(16) PRIMARY KEY,
+    current_radius_m REAL NOT NULL,
+    -- NULL when there was too little feedback to suggest anything
+    recommended_radius_m REAL,
+    samples BIGINT NOT NULL,
+    -- [{"max_distance_m", "shown", "engaged"}] per distance bucket
+    buckets JSONB NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+-- Simplified path (encoded polyline + bbox) for rating UI thumbnails,
+-- returned as `preview` by the evaluation API. NULL for older routes.
+ALTER TABLE evaluated_routes ADD COLUMN path_preview JSONB;
@@ -0,0 +1,14 @@
+-- Routes bookmarked by users (POST /routes/{id}/save). The full route is
+-- stored as returned by POST /routes/loop, so it can be re-fetched without
+-- regenerating. A route saved by several users has one row per user.
+CREATE TABLE saved_routes (
+    route_id UUID NOT NULL,
+    user_id VARCHAR(100) NOT NULL,
+    name VARCHAR(200),
+    route JSONB NOT NULL,
+    saved_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, route_id)
+);
+
+CREATE INDEX idx_saved_routes_route_id ON saved_routes(route_id);
+CREATE INDEX idx_saved_routes_user_saved_at ON saved_routes(user_id, saved_at DESC, route_id DESC);
@@ -0,0 +1,6 @@
+-- `path` now holds the route's StoredGeometry (simplified polyline + POIs).
+-- Routes stored before that have a JSON null there: turn it into SQL NULL so
+-- `path IS NULL` finds the routes the geometry backfill still has to fill.
+ALTER TABLE evaluated_routes ALTER COLUMN path DROP NOT NULL;
+UPDATE evaluated_routes SET path = NULL WHERE path = 'null'::jsonb;
+CREATE INDEX idx_evaluated_routes_missing_path ON evaluated_routes(id) WHERE path IS NULL;
@@ -0,0 +1,18 @@
+-- Metrics recomputed from stored geometry with a given version of the metric
+-- code (ROUTE_METRICS_VERSION). The columns on evaluated_routes keep the
+-- values computed at generation time, so correlation analysis can compare
+-- metric definitions against the same human ratings.
+CREATE TABLE evaluated_route_metrics (
+    route_id UUID NOT NULL REFERENCES evaluated_routes(id) ON DELETE CASCADE,
+    metrics_version INTEGER NOT NULL,
+    circularity REAL NOT NULL,
+    convexity REAL NOT NULL,
+    path_overlap_pct REAL NOT NULL,
+    poi_density_per_km REAL NOT NULL,
+    category_entropy REAL NOT NULL,
+    landmark_coverage REAL NOT NULL,
+    computed_at TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (route_id, metrics_version)
+);
+
+CREATE INDEX idx_evaluated_route_metrics_version ON evaluated_route_metrics(metrics_version);
@@ -0,0 +1,10 @@
+-- Short share links to saved routes (POST /routes/{id}/share). The route
+-- itself is read from saved_routes; expired tokens are simply not served.
+CREATE TABLE route_shares (
+    token VARCHAR(16) PRIMARY KEY,
+    route_id UUID NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_route_shares_route_id ON route_shares(route_id);
@@ -0,0 +1,22 @@
+-- Accounts that own API keys. Ids are the same user strings saved routes use.
+CREATE TABLE users (
+    id VARCHAR(100) PRIMARY KEY,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- API keys for API_KEY_AUTH. Only a SHA-256 of the key is stored; the
+-- prefix identifies a key in listings. Each authenticated request bumps
+-- request_count and last_used_at.
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id VARCHAR(100) NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    key_hash CHAR(64) NOT NULL UNIQUE,
+    key_prefix VARCHAR(16) NOT NULL,
+    scopes TEXT[] NOT NULL,
+    request_count BIGINT NOT NULL DEFAULT 0,
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_api_keys_user_id ON api_keys(user_id);
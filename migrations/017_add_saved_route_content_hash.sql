@@ -0,0 +1,6 @@
+-- Route.content_hash of saved routes, so saving a regenerated copy of a route
+-- a user already saved (same geometry and waypoints, new id) returns the
+-- existing bookmark. NULL for rows saved before the hash existed.
+ALTER TABLE saved_routes ADD COLUMN content_hash VARCHAR(32);
+
+CREATE INDEX idx_saved_routes_user_content_hash ON saved_routes(user_id, content_hash);
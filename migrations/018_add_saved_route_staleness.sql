@@ -0,0 +1,9 @@
+-- Results of the saved_route_consistency task, which checks saved routes'
+-- waypoints against the current POI data. stale_reasons lists waypoints
+-- deleted or moved since the route was saved (NULL while it still matches);
+-- checked_at orders the sweep, never-checked routes first.
+ALTER TABLE saved_routes
+    ADD COLUMN stale_reasons JSONB,
+    ADD COLUMN checked_at TIMESTAMPTZ;
+
+CREATE INDEX idx_saved_routes_checked_at ON saved_routes(checked_at NULLS FIRST);
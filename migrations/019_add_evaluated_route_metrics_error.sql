@@ -0,0 +1,19 @@
+-- Routes whose stored geometry can't be read are recorded with the error
+-- instead of metrics, so the metric_recompute task doesn't pick them up again
+-- on every run. A row has either all metrics or an error.
+ALTER TABLE evaluated_route_metrics
+    ALTER COLUMN circularity DROP NOT NULL,
+    ALTER COLUMN convexity DROP NOT NULL,
+    ALTER COLUMN path_overlap_pct DROP NOT NULL,
+    ALTER COLUMN poi_density_per_km DROP NOT NULL,
+    ALTER COLUMN category_entropy DROP NOT NULL,
+    ALTER COLUMN landmark_coverage DROP NOT NULL,
+    ADD COLUMN error TEXT,
+    ADD CONSTRAINT evaluated_route_metrics_error_or_metrics CHECK (
+        (error IS NULL) = (circularity IS NOT NULL
+            AND convexity IS NOT NULL
+            AND path_overlap_pct IS NOT NULL
+            AND poi_density_per_km IS NOT NULL
+            AND category_entropy IS NOT NULL
+            AND landmark_coverage IS NOT NULL)
+    );
@@ -0,0 +1,16 @@
+-- The user whose saved copy a share link serves. Several users can save the
+-- same route id, each with their own copy, so links join saved_routes on
+-- (user_id, route_id). Existing links keep serving the copy they resolved
+-- to before: the earliest saved one.
+ALTER TABLE route_shares ADD COLUMN user_id VARCHAR(100);
+
+UPDATE route_shares rs SET user_id = (
+    SELECT sr.user_id FROM saved_routes sr
+    WHERE sr.route_id = rs.route_id
+    ORDER BY sr.saved_at
+    LIMIT 1
+);
+
+DELETE FROM route_shares WHERE user_id IS NULL;
+
+ALTER TABLE route_shares ALTER COLUMN user_id SET NOT NULL;
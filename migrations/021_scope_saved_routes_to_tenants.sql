@@ -0,0 +1,16 @@
+-- Saved routes and share links belong to a tenant (TENANT_API_KEYS): the same
+-- user string under two tenants names two users. Rows from before tenants
+-- belong to the default tenant, as do all rows in single-tenant deployments.
+ALTER TABLE saved_routes ADD COLUMN tenant_id VARCHAR(100) NOT NULL DEFAULT 'default';
+ALTER TABLE route_shares ADD COLUMN tenant_id VARCHAR(100) NOT NULL DEFAULT 'default';
+
+ALTER TABLE saved_routes DROP CONSTRAINT saved_routes_pkey;
+ALTER TABLE saved_routes ADD PRIMARY KEY (tenant_id, user_id, route_id);
+
+DROP INDEX idx_saved_routes_user_saved_at;
+CREATE INDEX idx_saved_routes_user_saved_at
+    ON saved_routes(tenant_id, user_id, saved_at DESC, route_id DESC);
+
+DROP INDEX idx_saved_routes_user_content_hash;
+CREATE INDEX idx_saved_routes_user_content_hash
+    ON saved_routes(tenant_id, user_id, content_hash);
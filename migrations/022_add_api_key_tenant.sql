@@ -0,0 +1,4 @@
+-- API keys (API_KEY_AUTH) belong to a tenant, which scopes the requests made
+-- with them the way TENANT_API_KEYS does. Existing keys belong to the
+-- default tenant.
+ALTER TABLE api_keys ADD COLUMN tenant_id VARCHAR(100) NOT NULL DEFAULT 'default';
@@ -0,0 +1,99 @@
+-- Opt-in: convert `pois` into a table partitioned by 1-character geohash.
+--
+-- For planet-scale imports, where a single `pois` table and its indexes grow
+-- past what fits in memory. Not applied by `sqlx::migrate!` (only top-level
+-- files in migrations/ are); run it manually after the regular migrations
+-- and after the OSM import:
+--
+--   psql "$DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/optional/partition_pois.sql
+--
+-- Rows are routed by `poi_partition_key(location)` into 32 partitions (one
+-- per geohash cell of 45° x 45°). The server detects the partitioned table
+-- at startup and adds the covering partition keys to POI queries so the
+-- planner prunes the other partitions (see PgPoiRepository).
+--
+-- Differences from the regular table:
+-- - `id` is indexed but not a PRIMARY KEY: PostgreSQL cannot enforce unique
+--   constraints on a partitioned table whose key is an expression
+--   (import_osm.sh leaves partitioned tables without one).
+-- - `(osm_type, osm_id)` uniqueness is enforced per partition: node and way
+--   ids overlap, so `osm_id` alone is not unique. A POI's partition follows
+--   from its location, so this only differs if an OSM object moves across a
+--   cell boundary between imports; osm2pgsql's `--append` deletes the old
+--   row before inserting the new one, so moves still apply cleanly.
+--
+-- The original table is kept as `pois_unpartitioned`; drop it once the
+-- partitioned table has been checked.
+
+BEGIN;
+
+-- Must match POI_PARTITION_GEOHASH_PRECISION in src/constants.rs
+CREATE OR REPLACE FUNCTION poi_partition_key(location GEOGRAPHY)
+RETURNS TEXT AS $$
+    SELECT ST_GeoHash(location::geometry, 1)
+$$ LANGUAGE SQL IMMUTABLE PARALLEL SAFE;
+
+-- osm2pgsql keys rows by (osm_type, osm_id); tables created by the
+-- migrations alone have no osm_type yet
+ALTER TABLE pois ADD COLUMN IF NOT EXISTS osm_type CHAR(1);
+
+ALTER TABLE pois RENAME TO pois_unpartitioned;
+ALTER INDEX idx_pois_location RENAME TO idx_pois_unpartitioned_location;
+ALTER INDEX IF EXISTS idx_pois_location_geometry RENAME TO idx_pois_unpartitioned_location_geometry;
+ALTER INDEX idx_pois_category RENAME TO idx_pois_unpartitioned_category;
+ALTER INDEX idx_pois_popularity RENAME TO idx_pois_unpartitioned_popularity;
+ALTER INDEX idx_pois_osm_id RENAME TO idx_pois_unpartitioned_osm_id;
+ALTER INDEX IF EXISTS idx_pois_geohash RENAME TO idx_pois_unpartitioned_geohash;
+DROP TRIGGER IF EXISTS update_pois_updated_at ON pois_unpartitioned;
+
+CREATE TABLE pois (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    category VARCHAR(50) NOT NULL,
+    location GEOGRAPHY(POINT, 4326) NOT NULL,
+    popularity_score REAL DEFAULT 50.0 CHECK (popularity_score >= 0 AND popularity_score <= 100),
+    description TEXT,
+    estimated_visit_duration_minutes INTEGER,
+    osm_type CHAR(1),
+    osm_id BIGINT,
+    metadata JSONB,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    geohash TEXT COLLATE "C" GENERATED ALWAYS AS (ST_GeoHash(location::geometry, 9)) STORED
+) PARTITION BY LIST (poi_partition_key(location));
+
+DO $$
+DECLARE
+    cell TEXT;
+BEGIN
+    FOREACH cell IN ARRAY string_to_array('0123456789bcdefghjkmnpqrstuvwxyz', NULL)
+    LOOP
+        EXECUTE format('CREATE TABLE pois_p_%s PARTITION OF pois FOR VALUES IN (%L)', cell, cell);
+        EXECUTE format('CREATE UNIQUE INDEX idx_pois_p_%s_osm_id ON pois_p_%s(osm_type, osm_id) WHERE osm_id IS NOT NULL', cell, cell);
+    END LOOP;
+END $$;
+
+-- Indexes on the parent cascade to every partition
+CREATE INDEX idx_pois_id ON pois(id);
+CREATE INDEX idx_pois_location ON pois USING GIST(location);
+CREATE INDEX idx_pois_location_geometry ON pois USING GIST((location::geometry));
+CREATE INDEX idx_pois_category ON pois(category);
+CREATE INDEX idx_pois_popularity ON pois(popularity_score);
+CREATE INDEX idx_pois_geohash ON pois(geohash);
+
+CREATE TRIGGER update_pois_updated_at
+    BEFORE UPDATE ON pois
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO pois (id, name, category, location, popularity_score, description,
+                  estimated_visit_duration_minutes, osm_type, osm_id, metadata, created_at,
+                  updated_at)
+SELECT id, name, category, location, popularity_score, description,
+       estimated_visit_duration_minutes, osm_type, osm_id, metadata, created_at,
+       updated_at
+FROM pois_unpartitioned;
+
+COMMIT;
+
+ANALYZE pois;
@@ -282,9 +282,15 @@ UPDATE pois SET id = gen_random_uuid() WHERE id IS NULL;
 -- Ensure NOT NULL constraint exists
 ALTER TABLE pois ALTER COLUMN id SET NOT NULL;
 
--- Make id the primary key if it isn't already
+-- Make id the primary key if it isn't already. A geohash-partitioned table
+-- (migrations/optional/partition_pois.sql) can't have one: its partition key
+-- is an expression.
 DO \$\$
 BEGIN
+    IF EXISTS (SELECT 1 FROM pg_class WHERE oid = 'pois'::regclass AND relkind = 'p') THEN
+        RETURN;
+    END IF;
+
     -- Drop existing primary key on osm_id if it exists
     IF EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'pois_pkey' AND conrelid = 'pois'::regclass) THEN
         ALTER TABLE pois DROP CONSTRAINT IF EXISTS pois_pkey;
@@ -304,6 +310,16 @@ CREATE INDEX IF NOT EXISTS idx_pois_location ON pois USING GIST(location);
 CREATE INDEX IF NOT EXISTS idx_pois_category ON pois(category);
 CREATE INDEX IF NOT EXISTS idx_pois_popularity ON pois(popularity_score);
 CREATE INDEX IF NOT EXISTS idx_pois_osm_id ON pois(osm_id) WHERE osm_id IS NOT NULL;
+
+-- Planar index for bbox queries (migration 007)
+CREATE INDEX IF NOT EXISTS idx_pois_location_geometry ON pois USING GIST((location::geometry));
+
+-- Geohash prefilter column (migration 008); precision must match
+-- POI_GEOHASH_PRECISION in src/constants.rs
+ALTER TABLE pois
+    ADD COLUMN IF NOT EXISTS geohash TEXT COLLATE "C"
+    GENERATED ALWAYS AS (ST_GeoHash(location::geometry, 9)) STORED;
+CREATE INDEX IF NOT EXISTS idx_pois_geohash ON pois(geohash);
 EOF
 echo -e "${GREEN}Table schema corrected${NC}"
 
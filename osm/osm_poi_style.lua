@@ -18,6 +18,18 @@ local pois_table = osm2pgsql.define_table({
     }
 })
 
+-- Road segments with surface and incline tags, used for road-profile estimates
+local road_segments_table = osm2pgsql.define_table({
+    name = 'road_segments',
+    ids = { type = 'way', id_column = 'osm_id' },
+    columns = {
+        { column = 'highway', type = 'text', not_null = true },
+        { column = 'surface', type = 'text' },
+        { column = 'incline_pct', type = 'real' },
+        { column = 'geom', type = 'linestring', projection = 4326, not_null = true },
+    }
+})
+
 -- Category mapping: OSM tags -> our PoiCategory enum
 local category_mappings = {
     -- Tourism tags
@@ -27,6 +39,16 @@ local category_mappings = {
     attraction = 'cultural',
     artwork = 'artwork',
 
+    -- Overnight stays (stage ends of multi-day routes)
+    hotel = 'accommodation',
+    guest_house = 'accommodation',
+    hostel = 'accommodation',
+    motel = 'accommodation',
+    chalet = 'accommodation',
+    alpine_hut = 'accommodation',
+    wilderness_hut = 'accommodation',
+    camp_site = 'accommodation',
+
     -- Historic tags
     castle = 'castle',
     manor = 'castle',
@@ -152,6 +174,8 @@ local function estimate_duration(tags, category)
         cultural = 60,
         waterfall = 30,
         market = 45,
+        drinking_water = 5,
+        toilets = 5,
     }
 
     return defaults[category] or 30
@@ -189,6 +213,47 @@ local function build_description(tags)
 end
 
 -- Determine category from OSM tags
+-- Parse an OSM incline tag into an absolute grade in percent.
+-- Accepts "10%", "-8%" and degrees ("5°"); "up"/"down" carry no grade (nil).
+local function parse_incline(value)
+    if not value then
+        return nil
+    end
+    local pct = value:match('^%s*([-+]?%d+%.?%d*)%s*%%%s*$')
+    if pct then
+        return math.abs(tonumber(pct))
+    end
+    local deg = value:match('^%s*([-+]?%d+%.?%d*)%s*°%s*$')
+    if deg then
+        return math.abs(math.tan(math.rad(tonumber(deg))) * 100)
+    end
+    return nil
+end
+
+-- Names for amenities imported without a name tag (rarely named in OSM)
+local fallback_names = {
+    drinking_water = 'Drinking water',
+    toilets = 'Toilets',
+}
+
+-- Parks, green land cover and footpaths, where a dog tag marks somewhere to
+-- walk a dog
+local dog_walk_areas = {
+    leisure = { park = true, common = true, recreation_ground = true },
+    landuse = { meadow = true, grass = true, forest = true, recreation_ground = true, village_green = true },
+    natural = { grassland = true, heath = true, wood = true, scrub = true },
+    highway = { path = true, footway = true, track = true, bridleway = true },
+}
+
+local function is_green_area_or_path(tags)
+    for key, values in pairs(dog_walk_areas) do
+        if tags[key] and values[tags[key]] then
+            return true
+        end
+    end
+    return false
+end
+
 local function determine_category(tags)
     -- Priority order: tourism > historic > amenity > man_made > natural > leisure
 
@@ -224,6 +289,10 @@ local function determine_category(tags)
             return 'fountain'
         elseif tags.amenity == 'marketplace' then
             return 'market'
+        elseif tags.amenity == 'drinking_water' then
+            return 'drinking_water'
+        elseif tags.amenity == 'toilets' then
+            return 'toilets'
         end
     end
 
@@ -233,6 +302,8 @@ local function determine_category(tags)
             return 'park'
         elseif tags.leisure == 'nature_reserve' then
             return 'nature_reserve'
+        elseif tags.leisure == 'dog_park' then
+            return 'dog_park'
         end
     end
 
@@ -259,8 +330,10 @@ local function determine_category(tags)
     end
 
     -- Craft/industry
-    if tags.craft == 'winery' or tags.craft == 'brewery' then
-        return tags.craft == 'winery' and 'winery' or 'brewery'
+    if tags.craft == 'winery' then
+        return 'winery'
+    elseif tags.craft == 'brewery' then
+        return 'brewery'
     end
 
     -- Building types
@@ -277,6 +350,12 @@ local function determine_category(tags)
         return 'plaza'
     end
 
+    -- Dog-friendly green areas and paths not covered above (e.g. meadows,
+    -- off-leash trails). Elsewhere dog=yes only means dogs are allowed.
+    if (tags.dog == 'yes' or tags.dog == 'unleashed') and is_green_area_or_path(tags) then
+        return 'dog_park'
+    end
+
     return nil  -- Not a POI we're interested in
 end
 
@@ -307,17 +386,18 @@ end
 
 -- Process nodes (most POIs are nodes)
 function osm2pgsql.process_node(object)
-    -- Must have a name (we don't want unnamed POIs)
-    if not object.tags.name then
-        return
-    end
-
     -- Determine category
     local category = determine_category(object.tags)
     if not category then
         return  -- Not a POI we care about
     end
 
+    -- Must have a name (we don't want unnamed POIs), except for amenities
+    local name = object.tags.name or fallback_names[category]
+    if not name then
+        return
+    end
+
     -- Calculate attributes
     local popularity = calculate_popularity(object.tags)
     local description = build_description(object.tags)
@@ -326,7 +406,7 @@ function osm2pgsql.process_node(object)
 
     -- Insert into pois table
     pois_table:insert({
-        name = object.tags.name,
+        name = name,
         category = category,
         location = object:as_point(),
         popularity_score = popularity,
@@ -338,6 +418,17 @@ end
 
 -- Process ways (some POIs are areas, like parks, plazas)
 function osm2pgsql.process_way(object)
+    -- Routable ways feed the surface table, named or not. Closed ways count
+    -- too (roundabouts, loop paths), unless they are areas (squares).
+    if object.tags.highway and object.tags.area ~= 'yes' then
+        road_segments_table:insert({
+            highway = object.tags.highway,
+            surface = object.tags.surface,
+            incline_pct = parse_incline(object.tags.incline),
+            geom = object:as_linestring(),
+        })
+    end
+
     -- Must have a name
     if not object.tags.name then
         return
@@ -0,0 +1,124 @@
+//! Manage the API keys checked when `API_KEY_AUTH` is on.
+//!
+//! ```text
+//! cargo run --bin apikey -- create --user=alice --tenant=acme --scopes=routes:read,routes:write
+//! cargo run --bin apikey -- list
+//! cargo run --bin apikey -- revoke --id=<uuid>
+//! ```
+
+use easyroute::constants::DEFAULT_DATABASE_POOL_SIZE;
+use easyroute::db::queries;
+use easyroute::models::api_key::{generate_key, hash_key, key_prefix, ApiScope};
+use easyroute::services::tenant::TenantId;
+use std::env;
+use uuid::Uuid;
+
+fn print_help() {
+    eprintln!(
+        "\
+Usage: apikey <COMMAND> [OPTIONS]
+
+Commands:
+  create --user=ID [--tenant=ID] [--scopes=S1,S2]
+                        Create a key (default tenant: default, default scopes:
+                        routes:read,routes:write); the key is printed once and
+                        cannot be recovered
+  list                  List keys with their usage
+  revoke --id=UUID      Revoke a key
+
+Needs DATABASE_URL."
+    );
+}
+
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter().find_map(|a| a.strip_prefix(name))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(command) = args.first().map(String::as_str) else {
+        print_help();
+        std::process::exit(1);
+    };
+    if command == "--help" || command == "help" {
+        print_help();
+        return Ok(());
+    }
+
+    let database_url = env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set")?;
+    let pool = easyroute::db::create_pool(&database_url, DEFAULT_DATABASE_POOL_SIZE).await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
+
+    match command {
+        "create" => {
+            let user = flag(&args, "--user=").ok_or("create needs --user=ID")?;
+            let tenant = match flag(&args, "--tenant=") {
+                Some(id) if !TenantId::is_valid_id(id) => {
+                    return Err(format!("Invalid tenant id: {}. Use [A-Za-z0-9_-]", id).into())
+                }
+                Some(id) => TenantId::new(id),
+                None => TenantId::default(),
+            };
+            let scopes = match flag(&args, "--scopes=") {
+                Some(list) => list
+                    .split(',')
+                    .map(|s| s.trim().parse::<ApiScope>())
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => ApiScope::ALL.to_vec(),
+            };
+            let key = generate_key();
+            let id = queries::create_api_key(
+                &pool,
+                &tenant,
+                user,
+                &hash_key(&key),
+                &key_prefix(&key),
+                &scopes,
+            )
+            .await?;
+            eprintln!(
+                "Created key {} for {} of tenant {} ({:?})",
+                id, user, tenant, scopes
+            );
+            println!("{}", key);
+        }
+        "list" => {
+            for key in queries::list_api_keys(&pool).await? {
+                println!(
+                    "{}  {:<12} {:<20} {}…  [{}]  {} requests, last used {}{}",
+                    key.id,
+                    key.tenant_id,
+                    key.user_id,
+                    key.key_prefix,
+                    key.scopes.join(","),
+                    key.request_count,
+                    key.last_used_at.as_deref().unwrap_or("never"),
+                    if key.revoked_at.is_some() {
+                        "  (revoked)"
+                    } else {
+                        ""
+                    },
+                );
+            }
+        }
+        "revoke" => {
+            let id: Uuid = flag(&args, "--id=")
+                .ok_or("revoke needs --id=UUID")?
+                .parse()?;
+            if queries::revoke_api_key(&pool, id).await? {
+                eprintln!("Revoked {}", id);
+            } else {
+                eprintln!("No active key {}", id);
+                std::process::exit(1);
+            }
+        }
+        other => {
+            eprintln!("Unknown command: {}", other);
+            print_help();
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
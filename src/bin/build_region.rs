@@ -5,7 +5,14 @@
 //!     --input osm/data/monaco-latest.osm.pbf \
 //!     --output regions/monaco.db
 //! ```
+//!
+//! The POI data's SHA-256 is stored in `region_meta`; with
+//! `--signing-key=PATH` (a file holding a hex Ed25519 seed) so is a
+//! signature over it, for clients to check downloads against. The extent
+//! of the extract's nodes is stored as `min_lat`/`max_lat`/`min_lng`/`max_lng`
+//! for the proxy's region lookup.
 
+use easyroute::db::sqlite_repo::parse_signing_key;
 use easyroute::db::SqlitePoiRepository;
 use easyroute::models::{Coordinates, Poi, PoiCategory};
 use easyroute::osm;
@@ -21,16 +28,13 @@ const BATCH_SIZE: usize = 1000;
 const SCAN_PROGRESS_INTERVAL: usize = 500_000;
 
 /// Try to build a POI from OSM tags and coordinates. Returns `None` if the
-/// tags don't have a name or a recognized category.
+/// tags don't have a recognized category, or no name (amenities get a default one).
 fn try_build_poi(tags: &HashMap<&str, &str>, id: i64, lat: f64, lon: f64) -> Option<Poi> {
-    if !tags.contains_key("name") {
-        return None;
-    }
     let category = osm::determine_category(tags)?;
+    let name = osm::poi_name(tags, &category)?;
     let popularity = osm::calculate_popularity(tags);
     let duration = osm::estimate_duration(tags, &category);
     let description = osm::build_description(tags);
-    let name = tags["name"].to_string();
     let coords = Coordinates::new(lat, lon).ok()?;
 
     Some(Poi {
@@ -58,6 +62,33 @@ fn fmt_count(n: usize) -> String {
     result
 }
 
+/// `(min_lat, max_lat, min_lng, max_lng)` of `pois`, `None` when empty.
+/// Longitudes take the narrowest span, so a region crossing the antimeridian
+/// gets `min_lng > max_lng`.
+fn poi_extent(pois: &[Poi]) -> Option<(f64, f64, f64, f64)> {
+    let first = pois.first()?;
+    let (mut min_lat, mut max_lat) = (first.coordinates.lat, first.coordinates.lat);
+    let mut lngs = Vec::with_capacity(pois.len());
+    for poi in pois {
+        min_lat = min_lat.min(poi.coordinates.lat);
+        max_lat = max_lat.max(poi.coordinates.lat);
+        lngs.push(poi.coordinates.lng);
+    }
+    lngs.sort_by(f64::total_cmp);
+
+    // The span is everything but the widest gap between neighbouring
+    // longitudes, counting the one across the antimeridian
+    let (mut min_lng, mut max_lng) = (lngs[0], lngs[lngs.len() - 1]);
+    let mut widest = lngs[0] + 360.0 - lngs[lngs.len() - 1];
+    for pair in lngs.windows(2) {
+        if pair[1] - pair[0] > widest {
+            widest = pair[1] - pair[0];
+            (min_lng, max_lng) = (pair[1], pair[0]);
+        }
+    }
+    Some((min_lat, max_lat, min_lng, max_lng))
+}
+
 fn print_help() {
     eprintln!(
         "\
@@ -68,6 +99,8 @@ Build a SQLite region database from an OSM PBF file.
 Options:
   --input=PATH     Path to the .osm.pbf input file (required)
   --output=PATH    Path to the .db output file (required)
+  --signing-key=PATH
+                   File with a hex Ed25519 seed to sign the region with
   --help           Show this help message"
     );
 }
@@ -104,6 +137,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .map(PathBuf::from)
         .ok_or("Missing --output=PATH argument")?;
 
+    // Read the key up front so a bad one fails before the long import
+    let signing_key = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--signing-key="))
+        .map(|path| {
+            let seed = fs::read_to_string(path)
+                .map_err(|e| format!("Cannot read signing key {}: {}", path, e))?;
+            parse_signing_key(&seed)
+        })
+        .transpose()?;
+
     if !input.exists() {
         return Err(format!("Input file does not exist: {}", input.display()).into());
     }
@@ -211,26 +255,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let t_resolve = Instant::now();
     let ways_count = pending_ways.len();
     for way in pending_ways {
-        let mut sum_lat = 0.0;
-        let mut sum_lon = 0.0;
-        let mut resolved = 0usize;
-
-        for nref in &way.node_refs {
-            if let Some(&(lat, lon)) = node_coords.get(nref) {
-                sum_lat += lat;
-                sum_lon += lon;
-                resolved += 1;
-            }
-        }
+        let nodes: Vec<(f64, f64)> = way
+            .node_refs
+            .iter()
+            .filter_map(|nref| node_coords.get(nref).copied())
+            .collect();
 
-        if resolved == 0 {
-            continue;
-        }
-
-        let centroid_lat = sum_lat / resolved as f64;
-        let centroid_lon = sum_lon / resolved as f64;
-
-        if let Ok(coords) = Coordinates::new(centroid_lat, centroid_lon) {
+        if let Some(coords) = osm::way_centroid(&nodes) {
             pois.push(Poi {
                 id: Uuid::new_v4(),
                 name: way.name,
@@ -292,6 +323,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         );
     }
 
+    // Extent of the POIs, i.e. the area the region can route in
+    let bounds = poi_extent(&pois);
+
     let dupes = total_pois - total_inserted;
     eprintln!(
         "\r      {} POIs written in {:.1}s{}",
@@ -327,6 +361,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await?;
     repo.set_meta("source_file_size_bytes", &source_file_size.to_string())
         .await?;
+    if let Some((min_lat, max_lat, min_lng, max_lng)) = bounds {
+        repo.set_meta("min_lat", &min_lat.to_string()).await?;
+        repo.set_meta("max_lat", &max_lat.to_string()).await?;
+        repo.set_meta("min_lng", &min_lng.to_string()).await?;
+        repo.set_meta("max_lng", &max_lng.to_string()).await?;
+    }
+    let checksum = repo.write_checksum(signing_key.as_ref()).await?;
+    eprintln!(
+        "      Checksum {}{}",
+        checksum,
+        if signing_key.is_some() {
+            " (signed)"
+        } else {
+            ""
+        },
+    );
 
     let db_size = fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
     eprintln!();
@@ -340,3 +390,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pois(coords: &[(f64, f64)]) -> Vec<Poi> {
+        coords
+            .iter()
+            .map(|&(lat, lng)| {
+                Poi::new(
+                    "POI".to_string(),
+                    PoiCategory::Monument,
+                    Coordinates::new(lat, lng).unwrap(),
+                    50.0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn poi_extent_covers_pois() {
+        assert_eq!(poi_extent(&[]), None);
+        assert_eq!(
+            poi_extent(&pois(&[(43.73, 7.42), (43.75, 7.40), (43.72, 7.44)])),
+            Some((43.72, 43.75, 7.40, 7.44))
+        );
+    }
+
+    #[test]
+    fn poi_extent_wraps_across_the_antimeridian() {
+        // Fiji straddles 180°
+        assert_eq!(
+            poi_extent(&pois(&[(-17.8, 177.4), (-16.8, 179.9), (-16.5, -179.9)])),
+            Some((-17.8, -16.5, 177.4, -179.9))
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use easyroute::config::Config;
 use easyroute::db::PgPoiRepository;
 use easyroute::evaluation::{
-    compare, default_scenarios, format_comparison_report, format_report, load_baseline,
-    save_baseline, Baseline, EvalScenario, MetricsAggregate, ScenarioResult,
+    compare, default_scenarios, diff_baselines, format_github_annotations, format_report, Baseline,
+    BaselineStore, CallCounters, ComparisonReport, CountingDirectionsProvider,
+    CountingPoiRepository, EvalScenario, MetricsAggregate, QualityTierCounts, RegressionGate,
+    Report, ReportFormat, ScenarioCost, ScenarioResult, DEFAULT_BASELINE_NAME,
 };
 use easyroute::models::{Route, RoutePreferences};
 use easyroute::services::mapbox::{AuthMode, MapboxClient};
@@ -10,12 +12,16 @@ use easyroute::services::poi_service::PoiService;
 use easyroute::services::route_generator::RouteGenerator;
 use easyroute::services::snapping_service::SnappingService;
 use std::env;
-use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-const DEFAULT_BASELINE_PATH: &str = "evaluation/baseline.json";
+const DEFAULT_BASELINE_DIR: &str = "evaluation";
 const DEFAULT_REGRESSION_THRESHOLD: f32 = 0.15;
+/// Exit code when a comparison finds regressions
+const EXIT_REGRESSION: i32 = 1;
+/// Exit code for bad arguments or a failed run, so gates can tell it apart
+const EXIT_ERROR: i32 = 2;
 
 fn print_help() {
     eprintln!(
@@ -26,16 +32,81 @@ Options:
   --scenario=FILTER     Only run scenarios whose name contains FILTER
   --runs=N              Number of runs per scenario (default: 3)
   --json                Output results as JSON
-  --save-baseline       Save results as baseline to evaluation/baseline.json
-  --check               Compare results against saved baseline (exit 1 on regression)
+  --save-baseline       Save results as the current baseline (and in its history)
+  --check               Compare results against the baseline (exit 1 on regression)
   --regression-threshold=F
                         Regression threshold as fraction (default: 0.15 = 15%)
-  --help                Show this help message"
+  --format=FORMAT       Report format: text (default), markdown, or json for
+                        the same report with numbers as numbers
+  --output=FORMAT       Comparison output: text (default, see --format), or
+                        json for one record per metric plus a pass/fail verdict
+                        (needs --check or --diff-baselines)
+  --github-annotations  Also emit GitHub Actions ::error/::notice commands
+                        for comparisons (on stderr)
+  --help                Show this help message
+
+  --output=json wins over --format, and --format over --json.
+
+Baselines (no database needed):
+  --baseline=NAME       Baseline to save/check, e.g. per branch or config (default: default)
+  --baseline-dir=DIR    Baseline store root (default: evaluation)
+  --list-baselines      List named baselines
+  --baseline-history    List saved versions of --baseline
+  --diff-baselines=A,B  Compare baseline B against A (exit 1 on regression)
+  --promote-baseline=REF
+                        Make REF the current --baseline
+
+  REF is NAME (current) or NAME@ID (a version from --baseline-history).
+
+Exit codes: 0 ok, 1 regressions found, 2 error."
     );
 }
 
+/// How comparisons are printed
+#[derive(Clone, Copy, PartialEq)]
+enum ComparisonOutput {
+    /// A [`Report`] in the `--format` format
+    Rendered(ReportFormat),
+    /// The full [`ComparisonReport`] (`--json`)
+    Report,
+    /// A [`RegressionGate`] (`--output=json`)
+    Gate,
+}
+
+/// Print `report` and exit with [`EXIT_REGRESSION`] if it has regressions
+fn finish_comparison(
+    report: &ComparisonReport,
+    output: ComparisonOutput,
+    github_annotations: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match output {
+        ComparisonOutput::Rendered(format) => {
+            print!("{}", Report::comparison(report).render(format))
+        }
+        ComparisonOutput::Report => println!("{}", serde_json::to_string_pretty(report)?),
+        ComparisonOutput::Gate => println!(
+            "{}",
+            serde_json::to_string_pretty(&RegressionGate::from_report(report))?
+        ),
+    }
+    if github_annotations {
+        eprint!("{}", format_github_annotations(report));
+    }
+    if report.total_regressions > 0 {
+        std::process::exit(EXIT_REGRESSION);
+    }
+    Ok(())
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
+    if let Err(e) = run().await {
+        eprintln!("Error: {}", e);
+        std::process::exit(EXIT_ERROR);
+    }
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing (less verbose for eval)
     tracing_subscriber::registry()
         .with(
@@ -60,23 +131,95 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .and_then(|s| s.parse().ok())
         .unwrap_or(3);
     let json_output = args.iter().any(|a| a == "--json");
+    let report_format = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--format="))
+        .map(str::parse::<ReportFormat>)
+        .transpose()?;
+    let comparison_output = match args.iter().find_map(|a| a.strip_prefix("--output=")) {
+        Some("json") => ComparisonOutput::Gate,
+        Some("text") | None if json_output && report_format.is_none() => ComparisonOutput::Report,
+        Some("text") | None => ComparisonOutput::Rendered(report_format.unwrap_or_default()),
+        Some(other) => return Err(format!("Unknown --output={}; use text or json", other).into()),
+    };
+    let github_annotations = args.iter().any(|a| a == "--github-annotations");
     let save_baseline_flag = args.iter().any(|a| a == "--save-baseline");
     let check_flag = args.iter().any(|a| a == "--check");
+    let diff_refs = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--diff-baselines="));
+    if comparison_output == ComparisonOutput::Gate && !check_flag && diff_refs.is_none() {
+        return Err("--output=json needs --check or --diff-baselines; \
+                    use --format=json for the evaluation report"
+            .into());
+    }
     let regression_threshold: f32 = args
         .iter()
         .find_map(|a| a.strip_prefix("--regression-threshold="))
         .and_then(|s| s.parse().ok())
         .unwrap_or(DEFAULT_REGRESSION_THRESHOLD);
+    let baseline_name = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--baseline="))
+        .unwrap_or(DEFAULT_BASELINE_NAME);
+    let store = BaselineStore::new(
+        args.iter()
+            .find_map(|a| a.strip_prefix("--baseline-dir="))
+            .unwrap_or(DEFAULT_BASELINE_DIR),
+    );
+
+    // Baseline management commands work on the store alone
+    if args.iter().any(|a| a == "--list-baselines") {
+        for summary in store.list()? {
+            println!(
+                "{:<24} {}  {} scenarios, {} versions",
+                summary.name, summary.timestamp, summary.scenarios, summary.versions
+            );
+        }
+        return Ok(());
+    }
+    if args.iter().any(|a| a == "--baseline-history") {
+        for id in store.history(baseline_name)? {
+            println!("{}@{}", baseline_name, id);
+        }
+        return Ok(());
+    }
+    if let Some(refs) = diff_refs {
+        let (base_ref, other_ref) = refs
+            .split_once(',')
+            .ok_or("--diff-baselines expects two baselines: A,B")?;
+        let report = diff_baselines(
+            &store.load(base_ref)?,
+            &store.load(other_ref)?,
+            regression_threshold,
+        );
+        return finish_comparison(&report, comparison_output, github_annotations);
+    }
+    if let Some(source) = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--promote-baseline="))
+    {
+        let id = store.promote(source, baseline_name)?;
+        eprintln!(
+            "Promoted {} to {} ({}@{})",
+            source, baseline_name, baseline_name, id
+        );
+        return Ok(());
+    }
 
     let config = Config::from_env().map_err(|e| format!("Config error: {}", e))?;
 
     // Connect to database
-    let db_pool = easyroute::db::create_pool(&config.database_url).await?;
+    let db_pool =
+        easyroute::db::create_pool(&config.database_url, config.database_pool_size).await?;
     sqlx::migrate!("./migrations").run(&db_pool).await?;
 
-    // Initialize services
-    let poi_repo: Arc<dyn easyroute::db::PoiRepository> =
-        Arc::new(PgPoiRepository::new(db_pool.clone()));
+    // Initialize services, counting the calls each scenario makes
+    let counters = Arc::new(CallCounters::default());
+    let poi_repo: Arc<dyn easyroute::db::PoiRepository> = Arc::new(CountingPoiRepository::new(
+        Arc::new(PgPoiRepository::new(db_pool.clone())),
+        Arc::clone(&counters),
+    ));
     let mapbox_client = if let Some(ref base_url) = config.mapbox_base_url {
         MapboxClient::with_config(
             config.mapbox_api_key.clone(),
@@ -88,13 +231,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     let poi_service = PoiService::new(poi_repo.clone());
     let snapping_service = SnappingService::new(poi_repo.clone());
+    let directions =
+        CountingDirectionsProvider::new(Arc::new(mapbox_client.clone()), Arc::clone(&counters));
     let route_generator = RouteGenerator::new(
         mapbox_client,
         poi_service,
         snapping_service,
         config.snap_radius_m,
         config.route_generator.clone(),
-    );
+    )
+    .with_directions_provider(Arc::new(directions));
 
     // Select scenarios
     let all_scenarios = default_scenarios();
@@ -112,7 +258,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         for s in &all_scenarios {
             eprintln!("  {}", s.name);
         }
-        std::process::exit(1);
+        std::process::exit(EXIT_ERROR);
     }
 
     eprintln!(
@@ -128,6 +274,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for scenario in &scenarios {
         let mut all_routes: Vec<Route> = Vec::new();
         let mut successes = 0;
+        let calls_before = (counters.directions_calls(), counters.poi_queries());
+        let started = Instant::now();
 
         for run in 0..runs {
             eprintln!("  {} (run {}/{})", scenario.name, run + 1, runs);
@@ -152,6 +300,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
+        let cost = ScenarioCost::since(&counters, calls_before, started.elapsed());
         let route_refs: Vec<&Route> = all_routes.iter().collect();
         let metrics_agg = MetricsAggregate::from_routes(&route_refs, scenario.distance_km);
 
@@ -161,37 +310,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             total_routes: all_routes.len(),
             success_rate: successes as f32 / runs as f32,
             metrics_agg,
+            tier_counts: QualityTierCounts::from_routes(&route_refs),
+            cost,
         });
     }
 
     // Handle --save-baseline
     if save_baseline_flag {
         let baseline = Baseline::from_results(&results, runs);
-        let path = PathBuf::from(DEFAULT_BASELINE_PATH);
-        save_baseline(&baseline, &path)?;
-        eprintln!("Baseline saved to {}", path.display());
+        let id = store.save(baseline_name, &baseline)?;
+        eprintln!(
+            "Baseline saved as {} ({}@{})",
+            baseline_name, baseline_name, id
+        );
     }
 
     // Handle --check
     if check_flag {
-        let path = PathBuf::from(DEFAULT_BASELINE_PATH);
-        let baseline = load_baseline(&path)?;
+        let baseline = store.load(baseline_name)?;
         let report = compare(&baseline, &results, regression_threshold);
-
-        if json_output {
-            println!("{}", serde_json::to_string_pretty(&report)?);
-        } else {
-            print!("{}", format_comparison_report(&report));
-        }
-
-        if report.total_regressions > 0 {
-            std::process::exit(1);
-        }
-        return Ok(());
+        return finish_comparison(&report, comparison_output, github_annotations);
     }
 
     // Standard output
-    if json_output {
+    if let Some(format) = report_format {
+        print!("{}", Report::evaluation(&results).render(format));
+    } else if json_output {
         let json_results: Vec<serde_json::Value> = results
             .iter()
             .map(|r| {
@@ -200,6 +344,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     "runs": r.runs,
                     "total_routes": r.total_routes,
                     "success_rate": r.success_rate,
+                    "tier_counts": r.tier_counts,
+                    "cost": r.cost,
+                    "cost_per_run": r.cost.per_run(r.runs),
                 });
                 if let Some(ref agg) = r.metrics_agg {
                     obj["metrics"] = serde_json::json!({
@@ -0,0 +1,124 @@
+use easyroute::evaluation::load::{
+    format_load_report, LoadOutcome, LoadProfile, LoadReport, LoadSample,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::env;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+const DEFAULT_RPS: f64 = 2.0;
+const DEFAULT_DURATION_SECS: u64 = 30;
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+fn print_help() {
+    eprintln!(
+        "\
+Usage: loadtest --target=URL [OPTIONS]
+
+Sends synthetic loop route requests (city-weighted start points, mixed
+distances, modes and categories) at a fixed rate and reports latency
+percentiles and an error breakdown.
+
+Options:
+  --target=URL          Server base URL (e.g. http://localhost:3000)
+  --rps=F               Requests per second (default: 2)
+  --duration=SECS       Test duration in seconds (default: 30)
+  --timeout=SECS        Per-request timeout (default: 30)
+  --seed=N              RNG seed for a reproducible request mix
+  --json                Output the report as JSON
+  --help                Show this help message
+
+Each request may cost several Mapbox calls unless the server's
+MAPBOX_BASE_URL points at a mock or the routes are cached."
+    );
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|a| a == "--help") {
+        print_help();
+        return Ok(());
+    }
+
+    let Some(base_url) = args.iter().find_map(|a| a.strip_prefix("--target=")) else {
+        print_help();
+        std::process::exit(2);
+    };
+    let arg = |name: &str| args.iter().find_map(|a| a.strip_prefix(name));
+    let rps: f64 = arg("--rps=")
+        .and_then(|s| s.parse().ok())
+        .filter(|r: &f64| *r > 0.0)
+        .unwrap_or(DEFAULT_RPS);
+    let duration_secs: u64 = arg("--duration=")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_DURATION_SECS);
+    let timeout_secs: u64 = arg("--timeout=")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+    let mut rng = match arg("--seed=").and_then(|s| s.parse().ok()) {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let json_output = args.iter().any(|a| a == "--json");
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .build()?;
+    let url = format!("{}/api/v1/routes/loop", base_url.trim_end_matches('/'));
+    let profile = LoadProfile::default();
+    let total_requests = (rps * duration_secs as f64).round() as usize;
+
+    eprintln!(
+        "Sending {} requests at {:.2} rps to {}...",
+        total_requests, rps, url
+    );
+
+    // Open-loop schedule: requests fire on time even when the server is slow
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / rps));
+    let mut in_flight = JoinSet::new();
+    let started = Instant::now();
+    for _ in 0..total_requests {
+        ticker.tick().await;
+        let request = profile.sample_request(&mut rng);
+        let client = client.clone();
+        let url = url.clone();
+        in_flight.spawn(async move {
+            let sent = Instant::now();
+            let outcome = match client.post(&url).json(&request).send().await {
+                Ok(response) if response.status().is_success() => {
+                    // Include body transfer in the latency
+                    match response.bytes().await {
+                        Ok(_) => LoadOutcome::Ok,
+                        Err(e) if e.is_timeout() => LoadOutcome::Timeout,
+                        Err(_) => LoadOutcome::Connection,
+                    }
+                }
+                Ok(response) => LoadOutcome::Status(response.status().as_u16()),
+                Err(e) if e.is_timeout() => LoadOutcome::Timeout,
+                Err(_) => LoadOutcome::Connection,
+            };
+            LoadSample {
+                latency_ms: sent.elapsed().as_millis() as u64,
+                outcome,
+            }
+        });
+    }
+
+    let mut samples = Vec::with_capacity(total_requests);
+    while let Some(result) = in_flight.join_next().await {
+        if let Ok(sample) = result {
+            samples.push(sample);
+        }
+    }
+
+    let report = LoadReport::new(&samples, rps, started.elapsed());
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", format_load_report(&report));
+    }
+
+    Ok(())
+}
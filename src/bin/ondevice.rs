@@ -1,7 +1,7 @@
 use easyroute::cache::MemoryCacheService;
 use easyroute::config::RouteGeneratorConfig;
 use easyroute::constants::DEFAULT_MEMORY_CACHE_MAX_ENTRIES;
-use easyroute::db::SqlitePoiRepository;
+use easyroute::db::{SqlitePoiRepository, SqliteReadPoolConfig};
 use easyroute::services::mapbox::{AuthMode, MapboxClient};
 use easyroute::services::poi_service::PoiService;
 use easyroute::services::route_generator::RouteGenerator;
@@ -9,9 +9,7 @@ use easyroute::services::snapping_service::SnappingService;
 use easyroute::AppState;
 
 use axum::Router;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use std::env;
-use std::str::FromStr;
 use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
@@ -77,22 +75,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    // Open SQLite pool with read-performance pragmas
+    // Read-only SQLite pool sized for concurrent requests
     tracing::info!("Opening region database: {}", region_path);
-    let opts = SqliteConnectOptions::from_str(&format!("sqlite:{}", region_path))?
-        .create_if_missing(false)
-        .pragma("journal_mode", "WAL")
-        .pragma("mmap_size", "268435456") // 256 MB
-        .pragma("cache_size", "-16384"); // 16 MB (negative = KiB)
-
-    let pool = SqlitePoolOptions::new()
-        .max_connections(4)
-        .connect_with(opts)
+    let read_config = SqliteReadPoolConfig::server();
+    let pool = SqlitePoiRepository::open_read_pool(&region_path, &read_config)
         .await
         .map_err(|e| format!("Failed to open region DB '{}': {}", region_path, e))?;
-
-    // Ensure schema exists (idempotent)
-    SqlitePoiRepository::create_schema(&pool).await?;
+    tracing::info!(
+        max_connections = read_config.max_connections,
+        "Opened read pool with {} connections",
+        read_config.max_connections
+    );
 
     // Log region metadata
     let poi_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pois")
@@ -139,6 +132,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let route_generator_config = RouteGeneratorConfig::from_env()
         .map_err(|e| format!("Route generator config error: {}", e))?;
+    if let Err(issues) = route_generator_config.validate() {
+        let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+        return Err(format!("Invalid route generator config: {}", issues.join("; ")).into());
+    }
 
     let poi_repo: Arc<dyn easyroute::db::PoiRepository> = Arc::new(SqlitePoiRepository::new(pool));
     let poi_service = PoiService::new(poi_repo.clone());
@@ -156,6 +153,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         poi_repo,
         route_generator,
         cache: Some(cache),
+        shadow: None,
+        request_log: None,
+        privacy: None,
+        tenants: None,
+        guard: Default::default(),
+        events: Default::default(),
+        artifacts: None,
     });
 
     // Build router: API routes + static file fallback for web UI
@@ -0,0 +1,137 @@
+//! Scan the POI database for anomalies and print a repair plan.
+//!
+//! ```text
+//! cargo run --bin poi_doctor -- --bbox=7.40,43.72,7.44,43.76
+//! cargo run --bin poi_doctor -- --bbox=7.40,43.72,7.44,43.76 --fix
+//! ```
+//!
+//! Checks for POIs outside the region's bounding box, empty names, rows
+//! duplicating an `osm_id` and categories the osm2pgsql import style
+//! (`osm/osm_poi_style.lua`) never assigns. Without `--fix` nothing is
+//! written.
+
+use easyroute::constants::DEFAULT_DATABASE_POOL_SIZE;
+use easyroute::db::queries;
+use easyroute::models::poi_anomaly::{FlaggedPoi, RepairPlan};
+use easyroute::models::BoundingBox;
+use easyroute::osm;
+use std::env;
+
+fn print_help() {
+    eprintln!(
+        "\
+Usage: poi_doctor [OPTIONS]
+
+Scan the pois table for anomalies and print a repair plan.
+
+Options:
+  --bbox=MIN_LNG,MIN_LAT,MAX_LNG,MAX_LAT
+               Region the database covers; POIs outside it are flagged
+               (skipped when not given)
+  --fix        Apply the repair plan
+  --help       Show this help message
+
+Needs DATABASE_URL."
+    );
+}
+
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter().find_map(|a| a.strip_prefix(name))
+}
+
+/// Category names an import can produce, as assigned by the osm2pgsql
+/// style (`osm/osm_poi_style.lua`) that fills the Postgres database
+fn supported_categories() -> Vec<String> {
+    osm::style_categories()
+        .iter()
+        .map(|c| c.to_string())
+        .collect()
+}
+
+fn print_section(title: &str, flagged: &[FlaggedPoi]) {
+    println!("{} ({})", title, flagged.len());
+    for poi in flagged {
+        println!(
+            "  {}  {:<32} {:<16} {}",
+            poi.id, poi.name, poi.category, poi.anomaly
+        );
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "help") {
+        print_help();
+        return Ok(());
+    }
+    let fix = args.iter().any(|a| a == "--fix");
+    let bbox = flag(&args, "--bbox=").map(BoundingBox::parse).transpose()?;
+
+    let database_url = env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set")?;
+    let pool = easyroute::db::create_pool(&database_url, DEFAULT_DATABASE_POOL_SIZE).await?;
+
+    let mut flagged = Vec::new();
+    match &bbox {
+        Some(bbox) => {
+            let outside = queries::find_pois_outside_bbox(&pool, bbox).await?;
+            print_section("Outside region", &outside);
+            flagged.extend(outside);
+        }
+        None => println!("Outside region: skipped (no --bbox)"),
+    }
+    let sections = [
+        (
+            "Empty names",
+            queries::find_pois_with_empty_names(&pool).await?,
+        ),
+        (
+            "Duplicate osm_ids",
+            queries::find_duplicate_osm_ids(&pool).await?,
+        ),
+        (
+            "Unsupported categories",
+            queries::find_pois_outside_categories(&pool, &supported_categories()).await?,
+        ),
+    ];
+    for (title, found) in sections {
+        print_section(title, &found);
+        flagged.extend(found);
+    }
+
+    let plan = RepairPlan::new(&flagged);
+    println!();
+    if plan.is_empty() {
+        println!("No anomalies found");
+        return Ok(());
+    }
+    println!("Repair plan ({} POIs)", plan.len());
+    for id in &plan.deletes {
+        println!("  delete {}", id);
+    }
+    for (id, name) in &plan.renames {
+        println!("  rename {} -> {:?}", id, name);
+    }
+
+    if fix {
+        let (deleted, renamed) = queries::apply_poi_repairs(&pool, &plan).await?;
+        println!("Deleted {} POIs, renamed {}", deleted, renamed);
+    } else {
+        println!("Dry run; apply with --fix");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supported_categories_exclude_untagged() {
+        let supported = supported_categories();
+        assert!(supported.contains(&"toilets".to_string()));
+        assert!(!supported.contains(&"cafe".to_string()));
+        assert!(!supported.contains(&"waypoint".to_string()));
+    }
+}
@@ -1,21 +1,24 @@
 use axum::{
     body::Body,
     extract::{Path, Query, State},
-    http::{header, HeaderMap, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json},
     routing::{get, post},
     Router,
 };
 use reqwest::Client;
 use rusqlite::OpenFlags;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use std::{
     collections::HashMap,
+    io::SeekFrom,
     path::PathBuf,
-    sync::Arc,
+    sync::{Arc, RwLock},
     time::{Duration, Instant},
 };
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio::sync::Mutex;
 use tokio_util::io::ReaderStream;
 use tower_http::trace::TraceLayer;
@@ -33,6 +36,10 @@ struct ProxyConfig {
     rate_limit: usize,
     port: u16,
     regions_dir: PathBuf,
+    /// Bearer token for `/v1/admin/*`, which is only mounted when set
+    admin_key: Option<String>,
+    /// Rescan `regions_dir` this often, so new builds appear on their own
+    rescan_interval: Option<Duration>,
 }
 
 impl ProxyConfig {
@@ -63,6 +70,14 @@ impl ProxyConfig {
         let regions_dir: PathBuf = std::env::var("PROXY_REGIONS_DIR")
             .unwrap_or_else(|_| "./regions".to_string())
             .into();
+        let admin_key = std::env::var("PROXY_ADMIN_KEY")
+            .ok()
+            .map(|k| k.trim().to_string())
+            .filter(|k| !k.is_empty());
+        let rescan_secs: u64 = std::env::var("PROXY_REGIONS_RESCAN_SECS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .map_err(|_| "Invalid PROXY_REGIONS_RESCAN_SECS")?;
 
         Ok(Self {
             mapbox_api_key,
@@ -70,6 +85,8 @@ impl ProxyConfig {
             rate_limit,
             port,
             regions_dir,
+            admin_key,
+            rescan_interval: (rescan_secs > 0).then(|| Duration::from_secs(rescan_secs)),
         })
     }
 }
@@ -104,18 +121,107 @@ struct RegionInfo {
     id: String,
     name: String,
     size_bytes: u64,
+    /// Hex SHA-256 of the whole file, for clients to check downloads against
+    sha256: String,
     poi_count: u64,
     build_date: String,
+    /// SHA-256 of the POI data recorded at build time, which the signature covers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum_sha256: Option<String>,
+    /// Ed25519 signature over `checksum_sha256`, when the region was signed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature_ed25519: Option<String>,
+    /// Area the region covers; absent for regions built before it was recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bbox: Option<RegionBounds>,
 }
 
-fn scan_regions(dir: &std::path::Path) -> Vec<RegionInfo> {
-    let entries = match std::fs::read_dir(dir) {
-        Ok(e) => e,
-        Err(e) => {
-            tracing::warn!(path = %dir.display(), error = %e, "Cannot read regions directory");
-            return Vec::new();
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+struct RegionBounds {
+    min_lat: f64,
+    max_lat: f64,
+    min_lng: f64,
+    max_lng: f64,
+}
+
+impl RegionBounds {
+    /// From the `min_lat`/`max_lat`/`min_lng`/`max_lng` keys of `region_meta`
+    fn from_meta(meta: &HashMap<String, String>) -> Option<Self> {
+        let value = |key: &str| meta.get(key)?.parse::<f64>().ok();
+        Some(RegionBounds {
+            min_lat: value("min_lat")?,
+            max_lat: value("max_lat")?,
+            min_lng: value("min_lng")?,
+            max_lng: value("max_lng")?,
+        })
+    }
+
+    /// Regions crossing the antimeridian have `min_lng > max_lng`
+    fn crosses_antimeridian(&self) -> bool {
+        self.min_lng > self.max_lng
+    }
+
+    fn contains(&self, lat: f64, lng: f64) -> bool {
+        let in_lng = if self.crosses_antimeridian() {
+            lng >= self.min_lng || lng <= self.max_lng
+        } else {
+            (self.min_lng..=self.max_lng).contains(&lng)
+        };
+        (self.min_lat..=self.max_lat).contains(&lat) && in_lng
+    }
+
+    /// In square degrees, only for ranking nested regions
+    fn area(&self) -> f64 {
+        let width = self.max_lng - self.min_lng;
+        let width = if self.crosses_antimeridian() {
+            width + 360.0
+        } else {
+            width
+        };
+        (self.max_lat - self.min_lat) * width
+    }
+}
+
+/// Regions whose bbox covers (`lat`, `lng`), smallest first so that e.g. a
+/// city extract comes before the country it lies in
+fn locate_regions(regions: &[RegionInfo], lat: f64, lng: f64) -> Vec<&RegionInfo> {
+    let mut covering: Vec<(&RegionInfo, RegionBounds)> = regions
+        .iter()
+        .filter_map(|r| Some((r, r.bbox?)))
+        .filter(|(_, bbox)| bbox.contains(lat, lng))
+        .collect();
+    covering.sort_by(|(_, a), (_, b)| a.area().total_cmp(&b.area()));
+    covering.into_iter().map(|(r, _)| r).collect()
+}
+
+/// Hex SHA-256 of the file at `path`, read in chunks
+fn file_sha256(path: &std::path::Path) -> std::io::Result<String> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
         }
-    };
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// The regions in `dir`, or none if it can't be read
+fn scan_regions(dir: &std::path::Path) -> Vec<RegionInfo> {
+    try_scan_regions(dir).unwrap_or_else(|e| {
+        tracing::warn!(path = %dir.display(), error = %e, "Cannot read regions directory");
+        Vec::new()
+    })
+}
+
+/// Every `.db` file in `dir` that opens as a region database, sorted by id
+fn try_scan_regions(dir: &std::path::Path) -> std::io::Result<Vec<RegionInfo>> {
+    let entries = std::fs::read_dir(dir)?;
 
     let mut regions = Vec::new();
     for entry in entries.flatten() {
@@ -130,6 +236,13 @@ fn scan_regions(dir: &std::path::Path) -> Vec<RegionInfo> {
         };
 
         let size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let sha256 = match file_sha256(&path) {
+            Ok(sha256) => sha256,
+            Err(e) => {
+                tracing::warn!(file = %path.display(), error = %e, "Cannot read region DB");
+                continue;
+            }
+        };
 
         let conn = match rusqlite::Connection::open_with_flags(
             &path,
@@ -162,6 +275,7 @@ fn scan_regions(dir: &std::path::Path) -> Vec<RegionInfo> {
                 .cloned()
                 .unwrap_or_else(|| "Unknown".to_string()),
             size_bytes,
+            sha256,
             poi_count: meta
                 .get("poi_count")
                 .and_then(|v| v.parse().ok())
@@ -170,11 +284,58 @@ fn scan_regions(dir: &std::path::Path) -> Vec<RegionInfo> {
                 .get("build_date")
                 .cloned()
                 .unwrap_or_else(|| "Unknown".to_string()),
+            checksum_sha256: meta.get("checksum_sha256").cloned(),
+            signature_ed25519: meta.get("signature_ed25519").cloned(),
+            bbox: RegionBounds::from_meta(&meta),
         });
     }
 
     regions.sort_by(|a, b| a.id.cmp(&b.id));
-    regions
+    Ok(regions)
+}
+
+/// What a rescan changed in the catalog, by region id
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct CatalogChanges {
+    added: Vec<String>,
+    removed: Vec<String>,
+    /// Rebuilt: another build date, checksum or size
+    updated: Vec<String>,
+    /// Regions in the new catalog
+    regions: usize,
+}
+
+impl CatalogChanges {
+    fn between(old: &[RegionInfo], new: &[RegionInfo]) -> Self {
+        let find = |regions: &[RegionInfo], id: &str| regions.iter().position(|r| r.id == id);
+        let mut changes = CatalogChanges {
+            regions: new.len(),
+            ..Default::default()
+        };
+        for region in new {
+            match find(old, &region.id).map(|i| &old[i]) {
+                None => changes.added.push(region.id.clone()),
+                Some(before)
+                    if before.build_date != region.build_date
+                        || before.checksum_sha256 != region.checksum_sha256
+                        || before.size_bytes != region.size_bytes =>
+                {
+                    changes.updated.push(region.id.clone())
+                }
+                Some(_) => {}
+            }
+        }
+        changes.removed = old
+            .iter()
+            .filter(|r| find(new, &r.id).is_none())
+            .map(|r| r.id.clone())
+            .collect();
+        changes
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
 }
 
 // ── App state ───────────────────────────────────────────
@@ -183,7 +344,27 @@ struct AppState {
     config: ProxyConfig,
     http: Client,
     limiter: Mutex<RateLimiter>,
-    regions: Vec<RegionInfo>,
+    /// Replaced as a whole on rescan; readers keep the catalog they started with
+    regions: RwLock<Arc<Vec<RegionInfo>>>,
+}
+
+impl AppState {
+    fn regions(&self) -> Arc<Vec<RegionInfo>> {
+        self.regions.read().unwrap().clone()
+    }
+
+    /// Rescan `PROXY_REGIONS_DIR` and serve the result. An unreadable
+    /// directory keeps the current catalog.
+    async fn rescan_regions(&self) -> std::io::Result<CatalogChanges> {
+        let dir = self.config.regions_dir.clone();
+        let scanned = tokio::task::spawn_blocking(move || try_scan_regions(&dir))
+            .await
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))??;
+        let mut regions = self.regions.write().unwrap();
+        let changes = CatalogChanges::between(&regions, &scanned);
+        *regions = Arc::new(scanned);
+        Ok(changes)
+    }
 }
 
 // ── Auth helpers ────────────────────────────────────────
@@ -200,6 +381,79 @@ fn validate_api_key(token: &str, valid_keys: &[String]) -> bool {
     valid_keys.iter().any(|k| k == token)
 }
 
+// ── Range requests ──────────────────────────────────────
+
+/// Part of a region file to send, from the `Range` / `If-Range` headers
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    Full,
+    /// Inclusive byte offsets
+    Partial {
+        start: u64,
+        end: u64,
+    },
+    Unsatisfiable,
+}
+
+/// Strong ETag from the file's size and modification time, which change
+/// whenever a region is rebuilt
+fn region_etag(metadata: &std::fs::Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("\"{:x}-{:x}\"", metadata.len(), modified)
+}
+
+/// Range to serve for a `size`-byte file whose ETag is `etag`. Only single
+/// ranges are supported; anything else, or an `If-Range` validator that no
+/// longer matches, gets the whole file.
+fn requested_range(headers: &HeaderMap, size: u64, etag: &str) -> ByteRange {
+    let Some(range) = headers.get(header::RANGE).and_then(|v| v.to_str().ok()) else {
+        return ByteRange::Full;
+    };
+    if let Some(if_range) = headers.get(header::IF_RANGE) {
+        if if_range.to_str().ok() != Some(etag) {
+            return ByteRange::Full;
+        }
+    }
+    parse_range(range, size).unwrap_or(ByteRange::Full)
+}
+
+/// `bytes=start-end`, `bytes=start-` or `bytes=-suffix`; `None` when the
+/// header is malformed or asks for several ranges
+fn parse_range(range: &str, size: u64) -> Option<ByteRange> {
+    let spec = range.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.trim().split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", "") => return None,
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return Some(ByteRange::Unsatisfiable);
+            }
+            (size.saturating_sub(suffix), size.checked_sub(1)?)
+        }
+        (start, "") => (start.parse().ok()?, size.saturating_sub(1)),
+        (start, end) => {
+            let (start, end): (u64, u64) = (start.parse().ok()?, end.parse().ok()?);
+            if end < start {
+                return None;
+            }
+            (start, end.min(size.saturating_sub(1)))
+        }
+    };
+    if start >= size {
+        return Some(ByteRange::Unsatisfiable);
+    }
+    Some(ByteRange::Partial { start, end })
+}
+
 // ── Handlers ────────────────────────────────────────────
 
 async fn health(State(state): State<Arc<AppState>>) -> Json<Value> {
@@ -325,7 +579,50 @@ async fn list_regions(State(state): State<Arc<AppState>>, headers: HeaderMap) ->
             .into_response();
     }
 
-    Json(json!({ "regions": state.regions })).into_response()
+    Json(json!({ "regions": *state.regions() })).into_response()
+}
+
+#[derive(Deserialize)]
+struct LocateParams {
+    lat: f64,
+    lng: f64,
+}
+
+async fn locate_region(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LocateParams>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let token = match extract_bearer_token(&headers) {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"error": "Missing or invalid Authorization header"})),
+            )
+                .into_response()
+        }
+    };
+
+    if !validate_api_key(token, &state.config.api_keys) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": "Invalid API key"})),
+        )
+            .into_response();
+    }
+
+    if !(-90.0..=90.0).contains(&params.lat) || !(-180.0..=180.0).contains(&params.lng) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Coordinates out of range"})),
+        )
+            .into_response();
+    }
+
+    let catalog = state.regions();
+    let regions = locate_regions(&catalog, params.lat, params.lng);
+    Json(json!({ "regions": regions })).into_response()
 }
 
 async fn download_region(
@@ -365,7 +662,7 @@ async fn download_region(
     }
 
     // Verify the region exists in our catalog
-    if !state.regions.iter().any(|r| r.id == id) {
+    if !state.regions().iter().any(|r| r.id == id) {
         return (
             StatusCode::NOT_FOUND,
             Json(json!({"error": "Region not found"})),
@@ -375,7 +672,7 @@ async fn download_region(
 
     let path = state.config.regions_dir.join(format!("{}.db", id));
 
-    let file = match tokio::fs::File::open(&path).await {
+    let mut file = match tokio::fs::File::open(&path).await {
         Ok(f) => f,
         Err(e) => {
             tracing::error!(path = %path.display(), error = %e, "Cannot open region file");
@@ -387,21 +684,107 @@ async fn download_region(
         }
     };
 
-    let file_size = file.metadata().await.map(|m| m.len()).unwrap_or(0);
-    let stream = ReaderStream::new(file);
-    let body = Body::from_stream(stream);
+    let metadata = match file.metadata().await {
+        Ok(m) => m,
+        Err(e) => {
+            tracing::error!(path = %path.display(), error = %e, "Cannot stat region file");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Cannot read region file"})),
+            )
+                .into_response();
+        }
+    };
+    let file_size = metadata.len();
+    let etag = region_etag(&metadata);
 
     let filename = format!("{}.db", id);
-    let headers = [
-        (header::CONTENT_TYPE, "application/octet-stream".to_string()),
-        (header::CONTENT_LENGTH, file_size.to_string()),
-        (
-            header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", filename),
-        ),
-    ];
-
-    (headers, body).into_response()
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+    response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)) {
+        response_headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+
+    let (status, start, length) = match requested_range(&headers, file_size, &etag) {
+        ByteRange::Full => (StatusCode::OK, 0, file_size),
+        ByteRange::Partial { start, end } => {
+            if let Ok(value) =
+                HeaderValue::from_str(&format!("bytes {}-{}/{}", start, end, file_size))
+            {
+                response_headers.insert(header::CONTENT_RANGE, value);
+            }
+            (StatusCode::PARTIAL_CONTENT, start, end - start + 1)
+        }
+        ByteRange::Unsatisfiable => {
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes */{}", file_size)) {
+                response_headers.insert(header::CONTENT_RANGE, value);
+            }
+            return (StatusCode::RANGE_NOT_SATISFIABLE, response_headers).into_response();
+        }
+    };
+
+    if start > 0 {
+        if let Err(e) = file.seek(SeekFrom::Start(start)).await {
+            tracing::error!(path = %path.display(), error = %e, "Cannot seek region file");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Cannot read region file"})),
+            )
+                .into_response();
+        }
+    }
+    response_headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    let body = Body::from_stream(ReaderStream::new(file.take(length)));
+
+    (status, response_headers, body).into_response()
+}
+
+/// POST /v1/admin/regions/rescan - Re-read `PROXY_REGIONS_DIR`, so new or
+/// rebuilt regions are served without a restart
+async fn rescan_regions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let token = match extract_bearer_token(&headers) {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"error": "Missing or invalid Authorization header"})),
+            )
+                .into_response()
+        }
+    };
+
+    if state.config.admin_key.as_deref() != Some(token) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": "Invalid admin key"})),
+        )
+            .into_response();
+    }
+
+    match state.rescan_regions().await {
+        Ok(changes) => {
+            tracing::info!(?changes, "Region catalog rescanned on request");
+            Json(changes).into_response()
+        }
+        Err(e) => {
+            tracing::error!(path = %state.config.regions_dir.display(), error = %e, "Cannot rescan regions");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Cannot read regions directory"})),
+            )
+                .into_response()
+        }
+    }
 }
 
 // ── Main ────────────────────────────────────────────────
@@ -433,17 +816,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config,
         http: Client::new(),
         limiter: Mutex::new(RateLimiter::default()),
-        regions,
+        regions: RwLock::new(Arc::new(regions)),
     });
 
-    let app = Router::new()
+    if let Some(every) = state.config.rescan_interval {
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+            loop {
+                interval.tick().await;
+                match state.rescan_regions().await {
+                    Ok(changes) if !changes.is_empty() => {
+                        tracing::info!(?changes, "Region catalog changed")
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Cannot rescan regions"),
+                }
+            }
+        });
+    }
+
+    let mut app = Router::new()
         .route("/health", get(health))
         .route("/v1/directions/{profile}/{coordinates}", get(directions))
         .route("/v1/telemetry", post(telemetry))
         .route("/v1/regions", get(list_regions))
-        .route("/v1/regions/{id}/download", get(download_region))
-        .layer(TraceLayer::new_for_http())
-        .with_state(state);
+        .route("/v1/regions/locate", get(locate_region))
+        .route("/v1/regions/{id}/download", get(download_region));
+    if state.config.admin_key.is_some() {
+        app = app.route("/v1/admin/regions/rescan", post(rescan_regions));
+    }
+    let app = app.layer(TraceLayer::new_for_http()).with_state(state);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     tracing::info!("Proxy listening on http://{}", addr);
@@ -476,6 +879,8 @@ mod tests {
         assert_eq!(cfg.rate_limit, 30);
         assert_eq!(cfg.port, 5000);
         assert_eq!(cfg.regions_dir, PathBuf::from("./regions"));
+        assert_eq!(cfg.admin_key, None);
+        assert_eq!(cfg.rescan_interval, None);
         unsafe {
             std::env::remove_var("PROXY_RATE_LIMIT");
             std::env::remove_var("PROXY_PORT");
@@ -572,6 +977,69 @@ mod tests {
         assert!(!validate_api_key("wrong", &keys));
     }
 
+    // --- Range requests ---
+
+    #[test]
+    fn range_forms() {
+        assert_eq!(
+            parse_range("bytes=0-99", 1000),
+            Some(ByteRange::Partial { start: 0, end: 99 })
+        );
+        assert_eq!(
+            parse_range("bytes=500-", 1000),
+            Some(ByteRange::Partial {
+                start: 500,
+                end: 999
+            })
+        );
+        assert_eq!(
+            parse_range("bytes=-100", 1000),
+            Some(ByteRange::Partial {
+                start: 900,
+                end: 999
+            })
+        );
+        // End past the file is clamped
+        assert_eq!(
+            parse_range("bytes=900-5000", 1000),
+            Some(ByteRange::Partial {
+                start: 900,
+                end: 999
+            })
+        );
+    }
+
+    #[test]
+    fn range_unsatisfiable_or_ignored() {
+        assert_eq!(
+            parse_range("bytes=1000-", 1000),
+            Some(ByteRange::Unsatisfiable)
+        );
+        assert_eq!(
+            parse_range("bytes=-0", 1000),
+            Some(ByteRange::Unsatisfiable)
+        );
+        assert_eq!(parse_range("bytes=0-1,5-9", 1000), None);
+        assert_eq!(parse_range("bytes=9-1", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+    }
+
+    #[test]
+    fn if_range_must_match_etag() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RANGE, "bytes=100-".parse().unwrap());
+        let partial = ByteRange::Partial {
+            start: 100,
+            end: 999,
+        };
+        assert_eq!(requested_range(&headers, 1000, "\"a\""), partial);
+
+        headers.insert(header::IF_RANGE, "\"a\"".parse().unwrap());
+        assert_eq!(requested_range(&headers, 1000, "\"a\""), partial);
+        // Region rebuilt since the partial download started
+        assert_eq!(requested_range(&headers, 1000, "\"b\""), ByteRange::Full);
+    }
+
     // --- Region scanning ---
 
     #[test]
@@ -583,6 +1051,158 @@ mod tests {
         let _ = std::fs::remove_dir(&dir);
     }
 
+    #[test]
+    fn scan_regions_reads_checksum_and_signature() {
+        let dir = std::env::temp_dir().join("easyroute_test_signed_regions");
+        let _ = std::fs::create_dir_all(&dir);
+        let conn = rusqlite::Connection::open(dir.join("monaco.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS region_meta (key TEXT PRIMARY KEY, value TEXT);
+             INSERT OR REPLACE INTO region_meta VALUES ('region_name', 'monaco');
+             INSERT OR REPLACE INTO region_meta VALUES ('checksum_sha256', 'abc123');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let regions = scan_regions(&dir);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].checksum_sha256.as_deref(), Some("abc123"));
+        assert_eq!(regions[0].signature_ed25519, None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn region_db(dir: &std::path::Path, id: &str, build_date: &str) {
+        let conn = rusqlite::Connection::open(dir.join(format!("{}.db", id))).unwrap();
+        conn.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS region_meta (key TEXT PRIMARY KEY, value TEXT);
+             INSERT OR REPLACE INTO region_meta VALUES ('build_date', '{}');",
+            build_date
+        ))
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn rescan_serves_new_and_rebuilt_regions() {
+        let dir = std::env::temp_dir().join("easyroute_test_rescan_regions");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        region_db(&dir, "monaco", "2026-10-01T00:00:00Z");
+        region_db(&dir, "andorra", "2026-10-01T00:00:00Z");
+
+        let state = AppState {
+            config: ProxyConfig {
+                mapbox_api_key: "pk.test".to_string(),
+                api_keys: vec!["key1".to_string()],
+                rate_limit: 20,
+                port: 4000,
+                regions_dir: dir.clone(),
+                admin_key: Some("admin".to_string()),
+                rescan_interval: None,
+            },
+            http: Client::new(),
+            limiter: Mutex::new(RateLimiter::default()),
+            regions: RwLock::new(Arc::new(scan_regions(&dir))),
+        };
+        assert_eq!(state.regions().len(), 2);
+
+        region_db(&dir, "monaco", "2026-10-08T00:00:00Z");
+        region_db(&dir, "malta", "2026-10-08T00:00:00Z");
+        std::fs::remove_file(dir.join("andorra.db")).unwrap();
+        let changes = state.rescan_regions().await.unwrap();
+        assert_eq!(
+            changes,
+            CatalogChanges {
+                added: vec!["malta".to_string()],
+                removed: vec!["andorra".to_string()],
+                updated: vec!["monaco".to_string()],
+                regions: 2,
+            }
+        );
+        assert_eq!(state.regions()[1].build_date, "2026-10-08T00:00:00Z");
+        assert!(state.rescan_regions().await.unwrap().is_empty());
+
+        // A vanished directory keeps the catalog being served
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(state.rescan_regions().await.is_err());
+        assert_eq!(state.regions().len(), 2);
+    }
+
+    #[test]
+    fn locate_prefers_smallest_covering_region() {
+        let region = |id: &str, bbox: Option<RegionBounds>| RegionInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            size_bytes: 0,
+            sha256: String::new(),
+            poi_count: 0,
+            build_date: "Unknown".to_string(),
+            checksum_sha256: None,
+            signature_ed25519: None,
+            bbox,
+        };
+        let regions = vec![
+            region(
+                "france",
+                Some(RegionBounds {
+                    min_lat: 41.3,
+                    max_lat: 51.1,
+                    min_lng: -5.2,
+                    max_lng: 9.6,
+                }),
+            ),
+            region(
+                "monaco",
+                Some(RegionBounds {
+                    min_lat: 43.72,
+                    max_lat: 43.76,
+                    min_lng: 7.40,
+                    max_lng: 7.44,
+                }),
+            ),
+            region(
+                "fiji",
+                Some(RegionBounds {
+                    min_lat: -21.0,
+                    max_lat: -12.4,
+                    min_lng: 176.8,
+                    max_lng: -178.2,
+                }),
+            ),
+            region("unknown", None),
+        ];
+
+        let ids = |lat, lng| -> Vec<String> {
+            locate_regions(&regions, lat, lng)
+                .into_iter()
+                .map(|r| r.id.clone())
+                .collect()
+        };
+        assert_eq!(ids(43.74, 7.42), vec!["monaco", "france"]);
+        assert_eq!(ids(48.85, 2.35), vec!["france"]);
+        assert!(ids(40.71, -74.0).is_empty());
+        assert_eq!(ids(-17.7, 178.4), vec!["fiji"]);
+        assert_eq!(ids(-16.8, -179.9), vec!["fiji"]);
+        assert!(ids(-17.7, 170.0).is_empty());
+    }
+
+    #[test]
+    fn region_bounds_need_all_four_keys() {
+        let mut meta: HashMap<String, String> = [
+            ("min_lat", "43.72"),
+            ("max_lat", "43.76"),
+            ("min_lng", "7.40"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(RegionBounds::from_meta(&meta), None);
+
+        meta.insert("max_lng".to_string(), "7.44".to_string());
+        let bounds = RegionBounds::from_meta(&meta).unwrap();
+        assert!(bounds.contains(43.74, 7.42));
+        assert!(!bounds.contains(43.74, 7.45));
+    }
+
     #[test]
     fn scan_regions_missing_dir() {
         let dir = PathBuf::from("/tmp/easyroute_nonexistent_dir_12345");
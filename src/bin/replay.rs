@@ -0,0 +1,209 @@
+use easyroute::config::Config;
+use easyroute::db::PgPoiRepository;
+use easyroute::evaluation::replay::{
+    format_replay_report, load_corpus, ReplayOutcome, ReplayReport,
+};
+use easyroute::models::route::{LoopRouteRequest, RouteResponse};
+use easyroute::services::mapbox::{AuthMode, MapboxClient};
+use easyroute::services::poi_service::PoiService;
+use easyroute::services::request_log::LoggedRoute;
+use easyroute::services::route_generator::RouteGenerator;
+use easyroute::services::snapping_service::SnappingService;
+use futures::stream::{self, StreamExt};
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+const DEFAULT_CONCURRENCY: usize = 1;
+const HTTP_TIMEOUT_SECS: u64 = 60;
+
+fn print_help() {
+    eprintln!(
+        "\
+Usage: replay --corpus=PATH (--target=URL | --in-process) [OPTIONS]
+
+Replays a request log corpus (NDJSON written with REQUEST_LOG_PATH) and compares
+latency and route metric distributions with the recorded originals.
+
+Targets:
+  --target=URL          Live server base URL (e.g. http://localhost:3000)
+  --in-process          Generator built from the environment config (DATABASE_URL,
+                        ROUTE_*). Point MAPBOX_BASE_URL at a mock directions
+                        server to replay without Mapbox quota
+
+Options:
+  --limit=N             Replay only the first N records
+  --concurrency=N       Requests in flight at once (default: 1)
+  --json                Output the report as JSON
+  --help                Show this help message"
+    );
+}
+
+enum ReplayTarget {
+    Http {
+        client: reqwest::Client,
+        url: String,
+    },
+    InProcess(Box<RouteGenerator>),
+}
+
+impl ReplayTarget {
+    async fn replay(&self, request: &LoopRouteRequest) -> ReplayOutcome {
+        let started = Instant::now();
+        let result = match self {
+            ReplayTarget::Http { client, url } => replay_http(client, url, request).await,
+            ReplayTarget::InProcess(generator) => replay_in_process(generator, request).await,
+        };
+        let latency_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok(routes) => ReplayOutcome {
+                latency_ms,
+                routes,
+                error: None,
+            },
+            Err(e) => ReplayOutcome {
+                latency_ms,
+                routes: vec![],
+                error: Some(e),
+            },
+        }
+    }
+}
+
+async fn replay_http(
+    client: &reqwest::Client,
+    url: &str,
+    request: &LoopRouteRequest,
+) -> Result<Vec<LoggedRoute>, String> {
+    let response = client
+        .post(url)
+        .json(request)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let body: RouteResponse = response.json().await.map_err(|e| e.to_string())?;
+    Ok(body.routes.iter().map(LoggedRoute::from).collect())
+}
+
+async fn replay_in_process(
+    generator: &RouteGenerator,
+    request: &LoopRouteRequest,
+) -> Result<Vec<LoggedRoute>, String> {
+    request.validate()?;
+    let distance_km = request
+        .target_distance_km()
+        .ok_or_else(|| "distance_km is required".to_string())?;
+    let routes = generator
+        .generate_loop_route(
+            request.start_point,
+            distance_km,
+            request.distance_tolerance,
+            &request.mode,
+            &request.preferences,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(routes.iter().map(LoggedRoute::from).collect())
+}
+
+async fn in_process_generator() -> Result<RouteGenerator, Box<dyn std::error::Error>> {
+    let config = Config::from_env().map_err(|e| format!("Config error: {}", e))?;
+    let db_pool =
+        easyroute::db::create_pool(&config.database_url, config.database_pool_size).await?;
+
+    let poi_repo: Arc<dyn easyroute::db::PoiRepository> = Arc::new(PgPoiRepository::new(db_pool));
+    let mapbox_client = if let Some(ref base_url) = config.mapbox_base_url {
+        MapboxClient::with_config(
+            config.mapbox_api_key.clone(),
+            base_url.clone(),
+            AuthMode::BearerHeader,
+        )
+    } else {
+        MapboxClient::new(config.mapbox_api_key.clone())
+    };
+    Ok(RouteGenerator::new(
+        mapbox_client,
+        PoiService::new(poi_repo.clone()),
+        SnappingService::new(poi_repo),
+        config.snap_radius_m,
+        config.route_generator,
+    ))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "easyroute=warn".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|a| a == "--help") {
+        print_help();
+        return Ok(());
+    }
+
+    let Some(corpus_path) = args.iter().find_map(|a| a.strip_prefix("--corpus=")) else {
+        print_help();
+        std::process::exit(2);
+    };
+    let limit: Option<usize> = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--limit="))
+        .and_then(|s| s.parse().ok());
+    let concurrency: usize = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--concurrency="))
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_CONCURRENCY)
+        .max(1);
+    let json_output = args.iter().any(|a| a == "--json");
+
+    let target = if let Some(base_url) = args.iter().find_map(|a| a.strip_prefix("--target=")) {
+        ReplayTarget::Http {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+                .build()?,
+            url: format!("{}/api/v1/routes/loop", base_url.trim_end_matches('/')),
+        }
+    } else if args.iter().any(|a| a == "--in-process") {
+        ReplayTarget::InProcess(Box::new(in_process_generator().await?))
+    } else {
+        print_help();
+        std::process::exit(2);
+    };
+
+    let (mut records, skipped) = load_corpus(&PathBuf::from(corpus_path))?;
+    if let Some(limit) = limit {
+        records.truncate(limit);
+    }
+    eprintln!(
+        "Replaying {} requests ({} malformed lines skipped, concurrency {})...",
+        records.len(),
+        skipped,
+        concurrency
+    );
+
+    let outcomes: Vec<ReplayOutcome> = stream::iter(records.iter())
+        .map(|record| target.replay(&record.request))
+        .buffered(concurrency)
+        .collect()
+        .await;
+
+    let report = ReplayReport::new(&records, &outcomes);
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", format_replay_report(&report));
+    }
+
+    Ok(())
+}
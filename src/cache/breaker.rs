@@ -0,0 +1,103 @@
+//! Circuit breaker in front of Redis. After a run of failed commands the
+//! cache is disabled: calls skip Redis (misses, dropped writes) instead of
+//! each waiting on it and logging a warning, while a background probe pings
+//! Redis until it answers and re-enables the cache.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub struct CircuitBreaker {
+    /// Consecutive failures that disable the cache
+    threshold: u32,
+    consecutive_failures: AtomicU32,
+    /// When the cache was disabled, while it is
+    disabled_since: Mutex<Option<Instant>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32) -> Self {
+        CircuitBreaker {
+            threshold,
+            consecutive_failures: AtomicU32::new(0),
+            disabled_since: Mutex::new(None),
+        }
+    }
+
+    /// Whether calls should skip Redis
+    pub fn is_open(&self) -> bool {
+        self.disabled_since.lock().unwrap().is_some()
+    }
+
+    /// How long the cache has been disabled, if it is
+    pub fn disabled_for(&self) -> Option<Duration> {
+        self.disabled_since.lock().unwrap().map(|t| t.elapsed())
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures.load(Ordering::Relaxed)
+    }
+
+    /// A command (or probe) succeeded: re-enable the cache if it was disabled
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        if let Some(since) = self.disabled_since.lock().unwrap().take() {
+            tracing::info!(
+                "Redis is back, cache re-enabled after {}s",
+                since.elapsed().as_secs()
+            );
+        }
+    }
+
+    /// A command failed: disable the cache once `threshold` fail in a row.
+    /// Returns true when this failure disabled it.
+    pub fn record_failure(&self, error: &dyn std::fmt::Display) -> bool {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < self.threshold {
+            return false;
+        }
+        let mut disabled_since = self.disabled_since.lock().unwrap();
+        if disabled_since.is_some() {
+            return false;
+        }
+        *disabled_since = Some(Instant::now());
+        tracing::warn!(
+            "Redis failed {} times in a row, cache disabled until it answers again: {}",
+            failures,
+            error
+        );
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_and_closes_on_success() {
+        let breaker = CircuitBreaker::new(3);
+        assert!(!breaker.record_failure(&"timeout"));
+        assert!(!breaker.record_failure(&"timeout"));
+        assert!(!breaker.is_open());
+
+        assert!(breaker.record_failure(&"timeout"));
+        assert!(breaker.is_open());
+        // Already disabled
+        assert!(!breaker.record_failure(&"timeout"));
+        assert!(breaker.disabled_for().is_some());
+
+        breaker.record_success();
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn success_resets_the_failure_run() {
+        let breaker = CircuitBreaker::new(2);
+        breaker.record_failure(&"timeout");
+        breaker.record_success();
+        breaker.record_failure(&"timeout");
+        assert!(!breaker.is_open());
+    }
+}
@@ -0,0 +1,251 @@
+//! Wire format of cached routes in Redis.
+//!
+//! Entries are MessagePack, optionally compressed, behind a small envelope:
+//! the `ER` magic, a format version byte, the route schema version and the
+//! compression used. Paths with thousands of points shrink several-fold
+//! compared to JSON. Entries decode whatever compression they were written
+//! with, so changing `CACHE_COMPRESSION` keeps existing entries readable.
+//! Entries written before the envelope existed are plain JSON arrays and
+//! still decode.
+
+use crate::constants::CACHE_ZSTD_LEVEL;
+use crate::error::{AppError, Result};
+use crate::models::Route;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::fmt;
+use std::io::{Read, Write};
+
+const MAGIC: &[u8; 2] = b"ER";
+/// Bump when the envelope or payload encoding changes
+const VERSION: u8 = 3;
+/// Bump when `Route` changes in a way older entries can't be read as, so
+/// they are dropped instead of failing to decode until they expire
+pub const SCHEMA_VERSION: u8 = 2;
+
+/// How entry payloads are compressed. Env: `CACHE_COMPRESSION`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheCompression {
+    None,
+    #[default]
+    Zstd,
+    Gzip,
+}
+
+impl CacheCompression {
+    fn tag(self) -> u8 {
+        match self {
+            CacheCompression::None => 0,
+            CacheCompression::Zstd => 1,
+            CacheCompression::Gzip => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(CacheCompression::None),
+            1 => Some(CacheCompression::Zstd),
+            2 => Some(CacheCompression::Gzip),
+            _ => None,
+        }
+    }
+
+    fn compress(self, payload: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        match self {
+            CacheCompression::None => Ok(payload),
+            CacheCompression::Zstd => zstd::encode_all(payload.as_slice(), CACHE_ZSTD_LEVEL),
+            CacheCompression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&payload)?;
+                encoder.finish()
+            }
+        }
+    }
+
+    fn decompress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            CacheCompression::None => Ok(data.to_vec()),
+            CacheCompression::Zstd => zstd::decode_all(data),
+            CacheCompression::Gzip => {
+                let mut payload = Vec::new();
+                GzDecoder::new(data).read_to_end(&mut payload)?;
+                Ok(payload)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for CacheCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(CacheCompression::None),
+            "zstd" => Ok(CacheCompression::Zstd),
+            "gzip" => Ok(CacheCompression::Gzip),
+            _ => Err(format!(
+                "Invalid cache compression: {}. Use 'zstd', 'gzip' or 'none'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CacheCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CacheCompression::None => "none",
+            CacheCompression::Zstd => "zstd",
+            CacheCompression::Gzip => "gzip",
+        })
+    }
+}
+
+/// Why a cache entry couldn't be read
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// Written under an older format or schema version
+    Stale,
+    /// Written by a newer release, e.g. mid rolling deploy
+    Newer,
+    /// Unreadable although its versions match
+    Corrupt(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Stale => f.write_str("stale format or schema version"),
+            DecodeError::Newer => f.write_str("newer format or schema version"),
+            DecodeError::Corrupt(e) => f.write_str(e),
+        }
+    }
+}
+
+/// `routes` as a versioned entry, compressed with `compression`
+pub fn encode(routes: &[Route], compression: CacheCompression) -> Result<Vec<u8>> {
+    let payload = rmp_serde::to_vec_named(routes)
+        .map_err(|e| AppError::Cache(format!("Failed to encode routes: {}", e)))?;
+    let compressed = compression
+        .compress(payload)
+        .map_err(|e| AppError::Cache(format!("Failed to compress routes: {}", e)))?;
+
+    let mut entry = Vec::with_capacity(MAGIC.len() + 3 + compressed.len());
+    entry.extend_from_slice(MAGIC);
+    entry.push(VERSION);
+    entry.push(SCHEMA_VERSION);
+    entry.push(compression.tag());
+    entry.extend_from_slice(&compressed);
+    Ok(entry)
+}
+
+/// Routes from an entry written by [`encode`], or a legacy JSON entry
+pub fn decode(entry: &[u8]) -> std::result::Result<Vec<Route>, DecodeError> {
+    match entry.strip_prefix(MAGIC) {
+        Some([VERSION, SCHEMA_VERSION, tag, compressed @ ..]) => {
+            let compression = CacheCompression::from_tag(*tag).ok_or_else(|| {
+                DecodeError::Corrupt(format!("Unknown cache compression tag {}", tag))
+            })?;
+            let payload = compression
+                .decompress(compressed)
+                .map_err(|e| DecodeError::Corrupt(format!("Failed to decompress routes: {}", e)))?;
+            rmp_serde::from_slice(&payload)
+                .map_err(|e| DecodeError::Corrupt(format!("Failed to decode routes: {}", e)))
+        }
+        Some([format, schema, ..]) if (*format, *schema) > (VERSION, SCHEMA_VERSION) => {
+            Err(DecodeError::Newer)
+        }
+        Some(_) => Err(DecodeError::Stale),
+        None => serde_json::from_slice(entry)
+            .map_err(|e| DecodeError::Corrupt(format!("Failed to decode legacy routes: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Coordinates, Poi, PoiCategory};
+
+    fn make_test_route() -> Route {
+        let path = (0..2_000)
+            .map(|i| Coordinates::new(48.85 + i as f64 * 1e-5, 2.35 + i as f64 * 1e-5).unwrap())
+            .collect();
+        Route::builder()
+            .distance_km(5.0)
+            .duration_minutes(60)
+            .path(path)
+            .waypoint_at(
+                Poi::builder()
+                    .name("Jardin")
+                    .category(PoiCategory::Park)
+                    .coordinates(Coordinates::new(48.86, 2.36).unwrap())
+                    .build(),
+                2.5,
+            )
+            .score(7.5)
+            .build()
+    }
+
+    const COMPRESSIONS: [CacheCompression; 3] = [
+        CacheCompression::None,
+        CacheCompression::Zstd,
+        CacheCompression::Gzip,
+    ];
+
+    #[test]
+    fn test_roundtrip() {
+        let routes = vec![make_test_route()];
+        for compression in COMPRESSIONS {
+            let decoded = decode(&encode(&routes, compression).unwrap()).unwrap();
+
+            assert_eq!(decoded.len(), 1, "{compression}");
+            assert_eq!(decoded[0].id, routes[0].id);
+            assert_eq!(decoded[0].path, routes[0].path);
+            assert_eq!(decoded[0].pois[0].poi.name, "Jardin");
+            assert_eq!(decoded[0].pois[0].distance_from_start_km, 2.5);
+        }
+    }
+
+    #[test]
+    fn test_smaller_than_json() {
+        let routes = vec![make_test_route()];
+        let json = serde_json::to_vec(&routes).unwrap().len();
+        let size = |compression| encode(&routes, compression).unwrap().len();
+
+        assert!(size(CacheCompression::None) < json);
+        assert!(size(CacheCompression::Zstd) * 3 < json);
+        assert!(size(CacheCompression::Gzip) * 3 < json);
+    }
+
+    #[test]
+    fn test_compression_names() {
+        for compression in COMPRESSIONS {
+            assert_eq!(compression.to_string().parse(), Ok(compression));
+        }
+        assert!("brotli".parse::<CacheCompression>().is_err());
+    }
+
+    #[test]
+    fn test_legacy_json_entries_decode() {
+        let routes = vec![make_test_route()];
+        let json = serde_json::to_vec(&routes).unwrap();
+        assert_eq!(decode(&json).unwrap()[0].id, routes[0].id);
+    }
+
+    #[test]
+    fn test_other_versions_are_stale_or_newer() {
+        let entry = encode(&[make_test_route()], CacheCompression::Zstd).unwrap();
+        for offset in [MAGIC.len(), MAGIC.len() + 1] {
+            let mut older = entry.clone();
+            older[offset] -= 1;
+            assert_eq!(decode(&older).unwrap_err(), DecodeError::Stale);
+            let mut newer = entry.clone();
+            newer[offset] += 1;
+            assert_eq!(decode(&newer).unwrap_err(), DecodeError::Newer);
+        }
+        assert!(matches!(
+            decode(b"garbage").unwrap_err(),
+            DecodeError::Corrupt(_)
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+//! Stable hashing for cache keys.
+//!
+//! Cache keys outlive processes: Redis entries are shared between instances
+//! and across deploys, so a key must hash the same on every platform and
+//! toolchain. `std`'s `DefaultHasher` promises neither (its algorithm may
+//! change between Rust releases, and `Hash` writes integers in native byte
+//! order and lengths as `usize`), and a silent change would make every
+//! cached route unreachable after an upgrade.
+//!
+//! [`KeyHasher`] is XXH3-64 with a fixed seed. Values are fed as explicit
+//! little-endian bytes, and every hash starts with [`KEY_HASH_VERSION`].
+//! The golden key tests in [`crate::cache`] pin the resulting keys.
+
+use xxhash_rust::xxh3::Xxh3;
+
+/// Bump when what goes into a key changes on purpose, so old and new keys
+/// can't collide; existing entries then expire unread
+pub const KEY_HASH_VERSION: u8 = 1;
+/// XXH3 seed for every cache key. Changing it changes every key.
+const KEY_HASH_SEED: u64 = 0x6561_7379_726f_7574; // "easyrout"
+
+/// XXH3-64 over primitives, written without allocating
+#[derive(Clone)]
+pub struct KeyHasher(Xxh3);
+
+impl Default for KeyHasher {
+    fn default() -> Self {
+        KeyHasher::new()
+    }
+}
+
+impl KeyHasher {
+    pub fn new() -> Self {
+        let mut hasher = KeyHasher(Xxh3::with_seed(KEY_HASH_SEED));
+        hasher.write_bytes(&[KEY_HASH_VERSION]);
+        hasher
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_bytes(&[value as u8]);
+    }
+
+    /// Length-prefixed, so `("ab", "c")` and `("a", "bc")` differ
+    pub fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write_bytes(value.as_bytes());
+    }
+
+    /// Tagged, so `None` and `Some(0)` differ
+    pub fn write_opt_i64(&mut self, value: Option<i64>) {
+        match value {
+            Some(v) => {
+                self.write_bool(true);
+                self.write_i64(v);
+            }
+            None => self.write_bool(false),
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0.digest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_seeded_xxh3_over_the_version_and_input() {
+        let mut hasher = KeyHasher::new();
+        hasher.write_bytes(b"foobar");
+        let expected = xxhash_rust::xxh3::xxh3_64_with_seed(
+            &[&[KEY_HASH_VERSION][..], b"foobar"].concat(),
+            KEY_HASH_SEED,
+        );
+        assert_eq!(hasher.finish(), expected);
+    }
+
+    #[test]
+    fn tags_and_prefixes_keep_values_apart() {
+        let strs = |a: &str, b: &str| {
+            let mut hasher = KeyHasher::new();
+            hasher.write_str(a);
+            hasher.write_str(b);
+            hasher.finish()
+        };
+        assert_ne!(strs("ab", "c"), strs("a", "bc"));
+
+        let opt = |value: Option<i64>| {
+            let mut hasher = KeyHasher::new();
+            hasher.write_opt_i64(value);
+            hasher.finish()
+        };
+        assert_ne!(opt(None), opt(Some(0)));
+    }
+}
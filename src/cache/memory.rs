@@ -1,44 +1,109 @@
-use crate::cache::{CacheStats, RouteCache};
+use crate::cache::{CacheFilter, CacheKeyPrecision, CacheStats, CacheTtl, RouteCache};
 use crate::models::Route;
 use async_trait::async_trait;
 use moka::future::Cache;
+use moka::Expiry;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-/// In-memory cache backed by moka with TTL and bounded capacity.
+#[derive(Clone)]
+struct Entry {
+    routes: Arc<Vec<Route>>,
+    ttl: Duration,
+    /// Checked on read, on the tokio clock, so expiry follows paused time
+    /// in tests; moka's own expiry reclaims the memory
+    expires_at: tokio::time::Instant,
+}
+
+/// Expires each entry after its own TTL
+struct EntryTtl;
+
+impl Expiry<String, Entry> for EntryTtl {
+    fn expire_after_create(&self, _key: &String, entry: &Entry, _at: Instant) -> Option<Duration> {
+        Some(entry.ttl)
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        entry: &Entry,
+        _at: Instant,
+        _remaining: Option<Duration>,
+    ) -> Option<Duration> {
+        Some(entry.ttl)
+    }
+}
+
+/// In-memory cache backed by moka with per-entry TTL and bounded capacity.
 /// All methods are `&self` — no locking needed.
 pub struct MemoryCacheService {
-    routes: Cache<String, Arc<Vec<Route>>>,
+    routes: Cache<String, Entry>,
+    route_ttl_seconds: u64,
     hits: AtomicU64,
     misses: AtomicU64,
+    key_precision: CacheKeyPrecision,
 }
 
 impl MemoryCacheService {
     pub fn new(route_ttl_seconds: u64, max_capacity: u64) -> Self {
         let routes = Cache::builder()
-            .time_to_live(Duration::from_secs(route_ttl_seconds))
+            .expire_after(EntryTtl)
             .max_capacity(max_capacity)
             .build();
 
         MemoryCacheService {
             routes,
+            route_ttl_seconds,
             hits: AtomicU64::new(0),
             misses: AtomicU64::new(0),
+            key_precision: CacheKeyPrecision::DEFAULT,
         }
     }
+
+    pub fn with_key_precision(mut self, key_precision: CacheKeyPrecision) -> Self {
+        self.key_precision = key_precision;
+        self
+    }
+
+    /// The TTL `hint` gives entries here
+    pub fn ttl_for(&self, hint: CacheTtl) -> Duration {
+        Duration::from_secs(hint.apply(self.route_ttl_seconds))
+    }
+
+    /// Cache `routes` for exactly `ttl`, e.g. no longer than the copy they
+    /// were read from has left
+    pub async fn cache_routes_for(&self, key: &str, routes: &[Route], ttl: Duration) {
+        let entry = Entry {
+            routes: Arc::new(routes.to_vec()),
+            ttl,
+            expires_at: tokio::time::Instant::now() + ttl,
+        };
+        self.routes.insert(key.to_string(), entry).await;
+        tracing::debug!("Memory cached {} routes: {}", routes.len(), key);
+    }
 }
 
 #[async_trait]
 impl RouteCache for MemoryCacheService {
     async fn get_cached_routes(&self, key: &str) -> Option<Vec<Route>> {
+        self.get_cached_routes_with_ttl(key)
+            .await
+            .map(|(routes, _)| routes)
+    }
+
+    async fn get_cached_routes_with_ttl(
+        &self,
+        key: &str,
+    ) -> Option<(Vec<Route>, Option<Duration>)> {
+        let now = tokio::time::Instant::now();
         match self.routes.get(key).await {
-            Some(arc_routes) => {
+            Some(entry) if entry.expires_at > now => {
                 self.hits.fetch_add(1, Ordering::Relaxed);
                 tracing::debug!("Memory cache hit for route: {}", key);
-                Some((*arc_routes).clone())
+                Some(((*entry.routes).clone(), Some(entry.expires_at - now)))
             }
-            None => {
+            _ => {
                 self.misses.fetch_add(1, Ordering::Relaxed);
                 tracing::debug!("Memory cache miss for route: {}", key);
                 None
@@ -46,10 +111,8 @@ impl RouteCache for MemoryCacheService {
         }
     }
 
-    async fn cache_routes(&self, key: &str, routes: &[Route]) {
-        let arc_routes = Arc::new(routes.to_vec());
-        self.routes.insert(key.to_string(), arc_routes).await;
-        tracing::debug!("Memory cached {} routes: {}", routes.len(), key);
+    async fn cache_routes(&self, key: &str, routes: &[Route], ttl: CacheTtl) {
+        self.cache_routes_for(key, routes, self.ttl_for(ttl)).await;
     }
 
     async fn get_stats(&self) -> CacheStats {
@@ -66,6 +129,9 @@ impl RouteCache for MemoryCacheService {
             misses,
             hit_rate,
             connected: true,
+            stale_entries: 0,
+            decode_failures: 0,
+            disabled: false,
         }
     }
 
@@ -76,25 +142,35 @@ impl RouteCache for MemoryCacheService {
     fn backend_name(&self) -> &'static str {
         "memory"
     }
+
+    fn key_precision(&self) -> CacheKeyPrecision {
+        self.key_precision
+    }
+
+    async fn invalidate(&self, filter: &CacheFilter) -> u64 {
+        let keys: Vec<_> = self
+            .routes
+            .iter()
+            .filter(|(key, _)| filter.matches(key))
+            .map(|(key, _)| key)
+            .collect();
+        for key in &keys {
+            self.routes.invalidate(key.as_str()).await;
+        }
+        keys.len() as u64
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use uuid::Uuid;
 
     fn make_test_route(distance_km: f64) -> Route {
-        Route {
-            id: Uuid::new_v4(),
-            distance_km,
-            estimated_duration_minutes: 30,
-            elevation_gain_m: None,
-            path: vec![],
-            pois: vec![],
-            snapped_pois: vec![],
-            score: 7.0,
-            metrics: None,
-        }
+        Route::builder()
+            .distance_km(distance_km)
+            .duration_minutes(30)
+            .score(7.0)
+            .build()
     }
 
     #[tokio::test]
@@ -108,7 +184,9 @@ mod tests {
         let cache = MemoryCacheService::new(3600, 100);
         let routes = vec![make_test_route(5.0), make_test_route(3.0)];
 
-        cache.cache_routes("key1", &routes).await;
+        cache
+            .cache_routes("key1", &routes, CacheTtl::Standard)
+            .await;
         let cached = cache.get_cached_routes("key1").await.unwrap();
 
         assert_eq!(cached.len(), 2);
@@ -120,7 +198,9 @@ mod tests {
     async fn stats_tracking() {
         let cache = MemoryCacheService::new(3600, 100);
         let routes = vec![make_test_route(5.0)];
-        cache.cache_routes("key1", &routes).await;
+        cache
+            .cache_routes("key1", &routes, CacheTtl::Standard)
+            .await;
 
         // 1 miss
         cache.get_cached_routes("missing").await;
@@ -147,15 +227,56 @@ mod tests {
     }
 
     #[tokio::test]
+    async fn invalidate_by_prefix() {
+        let cache = MemoryCacheService::new(3600, 100);
+        let routes = vec![make_test_route(5.0)];
+        cache
+            .cache_routes("poi:region:48.86:2.35:1", &routes, CacheTtl::Standard)
+            .await;
+        cache
+            .cache_routes("route:loop:48.857:2.352:2", &routes, CacheTtl::Standard)
+            .await;
+
+        let filter = CacheFilter {
+            prefix: Some("poi:region".to_string()),
+            region: None,
+        };
+        assert_eq!(cache.invalidate(&filter).await, 1);
+        assert!(cache
+            .get_cached_routes("poi:region:48.86:2.35:1")
+            .await
+            .is_none());
+        assert!(cache
+            .get_cached_routes("route:loop:48.857:2.352:2")
+            .await
+            .is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
     async fn ttl_expiry() {
         let cache = MemoryCacheService::new(1, 100); // 1 second TTL
         let routes = vec![make_test_route(5.0)];
-        cache.cache_routes("key1", &routes).await;
+        cache
+            .cache_routes("key1", &routes, CacheTtl::Standard)
+            .await;
 
         assert!(cache.get_cached_routes("key1").await.is_some());
 
-        tokio::time::sleep(Duration::from_secs(2)).await;
+        tokio::time::advance(Duration::from_secs(2)).await;
 
         assert!(cache.get_cached_routes("key1").await.is_none());
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn ttl_hint_scales_expiry() {
+        let cache = MemoryCacheService::new(4, 100);
+        let routes = vec![make_test_route(5.0)];
+        cache.cache_routes("short", &routes, CacheTtl::Short).await;
+        cache.cache_routes("long", &routes, CacheTtl::Long).await;
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+
+        assert!(cache.get_cached_routes("short").await.is_none());
+        assert!(cache.get_cached_routes("long").await.is_some());
+    }
 }
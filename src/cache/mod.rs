@@ -1,100 +1,515 @@
+pub mod breaker;
+pub(crate) mod codec;
+pub(crate) mod key_hash;
 pub mod memory;
-pub mod redis;
+pub(crate) mod redis;
+pub(crate) mod tiered;
 
+pub use key_hash::KeyHasher;
 pub use memory::MemoryCacheService;
 pub use redis::RedisCacheService;
-
-use crate::models::{Coordinates, PoiCategory, Route};
+pub use tiered::TieredCacheService;
+
+use crate::constants::{
+    CACHE_TTL_GOLD_MULTIPLIER, CACHE_TTL_LOW_QUALITY_MULTIPLIER, DEFAULT_CACHE_COORD_PRECISION,
+    DEFAULT_CACHE_DISTANCE_BUCKET_KM,
+};
+use crate::models::{
+    BoundingBox, Coordinates, CostingOptions, Departure, MustInclude, PoiCategory, QualityTier,
+    Route, SurfacePreference,
+};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::fmt::Write;
+use std::time::Duration;
 
 /// Trait for route caching backends. All methods take `&self` — no locking needed.
 #[async_trait]
 pub trait RouteCache: Send + Sync {
     async fn get_cached_routes(&self, key: &str) -> Option<Vec<Route>>;
-    async fn cache_routes(&self, key: &str, routes: &[Route]);
+
+    /// Like [`get_cached_routes`](Self::get_cached_routes), with how long the
+    /// entry has left to live when the backend knows
+    async fn get_cached_routes_with_ttl(
+        &self,
+        key: &str,
+    ) -> Option<(Vec<Route>, Option<Duration>)> {
+        self.get_cached_routes(key)
+            .await
+            .map(|routes| (routes, None))
+    }
+    async fn cache_routes(&self, key: &str, routes: &[Route], ttl: CacheTtl);
     async fn get_stats(&self) -> CacheStats;
     async fn health_check(&self) -> bool;
     fn backend_name(&self) -> &'static str;
+
+    /// Granularity of the loop route keys this cache is used with
+    fn key_precision(&self) -> CacheKeyPrecision {
+        CacheKeyPrecision::default()
+    }
+
+    /// Drop every entry `filter` matches; returns how many were dropped
+    async fn invalidate(&self, filter: &CacheFilter) -> u64;
+
+    /// Drop only the entries this instance holds in process, for an
+    /// invalidation another instance already applied to the shared tier
+    async fn invalidate_local(&self, filter: &CacheFilter) -> u64 {
+        self.invalidate(filter).await
+    }
+
+    /// Drop loop routes and POI regions bucketed inside `bbox`, e.g. after
+    /// re-importing OSM data there
+    async fn invalidate_region(&self, bbox: BoundingBox) -> u64 {
+        self.invalidate(&CacheFilter {
+            region: Some(bbox),
+            ..Default::default()
+        })
+        .await
+    }
+}
+
+/// How coarsely loop route keys bucket requests: coarser keys share
+/// entries between more nearby requests (rural), finer ones keep results
+/// local (dense cities)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CacheKeyPrecision {
+    /// Decimals the start point is rounded to (3 ≈ 100m)
+    pub coord_decimals: u32,
+    /// Width of the requested-distance buckets
+    pub distance_bucket_km: f64,
+}
+
+impl CacheKeyPrecision {
+    pub const DEFAULT: CacheKeyPrecision = CacheKeyPrecision {
+        coord_decimals: DEFAULT_CACHE_COORD_PRECISION,
+        distance_bucket_km: DEFAULT_CACHE_DISTANCE_BUCKET_KM,
+    };
+}
+
+impl Default for CacheKeyPrecision {
+    fn default() -> Self {
+        CacheKeyPrecision::DEFAULT
+    }
+}
+
+/// Namespace of loop route keys: `route:loop:<lat>:<lng>:<hash>`
+pub const LOOP_ROUTE_NAMESPACE: &str = "route:loop";
+/// Namespace of POI region keys: `poi:region:<lat>:<lng>:<hash>`
+pub const POI_REGION_NAMESPACE: &str = "poi:region";
+
+/// Which cache entries to invalidate. Every condition given must hold, so
+/// an empty filter matches everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheFilter {
+    /// Key prefix such as `poi:region`, matched with or without a tenant scope
+    pub prefix: Option<String>,
+    /// Entries whose key cell lies inside this box
+    pub region: Option<BoundingBox>,
+}
+
+impl CacheFilter {
+    pub fn matches(&self, key: &str) -> bool {
+        let prefix_matches = self
+            .prefix
+            .as_deref()
+            .map_or(true, |p| key.starts_with(p) || unscoped(key).starts_with(p));
+        let region_matches = self.region.map_or(true, |bbox| {
+            key_cell(key).is_some_and(|cell| {
+                bbox.split_at_antimeridian().iter().any(|part| {
+                    (part.min_lat..=part.max_lat).contains(&cell.lat)
+                        && (part.min_lng..=part.max_lng).contains(&cell.lng)
+                })
+            })
+        });
+        prefix_matches && region_matches
+    }
+}
+
+/// `key` without its `tenant:<id>:` scope
+fn unscoped(key: &str) -> &str {
+    key.strip_prefix("tenant:")
+        .and_then(|rest| rest.split_once(':'))
+        .map_or(key, |(_, key)| key)
+}
+
+/// The rounded cell a loop route or POI region key is bucketed under
+fn key_cell(key: &str) -> Option<Coordinates> {
+    let key = unscoped(key);
+    let rest = [LOOP_ROUTE_NAMESPACE, POI_REGION_NAMESPACE]
+        .iter()
+        .find_map(|ns| key.strip_prefix(ns)?.strip_prefix(':'))?;
+    let mut parts = rest.split(':');
+    let lat = parts.next()?.parse().ok()?;
+    let lng = parts.next()?.parse().ok()?;
+    Coordinates::new(lat, lng).ok()
+}
+
+/// `point` rounded to `1 / scale` degrees, as integers. Longitudes 180 and
+/// -180 are the same meridian, and at a pole every longitude is the same
+/// place, so those share a cell.
+fn rounded_cell(point: &Coordinates, scale: f64) -> (i64, i64) {
+    let lat = (point.lat * scale).round() as i64;
+    let mut lng = (point.lng * scale).round() as i64;
+    let antimeridian = (180.0 * scale).round() as i64;
+    if lng == antimeridian {
+        lng = -antimeridian;
+    }
+    if lat.abs() == (90.0 * scale).round() as i64 {
+        lng = 0;
+    }
+    (lat, lng)
 }
 
 /// Generate a cache key for loop routes.
-/// Key includes: coordinates (3 decimal precision), distance (0.5km buckets), mode, preferences.
+/// Key includes: coordinates and distance bucketed by `precision`, mode, preferences.
+/// The rounded start stays readable in the key so regions can be invalidated.
+/// Everything else is hashed with [`KeyHasher`], so keys are stable across
+/// platforms and toolchains, and the returned key is the only allocation.
 pub fn loop_route_cache_key(
     start: &Coordinates,
     distance_km: f64,
     mode: &str,
     preferences: &RoutePreferencesHash,
+    precision: &CacheKeyPrecision,
 ) -> String {
-    let mut hasher = DefaultHasher::new();
-
-    // Round coordinates to 3 decimal places (~100m precision)
-    let lat = (start.lat * 1000.0).round() as i64;
-    let lng = (start.lng * 1000.0).round() as i64;
-
-    // Round distance to 0.5km buckets
-    let distance_bucket = (distance_km * 2.0).round() as i64;
-
-    lat.hash(&mut hasher);
-    lng.hash(&mut hasher);
-    distance_bucket.hash(&mut hasher);
-    mode.hash(&mut hasher);
-    preferences.hash(&mut hasher);
-
-    format!("route:loop:{:x}", hasher.finish())
+    let mut hasher = KeyHasher::new();
+
+    // Round coordinates to `coord_decimals` places
+    let scale = 10f64.powi(precision.coord_decimals as i32);
+    let (lat, lng) = rounded_cell(start, scale);
+
+    let distance_bucket = (distance_km / precision.distance_bucket_km).round() as i64;
+
+    hasher.write_i64(lat);
+    hasher.write_i64(lng);
+    hasher.write_i64(distance_bucket);
+    hasher.write_u64(precision.distance_bucket_km.to_bits());
+    hasher.write_str(mode);
+    preferences.write_to(&mut hasher);
+
+    let decimals = precision.coord_decimals as usize;
+    let mut key = String::with_capacity(LOOP_ROUTE_NAMESPACE.len() + 2 * (decimals + 6) + 19);
+    let _ = write!(
+        key,
+        "{}:{:.*}:{:.*}:{:x}",
+        LOOP_ROUTE_NAMESPACE,
+        decimals,
+        lat as f64 / scale,
+        decimals,
+        lng as f64 / scale,
+        hasher.finish()
+    );
+    key
 }
 
 /// Generate a cache key for POI region queries.
 /// Key includes: center coordinates (2 decimal precision), radius (1km buckets), categories.
+/// Like loop route keys, the rounded center stays readable and the rest is
+/// hashed with [`KeyHasher`].
 pub fn poi_region_cache_key(
     center: &Coordinates,
     radius_km: f64,
     categories: Option<&[PoiCategory]>,
 ) -> String {
-    let mut hasher = DefaultHasher::new();
+    let mut hasher = KeyHasher::new();
 
     // Round coordinates to 2 decimal places (~1km precision)
-    let lat = (center.lat * 100.0).round() as i64;
-    let lng = (center.lng * 100.0).round() as i64;
+    let (lat, lng) = rounded_cell(center, 100.0);
 
     // Round radius to 1km buckets
     let radius_bucket = radius_km.ceil() as i64;
 
-    lat.hash(&mut hasher);
-    lng.hash(&mut hasher);
-    radius_bucket.hash(&mut hasher);
-
-    // Hash categories if present
-    if let Some(cats) = categories {
-        let mut cat_strs: Vec<String> = cats.iter().map(|c| c.to_string()).collect();
-        cat_strs.sort(); // Ensure consistent ordering
-        cat_strs.hash(&mut hasher);
-    }
-
-    format!("poi:region:{:x}", hasher.finish())
+    hasher.write_i64(lat);
+    hasher.write_i64(lng);
+    hasher.write_i64(radius_bucket);
+    // The set of categories, whatever order they came in; no filter and an
+    // empty filter are different queries
+    hasher.write_bool(categories.is_some());
+    hasher.write_u64(category_mask(categories));
+
+    format!(
+        "{}:{:.2}:{:.2}:{:x}",
+        POI_REGION_NAMESPACE,
+        lat as f64 / 100.0,
+        lng as f64 / 100.0,
+        hasher.finish()
+    )
 }
 
-/// Hash-friendly representation of route preferences for cache key generation
-#[derive(Hash, Debug, Clone, Serialize, Deserialize)]
+/// Hash-friendly representation of route preferences for cache key
+/// generation. Every field is a primitive, so building one per request
+/// doesn't allocate.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RoutePreferencesHash {
-    pub categories: Vec<String>,
+    /// Requested categories, one bit each (see [`category_mask`])
+    pub categories: u64,
+    #[serde(default)]
+    pub excluded_categories: u64,
     pub hidden_gems: bool,
+    /// Per-request waypoint spacing, rounded to whole meters
+    #[serde(default)]
+    pub min_separation_m: Option<i64>,
+    /// Running pace, in whole seconds per km
+    #[serde(default)]
+    pub pace_s_per_km: Option<i64>,
+    /// Minimum paved share, in whole percent
+    #[serde(default)]
+    pub min_paved_pct: Option<i64>,
+    /// Maximum busy-road share, in whole percent
+    #[serde(default)]
+    pub max_busy_road_pct: Option<i64>,
+    #[serde(default)]
+    pub surface: SurfacePreference,
+    #[serde(default)]
+    pub prefer_green: bool,
+    #[serde(default)]
+    pub step_free: bool,
+    #[serde(default)]
+    pub minimize_exposure: bool,
+    /// Climb limit, in whole meters
+    #[serde(default)]
+    pub max_elevation_gain_m: Option<i64>,
+    #[serde(default)]
+    pub prefer_flat: bool,
+    #[serde(default)]
+    pub avoid_highways: bool,
+    /// Ferry willingness, in whole percent
+    #[serde(default)]
+    pub use_ferries_pct: Option<i64>,
+    /// Walkway cost factor, in hundredths
+    #[serde(default)]
+    pub walkway_factor_pct: Option<i64>,
+    #[serde(default)]
+    pub include_visit_time: bool,
+    /// Golden-hour routing: `Some` with the departure (positive) or arrival
+    /// (negative) time in 15-minute slots, 0 for "end at sunset"
+    #[serde(default)]
+    pub golden_hour_slot: Option<i64>,
+    #[serde(default)]
+    pub pareto: bool,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Order-independent digest of the pinned places, 0 for none
+    #[serde(default)]
+    pub must_include: u64,
 }
 
 impl RoutePreferencesHash {
     pub fn new(categories: Option<&[PoiCategory]>, hidden_gems: bool) -> Self {
-        let mut cat_strs: Vec<String> = categories
-            .map(|cats| cats.iter().map(|c| c.to_string()).collect())
-            .unwrap_or_default();
-        cat_strs.sort(); // Ensure consistent ordering
-
         RoutePreferencesHash {
-            categories: cat_strs,
+            categories: category_mask(categories),
+            excluded_categories: 0,
             hidden_gems,
+            min_separation_m: None,
+            pace_s_per_km: None,
+            min_paved_pct: None,
+            max_busy_road_pct: None,
+            surface: SurfacePreference::Mixed,
+            prefer_green: false,
+            step_free: false,
+            minimize_exposure: false,
+            max_elevation_gain_m: None,
+            prefer_flat: false,
+            avoid_highways: false,
+            use_ferries_pct: None,
+            walkway_factor_pct: None,
+            include_visit_time: false,
+            golden_hour_slot: None,
+            pareto: false,
+            seed: None,
+            must_include: 0,
         }
     }
+
+    /// Include excluded categories in the key so blacklists don't share cache entries
+    pub fn with_excluded_categories(mut self, excluded: Option<&[PoiCategory]>) -> Self {
+        self.excluded_categories = category_mask(excluded);
+        self
+    }
+
+    pub fn with_min_separation_km(mut self, separation_km: Option<f64>) -> Self {
+        self.min_separation_m = separation_km.map(|km| (km * 1000.0).round() as i64);
+        self
+    }
+
+    pub fn with_pace_min_per_km(mut self, pace: Option<f64>) -> Self {
+        self.pace_s_per_km = pace.map(|p| (p * 60.0).round() as i64);
+        self
+    }
+
+    pub fn with_min_paved_fraction(mut self, fraction: Option<f32>) -> Self {
+        self.min_paved_pct = fraction.map(|f| (f * 100.0).round() as i64);
+        self
+    }
+
+    pub fn with_max_busy_road_fraction(mut self, fraction: Option<f32>) -> Self {
+        self.max_busy_road_pct = fraction.map(|f| (f * 100.0).round() as i64);
+        self
+    }
+
+    pub fn with_surface(mut self, surface: SurfacePreference) -> Self {
+        self.surface = surface;
+        self
+    }
+
+    pub fn with_prefer_green(mut self, prefer_green: bool) -> Self {
+        self.prefer_green = prefer_green;
+        self
+    }
+
+    pub fn with_step_free(mut self, step_free: bool) -> Self {
+        self.step_free = step_free;
+        self
+    }
+
+    pub fn with_minimize_exposure(mut self, minimize_exposure: bool) -> Self {
+        self.minimize_exposure = minimize_exposure;
+        self
+    }
+
+    pub fn with_max_elevation_gain_m(mut self, gain_m: Option<f32>) -> Self {
+        self.max_elevation_gain_m = gain_m.map(|m| m.round() as i64);
+        self
+    }
+
+    pub fn with_prefer_flat(mut self, prefer_flat: bool) -> Self {
+        self.prefer_flat = prefer_flat;
+        self
+    }
+
+    pub fn with_costing(mut self, costing: &CostingOptions) -> Self {
+        self.avoid_highways = costing.avoid_highways;
+        self.use_ferries_pct = costing.use_ferries.map(|f| (f * 100.0).round() as i64);
+        self.walkway_factor_pct = costing.walkway_factor.map(|f| (f * 100.0).round() as i64);
+        self
+    }
+
+    pub fn with_include_visit_time(mut self, include_visit_time: bool) -> Self {
+        self.include_visit_time = include_visit_time;
+        self
+    }
+
+    /// Waypoint order depends on the time of day under golden-hour routing
+    pub fn with_golden_hour(mut self, golden_hour: bool, departure: Option<Departure>) -> Self {
+        const SLOT_SECS: i64 = 15 * 60;
+        self.golden_hour_slot = golden_hour.then(|| match departure {
+            Some(Departure::DepartAt(at)) => at.unix_timestamp().div_euclid(SLOT_SECS) + 1,
+            Some(Departure::ArriveBy(by)) => -(by.unix_timestamp().div_euclid(SLOT_SECS) + 1),
+            None => 0,
+        });
+        self
+    }
+
+    pub fn with_pareto(mut self, pareto: bool) -> Self {
+        self.pareto = pareto;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The loop through pinned places doesn't depend on their request
+    /// order, so pins are hashed one by one and their hashes summed
+    pub fn with_must_include(mut self, must_include: &[MustInclude]) -> Self {
+        self.must_include = must_include
+            .iter()
+            .map(|pin| {
+                let mut hasher = KeyHasher::new();
+                match pin {
+                    MustInclude::Poi(id) => {
+                        hasher.write_bytes(b"poi");
+                        hasher.write_bytes(id.as_bytes());
+                    }
+                    MustInclude::Point(c) => {
+                        hasher.write_bytes(b"at");
+                        hasher.write_i64((c.lat * 1e5).round() as i64);
+                        hasher.write_i64((c.lng * 1e5).round() as i64);
+                    }
+                }
+                hasher.finish()
+            })
+            .fold(0, u64::wrapping_add);
+        self
+    }
+
+    /// Feed every field, in declaration order, into `hasher`
+    pub fn write_to(&self, hasher: &mut KeyHasher) {
+        hasher.write_u64(self.categories);
+        hasher.write_u64(self.excluded_categories);
+        hasher.write_bool(self.hidden_gems);
+        hasher.write_opt_i64(self.min_separation_m);
+        hasher.write_opt_i64(self.pace_s_per_km);
+        hasher.write_opt_i64(self.min_paved_pct);
+        hasher.write_opt_i64(self.max_busy_road_pct);
+        hasher.write_bytes(&[match self.surface {
+            SurfacePreference::Paved => 0,
+            SurfacePreference::Mixed => 1,
+            SurfacePreference::Trail => 2,
+        }]);
+        hasher.write_bool(self.prefer_green);
+        hasher.write_bool(self.step_free);
+        hasher.write_bool(self.minimize_exposure);
+        hasher.write_opt_i64(self.max_elevation_gain_m);
+        hasher.write_bool(self.prefer_flat);
+        hasher.write_bool(self.avoid_highways);
+        hasher.write_opt_i64(self.use_ferries_pct);
+        hasher.write_opt_i64(self.walkway_factor_pct);
+        hasher.write_bool(self.include_visit_time);
+        hasher.write_opt_i64(self.golden_hour_slot);
+        hasher.write_bool(self.pareto);
+        hasher.write_opt_i64(self.seed.map(|seed| seed as i64));
+        hasher.write_u64(self.must_include);
+    }
+}
+
+/// One bit per category, by declaration order: the set is the same whatever
+/// order (or repetition) the request lists categories in. New categories
+/// must be appended to [`PoiCategory`] to keep existing keys.
+pub fn category_mask(categories: Option<&[PoiCategory]>) -> u64 {
+    categories
+        .unwrap_or_default()
+        .iter()
+        .fold(0, |mask, category| mask | (1 << (category.clone() as u32)))
+}
+
+/// How long to keep a route set, relative to the backend's configured TTL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheTtl {
+    Long,
+    Standard,
+    Short,
+}
+
+impl CacheTtl {
+    /// From the best quality tier in `routes`: gold sets are kept longer,
+    /// sets with nothing better than bronze (or untiered fallbacks) shorter
+    pub fn for_routes(routes: &[Route]) -> Self {
+        let best = routes
+            .iter()
+            .filter_map(|r| r.quality_tier)
+            .min_by_key(|tier| match tier {
+                QualityTier::Gold => 0,
+                QualityTier::Silver => 1,
+                QualityTier::Bronze => 2,
+            });
+        match best {
+            Some(QualityTier::Gold) => CacheTtl::Long,
+            Some(QualityTier::Silver) => CacheTtl::Standard,
+            Some(QualityTier::Bronze) | None => CacheTtl::Short,
+        }
+    }
+
+    /// `base_secs` scaled for this hint, never below one second
+    pub fn apply(self, base_secs: u64) -> u64 {
+        let multiplier = match self {
+            CacheTtl::Long => CACHE_TTL_GOLD_MULTIPLIER,
+            CacheTtl::Standard => 1.0,
+            CacheTtl::Short => CACHE_TTL_LOW_QUALITY_MULTIPLIER,
+        };
+        ((base_secs as f64 * multiplier).round() as u64).max(1)
+    }
 }
 
 /// Cache statistics for monitoring
@@ -104,6 +519,15 @@ pub struct CacheStats {
     pub misses: u64,
     pub hit_rate: f64,
     pub connected: bool,
+    /// Entries dropped on read for an older format or schema version
+    #[serde(default)]
+    pub stale_entries: u64,
+    /// Entries dropped on read because they failed to decode
+    #[serde(default)]
+    pub decode_failures: u64,
+    /// Redis failed repeatedly and is skipped until it answers again
+    #[serde(default)]
+    pub disabled: bool,
 }
 
 #[cfg(test)]
@@ -115,8 +539,10 @@ mod tests {
         let coord1 = Coordinates::new(48.8566, 2.3522).unwrap();
         let prefs = RoutePreferencesHash::new(Some(&[PoiCategory::Monument]), false);
 
-        let key1 = loop_route_cache_key(&coord1, 5.0, "walking", &prefs);
-        let key2 = loop_route_cache_key(&coord1, 5.0, "walking", &prefs);
+        let key1 =
+            loop_route_cache_key(&coord1, 5.0, "walking", &prefs, &CacheKeyPrecision::DEFAULT);
+        let key2 =
+            loop_route_cache_key(&coord1, 5.0, "walking", &prefs, &CacheKeyPrecision::DEFAULT);
 
         assert_eq!(key1, key2);
     }
@@ -129,28 +555,214 @@ mod tests {
 
         let prefs = RoutePreferencesHash::new(None, false);
 
-        let key1 = loop_route_cache_key(&coord1, 5.0, "walking", &prefs);
-        let key2 = loop_route_cache_key(&coord2, 5.0, "walking", &prefs);
+        let key1 =
+            loop_route_cache_key(&coord1, 5.0, "walking", &prefs, &CacheKeyPrecision::DEFAULT);
+        let key2 =
+            loop_route_cache_key(&coord2, 5.0, "walking", &prefs, &CacheKeyPrecision::DEFAULT);
 
         assert_eq!(key1, key2);
     }
 
+    #[test]
+    fn test_cache_keys_at_antimeridian_and_poles() {
+        let prefs = RoutePreferencesHash::new(None, false);
+        let key = |lat: f64, lng: f64| {
+            loop_route_cache_key(
+                &Coordinates::new(lat, lng).unwrap(),
+                5.0,
+                "walking",
+                &prefs,
+                &CacheKeyPrecision::DEFAULT,
+            )
+        };
+
+        // Fiji: both sides of the 180° meridian round onto it
+        assert_eq!(key(-17.0, 179.9999), key(-17.0, -179.9999));
+        assert_ne!(key(-17.0, 179.99), key(-17.0, -179.99));
+        // At the pole longitude is meaningless
+        assert_eq!(key(90.0, 15.0), key(89.9999, -120.0));
+        // Svalbard, just off the pole, keeps its longitude
+        assert_ne!(key(78.2, 15.6), key(78.2, -15.6));
+
+        let region =
+            |lng: f64| poi_region_cache_key(&Coordinates::new(-17.0, lng).unwrap(), 5.0, None);
+        assert_eq!(region(180.0), region(-180.0));
+    }
+
     #[test]
     fn test_loop_route_cache_key_distance_buckets() {
         let coord = Coordinates::new(48.8566, 2.3522).unwrap();
         let prefs = RoutePreferencesHash::new(None, false);
 
         // 4.8km and 5.2km should be in same bucket (5.0)
-        let key1 = loop_route_cache_key(&coord, 4.8, "walking", &prefs);
-        let key2 = loop_route_cache_key(&coord, 5.2, "walking", &prefs);
+        let key1 =
+            loop_route_cache_key(&coord, 4.8, "walking", &prefs, &CacheKeyPrecision::DEFAULT);
+        let key2 =
+            loop_route_cache_key(&coord, 5.2, "walking", &prefs, &CacheKeyPrecision::DEFAULT);
 
         assert_eq!(key1, key2);
 
         // 5.5km should be in different bucket (5.5)
-        let key3 = loop_route_cache_key(&coord, 5.5, "walking", &prefs);
+        let key3 =
+            loop_route_cache_key(&coord, 5.5, "walking", &prefs, &CacheKeyPrecision::DEFAULT);
         assert_ne!(key1, key3);
     }
 
+    #[test]
+    fn test_loop_route_cache_key_custom_precision() {
+        let coord = Coordinates::new(48.8566, 2.3522).unwrap();
+        let nearby = Coordinates::new(48.8612, 2.3538).unwrap(); // ~500m away
+        let prefs = RoutePreferencesHash::new(None, false);
+        let rural = CacheKeyPrecision {
+            coord_decimals: 2,
+            distance_bucket_km: 2.0,
+        };
+
+        let key = |start: &Coordinates, distance_km: f64, precision: &CacheKeyPrecision| {
+            loop_route_cache_key(start, distance_km, "walking", &prefs, precision)
+        };
+        assert_ne!(
+            key(&coord, 5.0, &CacheKeyPrecision::DEFAULT),
+            key(&nearby, 5.0, &CacheKeyPrecision::DEFAULT)
+        );
+        assert_eq!(key(&coord, 5.0, &rural), key(&nearby, 5.5, &rural));
+        assert!(key(&coord, 5.0, &rural).starts_with("route:loop:48.86:2.35:"));
+        assert_ne!(
+            key(&coord, 10.0, &rural),
+            key(&coord, 10.0, &CacheKeyPrecision::DEFAULT)
+        );
+    }
+
+    #[test]
+    fn test_loop_route_cache_key_excluded_categories() {
+        let coord = Coordinates::new(48.8566, 2.3522).unwrap();
+        let plain = RoutePreferencesHash::new(None, false);
+        let excluded = RoutePreferencesHash::new(None, false)
+            .with_excluded_categories(Some(&[PoiCategory::Cafe, PoiCategory::Brewery]));
+        let reordered = RoutePreferencesHash::new(None, false)
+            .with_excluded_categories(Some(&[PoiCategory::Brewery, PoiCategory::Cafe]));
+
+        let key_plain =
+            loop_route_cache_key(&coord, 5.0, "walking", &plain, &CacheKeyPrecision::DEFAULT);
+        let key_excluded = loop_route_cache_key(
+            &coord,
+            5.0,
+            "walking",
+            &excluded,
+            &CacheKeyPrecision::DEFAULT,
+        );
+        let key_reordered = loop_route_cache_key(
+            &coord,
+            5.0,
+            "walking",
+            &reordered,
+            &CacheKeyPrecision::DEFAULT,
+        );
+
+        assert_ne!(key_plain, key_excluded);
+        assert_eq!(key_excluded, key_reordered);
+    }
+
+    #[test]
+    fn test_loop_route_cache_key_must_include() {
+        let coord = Coordinates::new(48.8566, 2.3522).unwrap();
+        let poi = MustInclude::Poi(uuid::Uuid::new_v4());
+        let point = MustInclude::Point(Coordinates::new(48.86, 2.34).unwrap());
+        let key = |pins: &[MustInclude]| {
+            let prefs = RoutePreferencesHash::new(None, false).with_must_include(pins);
+            loop_route_cache_key(&coord, 5.0, "walking", &prefs, &CacheKeyPrecision::DEFAULT)
+        };
+
+        assert_ne!(key(&[]), key(&[poi]));
+        assert_ne!(key(&[poi]), key(&[poi, point]));
+        assert_eq!(key(&[poi, point]), key(&[point, poi]));
+    }
+
+    /// Keys are shared between instances and survive upgrades: a change to
+    /// these values invalidates every cached entry, so make it on purpose
+    /// (and bump `KEY_HASH_VERSION` if the hashed fields changed)
+    #[test]
+    fn test_cache_keys_are_stable() {
+        let coord = Coordinates::new(48.8566, 2.3522).unwrap();
+        let pin = MustInclude::Point(Coordinates::new(48.86, 2.34).unwrap());
+        let plain = RoutePreferencesHash::new(None, false);
+        let tuned =
+            RoutePreferencesHash::new(Some(&[PoiCategory::Park, PoiCategory::Museum]), true)
+                .with_excluded_categories(Some(&[PoiCategory::Cafe]))
+                .with_pace_min_per_km(Some(5.5))
+                .with_surface(SurfacePreference::Trail)
+                .with_seed(Some(42))
+                .with_must_include(&[pin]);
+
+        let key = |mode: &str, prefs: &RoutePreferencesHash| {
+            loop_route_cache_key(&coord, 5.0, mode, prefs, &CacheKeyPrecision::DEFAULT)
+        };
+        assert_eq!(
+            key("walk", &plain),
+            "route:loop:48.857:2.352:17661150fb4a62b"
+        );
+        assert_eq!(
+            key("run", &tuned),
+            "route:loop:48.857:2.352:df0f5a267ca5a8a7"
+        );
+        assert_eq!(
+            poi_region_cache_key(&coord, 5.0, None),
+            "poi:region:48.86:2.35:f2e32320a94127ef"
+        );
+        assert_eq!(
+            poi_region_cache_key(&coord, 5.0, Some(&[PoiCategory::Park])),
+            "poi:region:48.86:2.35:15d3a4f737eb6452"
+        );
+    }
+
+    #[test]
+    fn test_cache_keys_carry_their_cell() {
+        let coord = Coordinates::new(48.8566, 2.3522).unwrap();
+        let prefs = RoutePreferencesHash::new(None, false);
+
+        let key = loop_route_cache_key(&coord, 5.0, "walking", &prefs, &CacheKeyPrecision::DEFAULT);
+        assert!(key.starts_with("route:loop:48.857:2.352:"));
+        let key = poi_region_cache_key(&coord, 5.0, None);
+        assert!(key.starts_with("poi:region:48.86:2.35:"));
+    }
+
+    #[test]
+    fn test_cache_filter_matching() {
+        let prefs = RoutePreferencesHash::new(None, false);
+        let paris = Coordinates::new(48.8566, 2.3522).unwrap();
+        let lyon = Coordinates::new(45.764, 4.8357).unwrap();
+        let paris_route =
+            loop_route_cache_key(&paris, 5.0, "walking", &prefs, &CacheKeyPrecision::DEFAULT);
+        let paris_pois = poi_region_cache_key(&paris, 5.0, None);
+        let lyon_pois = format!("tenant:acme:{}", poi_region_cache_key(&lyon, 5.0, None));
+        let around_paris = BoundingBox::from_center_radius(&paris, 10_000.0);
+
+        let by_prefix = CacheFilter {
+            prefix: Some(POI_REGION_NAMESPACE.to_string()),
+            region: None,
+        };
+        assert!(!by_prefix.matches(&paris_route));
+        assert!(by_prefix.matches(&paris_pois));
+        assert!(by_prefix.matches(&lyon_pois));
+
+        let by_region = CacheFilter {
+            prefix: None,
+            region: Some(around_paris),
+        };
+        assert!(by_region.matches(&paris_route));
+        assert!(by_region.matches(&paris_pois));
+        assert!(!by_region.matches(&lyon_pois));
+        assert!(!by_region.matches("unrelated"));
+
+        let both = CacheFilter {
+            prefix: Some(POI_REGION_NAMESPACE.to_string()),
+            region: Some(around_paris),
+        };
+        assert!(!both.matches(&paris_route));
+        assert!(both.matches(&paris_pois));
+        assert!(CacheFilter::default().matches(&lyon_pois));
+    }
+
     #[test]
     fn test_poi_region_cache_key_consistency() {
         let coord = Coordinates::new(48.8566, 2.3522).unwrap();
@@ -179,4 +791,30 @@ mod tests {
 
         assert_eq!(key1, key2);
     }
+
+    #[test]
+    fn test_cache_ttl_follows_best_tier() {
+        let tiered = |tier| {
+            let mut route = Route::builder().distance_km(5.0).build();
+            route.quality_tier = tier;
+            route
+        };
+
+        let mixed = [
+            tiered(Some(QualityTier::Bronze)),
+            tiered(Some(QualityTier::Gold)),
+        ];
+        assert_eq!(CacheTtl::for_routes(&mixed), CacheTtl::Long);
+        assert_eq!(
+            CacheTtl::for_routes(&[tiered(Some(QualityTier::Silver))]),
+            CacheTtl::Standard
+        );
+        assert_eq!(CacheTtl::for_routes(&[tiered(None)]), CacheTtl::Short);
+        assert_eq!(CacheTtl::for_routes(&[]), CacheTtl::Short);
+
+        assert_eq!(CacheTtl::Long.apply(3600), 7200);
+        assert_eq!(CacheTtl::Standard.apply(3600), 3600);
+        assert_eq!(CacheTtl::Short.apply(3600), 900);
+        assert_eq!(CacheTtl::Short.apply(1), 1);
+    }
 }
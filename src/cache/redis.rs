@@ -1,15 +1,38 @@
-use crate::cache::{CacheStats, RouteCache};
+use crate::cache::breaker::CircuitBreaker;
+use crate::cache::codec::{self, CacheCompression, DecodeError};
+use crate::cache::{CacheFilter, CacheKeyPrecision, CacheStats, CacheTtl, RouteCache};
+use crate::constants::{
+    CACHE_INVALIDATION_BATCH_SIZE, DEFAULT_REDIS_TIMEOUT_MS, REDIS_BREAKER_FAILURE_THRESHOLD,
+    REDIS_RECONNECT_INTERVAL_SECS,
+};
 use crate::error::{AppError, Result};
 use crate::models::Route;
+use crate::services::dependency_guard::Dependency;
+use crate::services::timeouts::with_timeout;
 use async_trait::async_trait;
 use redis::aio::ConnectionManager;
 use redis::AsyncCommands;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
 /// Redis-backed cache service. All methods are `&self` — `ConnectionManager` is
 /// `Arc`-based internally, so `.clone()` is a cheap atomic increment.
 pub struct RedisCacheService {
     connection: ConnectionManager,
     route_cache_ttl: u64,
+    /// Entries dropped for an older format or schema version
+    stale_entries: AtomicU64,
+    /// Entries dropped because they failed to decode
+    decode_failures: AtomicU64,
+    key_precision: CacheKeyPrecision,
+    compression: CacheCompression,
+    /// Budget per command; a slower Redis counts as a miss instead of
+    /// holding up the request
+    op_timeout: Duration,
+    /// Open while Redis is down; commands are skipped instead of sent
+    breaker: Arc<CircuitBreaker>,
 }
 
 impl RedisCacheService {
@@ -26,28 +49,109 @@ impl RedisCacheService {
         Ok(RedisCacheService {
             connection,
             route_cache_ttl,
+            stale_entries: AtomicU64::new(0),
+            decode_failures: AtomicU64::new(0),
+            key_precision: CacheKeyPrecision::DEFAULT,
+            compression: CacheCompression::default(),
+            op_timeout: Duration::from_millis(DEFAULT_REDIS_TIMEOUT_MS),
+            breaker: Arc::new(CircuitBreaker::new(REDIS_BREAKER_FAILURE_THRESHOLD)),
         })
     }
+
+    pub fn with_op_timeout(mut self, op_timeout: Duration) -> Self {
+        self.op_timeout = op_timeout;
+        self
+    }
+
+    /// `command` within the per-command budget. The outcome feeds the
+    /// circuit breaker; the failure that opens it starts the reconnect probe.
+    async fn timed<T>(&self, command: impl Future<Output = redis::RedisResult<T>>) -> Result<T> {
+        let result = with_timeout(Dependency::Redis, self.op_timeout, async {
+            command.await.map_err(|e| AppError::Cache(e.to_string()))
+        })
+        .await;
+        match &result {
+            Ok(_) => self.breaker.record_success(),
+            Err(e) => {
+                if self.breaker.record_failure(e) {
+                    spawn_reconnect_probe(
+                        self.connection.clone(),
+                        Arc::downgrade(&self.breaker),
+                        self.op_timeout,
+                    );
+                }
+            }
+        }
+        result
+    }
+
+    /// Whether Redis is being skipped after repeated failures
+    pub fn is_disabled(&self) -> bool {
+        self.breaker.is_open()
+    }
+
+    pub fn with_compression(mut self, compression: CacheCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn with_key_precision(mut self, key_precision: CacheKeyPrecision) -> Self {
+        self.key_precision = key_precision;
+        self
+    }
 }
 
 #[async_trait]
 impl RouteCache for RedisCacheService {
     async fn get_cached_routes(&self, key: &str) -> Option<Vec<Route>> {
+        self.get_cached_routes_with_ttl(key)
+            .await
+            .map(|(routes, _)| routes)
+    }
+
+    /// Reads the remaining TTL (PTTL) in the same round trip as the entry
+    async fn get_cached_routes_with_ttl(
+        &self,
+        key: &str,
+    ) -> Option<(Vec<Route>, Option<Duration>)> {
+        if self.is_disabled() {
+            return None;
+        }
         let mut conn = self.connection.clone();
-        let result: redis::RedisResult<Option<String>> = conn.get(key).await;
+        let mut pipe = redis::pipe();
+        pipe.get(key).pttl(key);
+        let result: Result<(Option<Vec<u8>>, i64)> = self.timed(pipe.query_async(&mut conn)).await;
 
         match result {
-            Ok(Some(json)) => match serde_json::from_str(&json) {
+            Ok((Some(entry), pttl_ms)) => match codec::decode(&entry) {
                 Ok(routes) => {
                     tracing::debug!("Cache hit for route: {}", key);
-                    Some(routes)
+                    // Negative when the key has no expiry (or just expired)
+                    let remaining = u64::try_from(pttl_ms).ok().map(Duration::from_millis);
+                    Some((routes, remaining))
+                }
+                // Left for the newer release still serving it; deleting it
+                // would make old and new instances purge each other's
+                // entries during a rolling deploy
+                Err(DecodeError::Newer) => {
+                    tracing::debug!("Skipping cached routes {} from a newer release", key);
+                    None
                 }
                 Err(e) => {
-                    tracing::warn!("Failed to deserialize cached routes: {}", e);
+                    let counter = match e {
+                        DecodeError::Corrupt(_) => &self.decode_failures,
+                        _ => &self.stale_entries,
+                    };
+                    counter.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!("Dropping unreadable cached routes {}: {}", key, e);
+                    // It would fail the same way until it expires
+                    if let Err(e) = self.timed(conn.del::<_, ()>(key)).await {
+                        tracing::warn!("Failed to drop cached routes: {}", e);
+                    }
                     None
                 }
             },
-            Ok(None) => {
+            Ok((None, _)) => {
                 tracing::debug!("Cache miss for route: {}", key);
                 None
             }
@@ -58,24 +162,28 @@ impl RouteCache for RedisCacheService {
         }
     }
 
-    async fn cache_routes(&self, key: &str, routes: &[Route]) {
-        let json = match serde_json::to_string(routes) {
-            Ok(j) => j,
+    async fn cache_routes(&self, key: &str, routes: &[Route], ttl: CacheTtl) {
+        if self.is_disabled() {
+            return;
+        }
+        let entry = match codec::encode(routes, self.compression) {
+            Ok(entry) => entry,
             Err(e) => {
-                tracing::warn!("Failed to serialize routes for cache: {}", e);
+                tracing::warn!("Failed to encode routes for cache: {}", e);
                 return;
             }
         };
 
+        let ttl_secs = ttl.apply(self.route_cache_ttl);
         let mut conn = self.connection.clone();
-        let result: redis::RedisResult<()> = conn.set_ex(key, json, self.route_cache_ttl).await;
+        let result: Result<()> = self.timed(conn.set_ex(key, entry, ttl_secs)).await;
 
         match result {
             Ok(()) => {
                 tracing::debug!(
                     "Cached {} routes with TTL {}s: {}",
                     routes.len(),
-                    self.route_cache_ttl,
+                    ttl_secs,
                     key
                 );
             }
@@ -86,9 +194,16 @@ impl RouteCache for RedisCacheService {
     }
 
     async fn get_stats(&self) -> CacheStats {
-        let mut conn = self.connection.clone();
-        let info: redis::RedisResult<String> =
-            redis::cmd("INFO").arg("stats").query_async(&mut conn).await;
+        let disabled = self.is_disabled();
+        let info: Result<String> = if disabled {
+            Err(AppError::Cache("cache disabled".to_string()))
+        } else {
+            let mut conn = self.connection.clone();
+            self.timed(redis::cmd("INFO").arg("stats").query_async(&mut conn))
+                .await
+        };
+        let stale_entries = self.stale_entries.load(Ordering::Relaxed);
+        let decode_failures = self.decode_failures.load(Ordering::Relaxed);
 
         match info {
             Ok(info_str) => {
@@ -105,6 +220,9 @@ impl RouteCache for RedisCacheService {
                     misses,
                     hit_rate,
                     connected: true,
+                    stale_entries,
+                    decode_failures,
+                    disabled: false,
                 }
             }
             Err(_) => CacheStats {
@@ -112,19 +230,126 @@ impl RouteCache for RedisCacheService {
                 misses: 0,
                 hit_rate: 0.0,
                 connected: false,
+                stale_entries,
+                decode_failures,
+                disabled,
             },
         }
     }
 
     async fn health_check(&self) -> bool {
+        if self.is_disabled() {
+            return false;
+        }
         let mut conn = self.connection.clone();
-        let result: redis::RedisResult<String> = redis::cmd("PING").query_async(&mut conn).await;
+        let result: Result<String> = self.timed(redis::cmd("PING").query_async(&mut conn)).await;
         result.is_ok()
     }
 
     fn backend_name(&self) -> &'static str {
         "redis"
     }
+
+    fn key_precision(&self) -> CacheKeyPrecision {
+        self.key_precision
+    }
+
+    /// The SCAN itself is not time-limited: it walks the whole keyspace
+    async fn invalidate(&self, filter: &CacheFilter) -> u64 {
+        if self.is_disabled() {
+            tracing::warn!("Cache disabled, nothing invalidated");
+            return 0;
+        }
+        let mut conn = self.connection.clone();
+        let mut keys = Vec::new();
+        for pattern in scan_patterns(filter) {
+            let mut iter = match conn.scan_match::<_, String>(&pattern).await {
+                Ok(iter) => iter,
+                Err(e) => {
+                    tracing::warn!("Redis error scanning {}: {}", pattern, e);
+                    return 0;
+                }
+            };
+            while let Some(key) = iter.next_item().await {
+                match key {
+                    Ok(key) if filter.matches(&key) => keys.push(key),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Redis error scanning {}: {}", pattern, e),
+                }
+            }
+        }
+        keys.sort();
+        keys.dedup();
+
+        let mut deleted = 0;
+        for batch in keys.chunks(CACHE_INVALIDATION_BATCH_SIZE) {
+            match self.timed(conn.del::<_, u64>(batch)).await {
+                Ok(n) => deleted += n,
+                Err(e) => tracing::warn!("Failed to invalidate cached routes: {}", e),
+            }
+        }
+        tracing::info!("Invalidated {} cached entries", deleted);
+        deleted
+    }
+
+    /// Nothing is held in process; the shared keyspace was already purged
+    async fn invalidate_local(&self, _filter: &CacheFilter) -> u64 {
+        0
+    }
+}
+
+/// Ping Redis every [`REDIS_RECONNECT_INTERVAL_SECS`] until it answers,
+/// then re-enable the cache. Stops early if the cache service is dropped.
+fn spawn_reconnect_probe(
+    mut connection: ConnectionManager,
+    breaker: Weak<CircuitBreaker>,
+    budget: Duration,
+) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(REDIS_RECONNECT_INTERVAL_SECS));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let Some(breaker) = breaker.upgrade() else {
+                return;
+            };
+            if !breaker.is_open() {
+                return;
+            }
+            let ping = tokio::time::timeout(
+                budget,
+                redis::cmd("PING").query_async::<String>(&mut connection),
+            )
+            .await;
+            match ping {
+                Ok(Ok(_)) => {
+                    breaker.record_success();
+                    return;
+                }
+                Ok(Err(e)) => tracing::debug!("Redis still unavailable: {}", e),
+                Err(_) => tracing::debug!("Redis still unavailable: ping timed out"),
+            }
+        }
+    });
+}
+
+/// SCAN patterns covering the keys `filter` can match: its prefix bare and
+/// under any tenant scope, or every key when only a region is given
+fn scan_patterns(filter: &CacheFilter) -> Vec<String> {
+    match filter.prefix {
+        Some(ref prefix) => {
+            let escaped: String = prefix
+                .chars()
+                .flat_map(|c| match c {
+                    '*' | '?' | '[' | ']' | '\\' => vec!['\\', c],
+                    c => vec![c],
+                })
+                .collect();
+            vec![format!("{}*", escaped), format!("tenant:*:{}*", escaped)]
+        }
+        None => vec!["*".to_string()],
+    }
 }
 
 fn parse_info_value(info: &str, key: &str) -> u64 {
@@ -0,0 +1,203 @@
+use crate::cache::{
+    CacheFilter, CacheKeyPrecision, CacheStats, CacheTtl, MemoryCacheService, RouteCache,
+};
+use crate::models::Route;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Two-tier cache: an in-process LRU in front of a shared backend (Redis).
+/// Reads try the local tier first and backfill it from the remote one;
+/// writes go to both. The remote tier already swallows its own errors, so
+/// a Redis outage falls back to the local tier alone.
+pub struct TieredCacheService {
+    local: MemoryCacheService,
+    remote: Arc<dyn RouteCache>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl TieredCacheService {
+    pub fn new(local: MemoryCacheService, remote: Arc<dyn RouteCache>) -> Self {
+        TieredCacheService {
+            local,
+            remote,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl RouteCache for TieredCacheService {
+    async fn get_cached_routes(&self, key: &str) -> Option<Vec<Route>> {
+        if let Some(routes) = self.local.get_cached_routes(key).await {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Some(routes);
+        }
+        match self.remote.get_cached_routes_with_ttl(key).await {
+            Some((routes, remaining)) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                // Never outlive the remote copy, or an entry the remote tier
+                // already expired would keep being served from here
+                let ttl = self.local.ttl_for(CacheTtl::for_routes(&routes));
+                let ttl = remaining.map_or(ttl, |remaining| remaining.min(ttl));
+                self.local.cache_routes_for(key, &routes, ttl).await;
+                Some(routes)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    async fn cache_routes(&self, key: &str, routes: &[Route], ttl: CacheTtl) {
+        self.local.cache_routes(key, routes, ttl).await;
+        self.remote.cache_routes(key, routes, ttl).await;
+    }
+
+    async fn get_stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let hit_rate = if hits + misses > 0 {
+            (hits as f64 / (hits + misses) as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        let remote = self.remote.get_stats().await;
+        CacheStats {
+            hits,
+            misses,
+            hit_rate,
+            connected: remote.connected,
+            stale_entries: remote.stale_entries,
+            decode_failures: remote.decode_failures,
+            disabled: remote.disabled,
+        }
+    }
+
+    /// Reports the remote tier, so a Redis outage shows up as degraded even
+    /// though the local tier keeps serving
+    async fn health_check(&self) -> bool {
+        self.remote.health_check().await
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "tiered"
+    }
+
+    fn key_precision(&self) -> CacheKeyPrecision {
+        self.local.key_precision()
+    }
+
+    /// Counts entries once even when both tiers held them
+    async fn invalidate(&self, filter: &CacheFilter) -> u64 {
+        let local = self.local.invalidate(filter).await;
+        let remote = self.remote.invalidate(filter).await;
+        local.max(remote)
+    }
+
+    async fn invalidate_local(&self, filter: &CacheFilter) -> u64 {
+        self.local.invalidate(filter).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn make_test_route(distance_km: f64) -> Route {
+        Route::builder()
+            .distance_km(distance_km)
+            .duration_minutes(30)
+            .score(7.0)
+            .build()
+    }
+
+    fn tiered() -> (TieredCacheService, Arc<MemoryCacheService>) {
+        let remote = Arc::new(MemoryCacheService::new(3600, 100));
+        let cache = TieredCacheService::new(
+            MemoryCacheService::new(3600, 100),
+            Arc::clone(&remote) as Arc<dyn RouteCache>,
+        );
+        (cache, remote)
+    }
+
+    #[tokio::test]
+    async fn writes_reach_both_tiers() {
+        let (cache, remote) = tiered();
+        cache
+            .cache_routes("key1", &[make_test_route(5.0)], CacheTtl::Standard)
+            .await;
+
+        assert!(cache.local.get_cached_routes("key1").await.is_some());
+        assert!(remote.get_cached_routes("key1").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn remote_hit_backfills_local() {
+        let (cache, remote) = tiered();
+        remote
+            .cache_routes("key1", &[make_test_route(5.0)], CacheTtl::Standard)
+            .await;
+
+        let cached = cache.get_cached_routes("key1").await.unwrap();
+        assert_eq!(cached[0].distance_km, 5.0);
+        assert!(cache.local.get_cached_routes("key1").await.is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn backfill_expires_with_the_remote_copy() {
+        let (cache, remote) = tiered();
+        remote
+            .cache_routes_for("key1", &[make_test_route(5.0)], Duration::from_secs(10))
+            .await;
+
+        assert!(cache.get_cached_routes("key1").await.is_some());
+        tokio::time::advance(Duration::from_secs(11)).await;
+        assert!(cache.local.get_cached_routes("key1").await.is_none());
+        assert!(cache.get_cached_routes("key1").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn invalidation_clears_both_tiers() {
+        let (cache, remote) = tiered();
+        cache
+            .cache_routes(
+                "poi:region:48.86:2.35:1",
+                &[make_test_route(5.0)],
+                CacheTtl::Standard,
+            )
+            .await;
+
+        assert_eq!(cache.invalidate(&CacheFilter::default()).await, 1);
+        assert!(cache
+            .get_cached_routes("poi:region:48.86:2.35:1")
+            .await
+            .is_none());
+        assert!(remote
+            .get_cached_routes("poi:region:48.86:2.35:1")
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn stats_count_each_lookup_once() {
+        let (cache, remote) = tiered();
+        remote
+            .cache_routes("key1", &[make_test_route(5.0)], CacheTtl::Standard)
+            .await;
+
+        cache.get_cached_routes("missing").await;
+        cache.get_cached_routes("key1").await; // remote hit
+        cache.get_cached_routes("key1").await; // local hit
+
+        let stats = cache.get_stats().await;
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+        assert!(stats.connected);
+    }
+}
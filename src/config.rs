@@ -1,6 +1,13 @@
+use crate::cache::codec::CacheCompression;
+use crate::cache::CacheKeyPrecision;
 use crate::constants::*;
+use crate::models::{SpeedModel, SpeedModels};
 use std::env;
 
+mod validation;
+
+pub use validation::ConfigIssue;
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub enum ScoringStrategy {
     Simple, // Distance-only scoring (original)
@@ -23,17 +30,82 @@ impl std::str::FromStr for ScoringStrategy {
     }
 }
 
+impl std::fmt::Display for ScoringStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScoringStrategy::Simple => write!(f, "simple"),
+            ScoringStrategy::Advanced => write!(f, "advanced"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub host: String,
     pub port: u16,
+    /// Postgres connection string; unused (and optional) in offline mode
     pub database_url: String,
+    /// Connections in the request-path pool. Env: `DATABASE_POOL_SIZE` (default 10)
+    pub database_pool_size: u32,
+    /// Connections in the pool for migrations, scheduled tasks and admin
+    /// endpoints. Env: `DATABASE_TASK_POOL_SIZE` (default 4)
+    pub database_task_pool_size: u32,
     pub redis_url: Option<String>, // Optional for Phase 1, required in Phase 2
+    /// Optional in offline mode, where directions come from `OSRM_URL` or
+    /// `VALHALLA_URL`
     pub mapbox_api_key: String,
+    /// Offline mode: serve POIs from this SQLite region database instead of
+    /// Postgres (requires the `sqlite` feature). Env: `REGION_DB_PATH`
+    pub region_db_path: Option<String>,
+    pub region_sync: Option<RegionSyncConfig>,
     pub route_cache_ttl: u64,
     pub poi_region_cache_ttl: u64,
+    /// Decimals loop route cache keys round the start point to; fewer
+    /// share entries across a wider area (rural), more keep them local
+    /// (dense cities). Env: `CACHE_COORD_PRECISION` (default 3, ~100m)
+    pub cache_coord_precision: u32,
+    /// Width of the requested-distance buckets in loop route cache keys.
+    /// Env: `CACHE_DISTANCE_BUCKET_KM` (default 0.5)
+    pub cache_distance_bucket_km: f64,
+    /// Compression of route entries stored in Redis (the in-memory tier
+    /// holds decoded routes). Env: `CACHE_COMPRESSION` (zstd | gzip | none,
+    /// default zstd)
+    pub cache_compression: CacheCompression,
     pub snap_radius_m: f64,
+    /// Narrow POI radius searches with a geohash range scan before the
+    /// PostGIS distance check. Env: `POI_GEOHASH_PREFILTER` (default false)
+    pub poi_geohash_prefilter: bool,
     pub mapbox_base_url: Option<String>,
+    pub environmental_layer: Option<EnvironmentalLayerConfig>,
+    pub elevation: Option<ElevationConfig>,
+    pub valhalla: Option<ValhallaConfig>,
+    pub osrm: Option<OsrmConfig>,
+    pub shadow: Option<ShadowConfig>,
+    pub request_log: Option<RequestLogConfig>,
+    pub privacy: Option<PrivacyConfig>,
+    pub warm_cities: Option<WarmCitiesConfig>,
+    /// Evaluated routes (with their ratings and shadow comparisons) older than
+    /// this are deleted. Env: `EVALUATION_RETENTION_DAYS` (default: kept forever)
+    pub evaluation_retention_days: Option<u32>,
+    /// Lifetime of share links unless the request asks for less.
+    /// Env: `SHARE_LINK_TTL_HOURS` (default 168)
+    pub share_link_ttl_hours: u32,
+    /// Require a Postgres-stored API key (`api_keys`) for `/routes/*`.
+    /// Env: `API_KEY_AUTH` (default false)
+    pub api_key_auth: bool,
+    /// Token guarding the `/admin/*` endpoints, which are only mounted when
+    /// it is set. Env: `ADMIN_TOKEN`
+    pub admin_token: Option<String>,
+    pub tenants: Option<TenantConfig>,
+    pub artifact_store: Option<ArtifactStoreConfig>,
+    /// Redis pub/sub channel lifecycle events are fanned out on, so every
+    /// instance's subscribers see them. Env: `EVENT_FANOUT_CHANNEL` (requires `REDIS_URL`)
+    pub event_fanout_channel: Option<String>,
+    pub scheduler: SchedulerConfig,
+    pub degradation: DegradationConfig,
+    /// Fault injection for resilience tests. Env: `CHAOS_ENABLED` (default false)
+    pub chaos: Option<ChaosConfig>,
+    pub cors: CorsConfig,
     pub route_generator: RouteGeneratorConfig,
 }
 
@@ -78,6 +150,11 @@ pub struct RouteGeneratorConfig {
     /// Env: `ROUTE_TOLERANCE_LEVEL_VERY_RELAXED` (default 0.5)
     pub tolerance_level_very_relaxed: f64,
 
+    /// Re-route only the worst-detouring leg of an overshooting route through
+    /// an extra sub-waypoint before discarding the attempt (one extra API call).
+    /// Env: `ROUTE_LEG_REPAIR` (default true)
+    pub leg_repair_enabled: bool,
+
     /// Maximum Mapbox API calls per single route generation attempt.
     /// Each retry uses a different waypoint combination or distance correction.
     /// Env: `ROUTE_MAX_GENERATION_RETRIES` (default 4)
@@ -97,6 +174,12 @@ pub struct RouteGeneratorConfig {
     /// Env: `ROUTE_WAYPOINTS_COUNT_LONG` (default 4)
     pub waypoints_count_long: usize,
 
+    /// Fewest waypoints for which the clockwise order is replaced by the
+    /// shortest tour. The default sits above `waypoints_count_long`, so
+    /// reordering is off unless one of the two is changed.
+    /// Env: `ROUTE_TSP_MIN_WAYPOINTS` (default 5)
+    pub tsp_min_waypoints: usize,
+
     /// Distance threshold (km) above which routes are classified as "long"
     /// and use `waypoints_count_long` waypoints plus area-based POI discovery.
     /// Env: `ROUTE_LONG_ROUTE_THRESHOLD_KM` (default 8.0)
@@ -132,6 +215,7 @@ pub struct RouteGeneratorConfig {
 
     /// Minimum separation (km) between any two selected waypoints.
     /// Prevents clustered waypoints that produce out-and-back shapes.
+    /// Overridable per request via `preferences.poi_min_separation_km`.
     /// Env: `ROUTE_POI_MIN_SEPARATION_KM` (default 0.3)
     pub poi_min_separation_km: f64,
 
@@ -169,6 +253,59 @@ pub struct RouteGeneratorConfig {
     /// Env: `ROUTE_SCORING_VERSION` (default 1)
     pub scoring_version: u32,
 
+    /// Re-query the cycling profile over walking routes' waypoints to report a
+    /// cycling duration when bikes can follow the same geometry. Costs one extra
+    /// directions call per returned walking route.
+    /// Env: `ROUTE_DURATION_REQUERY_CYCLING` (default false)
+    pub duration_requery_cycling: bool,
+
+    /// Routes at least this long (km) list drinking water and toilets along
+    /// the path in `amenities`. Amenities never influence waypoint selection.
+    /// Env: `ROUTE_AMENITIES_MIN_DISTANCE_KM` (default 8.0)
+    pub amenities_min_distance_km: f64,
+
+    // --- Speed Model ---
+    // Flat-ground speeds plus a time penalty per 100 m climbed, per profile.
+    // Used for derived duration estimates, run timings and planning times;
+    // a route's own mode keeps the directions backend's duration.
+    /// Walking (and dog walk) speed on flat ground, km/h.
+    /// Env: `ROUTE_WALKING_SPEED_KMH` (default 5.0)
+    pub walking_speed_kmh: f64,
+    /// Easy running speed on flat ground, km/h; a request's pace overrides it.
+    /// Env: `ROUTE_RUNNING_SPEED_KMH` (default 10.0)
+    pub running_speed_kmh: f64,
+    /// Leisure cycling speed on flat ground, km/h.
+    /// Env: `ROUTE_CYCLING_SPEED_KMH` (default 16.0)
+    pub cycling_speed_kmh: f64,
+    /// Extra walking minutes per 100 m climbed.
+    /// Env: `ROUTE_WALKING_CLIMB_MIN_PER_100M` (default 10.0)
+    pub walking_climb_min_per_100m: f64,
+    /// Extra running minutes per 100 m climbed.
+    /// Env: `ROUTE_RUNNING_CLIMB_MIN_PER_100M` (default 6.0)
+    pub running_climb_min_per_100m: f64,
+    /// Extra cycling minutes per 100 m climbed.
+    /// Env: `ROUTE_CYCLING_CLIMB_MIN_PER_100M` (default 6.0)
+    pub cycling_climb_min_per_100m: f64,
+
+    // --- Quality Tiers ---
+    // Classify scored routes into gold/silver/bronze badges. Routes below the
+    // bronze score get no tier.
+    /// Minimum route score (0–10) for a gold tier.
+    /// Env: `ROUTE_QUALITY_TIER_GOLD_SCORE` (default 8.0)
+    pub quality_tier_gold_score: f32,
+    /// Minimum route score (0–10) for a silver tier.
+    /// Env: `ROUTE_QUALITY_TIER_SILVER_SCORE` (default 6.5)
+    pub quality_tier_silver_score: f32,
+    /// Minimum route score (0–10) for a bronze tier.
+    /// Env: `ROUTE_QUALITY_TIER_BRONZE_SCORE` (default 5.0)
+    pub quality_tier_bronze_score: f32,
+    /// Minimum circularity a gold route must also reach.
+    /// Env: `ROUTE_QUALITY_TIER_GOLD_MIN_CIRCULARITY` (default 0.35)
+    pub quality_tier_gold_min_circularity: f32,
+    /// Maximum path overlap fraction allowed for gold and silver routes.
+    /// Env: `ROUTE_QUALITY_TIER_MAX_OVERLAP` (default 0.25)
+    pub quality_tier_max_overlap: f32,
+
     // --- POI Discovery Limits ---
     // Control how many POIs are fetched from the database before filtering.
     // Short routes scale linearly with distance; long routes scale with area
@@ -231,10 +368,12 @@ impl Default for RouteGeneratorConfig {
             min_poi_distance_km: 0.3,
             tolerance_level_relaxed: 0.3,
             tolerance_level_very_relaxed: 0.5,
+            leg_repair_enabled: true,
             max_route_generation_retries: 4,
             waypoints_count_short: 2,
             waypoints_count_medium: 3,
             waypoints_count_long: 4,
+            tsp_min_waypoints: TSP_MIN_WAYPOINTS,
             long_route_threshold_km: 8.0,
             poi_count_threshold_long: 3,
             waypoint_distance_multiplier_2wp: 0.50,
@@ -250,6 +389,21 @@ impl Default for RouteGeneratorConfig {
             poi_score_weight_variation: 0.05,
             metrics_overlap_threshold_m: 25.0,
             scoring_version: 1,
+            duration_requery_cycling: false,
+            amenities_min_distance_km: 8.0,
+            // Speed model
+            walking_speed_kmh: WALKING_SPEED_KMH,
+            running_speed_kmh: RUNNING_SPEED_KMH,
+            cycling_speed_kmh: CYCLING_SPEED_KMH,
+            walking_climb_min_per_100m: WALKING_CLIMB_MIN_PER_100M,
+            running_climb_min_per_100m: RUNNING_CLIMB_MIN_PER_100M,
+            cycling_climb_min_per_100m: CYCLING_CLIMB_MIN_PER_100M,
+            // Quality tiers
+            quality_tier_gold_score: 8.0,
+            quality_tier_silver_score: 6.5,
+            quality_tier_bronze_score: 5.0,
+            quality_tier_gold_min_circularity: 0.35,
+            quality_tier_max_overlap: 0.25,
             // POI discovery limits
             poi_limit_short_factor: 20.0,
             poi_limit_short_min: 50.0,
@@ -279,6 +433,23 @@ macro_rules! parse_env {
     };
 }
 
+// Declared after `parse_env!` so these configs can use it
+mod chaos;
+mod cors;
+mod degradation;
+mod optional;
+mod scheduler;
+
+pub use chaos::{ChaosConfig, FaultRates};
+pub use cors::{CorsConfig, CorsPreset};
+pub use degradation::{DegradationConfig, FallbackPolicy};
+pub use optional::{
+    ArtifactStoreConfig, ElevationConfig, EnvironmentalLayerConfig, OsrmConfig, PrivacyConfig,
+    RegionSyncConfig, RequestLogConfig, RequestLogSinkConfig, S3Config, ShadowConfig, TenantConfig,
+    ValhallaConfig, WarmCitiesConfig, WarmCity,
+};
+pub use scheduler::SchedulerConfig;
+
 impl RouteGeneratorConfig {
     pub fn from_env() -> Result<Self, String> {
         let d = Self::default();
@@ -305,6 +476,7 @@ impl RouteGeneratorConfig {
                 "ROUTE_TOLERANCE_LEVEL_VERY_RELAXED",
                 d.tolerance_level_very_relaxed
             ),
+            leg_repair_enabled: parse_env!("ROUTE_LEG_REPAIR", d.leg_repair_enabled),
             max_route_generation_retries: parse_env!(
                 "ROUTE_MAX_GENERATION_RETRIES",
                 d.max_route_generation_retries
@@ -318,6 +490,7 @@ impl RouteGeneratorConfig {
                 d.waypoints_count_medium
             ),
             waypoints_count_long: parse_env!("ROUTE_WAYPOINTS_COUNT_LONG", d.waypoints_count_long),
+            tsp_min_waypoints: parse_env!("ROUTE_TSP_MIN_WAYPOINTS", d.tsp_min_waypoints),
             long_route_threshold_km: parse_env!(
                 "ROUTE_LONG_ROUTE_THRESHOLD_KM",
                 d.long_route_threshold_km
@@ -370,6 +543,51 @@ impl RouteGeneratorConfig {
                 d.metrics_overlap_threshold_m
             ),
             scoring_version: parse_env!("ROUTE_SCORING_VERSION", d.scoring_version),
+            duration_requery_cycling: parse_env!(
+                "ROUTE_DURATION_REQUERY_CYCLING",
+                d.duration_requery_cycling
+            ),
+            amenities_min_distance_km: parse_env!(
+                "ROUTE_AMENITIES_MIN_DISTANCE_KM",
+                d.amenities_min_distance_km
+            ),
+            // Speed model
+            walking_speed_kmh: parse_env!("ROUTE_WALKING_SPEED_KMH", d.walking_speed_kmh),
+            running_speed_kmh: parse_env!("ROUTE_RUNNING_SPEED_KMH", d.running_speed_kmh),
+            cycling_speed_kmh: parse_env!("ROUTE_CYCLING_SPEED_KMH", d.cycling_speed_kmh),
+            walking_climb_min_per_100m: parse_env!(
+                "ROUTE_WALKING_CLIMB_MIN_PER_100M",
+                d.walking_climb_min_per_100m
+            ),
+            running_climb_min_per_100m: parse_env!(
+                "ROUTE_RUNNING_CLIMB_MIN_PER_100M",
+                d.running_climb_min_per_100m
+            ),
+            cycling_climb_min_per_100m: parse_env!(
+                "ROUTE_CYCLING_CLIMB_MIN_PER_100M",
+                d.cycling_climb_min_per_100m
+            ),
+            // Quality tiers
+            quality_tier_gold_score: parse_env!(
+                "ROUTE_QUALITY_TIER_GOLD_SCORE",
+                d.quality_tier_gold_score
+            ),
+            quality_tier_silver_score: parse_env!(
+                "ROUTE_QUALITY_TIER_SILVER_SCORE",
+                d.quality_tier_silver_score
+            ),
+            quality_tier_bronze_score: parse_env!(
+                "ROUTE_QUALITY_TIER_BRONZE_SCORE",
+                d.quality_tier_bronze_score
+            ),
+            quality_tier_gold_min_circularity: parse_env!(
+                "ROUTE_QUALITY_TIER_GOLD_MIN_CIRCULARITY",
+                d.quality_tier_gold_min_circularity
+            ),
+            quality_tier_max_overlap: parse_env!(
+                "ROUTE_QUALITY_TIER_MAX_OVERLAP",
+                d.quality_tier_max_overlap
+            ),
             // POI discovery limits
             poi_limit_short_factor: parse_env!(
                 "ROUTE_POI_LIMIT_SHORT_FACTOR",
@@ -410,6 +628,31 @@ impl RouteGeneratorConfig {
     }
 }
 
+impl RouteGeneratorConfig {
+    /// Short strategy label stored with evaluated routes, e.g. "advanced/v2"
+    pub fn strategy_label(&self) -> String {
+        format!("{}/v{}", self.poi_scoring_strategy, self.scoring_version)
+    }
+
+    /// The configured speed model for each profile
+    pub fn speed_models(&self) -> SpeedModels {
+        SpeedModels {
+            walking: SpeedModel {
+                flat_kmh: self.walking_speed_kmh,
+                climb_min_per_100m: self.walking_climb_min_per_100m,
+            },
+            running: SpeedModel {
+                flat_kmh: self.running_speed_kmh,
+                climb_min_per_100m: self.running_climb_min_per_100m,
+            },
+            cycling: SpeedModel {
+                flat_kmh: self.cycling_speed_kmh,
+                climb_min_per_100m: self.cycling_climb_min_per_100m,
+            },
+        }
+    }
+}
+
 impl Config {
     pub fn from_env() -> Result<Self, String> {
         dotenv::dotenv().ok();
@@ -424,15 +667,32 @@ impl Config {
             return Err("SNAP_RADIUS_M must be between 0 and 1000 meters".to_string());
         }
 
+        let port = env::var("PORT")
+            .unwrap_or_else(|_| DEFAULT_PORT.to_string())
+            .parse()
+            .map_err(|_| "Invalid PORT")?;
+
+        // Offline mode needs neither Postgres nor Mapbox
+        let region_db_path = env::var("REGION_DB_PATH").ok().filter(|p| !p.is_empty());
+        let required = |name: &'static str| match env::var(name) {
+            Ok(value) => Ok(value),
+            Err(_) if region_db_path.is_some() => Ok(String::new()),
+            Err(_) => Err(format!("{} must be set", name)),
+        };
+
         Ok(Config {
             host: env::var("HOST").unwrap_or_else(|_| DEFAULT_HOST.to_string()),
-            port: env::var("PORT")
-                .unwrap_or_else(|_| DEFAULT_PORT.to_string())
-                .parse()
-                .map_err(|_| "Invalid PORT")?,
-            database_url: env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set")?,
+            port,
+            database_url: required("DATABASE_URL")?,
+            database_pool_size: parse_env!("DATABASE_POOL_SIZE", DEFAULT_DATABASE_POOL_SIZE),
+            database_task_pool_size: parse_env!(
+                "DATABASE_TASK_POOL_SIZE",
+                DEFAULT_DATABASE_TASK_POOL_SIZE
+            ),
             redis_url: env::var("REDIS_URL").ok(), // Optional for now
-            mapbox_api_key: env::var("MAPBOX_API_KEY").map_err(|_| "MAPBOX_API_KEY must be set")?,
+            mapbox_api_key: required("MAPBOX_API_KEY")?,
+            region_db_path,
+            region_sync: RegionSyncConfig::from_env()?,
             route_cache_ttl: env::var("ROUTE_CACHE_TTL")
                 .unwrap_or_else(|_| DEFAULT_ROUTE_CACHE_TTL_SECONDS.to_string())
                 .parse()
@@ -441,12 +701,70 @@ impl Config {
                 .unwrap_or_else(|_| DEFAULT_POI_REGION_CACHE_TTL_SECONDS.to_string())
                 .parse()
                 .map_err(|_| "Invalid POI_REGION_CACHE_TTL")?,
+            cache_coord_precision: parse_env!(
+                "CACHE_COORD_PRECISION",
+                DEFAULT_CACHE_COORD_PRECISION
+            ),
+            cache_distance_bucket_km: parse_env!(
+                "CACHE_DISTANCE_BUCKET_KM",
+                DEFAULT_CACHE_DISTANCE_BUCKET_KM
+            ),
+            cache_compression: parse_env!("CACHE_COMPRESSION", CacheCompression::default()),
             snap_radius_m,
+            poi_geohash_prefilter: parse_env!("POI_GEOHASH_PREFILTER", false),
             mapbox_base_url: env::var("MAPBOX_BASE_URL").ok(),
+            environmental_layer: EnvironmentalLayerConfig::from_env()?,
+            elevation: ElevationConfig::from_env()?,
+            valhalla: ValhallaConfig::from_env()?,
+            osrm: OsrmConfig::from_env()?,
+            shadow: ShadowConfig::from_env()?,
+            request_log: RequestLogConfig::from_env()?,
+            privacy: PrivacyConfig::from_env()?,
+            warm_cities: WarmCitiesConfig::from_env()?,
+            evaluation_retention_days: env::var("EVALUATION_RETENTION_DAYS")
+                .ok()
+                .map(|s| s.parse().map_err(|_| "Invalid EVALUATION_RETENTION_DAYS"))
+                .transpose()?,
+            share_link_ttl_hours: parse_env!("SHARE_LINK_TTL_HOURS", DEFAULT_SHARE_LINK_TTL_HOURS),
+            api_key_auth: parse_env!("API_KEY_AUTH", false),
+            admin_token: env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
+            tenants: TenantConfig::from_env()?,
+            artifact_store: ArtifactStoreConfig::from_env(port)?,
+            event_fanout_channel: env::var("EVENT_FANOUT_CHANNEL").ok(),
+            scheduler: SchedulerConfig::from_env()?,
+            degradation: DegradationConfig::from_env()?,
+            chaos: ChaosConfig::from_env()?,
+            cors: CorsConfig::from_env()?,
             route_generator: RouteGeneratorConfig::from_env()?,
         })
     }
 
+    /// Serving POIs from a SQLite region database, without Postgres
+    pub fn is_offline(&self) -> bool {
+        self.region_db_path.is_some()
+    }
+
+    /// Catalog id of the `REGION_DB_PATH` region: its file stem, if that is
+    /// a valid id (letters, digits, `-` and `_`, as the region proxy uses)
+    pub fn region_id(&self) -> Option<&str> {
+        let stem = std::path::Path::new(self.region_db_path.as_deref()?)
+            .file_stem()?
+            .to_str()?;
+        let valid = !stem.is_empty()
+            && stem
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        valid.then_some(stem)
+    }
+
+    /// Granularity of loop route cache keys
+    pub fn cache_key_precision(&self) -> CacheKeyPrecision {
+        CacheKeyPrecision {
+            coord_decimals: self.cache_coord_precision,
+            distance_bucket_km: self.cache_distance_bucket_km,
+        }
+    }
+
     pub fn server_address(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
@@ -503,6 +821,7 @@ mod tests {
         assert_eq!(d.waypoints_count_short, 2);
         assert_eq!(d.waypoints_count_medium, 3);
         assert_eq!(d.waypoints_count_long, 4);
+        assert_eq!(d.tsp_min_waypoints, 5);
         assert_eq!(d.long_route_threshold_km, 8.0);
         assert_eq!(d.scoring_version, 1);
         assert_eq!(d.poi_scoring_strategy, ScoringStrategy::Advanced);
@@ -551,6 +870,34 @@ mod tests {
         unsafe { env::remove_var("ROUTE_SCORING_VERSION") };
     }
 
+    #[test]
+    #[serial]
+    fn warm_cities_from_env() {
+        unsafe {
+            env::set_var(
+                "WARM_CITIES",
+                "paris:48.8566:2.3522:12, lyon:45.764:4.8357:8",
+            )
+        };
+        let config = WarmCitiesConfig::from_env().unwrap().unwrap();
+        assert_eq!(config.cities.len(), 2);
+        assert_eq!(
+            config.cities[1],
+            WarmCity {
+                name: "lyon".to_string(),
+                lat: 45.764,
+                lng: 4.8357,
+                radius_km: 8.0,
+            }
+        );
+        assert_eq!(config.refresh_secs, DEFAULT_WARM_CITIES_REFRESH_SECS);
+
+        unsafe { env::set_var("WARM_CITIES", "paris:48.8566:2.3522") };
+        assert!(WarmCitiesConfig::from_env().is_err());
+        unsafe { env::remove_var("WARM_CITIES") };
+        assert!(WarmCitiesConfig::from_env().unwrap().is_none());
+    }
+
     // --- Config::server_address ---
 
     #[test]
@@ -559,14 +906,56 @@ mod tests {
             host: "127.0.0.1".to_string(),
             port: 8080,
             database_url: String::new(),
+            database_pool_size: 10,
+            database_task_pool_size: 4,
             redis_url: None,
             mapbox_api_key: String::new(),
+            region_db_path: None,
+            region_sync: None,
             route_cache_ttl: 0,
             poi_region_cache_ttl: 0,
+            cache_coord_precision: 3,
+            cache_distance_bucket_km: 0.5,
+            cache_compression: Default::default(),
             snap_radius_m: 100.0,
+            poi_geohash_prefilter: false,
             mapbox_base_url: None,
+            environmental_layer: None,
+            elevation: None,
+            valhalla: None,
+            osrm: None,
+            shadow: None,
+            request_log: None,
+            privacy: None,
+            warm_cities: None,
+            evaluation_retention_days: None,
+            share_link_ttl_hours: 168,
+            api_key_auth: false,
+            admin_token: None,
+            tenants: None,
+            artifact_store: None,
+            event_fanout_channel: None,
+            scheduler: SchedulerConfig::default(),
+            degradation: DegradationConfig::default(),
+            chaos: None,
+            cors: CorsConfig::default(),
             route_generator: RouteGeneratorConfig::default(),
         };
         assert_eq!(config.server_address(), "127.0.0.1:8080");
     }
+
+    // --- ShadowConfig ---
+
+    #[test]
+    fn shadow_candidate_overrides_primary() {
+        let primary = RouteGeneratorConfig::default();
+        let shadow = ShadowConfig {
+            sample_rate: 0.1,
+            poi_scoring_strategy: Some(ScoringStrategy::Simple),
+            scoring_version: None,
+        };
+        let candidate = shadow.candidate_config(&primary);
+        assert_eq!(primary.strategy_label(), "advanced/v1");
+        assert_eq!(candidate.strategy_label(), "simple/v1");
+    }
 }
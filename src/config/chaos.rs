@@ -0,0 +1,85 @@
+//! Fault injection for resilience testing (see
+//! [`FaultInjector`](crate::services::chaos::FaultInjector)).
+//!
+//! Off unless `CHAOS_ENABLED=true`. Never enable it in production: it makes
+//! Mapbox and Postgres calls fail on purpose.
+
+use crate::constants::{DEFAULT_CHAOS_SEED, DEFAULT_CHAOS_TIMEOUT_MS};
+use std::env;
+
+/// Faults injected into calls to one dependency
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultRates {
+    /// Delay added before every call. Env: `CHAOS_<DEP>_LATENCY_MS`
+    pub latency_ms: u64,
+    /// Fraction of calls failing like a 5xx / dropped connection.
+    /// Env: `CHAOS_<DEP>_ERROR_RATE`
+    pub error_rate: f64,
+    /// Fraction of calls hanging for the chaos timeout, then failing.
+    /// Env: `CHAOS_<DEP>_TIMEOUT_RATE`
+    pub timeout_rate: f64,
+    /// The first N calls fail regardless of the rates. Env: `CHAOS_<DEP>_FAIL_FIRST`
+    pub fail_first: u64,
+}
+
+impl FaultRates {
+    fn from_env(dependency: &str) -> Result<Self, String> {
+        let prefix = format!("CHAOS_{}", dependency);
+        let var = |suffix: &str| env::var(format!("{}_{}", prefix, suffix)).ok();
+        let invalid = |suffix: &str| format!("Invalid {}_{}", prefix, suffix);
+        Ok(FaultRates {
+            latency_ms: var("LATENCY_MS")
+                .map(|s| s.parse().map_err(|_| invalid("LATENCY_MS")))
+                .transpose()?
+                .unwrap_or(0),
+            error_rate: var("ERROR_RATE")
+                .map(|s| s.parse().map_err(|_| invalid("ERROR_RATE")))
+                .transpose()?
+                .unwrap_or(0.0),
+            timeout_rate: var("TIMEOUT_RATE")
+                .map(|s| s.parse().map_err(|_| invalid("TIMEOUT_RATE")))
+                .transpose()?
+                .unwrap_or(0.0),
+            fail_first: var("FAIL_FIRST")
+                .map(|s| s.parse().map_err(|_| invalid("FAIL_FIRST")))
+                .transpose()?
+                .unwrap_or(0),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosConfig {
+    /// Seed for the fault dice, so a run is reproducible. Env: `CHAOS_SEED` (default 0)
+    pub seed: u64,
+    /// How long an injected timeout hangs before failing.
+    /// Env: `CHAOS_TIMEOUT_MS` (default 5000)
+    pub timeout_ms: u64,
+    pub mapbox: FaultRates,
+    pub postgres: FaultRates,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        ChaosConfig {
+            seed: DEFAULT_CHAOS_SEED,
+            timeout_ms: DEFAULT_CHAOS_TIMEOUT_MS,
+            mapbox: FaultRates::default(),
+            postgres: FaultRates::default(),
+        }
+    }
+}
+
+impl ChaosConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        if !parse_env!("CHAOS_ENABLED", false) {
+            return Ok(None);
+        }
+        Ok(Some(ChaosConfig {
+            seed: parse_env!("CHAOS_SEED", DEFAULT_CHAOS_SEED),
+            timeout_ms: parse_env!("CHAOS_TIMEOUT_MS", DEFAULT_CHAOS_TIMEOUT_MS),
+            mapbox: FaultRates::from_env("MAPBOX")?,
+            postgres: FaultRates::from_env("POSTGRES")?,
+        }))
+    }
+}
@@ -0,0 +1,219 @@
+//! Cross-origin (CORS) policy for the API server.
+//!
+//! `CORS_PRESET` picks a baseline (`production` by default, `development` for
+//! the old allow-everything behavior); the other `CORS_*` variables override
+//! individual fields of it.
+
+use crate::constants::DEFAULT_CORS_MAX_AGE_SECONDS;
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CorsPreset {
+    /// Any origin, method and header; no credentials
+    Development,
+    /// Same-origin only until origins are listed; API methods and headers only
+    #[default]
+    Production,
+}
+
+impl std::str::FromStr for CorsPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "development" | "dev" => Ok(CorsPreset::Development),
+            "production" | "prod" => Ok(CorsPreset::Production),
+            _ => Err(format!(
+                "Invalid CORS preset: {}. Use 'development' or 'production'",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for CorsPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorsPreset::Development => write!(f, "development"),
+            CorsPreset::Production => write!(f, "production"),
+        }
+    }
+}
+
+/// For each list, `None` allows anything (`*`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsConfig {
+    /// Env: `CORS_PRESET` (default production)
+    pub preset: CorsPreset,
+    /// Exact origins, e.g. `https://app.example.com`. Env: `CORS_ALLOWED_ORIGINS`
+    pub allowed_origins: Option<Vec<String>>,
+    /// Env: `CORS_ALLOWED_METHODS`
+    pub allowed_methods: Option<Vec<String>>,
+    /// Env: `CORS_ALLOWED_HEADERS`
+    pub allowed_headers: Option<Vec<String>>,
+    /// Allow cookies/auth headers on cross-origin requests; needs explicit
+    /// origins, methods and headers. Env: `CORS_ALLOW_CREDENTIALS`
+    pub allow_credentials: bool,
+    /// How long browsers may cache preflight results. Env: `CORS_MAX_AGE_SECS`
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig::preset(CorsPreset::default())
+    }
+}
+
+impl CorsConfig {
+    pub fn preset(preset: CorsPreset) -> Self {
+        let list = |items: &[&str]| Some(items.iter().map(|s| s.to_string()).collect());
+        match preset {
+            CorsPreset::Development => CorsConfig {
+                preset,
+                allowed_origins: None,
+                allowed_methods: None,
+                allowed_headers: None,
+                allow_credentials: false,
+                max_age_secs: DEFAULT_CORS_MAX_AGE_SECONDS,
+            },
+            CorsPreset::Production => CorsConfig {
+                preset,
+                allowed_origins: Some(Vec::new()),
+                allowed_methods: list(&["GET", "POST", "OPTIONS"]),
+                allowed_headers: list(&[
+                    "content-type",
+                    "authorization",
+                    "x-api-key",
+                    "if-none-match",
+                ]),
+                allow_credentials: false,
+                max_age_secs: DEFAULT_CORS_MAX_AGE_SECONDS,
+            },
+        }
+    }
+
+    pub(super) fn from_env() -> Result<Self, String> {
+        let preset: CorsPreset = match env::var("CORS_PRESET") {
+            Ok(s) => s.parse()?,
+            Err(_) => CorsPreset::default(),
+        };
+        let d = CorsConfig::preset(preset);
+        let list = |name: &str, default: Option<Vec<String>>| match env::var(name) {
+            Ok(value) => parse_list(&value),
+            Err(_) => default,
+        };
+
+        Ok(CorsConfig {
+            preset,
+            allowed_origins: list("CORS_ALLOWED_ORIGINS", d.allowed_origins),
+            allowed_methods: list("CORS_ALLOWED_METHODS", d.allowed_methods),
+            allowed_headers: list("CORS_ALLOWED_HEADERS", d.allowed_headers),
+            allow_credentials: parse_env!("CORS_ALLOW_CREDENTIALS", d.allow_credentials),
+            max_age_secs: parse_env!("CORS_MAX_AGE_SECS", d.max_age_secs),
+        })
+    }
+
+    /// Whether any of origins, methods or headers is a wildcard
+    pub fn has_wildcard(&self) -> bool {
+        self.allowed_origins.is_none()
+            || self.allowed_methods.is_none()
+            || self.allowed_headers.is_none()
+    }
+}
+
+/// Comma-separated list; `*` means anything
+fn parse_list(value: &str) -> Option<Vec<String>> {
+    if value.trim() == "*" {
+        return None;
+    }
+    Some(
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    const VARS: [&str; 6] = [
+        "CORS_PRESET",
+        "CORS_ALLOWED_ORIGINS",
+        "CORS_ALLOWED_METHODS",
+        "CORS_ALLOWED_HEADERS",
+        "CORS_ALLOW_CREDENTIALS",
+        "CORS_MAX_AGE_SECS",
+    ];
+
+    fn clear_env() {
+        for var in VARS {
+            env::remove_var(var);
+        }
+    }
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(parse_list("*"), None);
+        assert_eq!(
+            parse_list("https://a.example, https://b.example,"),
+            Some(vec![
+                "https://a.example".to_string(),
+                "https://b.example".to_string()
+            ])
+        );
+        assert_eq!(parse_list(""), Some(vec![]));
+    }
+
+    #[test]
+    fn test_presets() {
+        let production = CorsConfig::default();
+        assert_eq!(production.preset, CorsPreset::Production);
+        assert_eq!(production.allowed_origins, Some(vec![]));
+        assert!(!production.has_wildcard());
+
+        let development = CorsConfig::preset(CorsPreset::Development);
+        assert!(development.has_wildcard());
+        assert!(!development.allow_credentials);
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_env_defaults_to_production() {
+        clear_env();
+        assert_eq!(CorsConfig::from_env().unwrap(), CorsConfig::default());
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_env_overrides_preset() {
+        clear_env();
+        env::set_var("CORS_PRESET", "development");
+        env::set_var("CORS_ALLOWED_ORIGINS", "https://app.example.com");
+        env::set_var("CORS_ALLOW_CREDENTIALS", "true");
+        let config = CorsConfig::from_env().unwrap();
+        clear_env();
+
+        assert_eq!(config.preset, CorsPreset::Development);
+        assert_eq!(
+            config.allowed_origins,
+            Some(vec!["https://app.example.com".to_string()])
+        );
+        assert_eq!(config.allowed_methods, None);
+        assert!(config.allow_credentials);
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_env_invalid_preset() {
+        clear_env();
+        env::set_var("CORS_PRESET", "staging");
+        let result = CorsConfig::from_env();
+        clear_env();
+        assert!(result.is_err());
+    }
+}
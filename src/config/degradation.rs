@@ -0,0 +1,121 @@
+//! What happens when a dependency is unavailable (see
+//! [`DependencyGuard`](crate::services::dependency_guard::DependencyGuard)).
+//!
+//! | Dependency | `fail`                   | `degrade`                   | `cached-only`             |
+//! |------------|--------------------------|-----------------------------|---------------------------|
+//! | Redis      | refuse to start          | in-memory cache (default)   | -                         |
+//! | Postgres   | request errors (default) | geometric loop without POIs | cache hits only while down |
+//! | Mapbox     | request errors (default) | -                           | cache hits only while down |
+//! | Valhalla   | request errors (default) | -                           | cache hits only while down |
+//! | OSRM       | request errors (default) | -                           | cache hits only while down |
+//!
+//! Overpass is not a runtime dependency (POIs come from the local OSM import),
+//! so it has no policy.
+
+use crate::constants::{
+    DEFAULT_DEPENDENCY_DOWN_COOLDOWN_SECS, DEFAULT_POSTGRES_TIMEOUT_MS, DEFAULT_REDIS_TIMEOUT_MS,
+};
+use std::env;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FallbackPolicy {
+    /// Surface the failure
+    Fail,
+    /// Keep serving with reduced functionality
+    Degrade,
+    /// While the dependency is down, serve cached routes and reject the rest
+    /// with 503 instead of waiting on it
+    CachedOnly,
+}
+
+impl std::str::FromStr for FallbackPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fail" => Ok(FallbackPolicy::Fail),
+            "degrade" => Ok(FallbackPolicy::Degrade),
+            "cached-only" | "cached_only" => Ok(FallbackPolicy::CachedOnly),
+            _ => Err(format!(
+                "Invalid fallback policy: {}. Use 'fail', 'degrade' or 'cached-only'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FallbackPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FallbackPolicy::Fail => "fail",
+            FallbackPolicy::Degrade => "degrade",
+            FallbackPolicy::CachedOnly => "cached-only",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DegradationConfig {
+    /// Env: `FALLBACK_REDIS` (default degrade)
+    pub redis: FallbackPolicy,
+    /// Env: `FALLBACK_POSTGRES` (default fail)
+    pub postgres: FallbackPolicy,
+    /// Env: `FALLBACK_MAPBOX` (default fail)
+    pub mapbox: FallbackPolicy,
+    /// Env: `FALLBACK_VALHALLA` (default fail), when `VALHALLA_URL` is set
+    pub valhalla: FallbackPolicy,
+    /// Env: `FALLBACK_OSRM` (default fail), when `OSRM_URL` is set
+    pub osrm: FallbackPolicy,
+    /// A failed dependency counts as down this long after its last failure.
+    /// Env: `DEPENDENCY_DOWN_COOLDOWN_SECS` (default 30)
+    pub down_cooldown_secs: u64,
+    /// Budget per POI query; slower ones fail as a Postgres timeout.
+    /// Env: `POSTGRES_TIMEOUT_MS` (default 5000)
+    pub postgres_timeout_ms: u64,
+    /// Budget per Redis command; slower ones count as a miss.
+    /// Env: `REDIS_TIMEOUT_MS` (default 500)
+    pub redis_timeout_ms: u64,
+}
+
+impl Default for DegradationConfig {
+    /// Today's behavior: in-memory cache without Redis, errors otherwise
+    fn default() -> Self {
+        DegradationConfig {
+            redis: FallbackPolicy::Degrade,
+            postgres: FallbackPolicy::Fail,
+            mapbox: FallbackPolicy::Fail,
+            valhalla: FallbackPolicy::Fail,
+            osrm: FallbackPolicy::Fail,
+            down_cooldown_secs: DEFAULT_DEPENDENCY_DOWN_COOLDOWN_SECS,
+            postgres_timeout_ms: DEFAULT_POSTGRES_TIMEOUT_MS,
+            redis_timeout_ms: DEFAULT_REDIS_TIMEOUT_MS,
+        }
+    }
+}
+
+impl DegradationConfig {
+    pub(super) fn from_env() -> Result<Self, String> {
+        let defaults = DegradationConfig::default();
+        let policy = |name: &str, default: FallbackPolicy| {
+            env::var(name)
+                .ok()
+                .map(|s| s.parse().map_err(|e| format!("{}: {}", name, e)))
+                .transpose()
+                .map(|p| p.unwrap_or(default))
+        };
+        Ok(DegradationConfig {
+            redis: policy("FALLBACK_REDIS", defaults.redis)?,
+            postgres: policy("FALLBACK_POSTGRES", defaults.postgres)?,
+            mapbox: policy("FALLBACK_MAPBOX", defaults.mapbox)?,
+            valhalla: policy("FALLBACK_VALHALLA", defaults.valhalla)?,
+            osrm: policy("FALLBACK_OSRM", defaults.osrm)?,
+            down_cooldown_secs: parse_env!(
+                "DEPENDENCY_DOWN_COOLDOWN_SECS",
+                defaults.down_cooldown_secs
+            ),
+            postgres_timeout_ms: parse_env!("POSTGRES_TIMEOUT_MS", defaults.postgres_timeout_ms),
+            redis_timeout_ms: parse_env!("REDIS_TIMEOUT_MS", defaults.redis_timeout_ms),
+        })
+    }
+}
@@ -0,0 +1,407 @@
+//! Optional server features, each enabled by its own env var.
+
+use super::{RouteGeneratorConfig, ScoringStrategy};
+use crate::constants::*;
+use std::env;
+
+/// Optional noise/air-quality raster used for exposure metrics.
+/// Enabled by setting `ENV_LAYER_PATH` to an ESRI ASCII grid (`.asc`).
+#[derive(Debug, Clone)]
+pub struct EnvironmentalLayerConfig {
+    /// Env: `ENV_LAYER_PATH`
+    pub path: String,
+    /// Env: `ENV_LAYER_NAME` (default "noise")
+    pub name: String,
+    /// Raw value mapped to exposure 0.0. Env: `ENV_LAYER_MIN` (default 45.0)
+    pub min_value: f64,
+    /// Raw value mapped to exposure 1.0. Env: `ENV_LAYER_MAX` (default 75.0)
+    pub max_value: f64,
+}
+
+impl EnvironmentalLayerConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        let Ok(path) = env::var("ENV_LAYER_PATH") else {
+            return Ok(None);
+        };
+        Ok(Some(EnvironmentalLayerConfig {
+            path,
+            name: env::var("ENV_LAYER_NAME").unwrap_or_else(|_| "noise".to_string()),
+            min_value: parse_env!("ENV_LAYER_MIN", DEFAULT_ENV_LAYER_MIN_VALUE),
+            max_value: parse_env!("ENV_LAYER_MAX", DEFAULT_ENV_LAYER_MAX_VALUE),
+        }))
+    }
+}
+
+/// Optional elevation lookups for `Route.elevation_gain_m` and the
+/// `prefer_flat` / `max_elevation_gain_m` preferences. Enabled by setting
+/// `ELEVATION_API_URL` to an Open-Elevation compatible server.
+#[derive(Debug, Clone)]
+pub struct ElevationConfig {
+    /// Env: `ELEVATION_API_URL` (e.g. `https://api.open-elevation.com`)
+    pub api_url: String,
+}
+
+impl ElevationConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        Ok(env::var("ELEVATION_API_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .map(|api_url| ElevationConfig { api_url }))
+    }
+}
+
+/// Optional Valhalla routing server used for directions instead of Mapbox,
+/// enabling `preferences.costing`. Enabled by setting `VALHALLA_URL`.
+#[derive(Debug, Clone)]
+pub struct ValhallaConfig {
+    /// Env: `VALHALLA_URL` (e.g. `http://localhost:8002`)
+    pub url: String,
+}
+
+impl ValhallaConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        Ok(env::var("VALHALLA_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .map(|url| ValhallaConfig { url }))
+    }
+}
+
+/// Optional OSRM routing server used for directions instead of Mapbox,
+/// typically alongside `REGION_DB_PATH` for offline deployments.
+/// Enabled by setting `OSRM_URL`.
+#[derive(Debug, Clone)]
+pub struct OsrmConfig {
+    /// Env: `OSRM_URL` (e.g. `http://localhost:5000`)
+    pub url: String,
+}
+
+impl OsrmConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        Ok(env::var("OSRM_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .map(|url| OsrmConfig { url }))
+    }
+}
+
+/// Shadow-mode evaluation: a sample of live requests also runs a candidate
+/// strategy in the background, and both results are stored in the evaluation
+/// tables. Enabled by setting `SHADOW_SAMPLE_RATE` above 0.
+#[derive(Debug, Clone)]
+pub struct ShadowConfig {
+    /// Fraction of generated (non-cached) requests to shadow. Env: `SHADOW_SAMPLE_RATE`
+    pub sample_rate: f64,
+    /// Env: `SHADOW_POI_SCORING_STRATEGY` (default: same as primary)
+    pub poi_scoring_strategy: Option<ScoringStrategy>,
+    /// Env: `SHADOW_SCORING_VERSION` (default: same as primary)
+    pub scoring_version: Option<u32>,
+}
+
+impl ShadowConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        let sample_rate: f64 = parse_env!("SHADOW_SAMPLE_RATE", 0.0);
+        if sample_rate <= 0.0 {
+            return Ok(None);
+        }
+        Ok(Some(ShadowConfig {
+            sample_rate,
+            poi_scoring_strategy: env::var("SHADOW_POI_SCORING_STRATEGY")
+                .ok()
+                .map(|s| s.parse())
+                .transpose()?,
+            scoring_version: env::var("SHADOW_SCORING_VERSION")
+                .ok()
+                .map(|s| s.parse().map_err(|_| "Invalid SHADOW_SCORING_VERSION"))
+                .transpose()?,
+        }))
+    }
+
+    /// The primary config with the candidate's overrides applied
+    pub fn candidate_config(&self, primary: &RouteGeneratorConfig) -> RouteGeneratorConfig {
+        let mut candidate = primary.clone();
+        if let Some(ref strategy) = self.poi_scoring_strategy {
+            candidate.poi_scoring_strategy = strategy.clone();
+        }
+        if let Some(version) = self.scoring_version {
+            candidate.scoring_version = version;
+        }
+        candidate
+    }
+}
+
+/// Sampled request/response logging to an NDJSON corpus for offline replay.
+/// Enabled by setting `REQUEST_LOG_PATH` or `REQUEST_LOG_STORE_PREFIX`.
+#[derive(Debug, Clone)]
+pub struct RequestLogConfig {
+    pub sink: RequestLogSinkConfig,
+    /// Fraction of loop requests logged. Env: `REQUEST_LOG_SAMPLE_RATE` (default 0.01)
+    pub sample_rate: f64,
+}
+
+/// Where request log records are written
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestLogSinkConfig {
+    /// File that records are appended to. Env: `REQUEST_LOG_PATH`
+    File(String),
+    /// Batches of records uploaded to the artifact store under this key
+    /// prefix. Env: `REQUEST_LOG_STORE_PREFIX`
+    ObjectStore { prefix: String },
+}
+
+impl RequestLogConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        let sink = match (
+            env::var("REQUEST_LOG_PATH"),
+            env::var("REQUEST_LOG_STORE_PREFIX"),
+        ) {
+            (Ok(_), Ok(_)) => {
+                return Err(
+                    "Set either REQUEST_LOG_PATH or REQUEST_LOG_STORE_PREFIX, not both".to_string(),
+                )
+            }
+            (Ok(path), Err(_)) => RequestLogSinkConfig::File(path),
+            (Err(_), Ok(prefix)) => RequestLogSinkConfig::ObjectStore { prefix },
+            (Err(_), Err(_)) => return Ok(None),
+        };
+        Ok(Some(RequestLogConfig {
+            sink,
+            sample_rate: parse_env!("REQUEST_LOG_SAMPLE_RATE", DEFAULT_REQUEST_LOG_SAMPLE_RATE),
+        }))
+    }
+}
+
+/// Cities whose POIs are kept in memory, see
+/// [`crate::services::poi_snapshot`]. Enabled by setting `WARM_CITIES` to a
+/// comma-separated list of `name:lat:lng:radius_km` entries.
+#[derive(Debug, Clone)]
+pub struct WarmCitiesConfig {
+    pub cities: Vec<WarmCity>,
+    /// Env: `WARM_CITIES_REFRESH_SECS` (default 900)
+    pub refresh_secs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmCity {
+    pub name: String,
+    pub lat: f64,
+    pub lng: f64,
+    pub radius_km: f64,
+}
+
+impl WarmCitiesConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        let Ok(value) = env::var("WARM_CITIES") else {
+            return Ok(None);
+        };
+        let cities = value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                WarmCity::parse(entry).ok_or_else(|| {
+                    format!(
+                        "Invalid WARM_CITIES entry '{}': expected name:lat:lng:radius_km",
+                        entry
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(WarmCitiesConfig {
+            cities,
+            refresh_secs: parse_env!("WARM_CITIES_REFRESH_SECS", DEFAULT_WARM_CITIES_REFRESH_SECS),
+        }))
+    }
+}
+
+impl WarmCity {
+    fn parse(entry: &str) -> Option<Self> {
+        let mut parts = entry.split(':').map(str::trim);
+        let city = WarmCity {
+            name: parts.next().filter(|name| !name.is_empty())?.to_string(),
+            lat: parts.next()?.parse().ok()?,
+            lng: parts.next()?.parse().ok()?,
+            radius_km: parts.next()?.parse().ok()?,
+        };
+        parts.next().is_none().then_some(city)
+    }
+}
+
+/// Offline region updates: the server downloads its region database from a
+/// region proxy's catalog and swaps in newer builds while running. Enabled
+/// by setting `REGION_CATALOG_URL` alongside `REGION_DB_PATH`, whose file
+/// stem is the region id.
+#[derive(Debug, Clone)]
+pub struct RegionSyncConfig {
+    /// Proxy base URL. Env: `REGION_CATALOG_URL` (e.g. `https://regions.example.com`)
+    pub catalog_url: String,
+    /// Bearer token for the proxy. Env: `REGION_CATALOG_API_KEY`
+    pub api_key: Option<String>,
+    /// Env: `REGION_SYNC_INTERVAL_SECS` (default 3600)
+    pub interval_secs: u64,
+    /// Hex Ed25519 public key downloads must be signed with.
+    /// Env: `REGION_TRUSTED_KEY` (default: SHA-256 check only)
+    pub trusted_key: Option<String>,
+}
+
+impl RegionSyncConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        let Some(catalog_url) = env::var("REGION_CATALOG_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+        else {
+            return Ok(None);
+        };
+        Ok(Some(RegionSyncConfig {
+            catalog_url: catalog_url.trim_end_matches('/').to_string(),
+            api_key: env::var("REGION_CATALOG_API_KEY")
+                .ok()
+                .filter(|k| !k.is_empty()),
+            interval_secs: parse_env!(
+                "REGION_SYNC_INTERVAL_SECS",
+                DEFAULT_REGION_SYNC_INTERVAL_SECS
+            ),
+            trusted_key: env::var("REGION_TRUSTED_KEY")
+                .ok()
+                .filter(|k| !k.is_empty()),
+        }))
+    }
+}
+
+/// Start-location privacy: stored start points are jittered and logged ones
+/// rounded. Enabled by `LOCATION_PRIVACY=true`.
+#[derive(Debug, Clone)]
+pub struct PrivacyConfig {
+    /// Env: `LOCATION_PRIVACY_JITTER_M` (default 100)
+    pub jitter_m: f64,
+    /// Env: `LOCATION_PRIVACY_LOG_DECIMALS` (default 2)
+    pub log_decimals: i32,
+}
+
+impl PrivacyConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        if !parse_env!("LOCATION_PRIVACY", false) {
+            return Ok(None);
+        }
+        Ok(Some(PrivacyConfig {
+            jitter_m: parse_env!(
+                "LOCATION_PRIVACY_JITTER_M",
+                DEFAULT_LOCATION_PRIVACY_JITTER_M
+            ),
+            log_decimals: parse_env!(
+                "LOCATION_PRIVACY_LOG_DECIMALS",
+                DEFAULT_LOCATION_PRIVACY_LOG_DECIMALS
+            ),
+        }))
+    }
+}
+
+/// API keys mapped to tenants, so one deployment can serve several apps with
+/// separate namespaces. Enabled by setting `TENANT_API_KEYS` to a
+/// comma-separated list of `tenant:key` pairs.
+#[derive(Debug, Clone)]
+pub struct TenantConfig {
+    /// (tenant, API key) pairs. A tenant may have several keys
+    pub api_keys: Vec<(String, String)>,
+}
+
+impl TenantConfig {
+    pub(super) fn from_env() -> Result<Option<Self>, String> {
+        let Ok(value) = env::var("TENANT_API_KEYS") else {
+            return Ok(None);
+        };
+        let api_keys = value
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                pair.split_once(':')
+                    .map(|(tenant, key)| (tenant.trim().to_string(), key.trim().to_string()))
+                    .ok_or_else(|| "Invalid TENANT_API_KEYS: expected tenant:key pairs".to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(TenantConfig { api_keys }))
+    }
+}
+
+/// Storage for large artifacts (traces, exports, previews), see
+/// [`crate::storage`]. Enabled by setting `ARTIFACT_STORE` to `local` or `s3`.
+#[derive(Debug, Clone)]
+pub enum ArtifactStoreConfig {
+    Local {
+        /// Env: `ARTIFACT_DIR` (default "./artifacts")
+        dir: String,
+        /// Base of signed URLs, as clients reach this server.
+        /// Env: `ARTIFACT_PUBLIC_URL` (default `http://localhost:{PORT}`)
+        public_base_url: String,
+        /// HMAC key for signed URLs. Env: `ARTIFACT_SIGNING_KEY` (default:
+        /// random per process, so links do not survive a restart)
+        signing_key: Option<String>,
+    },
+    S3(S3Config),
+}
+
+/// S3-compatible bucket (AWS, MinIO, R2, ...)
+#[derive(Clone)]
+pub struct S3Config {
+    /// Env: `S3_ENDPOINT` (default `https://s3.{region}.amazonaws.com`)
+    pub endpoint: String,
+    /// Env: `S3_BUCKET`
+    pub bucket: String,
+    /// Env: `S3_REGION` (default "us-east-1")
+    pub region: String,
+    /// Env: `S3_ACCESS_KEY_ID`
+    pub access_key_id: String,
+    /// Env: `S3_SECRET_ACCESS_KEY`
+    pub secret_access_key: String,
+    /// `{endpoint}/{bucket}/{key}` URLs instead of `{bucket}.{endpoint}/{key}`;
+    /// most self-hosted stores need this. Env: `S3_PATH_STYLE` (default false)
+    pub path_style: bool,
+}
+
+impl std::fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field("path_style", &self.path_style)
+            .finish()
+    }
+}
+
+impl ArtifactStoreConfig {
+    pub(super) fn from_env(port: u16) -> Result<Option<Self>, String> {
+        let Ok(backend) = env::var("ARTIFACT_STORE") else {
+            return Ok(None);
+        };
+        match backend.to_lowercase().as_str() {
+            "local" => Ok(Some(ArtifactStoreConfig::Local {
+                dir: env::var("ARTIFACT_DIR").unwrap_or_else(|_| "./artifacts".to_string()),
+                public_base_url: env::var("ARTIFACT_PUBLIC_URL")
+                    .unwrap_or_else(|_| format!("http://localhost:{}", port)),
+                signing_key: env::var("ARTIFACT_SIGNING_KEY").ok(),
+            })),
+            "s3" => {
+                let region = env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+                let required =
+                    |name: &str| env::var(name).map_err(|_| format!("{} must be set", name));
+                Ok(Some(ArtifactStoreConfig::S3(S3Config {
+                    endpoint: env::var("S3_ENDPOINT")
+                        .unwrap_or_else(|_| format!("https://s3.{}.amazonaws.com", region)),
+                    bucket: required("S3_BUCKET")?,
+                    region,
+                    access_key_id: required("S3_ACCESS_KEY_ID")?,
+                    secret_access_key: required("S3_SECRET_ACCESS_KEY")?,
+                    path_style: parse_env!("S3_PATH_STYLE", false),
+                })))
+            }
+            other => Err(format!(
+                "Invalid ARTIFACT_STORE '{}': expected local or s3",
+                other
+            )),
+        }
+    }
+}
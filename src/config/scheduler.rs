@@ -0,0 +1,46 @@
+//! Schedules for background tasks (see [`crate::scheduler`]).
+//!
+//! Each task runs on its built-in default schedule unless
+//! `SCHEDULE_<TASK NAME>` overrides it, e.g.
+//! `SCHEDULE_EVALUATION_RETENTION="0 3 * * *"`, or `off` to disable it.
+
+use crate::constants::DEFAULT_SCHEDULER_MAX_JITTER_SECS;
+use crate::scheduler::Schedule;
+use std::collections::BTreeMap;
+use std::env;
+
+#[derive(Debug, Clone, Default)]
+pub struct SchedulerConfig {
+    /// Per-task overrides by lowercase task name; `None` disables the task
+    pub schedules: BTreeMap<String, Option<Schedule>>,
+    /// Each run is delayed by up to this much so replicas spread out.
+    /// Env: `SCHEDULER_MAX_JITTER_SECS` (default 30)
+    pub max_jitter_secs: u64,
+}
+
+impl SchedulerConfig {
+    pub(super) fn from_env() -> Result<Self, String> {
+        let mut schedules = BTreeMap::new();
+        for (name, value) in env::vars() {
+            let Some(task) = name.strip_prefix("SCHEDULE_") else {
+                continue;
+            };
+            let schedule = match value.trim() {
+                "off" | "disabled" => None,
+                value => Some(
+                    value
+                        .parse()
+                        .map_err(|e| format!("Invalid {}: {}", name, e))?,
+                ),
+            };
+            schedules.insert(task.to_lowercase(), schedule);
+        }
+        Ok(SchedulerConfig {
+            schedules,
+            max_jitter_secs: parse_env!(
+                "SCHEDULER_MAX_JITTER_SECS",
+                DEFAULT_SCHEDULER_MAX_JITTER_SECS
+            ),
+        })
+    }
+}
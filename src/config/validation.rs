@@ -0,0 +1,1243 @@
+//! Cross-field checks for [`Config`] and [`RouteGeneratorConfig`].
+//!
+//! `from_env` only checks that each variable parses; these checks catch
+//! tuning that parses fine but makes no sense together (e.g. a relaxed
+//! tolerance wider than the very-relaxed one).
+
+use super::{
+    ArtifactStoreConfig, Config, CorsConfig, FallbackPolicy, RequestLogSinkConfig,
+    RouteGeneratorConfig, ScoringStrategy,
+};
+use crate::constants::{
+    MAPBOX_MAX_INTERMEDIATE_WAYPOINTS, MAX_WARM_CITY_RADIUS_KM, POI_SCORE_WEIGHT_SUM_MAX,
+    POI_SCORE_WEIGHT_SUM_MIN, SHARE_LINK_MAX_TTL_HOURS,
+};
+use crate::models::Coordinates;
+use crate::services::tenant::TenantId;
+use axum::http::{HeaderName, Method};
+use std::fmt;
+
+/// One violated configuration invariant
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    /// Struct field the issue is about (first field for cross-field checks)
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Collects issues while running checks
+#[derive(Default)]
+struct Checker {
+    issues: Vec<ConfigIssue>,
+}
+
+impl Checker {
+    fn check(&mut self, ok: bool, field: &'static str, message: impl Into<String>) {
+        if !ok {
+            self.issues.push(ConfigIssue {
+                field,
+                message: message.into(),
+            });
+        }
+    }
+
+    fn positive(&mut self, value: f64, field: &'static str) {
+        self.check(value > 0.0, field, format!("must be > 0 (got {})", value));
+    }
+
+    fn fraction(&mut self, value: f32, field: &'static str) {
+        self.check(
+            (0.0..=1.0).contains(&value),
+            field,
+            format!("must be between 0 and 1 (got {})", value),
+        );
+    }
+
+    fn finish(self) -> Result<(), Vec<ConfigIssue>> {
+        if self.issues.is_empty() {
+            Ok(())
+        } else {
+            Err(self.issues)
+        }
+    }
+}
+
+impl RouteGeneratorConfig {
+    /// Check cross-field invariants. Returns every violated invariant, not just the first.
+    pub fn validate(&self) -> Result<(), Vec<ConfigIssue>> {
+        let mut c = Checker::default();
+
+        // POI search & filtering
+        c.positive(
+            self.poi_search_radius_multiplier,
+            "poi_search_radius_multiplier",
+        );
+        c.positive(
+            self.waypoint_distance_multiplier,
+            "waypoint_distance_multiplier",
+        );
+        c.positive(
+            self.max_poi_distance_multiplier,
+            "max_poi_distance_multiplier",
+        );
+        c.check(
+            self.min_poi_distance_km >= 0.0,
+            "min_poi_distance_km",
+            "must be >= 0",
+        );
+
+        // Tolerance levels
+        c.positive(self.tolerance_level_relaxed, "tolerance_level_relaxed");
+        c.check(
+            self.tolerance_level_relaxed < self.tolerance_level_very_relaxed,
+            "tolerance_level_relaxed",
+            format!(
+                "must be smaller than tolerance_level_very_relaxed ({} >= {})",
+                self.tolerance_level_relaxed, self.tolerance_level_very_relaxed
+            ),
+        );
+        c.check(
+            self.max_route_generation_retries >= 1,
+            "max_route_generation_retries",
+            "must be at least 1",
+        );
+
+        // Waypoint counts and distances
+        c.check(
+            self.waypoints_count_short >= 1
+                && self.waypoints_count_short <= self.waypoints_count_medium
+                && self.waypoints_count_medium <= self.waypoints_count_long,
+            "waypoints_count_short",
+            format!(
+                "must satisfy 1 <= short <= medium <= long (got {}, {}, {})",
+                self.waypoints_count_short, self.waypoints_count_medium, self.waypoints_count_long
+            ),
+        );
+        c.check(
+            self.waypoints_count_long <= MAPBOX_MAX_INTERMEDIATE_WAYPOINTS,
+            "waypoints_count_long",
+            format!(
+                "must be at most {} (directions waypoint limit)",
+                MAPBOX_MAX_INTERMEDIATE_WAYPOINTS
+            ),
+        );
+        c.positive(self.long_route_threshold_km, "long_route_threshold_km");
+        c.positive(
+            self.waypoint_distance_multiplier_4wp,
+            "waypoint_distance_multiplier_4wp",
+        );
+        c.check(
+            self.waypoint_distance_multiplier_2wp >= self.waypoint_distance_multiplier_3wp
+                && self.waypoint_distance_multiplier_3wp >= self.waypoint_distance_multiplier_4wp,
+            "waypoint_distance_multiplier_2wp",
+            "fewer waypoints must sit at least as far out (2wp >= 3wp >= 4wp)",
+        );
+
+        // POI scoring weights
+        c.check(
+            self.poi_min_separation_km >= 0.0,
+            "poi_min_separation_km",
+            "must be >= 0",
+        );
+        let weights = [
+            ("poi_score_weight_distance", self.poi_score_weight_distance),
+            ("poi_score_weight_quality", self.poi_score_weight_quality),
+            ("poi_score_weight_angular", self.poi_score_weight_angular),
+            (
+                "poi_score_weight_clustering",
+                self.poi_score_weight_clustering,
+            ),
+            (
+                "poi_score_weight_variation",
+                self.poi_score_weight_variation,
+            ),
+        ];
+        for (field, weight) in weights {
+            c.fraction(weight, field);
+        }
+        if self.poi_scoring_strategy == ScoringStrategy::Advanced {
+            let sum: f32 = weights.iter().map(|(_, w)| w).sum();
+            c.check(
+                (POI_SCORE_WEIGHT_SUM_MIN..=POI_SCORE_WEIGHT_SUM_MAX).contains(&sum),
+                "poi_score_weight_distance",
+                format!(
+                    "advanced scoring weights should sum to ~1.0 (got {:.2})",
+                    sum
+                ),
+            );
+        }
+
+        // Route scoring & quality tiers
+        c.positive(
+            self.metrics_overlap_threshold_m,
+            "metrics_overlap_threshold_m",
+        );
+        c.check(
+            (1..=2).contains(&self.scoring_version),
+            "scoring_version",
+            format!("must be 1 or 2 (got {})", self.scoring_version),
+        );
+        c.check(
+            0.0 <= self.quality_tier_bronze_score
+                && self.quality_tier_bronze_score <= self.quality_tier_silver_score
+                && self.quality_tier_silver_score <= self.quality_tier_gold_score
+                && self.quality_tier_gold_score <= 10.0,
+            "quality_tier_bronze_score",
+            "must satisfy 0 <= bronze <= silver <= gold <= 10",
+        );
+        c.fraction(
+            self.quality_tier_gold_min_circularity,
+            "quality_tier_gold_min_circularity",
+        );
+        c.fraction(self.quality_tier_max_overlap, "quality_tier_max_overlap");
+        c.check(
+            self.amenities_min_distance_km >= 0.0,
+            "amenities_min_distance_km",
+            "must be >= 0",
+        );
+
+        // Speed model
+        c.positive(self.walking_speed_kmh, "walking_speed_kmh");
+        c.positive(self.running_speed_kmh, "running_speed_kmh");
+        c.positive(self.cycling_speed_kmh, "cycling_speed_kmh");
+        for (climb, field) in [
+            (
+                self.walking_climb_min_per_100m,
+                "walking_climb_min_per_100m",
+            ),
+            (
+                self.running_climb_min_per_100m,
+                "running_climb_min_per_100m",
+            ),
+            (
+                self.cycling_climb_min_per_100m,
+                "cycling_climb_min_per_100m",
+            ),
+        ] {
+            c.check(climb >= 0.0, field, "must be >= 0");
+        }
+
+        // POI discovery & candidate limits
+        c.positive(self.poi_limit_short_factor, "poi_limit_short_factor");
+        c.check(
+            0.0 < self.poi_limit_short_min && self.poi_limit_short_min <= self.poi_limit_short_max,
+            "poi_limit_short_min",
+            "must satisfy 0 < min <= poi_limit_short_max",
+        );
+        c.positive(self.poi_limit_long_density, "poi_limit_long_density");
+        c.check(
+            0.0 < self.poi_limit_long_min && self.poi_limit_long_min <= self.poi_limit_long_max,
+            "poi_limit_long_min",
+            "must satisfy 0 < min <= poi_limit_long_max",
+        );
+        c.positive(
+            self.poi_limit_long_wp_dist_factor,
+            "poi_limit_long_wp_dist_factor",
+        );
+        c.positive(self.candidate_limit_factor, "candidate_limit_factor");
+        c.check(
+            0.0 < self.candidate_limit_min
+                && self.candidate_limit_min <= self.candidate_limit_short
+                && self.candidate_limit_short <= self.candidate_limit_medium
+                && self.candidate_limit_medium <= self.candidate_limit_long,
+            "candidate_limit_min",
+            "must satisfy 0 < min <= short <= medium <= long",
+        );
+        c.check(
+            self.candidate_medium_threshold_km < self.long_route_threshold_km,
+            "candidate_medium_threshold_km",
+            "must be smaller than long_route_threshold_km",
+        );
+
+        c.finish()
+    }
+}
+
+impl CorsConfig {
+    fn check(&self, c: &mut Checker) {
+        c.check(
+            !(self.allow_credentials && self.has_wildcard()),
+            "cors",
+            "CORS_ALLOW_CREDENTIALS needs explicit origins, methods and headers (no '*')",
+        );
+        for origin in self.allowed_origins.iter().flatten() {
+            let host = origin
+                .strip_prefix("https://")
+                .or_else(|| origin.strip_prefix("http://"));
+            c.check(
+                host.is_some_and(|host| !host.is_empty() && !host.contains('/')),
+                "cors",
+                format!(
+                    "origin '{}' must be scheme://host[:port] with no path",
+                    origin
+                ),
+            );
+        }
+        for method in self.allowed_methods.iter().flatten() {
+            c.check(
+                Method::from_bytes(method.as_bytes()).is_ok(),
+                "cors",
+                format!("invalid HTTP method '{}'", method),
+            );
+        }
+        for header in self.allowed_headers.iter().flatten() {
+            c.check(
+                HeaderName::from_bytes(header.as_bytes()).is_ok(),
+                "cors",
+                format!("invalid header name '{}'", header),
+            );
+        }
+    }
+}
+
+impl Config {
+    /// Check server-level settings plus the route generator's invariants.
+    pub fn validate(&self) -> Result<(), Vec<ConfigIssue>> {
+        let mut c = Checker::default();
+
+        c.check(self.port != 0, "port", "must be non-zero");
+        c.check(
+            self.route_cache_ttl > 0,
+            "route_cache_ttl",
+            "must be > 0 seconds",
+        );
+        c.check(
+            self.poi_region_cache_ttl > 0,
+            "poi_region_cache_ttl",
+            "must be > 0 seconds",
+        );
+        c.check(
+            self.cache_coord_precision <= 6,
+            "cache_coord_precision",
+            format!(
+                "CACHE_COORD_PRECISION must be between 0 and 6 (got {})",
+                self.cache_coord_precision
+            ),
+        );
+        c.positive(self.cache_distance_bucket_km, "cache_distance_bucket_km");
+        c.check(
+            self.snap_radius_m > 0.0 && self.snap_radius_m <= 1000.0,
+            "snap_radius_m",
+            "must be between 0 and 1000 meters",
+        );
+        if let Some(ref layer) = self.environmental_layer {
+            c.check(
+                layer.min_value < layer.max_value,
+                "environmental_layer",
+                "ENV_LAYER_MIN must be smaller than ENV_LAYER_MAX",
+            );
+        }
+
+        if let Some(ref elevation) = self.elevation {
+            c.check(
+                elevation.api_url.starts_with("http://")
+                    || elevation.api_url.starts_with("https://"),
+                "elevation",
+                "ELEVATION_API_URL must be an http(s) URL",
+            );
+        }
+
+        if let Some(ref valhalla) = self.valhalla {
+            c.check(
+                valhalla.url.starts_with("http://") || valhalla.url.starts_with("https://"),
+                "valhalla",
+                "VALHALLA_URL must be an http(s) URL",
+            );
+        }
+
+        if let Some(ref osrm) = self.osrm {
+            c.check(
+                is_http_url(&osrm.url),
+                "osrm",
+                "OSRM_URL must be an http(s) URL",
+            );
+            c.check(
+                self.valhalla.is_none(),
+                "osrm",
+                "OSRM_URL and VALHALLA_URL both replace Mapbox; set one",
+            );
+        }
+
+        if self.is_offline() {
+            c.check(
+                cfg!(feature = "sqlite"),
+                "region_db_path",
+                "REGION_DB_PATH requires building with the `sqlite` feature",
+            );
+            c.check(
+                self.osrm.is_some() || self.valhalla.is_some(),
+                "region_db_path",
+                "REGION_DB_PATH needs local directions: set OSRM_URL or VALHALLA_URL",
+            );
+            for (enabled, name) in [
+                (self.shadow.is_some(), "SHADOW_SAMPLE_RATE"),
+                (self.api_key_auth, "API_KEY_AUTH"),
+                (
+                    self.evaluation_retention_days.is_some(),
+                    "EVALUATION_RETENTION_DAYS",
+                ),
+            ] {
+                c.check(
+                    !enabled,
+                    "region_db_path",
+                    format!("{} needs Postgres, which REGION_DB_PATH runs without", name),
+                );
+            }
+        }
+
+        if let Some(ref sync) = self.region_sync {
+            c.check(
+                self.is_offline(),
+                "region_sync",
+                "REGION_CATALOG_URL updates the REGION_DB_PATH region; set REGION_DB_PATH",
+            );
+            c.check(
+                sync.catalog_url.starts_with("http://") || sync.catalog_url.starts_with("https://"),
+                "region_sync",
+                "REGION_CATALOG_URL must be an http(s) URL",
+            );
+            if let Some(ref path) = self.region_db_path {
+                c.check(
+                    self.region_id().is_some(),
+                    "region_sync",
+                    format!(
+                        "REGION_DB_PATH file name '{}' is not a region id (letters, digits, '-', '_')",
+                        path
+                    ),
+                );
+            }
+            c.check(
+                sync.interval_secs > 0,
+                "region_sync",
+                "REGION_SYNC_INTERVAL_SECS must be > 0",
+            );
+            #[cfg(feature = "sqlite")]
+            if let Some(ref key) = sync.trusted_key {
+                if let Err(e) = crate::db::sqlite_repo::parse_verifying_key(key) {
+                    c.check(false, "region_sync", format!("REGION_TRUSTED_KEY: {}", e));
+                }
+            }
+        }
+
+        if let Some(ref shadow) = self.shadow {
+            c.check(
+                shadow.sample_rate <= 1.0,
+                "shadow",
+                format!(
+                    "SHADOW_SAMPLE_RATE must be between 0 and 1 (got {})",
+                    shadow.sample_rate
+                ),
+            );
+            c.check(
+                shadow
+                    .candidate_config(&self.route_generator)
+                    .strategy_label()
+                    != self.route_generator.strategy_label(),
+                "shadow",
+                "candidate strategy is identical to the primary one",
+            );
+        }
+
+        if let Some(ref request_log) = self.request_log {
+            c.check(
+                request_log.sample_rate > 0.0 && request_log.sample_rate <= 1.0,
+                "request_log",
+                format!(
+                    "REQUEST_LOG_SAMPLE_RATE must be in (0, 1] (got {})",
+                    request_log.sample_rate
+                ),
+            );
+            if let RequestLogSinkConfig::ObjectStore { ref prefix } = request_log.sink {
+                c.check(
+                    self.artifact_store.is_some(),
+                    "request_log",
+                    "REQUEST_LOG_STORE_PREFIX needs an artifact store (ARTIFACT_STORE)",
+                );
+                c.check(
+                    crate::storage::validate_key(prefix).is_ok(),
+                    "request_log",
+                    format!(
+                        "REQUEST_LOG_STORE_PREFIX must be a /-separated path of [A-Za-z0-9._-] segments (got '{}')",
+                        prefix
+                    ),
+                );
+            }
+        }
+
+        if let Some(ref privacy) = self.privacy {
+            c.check(
+                privacy.jitter_m > 0.0 && privacy.jitter_m <= 5000.0,
+                "privacy",
+                format!(
+                    "LOCATION_PRIVACY_JITTER_M must be in (0, 5000] (got {})",
+                    privacy.jitter_m
+                ),
+            );
+            c.check(
+                (0..=6).contains(&privacy.log_decimals),
+                "privacy",
+                format!(
+                    "LOCATION_PRIVACY_LOG_DECIMALS must be between 0 and 6 (got {})",
+                    privacy.log_decimals
+                ),
+            );
+        }
+        if let Some(ref warm_cities) = self.warm_cities {
+            c.check(
+                !warm_cities.cities.is_empty(),
+                "warm_cities",
+                "WARM_CITIES lists no cities",
+            );
+            for city in &warm_cities.cities {
+                c.check(
+                    Coordinates::new(city.lat, city.lng).is_ok(),
+                    "warm_cities",
+                    format!("{}: invalid center ({}, {})", city.name, city.lat, city.lng),
+                );
+                c.check(
+                    city.radius_km > 0.0 && city.radius_km <= MAX_WARM_CITY_RADIUS_KM,
+                    "warm_cities",
+                    format!(
+                        "{}: radius must be in (0, {}] km (got {})",
+                        city.name, MAX_WARM_CITY_RADIUS_KM, city.radius_km
+                    ),
+                );
+            }
+            c.check(
+                warm_cities.refresh_secs > 0,
+                "warm_cities",
+                "WARM_CITIES_REFRESH_SECS must be > 0",
+            );
+        }
+        c.check(
+            self.evaluation_retention_days != Some(0),
+            "evaluation_retention_days",
+            "must be at least 1 day",
+        );
+        c.check(
+            (1..=SHARE_LINK_MAX_TTL_HOURS).contains(&self.share_link_ttl_hours),
+            "share_link_ttl_hours",
+            format!(
+                "SHARE_LINK_TTL_HOURS must be between 1 and {} (got {})",
+                SHARE_LINK_MAX_TTL_HOURS, self.share_link_ttl_hours
+            ),
+        );
+
+        c.check(
+            !(self.api_key_auth && self.tenants.is_some()),
+            "api_key_auth",
+            "API_KEY_AUTH keys carry their own tenant (apikey create --tenant=); unset TENANT_API_KEYS",
+        );
+
+        if let Some(ref tenants) = self.tenants {
+            c.check(
+                !tenants.api_keys.is_empty(),
+                "tenants",
+                "TENANT_API_KEYS must contain at least one tenant:key pair",
+            );
+            let mut seen = std::collections::HashSet::new();
+            for (tenant, key) in &tenants.api_keys {
+                c.check(
+                    TenantId::is_valid_id(tenant),
+                    "tenants",
+                    format!("tenant id '{}' must be non-empty [A-Za-z0-9_-]", tenant),
+                );
+                c.check(
+                    !key.is_empty(),
+                    "tenants",
+                    format!("tenant '{}' has an empty API key", tenant),
+                );
+                c.check(
+                    key.is_empty() || seen.insert(key.as_str()),
+                    "tenants",
+                    format!("API key for tenant '{}' is assigned more than once", tenant),
+                );
+            }
+        }
+
+        match self.artifact_store {
+            Some(ArtifactStoreConfig::Local {
+                ref public_base_url,
+                ref signing_key,
+                ..
+            }) => {
+                c.check(
+                    is_http_url(public_base_url),
+                    "artifact_store",
+                    format!(
+                        "ARTIFACT_PUBLIC_URL must be an http(s) URL (got '{}')",
+                        public_base_url
+                    ),
+                );
+                c.check(
+                    signing_key.as_ref().map_or(true, |key| key.len() >= 16),
+                    "artifact_store",
+                    "ARTIFACT_SIGNING_KEY must be at least 16 bytes",
+                );
+            }
+            Some(ArtifactStoreConfig::S3(ref s3)) => {
+                c.check(
+                    is_http_url(&s3.endpoint),
+                    "artifact_store",
+                    format!("S3_ENDPOINT must be an http(s) URL (got '{}')", s3.endpoint),
+                );
+                c.check(
+                    !s3.bucket.is_empty() && !s3.bucket.contains('/'),
+                    "artifact_store",
+                    format!("invalid S3_BUCKET '{}'", s3.bucket),
+                );
+                c.check(
+                    !s3.access_key_id.is_empty() && !s3.secret_access_key.is_empty(),
+                    "artifact_store",
+                    "S3 credentials must not be empty",
+                );
+            }
+            None => {}
+        }
+
+        for task in self.scheduler.schedules.keys() {
+            c.check(
+                crate::scheduler::TASK_NAMES.contains(&task.as_str()),
+                "scheduler",
+                format!(
+                    "SCHEDULE_{} does not name a task (known: {})",
+                    task.to_uppercase(),
+                    crate::scheduler::TASK_NAMES.join(", ")
+                ),
+            );
+        }
+        c.check(
+            self.scheduler.max_jitter_secs <= 3600,
+            "scheduler",
+            format!(
+                "SCHEDULER_MAX_JITTER_SECS must be at most 3600 (got {})",
+                self.scheduler.max_jitter_secs
+            ),
+        );
+
+        c.check(
+            self.degradation.redis != FallbackPolicy::CachedOnly,
+            "degradation",
+            "FALLBACK_REDIS must be fail or degrade",
+        );
+        c.check(
+            self.degradation.mapbox != FallbackPolicy::Degrade,
+            "degradation",
+            "FALLBACK_MAPBOX must be fail or cached-only (there is no routing without Mapbox)",
+        );
+        c.check(
+            self.degradation.valhalla != FallbackPolicy::Degrade,
+            "degradation",
+            "FALLBACK_VALHALLA must be fail or cached-only (there is no routing without Valhalla)",
+        );
+        c.check(
+            self.degradation.osrm != FallbackPolicy::Degrade,
+            "degradation",
+            "FALLBACK_OSRM must be fail or cached-only (there is no routing without OSRM)",
+        );
+        c.check(
+            self.degradation.down_cooldown_secs > 0,
+            "degradation",
+            "DEPENDENCY_DOWN_COOLDOWN_SECS must be at least 1",
+        );
+        c.check(
+            self.database_pool_size > 0 && self.database_task_pool_size > 0,
+            "database",
+            "DATABASE_POOL_SIZE and DATABASE_TASK_POOL_SIZE must be at least 1",
+        );
+        c.check(
+            self.degradation.postgres_timeout_ms > 0 && self.degradation.redis_timeout_ms > 0,
+            "degradation",
+            "POSTGRES_TIMEOUT_MS and REDIS_TIMEOUT_MS must be at least 1",
+        );
+
+        if let Some(ref chaos) = self.chaos {
+            for (dependency, rates) in [("MAPBOX", &chaos.mapbox), ("POSTGRES", &chaos.postgres)] {
+                let valid_rate = |r: f64| (0.0..=1.0).contains(&r);
+                c.check(
+                    valid_rate(rates.error_rate)
+                        && valid_rate(rates.timeout_rate)
+                        && rates.error_rate + rates.timeout_rate <= 1.0,
+                    "chaos",
+                    format!(
+                        "CHAOS_{0}_ERROR_RATE and CHAOS_{0}_TIMEOUT_RATE must be in [0, 1] and sum to at most 1",
+                        dependency
+                    ),
+                );
+            }
+            c.check(
+                chaos.timeout_ms > 0,
+                "chaos",
+                "CHAOS_TIMEOUT_MS must be at least 1",
+            );
+            c.check(
+                cfg!(feature = "chaos"),
+                "chaos",
+                "CHAOS_ENABLED requires building with the `chaos` feature",
+            );
+        }
+
+        c.check(
+            self.event_fanout_channel.is_none() || self.redis_url.is_some(),
+            "event_fanout_channel",
+            "EVENT_FANOUT_CHANNEL requires REDIS_URL",
+        );
+
+        self.cors.check(&mut c);
+
+        if let Err(issues) = self.route_generator.validate() {
+            c.issues.extend(issues);
+        }
+
+        c.finish()
+    }
+}
+
+fn is_http_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .map(|u| matches!(u.scheme(), "http" | "https") && u.host_str().is_some())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        ChaosConfig, CorsPreset, DegradationConfig, FaultRates, PrivacyConfig, RequestLogConfig,
+        S3Config, SchedulerConfig, ShadowConfig, TenantConfig, WarmCitiesConfig, WarmCity,
+    };
+
+    #[test]
+    fn default_route_generator_config_is_valid() {
+        assert_eq!(RouteGeneratorConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn inverted_tolerance_levels_are_rejected() {
+        let config = RouteGeneratorConfig {
+            tolerance_level_relaxed: 0.6,
+            tolerance_level_very_relaxed: 0.5,
+            ..Default::default()
+        };
+        let issues = config.validate().unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "tolerance_level_relaxed");
+    }
+
+    #[test]
+    fn all_issues_are_reported() {
+        let config = RouteGeneratorConfig {
+            poi_search_radius_multiplier: 0.0,
+            waypoints_count_short: 5,
+            scoring_version: 3,
+            ..Default::default()
+        };
+        let fields: Vec<_> = config
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|i| i.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "poi_search_radius_multiplier",
+                "waypoints_count_short",
+                "scoring_version"
+            ]
+        );
+    }
+
+    #[test]
+    fn advanced_weights_must_sum_to_about_one() {
+        let config = RouteGeneratorConfig {
+            poi_score_weight_distance: 0.9,
+            poi_score_weight_angular: 0.9,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        // Simple strategy ignores the weights' sum
+        let simple = RouteGeneratorConfig {
+            poi_scoring_strategy: ScoringStrategy::Simple,
+            ..config
+        };
+        assert_eq!(simple.validate(), Ok(()));
+    }
+
+    #[test]
+    fn config_validate_includes_route_generator_issues() {
+        let config = Config {
+            host: "0.0.0.0".to_string(),
+            port: 3000,
+            database_url: String::new(),
+            database_pool_size: 10,
+            database_task_pool_size: 4,
+            redis_url: None,
+            mapbox_api_key: String::new(),
+            region_db_path: None,
+            region_sync: None,
+            route_cache_ttl: 0,
+            poi_region_cache_ttl: 60,
+            cache_coord_precision: 3,
+            cache_distance_bucket_km: 0.5,
+            cache_compression: Default::default(),
+            snap_radius_m: 100.0,
+            poi_geohash_prefilter: false,
+            mapbox_base_url: None,
+            environmental_layer: None,
+            elevation: None,
+            valhalla: None,
+            osrm: None,
+            shadow: None,
+            request_log: None,
+            privacy: None,
+            warm_cities: None,
+            evaluation_retention_days: None,
+            share_link_ttl_hours: 168,
+            api_key_auth: false,
+            admin_token: None,
+            tenants: None,
+            artifact_store: None,
+            event_fanout_channel: None,
+            scheduler: SchedulerConfig::default(),
+            degradation: DegradationConfig::default(),
+            chaos: None,
+            cors: CorsConfig::default(),
+            route_generator: RouteGeneratorConfig {
+                scoring_version: 0,
+                ..Default::default()
+            },
+        };
+        let fields: Vec<_> = config
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|i| i.field)
+            .collect();
+        assert_eq!(fields, vec!["route_cache_ttl", "scoring_version"]);
+
+        // Shadowing the primary strategy with itself measures nothing
+        let config = Config {
+            route_cache_ttl: 60,
+            route_generator: RouteGeneratorConfig::default(),
+            shadow: Some(ShadowConfig {
+                sample_rate: 0.1,
+                poi_scoring_strategy: Some(ScoringStrategy::Advanced),
+                scoring_version: None,
+            }),
+            ..config
+        };
+        let issues = config.validate().unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "shadow");
+    }
+
+    /// Server config that passes validation, for struct-update syntax
+    fn valid_config() -> Config {
+        Config {
+            host: "0.0.0.0".to_string(),
+            port: 3000,
+            database_url: String::new(),
+            database_pool_size: 10,
+            database_task_pool_size: 4,
+            redis_url: None,
+            mapbox_api_key: String::new(),
+            region_db_path: None,
+            region_sync: None,
+            route_cache_ttl: 60,
+            poi_region_cache_ttl: 60,
+            cache_coord_precision: 3,
+            cache_distance_bucket_km: 0.5,
+            cache_compression: Default::default(),
+            snap_radius_m: 100.0,
+            poi_geohash_prefilter: false,
+            mapbox_base_url: None,
+            environmental_layer: None,
+            elevation: None,
+            valhalla: None,
+            osrm: None,
+            shadow: None,
+            request_log: None,
+            privacy: None,
+            warm_cities: None,
+            evaluation_retention_days: None,
+            share_link_ttl_hours: 168,
+            api_key_auth: false,
+            admin_token: None,
+            tenants: None,
+            artifact_store: None,
+            event_fanout_channel: None,
+            scheduler: SchedulerConfig::default(),
+            degradation: DegradationConfig::default(),
+            chaos: None,
+            cors: CorsConfig::default(),
+            route_generator: RouteGeneratorConfig::default(),
+        }
+    }
+
+    #[test]
+    fn privacy_and_retention_validation() {
+        let base = Config {
+            privacy: Some(PrivacyConfig {
+                jitter_m: 100.0,
+                log_decimals: 2,
+            }),
+            evaluation_retention_days: Some(30),
+            ..valid_config()
+        };
+        assert_eq!(base.validate(), Ok(()));
+
+        let config = Config {
+            privacy: Some(PrivacyConfig {
+                jitter_m: 0.0,
+                log_decimals: 9,
+            }),
+            evaluation_retention_days: Some(0),
+            share_link_ttl_hours: SHARE_LINK_MAX_TTL_HOURS + 1,
+            ..base
+        };
+        let fields: Vec<_> = config
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|i| i.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "privacy",
+                "privacy",
+                "evaluation_retention_days",
+                "share_link_ttl_hours"
+            ]
+        );
+    }
+
+    #[test]
+    fn warm_cities_validation() {
+        let city = |name: &str, lat: f64, radius_km: f64| WarmCity {
+            name: name.to_string(),
+            lat,
+            lng: 2.35,
+            radius_km,
+        };
+        let issues = |cities: Vec<WarmCity>, refresh_secs: u64| {
+            let config = Config {
+                warm_cities: Some(WarmCitiesConfig {
+                    cities,
+                    refresh_secs,
+                }),
+                ..valid_config()
+            };
+            config.validate().err().map_or(0, |issues| issues.len())
+        };
+
+        assert_eq!(issues(vec![city("paris", 48.86, 10.0)], 900), 0);
+        assert_eq!(issues(vec![], 900), 1);
+        assert_eq!(issues(vec![city("nowhere", 95.0, 0.0)], 0), 3);
+    }
+
+    #[test]
+    fn tenant_validation() {
+        let pairs = |pairs: &[(&str, &str)]| TenantConfig {
+            api_keys: pairs
+                .iter()
+                .map(|(t, k)| (t.to_string(), k.to_string()))
+                .collect(),
+        };
+        let issues = |tenants: TenantConfig| {
+            let config = Config {
+                tenants: Some(tenants),
+                ..valid_config()
+            };
+            config.validate().err().map_or(0, |issues| issues.len())
+        };
+
+        assert_eq!(
+            issues(pairs(&[("acme", "k1"), ("acme", "k2"), ("globex", "k3")])),
+            0
+        );
+        assert_eq!(issues(pairs(&[])), 1);
+        assert_eq!(issues(pairs(&[("acme", "k1"), ("globex", "k1")])), 1);
+        assert_eq!(issues(pairs(&[("white label", "k1"), ("acme", "")])), 2);
+
+        let both = Config {
+            api_key_auth: true,
+            tenants: Some(pairs(&[("acme", "k1")])),
+            ..valid_config()
+        };
+        assert_eq!(both.validate().unwrap_err()[0].field, "api_key_auth");
+    }
+
+    #[test]
+    fn artifact_store_validation() {
+        let issues = |store: ArtifactStoreConfig| {
+            let config = Config {
+                artifact_store: Some(store),
+                ..valid_config()
+            };
+            config.validate().err().map_or(0, |issues| issues.len())
+        };
+        let local = |public_base_url: &str, signing_key: Option<&str>| ArtifactStoreConfig::Local {
+            dir: "./artifacts".to_string(),
+            public_base_url: public_base_url.to_string(),
+            signing_key: signing_key.map(str::to_string),
+        };
+        let s3 = S3Config {
+            endpoint: "http://localhost:9000".to_string(),
+            bucket: "artifacts".to_string(),
+            region: "us-east-1".to_string(),
+            access_key_id: "minio".to_string(),
+            secret_access_key: "minio-secret".to_string(),
+            path_style: true,
+        };
+
+        assert_eq!(issues(local("https://api.example.com", None)), 0);
+        assert_eq!(issues(local("api.example.com", Some("short"))), 2);
+        assert_eq!(issues(ArtifactStoreConfig::S3(s3.clone())), 0);
+        assert_eq!(
+            issues(ArtifactStoreConfig::S3(S3Config {
+                endpoint: "localhost:9000".to_string(),
+                bucket: String::new(),
+                secret_access_key: String::new(),
+                ..s3
+            })),
+            3
+        );
+    }
+
+    #[test]
+    fn request_log_store_validation() {
+        let config = |prefix: &str, store: bool| Config {
+            request_log: Some(RequestLogConfig {
+                sink: RequestLogSinkConfig::ObjectStore {
+                    prefix: prefix.to_string(),
+                },
+                sample_rate: 0.1,
+            }),
+            artifact_store: store.then(|| ArtifactStoreConfig::Local {
+                dir: "./artifacts".to_string(),
+                public_base_url: "http://localhost:3000".to_string(),
+                signing_key: None,
+            }),
+            ..valid_config()
+        };
+        assert!(config("logs/requests", true).validate().is_ok());
+        let issues = config("logs/requests", false).validate().unwrap_err();
+        assert_eq!(issues[0].field, "request_log");
+        assert_eq!(config("/logs", true).validate().unwrap_err().len(), 1);
+    }
+
+    #[test]
+    fn scheduler_validation() {
+        let config = Config {
+            scheduler: SchedulerConfig {
+                schedules: [
+                    ("evaluation_retention".to_string(), None),
+                    ("cache_warming".to_string(), "@daily".parse().ok()),
+                ]
+                .into(),
+                max_jitter_secs: 7200,
+            },
+            ..valid_config()
+        };
+        let issues = config.validate().unwrap_err();
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("SCHEDULE_CACHE_WARMING"));
+    }
+
+    #[test]
+    fn degradation_validation() {
+        let config = Config {
+            degradation: DegradationConfig {
+                redis: FallbackPolicy::CachedOnly,
+                postgres: FallbackPolicy::Degrade,
+                mapbox: FallbackPolicy::Degrade,
+                valhalla: FallbackPolicy::Degrade,
+                osrm: FallbackPolicy::Degrade,
+                redis_timeout_ms: 0,
+                ..DegradationConfig::default()
+            },
+            ..valid_config()
+        };
+        let issues = config.validate().unwrap_err();
+        assert_eq!(issues.len(), 5);
+
+        let config = Config {
+            degradation: DegradationConfig {
+                postgres: FallbackPolicy::CachedOnly,
+                mapbox: FallbackPolicy::CachedOnly,
+                valhalla: FallbackPolicy::CachedOnly,
+                osrm: FallbackPolicy::CachedOnly,
+                ..DegradationConfig::default()
+            },
+            ..valid_config()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            database_task_pool_size: 0,
+            ..valid_config()
+        };
+        assert_eq!(config.validate().unwrap_err()[0].field, "database");
+    }
+
+    #[test]
+    fn chaos_validation() {
+        let config = Config {
+            chaos: Some(ChaosConfig {
+                mapbox: FaultRates {
+                    error_rate: 0.7,
+                    timeout_rate: 0.5,
+                    ..Default::default()
+                },
+                postgres: FaultRates {
+                    error_rate: -0.1,
+                    ..Default::default()
+                },
+                timeout_ms: 0,
+                ..Default::default()
+            }),
+            ..valid_config()
+        };
+        // Builds without the `chaos` feature also reject CHAOS_ENABLED itself
+        let feature_issues = usize::from(!cfg!(feature = "chaos"));
+        let issues = config.validate().unwrap_err();
+        assert_eq!(issues.len(), feature_issues + 3);
+        assert!(issues[0].message.contains("CHAOS_MAPBOX_ERROR_RATE"));
+
+        let config = Config {
+            chaos: Some(ChaosConfig {
+                mapbox: FaultRates {
+                    error_rate: 1.0,
+                    fail_first: 3,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            ..valid_config()
+        };
+        let issues = config.validate().err().unwrap_or_default();
+        assert_eq!(issues.len(), feature_issues);
+    }
+
+    #[test]
+    fn event_fanout_requires_redis() {
+        let config = Config {
+            event_fanout_channel: Some("easyroute:events".to_string()),
+            ..valid_config()
+        };
+        let issues = config.validate().unwrap_err();
+        assert_eq!(issues[0].field, "event_fanout_channel");
+
+        let config = Config {
+            redis_url: Some("redis://localhost:6379".to_string()),
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn offline_mode_validation() {
+        use crate::config::{OsrmConfig, RegionSyncConfig, ValhallaConfig};
+
+        let offline = Config {
+            region_db_path: Some("regions/monaco.db".to_string()),
+            osrm: Some(OsrmConfig {
+                url: "http://localhost:5000".to_string(),
+            }),
+            ..valid_config()
+        };
+        let messages = |config: &Config| -> Vec<String> {
+            let issues = config.validate().err().unwrap_or_default();
+            issues.into_iter().map(|i| i.message).collect()
+        };
+        // Builds without the `sqlite` feature also reject REGION_DB_PATH itself
+        let feature_issues = usize::from(!cfg!(feature = "sqlite"));
+        assert_eq!(messages(&offline).len(), feature_issues);
+
+        // No local directions, and a feature that needs Postgres
+        let config = Config {
+            osrm: None,
+            api_key_auth: true,
+            ..offline.clone()
+        };
+        let issues = messages(&config);
+        assert_eq!(issues.len(), feature_issues + 2);
+        assert!(issues.iter().any(|m| m.contains("API_KEY_AUTH")));
+
+        let config = Config {
+            valhalla: Some(ValhallaConfig {
+                url: "http://localhost:8002".to_string(),
+            }),
+            ..offline.clone()
+        };
+        assert!(messages(&config).iter().any(|m| m.contains("set one")));
+
+        // Region sync updates the offline region, whose file stem is its id
+        let sync = RegionSyncConfig {
+            catalog_url: "https://regions.example.com".to_string(),
+            api_key: None,
+            interval_secs: 3600,
+            trusted_key: None,
+        };
+        let config = Config {
+            region_sync: Some(sync.clone()),
+            ..offline.clone()
+        };
+        assert_eq!(config.region_id(), Some("monaco"));
+        assert_eq!(messages(&config).len(), feature_issues);
+        let config = Config {
+            region_db_path: Some("regions/monaco 2.db".to_string()),
+            region_sync: Some(RegionSyncConfig {
+                catalog_url: "regions.example.com".to_string(),
+                interval_secs: 0,
+                ..sync.clone()
+            }),
+            ..offline
+        };
+        assert_eq!(messages(&config).len(), feature_issues + 3);
+        let online = Config {
+            region_sync: Some(sync),
+            ..valid_config()
+        };
+        assert!(messages(&online)[0].contains("set REGION_DB_PATH"));
+    }
+
+    #[test]
+    fn cors_validation() {
+        assert_eq!(CorsConfig::default().validate_alone(), Vec::<String>::new());
+
+        let credentials_with_wildcard = CorsConfig {
+            allow_credentials: true,
+            ..CorsConfig::preset(CorsPreset::Development)
+        };
+        assert_eq!(credentials_with_wildcard.validate_alone().len(), 1);
+
+        let malformed = CorsConfig {
+            allowed_origins: Some(vec![
+                "https://app.example.com".to_string(),
+                "app.example.com".to_string(),
+                "https://app.example.com/".to_string(),
+            ]),
+            allowed_methods: Some(vec!["GET".to_string(), "GE T".to_string()]),
+            ..CorsConfig::default()
+        };
+        assert_eq!(malformed.validate_alone().len(), 3);
+    }
+
+    impl CorsConfig {
+        fn validate_alone(&self) -> Vec<String> {
+            let mut c = Checker::default();
+            self.check(&mut c);
+            c.issues.into_iter().map(|i| i.message).collect()
+        }
+    }
+}
@@ -19,6 +19,21 @@ pub const DEFAULT_ROUTE_CACHE_TTL_SECONDS: u64 = 86_400;
 /// Default POI-region cache TTL: 7 days. Overridden by `POI_REGION_CACHE_TTL`.
 pub const DEFAULT_POI_REGION_CACHE_TTL_SECONDS: u64 = 604_800;
 
+// --- Cache key granularity ---
+
+/// Decimals loop route keys round the start to (3 ≈ 100m). Overridden by
+/// `CACHE_COORD_PRECISION`.
+pub const DEFAULT_CACHE_COORD_PRECISION: u32 = 3;
+/// Width of the distance buckets in loop route keys. Overridden by
+/// `CACHE_DISTANCE_BUCKET_KM`.
+pub const DEFAULT_CACHE_DISTANCE_BUCKET_KM: f64 = 0.5;
+
+// --- Geodesy ---
+
+/// Meters per degree of latitude (and of longitude at the equator), for
+/// local flat-earth approximations
+pub const METERS_PER_DEGREE: f64 = 111_320.0;
+
 // --- Route generation structural limits ---
 
 /// Default snap radius (meters) for associating nearby POIs with a route path.
@@ -30,6 +45,14 @@ pub const DEFAULT_SNAP_RADIUS_METERS: f64 = 100.0;
 pub const MIN_ALTERNATIVES_FOR_SUCCESS: u32 = 3;
 /// Hard upper bound on alternative routes returned, regardless of user request.
 pub const MAX_ALTERNATIVES_CLAMP: u32 = 5;
+/// Extra generation attempts per tolerance level for Pareto route sets, so
+/// the frontier has more than the requested number of candidates to pick from.
+pub const PARETO_EXTRA_ATTEMPTS: usize = 4;
+/// Largest per-request POI separation, as a fraction of route distance.
+/// Beyond a third of the loop, even three waypoints can't satisfy the spacing.
+pub const MAX_POI_SEPARATION_DISTANCE_RATIO: f64 = 1.0 / 3.0;
+/// Most `must_include` entries per request; each takes a waypoint slot
+pub const MUST_INCLUDE_MAX_POINTS: usize = 4;
 
 // --- Spatial distribution angle thresholds (radians) ---
 // Used by `WaypointSelector::verify_loop_shape()` to reject waypoint
@@ -41,24 +64,46 @@ pub const SPATIAL_DISTRIBUTION_MIN_ANGLE_TWO_POIS_RAD: f64 = 1.0;
 /// Minimum angular separation (radians, 60 deg) between any pair of 3 waypoints.
 pub const SPATIAL_DISTRIBUTION_MIN_ANGLE_THREE_POIS_RAD: f64 = 1.047;
 
-// --- Variation / pseudo-random scoring coefficients ---
-// Used by `WaypointSelector` to introduce deterministic variation across
-// alternative route generations so each attempt produces distinct waypoint sets.
+// --- Waypoint ordering ---
+// Clockwise ordering can cross itself once a loop has many waypoints; from
+// `ROUTE_TSP_MIN_WAYPOINTS` on, `tsp::shortest_loop_order()` reorders them
+// by tour length.
+
+/// Default for `ROUTE_TSP_MIN_WAYPOINTS`.
+pub const TSP_MIN_WAYPOINTS: usize = 5;
+/// Largest waypoint count solved exactly (Held-Karp, O(2^n * n^2)); above
+/// it, 2-opt improves the clockwise order instead.
+pub const TSP_EXACT_MAX_WAYPOINTS: usize = 10;
+
+// --- Variation ---
+// Random choices during generation draw from a seeded RNG
+// (`route_generator::variation`) so alternatives differ per attempt while the
+// same request and seed reproduce the same routes.
 
-/// Multiplied with the attempt seed to spread hash values.
-pub const VARIATION_MULTIPLIER: usize = 3;
-/// Added to the seed-derived value before modular reduction.
-pub const VARIATION_OFFSET_BASE: usize = 11;
-/// Modulus for the pseudo-random variation hash.
-pub const VARIATION_MOD: usize = 100;
-/// Scaling factor converting the variation hash (0..100) to a score bonus (0.0..0.05).
-pub const VARIATION_SCORE_FACTOR: f32 = 0.05;
+/// Seed used when the request has no `seed` preference.
+pub const DEFAULT_VARIATION_SEED: u64 = 0x5EED;
+/// Upper bound of the random score bonus added to each candidate POI.
+pub const VARIATION_MAX_BONUS: f32 = 5.0;
 
 // --- In-memory cache defaults ---
 
 /// Maximum entries for the on-device in-memory route cache (LRU eviction).
 pub const DEFAULT_MEMORY_CACHE_MAX_ENTRIES: u64 = 1_000;
 
+/// zstd level for cached route entries: fast, still several-fold smaller than JSON.
+pub const CACHE_ZSTD_LEVEL: i32 = 3;
+
+/// Route cache TTL multiplier for sets whose best route is gold.
+pub const CACHE_TTL_GOLD_MULTIPLIER: f64 = 2.0;
+
+/// Route cache TTL multiplier for sets with nothing better than bronze,
+/// including untiered geometric fallbacks: a retry once more POIs are
+/// imported may do better.
+pub const CACHE_TTL_LOW_QUALITY_MULTIPLIER: f64 = 0.25;
+
+/// Keys per Redis `DEL` when invalidating cache entries.
+pub const CACHE_INVALIDATION_BATCH_SIZE: usize = 500;
+
 // --- Distance correction feedback loop ---
 // After each Mapbox route response, the generator adjusts the waypoint distance
 // multiplier based on how far the actual route distance was from the target.
@@ -74,6 +119,23 @@ pub const DISTANCE_CORRECTION_MIN: f64 = 0.5;
 /// Prevents runaway expansion of waypoint distances.
 pub const DISTANCE_CORRECTION_MAX: f64 = 2.5;
 
+// --- Directions response sanitization ---
+
+/// Consecutive geometry points farther apart than this (meters) indicate a
+/// corrupted backend response; the route is rejected.
+pub const DIRECTIONS_MAX_POINT_JUMP_M: f64 = 5_000.0;
+/// Routes shorter than this (meters) are treated as degenerate.
+pub const DIRECTIONS_MIN_DISTANCE_M: f64 = 1.0;
+
+// --- Leg repair ---
+// Before discarding an overshooting attempt, the leg with the worst detour is
+// re-routed through an extra POI close to the straight line between its ends.
+
+/// Minimum road / straight-line distance ratio for a leg to be worth repairing.
+pub const LEG_REPAIR_MIN_DETOUR_RATIO: f64 = 1.5;
+/// Maximum straight-line detour (ratio to the direct leg) allowed through a sub-waypoint.
+pub const LEG_REPAIR_MAX_SUB_WAYPOINT_DETOUR: f64 = 1.2;
+
 // --- Distance-stratified candidate selection ---
 // For long routes in dense areas, POIs are bucketed into concentric distance
 // rings to prevent the closest-first DB limit from filling the candidate pool
@@ -84,3 +146,464 @@ pub const STRATIFIED_RING_COUNT: usize = 4;
 /// Outermost ring distance as a fraction of target route distance.
 /// E.g., for a 10 km target, rings extend out to 6 km from the start.
 pub const STRATIFIED_MAX_RING_DISTANCE_FACTOR: f64 = 0.6;
+
+// --- Duration estimates (per-profile speed model) ---
+
+/// Average walking speed (km/h) used for derived walking durations.
+pub const WALKING_SPEED_KMH: f64 = 5.0;
+/// Average easy running pace (km/h) used for derived running durations.
+pub const RUNNING_SPEED_KMH: f64 = 10.0;
+/// Fastest and slowest `pace_min_per_km` a run request may set.
+pub const RUN_PACE_RANGE_MIN_PER_KM: (f64, f64) = (2.5, 15.0);
+/// Longest stop (minutes) a runner makes at a waypoint: a photo, not a tour.
+pub const RUN_MAX_VISIT_MINUTES: u32 = 2;
+/// Default waypoint spacing (km) for runs: fewer, farther-apart stops than a walk.
+pub const RUN_POI_MIN_SEPARATION_KM: f64 = 0.6;
+/// Average leisure cycling speed (km/h), for planning before a route exists.
+/// Route durations for cycling modes come from the directions backend.
+pub const CYCLING_SPEED_KMH: f64 = 16.0;
+/// Extra minutes per 100 m climbed on foot (Naismith's rule: 1 h per 600 m).
+pub const WALKING_CLIMB_MIN_PER_100M: f64 = 10.0;
+/// Extra minutes per 100 m climbed at an easy running pace.
+pub const RUNNING_CLIMB_MIN_PER_100M: f64 = 6.0;
+/// Extra minutes per 100 m climbed on a bike (leisure riders climb ~600 m/h).
+pub const CYCLING_CLIMB_MIN_PER_100M: f64 = 6.0;
+/// Maximum relative distance difference for a cycling re-query to count as the
+/// same geometry. Larger deviations mean bikes must detour, so no estimate is given.
+pub const PROFILE_REQUERY_MAX_DISTANCE_DEVIATION: f64 = 0.1;
+
+// --- Surface classification (paved-fraction estimates) ---
+
+/// OSM `surface` values counted as paved.
+pub const PAVED_SURFACES: &[&str] = &[
+    "asphalt",
+    "paved",
+    "concrete",
+    "concrete:plates",
+    "concrete:lanes",
+    "paving_stones",
+    "sett",
+    "chipseal",
+];
+/// OSM `highway` values assumed paved when the way has no `surface` tag.
+pub const PAVED_BY_DEFAULT_HIGHWAYS: &[&str] = &[
+    "motorway",
+    "trunk",
+    "primary",
+    "secondary",
+    "tertiary",
+    "unclassified",
+    "residential",
+    "living_street",
+    "service",
+    "cycleway",
+];
+/// OSM `highway` values counted as busy roads (avoided by dog walks).
+pub const BUSY_ROAD_HIGHWAYS: &[&str] = &[
+    "motorway",
+    "motorway_link",
+    "trunk",
+    "trunk_link",
+    "primary",
+    "primary_link",
+    "secondary",
+    "secondary_link",
+];
+/// Minimum paved share for `surface: paved` when the request sets no
+/// `min_paved_fraction` (as for road bikes).
+pub const PAVED_SURFACE_MIN_FRACTION: f32 = 0.9;
+/// Maximum paved share for `surface: trail`: most of the route should be on
+/// tracks and paths, but towns have to be crossed on tarmac.
+pub const TRAIL_SURFACE_MAX_PAVED_FRACTION: f32 = 0.5;
+/// OSM `surface` values smooth enough for strollers and wheelchairs
+/// (`PAVED_SURFACES` minus cobbles).
+pub const SMOOTH_SURFACES: &[&str] = &[
+    "asphalt",
+    "paved",
+    "concrete",
+    "concrete:plates",
+    "paving_stones",
+    "chipseal",
+];
+/// Minimum share of a way's length inside the route corridor for it to count as
+/// "on" the route (rather than crossing it), when looking for steps and inclines.
+pub const ROAD_SEGMENT_ON_ROUTE_MIN_SHARE: f64 = 0.5;
+/// Corridor half-width (meters) for matching road segments to a route path.
+pub const PAVED_FRACTION_MATCH_RADIUS_M: f64 = 15.0;
+
+// --- Step-free (stroller) routing ---
+
+/// Steepest grade (percent) a step-free route may include (wheelchair ramp guidance).
+pub const STEP_FREE_MAX_INCLINE_PCT: f32 = 8.0;
+/// Minimum smooth-surface share applied when a request asks for step-free routing.
+pub const STEP_FREE_MIN_SMOOTH_FRACTION: f32 = 0.8;
+
+// --- Environmental exposure (noise / air quality layers) ---
+
+/// Spacing (meters) between exposure samples along a route path.
+pub const ENV_EXPOSURE_SAMPLE_SPACING_M: f64 = 50.0;
+/// Score points subtracted per unit of mean exposure (0-1) when a request
+/// asks to minimize exposure.
+pub const ENV_EXPOSURE_SCORE_PENALTY: f32 = 2.0;
+/// Default normalization range for environmental layers, in the layer's units
+/// (tuned for Lden noise maps in dB).
+pub const DEFAULT_ENV_LAYER_MIN_VALUE: f64 = 45.0;
+pub const DEFAULT_ENV_LAYER_MAX_VALUE: f64 = 75.0;
+
+// --- Elevation (ELEVATION_API_URL) ---
+
+/// Spacing (meters) between elevation samples along a route path.
+pub const ELEVATION_SAMPLE_SPACING_M: f64 = 100.0;
+/// Most points sent per elevation lookup; long routes are sampled more coarsely.
+pub const ELEVATION_MAX_SAMPLES: usize = 200;
+/// Rises smaller than this (meters) are treated as DEM noise, not climbing.
+pub const ELEVATION_NOISE_THRESHOLD_M: f32 = 3.0;
+/// Elevation cache cells are this many degrees wide (~11 m, finer than the
+/// ~30 m SRTM/Copernicus grid), so nearby samples share one lookup.
+pub const ELEVATION_CACHE_CELL_DEG: f64 = 0.0001;
+/// Most grid cells kept in the elevation cache.
+pub const ELEVATION_CACHE_CAPACITY: u64 = 200_000;
+/// Timeout for one elevation lookup; routes are returned without gain on failure.
+pub const ELEVATION_REQUEST_TIMEOUT_SECS: u64 = 5;
+/// Climb per km at which `prefer_flat` applies its full penalty (rolling hills).
+pub const ELEVATION_HILLY_GAIN_M_PER_KM: f32 = 25.0;
+/// Score points subtracted from the hilliest routes when a request prefers flat ones.
+pub const ELEVATION_FLAT_SCORE_PENALTY: f32 = 2.0;
+/// Score points subtracted once a route climbs twice `max_elevation_gain_m`,
+/// scaled linearly from 0 at the limit.
+pub const ELEVATION_OVER_LIMIT_SCORE_PENALTY: f32 = 3.0;
+
+// --- Configuration validation ---
+
+/// Intermediate waypoints a loop can use: the directions API accepts 25
+/// coordinates and the start point appears at both ends.
+pub const MAPBOX_MAX_INTERMEDIATE_WAYPOINTS: usize = 23;
+/// Accepted band for the sum of the advanced POI scoring weights.
+pub const POI_SCORE_WEIGHT_SUM_MIN: f32 = 0.8;
+pub const POI_SCORE_WEIGHT_SUM_MAX: f32 = 1.2;
+
+// --- Request/response logging ---
+
+/// Default fraction of loop requests written to the request log.
+pub const DEFAULT_REQUEST_LOG_SAMPLE_RATE: f64 = 0.01;
+/// Decimal places kept for logged coordinates (3 ≈ 110 m), so the corpus
+/// doesn't pinpoint users' homes.
+pub const REQUEST_LOG_COORD_DECIMALS: i32 = 3;
+/// Records buffered for the background writer; beyond this, records are dropped.
+pub const REQUEST_LOG_CHANNEL_CAPACITY: usize = 1024;
+/// Records per NDJSON object uploaded to the artifact store.
+pub const REQUEST_LOG_OBJECT_MAX_LINES: usize = 1000;
+/// Interval at which records buffered for the artifact store are uploaded
+/// even if the batch isn't full.
+pub const REQUEST_LOG_FLUSH_INTERVAL_SECS: u64 = 60;
+
+// --- Location privacy ---
+
+/// Default jitter (meters) applied to stored start points in privacy mode.
+pub const DEFAULT_LOCATION_PRIVACY_JITTER_M: f64 = 100.0;
+/// Default decimals kept for start points in log lines (2 ≈ 1.1 km).
+pub const DEFAULT_LOCATION_PRIVACY_LOG_DECIMALS: i32 = 2;
+/// How often expired evaluation data is purged when a retention period is set
+/// (default schedule of the `evaluation_retention` task).
+pub const EVALUATION_RETENTION_SWEEP_INTERVAL_SECS: u64 = 3600;
+
+// --- SQLite read pool (region databases) ---
+
+/// Default interval between region catalog checks (`REGION_CATALOG_URL`).
+pub const DEFAULT_REGION_SYNC_INTERVAL_SECS: u64 = 3600;
+/// Time budget for a region catalog request (downloads are not capped).
+pub const REGION_CATALOG_TIMEOUT_SECS: u64 = 30;
+/// A region download receiving nothing for this long is abandoned; the
+/// partial file is resumed by the next sync.
+pub const REGION_DOWNLOAD_STALL_SECS: u64 = 60;
+
+/// Upper bound on concurrent read connections in server mode.
+pub const SQLITE_SERVER_MAX_READ_CONNECTIONS: u32 = 16;
+/// Memory-mapped window per connection in server mode (256 MB).
+pub const SQLITE_SERVER_MMAP_BYTES: u64 = 268_435_456;
+/// Page cache per connection in server mode (16 MB).
+pub const SQLITE_SERVER_CACHE_KIB: u32 = 16_384;
+pub const SQLITE_MOBILE_MAX_READ_CONNECTIONS: u32 = 2;
+/// Memory-mapped window per connection on mobile (32 MB).
+pub const SQLITE_MOBILE_MMAP_BYTES: u64 = 33_554_432;
+/// Page cache per connection on mobile (8 MB).
+pub const SQLITE_MOBILE_CACHE_KIB: u32 = 8_192;
+
+// --- CORS ---
+
+/// Default `Access-Control-Max-Age`: browsers reuse a preflight for 1 hour.
+/// Overridden by `CORS_MAX_AGE_SECS`.
+pub const DEFAULT_CORS_MAX_AGE_SECONDS: u64 = 3600;
+
+// --- HTTP caching and compression ---
+
+/// Version of the route generation algorithm, part of route ETags. Bump when a
+/// change alters what a stored route id serves, so clients drop cached copies.
+pub const ROUTE_ALGORITHM_VERSION: u32 = 1;
+/// Responses smaller than this are sent uncompressed (gzip overhead dominates).
+pub const COMPRESSION_MIN_BODY_BYTES: usize = 1024;
+
+// --- POI partitioning (migrations/optional/partition_pois.sql) ---
+
+/// Geohash length of the `poi_partition_key` SQL function (45° x 45° cells).
+pub const POI_PARTITION_GEOHASH_PRECISION: usize = 1;
+/// Queries spanning more partitions than this are sent without a pruning hint.
+pub const POI_PARTITION_MAX_HINT_KEYS: usize = 4;
+
+// --- POI geohash pre-filter (migrations/008_add_pois_geohash.sql) ---
+
+/// Length of the stored `pois.geohash` column (~5 m cells).
+pub const POI_GEOHASH_PRECISION: usize = 9;
+/// Cells per radius query; the finest precision needing at most this many is used.
+pub const POI_GEOHASH_MAX_PREFILTER_CELLS: usize = 9;
+
+// --- Dependency fallback policy ---
+
+/// A dependency counts as down for this long after its last failure.
+pub const DEFAULT_DEPENDENCY_DOWN_COOLDOWN_SECS: u64 = 30;
+
+/// Budget for one POI repository query before it fails as timed out.
+pub const DEFAULT_POSTGRES_TIMEOUT_MS: u64 = 5_000;
+
+/// Connections in the request-path Postgres pool (and CLI pools).
+pub const DEFAULT_DATABASE_POOL_SIZE: u32 = 10;
+
+/// Connections in the server's pool for migrations, scheduled tasks and
+/// admin endpoints, which run without the request time budget.
+pub const DEFAULT_DATABASE_TASK_POOL_SIZE: u32 = 4;
+
+/// Budget for one Redis command before it is treated as a cache miss.
+pub const DEFAULT_REDIS_TIMEOUT_MS: u64 = 500;
+
+/// Consecutive failed Redis commands that disable the cache.
+pub const REDIS_BREAKER_FAILURE_THRESHOLD: u32 = 5;
+
+/// How often a disabled cache pings Redis to see whether it is back.
+pub const REDIS_RECONNECT_INTERVAL_SECS: u64 = 10;
+
+// --- Scheduled tasks ---
+
+/// Upper bound of the random delay added to each scheduled run.
+pub const DEFAULT_SCHEDULER_MAX_JITTER_SECS: u64 = 30;
+
+// --- Lifecycle events ---
+
+/// Events a subscriber may fall behind by before it misses the oldest ones
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+// --- Artifact storage ---
+
+/// Longest expiry SigV4 presigned URLs accept (7 days)
+pub const S3_MAX_PRESIGN_EXPIRY_SECS: u64 = 7 * 24 * 3600;
+
+// --- Fault injection (resilience testing) ---
+
+/// Seed for the fault dice when `CHAOS_SEED` is unset
+pub const DEFAULT_CHAOS_SEED: u64 = 0;
+/// How long an injected timeout hangs before failing
+pub const DEFAULT_CHAOS_TIMEOUT_MS: u64 = 5000;
+
+// --- Snap radius tuning (client feedback on snapped POIs) ---
+
+/// Width of the distance-from-path buckets feedback is grouped into
+pub const SNAP_FEEDBACK_BUCKET_M: f64 = 25.0;
+/// Feedback further from the path than this is ignored
+pub const SNAP_FEEDBACK_MAX_DISTANCE_M: f64 = 500.0;
+/// Events accepted per telemetry request
+pub const SNAP_FEEDBACK_MAX_EVENTS: usize = 500;
+/// Feedback older than this does not count towards recommendations
+pub const SNAP_TUNING_LOOKBACK_DAYS: u32 = 30;
+/// A bucket needs this many shown POIs before its engagement rate is trusted
+pub const SNAP_TUNING_MIN_BUCKET_SAMPLES: i64 = 30;
+/// A mode needs this many shown POIs in total before a radius is suggested
+pub const SNAP_TUNING_MIN_SAMPLES: i64 = 200;
+/// The suggested radius extends while a bucket's engagement rate stays at
+/// least this fraction of the nearest bucket's
+pub const SNAP_TUNING_RELATIVE_ENGAGEMENT: f64 = 0.5;
+/// How often the tuning task runs by default (daily)
+pub const SNAP_TUNING_INTERVAL_SECS: u64 = 24 * 3600;
+
+// --- Distance suggestions (GET /areas/suggest) ---
+
+/// Loop distances considered for walking and dog walks
+pub const SUGGEST_WALK_DISTANCES_KM: [f64; 10] =
+    [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0, 12.0, 15.0];
+/// Loop distances considered for runs
+pub const SUGGEST_RUN_DISTANCES_KM: [f64; 8] = [3.0, 5.0, 6.0, 8.0, 10.0, 12.0, 15.0, 21.0];
+/// Loop distances considered for cycling modes
+pub const SUGGEST_CYCLING_DISTANCES_KM: [f64; 8] = [5.0, 10.0, 15.0, 20.0, 25.0, 30.0, 40.0, 50.0];
+/// POIs within this fraction of the loop distance from the start can serve
+/// as waypoints (the default 3-waypoint distance multiplier)
+pub const SUGGEST_REACH_RATIO: f64 = 0.35;
+/// POIs in reach a loop needs per km of distance to have enough waypoint choices
+pub const SUGGEST_POIS_PER_KM: f64 = 1.5;
+/// Fewest POIs in reach a loop of any distance needs
+pub const SUGGEST_MIN_POIS: f64 = 6.0;
+/// Past generations started within this radius inform the suggestion
+pub const SUGGEST_HISTORY_RADIUS_M: f64 = 3000.0;
+/// Most recent past generations considered
+pub const SUGGEST_HISTORY_LIMIT: i64 = 500;
+/// Pseudo-count of the area's mean score when estimating a distance's
+/// quality: with this many past routes at a distance, its own mean and the
+/// area's weigh the same
+pub const SUGGEST_HISTORY_PRIOR: f64 = 5.0;
+/// Distances scoring within this of the best are part of the suggested range
+pub const SUGGEST_RANGE_MARGIN: f64 = 0.1;
+
+// --- Rating agreement (GET /evaluations/stats) ---
+
+/// Krippendorff's alpha at which ratings support tentative conclusions
+pub const AGREEMENT_RELIABLE_ALPHA: f64 = 0.667;
+/// Most-disputed routes listed in the agreement report
+pub const AGREEMENT_MOST_DISPUTED_LIMIT: usize = 10;
+
+// --- Valhalla directions (VALHALLA_URL) ---
+
+/// Per-request timeout for Valhalla `/route` calls
+pub const VALHALLA_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+// --- OSRM directions (OSRM_URL) ---
+
+/// Per-request timeout for OSRM `/route/v1` calls
+pub const OSRM_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+// --- Evaluated route previews (EvaluatedRoute.preview) ---
+
+/// Most points kept in a path preview polyline
+pub const PATH_PREVIEW_MAX_POINTS: usize = 64;
+/// Starting simplification tolerance, as a fraction of the bounding box
+/// diagonal (raised until the preview fits `PATH_PREVIEW_MAX_POINTS`)
+pub const PATH_PREVIEW_TOLERANCE_FRACTION: f64 = 0.005;
+/// Preview polylines use the common 5-decimal precision (~1 m)
+pub const PATH_PREVIEW_POLYLINE_PRECISION: f64 = 1e5;
+
+// --- Stored evaluated-route geometry and metric recomputation ---
+
+/// Simplification tolerance for stored paths; well under the 25 m overlap
+/// threshold so recomputed metrics match the originals
+pub const STORED_PATH_TOLERANCE_M: f64 = 2.0;
+/// Stored paths keep 6 decimal places (~0.1 m)
+pub const STORED_PATH_POLYLINE_PRECISION: f64 = 1e6;
+/// Evaluated routes given geometry per `geometry_backfill` run
+pub const GEOMETRY_BACKFILL_BATCH_SIZE: i64 = 500;
+/// How often `geometry_backfill` looks for routes to fill in (1 hour)
+pub const GEOMETRY_BACKFILL_INTERVAL_SECS: u64 = 3600;
+/// Saved copies are client-supplied: `geometry_backfill` only copies one
+/// whose path length is within this fraction of the generated distance
+/// (and whose waypoints match the evaluated route's).
+pub const GEOMETRY_BACKFILL_MAX_LENGTH_DEVIATION: f64 = 0.05;
+/// Version of the route quality metric code (`RouteMetrics`). Bump when a
+/// metric definition changes; the `metric_recompute` task then recomputes
+/// every stored route's metrics under the new version.
+pub const ROUTE_METRICS_VERSION: i32 = 1;
+/// Evaluated routes rescored per `metric_recompute` run
+pub const METRIC_RECOMPUTE_BATCH_SIZE: i64 = 1000;
+/// How often `metric_recompute` looks for routes lacking current metrics (1 hour)
+pub const METRIC_RECOMPUTE_INTERVAL_SECS: u64 = 3600;
+
+// --- Saved routes (POST /routes/{id}/save) ---
+
+/// Longest `user` identifier accepted (matches `saved_routes.user_id`)
+pub const SAVED_ROUTE_MAX_USER_LEN: usize = 100;
+/// Longest route name accepted (matches `saved_routes.name`)
+pub const SAVED_ROUTE_MAX_NAME_LEN: usize = 200;
+/// A waypoint farther than this from where the saved route passes it has
+/// moved, and the route is flagged stale
+pub const SAVED_ROUTE_POI_MOVED_M: f64 = 100.0;
+/// Saved routes checked per `saved_route_consistency` run, least recently
+/// checked first
+pub const SAVED_ROUTE_CHECK_BATCH_SIZE: i64 = 500;
+/// How often `saved_route_consistency` runs (6 hours)
+pub const SAVED_ROUTE_CHECK_INTERVAL_SECS: u64 = 21_600;
+
+// --- API keys (API_KEY_AUTH) ---
+
+/// Prefix of generated API keys, so leaked keys are easy to grep for
+pub const API_KEY_PREFIX: &str = "er_";
+/// Random characters after the prefix (base62, ~190 bits)
+pub const API_KEY_RANDOM_LEN: usize = 32;
+
+// --- Share links (POST /routes/{id}/share) ---
+
+/// Default lifetime of a share link (1 week)
+pub const DEFAULT_SHARE_LINK_TTL_HOURS: u32 = 168;
+/// Longest lifetime a share link can be given (1 year)
+pub const SHARE_LINK_MAX_TTL_HOURS: u32 = 8760;
+/// Characters in a share token (base62, ~59 bits)
+pub const SHARE_TOKEN_LEN: usize = 10;
+
+// --- Rating sampler (GET /evaluations/next) ---
+
+/// Most recent routes considered when picking what to rate next
+pub const SAMPLER_CANDIDATE_POOL: i64 = 500;
+/// Routes with this many ratings are no longer offered
+pub const SAMPLER_MAX_RATINGS_PER_ROUTE: i64 = 3;
+/// Rated neighbours used to predict a route's rating
+pub const SAMPLER_NEIGHBORS: usize = 5;
+/// Mean metric-space distance to the neighbours at which a region counts
+/// as wholly unexplored
+pub const SAMPLER_NOVELTY_RADIUS: f64 = 0.5;
+/// POI density (per km) that maps to the top of its 0-1 axis
+pub const SAMPLER_DENSITY_SCALE: f32 = 5.0;
+/// Weight of novelty against score disagreement in the priority
+pub const SAMPLER_NOVELTY_WEIGHT: f64 = 0.5;
+
+// --- Evaluation export (GET /admin/evaluations/export) ---
+
+/// Rows read per query while exporting; CSV is streamed a page at a time
+pub const EVALUATION_EXPORT_PAGE_SIZE: i64 = 1000;
+/// Decimal places kept for exported start points (3 ≈ 110 m): exports leave
+/// the server, and starts are often users' homes.
+pub const EVALUATION_EXPORT_COORD_DECIMALS: i32 = 3;
+
+// --- List pagination ---
+
+/// Deepest a POI cursor pages into the nearest-first results: each page
+/// re-reads every POI before it, so deeper pages need a smaller radius
+pub const POI_PAGINATION_MAX_DEPTH: u32 = 1000;
+
+// --- Staged routes (RouteGenerator::generate_staged_route) ---
+
+/// Longest multi-day route, in days
+pub const STAGED_ROUTE_MAX_DAYS: u32 = 14;
+/// Radius around the start searched for the base of a daily-loops route (km)
+pub const STAGE_BASE_SEARCH_RADIUS_KM: f64 = 3.0;
+/// Accommodation candidates considered per search
+pub const STAGE_ACCOMMODATION_LIMIT: usize = 50;
+/// POIs fetched around a day's midpoint when picking its stops. Larger than
+/// the accommodation limit, as most are dropped by the detour and category
+/// filters.
+pub const STAGE_VIA_CANDIDATE_LIMIT: usize = 200;
+/// Routed distance over straight-line distance, used to pick a stay a
+/// day's walk away before asking for directions
+pub const STAGE_ROUTING_DETOUR_FACTOR: f64 = 1.3;
+/// Allowed deviation of a day from its share of the total, as a fraction
+pub const STAGE_DISTANCE_TOLERANCE: f64 = 0.2;
+/// Weight of turning back against distance mismatch when picking the next
+/// stay (0 = any direction, 1 = a U-turn costs as much as a day's mismatch)
+pub const STAGE_HEADING_WEIGHT: f64 = 0.5;
+/// Points of interest visited on the way between two stays
+pub const STAGE_MAX_VIA_POIS: usize = 2;
+
+// --- POI heatmap (GET /heatmap) ---
+
+/// Cell edge used unless the request sets `cell_m` (meters)
+pub const HEATMAP_DEFAULT_CELL_M: f64 = 100.0;
+/// Allowed `cell_m` (meters)
+pub const HEATMAP_CELL_RANGE_M: (f64, f64) = (50.0, 2_000.0);
+/// Most cells one request may cover
+pub const HEATMAP_MAX_CELLS: usize = 40_000;
+/// Most POIs read for one heatmap; beyond it the response is `truncated`
+pub const HEATMAP_MAX_POIS: i64 = 20_000;
+
+// --- Warm city POI snapshots (WARM_CITIES) ---
+
+/// Default interval between reloads of the in-memory city snapshots.
+pub const DEFAULT_WARM_CITIES_REFRESH_SECS: u64 = 900;
+
+/// Largest warm city radius, keeping snapshots to a city's worth of POIs.
+pub const MAX_WARM_CITY_RADIUS_KM: f64 = 50.0;
+
+/// POIs loaded per city snapshot. A city reaching it is not served from
+/// memory, since its snapshot would be missing POIs.
+pub const WARM_CITY_MAX_POIS: i64 = 200_000;
@@ -0,0 +1,87 @@
+use crate::models::api_key::{ApiKey, ApiKeySummary, ApiScope};
+use crate::services::tenant::TenantId;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Store a key (by hash) for `user_id` of `tenant`, creating the user if
+/// needed
+pub async fn create_api_key(
+    pool: &PgPool,
+    tenant: &TenantId,
+    user_id: &str,
+    key_hash: &str,
+    key_prefix: &str,
+    scopes: &[ApiScope],
+) -> Result<Uuid, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("INSERT INTO users (id) VALUES ($1) ON CONFLICT (id) DO NOTHING")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    let scopes: Vec<&str> = scopes.iter().map(|scope| scope.as_str()).collect();
+    let (id,): (Uuid,) = sqlx::query_as(
+        r#"
+        INSERT INTO api_keys (user_id, key_hash, key_prefix, scopes)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(key_hash)
+    .bind(key_prefix)
+    .bind(&scopes)
+    .bind(tenant.as_str())
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(id)
+}
+
+/// The unrevoked key with `key_hash`, counting this use of it
+pub async fn authenticate_api_key(
+    pool: &PgPool,
+    key_hash: &str,
+) -> Result<Option<ApiKey>, sqlx::Error> {
+    let row: Option<(Uuid, String, String, Vec<String>)> = sqlx::query_as(
+        r#"
+        UPDATE api_keys
+        SET request_count = request_count + 1, last_used_at = NOW()
+        WHERE key_hash = $1 AND revoked_at IS NULL
+        RETURNING id, user_id, tenant_id, scopes
+        "#,
+    )
+    .bind(key_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(id, user_id, tenant, scopes)| ApiKey {
+        id,
+        user_id,
+        tenant: TenantId::new(tenant),
+        scopes: scopes.iter().filter_map(|s| s.parse().ok()).collect(),
+    }))
+}
+
+pub async fn list_api_keys(pool: &PgPool) -> Result<Vec<ApiKeySummary>, sqlx::Error> {
+    sqlx::query_as::<_, ApiKeySummary>(
+        r#"
+        SELECT id, tenant_id, user_id, key_prefix, scopes, request_count,
+               last_used_at::text as last_used_at, created_at::text as created_at,
+               revoked_at::text as revoked_at
+        FROM api_keys
+        ORDER BY tenant_id, user_id, created_at
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Returns whether an unrevoked key was revoked
+pub async fn revoke_api_key(pool: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("UPDATE api_keys SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL")
+            .bind(id)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,52 @@
+use crate::models::Coordinates;
+use sqlx::PgPool;
+
+/// POIs within each of `radii_m` of `center`, in the order of `radii_m`
+pub async fn count_pois_within_radii(
+    pool: &PgPool,
+    center: &Coordinates,
+    radii_m: &[f64],
+) -> Result<Vec<i64>, sqlx::Error> {
+    let point_wkt = format!("POINT({} {})", center.lng, center.lat);
+    let rows: Vec<(i64,)> = sqlx::query_as(
+        "SELECT COUNT(p.id)
+         FROM UNNEST($2::float8[]) WITH ORDINALITY AS r(radius_m, ord)
+         LEFT JOIN pois p ON ST_DWithin(p.location, ST_GeogFromText($1), r.radius_m)
+         GROUP BY r.ord
+         ORDER BY r.ord",
+    )
+    .bind(&point_wkt)
+    .bind(radii_m)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|(count,)| count).collect())
+}
+
+/// `(target_distance_km, system_score)` of the most recent evaluated routes
+/// for `transport_mode` that started within `radius_m` of `center`
+pub async fn generation_history_near(
+    pool: &PgPool,
+    center: &Coordinates,
+    transport_mode: &str,
+    radius_m: f64,
+    limit: i64,
+) -> Result<Vec<(f64, f32)>, sqlx::Error> {
+    let point_wkt = format!("POINT({} {})", center.lng, center.lat);
+    sqlx::query_as(
+        "SELECT target_distance_km, system_score
+         FROM evaluated_routes
+         WHERE transport_mode = $2
+           AND ST_DWithin(
+                 ST_SetSRID(ST_MakePoint(start_lng, start_lat), 4326)::geography,
+                 ST_GeogFromText($1),
+                 $3)
+         ORDER BY created_at DESC
+         LIMIT $4",
+    )
+    .bind(&point_wkt)
+    .bind(transport_mode)
+    .bind(radius_m)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
@@ -1,13 +1,56 @@
-use crate::models::evaluation::{EvaluatedRoute, MetricCorrelation, RouteRating};
+use crate::constants::GEOMETRY_BACKFILL_MAX_LENGTH_DEVIATION;
+use crate::models::evaluation::{
+    EvaluatedRoute, EvaluationCursor, MetricCorrelation, RouteRating, ShadowComparison,
+    StoredGeometry,
+};
+use crate::models::Route;
+use serde::Serialize;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+/// One evaluated route with its metrics and ratings averaged per dimension,
+/// as exported for offline analysis
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct EvaluationExportRow {
+    pub id: Uuid,
+    pub created_at: String,
+    pub start_lat: f64,
+    pub start_lng: f64,
+    pub target_distance_km: f64,
+    pub transport_mode: String,
+    pub actual_distance_km: f64,
+    pub duration_minutes: i32,
+    pub poi_count: i32,
+    pub snapped_poi_count: i32,
+    pub circularity: Option<f32>,
+    pub convexity: Option<f32>,
+    pub path_overlap_pct: Option<f32>,
+    pub poi_density_per_km: Option<f32>,
+    pub category_entropy: Option<f32>,
+    pub landmark_coverage: Option<f32>,
+    pub system_score: f32,
+    pub poi_density_context: Option<String>,
+    pub scoring_strategy: String,
+    pub rating_count: i64,
+    pub avg_overall_rating: Option<f64>,
+    pub avg_shape_rating: Option<f64>,
+    pub avg_scenicness_rating: Option<f64>,
+    pub avg_variety_rating: Option<f64>,
+}
+
 pub async fn insert_evaluated_route(
     pool: &PgPool,
     route: &EvaluatedRoute,
 ) -> Result<Uuid, sqlx::Error> {
     let poi_names_json = serde_json::to_value(&route.poi_names).unwrap_or_default();
-    let path_json = serde_json::Value::Null; // Path stored separately or as empty
+    let path_json = route
+        .geometry
+        .as_ref()
+        .and_then(|geometry| serde_json::to_value(geometry).ok());
+    let preview_json = route
+        .preview
+        .as_ref()
+        .and_then(|preview| serde_json::to_value(preview).ok());
 
     let result: (Uuid,) = sqlx::query_as(
         r#"
@@ -17,9 +60,9 @@ pub async fn insert_evaluated_route(
             poi_count, snapped_poi_count,
             circularity, convexity, path_overlap_pct,
             poi_density_per_km, category_entropy, landmark_coverage,
-            system_score, poi_density_context, scoring_strategy
+            system_score, poi_density_context, scoring_strategy, path_preview
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
         RETURNING id
         "#,
     )
@@ -43,6 +86,44 @@ pub async fn insert_evaluated_route(
     .bind(route.system_score)
     .bind(&route.poi_density_context)
     .bind(&route.scoring_strategy)
+    .bind(&preview_json)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.0)
+}
+
+pub async fn insert_shadow_comparison(
+    pool: &PgPool,
+    comparison: &ShadowComparison,
+) -> Result<Uuid, sqlx::Error> {
+    let result: (Uuid,) = sqlx::query_as(
+        r#"
+        INSERT INTO shadow_comparisons (
+            primary_route_id, candidate_route_id, primary_strategy, candidate_strategy,
+            candidate_error, score_delta, distance_error_delta,
+            circularity_delta, convexity_delta, path_overlap_pct_delta,
+            poi_density_per_km_delta, category_entropy_delta, landmark_coverage_delta,
+            latency_ms_delta
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+        RETURNING id
+        "#,
+    )
+    .bind(comparison.primary_route_id)
+    .bind(comparison.candidate_route_id)
+    .bind(&comparison.primary_strategy)
+    .bind(&comparison.candidate_strategy)
+    .bind(&comparison.candidate_error)
+    .bind(comparison.score_delta)
+    .bind(comparison.distance_error_delta)
+    .bind(comparison.circularity_delta)
+    .bind(comparison.convexity_delta)
+    .bind(comparison.path_overlap_pct_delta)
+    .bind(comparison.poi_density_per_km_delta)
+    .bind(comparison.category_entropy_delta)
+    .bind(comparison.landmark_coverage_delta)
+    .bind(comparison.latency_ms_delta)
     .fetch_one(pool)
     .await?;
 
@@ -91,7 +172,7 @@ pub async fn get_evaluated_route(
                actual_distance_km, duration_minutes, poi_names, poi_count, snapped_poi_count,
                circularity, convexity, path_overlap_pct,
                poi_density_per_km, category_entropy, landmark_coverage,
-               system_score, poi_density_context, scoring_strategy,
+               system_score, poi_density_context, scoring_strategy, path_preview,
                created_at::text as created_at
         FROM evaluated_routes
         WHERE id = $1
@@ -123,10 +204,91 @@ pub async fn get_evaluated_route(
     Ok(Some(route))
 }
 
+/// Stored geometry of an evaluated route: `None` if the route doesn't exist,
+/// `Some(None)` if it was stored without geometry
+pub async fn get_evaluated_route_geometry(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<Option<StoredGeometry>>, sqlx::Error> {
+    let row: Option<(Option<serde_json::Value>,)> =
+        sqlx::query_as("SELECT path FROM evaluated_routes WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.map(|(path,)| path.and_then(|path| serde_json::from_value(path).ok())))
+}
+
+/// Up to `limit` evaluated routes without stored geometry that were also
+/// saved by a user, with the saved copy to take the geometry from. Saved
+/// copies are sent by clients, so only one that matches the generated route
+/// qualifies: same waypoint names in order, and a path length within
+/// `GEOMETRY_BACKFILL_MAX_LENGTH_DEVIATION` of the generated distance.
+pub async fn list_geometry_backfill_sources(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<(Uuid, Route)>, sqlx::Error> {
+    let rows: Vec<(Uuid, sqlx::types::Json<Route>)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT ON (er.id) er.id, sr.route
+        FROM evaluated_routes er
+        JOIN saved_routes sr ON sr.route_id = er.id
+        CROSS JOIN LATERAL (
+            SELECT COALESCE(jsonb_agg(p.value->'name' ORDER BY p.ord), '[]'::jsonb) AS names
+            FROM jsonb_array_elements(sr.route->'pois') WITH ORDINALITY AS p(value, ord)
+        ) saved_pois
+        CROSS JOIN LATERAL (
+            SELECT COALESCE(ST_Length(ST_MakeLine(
+                ST_SetSRID(ST_MakePoint((c.value->>'lng')::float8, (c.value->>'lat')::float8), 4326)
+                ORDER BY c.ord
+            )::geography), 0) / 1000.0 AS length_km
+            FROM jsonb_array_elements(sr.route->'path') WITH ORDINALITY AS c(value, ord)
+        ) saved_path
+        WHERE er.path IS NULL
+          AND saved_pois.names = er.poi_names
+          AND abs(saved_path.length_km - er.actual_distance_km) <= er.actual_distance_km * $2
+        ORDER BY er.id, sr.saved_at
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .bind(GEOMETRY_BACKFILL_MAX_LENGTH_DEVIATION)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|(id, route)| (id, route.0)).collect())
+}
+
+/// Fill in the geometry of a route stored without one; routes that already
+/// have geometry are left alone. Returns whether the route was updated.
+pub async fn backfill_evaluated_route_geometry(
+    pool: &PgPool,
+    id: Uuid,
+    geometry: &StoredGeometry,
+) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("UPDATE evaluated_routes SET path = $2 WHERE id = $1 AND path IS NULL")
+            .bind(id)
+            .bind(serde_json::to_value(geometry).unwrap_or_default())
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Evaluated routes still stored without geometry
+pub async fn count_routes_missing_geometry(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM evaluated_routes WHERE path IS NULL")
+            .fetch_one(pool)
+            .await?;
+    Ok(count)
+}
+
+/// Newest first, ties broken by id. With `after`, only routes that sort
+/// after that cursor position.
 pub async fn list_evaluated_routes(
     pool: &PgPool,
     limit: i64,
     offset: i64,
+    after: Option<&EvaluationCursor>,
 ) -> Result<Vec<EvaluatedRoute>, sqlx::Error> {
     let rows = sqlx::query_as::<_, EvaluatedRouteRow>(
         r#"
@@ -134,41 +296,172 @@ pub async fn list_evaluated_routes(
                actual_distance_km, duration_minutes, poi_names, poi_count, snapped_poi_count,
                circularity, convexity, path_overlap_pct,
                poi_density_per_km, category_entropy, landmark_coverage,
-               system_score, poi_density_context, scoring_strategy,
+               system_score, poi_density_context, scoring_strategy, path_preview,
                created_at::text as created_at
         FROM evaluated_routes
-        ORDER BY created_at DESC
+        WHERE $3::timestamptz IS NULL OR (created_at, id) < ($3::timestamptz, $4::uuid)
+        ORDER BY created_at DESC, id DESC
         LIMIT $1 OFFSET $2
         "#,
     )
     .bind(limit)
     .bind(offset)
+    .bind(after.map(|cursor| cursor.created_at.as_str()))
+    .bind(after.map(|cursor| cursor.id))
     .fetch_all(pool)
     .await?;
 
     Ok(rows.into_iter().map(|r| r.into()).collect())
 }
 
-/// Compute Pearson correlations between each route metric and average human rating.
-pub async fn get_correlation_data(pool: &PgPool) -> Result<Vec<MetricCorrelation>, sqlx::Error> {
-    let rows = sqlx::query_as::<_, CorrelationRow>(
+/// Up to `limit` export rows, oldest first, after the `after` position
+/// (keyset paging, so a long export never holds a transaction open)
+pub async fn export_evaluated_routes(
+    pool: &PgPool,
+    after: Option<&EvaluationCursor>,
+    limit: i64,
+) -> Result<Vec<EvaluationExportRow>, sqlx::Error> {
+    sqlx::query_as::<_, EvaluationExportRow>(
         r#"
-        SELECT
-            er.circularity,
-            er.convexity,
-            er.path_overlap_pct,
-            er.poi_density_per_km,
-            er.category_entropy,
-            er.landmark_coverage,
-            AVG(rr.overall_rating)::float8 as avg_rating
+        SELECT er.id, COALESCE(er.created_at, 'epoch')::text as created_at,
+               er.start_lat, er.start_lng, er.target_distance_km, er.transport_mode,
+               er.actual_distance_km, er.duration_minutes, er.poi_count, er.snapped_poi_count,
+               er.circularity, er.convexity, er.path_overlap_pct,
+               er.poi_density_per_km, er.category_entropy, er.landmark_coverage,
+               er.system_score, er.poi_density_context, er.scoring_strategy,
+               COUNT(rr.id) as rating_count,
+               AVG(rr.overall_rating)::float8 as avg_overall_rating,
+               AVG(rr.shape_rating)::float8 as avg_shape_rating,
+               AVG(rr.scenicness_rating)::float8 as avg_scenicness_rating,
+               AVG(rr.variety_rating)::float8 as avg_variety_rating
+        FROM evaluated_routes er
+        LEFT JOIN route_ratings rr ON rr.route_id = er.id
+        WHERE $1::timestamptz IS NULL
+           OR (COALESCE(er.created_at, 'epoch'), er.id) > ($1::timestamptz, $2::uuid)
+        GROUP BY er.id
+        ORDER BY COALESCE(er.created_at, 'epoch'), er.id
+        LIMIT $3
+        "#,
+    )
+    .bind(after.map(|cursor| cursor.created_at.as_str()))
+    .bind(after.map(|cursor| cursor.id))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Up to `limit` of the newest routes with fewer than `max_ratings` ratings,
+/// skipping any `rater_id` has already rated
+pub async fn list_rating_candidates(
+    pool: &PgPool,
+    rater_id: Option<&str>,
+    max_ratings: i64,
+    limit: i64,
+) -> Result<Vec<EvaluationExportRow>, sqlx::Error> {
+    sqlx::query_as::<_, EvaluationExportRow>(
+        r#"
+        SELECT er.id, COALESCE(er.created_at, 'epoch')::text as created_at,
+               er.start_lat, er.start_lng, er.target_distance_km, er.transport_mode,
+               er.actual_distance_km, er.duration_minutes, er.poi_count, er.snapped_poi_count,
+               er.circularity, er.convexity, er.path_overlap_pct,
+               er.poi_density_per_km, er.category_entropy, er.landmark_coverage,
+               er.system_score, er.poi_density_context, er.scoring_strategy,
+               COUNT(rr.id) as rating_count,
+               AVG(rr.overall_rating)::float8 as avg_overall_rating,
+               AVG(rr.shape_rating)::float8 as avg_shape_rating,
+               AVG(rr.scenicness_rating)::float8 as avg_scenicness_rating,
+               AVG(rr.variety_rating)::float8 as avg_variety_rating
+        FROM evaluated_routes er
+        LEFT JOIN route_ratings rr ON rr.route_id = er.id
+        GROUP BY er.id
+        HAVING COUNT(rr.id) < $2
+           AND NOT COALESCE(bool_or(rr.rater_id = $1), false)
+        ORDER BY COALESCE(er.created_at, 'epoch') DESC, er.id DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(rater_id)
+    .bind(max_ratings)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Every route with at least one rating, ratings averaged as in the export
+pub async fn list_rated_routes(pool: &PgPool) -> Result<Vec<EvaluationExportRow>, sqlx::Error> {
+    sqlx::query_as::<_, EvaluationExportRow>(
+        r#"
+        SELECT er.id, COALESCE(er.created_at, 'epoch')::text as created_at,
+               er.start_lat, er.start_lng, er.target_distance_km, er.transport_mode,
+               er.actual_distance_km, er.duration_minutes, er.poi_count, er.snapped_poi_count,
+               er.circularity, er.convexity, er.path_overlap_pct,
+               er.poi_density_per_km, er.category_entropy, er.landmark_coverage,
+               er.system_score, er.poi_density_context, er.scoring_strategy,
+               COUNT(rr.id) as rating_count,
+               AVG(rr.overall_rating)::float8 as avg_overall_rating,
+               AVG(rr.shape_rating)::float8 as avg_shape_rating,
+               AVG(rr.scenicness_rating)::float8 as avg_scenicness_rating,
+               AVG(rr.variety_rating)::float8 as avg_variety_rating
         FROM evaluated_routes er
         JOIN route_ratings rr ON rr.route_id = er.id
-        GROUP BY er.id, er.circularity, er.convexity, er.path_overlap_pct,
-                 er.poi_density_per_km, er.category_entropy, er.landmark_coverage
+        GROUP BY er.id
         "#,
     )
     .fetch_all(pool)
-    .await?;
+    .await
+}
+
+/// Compute Pearson correlations between each route metric and average human rating.
+/// `metrics_version` picks metrics recomputed with that version of the metric
+/// code (`evaluated_route_metrics`); `None` uses those stored at generation.
+pub async fn get_correlation_data(
+    pool: &PgPool,
+    metrics_version: Option<i32>,
+) -> Result<Vec<MetricCorrelation>, sqlx::Error> {
+    let rows = match metrics_version {
+        None => {
+            sqlx::query_as::<_, CorrelationRow>(
+                r#"
+                SELECT
+                    er.circularity,
+                    er.convexity,
+                    er.path_overlap_pct,
+                    er.poi_density_per_km,
+                    er.category_entropy,
+                    er.landmark_coverage,
+                    AVG(rr.overall_rating)::float8 as avg_rating
+                FROM evaluated_routes er
+                JOIN route_ratings rr ON rr.route_id = er.id
+                GROUP BY er.id, er.circularity, er.convexity, er.path_overlap_pct,
+                         er.poi_density_per_km, er.category_entropy, er.landmark_coverage
+                "#,
+            )
+            .fetch_all(pool)
+            .await?
+        }
+        Some(version) => {
+            sqlx::query_as::<_, CorrelationRow>(
+                r#"
+                SELECT
+                    m.circularity,
+                    m.convexity,
+                    m.path_overlap_pct,
+                    m.poi_density_per_km,
+                    m.category_entropy,
+                    m.landmark_coverage,
+                    AVG(rr.overall_rating)::float8 as avg_rating
+                FROM evaluated_route_metrics m
+                JOIN route_ratings rr ON rr.route_id = m.route_id
+                WHERE m.metrics_version = $1 AND m.error IS NULL
+                GROUP BY m.route_id, m.circularity, m.convexity, m.path_overlap_pct,
+                         m.poi_density_per_km, m.category_entropy, m.landmark_coverage
+                "#,
+            )
+            .bind(version)
+            .fetch_all(pool)
+            .await?
+        }
+    };
 
     if rows.len() < 2 {
         return Ok(vec![]);
@@ -206,6 +499,21 @@ pub async fn get_correlation_data(pool: &PgPool) -> Result<Vec<MetricCorrelation
     Ok(correlations)
 }
 
+/// Every rating, for agreement statistics
+pub async fn list_all_ratings(pool: &PgPool) -> Result<Vec<RouteRating>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, RouteRatingRow>(
+        r#"
+        SELECT id, route_id, overall_rating, shape_rating, scenicness_rating,
+               variety_rating, comment, rater_id, created_at::text as created_at
+        FROM route_ratings
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.into()).collect())
+}
+
 pub async fn get_evaluation_counts(pool: &PgPool) -> Result<(i64, i64), sqlx::Error> {
     let route_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM evaluated_routes")
         .fetch_one(pool)
@@ -216,6 +524,18 @@ pub async fn get_evaluation_counts(pool: &PgPool) -> Result<(i64, i64), sqlx::Er
     Ok((route_count, rating_count))
 }
 
+/// Delete evaluated routes created more than `days` ago. Their ratings and
+/// shadow comparisons go with them (ON DELETE CASCADE). Returns routes deleted.
+pub async fn delete_evaluations_older_than(pool: &PgPool, days: u32) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM evaluated_routes WHERE created_at < NOW() - make_interval(days => $1)",
+    )
+    .bind(days as i32)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
 fn pearson_correlation(pairs: &[(f64, f64)]) -> f64 {
     let n = pairs.len() as f64;
     if n < 2.0 {
@@ -259,6 +579,7 @@ struct EvaluatedRouteRow {
     system_score: f32,
     poi_density_context: Option<String>,
     scoring_strategy: String,
+    path_preview: Option<serde_json::Value>,
     created_at: Option<String>,
 }
 
@@ -286,6 +607,10 @@ impl From<EvaluatedRouteRow> for EvaluatedRoute {
             system_score: row.system_score,
             poi_density_context: row.poi_density_context,
             scoring_strategy: row.scoring_strategy,
+            preview: row
+                .path_preview
+                .and_then(|preview| serde_json::from_value(preview).ok()),
+            geometry: None,
             created_at: row.created_at,
             ratings: None,
         }
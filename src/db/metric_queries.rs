@@ -0,0 +1,116 @@
+use crate::models::evaluation::StoredGeometry;
+use crate::services::route_generator::route_metrics::RouteMetrics;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// An evaluated route with stored geometry, as needed to recompute its metrics
+pub struct RecomputeSource {
+    pub id: Uuid,
+    pub actual_distance_km: f64,
+    /// The stored geometry, or why it doesn't deserialize
+    pub geometry: Result<StoredGeometry, String>,
+}
+
+/// Up to `limit` evaluated routes with stored geometry that have neither
+/// metrics nor a recorded error for `metrics_version` yet
+pub async fn list_routes_for_recompute(
+    pool: &PgPool,
+    metrics_version: i32,
+    limit: i64,
+) -> Result<Vec<RecomputeSource>, sqlx::Error> {
+    let rows: Vec<(Uuid, f64, serde_json::Value)> = sqlx::query_as(
+        r#"
+        SELECT er.id, er.actual_distance_km, er.path
+        FROM evaluated_routes er
+        WHERE er.path IS NOT NULL
+          AND NOT EXISTS (
+              SELECT 1 FROM evaluated_route_metrics m
+              WHERE m.route_id = er.id AND m.metrics_version = $1
+          )
+        ORDER BY er.id
+        LIMIT $2
+        "#,
+    )
+    .bind(metrics_version)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, actual_distance_km, path)| RecomputeSource {
+            id,
+            actual_distance_km,
+            geometry: serde_json::from_value(path).map_err(|e| e.to_string()),
+        })
+        .collect())
+}
+
+pub async fn upsert_recomputed_metrics(
+    pool: &PgPool,
+    route_id: Uuid,
+    metrics_version: i32,
+    metrics: &RouteMetrics,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO evaluated_route_metrics (
+            route_id, metrics_version, circularity, convexity, path_overlap_pct,
+            poi_density_per_km, category_entropy, landmark_coverage
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (route_id, metrics_version) DO UPDATE SET
+            circularity = EXCLUDED.circularity,
+            convexity = EXCLUDED.convexity,
+            path_overlap_pct = EXCLUDED.path_overlap_pct,
+            poi_density_per_km = EXCLUDED.poi_density_per_km,
+            category_entropy = EXCLUDED.category_entropy,
+            landmark_coverage = EXCLUDED.landmark_coverage,
+            error = NULL,
+            computed_at = NOW()
+        "#,
+    )
+    .bind(route_id)
+    .bind(metrics_version)
+    .bind(metrics.circularity)
+    .bind(metrics.convexity)
+    .bind(metrics.path_overlap_pct)
+    .bind(metrics.poi_density_per_km)
+    .bind(metrics.category_entropy)
+    .bind(metrics.landmark_coverage)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record that `route_id`'s stored geometry can't be read with
+/// `metrics_version`, so it isn't listed for recompute again. The row holds
+/// no metrics and is left out of correlation analysis.
+pub async fn mark_recompute_failed(
+    pool: &PgPool,
+    route_id: Uuid,
+    metrics_version: i32,
+    error: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO evaluated_route_metrics (route_id, metrics_version, error)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (route_id, metrics_version) DO UPDATE SET
+            circularity = NULL,
+            convexity = NULL,
+            path_overlap_pct = NULL,
+            poi_density_per_km = NULL,
+            category_entropy = NULL,
+            landmark_coverage = NULL,
+            error = EXCLUDED.error,
+            computed_at = NOW()
+        "#,
+    )
+    .bind(route_id)
+    .bind(metrics_version)
+    .bind(error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -1,26 +1,72 @@
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use std::str::FromStr;
 use std::time::Duration;
 
+mod api_key_queries;
+mod area_queries;
 mod evaluation_queries;
+mod metric_queries;
+mod poi_audit_queries;
 mod poi_queries;
 pub mod poi_repository;
+mod saved_route_queries;
+mod scheduler_queries;
+mod snap_feedback_queries;
 #[cfg(feature = "sqlite")]
 pub mod sqlite_repo;
+mod surface_queries;
 
 /// Re-export all query functions under `queries` for backwards compatibility
 pub mod queries {
+    pub use super::api_key_queries::*;
+    pub use super::area_queries::*;
     pub use super::evaluation_queries::*;
+    pub use super::metric_queries::*;
+    pub use super::poi_audit_queries::*;
     pub use super::poi_queries::*;
+    pub use super::saved_route_queries::*;
+    pub use super::scheduler_queries::*;
+    pub use super::snap_feedback_queries::*;
+    pub use super::surface_queries::*;
 }
 
 pub use poi_repository::{PgPoiRepository, PoiRepository};
 #[cfg(feature = "sqlite")]
-pub use sqlite_repo::SqlitePoiRepository;
+pub use sqlite_repo::{RegionIntegrityError, SqlitePoiRepository, SqliteReadPoolConfig};
 
-pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
+pub async fn create_pool(database_url: &str, max_connections: u32) -> Result<PgPool, sqlx::Error> {
     PgPoolOptions::new()
-        .max_connections(10)
+        .max_connections(max_connections)
         .acquire_timeout(Duration::from_secs(5))
         .connect(database_url)
         .await
 }
+
+/// Pool for the request path: Postgres cancels any statement running past
+/// `budget`, and waiting for a free connection is bounded by it too, so a
+/// slow query or an exhausted pool fails the request instead of hanging it.
+/// Migrations, background tasks and admin endpoints use [`create_pool`],
+/// whose batch statements may legitimately run longer.
+pub async fn create_budgeted_pool(
+    database_url: &str,
+    budget: Duration,
+    max_connections: u32,
+) -> Result<PgPool, sqlx::Error> {
+    let options = PgConnectOptions::from_str(database_url)?
+        .options([("statement_timeout", budget.as_millis().to_string())]);
+    PgPoolOptions::new()
+        .max_connections(max_connections)
+        .acquire_timeout(budget)
+        .connect_with(options)
+        .await
+}
+
+/// Whether a query failed on the pool's time budget: Postgres cancelled it
+/// (`query_canceled`) or no connection freed up in time
+pub fn is_timeout(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(e) => e.code().as_deref() == Some("57014"),
+        _ => false,
+    }
+}
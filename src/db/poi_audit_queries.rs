@@ -0,0 +1,137 @@
+use crate::models::poi_anomaly::{FlaggedPoi, PoiAnomaly, RepairPlan};
+use crate::models::BoundingBox;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// POIs outside `bbox`
+pub async fn find_pois_outside_bbox(
+    pool: &PgPool,
+    bbox: &BoundingBox,
+) -> Result<Vec<FlaggedPoi>, sqlx::Error> {
+    let rows: Vec<(Uuid, String, String, f64, f64)> = sqlx::query_as(
+        "SELECT id, name, category,
+                ST_Y(location::geometry) AS lat, ST_X(location::geometry) AS lng
+         FROM pois
+         WHERE NOT ST_Intersects(location::geometry, ST_MakeEnvelope($1, $2, $3, $4, 4326))
+         ORDER BY id",
+    )
+    .bind(bbox.min_lng)
+    .bind(bbox.min_lat)
+    .bind(bbox.max_lng)
+    .bind(bbox.max_lat)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, category, lat, lng)| FlaggedPoi {
+            id,
+            name,
+            category,
+            anomaly: PoiAnomaly::OutsideRegion { lat, lng },
+        })
+        .collect())
+}
+
+pub async fn find_pois_with_empty_names(pool: &PgPool) -> Result<Vec<FlaggedPoi>, sqlx::Error> {
+    let rows: Vec<(Uuid, String, String)> =
+        sqlx::query_as("SELECT id, name, category FROM pois WHERE btrim(name) = '' ORDER BY id")
+            .fetch_all(pool)
+            .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, category)| FlaggedPoi {
+            id,
+            name,
+            category,
+            anomaly: PoiAnomaly::EmptyName,
+        })
+        .collect())
+}
+
+/// Every row but one of each group sharing an `osm_id` and a name. The
+/// most popular row of a group is kept. Rows sharing only the id are a
+/// node and a way that happen to be numbered alike, and are left alone.
+pub async fn find_duplicate_osm_ids(pool: &PgPool) -> Result<Vec<FlaggedPoi>, sqlx::Error> {
+    let rows: Vec<(Uuid, String, String, i64, Uuid)> = sqlx::query_as(
+        "SELECT id, name, category, osm_id, kept
+         FROM (
+             SELECT id, name, category, osm_id,
+                    first_value(id) OVER w AS kept,
+                    row_number() OVER w AS rank
+             FROM pois
+             WHERE osm_id IS NOT NULL
+             WINDOW w AS (PARTITION BY osm_id, name
+                          ORDER BY popularity_score DESC NULLS LAST, id)
+         ) ranked
+         WHERE rank > 1
+         ORDER BY osm_id, id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, category, osm_id, kept)| FlaggedPoi {
+            id,
+            name,
+            category,
+            anomaly: PoiAnomaly::DuplicateOsmId { osm_id, kept },
+        })
+        .collect())
+}
+
+/// POIs whose category is not one of `supported`
+pub async fn find_pois_outside_categories(
+    pool: &PgPool,
+    supported: &[String],
+) -> Result<Vec<FlaggedPoi>, sqlx::Error> {
+    let rows: Vec<(Uuid, String, String)> = sqlx::query_as(
+        "SELECT id, name, category FROM pois
+         WHERE NOT (category = ANY($1))
+         ORDER BY id",
+    )
+    .bind(supported)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, category)| FlaggedPoi {
+            id,
+            name,
+            category,
+            anomaly: PoiAnomaly::UnsupportedCategory,
+        })
+        .collect())
+}
+
+/// Apply `plan` in one transaction. Returns (deleted, renamed).
+pub async fn apply_poi_repairs(
+    pool: &PgPool,
+    plan: &RepairPlan,
+) -> Result<(u64, u64), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let deleted = sqlx::query("DELETE FROM pois WHERE id = ANY($1)")
+        .bind(&plan.deletes)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    let (ids, names): (Vec<Uuid>, Vec<String>) = plan.renames.iter().cloned().unzip();
+    let renamed = sqlx::query(
+        "UPDATE pois SET name = renames.name
+         FROM UNNEST($1::uuid[], $2::text[]) AS renames(id, name)
+         WHERE pois.id = renames.id",
+    )
+    .bind(&ids)
+    .bind(&names)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+    Ok((deleted, renamed))
+}
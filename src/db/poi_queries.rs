@@ -1,36 +1,59 @@
-use crate::models::{Coordinates, Poi, PoiCategory};
+use crate::models::{BoundingBox, Coordinates, Poi, PoiCategory};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use super::poi_repository::RawPoiRow;
 
+/// Optional narrowing filters for POI searches. Each must cover the whole
+/// search area; they only let PostgreSQL skip work, never change results.
+#[derive(Debug, Clone, Default)]
+pub struct PoiQueryHints {
+    /// `poi_partition_key` values, for partition pruning
+    pub partition_keys: Option<Vec<String>>,
+    /// Geohash cells scanned through `idx_pois_geohash` before the distance check
+    pub geohash_cells: Option<Vec<String>>,
+}
+
+/// Which optional clauses a query includes (see [`radius_query_sql`])
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueryShape {
+    pub categories: bool,
+    pub partition_keys: bool,
+    pub geohash_cells: bool,
+}
+
+impl QueryShape {
+    fn of(categories: &Option<Vec<String>>, hints: &PoiQueryHints) -> Self {
+        QueryShape {
+            categories: categories.is_some(),
+            partition_keys: hints.partition_keys.is_some(),
+            geohash_cells: hints.geohash_cells.is_some(),
+        }
+    }
+}
+
 pub async fn find_pois_within_radius(
     pool: &PgPool,
     center: &Coordinates,
     radius_meters: f64,
     categories: Option<&[PoiCategory]>,
     limit: i64,
+) -> Result<Vec<Poi>, sqlx::Error> {
+    let hints = PoiQueryHints::default();
+    find_pois_within_radius_with_hints(pool, center, radius_meters, categories, &hints, limit).await
+}
+
+pub async fn find_pois_within_radius_with_hints(
+    pool: &PgPool,
+    center: &Coordinates,
+    radius_meters: f64,
+    categories: Option<&[PoiCategory]>,
+    hints: &PoiQueryHints,
+    limit: i64,
 ) -> Result<Vec<Poi>, sqlx::Error> {
     let point_wkt = format!("POINT({} {})", center.lng, center.lat);
     let category_strs = categories_to_strings(categories);
-
-    let (category_clause, limit_param) = if category_strs.is_some() {
-        ("AND category = ANY($3)", "$4")
-    } else {
-        ("", "$3")
-    };
-
-    let sql = format!(
-        "SELECT id, name, category,
-                ST_Y(location::geometry) as lat, ST_X(location::geometry) as lng,
-                popularity_score, description, estimated_visit_duration_minutes,
-                osm_id, ST_Distance(location, ST_GeogFromText($1)) as distance_meters
-         FROM pois
-         WHERE ST_DWithin(location, ST_GeogFromText($1), $2)
-         {category_clause}
-         ORDER BY distance_meters
-         LIMIT {limit_param}"
-    );
+    let sql = radius_query_sql(QueryShape::of(&category_strs, hints));
 
     let mut query = sqlx::query_as::<_, PoiRow>(&sql)
         .bind(&point_wkt)
@@ -39,6 +62,13 @@ pub async fn find_pois_within_radius(
     if let Some(ref cats) = category_strs {
         query = query.bind(cats);
     }
+    if let Some(ref keys) = hints.partition_keys {
+        query = query.bind(keys);
+    }
+    if let Some(ref cells) = hints.geohash_cells {
+        let (lower, upper) = geohash_ranges(cells);
+        query = query.bind(lower).bind(upper);
+    }
 
     let rows = query.bind(limit).fetch_all(pool).await?;
     Ok(rows
@@ -55,44 +85,263 @@ pub async fn find_pois_in_bbox(
     max_lng: f64,
     categories: Option<&[PoiCategory]>,
     limit: i64,
+) -> Result<Vec<Poi>, sqlx::Error> {
+    let bbox = BoundingBox {
+        min_lat,
+        max_lat,
+        min_lng,
+        max_lng,
+    };
+    find_pois_in_bbox_with_hints(pool, &bbox, categories, &PoiQueryHints::default(), limit).await
+}
+
+/// Bounding box search. Only `hints.partition_keys` applies; the envelope
+/// test is already an index scan. A box crossing the antimeridian is
+/// searched as its two halves (the envelope is planar).
+pub async fn find_pois_in_bbox_with_hints(
+    pool: &PgPool,
+    bbox: &BoundingBox,
+    categories: Option<&[PoiCategory]>,
+    hints: &PoiQueryHints,
+    limit: i64,
 ) -> Result<Vec<Poi>, sqlx::Error> {
     let category_strs = categories_to_strings(categories);
+    let sql = bbox_query_sql(QueryShape {
+        geohash_cells: false,
+        ..QueryShape::of(&category_strs, hints)
+    });
 
-    let (category_clause, limit_param) = if category_strs.is_some() {
-        ("AND category = ANY($5)", "$6")
-    } else {
-        ("", "$5")
-    };
+    let mut pois = Vec::new();
+    for part in bbox.split_at_antimeridian() {
+        let remaining = limit - pois.len() as i64;
+        if remaining <= 0 {
+            break;
+        }
+        let mut query = sqlx::query_as::<_, PoiRow>(&sql)
+            .bind(part.min_lat)
+            .bind(part.max_lat)
+            .bind(part.min_lng)
+            .bind(part.max_lng);
 
-    let sql = format!(
-        "SELECT id, name, category,
-                ST_Y(location::geometry) as lat, ST_X(location::geometry) as lng,
-                popularity_score, description, estimated_visit_duration_minutes,
-                osm_id, NULL::float8 as distance_meters
-         FROM pois
-         WHERE ST_Y(location::geometry) BETWEEN $1 AND $2
-         AND ST_X(location::geometry) BETWEEN $3 AND $4
-         {category_clause}
-         LIMIT {limit_param}"
-    );
+        if let Some(ref cats) = category_strs {
+            query = query.bind(cats);
+        }
+        if let Some(ref keys) = hints.partition_keys {
+            query = query.bind(keys);
+        }
 
-    let mut query = sqlx::query_as::<_, PoiRow>(&sql)
-        .bind(min_lat)
-        .bind(max_lat)
-        .bind(min_lng)
-        .bind(max_lng);
+        let rows = query.bind(remaining).fetch_all(pool).await?;
+        pois.extend(rows.into_iter().map(|row| row.into_raw().into_poi()));
+    }
+    Ok(pois)
+}
 
+/// POIs within `radius_meters` of `path` in one round trip, as (POI,
+/// distance from the path in meters, distance along the path in km),
+/// ordered along the path. Only `hints.partition_keys` applies.
+pub async fn find_pois_near_path_with_hints(
+    pool: &PgPool,
+    path: &[Coordinates],
+    radius_meters: f64,
+    categories: Option<&[PoiCategory]>,
+    hints: &PoiQueryHints,
+    limit: i64,
+) -> Result<Vec<(Poi, f64, f64)>, sqlx::Error> {
+    let line_wkt = format!(
+        "LINESTRING({})",
+        path.iter()
+            .map(|c| format!("{} {}", c.lng, c.lat))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let category_strs = categories_to_strings(categories);
+    let sql = path_query_sql(QueryShape {
+        geohash_cells: false,
+        ..QueryShape::of(&category_strs, hints)
+    });
+
+    let mut query = sqlx::query_as::<_, PathPoiRow>(&sql)
+        .bind(&line_wkt)
+        .bind(radius_meters);
     if let Some(ref cats) = category_strs {
         query = query.bind(cats);
     }
+    if let Some(ref keys) = hints.partition_keys {
+        query = query.bind(keys);
+    }
 
     let rows = query.bind(limit).fetch_all(pool).await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let distance_m = row.poi.distance_meters.unwrap_or_default();
+            (
+                row.poi.into_raw().into_poi(),
+                distance_m,
+                row.distance_along_km,
+            )
+        })
+        .collect())
+}
+
+pub async fn find_pois_by_ids(pool: &PgPool, ids: &[Uuid]) -> Result<Vec<Poi>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, PoiRow>(
+        "SELECT id, name, category,
+                ST_Y(location::geometry) as lat, ST_X(location::geometry) as lng,
+                popularity_score, description, estimated_visit_duration_minutes,
+                osm_id, NULL::float8 as distance_meters
+         FROM pois
+         WHERE id = ANY($1)",
+    )
+    .bind(ids)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.into_raw().into_poi())
+        .collect())
+}
+
+pub async fn find_pois_by_osm_ids(pool: &PgPool, osm_ids: &[i64]) -> Result<Vec<Poi>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, PoiRow>(
+        "SELECT id, name, category,
+                ST_Y(location::geometry) as lat, ST_X(location::geometry) as lng,
+                popularity_score, description, estimated_visit_duration_minutes,
+                osm_id, NULL::float8 as distance_meters
+         FROM pois
+         WHERE osm_id = ANY($1)",
+    )
+    .bind(osm_ids)
+    .fetch_all(pool)
+    .await?;
     Ok(rows
         .into_iter()
         .map(|row| row.into_raw().into_poi())
         .collect())
 }
 
+/// Half-open `[lower, upper)` ranges matching each geohash prefix. The
+/// column uses the "C" collation, where incrementing the last byte gives the
+/// first string past the prefix.
+pub fn geohash_ranges(cells: &[String]) -> (Vec<String>, Vec<String>) {
+    let upper = cells
+        .iter()
+        .map(|cell| {
+            let mut bytes = cell.clone().into_bytes();
+            if let Some(last) = bytes.last_mut() {
+                *last += 1;
+            }
+            String::from_utf8(bytes).unwrap_or_default()
+        })
+        .collect();
+    (cells.to_vec(), upper)
+}
+
+/// Clauses for the optional parts of `shape`, numbered from `$first_param` in
+/// bind order (categories, partition keys, geohash lower/upper bounds).
+/// Returns (join clause, where clauses, limit placeholder).
+fn optional_clauses(first_param: usize, shape: QueryShape) -> (String, String, String) {
+    let mut next = first_param;
+    let mut join = String::new();
+    let mut filters = String::new();
+    if shape.categories {
+        filters.push_str(&format!("AND category = ANY(${next})\n"));
+        next += 1;
+    }
+    if shape.partition_keys {
+        filters.push_str(&format!("AND poi_partition_key(location) = ANY(${next})\n"));
+        next += 1;
+    }
+    if shape.geohash_cells {
+        join = format!(
+            "JOIN unnest(${}::text[], ${}::text[]) AS cell(lower_bound, upper_bound)
+              ON geohash >= cell.lower_bound AND geohash < cell.upper_bound",
+            next,
+            next + 1
+        );
+        next += 2;
+    }
+    (join, filters, format!("${next}"))
+}
+
+/// SQL for [`find_pois_within_radius_with_hints`].
+/// Parameters: `$1` point WKT, `$2` radius (m), then the optional parameters
+/// in [`optional_clauses`] order, then the limit.
+pub fn radius_query_sql(shape: QueryShape) -> String {
+    let (join, filters, limit_param) = optional_clauses(3, shape);
+
+    format!(
+        "SELECT id, name, category,
+                ST_Y(location::geometry) as lat, ST_X(location::geometry) as lng,
+                popularity_score, description, estimated_visit_duration_minutes,
+                osm_id, ST_Distance(location, ST_GeogFromText($1)) as distance_meters
+         FROM pois
+         {join}
+         WHERE ST_DWithin(location, ST_GeogFromText($1), $2)
+         {filters}
+         ORDER BY distance_meters
+         LIMIT {limit_param}"
+    )
+}
+
+/// SQL for [`find_pois_in_bbox_with_hints`].
+/// Parameters: `$1..$4` min/max lat, min/max lng, then the optional
+/// parameters, then the limit. The `&&` envelope test uses
+/// `idx_pois_location_geometry`; the planar box matches the lat/lng bounds exactly.
+pub fn bbox_query_sql(shape: QueryShape) -> String {
+    let (join, filters, limit_param) = optional_clauses(5, shape);
+
+    format!(
+        "SELECT id, name, category,
+                ST_Y(location::geometry) as lat, ST_X(location::geometry) as lng,
+                popularity_score, description, estimated_visit_duration_minutes,
+                osm_id, NULL::float8 as distance_meters
+         FROM pois
+         {join}
+         WHERE location::geometry && ST_MakeEnvelope($3, $1, $4, $2, 4326)
+         {filters}
+         LIMIT {limit_param}"
+    )
+}
+
+/// SQL for [`find_pois_near_path_with_hints`].
+/// Parameters: `$1` path LINESTRING WKT, `$2` radius (m), then the optional
+/// parameters, then the limit. `ST_DWithin` on the geography line uses the
+/// location index; the position along the path is located on the planar line
+/// and scaled by its geodesic length.
+pub fn path_query_sql(shape: QueryShape) -> String {
+    let (join, filters, limit_param) = optional_clauses(3, shape);
+
+    format!(
+        "WITH route AS (
+            SELECT ST_GeogFromText($1) AS g
+         )
+         SELECT id, name, category,
+                ST_Y(location::geometry) as lat, ST_X(location::geometry) as lng,
+                popularity_score, description, estimated_visit_duration_minutes,
+                osm_id, ST_Distance(location, route.g) as distance_meters,
+                ST_LineLocatePoint(route.g::geometry, location::geometry)
+                    * ST_Length(route.g) / 1000.0 as distance_along_km
+         FROM pois
+         CROSS JOIN route
+         {join}
+         WHERE ST_DWithin(location, route.g, $2)
+         {filters}
+         ORDER BY distance_along_km
+         LIMIT {limit_param}"
+    )
+}
+
+/// Whether `pois` has been converted to a partitioned table
+/// (migrations/optional/partition_pois.sql).
+pub async fn pois_partitioned(pool: &PgPool) -> Result<bool, sqlx::Error> {
+    let kind: Option<String> =
+        sqlx::query_scalar("SELECT relkind::text FROM pg_class WHERE oid = to_regclass('pois')")
+            .fetch_optional(pool)
+            .await?;
+    Ok(kind.as_deref() == Some("p"))
+}
+
 fn categories_to_strings(categories: Option<&[PoiCategory]>) -> Option<Vec<String>> {
     categories.map(|cats| cats.iter().map(|c| c.to_string()).collect())
 }
@@ -172,10 +421,16 @@ struct PoiRow {
     description: Option<String>,
     estimated_visit_duration_minutes: Option<i32>,
     osm_id: Option<i64>,
-    #[allow(dead_code)]
     distance_meters: Option<f64>,
 }
 
+#[derive(sqlx::FromRow)]
+struct PathPoiRow {
+    #[sqlx(flatten)]
+    poi: PoiRow,
+    distance_along_km: f64,
+}
+
 impl PoiRow {
     fn into_raw(self) -> RawPoiRow {
         RawPoiRow {
@@ -191,3 +446,44 @@ impl PoiRow {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geohash_ranges_cover_prefixes() {
+        let cells = vec!["u09t".to_string(), "u09z".to_string()];
+        let (lower, upper) = geohash_ranges(&cells);
+        assert_eq!(lower, cells);
+        assert_eq!(upper, vec!["u09u", "u09{"]);
+        assert!("u09tunq" >= "u09t" && "u09tunq" < upper[0].as_str());
+    }
+
+    #[test]
+    fn optional_parameters_are_numbered_in_bind_order() {
+        let sql = radius_query_sql(QueryShape {
+            categories: true,
+            partition_keys: true,
+            geohash_cells: true,
+        });
+        assert!(sql.contains("category = ANY($3)"));
+        assert!(sql.contains("poi_partition_key(location) = ANY($4)"));
+        assert!(sql.contains("unnest($5::text[], $6::text[])"));
+        assert!(sql.contains("LIMIT $7"));
+
+        let sql = bbox_query_sql(QueryShape::default());
+        assert!(sql.contains("LIMIT $5"));
+        assert!(!sql.contains("JOIN"));
+
+        let sql = path_query_sql(QueryShape {
+            categories: true,
+            partition_keys: true,
+            geohash_cells: false,
+        });
+        assert!(sql.contains("category = ANY($3)"));
+        assert!(sql.contains("poi_partition_key(location) = ANY($4)"));
+        assert!(sql.contains("LIMIT $5"));
+        assert!(sql.contains("ORDER BY distance_along_km"));
+    }
+}
@@ -1,5 +1,12 @@
+use crate::constants::{
+    POI_GEOHASH_MAX_PREFILTER_CELLS, POI_GEOHASH_PRECISION, POI_PARTITION_GEOHASH_PRECISION,
+    POI_PARTITION_MAX_HINT_KEYS,
+};
+use crate::db::queries::PoiQueryHints;
 use crate::error::Result;
-use crate::models::{Coordinates, Poi, PoiCategory};
+use crate::models::road_profile::RoadProfile;
+use crate::models::route::SnappedPoi;
+use crate::models::{geohash, BoundingBox, Coordinates, Poi, PoiCategory};
 use async_trait::async_trait;
 use uuid::Uuid;
 
@@ -91,23 +98,97 @@ pub trait PoiRepository: Send + Sync {
         limit: i64,
     ) -> Result<Vec<Poi>>;
 
+    /// POIs with these IDs, in no particular order; unknown IDs are skipped
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Poi>>;
+
+    /// POIs with these OpenStreetMap IDs, in no particular order; unknown
+    /// IDs are skipped
+    async fn find_by_osm_ids(&self, osm_ids: &[i64]) -> Result<Vec<Poi>>;
+
     async fn insert(&self, poi: &Poi) -> Result<Uuid>;
 
     async fn count(&self) -> Result<i64>;
+
+    /// Summarize the roads a path runs along (paved share, busy-road share).
+    /// Backends without way data return `None`.
+    async fn road_profile(&self, _path: &[Coordinates]) -> Result<Option<RoadProfile>> {
+        Ok(None)
+    }
+
+    /// POIs within `radius_meters` of `path`, with their distance from it and
+    /// along it, ordered along the path. Backends that can't query along a
+    /// line return `None` and callers snap a bounding box search themselves.
+    async fn find_near_path(
+        &self,
+        _path: &[Coordinates],
+        _radius_meters: f64,
+        _categories: Option<&[PoiCategory]>,
+        _limit: i64,
+    ) -> Result<Option<Vec<SnappedPoi>>> {
+        Ok(None)
+    }
 }
 
 pub struct PgPoiRepository {
     pool: sqlx::PgPool,
+    /// Add `poi_partition_key` filters so the planner prunes partitions
+    partition_pruning: bool,
+    /// Range-scan `pois.geohash` before the distance check
+    geohash_prefilter: bool,
 }
 
 impl PgPoiRepository {
     pub fn new(pool: sqlx::PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            partition_pruning: false,
+            geohash_prefilter: false,
+        }
+    }
+
+    /// Enable partition pruning hints. Only valid once `pois` is partitioned
+    /// (see [`super::queries::pois_partitioned`]).
+    pub fn with_partition_pruning(mut self, enabled: bool) -> Self {
+        self.partition_pruning = enabled;
+        self
+    }
+
+    /// Enable the geohash pre-filter for radius searches (`POI_GEOHASH_PREFILTER`)
+    pub fn with_geohash_prefilter(mut self, enabled: bool) -> Self {
+        self.geohash_prefilter = enabled;
+        self
     }
 
     pub fn pool(&self) -> &sqlx::PgPool {
         &self.pool
     }
+
+    /// Partition keys covering `bbox`, or `None` to query all partitions
+    fn partition_keys(&self, bbox: &BoundingBox) -> Option<Vec<String>> {
+        if !self.partition_pruning {
+            return None;
+        }
+        // Near the poles the box's longitude span is unreliable
+        if bbox.min_lat < -85.0 || bbox.max_lat > 85.0 {
+            return None;
+        }
+        geohash::covering_cells(
+            bbox,
+            POI_PARTITION_GEOHASH_PRECISION,
+            POI_PARTITION_MAX_HINT_KEYS,
+        )
+    }
+
+    /// Geohash cells covering `bbox` at the finest precision that needs at most
+    /// `POI_GEOHASH_MAX_PREFILTER_CELLS` cells
+    fn geohash_cells(&self, bbox: &BoundingBox) -> Option<Vec<String>> {
+        if !self.geohash_prefilter || bbox.min_lat < -85.0 || bbox.max_lat > 85.0 {
+            return None;
+        }
+        (1..=POI_GEOHASH_PRECISION).rev().find_map(|precision| {
+            geohash::covering_cells(bbox, precision, POI_GEOHASH_MAX_PREFILTER_CELLS)
+        })
+    }
 }
 
 #[async_trait]
@@ -119,11 +200,17 @@ impl PoiRepository for PgPoiRepository {
         categories: Option<&[PoiCategory]>,
         limit: i64,
     ) -> Result<Vec<Poi>> {
-        Ok(super::poi_queries::find_pois_within_radius(
+        let bbox = BoundingBox::from_center_radius(center, radius_meters);
+        let hints = PoiQueryHints {
+            partition_keys: self.partition_keys(&bbox),
+            geohash_cells: self.geohash_cells(&bbox),
+        };
+        Ok(super::poi_queries::find_pois_within_radius_with_hints(
             &self.pool,
             center,
             radius_meters,
             categories,
+            &hints,
             limit,
         )
         .await?)
@@ -138,12 +225,30 @@ impl PoiRepository for PgPoiRepository {
         categories: Option<&[PoiCategory]>,
         limit: i64,
     ) -> Result<Vec<Poi>> {
-        Ok(super::poi_queries::find_pois_in_bbox(
-            &self.pool, min_lat, max_lat, min_lng, max_lng, categories, limit,
+        let bbox = BoundingBox {
+            min_lat,
+            max_lat,
+            min_lng,
+            max_lng,
+        };
+        let hints = PoiQueryHints {
+            partition_keys: self.partition_keys(&bbox),
+            geohash_cells: None,
+        };
+        Ok(super::poi_queries::find_pois_in_bbox_with_hints(
+            &self.pool, &bbox, categories, &hints, limit,
         )
         .await?)
     }
 
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Poi>> {
+        Ok(super::poi_queries::find_pois_by_ids(&self.pool, ids).await?)
+    }
+
+    async fn find_by_osm_ids(&self, osm_ids: &[i64]) -> Result<Vec<Poi>> {
+        Ok(super::poi_queries::find_pois_by_osm_ids(&self.pool, osm_ids).await?)
+    }
+
     async fn insert(&self, poi: &Poi) -> Result<Uuid> {
         Ok(super::poi_queries::insert_poi(&self.pool, poi).await?)
     }
@@ -154,4 +259,49 @@ impl PoiRepository for PgPoiRepository {
             .await?;
         Ok(count)
     }
+
+    /// One `ST_DWithin` query along the path. Paths crossing the
+    /// antimeridian fall back to the caller's bounding box search, since the
+    /// position along the path is located on the planar line.
+    async fn find_near_path(
+        &self,
+        path: &[Coordinates],
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Option<Vec<SnappedPoi>>> {
+        let bbox = BoundingBox::from_path_with_buffer(path, radius_meters);
+        if path.len() < 2 || bbox.crosses_antimeridian() {
+            return Ok(None);
+        }
+        let hints = PoiQueryHints {
+            partition_keys: self.partition_keys(&bbox),
+            geohash_cells: None,
+        };
+        let rows = super::poi_queries::find_pois_near_path_with_hints(
+            &self.pool,
+            path,
+            radius_meters,
+            categories,
+            &hints,
+            limit,
+        )
+        .await?;
+        Ok(Some(
+            rows.into_iter()
+                .map(|(poi, distance_m, along_km)| {
+                    SnappedPoi::new(poi, along_km, distance_m as f32)
+                })
+                .collect(),
+        ))
+    }
+
+    async fn road_profile(&self, path: &[Coordinates]) -> Result<Option<RoadProfile>> {
+        Ok(super::surface_queries::road_profile_along_path(
+            &self.pool,
+            path,
+            crate::constants::PAVED_FRACTION_MATCH_RADIUS_M,
+        )
+        .await?)
+    }
 }
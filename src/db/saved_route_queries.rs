@@ -0,0 +1,224 @@
+use crate::models::saved_route::{SavedRoute, SavedRouteCursor, StaleReason};
+use crate::models::Route;
+use crate::services::tenant::TenantId;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow)]
+struct SavedRouteRow {
+    route_id: Uuid,
+    user_id: String,
+    name: Option<String>,
+    route: sqlx::types::Json<Route>,
+    saved_at: String,
+    stale_reasons: Option<sqlx::types::Json<Vec<StaleReason>>>,
+}
+
+impl From<SavedRouteRow> for SavedRoute {
+    fn from(row: SavedRouteRow) -> Self {
+        SavedRoute {
+            route_id: row.route_id,
+            user: row.user_id,
+            name: row.name,
+            saved_at: row.saved_at,
+            route: with_staleness(row.route, row.stale_reasons),
+        }
+    }
+}
+
+type StoredRoute = (
+    sqlx::types::Json<Route>,
+    Option<sqlx::types::Json<Vec<StaleReason>>>,
+);
+
+/// The stored route, flagged with the consistency check's findings
+fn with_staleness(
+    route: sqlx::types::Json<Route>,
+    stale_reasons: Option<sqlx::types::Json<Vec<StaleReason>>>,
+) -> Route {
+    let mut route = route.0;
+    route.mark_stale(stale_reasons.map(|reasons| reasons.0).unwrap_or_default());
+    route
+}
+
+/// Save `route` for `user` of `tenant`. Saving again, or saving a route with
+/// the same `content_hash` under another id, keeps the original row (its id
+/// and `saved_at`) and updates the name.
+pub async fn save_route(
+    pool: &PgPool,
+    tenant: &TenantId,
+    user: &str,
+    name: Option<&str>,
+    route: &Route,
+) -> Result<SavedRoute, sqlx::Error> {
+    let row = sqlx::query_as::<_, SavedRouteRow>(
+        r#"
+        WITH existing AS (
+            UPDATE saved_routes SET name = $3
+            WHERE tenant_id = $6 AND user_id = $2 AND (route_id = $1 OR content_hash = $5)
+            RETURNING route_id, user_id, name, route, saved_at, stale_reasons
+        ),
+        inserted AS (
+            INSERT INTO saved_routes (route_id, user_id, name, route, content_hash, tenant_id)
+            SELECT $1, $2, $3, $4, $5, $6
+            WHERE NOT EXISTS (SELECT 1 FROM existing)
+            ON CONFLICT (tenant_id, user_id, route_id) DO UPDATE SET name = EXCLUDED.name
+            RETURNING route_id, user_id, name, route, saved_at, stale_reasons
+        )
+        SELECT route_id, user_id, name, route, saved_at::text as saved_at, stale_reasons
+        FROM (SELECT * FROM existing UNION ALL SELECT * FROM inserted) saved
+        ORDER BY saved_at
+        LIMIT 1
+        "#,
+    )
+    .bind(route.id)
+    .bind(user)
+    .bind(name)
+    .bind(sqlx::types::Json(route))
+    .bind(&route.content_hash)
+    .bind(tenant.as_str())
+    .fetch_one(pool)
+    .await?;
+    Ok(row.into())
+}
+
+/// `user`'s saved copy of a route. Route ids are shared between users (e.g.
+/// through cached responses), and each user's copy is as their client sent
+/// it, so a user only ever reads their own.
+pub async fn get_saved_route(
+    pool: &PgPool,
+    tenant: &TenantId,
+    user: &str,
+    route_id: Uuid,
+) -> Result<Option<Route>, sqlx::Error> {
+    let route: Option<StoredRoute> = sqlx::query_as(
+        "SELECT route, stale_reasons FROM saved_routes
+         WHERE tenant_id = $3 AND user_id = $1 AND route_id = $2",
+    )
+    .bind(user)
+    .bind(route_id)
+    .bind(tenant.as_str())
+    .fetch_optional(pool)
+    .await?;
+    Ok(route.map(|(route, stale_reasons)| with_staleness(route, stale_reasons)))
+}
+
+/// A user's saved routes, most recently saved first, after the `after`
+/// position
+pub async fn list_saved_routes(
+    pool: &PgPool,
+    tenant: &TenantId,
+    user: &str,
+    limit: i64,
+    after: Option<&SavedRouteCursor>,
+) -> Result<Vec<SavedRoute>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, SavedRouteRow>(
+        r#"
+        SELECT route_id, user_id, name, route, saved_at::text as saved_at, stale_reasons
+        FROM saved_routes
+        WHERE tenant_id = $5 AND user_id = $1
+          AND ($2::timestamptz IS NULL OR (saved_at, route_id) < ($2::timestamptz, $3::uuid))
+        ORDER BY saved_at DESC, route_id DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(user)
+    .bind(after.map(|cursor| cursor.saved_at.as_str()))
+    .bind(after.map(|cursor| cursor.route_id))
+    .bind(limit)
+    .bind(tenant.as_str())
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|r| r.into()).collect())
+}
+
+/// Store a share link to `user`'s copy of `route_id` valid for `ttl_hours`.
+/// `None` if `token` is already taken.
+pub async fn create_route_share(
+    pool: &PgPool,
+    token: &str,
+    tenant: &TenantId,
+    user: &str,
+    route_id: Uuid,
+    ttl_hours: u32,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO route_shares (token, user_id, route_id, expires_at, tenant_id)
+        VALUES ($1, $2, $3, NOW() + make_interval(hours => $4), $5)
+        ON CONFLICT (token) DO NOTHING
+        RETURNING expires_at::text
+        "#,
+    )
+    .bind(token)
+    .bind(user)
+    .bind(route_id)
+    .bind(ttl_hours as i32)
+    .bind(tenant.as_str())
+    .fetch_optional(pool)
+    .await
+}
+
+/// The sharer's copy of the route behind an unexpired share `token`
+pub async fn get_shared_route(pool: &PgPool, token: &str) -> Result<Option<Route>, sqlx::Error> {
+    let route: Option<StoredRoute> = sqlx::query_as(
+        r#"
+        SELECT sr.route, sr.stale_reasons
+        FROM route_shares rs
+        JOIN saved_routes sr ON sr.tenant_id = rs.tenant_id
+            AND sr.user_id = rs.user_id
+            AND sr.route_id = rs.route_id
+        WHERE rs.token = $1 AND rs.expires_at > NOW()
+        "#,
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await?;
+    Ok(route.map(|(route, stale_reasons)| with_staleness(route, stale_reasons)))
+}
+
+/// Saved routes for the consistency check, least recently checked first:
+/// `(tenant_id, user_id, route_id, route)`
+pub async fn list_saved_routes_to_check(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<(TenantId, String, Uuid, Route)>, sqlx::Error> {
+    let rows: Vec<(String, String, Uuid, sqlx::types::Json<Route>)> = sqlx::query_as(
+        r#"
+        SELECT tenant_id, user_id, route_id, route
+        FROM saved_routes
+        ORDER BY checked_at NULLS FIRST, saved_at
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(tenant, user, route_id, route)| (TenantId::new(tenant), user, route_id, route.0))
+        .collect())
+}
+
+/// Record a consistency check of one saved route; no `reasons` clears the
+/// stale flag
+pub async fn mark_saved_route_checked(
+    pool: &PgPool,
+    tenant: &TenantId,
+    user: &str,
+    route_id: Uuid,
+    reasons: &[StaleReason],
+) -> Result<(), sqlx::Error> {
+    let stale_reasons = (!reasons.is_empty()).then_some(sqlx::types::Json(reasons));
+    sqlx::query(
+        "UPDATE saved_routes SET stale_reasons = $3, checked_at = NOW()
+         WHERE tenant_id = $4 AND user_id = $1 AND route_id = $2",
+    )
+    .bind(user)
+    .bind(route_id)
+    .bind(stale_reasons)
+    .bind(tenant.as_str())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// One row of `scheduled_task_runs`
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TaskRun {
+    pub id: Uuid,
+    pub task: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub status: String,
+    pub detail: Option<String>,
+}
+
+/// Take the task's session advisory lock on `conn` without waiting. Only one
+/// instance runs a task at a time; release on the same connection.
+pub async fn try_lock_task(conn: &mut PgConnection, task: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtext('scheduled_task:' || $1))")
+        .bind(task)
+        .fetch_one(conn)
+        .await
+}
+
+pub async fn unlock_task(conn: &mut PgConnection, task: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_unlock(hashtext('scheduled_task:' || $1))")
+        .bind(task)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert_task_run(pool: &PgPool, task: &str) -> Result<Uuid, sqlx::Error> {
+    sqlx::query_scalar("INSERT INTO scheduled_task_runs (task) VALUES ($1) RETURNING id")
+        .bind(task)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn finish_task_run(
+    pool: &PgPool,
+    id: Uuid,
+    succeeded: bool,
+    detail: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE scheduled_task_runs SET finished_at = NOW(), status = $2, detail = $3 WHERE id = $1",
+    )
+    .bind(id)
+    .bind(if succeeded { "succeeded" } else { "failed" })
+    .bind(detail)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Most recent runs, newest first
+pub async fn recent_task_runs(pool: &PgPool, limit: i64) -> Result<Vec<TaskRun>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, task, started_at::text AS started_at, finished_at::text AS finished_at,
+                status, detail
+         FROM scheduled_task_runs ORDER BY started_at DESC LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
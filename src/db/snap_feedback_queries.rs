@@ -0,0 +1,111 @@
+use crate::models::snap_feedback::{
+    SnapDistanceBucket, SnapFeedbackEvent, SnapRadiusRecommendation,
+};
+use sqlx::PgPool;
+
+pub async fn insert_snap_feedback(
+    pool: &PgPool,
+    events: &[SnapFeedbackEvent],
+) -> Result<u64, sqlx::Error> {
+    let modes: Vec<String> = events.iter().map(|e| e.mode.to_string()).collect();
+    let distances: Vec<f32> = events.iter().map(|e| e.distance_from_path_m).collect();
+    let tapped: Vec<bool> = events.iter().map(|e| e.tapped).collect();
+    let kept: Vec<bool> = events.iter().map(|e| e.kept).collect();
+
+    let result = sqlx::query(
+        "INSERT INTO snap_feedback (transport_mode, distance_from_path_m, tapped, kept)
+         SELECT * FROM UNNEST($1::varchar[], $2::real[], $3::bool[], $4::bool[])",
+    )
+    .bind(&modes)
+    .bind(&distances)
+    .bind(&tapped)
+    .bind(&kept)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Shown/engaged counts per (mode, distance bucket) over the last
+/// `lookback_days`, for feedback within `max_distance_m` of the path.
+/// Buckets are `bucket_m` wide; the distance at exactly `max_distance_m`
+/// falls in the last one.
+pub async fn snap_feedback_counts(
+    pool: &PgPool,
+    bucket_m: f64,
+    max_distance_m: f64,
+    lookback_days: u32,
+) -> Result<Vec<(String, i32, i64, i64)>, sqlx::Error> {
+    let bucket_count = (max_distance_m / bucket_m).ceil() as i32;
+    sqlx::query_as(
+        "SELECT transport_mode,
+                LEAST(FLOOR(distance_from_path_m / $1)::INT, $2 - 1) AS bucket,
+                COUNT(*) AS shown,
+                COUNT(*) FILTER (WHERE tapped OR kept) AS engaged
+         FROM snap_feedback
+         WHERE created_at > NOW() - make_interval(days => $3)
+           AND distance_from_path_m <= $4
+         GROUP BY 1, 2
+         ORDER BY 1, 2",
+    )
+    .bind(bucket_m)
+    .bind(bucket_count)
+    .bind(lookback_days as i32)
+    .bind(max_distance_m)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn upsert_snap_recommendation(
+    pool: &PgPool,
+    rec: &SnapRadiusRecommendation,
+) -> Result<(), sqlx::Error> {
+    let buckets = serde_json::to_value(&rec.buckets).unwrap_or_default();
+    sqlx::query(
+        "INSERT INTO snap_radius_recommendations
+             (transport_mode, current_radius_m, recommended_radius_m, samples, buckets, computed_at)
+         VALUES ($1, $2, $3, $4, $5, NOW())
+         ON CONFLICT (transport_mode) DO UPDATE SET
+             current_radius_m = EXCLUDED.current_radius_m,
+             recommended_radius_m = EXCLUDED.recommended_radius_m,
+             samples = EXCLUDED.samples,
+             buckets = EXCLUDED.buckets,
+             computed_at = EXCLUDED.computed_at",
+    )
+    .bind(&rec.transport_mode)
+    .bind(rec.current_radius_m as f32)
+    .bind(rec.recommended_radius_m.map(|r| r as f32))
+    .bind(rec.samples)
+    .bind(&buckets)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn list_snap_recommendations(
+    pool: &PgPool,
+) -> Result<Vec<SnapRadiusRecommendation>, sqlx::Error> {
+    let rows: Vec<(String, f32, Option<f32>, i64, serde_json::Value, String)> = sqlx::query_as(
+        "SELECT transport_mode, current_radius_m, recommended_radius_m, samples, buckets,
+                computed_at::text
+         FROM snap_radius_recommendations ORDER BY transport_mode",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(transport_mode, current, recommended, samples, buckets, computed_at)| {
+                SnapRadiusRecommendation {
+                    transport_mode,
+                    current_radius_m: current as f64,
+                    recommended_radius_m: recommended.map(|r| r as f64),
+                    samples,
+                    buckets: serde_json::from_value::<Vec<SnapDistanceBucket>>(buckets)
+                        .unwrap_or_default(),
+                    computed_at: Some(computed_at),
+                }
+            },
+        )
+        .collect())
+}
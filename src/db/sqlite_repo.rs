@@ -1,8 +1,17 @@
 use async_trait::async_trait;
-use sqlx::sqlite::SqlitePool;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use futures::TryStreamExt;
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
 use std::collections::HashSet;
+use std::str::FromStr;
+use thiserror::Error;
 use uuid::Uuid;
 
+use crate::constants::{
+    SQLITE_MOBILE_CACHE_KIB, SQLITE_MOBILE_MAX_READ_CONNECTIONS, SQLITE_MOBILE_MMAP_BYTES,
+    SQLITE_SERVER_CACHE_KIB, SQLITE_SERVER_MAX_READ_CONNECTIONS, SQLITE_SERVER_MMAP_BYTES,
+};
 use crate::error::Result;
 use crate::models::{BoundingBox, Coordinates, Poi, PoiCategory};
 
@@ -49,6 +58,48 @@ impl SqlitePoiRow {
         }
         .into_poi()
     }
+
+    /// Feed every column into `hasher`, length-prefixed so adjacent values
+    /// can't run together
+    fn hash_into(&self, hasher: &mut Sha256) {
+        let texts = [
+            Some(&self.id),
+            Some(&self.name),
+            Some(&self.category),
+            self.description.as_ref(),
+        ];
+        for text in texts {
+            hash_text(hasher, text.map(String::as_str));
+        }
+        for number in [self.lat, self.lng, self.popularity_score] {
+            hasher.update(number.to_le_bytes());
+        }
+        let integers = [
+            self.estimated_visit_duration_minutes.map(i64::from),
+            self.osm_id,
+        ];
+        for integer in integers {
+            match integer {
+                Some(integer) => {
+                    hasher.update([1]);
+                    hasher.update(integer.to_le_bytes());
+                }
+                None => hasher.update([0]),
+            }
+        }
+    }
+}
+
+/// Length-prefixed, so adjacent strings can't trade bytes
+fn hash_text(hasher: &mut Sha256, text: Option<&str>) {
+    match text {
+        Some(text) => {
+            hasher.update([1]);
+            hasher.update((text.len() as u64).to_le_bytes());
+            hasher.update(text.as_bytes());
+        }
+        None => hasher.update([0]),
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -66,6 +117,41 @@ fn matches_category_filter(poi: &Poi, filter: &Option<HashSet<PoiCategory>>) ->
     }
 }
 
+// ---------------------------------------------------------------------------
+// Read pool
+// ---------------------------------------------------------------------------
+
+/// Connection settings for serving queries from a region database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqliteReadPoolConfig {
+    pub max_connections: u32,
+    /// Bytes of the file memory-mapped per connection (`PRAGMA mmap_size`)
+    pub mmap_size_bytes: u64,
+    /// Page cache per connection in KiB (`PRAGMA cache_size`)
+    pub cache_size_kib: u32,
+}
+
+impl SqliteReadPoolConfig {
+    /// Server / desktop: one reader per core (capped), large mmap window
+    pub fn server() -> Self {
+        let cores = std::thread::available_parallelism().map_or(2, |n| n.get() as u32);
+        Self {
+            max_connections: cores.clamp(2, SQLITE_SERVER_MAX_READ_CONNECTIONS),
+            mmap_size_bytes: SQLITE_SERVER_MMAP_BYTES,
+            cache_size_kib: SQLITE_SERVER_CACHE_KIB,
+        }
+    }
+
+    /// Phones: few readers, small memory footprint
+    pub fn mobile() -> Self {
+        Self {
+            max_connections: SQLITE_MOBILE_MAX_READ_CONNECTIONS,
+            mmap_size_bytes: SQLITE_MOBILE_MMAP_BYTES,
+            cache_size_kib: SQLITE_MOBILE_CACHE_KIB,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Repository
 // ---------------------------------------------------------------------------
@@ -79,6 +165,40 @@ impl SqlitePoiRepository {
         Self { pool }
     }
 
+    /// Open an existing region database for concurrent reads.
+    ///
+    /// A short-lived writable connection first switches the file to WAL (persisted
+    /// in the file, so readers never block each other or a region update) and
+    /// ensures the schema. The returned pool is `query_only`: write methods
+    /// such as [`insert_batch`](Self::insert_batch) fail on it.
+    pub async fn open_read_pool(
+        path: &str,
+        config: &SqliteReadPoolConfig,
+    ) -> std::result::Result<SqlitePool, sqlx::Error> {
+        let url = format!("sqlite:{}", path);
+        let setup_opts = SqliteConnectOptions::from_str(&url)?
+            .create_if_missing(false)
+            .journal_mode(SqliteJournalMode::Wal);
+        let setup = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(setup_opts)
+            .await?;
+        Self::create_schema(&setup).await?;
+        setup.close().await;
+
+        let read_opts = SqliteConnectOptions::from_str(&url)?
+            .create_if_missing(false)
+            .pragma("query_only", "ON")
+            .pragma("mmap_size", config.mmap_size_bytes.to_string())
+            // Negative cache_size is in KiB rather than pages
+            .pragma("cache_size", format!("-{}", config.cache_size_kib));
+        SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(1)
+            .connect_with(read_opts)
+            .await
+    }
+
     /// Create the SQLite schema (tables + R-tree). Idempotent.
     pub async fn create_schema(pool: &SqlitePool) -> std::result::Result<(), sqlx::Error> {
         sqlx::query(
@@ -177,6 +297,34 @@ impl SqlitePoiRepository {
         Ok(inserted)
     }
 
+    /// Rows whose R-tree entry intersects `bbox`. The R-tree stores plain
+    /// longitudes, so a box crossing the antimeridian is queried as its two
+    /// halves.
+    async fn rtree_candidates(
+        &self,
+        bbox: &BoundingBox,
+    ) -> std::result::Result<Vec<SqlitePoiRow>, sqlx::Error> {
+        let mut rows = Vec::new();
+        for part in bbox.split_at_antimeridian() {
+            let part_rows: Vec<SqlitePoiRow> = sqlx::query_as(
+                "SELECT p.id, p.name, p.category, p.lat, p.lng, p.popularity_score,
+                        p.description, p.estimated_visit_duration_minutes, p.osm_id
+                 FROM pois p
+                 INNER JOIN pois_rtree r ON p.rowid = r.id
+                 WHERE r.max_lat >= ?1 AND r.min_lat <= ?2
+                   AND r.max_lng >= ?3 AND r.min_lng <= ?4",
+            )
+            .bind(part.min_lat)
+            .bind(part.max_lat)
+            .bind(part.min_lng)
+            .bind(part.max_lng)
+            .fetch_all(&self.pool)
+            .await?;
+            rows.extend(part_rows);
+        }
+        Ok(rows)
+    }
+
     /// Set a key/value pair in the `region_meta` table.
     pub async fn set_meta(&self, key: &str, value: &str) -> std::result::Result<(), sqlx::Error> {
         sqlx::query("INSERT OR REPLACE INTO region_meta (key, value) VALUES (?1, ?2)")
@@ -186,6 +334,173 @@ impl SqlitePoiRepository {
             .await?;
         Ok(())
     }
+
+    pub async fn get_meta(&self, key: &str) -> std::result::Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT value FROM region_meta WHERE key = ?1")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Hex SHA-256 over the region's contents: every POI row in `id` order,
+    /// every R-tree entry with the POI it points to, and every `region_meta`
+    /// entry except the checksum and signature themselves. Not over the file
+    /// bytes, which change with page layout.
+    pub async fn content_checksum(&self) -> std::result::Result<String, sqlx::Error> {
+        let mut hasher = Sha256::new();
+
+        let mut rows = sqlx::query_as::<_, SqlitePoiRow>(
+            "SELECT id, name, category, lat, lng, popularity_score,
+                    description, estimated_visit_duration_minutes, osm_id
+             FROM pois ORDER BY id",
+        )
+        .fetch(&self.pool);
+        while let Some(row) = rows.try_next().await? {
+            row.hash_into(&mut hasher);
+        }
+        drop(rows);
+
+        hasher.update(b"pois_rtree");
+        let mut entries = sqlx::query_as::<_, (i64, Option<String>, f64, f64, f64, f64)>(
+            "SELECT r.id, p.id, r.min_lat, r.max_lat, r.min_lng, r.max_lng
+             FROM pois_rtree r LEFT JOIN pois p ON p.rowid = r.id
+             ORDER BY r.id",
+        )
+        .fetch(&self.pool);
+        while let Some((rowid, poi_id, min_lat, max_lat, min_lng, max_lng)) =
+            entries.try_next().await?
+        {
+            hasher.update(rowid.to_le_bytes());
+            hash_text(&mut hasher, poi_id.as_deref());
+            for bound in [min_lat, max_lat, min_lng, max_lng] {
+                hasher.update(bound.to_le_bytes());
+            }
+        }
+        drop(entries);
+
+        hasher.update(b"region_meta");
+        let mut meta = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT key, value FROM region_meta
+             WHERE key NOT IN (?1, ?2, ?3)
+             ORDER BY key",
+        )
+        .bind(META_CHECKSUM)
+        .bind(META_SIGNATURE)
+        .bind(META_PUBLIC_KEY)
+        .fetch(&self.pool);
+        while let Some((key, value)) = meta.try_next().await? {
+            hash_text(&mut hasher, Some(&key));
+            hash_text(&mut hasher, value.as_deref());
+        }
+        drop(meta);
+
+        Ok(hex::encode(hasher.finalize()))
+    }
+
+    /// Record the content checksum in `region_meta` and, with a key, an
+    /// Ed25519 signature over it plus the public key. Call after the last
+    /// POI is written. Returns the checksum.
+    pub async fn write_checksum(
+        &self,
+        signing_key: Option<&SigningKey>,
+    ) -> std::result::Result<String, sqlx::Error> {
+        let checksum = self.content_checksum().await?;
+        self.set_meta(META_CHECKSUM, &checksum).await?;
+        if let Some(key) = signing_key {
+            let signature = key.sign(checksum.as_bytes());
+            self.set_meta(META_SIGNATURE, &hex::encode(signature.to_bytes()))
+                .await?;
+            self.set_meta(
+                META_PUBLIC_KEY,
+                &hex::encode(key.verifying_key().to_bytes()),
+            )
+            .await?;
+        }
+        Ok(checksum)
+    }
+
+    /// Check the region's contents against the recorded checksum and, given a
+    /// `trusted_key`, that the checksum was signed with it. The public key
+    /// stored in the file is informational and never trusted.
+    pub async fn verify_integrity(
+        &self,
+        trusted_key: Option<&VerifyingKey>,
+    ) -> std::result::Result<(), RegionIntegrityError> {
+        let expected = self
+            .get_meta(META_CHECKSUM)
+            .await?
+            .ok_or(RegionIntegrityError::MissingChecksum)?;
+        let actual = self.content_checksum().await?;
+        if actual != expected {
+            return Err(RegionIntegrityError::ChecksumMismatch { expected, actual });
+        }
+
+        let Some(trusted_key) = trusted_key else {
+            return Ok(());
+        };
+        let signature = self
+            .get_meta(META_SIGNATURE)
+            .await?
+            .ok_or(RegionIntegrityError::Unsigned)?;
+        let signature = hex::decode(signature)
+            .ok()
+            .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+            .map(|bytes| Signature::from_bytes(&bytes))
+            .ok_or(RegionIntegrityError::BadSignature)?;
+        trusted_key
+            .verify(expected.as_bytes(), &signature)
+            .map_err(|_| RegionIntegrityError::BadSignature)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Integrity
+// ---------------------------------------------------------------------------
+
+/// `region_meta` key of the hex SHA-256 from [`SqlitePoiRepository::content_checksum`]
+pub const META_CHECKSUM: &str = "checksum_sha256";
+/// `region_meta` key of the hex Ed25519 signature over the checksum
+pub const META_SIGNATURE: &str = "signature_ed25519";
+/// `region_meta` key of the hex public key the region was signed with
+pub const META_PUBLIC_KEY: &str = "signing_public_key";
+
+/// Why a region database failed [`SqlitePoiRepository::verify_integrity`]
+#[derive(Error, Debug)]
+pub enum RegionIntegrityError {
+    #[error("region has no checksum (built before checksums were recorded)")]
+    MissingChecksum,
+
+    #[error("region data does not match its checksum (expected {expected}, got {actual})")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("region is not signed")]
+    Unsigned,
+
+    #[error("region signature is invalid or from another key")]
+    BadSignature,
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Ed25519 key from 32 hex-encoded bytes: a secret seed for
+/// [`SigningKey`], a public key for [`VerifyingKey`]
+pub fn parse_signing_key(hex_seed: &str) -> std::result::Result<SigningKey, String> {
+    let bytes = key_bytes(hex_seed)?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+/// See [`parse_signing_key`]
+pub fn parse_verifying_key(hex_key: &str) -> std::result::Result<VerifyingKey, String> {
+    let bytes = key_bytes(hex_key)?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid Ed25519 public key: {}", e))
+}
+
+fn key_bytes(hex_key: &str) -> std::result::Result<[u8; 32], String> {
+    hex::decode(hex_key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| "Ed25519 keys must be 32 hex-encoded bytes".to_string())
 }
 
 #[async_trait]
@@ -200,20 +515,7 @@ impl PoiRepository for SqlitePoiRepository {
         let bbox = BoundingBox::from_center_radius(center, radius_meters);
 
         // R-tree pre-filter
-        let rows: Vec<SqlitePoiRow> = sqlx::query_as(
-            "SELECT p.id, p.name, p.category, p.lat, p.lng, p.popularity_score,
-                    p.description, p.estimated_visit_duration_minutes, p.osm_id
-             FROM pois p
-             INNER JOIN pois_rtree r ON p.rowid = r.id
-             WHERE r.max_lat >= ?1 AND r.min_lat <= ?2
-               AND r.max_lng >= ?3 AND r.min_lng <= ?4",
-        )
-        .bind(bbox.min_lat)
-        .bind(bbox.max_lat)
-        .bind(bbox.min_lng)
-        .bind(bbox.max_lng)
-        .fetch_all(&self.pool)
-        .await?;
+        let rows = self.rtree_candidates(&bbox).await?;
 
         let cat_filter = build_category_filter(categories);
 
@@ -251,20 +553,13 @@ impl PoiRepository for SqlitePoiRepository {
         categories: Option<&[PoiCategory]>,
         limit: i64,
     ) -> Result<Vec<Poi>> {
-        let rows: Vec<SqlitePoiRow> = sqlx::query_as(
-            "SELECT p.id, p.name, p.category, p.lat, p.lng, p.popularity_score,
-                    p.description, p.estimated_visit_duration_minutes, p.osm_id
-             FROM pois p
-             INNER JOIN pois_rtree r ON p.rowid = r.id
-             WHERE r.max_lat >= ?1 AND r.min_lat <= ?2
-               AND r.max_lng >= ?3 AND r.min_lng <= ?4",
-        )
-        .bind(min_lat)
-        .bind(max_lat)
-        .bind(min_lng)
-        .bind(max_lng)
-        .fetch_all(&self.pool)
-        .await?;
+        let bbox = BoundingBox {
+            min_lat,
+            max_lat,
+            min_lng,
+            max_lng,
+        };
+        let rows = self.rtree_candidates(&bbox).await?;
 
         let cat_filter = build_category_filter(categories);
 
@@ -278,6 +573,38 @@ impl PoiRepository for SqlitePoiRepository {
         Ok(results)
     }
 
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Poi>> {
+        let mut pois = Vec::with_capacity(ids.len());
+        for id in ids {
+            let row: Option<SqlitePoiRow> = sqlx::query_as(
+                "SELECT id, name, category, lat, lng, popularity_score,
+                        description, estimated_visit_duration_minutes, osm_id
+                 FROM pois WHERE id = ?1",
+            )
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+            pois.extend(row.map(SqlitePoiRow::into_poi));
+        }
+        Ok(pois)
+    }
+
+    async fn find_by_osm_ids(&self, osm_ids: &[i64]) -> Result<Vec<Poi>> {
+        let mut pois = Vec::with_capacity(osm_ids.len());
+        for osm_id in osm_ids {
+            let row: Option<SqlitePoiRow> = sqlx::query_as(
+                "SELECT id, name, category, lat, lng, popularity_score,
+                        description, estimated_visit_duration_minutes, osm_id
+                 FROM pois WHERE osm_id = ?1",
+            )
+            .bind(osm_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            pois.extend(row.map(SqlitePoiRow::into_poi));
+        }
+        Ok(pois)
+    }
+
     async fn insert(&self, poi: &Poi) -> Result<Uuid> {
         let mut tx = self.pool.begin().await?;
 
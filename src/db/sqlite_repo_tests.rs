@@ -1,5 +1,8 @@
 use super::*;
-use sqlx::sqlite::SqlitePoolOptions;
+use crate::constants::METERS_PER_DEGREE;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::str::FromStr;
+use std::sync::Arc;
 
 async fn setup_test_repo() -> SqlitePoiRepository {
     let pool = SqlitePoolOptions::new()
@@ -166,8 +169,8 @@ async fn find_within_radius_haversine_rejects_bbox_corners() {
     // Place POI at the bbox corner (diagonal from center).
     // Bbox extends ~0.009° in each direction. Corner is at (0.009, 0.009),
     // which is ~sqrt(2) * 1km ≈ 1.41km from center — outside the 1km circle.
-    let lat_delta = radius_m / 111_000.0;
-    let lng_delta = radius_m / (111_000.0 * 0.0_f64.to_radians().cos());
+    let lat_delta = radius_m / METERS_PER_DEGREE;
+    let lng_delta = radius_m / (METERS_PER_DEGREE * 0.0_f64.to_radians().cos());
     let corner_lat = lat_delta * 0.99; // just inside bbox
     let corner_lng = lng_delta * 0.99;
 
@@ -230,6 +233,56 @@ async fn find_in_bbox_category_filter() {
     assert_eq!(results[0].name, "Park");
 }
 
+#[tokio::test]
+async fn find_within_radius_across_antimeridian() {
+    let repo = setup_test_repo().await;
+
+    // Taveuni, Fiji: the 180th meridian runs through the island
+    repo.insert(&make_poi("West", PoiCategory::Viewpoint, -16.85, 179.99))
+        .await
+        .unwrap();
+    repo.insert(&make_poi("East", PoiCategory::Viewpoint, -16.85, -179.99))
+        .await
+        .unwrap();
+    repo.insert(&make_poi("Far", PoiCategory::Viewpoint, -16.85, 179.5))
+        .await
+        .unwrap();
+
+    let center = Coordinates::new(-16.85, 180.0 - 1e-9).unwrap();
+    let results = repo
+        .find_within_radius(&center, 3000.0, None, 100)
+        .await
+        .unwrap();
+    let mut names: Vec<_> = results.iter().map(|p| p.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["East", "West"]);
+}
+
+#[tokio::test]
+async fn find_in_bbox_across_antimeridian() {
+    let repo = setup_test_repo().await;
+
+    repo.insert(&make_poi("West", PoiCategory::Park, -16.85, 179.95))
+        .await
+        .unwrap();
+    repo.insert(&make_poi("East", PoiCategory::Park, -16.85, -179.95))
+        .await
+        .unwrap();
+    repo.insert(&make_poi("Greenwich", PoiCategory::Park, -16.85, 0.0))
+        .await
+        .unwrap();
+
+    // GeoJSON convention (min_lng > max_lng) and continuous longitudes
+    // describe the same box
+    for (min_lng, max_lng) in [(179.9, -179.9), (179.9, 180.1)] {
+        let results = repo
+            .find_in_bbox(-17.0, -16.7, min_lng, max_lng, None, 100)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2, "bbox {min_lng}..{max_lng}");
+    }
+}
+
 #[tokio::test]
 async fn insert_rtree_sync() {
     let repo = setup_test_repo().await;
@@ -297,6 +350,77 @@ async fn set_and_read_meta() {
     assert_eq!(val, "paris");
 }
 
+#[tokio::test]
+async fn checksum_detects_modified_pois() {
+    let repo = setup_test_repo().await;
+    repo.insert_batch(&[
+        make_poi("A", PoiCategory::Monument, 48.85, 2.35),
+        make_poi("B", PoiCategory::Park, 48.86, 2.36),
+    ])
+    .await
+    .unwrap();
+    assert!(matches!(
+        repo.verify_integrity(None).await,
+        Err(RegionIntegrityError::MissingChecksum)
+    ));
+
+    let checksum = repo.write_checksum(None).await.unwrap();
+    assert_eq!(checksum.len(), 64);
+    assert_eq!(repo.content_checksum().await.unwrap(), checksum);
+    repo.verify_integrity(None).await.unwrap();
+
+    // Any table tampered with fails, each on its own
+    let tampering = [
+        "UPDATE pois SET lat = lat + 0.001 WHERE name = 'A'",
+        "UPDATE pois_rtree SET min_lat = 0, max_lat = 90 WHERE id = 1",
+        "INSERT INTO region_meta (key, value) VALUES ('min_lat', '-90')",
+    ];
+    for statement in tampering {
+        sqlx::query(statement).execute(&repo.pool).await.unwrap();
+        assert!(
+            matches!(
+                repo.verify_integrity(None).await,
+                Err(RegionIntegrityError::ChecksumMismatch { .. })
+            ),
+            "{statement}"
+        );
+        repo.write_checksum(None).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn signature_verifies_against_trusted_key_only() {
+    let repo = setup_test_repo().await;
+    repo.insert(&make_poi("A", PoiCategory::Monument, 48.85, 2.35))
+        .await
+        .unwrap();
+    let key = parse_signing_key(&"01".repeat(32)).unwrap();
+    let other = parse_signing_key(&"02".repeat(32)).unwrap();
+
+    // Unsigned regions fail only when the caller asks for a signature
+    repo.write_checksum(None).await.unwrap();
+    repo.verify_integrity(None).await.unwrap();
+    assert!(matches!(
+        repo.verify_integrity(Some(&key.verifying_key())).await,
+        Err(RegionIntegrityError::Unsigned)
+    ));
+
+    repo.write_checksum(Some(&key)).await.unwrap();
+    repo.verify_integrity(Some(&key.verifying_key()))
+        .await
+        .unwrap();
+    assert!(matches!(
+        repo.verify_integrity(Some(&other.verifying_key())).await,
+        Err(RegionIntegrityError::BadSignature)
+    ));
+    let public_key = repo.get_meta(META_PUBLIC_KEY).await.unwrap().unwrap();
+    assert_eq!(
+        parse_verifying_key(&public_key).unwrap(),
+        key.verifying_key()
+    );
+    assert!(parse_signing_key("not hex").is_err());
+}
+
 #[tokio::test]
 async fn insert_null_optionals() {
     let repo = setup_test_repo().await;
@@ -324,3 +448,110 @@ async fn insert_null_optionals() {
     assert!(results[0].estimated_visit_duration_minutes.is_none());
     assert!(results[0].osm_id.is_none());
 }
+
+/// Region file in the temp dir, pre-populated through a writable pool
+async fn temp_region_db(pois: &[Poi]) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("easyroute-read-pool-{}.db", Uuid::new_v4()));
+    let opts = SqliteConnectOptions::from_str(&format!("sqlite:{}", path.display()))
+        .unwrap()
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await
+        .unwrap();
+    SqlitePoiRepository::create_schema(&pool).await.unwrap();
+    let repo = SqlitePoiRepository::new(pool.clone());
+    for poi in pois {
+        repo.insert(poi).await.unwrap();
+    }
+    pool.close().await;
+    path
+}
+
+fn remove_region_db(path: &std::path::Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+#[tokio::test]
+async fn read_pool_serves_concurrent_queries() {
+    let pois: Vec<Poi> = (0..20)
+        .map(|i| {
+            make_poi(
+                &format!("P{i}"),
+                PoiCategory::Park,
+                48.85 + i as f64 * 0.001,
+                2.35,
+            )
+        })
+        .collect();
+    let path = temp_region_db(&pois).await;
+    let config = SqliteReadPoolConfig {
+        max_connections: 4,
+        ..SqliteReadPoolConfig::mobile()
+    };
+    let pool = SqlitePoiRepository::open_read_pool(path.to_str().unwrap(), &config)
+        .await
+        .unwrap();
+    let repo = Arc::new(SqlitePoiRepository::new(pool.clone()));
+
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(journal_mode.to_lowercase(), "wal");
+
+    let center = Coordinates::new(48.86, 2.35).unwrap();
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let repo = repo.clone();
+            tokio::spawn(async move { repo.find_within_radius(&center, 5000.0, None, 50).await })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.await.unwrap().unwrap().len(), 20);
+    }
+
+    pool.close().await;
+    remove_region_db(&path);
+}
+
+#[tokio::test]
+async fn read_pool_rejects_writes() {
+    let path = temp_region_db(&[]).await;
+    let pool = SqlitePoiRepository::open_read_pool(
+        path.to_str().unwrap(),
+        &SqliteReadPoolConfig::mobile(),
+    )
+    .await
+    .unwrap();
+    let repo = SqlitePoiRepository::new(pool.clone());
+
+    let result = repo
+        .insert(&make_poi("A", PoiCategory::Monument, 48.85, 2.35))
+        .await;
+    assert!(result.is_err());
+
+    pool.close().await;
+    remove_region_db(&path);
+}
+
+#[tokio::test]
+async fn read_pool_requires_existing_file() {
+    let path = std::env::temp_dir().join(format!("easyroute-missing-{}.db", Uuid::new_v4()));
+    let result = SqlitePoiRepository::open_read_pool(
+        path.to_str().unwrap(),
+        &SqliteReadPoolConfig::mobile(),
+    )
+    .await;
+    assert!(result.is_err());
+}
+
+#[test]
+fn server_read_pool_has_multiple_connections() {
+    let config = SqliteReadPoolConfig::server();
+    assert!(config.max_connections >= 2);
+    assert!(config.mmap_size_bytes > SqliteReadPoolConfig::mobile().mmap_size_bytes);
+}
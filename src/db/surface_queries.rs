@@ -0,0 +1,82 @@
+use crate::constants::{
+    BUSY_ROAD_HIGHWAYS, PAVED_BY_DEFAULT_HIGHWAYS, PAVED_SURFACES, ROAD_SEGMENT_ON_ROUTE_MIN_SHARE,
+    SMOOTH_SURFACES,
+};
+use crate::models::road_profile::RoadProfile;
+use crate::models::Coordinates;
+use sqlx::PgPool;
+
+/// Summarize the road segments a path runs along.
+/// Segments within `match_radius_m` of the path are clipped to that corridor and
+/// weighted by length. Steps and inclines only count for ways that mostly run
+/// inside the corridor, so stairs crossing the route don't flag it.
+/// Returns `None` when no road segments are nearby (no data).
+pub async fn road_profile_along_path(
+    pool: &PgPool,
+    path: &[Coordinates],
+    match_radius_m: f64,
+) -> Result<Option<RoadProfile>, sqlx::Error> {
+    if path.len() < 2 {
+        return Ok(None);
+    }
+
+    let line_wkt = format!(
+        "LINESTRING({})",
+        path.iter()
+            .map(|c| format!("{} {}", c.lng, c.lat))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let row: (Option<f64>, Option<f64>, Option<f64>, i64, Option<f32>) = sqlx::query_as(
+        r#"
+        WITH route AS (
+            SELECT ST_GeogFromText($1) AS g
+        ),
+        matched AS (
+            SELECT
+                ST_Length(ST_Intersection(r.geom::geometry, ST_Buffer(route.g, $2)::geometry)::geography) AS len,
+                ST_Length(r.geom) AS way_len,
+                (r.surface = ANY($3) OR (r.surface IS NULL AND r.highway = ANY($4))) AS paved,
+                r.highway = ANY($5) AS busy,
+                (r.surface = ANY($6) OR (r.surface IS NULL AND r.highway = ANY($4))) AS smooth,
+                r.highway = 'steps' AS steps,
+                r.incline_pct
+            FROM road_segments r, route
+            WHERE ST_DWithin(r.geom, route.g, $2)
+        ),
+        on_route AS (
+            SELECT * FROM matched WHERE len >= $7 * NULLIF(way_len, 0)
+        )
+        SELECT
+            (COALESCE(SUM(len) FILTER (WHERE paved), 0) / NULLIF(SUM(len), 0))::float8,
+            (COALESCE(SUM(len) FILTER (WHERE busy), 0) / NULLIF(SUM(len), 0))::float8,
+            (COALESCE(SUM(len) FILTER (WHERE smooth), 0) / NULLIF(SUM(len), 0))::float8,
+            (SELECT COUNT(*) FROM on_route WHERE steps),
+            (SELECT MAX(incline_pct) FROM on_route)
+        FROM matched
+        "#,
+    )
+    .bind(&line_wkt)
+    .bind(match_radius_m)
+    .bind(PAVED_SURFACES)
+    .bind(PAVED_BY_DEFAULT_HIGHWAYS)
+    .bind(BUSY_ROAD_HIGHWAYS)
+    .bind(SMOOTH_SURFACES)
+    .bind(ROAD_SEGMENT_ON_ROUTE_MIN_SHARE)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(match row {
+        (Some(paved), Some(busy), Some(smooth), steps_count, max_incline_pct) => {
+            Some(RoadProfile {
+                paved_fraction: paved as f32,
+                busy_road_fraction: busy as f32,
+                smooth_fraction: smooth as f32,
+                steps_count: steps_count as u32,
+                max_incline_pct,
+            })
+        }
+        _ => None,
+    })
+}
@@ -6,6 +6,8 @@ use axum::{
 use serde_json::json;
 use thiserror::Error;
 
+use crate::services::dependency_guard::Dependency;
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database error: {0}")]
@@ -23,20 +25,127 @@ pub enum AppError {
     #[error("Route generation failed: {0}")]
     RouteGeneration(String),
 
+    #[error("Route generation failed: {0}")]
+    GenerationFailed(GenerationFailure),
+
     #[error("No POIs found in database: {0}")]
     NoPoisFound(String),
 
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    #[error("{} error: {}", .dependency.name(), .message)]
+    RoutingBackend {
+        dependency: Dependency,
+        message: String,
+    },
+
+    #[error("{} timed out after {}ms", .dependency.name(), .after_ms)]
+    Timeout {
+        dependency: Dependency,
+        after_ms: u64,
+    },
+
     #[error("Internal server error: {0}")]
     Internal(String),
 }
 
+/// Why route generation failed, with a hint the client can act on
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+pub enum GenerationFailure {
+    #[error("not enough points of interest in this area")]
+    NoPoisInArea,
+
+    #[error("no loop came within tolerance of the requested distance (closest: {best_km:.1}km)")]
+    AllAttemptsOutOfTolerance { best_km: f64 },
+
+    #[error("routing backend unavailable")]
+    BackendUnavailable,
+
+    #[error("every candidate loop was rejected for its shape")]
+    ShapeRejected,
+
+    #[error("the routing backend found no path between the waypoints")]
+    Unroutable,
+}
+
+impl GenerationFailure {
+    /// Stable snake_case code returned as `reason`
+    pub fn code(&self) -> &'static str {
+        match self {
+            GenerationFailure::NoPoisInArea => "no_pois_in_area",
+            GenerationFailure::AllAttemptsOutOfTolerance { .. } => "all_attempts_out_of_tolerance",
+            GenerationFailure::BackendUnavailable => "backend_unavailable",
+            GenerationFailure::ShapeRejected => "shape_rejected",
+            GenerationFailure::Unroutable => "unroutable",
+        }
+    }
+
+    pub fn hint(&self) -> String {
+        match self {
+            GenerationFailure::NoPoisInArea => {
+                "try a longer distance or a start point closer to parks and landmarks".to_string()
+            }
+            GenerationFailure::AllAttemptsOutOfTolerance { best_km } => {
+                let low = (best_km * 0.75).floor().max(1.0);
+                let high = (best_km * 1.25).ceil().max(low + 1.0);
+                format!("try {:.0}–{:.0} km in this area", low, high)
+            }
+            GenerationFailure::BackendUnavailable => "retry in a minute".to_string(),
+            GenerationFailure::ShapeRejected => {
+                "try a different start point or distance".to_string()
+            }
+            GenerationFailure::Unroutable => {
+                "try a start point on a road or path the selected mode can use".to_string()
+            }
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            GenerationFailure::NoPoisInArea => StatusCode::NOT_FOUND,
+            GenerationFailure::BackendUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            GenerationFailure::AllAttemptsOutOfTolerance { .. }
+            | GenerationFailure::ShapeRejected
+            | GenerationFailure::Unroutable => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+impl IntoResponse for GenerationFailure {
+    fn into_response(self) -> Response {
+        tracing::warn!(reason = self.code(), "Route generation failed: {}", self);
+        let status = self.status();
+        let body = Json(json!({
+            "error": status.canonical_reason().unwrap_or("Unknown error"),
+            "message": self.to_string(),
+            "reason": self.code(),
+            "hint": self.hint(),
+        }));
+        (status, body).into_response()
+    }
+}
+
 // Convert AppError into HTTP responses
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
+            AppError::Database(ref e) if crate::db::is_timeout(e) => {
+                tracing::warn!("Database error: {}", e);
+                (StatusCode::GATEWAY_TIMEOUT, "Upstream dependency timed out")
+            }
             AppError::Database(ref e) => {
                 tracing::error!("Database error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
@@ -45,6 +154,10 @@ impl IntoResponse for AppError {
                 tracing::error!("Mapbox API error: {}", e);
                 (StatusCode::BAD_GATEWAY, "Routing service error")
             }
+            AppError::RoutingBackend { .. } => {
+                tracing::error!("{}", self);
+                (StatusCode::BAD_GATEWAY, "Routing service error")
+            }
             AppError::Cache(ref e) => {
                 tracing::warn!("Cache error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Cache error")
@@ -59,10 +172,22 @@ impl IntoResponse for AppError {
                 (StatusCode::NOT_FOUND, e.as_str())
             }
             AppError::NotFound(ref e) => (StatusCode::NOT_FOUND, e.as_str()),
+            AppError::Unauthorized(ref e) => (StatusCode::UNAUTHORIZED, e.as_str()),
+            AppError::Forbidden(ref e) => (StatusCode::FORBIDDEN, e.as_str()),
+            AppError::ServiceUnavailable(ref e) => (StatusCode::SERVICE_UNAVAILABLE, e.as_str()),
+            AppError::Timeout { .. } => {
+                tracing::warn!("{}", self);
+                (StatusCode::GATEWAY_TIMEOUT, "Upstream dependency timed out")
+            }
+            AppError::Storage(ref e) => {
+                tracing::error!("Storage error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Storage error")
+            }
             AppError::Internal(ref e) => {
                 tracing::error!("Internal error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
             }
+            AppError::GenerationFailed(failure) => return failure.into_response(),
         };
 
         let body = Json(json!({
@@ -92,18 +217,44 @@ mod tests {
         assert_eq!(status_of(err), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[test]
+    fn database_pool_timeout_504() {
+        let err = AppError::Database(sqlx::Error::PoolTimedOut);
+        assert_eq!(status_of(err), StatusCode::GATEWAY_TIMEOUT);
+    }
+
     #[test]
     fn mapbox_api_error_502() {
         let err = AppError::MapboxApi("timeout".into());
         assert_eq!(status_of(err), StatusCode::BAD_GATEWAY);
     }
 
+    #[test]
+    fn routing_backend_error_502() {
+        let err = AppError::RoutingBackend {
+            dependency: Dependency::Valhalla,
+            message: "HTTP 500".into(),
+        };
+        assert_eq!(err.to_string(), "valhalla error: HTTP 500");
+        assert_eq!(status_of(err), StatusCode::BAD_GATEWAY);
+    }
+
     #[test]
     fn cache_error_500() {
         let err = AppError::Cache("connection lost".into());
         assert_eq!(status_of(err), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[test]
+    fn timeout_504() {
+        let err = AppError::Timeout {
+            dependency: Dependency::Postgres,
+            after_ms: 5000,
+        };
+        assert_eq!(err.to_string(), "postgres timed out after 5000ms");
+        assert_eq!(status_of(err), StatusCode::GATEWAY_TIMEOUT);
+    }
+
     #[test]
     fn invalid_request_400() {
         let err = AppError::InvalidRequest("bad field".into());
@@ -116,6 +267,41 @@ mod tests {
         assert_eq!(status_of(err), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[test]
+    fn generation_failure_statuses() {
+        let out_of_tolerance = GenerationFailure::AllAttemptsOutOfTolerance { best_km: 9.2 };
+        assert_eq!(
+            status_of(AppError::GenerationFailed(out_of_tolerance)),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            status_of(AppError::GenerationFailed(
+                GenerationFailure::BackendUnavailable
+            )),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            status_of(AppError::GenerationFailed(GenerationFailure::NoPoisInArea)),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status_of(AppError::GenerationFailed(GenerationFailure::Unroutable)),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    #[tokio::test]
+    async fn generation_failure_body_has_reason_and_hint() {
+        let failure = GenerationFailure::AllAttemptsOutOfTolerance { best_km: 5.0 };
+        let response = AppError::GenerationFailed(failure).into_response();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["reason"], "all_attempts_out_of_tolerance");
+        assert_eq!(body["hint"], "try 3–7 km in this area");
+    }
+
     #[test]
     fn no_pois_found_404() {
         let err = AppError::NoPoisFound("empty area".into());
@@ -128,6 +314,30 @@ mod tests {
         assert_eq!(status_of(err), StatusCode::NOT_FOUND);
     }
 
+    #[test]
+    fn unauthorized_401() {
+        let err = AppError::Unauthorized("missing API key".into());
+        assert_eq!(status_of(err), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn forbidden_403() {
+        let err = AppError::Forbidden("expired link".into());
+        assert_eq!(status_of(err), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn storage_error_500() {
+        let err = AppError::Storage("bucket unreachable".into());
+        assert_eq!(status_of(err), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn service_unavailable_503() {
+        let err = AppError::ServiceUnavailable("mapbox down".into());
+        assert_eq!(status_of(err), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
     #[test]
     fn internal_500() {
         let err = AppError::Internal("unexpected".into());
@@ -3,7 +3,11 @@ use std::path::Path;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
-use crate::evaluation::ScenarioResult;
+use crate::evaluation::cost::CostPerRun;
+use crate::evaluation::significance::{
+    two_proportion_p_value, welch_p_value, Sample, DEFAULT_SIGNIFICANCE_LEVEL,
+};
+use crate::evaluation::{MetricsAggregate, ScenarioResult};
 
 const BASELINE_VERSION: u32 = 1;
 
@@ -22,6 +26,16 @@ pub struct BaselineScenario {
     pub name: String,
     pub success_rate: f32,
     pub metrics: Option<BaselineMetrics>,
+    /// Routes the metrics were aggregated over (0 in baselines saved before
+    /// significance testing, which fall back to thresholds alone)
+    #[serde(default)]
+    pub samples: usize,
+    /// Standard deviation of each metric across those routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub std_dev: Option<BaselineMetrics>,
+    /// Mean API spend per run (absent in baselines saved before cost tracking)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<CostPerRun>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +58,9 @@ pub struct BaselineMetrics {
 pub struct ComparisonReport {
     pub baseline_timestamp: String,
     pub threshold: f32,
+    /// A change past the threshold only regresses when its p-value is below this
+    #[serde(default = "default_significance_level")]
+    pub significance_level: f32,
     pub scenario_comparisons: Vec<ScenarioComparison>,
     pub total_regressions: usize,
     pub new_scenarios: Vec<String>,
@@ -64,6 +81,18 @@ pub struct MetricComparison {
     pub baseline: f32,
     pub change_pct: f32,
     pub regressed: bool,
+    /// The change's p-value (Welch's t-test, or the two-proportion z-test
+    /// for the success rate), when both sides have the spread and run
+    /// counts to compute it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p_value: Option<f32>,
+}
+
+/// Reads one metric out of a [`BaselineMetrics`]
+type MetricField = fn(&BaselineMetrics) -> f32;
+
+fn default_significance_level() -> f32 {
+    DEFAULT_SIGNIFICANCE_LEVEL
 }
 
 // ── Build baseline from results ─────────────────────────────
@@ -74,26 +103,7 @@ impl Baseline {
             .format(&Rfc3339)
             .unwrap_or_else(|_| "unknown".to_string());
 
-        let scenarios = results
-            .iter()
-            .map(|r| {
-                let metrics = r.metrics_agg.as_ref().map(|agg| BaselineMetrics {
-                    circularity: agg.circularity.mean,
-                    convexity: agg.convexity.mean,
-                    path_overlap_pct: agg.path_overlap_pct.mean,
-                    poi_density_per_km: agg.poi_density_per_km.mean,
-                    category_entropy: agg.category_entropy.mean,
-                    landmark_coverage: agg.landmark_coverage.mean,
-                    distance_accuracy: agg.distance_accuracy.mean,
-                    route_score: agg.route_score.mean,
-                });
-                BaselineScenario {
-                    name: r.scenario.name.clone(),
-                    success_rate: r.success_rate,
-                    metrics,
-                }
-            })
-            .collect();
+        let scenarios = results.iter().map(BaselineScenario::from_result).collect();
 
         Baseline {
             version: BASELINE_VERSION,
@@ -104,6 +114,50 @@ impl Baseline {
     }
 }
 
+impl BaselineScenario {
+    pub fn from_result(result: &ScenarioResult) -> Self {
+        let agg = result.metrics_agg.as_ref();
+        BaselineScenario {
+            name: result.scenario.name.clone(),
+            success_rate: result.success_rate,
+            metrics: agg.map(BaselineMetrics::from_aggregate),
+            samples: result.total_routes,
+            std_dev: agg.map(BaselineMetrics::std_dev_of),
+            cost: Some(result.cost.per_run(result.runs)),
+        }
+    }
+}
+
+impl BaselineMetrics {
+    /// Means of an aggregate, as stored in a baseline
+    pub fn from_aggregate(agg: &MetricsAggregate) -> Self {
+        BaselineMetrics {
+            circularity: agg.circularity.mean,
+            convexity: agg.convexity.mean,
+            path_overlap_pct: agg.path_overlap_pct.mean,
+            poi_density_per_km: agg.poi_density_per_km.mean,
+            category_entropy: agg.category_entropy.mean,
+            landmark_coverage: agg.landmark_coverage.mean,
+            distance_accuracy: agg.distance_accuracy.mean,
+            route_score: agg.route_score.mean,
+        }
+    }
+
+    /// Standard deviations of an aggregate
+    pub fn std_dev_of(agg: &MetricsAggregate) -> Self {
+        BaselineMetrics {
+            circularity: agg.circularity.std_dev,
+            convexity: agg.convexity.std_dev,
+            path_overlap_pct: agg.path_overlap_pct.std_dev,
+            poi_density_per_km: agg.poi_density_per_km.std_dev,
+            category_entropy: agg.category_entropy.std_dev,
+            landmark_coverage: agg.landmark_coverage.std_dev,
+            distance_accuracy: agg.distance_accuracy.std_dev,
+            route_score: agg.route_score.std_dev,
+        }
+    }
+}
+
 // ── Save / Load ─────────────────────────────────────────────
 
 pub fn save_baseline(baseline: &Baseline, path: &Path) -> Result<(), String> {
@@ -125,11 +179,36 @@ pub fn load_baseline(path: &Path) -> Result<Baseline, String> {
 // ── Comparison logic ────────────────────────────────────────
 
 /// Compare current evaluation results against a saved baseline.
-/// `threshold` is a fraction (e.g. 0.15 = 15%).
+/// `threshold` is a fraction (e.g. 0.15 = 15%). A metric regresses when it
+/// moves past the threshold in the bad direction and, where the baseline
+/// recorded spreads, Welch's t-test finds the change significant (a
+/// two-proportion z-test for the success rate).
 pub fn compare(
     baseline: &Baseline,
     results: &[ScenarioResult],
     threshold: f32,
+) -> ComparisonReport {
+    let current: Vec<(BaselineScenario, usize)> = results
+        .iter()
+        .map(|r| (BaselineScenario::from_result(r), r.runs))
+        .collect();
+    compare_scenarios(baseline, &current, threshold)
+}
+
+/// Compare two saved baselines, `other` playing the current results
+pub fn diff_baselines(baseline: &Baseline, other: &Baseline, threshold: f32) -> ComparisonReport {
+    let current: Vec<(BaselineScenario, usize)> = other
+        .scenarios
+        .iter()
+        .map(|s| (s.clone(), other.runs_per_scenario))
+        .collect();
+    compare_scenarios(baseline, &current, threshold)
+}
+
+fn compare_scenarios(
+    baseline: &Baseline,
+    current: &[(BaselineScenario, usize)],
+    threshold: f32,
 ) -> ComparisonReport {
     let baseline_map: std::collections::HashMap<&str, &BaselineScenario> = baseline
         .scenarios
@@ -141,99 +220,95 @@ pub fn compare(
     let mut total_regressions = 0;
     let mut new_scenarios = Vec::new();
 
-    for result in results {
-        let name = &result.scenario.name;
+    for (scenario, runs) in current {
+        let name = &scenario.name;
         let Some(base_scenario) = baseline_map.get(name.as_str()) else {
             new_scenarios.push(name.clone());
             continue;
         };
 
-        let mut metric_comparisons = Vec::new();
-
-        // success_rate: higher is better
-        metric_comparisons.push(compare_metric(
+        // success_rate: higher is better. Each run succeeds or not, so it's
+        // tested as a proportion rather than a mean.
+        let mut success = compare_metric(
             "success_rate",
-            result.success_rate,
+            scenario.success_rate,
             base_scenario.success_rate,
             threshold,
             true,
-        ));
+        );
+        apply_p_value(
+            &mut success,
+            two_proportion_p_value(
+                scenario.success_rate as f64,
+                *runs,
+                base_scenario.success_rate as f64,
+                baseline.runs_per_scenario,
+            ),
+        );
+        let mut metric_comparisons = vec![success];
 
         // Compare individual metrics if both sides have them
-        if let (Some(cur_agg), Some(base_m)) = (&result.metrics_agg, &base_scenario.metrics) {
-            // Higher-is-better metrics
-            metric_comparisons.push(compare_metric(
-                "circularity",
-                cur_agg.circularity.mean,
-                base_m.circularity,
-                threshold,
-                true,
-            ));
-            metric_comparisons.push(compare_metric(
-                "convexity",
-                cur_agg.convexity.mean,
-                base_m.convexity,
-                threshold,
-                true,
-            ));
-            metric_comparisons.push(compare_metric(
-                "poi_density_per_km",
-                cur_agg.poi_density_per_km.mean,
-                base_m.poi_density_per_km,
-                threshold,
-                true,
-            ));
-            metric_comparisons.push(compare_metric(
-                "category_entropy",
-                cur_agg.category_entropy.mean,
-                base_m.category_entropy,
-                threshold,
-                true,
-            ));
-            metric_comparisons.push(compare_metric(
-                "landmark_coverage",
-                cur_agg.landmark_coverage.mean,
-                base_m.landmark_coverage,
-                threshold,
-                true,
-            ));
-            // Lower-is-better metric
-            metric_comparisons.push(compare_metric(
-                "path_overlap_pct",
-                cur_agg.path_overlap_pct.mean,
-                base_m.path_overlap_pct,
-                threshold,
-                false,
-            ));
-            // Distance accuracy: closer to 1.0 is better, but we track as higher-is-better
-            // since undersized routes (< 1.0) are the problem we're fixing
-            if base_m.distance_accuracy > 0.0 {
-                metric_comparisons.push(compare_metric(
-                    "distance_accuracy",
-                    cur_agg.distance_accuracy.mean,
-                    base_m.distance_accuracy,
-                    threshold,
-                    true,
-                ));
-            }
-            // Route score: higher is better
-            if base_m.route_score > 0.0 {
-                metric_comparisons.push(compare_metric(
-                    "route_score",
-                    cur_agg.route_score.mean,
-                    base_m.route_score,
+        if let (Some(cur), Some(base_m)) = (&scenario.metrics, &base_scenario.metrics) {
+            // (name, field, higher is better, skipped when the baseline is
+            // zero because older baselines didn't record it)
+            let fields: [(&str, MetricField, bool, bool); 8] = [
+                ("circularity", |m| m.circularity, true, false),
+                ("convexity", |m| m.convexity, true, false),
+                ("poi_density_per_km", |m| m.poi_density_per_km, true, false),
+                ("category_entropy", |m| m.category_entropy, true, false),
+                ("landmark_coverage", |m| m.landmark_coverage, true, false),
+                ("path_overlap_pct", |m| m.path_overlap_pct, false, false),
+                // Closer to 1.0 is better, but tracked as higher-is-better
+                // since undersized routes (< 1.0) are the problem we're fixing
+                ("distance_accuracy", |m| m.distance_accuracy, true, true),
+                ("route_score", |m| m.route_score, true, true),
+            ];
+            for (metric, field, higher_is_better, optional) in fields {
+                if optional && field(base_m) <= 0.0 {
+                    continue;
+                }
+                let mut mc = compare_metric(
+                    metric,
+                    field(cur),
+                    field(base_m),
                     threshold,
-                    true,
-                ));
+                    higher_is_better,
+                );
+                let samples = scenario
+                    .std_dev
+                    .as_ref()
+                    .zip(base_scenario.std_dev.as_ref());
+                apply_significance(
+                    &mut mc,
+                    samples.map(|(cur_sd, base_sd)| {
+                        (
+                            Sample {
+                                mean: field(cur) as f64,
+                                std_dev: field(cur_sd) as f64,
+                                n: scenario.samples,
+                            },
+                            Sample {
+                                mean: field(base_m) as f64,
+                                std_dev: field(base_sd) as f64,
+                                n: base_scenario.samples,
+                            },
+                        )
+                    }),
+                );
+                metric_comparisons.push(mc);
             }
         }
 
+        if let (Some(cur), Some(base_c)) = (&scenario.cost, &base_scenario.cost) {
+            metric_comparisons.extend(compare_cost(cur, base_c, threshold));
+        }
+
         let regressions = metric_comparisons.iter().filter(|m| m.regressed).count();
         total_regressions += regressions;
 
         scenario_comparisons.push(ScenarioComparison {
             name: name.clone(),
-            runs: result.runs,
+            runs: *runs,
             metric_comparisons,
             regressions,
         });
@@ -242,6 +317,7 @@ pub fn compare(
     ComparisonReport {
         baseline_timestamp: baseline.timestamp.clone(),
         threshold,
+        significance_level: DEFAULT_SIGNIFICANCE_LEVEL,
         scenario_comparisons,
         total_regressions,
         new_scenarios,
@@ -276,68 +352,174 @@ fn compare_metric(
         baseline,
         change_pct,
         regressed,
+        p_value: None,
+    }
+}
+
+/// API spend per run: lower is better. Wall-clock is reported but never
+/// flagged, since it mostly tracks network latency to the backends.
+fn compare_cost(
+    current: &CostPerRun,
+    baseline: &CostPerRun,
+    threshold: f32,
+) -> Vec<MetricComparison> {
+    let mut wall_clock = compare_metric(
+        "wall_clock_ms_per_run",
+        current.wall_clock_ms,
+        baseline.wall_clock_ms,
+        threshold,
+        false,
+    );
+    wall_clock.regressed = false;
+    vec![
+        compare_metric(
+            "directions_calls_per_run",
+            current.directions_calls,
+            baseline.directions_calls,
+            threshold,
+            false,
+        ),
+        compare_metric(
+            "poi_queries_per_run",
+            current.poi_queries,
+            baseline.poi_queries,
+            threshold,
+            false,
+        ),
+        wall_clock,
+    ]
+}
+
+/// Record the p-value of `(current, baseline)` and clear a threshold
+/// regression that isn't significant. Without samples (or with too few runs
+/// for a test) the threshold alone decides.
+fn apply_significance(mc: &mut MetricComparison, samples: Option<(Sample, Sample)>) {
+    apply_p_value(
+        mc,
+        samples.and_then(|(current, baseline)| welch_p_value(current, baseline)),
+    );
+}
+
+/// Record `p` and clear a threshold regression that isn't significant
+fn apply_p_value(mc: &mut MetricComparison, p: Option<f64>) {
+    let Some(p) = p else {
+        return;
+    };
+    let p = p as f32;
+    mc.p_value = Some(p);
+    if p >= DEFAULT_SIGNIFICANCE_LEVEL {
+        mc.regressed = false;
     }
 }
 
 // ── Display formatting ──────────────────────────────────────
 
 pub fn format_comparison_report(report: &ComparisonReport) -> String {
-    let mut out = String::new();
+    super::Report::comparison(report).render_text()
+}
 
-    out.push_str(&format!(
-        "Checking against baseline (saved {})...\n",
-        report.baseline_timestamp
-    ));
-    out.push_str(&format!(
-        "Regression threshold: {:.0}%\n",
-        report.threshold * 100.0
-    ));
+// ── CI output ───────────────────────────────────────────────
 
-    for sc in &report.scenario_comparisons {
-        out.push_str(&format!("\nScenario: {} ({} runs)\n", sc.name, sc.runs));
-
-        for mc in &sc.metric_comparisons {
-            let icon = if mc.regressed { "x" } else { "ok" };
-            let change_str = if mc.baseline.abs() < f32::EPSILON {
-                "  (baseline=0)".to_string()
-            } else {
-                format!(
-                    " (baseline: {:.2}, {:+.1}%)",
-                    mc.baseline,
-                    mc.change_pct * 100.0
-                )
-            };
-            let regression_marker = if mc.regressed { " <- REGRESSION" } else { "" };
-            out.push_str(&format!(
-                "  [{icon}] {:<22} {:.2}{change_str}{regression_marker}\n",
-                mc.name, mc.current,
-            ));
+/// One metric's verdict, flattened for merge gates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionRecord {
+    pub scenario: String,
+    pub metric: String,
+    pub baseline: f32,
+    pub current: f32,
+    pub change_pct: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p_value: Option<f32>,
+    pub regressed: bool,
+}
+
+/// Machine-readable outcome of a comparison (`evaluate --output=json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionGate {
+    pub passed: bool,
+    pub total_regressions: usize,
+    pub threshold: f32,
+    pub significance_level: f32,
+    pub baseline_timestamp: String,
+    pub records: Vec<RegressionRecord>,
+    pub new_scenarios: Vec<String>,
+}
+
+impl RegressionGate {
+    pub fn from_report(report: &ComparisonReport) -> Self {
+        let records = report
+            .scenario_comparisons
+            .iter()
+            .flat_map(|sc| {
+                sc.metric_comparisons.iter().map(|mc| RegressionRecord {
+                    scenario: sc.name.clone(),
+                    metric: mc.name.clone(),
+                    baseline: mc.baseline,
+                    current: mc.current,
+                    change_pct: mc.change_pct,
+                    p_value: mc.p_value,
+                    regressed: mc.regressed,
+                })
+            })
+            .collect();
+        RegressionGate {
+            passed: report.total_regressions == 0,
+            total_regressions: report.total_regressions,
+            threshold: report.threshold,
+            significance_level: report.significance_level,
+            baseline_timestamp: report.baseline_timestamp.clone(),
+            records,
+            new_scenarios: report.new_scenarios.clone(),
         }
     }
+}
 
-    if !report.new_scenarios.is_empty() {
-        out.push_str("\nNew scenarios (no baseline):\n");
-        for name in &report.new_scenarios {
-            out.push_str(&format!("  - {name}\n"));
+/// GitHub Actions workflow commands: an `::error` per regressed metric and a
+/// `::notice` per scenario missing from the baseline
+pub fn format_github_annotations(report: &ComparisonReport) -> String {
+    let mut out = String::new();
+    for sc in &report.scenario_comparisons {
+        for mc in sc.metric_comparisons.iter().filter(|mc| mc.regressed) {
+            let p_str = mc
+                .p_value
+                .map(|p| format!(", p={p:.3}"))
+                .unwrap_or_default();
+            let message = format!(
+                "{} regressed to {:.2} (baseline: {:.2}, {:+.1}%{p_str})",
+                mc.name,
+                mc.current,
+                mc.baseline,
+                mc.change_pct * 100.0
+            );
+            out.push_str(&format!(
+                "::error title={}::{}\n",
+                escape_annotation_property(&format!("Regression in {}", sc.name)),
+                escape_annotation_data(&message),
+            ));
         }
     }
-
-    let scenario_count = report.scenario_comparisons.len() + report.new_scenarios.len();
-    if report.total_regressions > 0 {
+    for name in &report.new_scenarios {
         out.push_str(&format!(
-            "\nRESULT: {} regression(s) detected across {} scenarios\n",
-            report.total_regressions, scenario_count,
-        ));
-    } else {
-        out.push_str(&format!(
-            "\nRESULT: No regressions detected across {} scenarios\n",
-            scenario_count,
+            "::notice title={}::{}\n",
+            escape_annotation_property(&format!("New scenario {}", name)),
+            escape_annotation_data("No baseline to compare against; save one with --save-baseline"),
         ));
     }
-
     out
 }
 
+fn escape_annotation_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_annotation_property(s: &str) -> String {
+    escape_annotation_data(s)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
 // ── Tests ───────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -441,11 +623,17 @@ mod tests {
                         distance_accuracy: 0.95,
                         route_score: 7.5,
                     }),
+                    samples: 9,
+                    std_dev: None,
+                    cost: None,
                 },
                 BaselineScenario {
                     name: "no_metrics".to_string(),
                     success_rate: 0.5,
                     metrics: None,
+                    samples: 0,
+                    std_dev: None,
+                    cost: None,
                 },
             ],
         };
@@ -481,6 +669,9 @@ mod tests {
                     distance_accuracy: 0.98,
                     route_score: 8.0,
                 }),
+                samples: 0,
+                std_dev: None,
+                cost: None,
             }],
         };
 
@@ -531,6 +722,8 @@ mod tests {
                     std_dev: 0.5,
                 },
             }),
+            tier_counts: Default::default(),
+            cost: Default::default(),
         };
 
         let report = compare(&baseline, &[result], 0.15);
@@ -561,10 +754,171 @@ mod tests {
             total_routes: 1,
             success_rate: 1.0,
             metrics_agg: None,
+            tier_counts: Default::default(),
+            cost: Default::default(),
         };
 
         let report = compare(&baseline, &[result], 0.15);
         assert_eq!(report.total_regressions, 0);
         assert_eq!(report.new_scenarios, vec!["brand_new"]);
     }
+
+    fn metrics(circularity: f32) -> BaselineMetrics {
+        BaselineMetrics {
+            circularity,
+            convexity: 0.9,
+            path_overlap_pct: 0.1,
+            poi_density_per_km: 3.0,
+            category_entropy: 2.0,
+            landmark_coverage: 0.7,
+            distance_accuracy: 0.98,
+            route_score: 8.0,
+        }
+    }
+
+    fn sampled_baseline(circularity: f32, circularity_sd: f32, samples: usize) -> Baseline {
+        let mut std_dev = metrics(circularity_sd);
+        std_dev.convexity = 0.01;
+        Baseline {
+            version: 1,
+            timestamp: "2026-03-01T00:00:00Z".to_string(),
+            runs_per_scenario: samples,
+            scenarios: vec![BaselineScenario {
+                name: "s1".to_string(),
+                success_rate: 1.0,
+                metrics: Some(metrics(circularity)),
+                samples,
+                std_dev: Some(std_dev),
+                cost: None,
+            }],
+        }
+    }
+
+    fn circularity(report: &ComparisonReport) -> &MetricComparison {
+        report.scenario_comparisons[0]
+            .metric_comparisons
+            .iter()
+            .find(|m| m.name == "circularity")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_noisy_drop_is_not_a_regression() {
+        // -25% with a spread as large as the drop, over three routes each
+        let report = diff_baselines(
+            &sampled_baseline(0.80, 0.25, 3),
+            &sampled_baseline(0.60, 0.25, 3),
+            0.15,
+        );
+        let mc = circularity(&report);
+        assert!(mc.p_value.unwrap() > 0.05);
+        assert!(!mc.regressed);
+        assert_eq!(report.total_regressions, 0);
+    }
+
+    #[test]
+    fn test_significant_drop_is_a_regression() {
+        let report = diff_baselines(
+            &sampled_baseline(0.80, 0.03, 10),
+            &sampled_baseline(0.60, 0.03, 10),
+            0.15,
+        );
+        let mc = circularity(&report);
+        assert!(mc.p_value.unwrap() < 0.001);
+        assert!(mc.regressed);
+        assert_eq!(report.total_regressions, 1);
+        let text = format_comparison_report(&report);
+        let row = text.lines().find(|l| l.contains("circularity")).unwrap();
+        assert!(row.contains(" 0.000 ") && row.ends_with("REGRESSION"));
+    }
+
+    #[test]
+    fn test_significant_change_within_threshold_is_not_a_regression() {
+        // Tiny spreads make a -5% change significant, but it's under 15%
+        let report = diff_baselines(
+            &sampled_baseline(0.80, 0.001, 10),
+            &sampled_baseline(0.76, 0.001, 10),
+            0.15,
+        );
+        assert!(circularity(&report).p_value.unwrap() < 0.001);
+        assert_eq!(report.total_regressions, 0);
+    }
+
+    #[test]
+    fn test_success_rate_is_tested_as_a_proportion() {
+        let success_rate = |rate: f32, runs: usize| {
+            let mut current = sampled_baseline(0.80, 0.03, runs);
+            current.scenarios[0].success_rate = rate;
+            let report = diff_baselines(&sampled_baseline(0.80, 0.03, runs), &current, 0.15);
+            report.scenario_comparisons[0].metric_comparisons[0].clone()
+        };
+        // A perfect baseline has no spread, but 7/10 against 10/10 is still
+        // within chance
+        let mc = success_rate(0.7, 10);
+        assert!((mc.p_value.unwrap() - 0.06).abs() < 0.005);
+        assert!(!mc.regressed);
+        let mc = success_rate(0.7, 100);
+        assert!(mc.regressed);
+    }
+
+    #[test]
+    fn test_doubled_api_spend_is_a_regression() {
+        let with_cost = |directions_calls: f32, wall_clock_ms: f32| {
+            let mut baseline = sampled_baseline(0.80, 0.03, 10);
+            baseline.scenarios[0].cost = Some(CostPerRun {
+                directions_calls,
+                poi_queries: 4.0,
+                wall_clock_ms,
+            });
+            baseline
+        };
+        // Same quality, twice the directions calls and slower backends
+        let report = diff_baselines(&with_cost(6.0, 800.0), &with_cost(12.0, 2000.0), 0.15);
+        let regressed: Vec<&str> = report.scenario_comparisons[0]
+            .metric_comparisons
+            .iter()
+            .filter(|m| m.regressed)
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(regressed, vec!["directions_calls_per_run"]);
+        assert_eq!(report.total_regressions, 1);
+
+        // Older baselines without costs compare on quality alone
+        let report = diff_baselines(
+            &sampled_baseline(0.80, 0.03, 10),
+            &with_cost(12.0, 2000.0),
+            0.15,
+        );
+        assert_eq!(report.total_regressions, 0);
+    }
+
+    #[test]
+    fn test_regression_gate_and_annotations() {
+        let report = diff_baselines(
+            &sampled_baseline(0.80, 0.03, 10),
+            &sampled_baseline(0.60, 0.03, 10),
+            0.15,
+        );
+        let gate = RegressionGate::from_report(&report);
+        assert!(!gate.passed);
+        let regressed: Vec<&RegressionRecord> =
+            gate.records.iter().filter(|r| r.regressed).collect();
+        assert_eq!(regressed.len(), 1);
+        assert_eq!(regressed[0].metric, "circularity");
+        assert_eq!(
+            gate.records.len(),
+            report.scenario_comparisons[0].metric_comparisons.len()
+        );
+
+        let annotations = format_github_annotations(&report);
+        assert_eq!(annotations.lines().count(), 1);
+        assert!(annotations.starts_with("::error title=Regression in "));
+        assert!(annotations.contains("::circularity regressed to 0.60 (baseline: 0.80, -25.0%"));
+    }
+
+    #[test]
+    fn test_annotation_escaping() {
+        assert_eq!(escape_annotation_data("50%\nmore"), "50%25%0Amore");
+        assert_eq!(escape_annotation_property("a: b, c"), "a%3A b%2C c");
+    }
 }
@@ -0,0 +1,297 @@
+//! Named baselines with history, so concurrent experiments (branches, config
+//! variants) each track quality against their own reference.
+//!
+//! Layout under the store root (`evaluation/` by default):
+//! - `baselines/<name>.json`: the current baseline for `<name>`
+//! - `baselines/history/<name>/<id>.json`: every version saved under
+//!   `<name>`, `<id>` derived from its timestamp so ids sort by age
+//!
+//! A baseline is referenced as `<name>` (current) or `<name>@<id>` (a
+//! history entry). The single `baseline.json` at the root, from before named
+//! baselines, is read as `default` until a `default` baseline is saved.
+
+use std::path::{Path, PathBuf};
+
+use super::baseline::{load_baseline, save_baseline, Baseline};
+
+pub const DEFAULT_BASELINE_NAME: &str = "default";
+
+const LEGACY_BASELINE_FILE: &str = "baseline.json";
+
+/// One named baseline, as listed by `--list-baselines`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaselineSummary {
+    pub name: String,
+    pub timestamp: String,
+    pub scenarios: usize,
+    /// Versions in the name's history
+    pub versions: usize,
+}
+
+pub struct BaselineStore {
+    root: PathBuf,
+}
+
+/// Names become file names: letters, digits, `.`, `_` and `-` only, so
+/// branch names like `feature/x` need their slashes replaced
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid baseline name '{name}': use letters, digits, '.', '_' or '-'"
+        ))
+    }
+}
+
+impl BaselineStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        BaselineStore { root: root.into() }
+    }
+
+    fn current_path(&self, name: &str) -> PathBuf {
+        self.root.join("baselines").join(format!("{name}.json"))
+    }
+
+    fn history_dir(&self, name: &str) -> PathBuf {
+        self.root.join("baselines").join("history").join(name)
+    }
+
+    /// Save `baseline` as the current `name` and add it to the history.
+    /// Returns the history id.
+    pub fn save(&self, name: &str, baseline: &Baseline) -> Result<String, String> {
+        validate_name(name)?;
+        let dir = self.history_dir(name);
+        let stem: String = baseline
+            .timestamp
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        // Several saves within a second (or a promotion of an old version)
+        // would share a timestamp
+        let mut id = stem.clone();
+        let mut n = 1;
+        while dir.join(format!("{id}.json")).exists() {
+            n += 1;
+            id = format!("{stem}.{n}");
+        }
+        save_baseline(baseline, &dir.join(format!("{id}.json")))?;
+        save_baseline(baseline, &self.current_path(name))?;
+        Ok(id)
+    }
+
+    /// Load `<name>` or `<name>@<id>`
+    pub fn load(&self, reference: &str) -> Result<Baseline, String> {
+        let (name, id) = match reference.split_once('@') {
+            Some((name, id)) => (name, Some(id)),
+            None => (reference, None),
+        };
+        validate_name(name)?;
+        let path = match id {
+            Some(id) => {
+                validate_name(id)?;
+                self.history_dir(name).join(format!("{id}.json"))
+            }
+            None => {
+                let path = self.current_path(name);
+                let legacy = self.root.join(LEGACY_BASELINE_FILE);
+                if name == DEFAULT_BASELINE_NAME && !path.exists() && legacy.exists() {
+                    legacy
+                } else {
+                    path
+                }
+            }
+        };
+        load_baseline(&path)
+    }
+
+    /// History ids of `name`, oldest first
+    pub fn history(&self, name: &str) -> Result<Vec<String>, String> {
+        validate_name(name)?;
+        let mut ids = json_stems(&self.history_dir(name))?;
+        ids.sort_by(|a, b| history_order(a).cmp(&history_order(b)));
+        Ok(ids)
+    }
+
+    /// Every named baseline, by name
+    pub fn list(&self) -> Result<Vec<BaselineSummary>, String> {
+        let mut names = json_stems(&self.root.join("baselines"))?;
+        if !names.iter().any(|n| n == DEFAULT_BASELINE_NAME)
+            && self.root.join(LEGACY_BASELINE_FILE).exists()
+        {
+            names.push(DEFAULT_BASELINE_NAME.to_string());
+        }
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| {
+                let baseline = self.load(&name)?;
+                Ok(BaselineSummary {
+                    versions: self.history(&name)?.len(),
+                    timestamp: baseline.timestamp,
+                    scenarios: baseline.scenarios.len(),
+                    name,
+                })
+            })
+            .collect()
+    }
+
+    /// Make the baseline `source` refers to the current `target` (recorded
+    /// in `target`'s history). Returns the new history id.
+    pub fn promote(&self, source: &str, target: &str) -> Result<String, String> {
+        let baseline = self.load(source)?;
+        self.save(target, &baseline)
+    }
+}
+
+/// `(timestamp stem, collision counter)`, so `x.10` sorts after `x.9`
+fn history_order(id: &str) -> (&str, u32) {
+    match id.rsplit_once('.') {
+        Some((stem, n)) => match n.parse() {
+            Ok(n) => (stem, n),
+            Err(_) => (id, 1),
+        },
+        None => (id, 1),
+    }
+}
+
+/// Stems of the `.json` files directly in `dir` (none if it doesn't exist)
+fn json_stems(dir: &Path) -> Result<Vec<String>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", dir.display())),
+    };
+    let mut stems = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Failed to read {}: {e}", dir.display()))?
+            .path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                stems.push(stem.to_string());
+            }
+        }
+    }
+    Ok(stems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline(timestamp: &str, success_rate: f32) -> Baseline {
+        Baseline {
+            version: 1,
+            timestamp: timestamp.to_string(),
+            runs_per_scenario: 3,
+            scenarios: vec![crate::evaluation::baseline::BaselineScenario {
+                name: "s1".to_string(),
+                success_rate,
+                metrics: None,
+                samples: 0,
+                std_dev: None,
+                cost: None,
+            }],
+        }
+    }
+
+    fn temp_store() -> (BaselineStore, PathBuf) {
+        let root =
+            std::env::temp_dir().join(format!("easyroute-baselines-{}", uuid::Uuid::new_v4()));
+        (BaselineStore::new(&root), root)
+    }
+
+    #[test]
+    fn test_save_history_and_promote() {
+        let (store, root) = temp_store();
+        store
+            .save("main", &baseline("2026-03-01T10:00:00Z", 1.0))
+            .unwrap();
+        store
+            .save("main", &baseline("2026-03-02T10:00:00Z", 0.9))
+            .unwrap();
+        store
+            .save("exp-a", &baseline("2026-03-02T11:00:00Z", 0.8))
+            .unwrap();
+
+        assert_eq!(
+            store.load("main").unwrap().timestamp,
+            "2026-03-02T10:00:00Z"
+        );
+        let history = store.history("main").unwrap();
+        assert_eq!(
+            history,
+            vec!["2026-03-01T10-00-00Z", "2026-03-02T10-00-00Z"]
+        );
+        let first = store.load(&format!("main@{}", history[0])).unwrap();
+        assert_eq!(first.scenarios[0].success_rate, 1.0);
+
+        // Rolling back to an old version records it again
+        let id = store
+            .promote(&format!("main@{}", history[0]), "main")
+            .unwrap();
+        assert_eq!(id, "2026-03-01T10-00-00Z.2");
+        assert_eq!(
+            store.load("main").unwrap().timestamp,
+            "2026-03-01T10:00:00Z"
+        );
+
+        store.promote("exp-a", "main").unwrap();
+        let names: Vec<_> = store
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.name, s.versions))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("exp-a".to_string(), 1), ("main".to_string(), 4)]
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_file_is_default() {
+        let (store, root) = temp_store();
+        save_baseline(
+            &baseline("2026-01-01T00:00:00Z", 1.0),
+            &root.join(LEGACY_BASELINE_FILE),
+        )
+        .unwrap();
+        assert_eq!(
+            store.load(DEFAULT_BASELINE_NAME).unwrap().timestamp,
+            "2026-01-01T00:00:00Z"
+        );
+        assert_eq!(store.list().unwrap()[0].name, DEFAULT_BASELINE_NAME);
+
+        store
+            .save(
+                DEFAULT_BASELINE_NAME,
+                &baseline("2026-02-01T00:00:00Z", 1.0),
+            )
+            .unwrap();
+        assert_eq!(
+            store.load(DEFAULT_BASELINE_NAME).unwrap().timestamp,
+            "2026-02-01T00:00:00Z"
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_names_are_validated() {
+        assert!(validate_name("feature-x_2.1").is_ok());
+        for name in ["", "../etc", "a/b", ".hidden", "a b"] {
+            assert!(validate_name(name).is_err(), "{name}");
+        }
+        let (store, _) = temp_store();
+        assert!(store.load("../../secret").is_err());
+        assert!(store.load("main@../x").is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! What an evaluation run spends: directions calls (Mapbox, or whichever
+//! provider is configured), POI queries and wall-clock time. A change that
+//! keeps route quality but doubles API spend shows up in baseline checks.
+//!
+//! Overpass is archived (POIs come from the local OSM import), so POI
+//! queries are counted against the repository instead.
+
+use crate::db::PoiRepository;
+use crate::error::Result;
+use crate::models::road_profile::RoadProfile;
+use crate::models::route::SnappedPoi;
+use crate::models::{
+    Coordinates, CostingOptions, Poi, PoiCategory, SurfacePreference, TransportMode,
+};
+use crate::services::directions::DirectionsProvider;
+use crate::services::mapbox::DirectionsResponse;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Calls made through the counting wrappers sharing these counters
+#[derive(Debug, Default)]
+pub struct CallCounters {
+    directions: AtomicU64,
+    poi_queries: AtomicU64,
+}
+
+impl CallCounters {
+    pub fn directions_calls(&self) -> u64 {
+        self.directions.load(Ordering::Relaxed)
+    }
+
+    pub fn poi_queries(&self) -> u64 {
+        self.poi_queries.load(Ordering::Relaxed)
+    }
+}
+
+/// Totals for one scenario across all of its runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioCost {
+    pub directions_calls: u64,
+    pub poi_queries: u64,
+    pub wall_clock_ms: u64,
+}
+
+impl ScenarioCost {
+    /// Cost of the calls made between `before` (directions, POI queries)
+    /// and now
+    pub fn since(counters: &CallCounters, before: (u64, u64), elapsed: Duration) -> Self {
+        ScenarioCost {
+            directions_calls: counters.directions_calls() - before.0,
+            poi_queries: counters.poi_queries() - before.1,
+            wall_clock_ms: elapsed.as_millis() as u64,
+        }
+    }
+
+    pub fn per_run(&self, runs: usize) -> CostPerRun {
+        let runs = runs.max(1) as f32;
+        CostPerRun {
+            directions_calls: self.directions_calls as f32 / runs,
+            poi_queries: self.poi_queries as f32 / runs,
+            wall_clock_ms: self.wall_clock_ms as f32 / runs,
+        }
+    }
+}
+
+/// Mean cost of one run, as stored in baselines
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostPerRun {
+    pub directions_calls: f32,
+    pub poi_queries: f32,
+    pub wall_clock_ms: f32,
+}
+
+/// Counts every directions request before passing it on
+pub struct CountingDirectionsProvider {
+    inner: Arc<dyn DirectionsProvider>,
+    counters: Arc<CallCounters>,
+}
+
+impl CountingDirectionsProvider {
+    pub fn new(inner: Arc<dyn DirectionsProvider>, counters: Arc<CallCounters>) -> Self {
+        CountingDirectionsProvider { inner, counters }
+    }
+}
+
+#[async_trait]
+impl DirectionsProvider for CountingDirectionsProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn directions(
+        &self,
+        waypoints: &[Coordinates],
+        mode: &TransportMode,
+        costing: &CostingOptions,
+        surface: SurfacePreference,
+    ) -> Result<DirectionsResponse> {
+        self.counters.directions.fetch_add(1, Ordering::Relaxed);
+        self.inner
+            .directions(waypoints, mode, costing, surface)
+            .await
+    }
+}
+
+/// Counts POI lookups (radius, bbox and ID queries, road profiles) before
+/// passing them on; inserts and counts aren't part of route generation
+pub struct CountingPoiRepository {
+    inner: Arc<dyn PoiRepository>,
+    counters: Arc<CallCounters>,
+}
+
+impl CountingPoiRepository {
+    pub fn new(inner: Arc<dyn PoiRepository>, counters: Arc<CallCounters>) -> Self {
+        CountingPoiRepository { inner, counters }
+    }
+
+    fn count_query(&self) {
+        self.counters.poi_queries.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl PoiRepository for CountingPoiRepository {
+    async fn find_within_radius(
+        &self,
+        center: &Coordinates,
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        self.count_query();
+        self.inner
+            .find_within_radius(center, radius_meters, categories, limit)
+            .await
+    }
+
+    async fn find_in_bbox(
+        &self,
+        min_lat: f64,
+        max_lat: f64,
+        min_lng: f64,
+        max_lng: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        self.count_query();
+        self.inner
+            .find_in_bbox(min_lat, max_lat, min_lng, max_lng, categories, limit)
+            .await
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Poi>> {
+        self.count_query();
+        self.inner.find_by_ids(ids).await
+    }
+
+    async fn find_by_osm_ids(&self, osm_ids: &[i64]) -> Result<Vec<Poi>> {
+        self.count_query();
+        self.inner.find_by_osm_ids(osm_ids).await
+    }
+
+    async fn insert(&self, poi: &Poi) -> Result<Uuid> {
+        self.inner.insert(poi).await
+    }
+
+    async fn count(&self) -> Result<i64> {
+        self.inner.count().await
+    }
+
+    async fn road_profile(&self, path: &[Coordinates]) -> Result<Option<RoadProfile>> {
+        self.count_query();
+        self.inner.road_profile(path).await
+    }
+
+    async fn find_near_path(
+        &self,
+        path: &[Coordinates],
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Option<Vec<SnappedPoi>>> {
+        let snapped = self
+            .inner
+            .find_near_path(path, radius_meters, categories, limit)
+            .await?;
+        // Unanswered, the caller falls back to a (counted) bbox search
+        if snapped.is_some() {
+            self.count_query();
+        }
+        Ok(snapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed;
+
+    #[async_trait]
+    impl DirectionsProvider for Fixed {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn directions(
+            &self,
+            waypoints: &[Coordinates],
+            _mode: &TransportMode,
+            _costing: &CostingOptions,
+            _surface: SurfacePreference,
+        ) -> Result<DirectionsResponse> {
+            Ok(DirectionsResponse {
+                distance_meters: 1000.0,
+                duration_seconds: 600.0,
+                geometry: waypoints.iter().map(|c| [c.lng, c.lat]).collect(),
+                legs: Vec::new(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_counts_directions_calls_per_scenario() {
+        let counters = Arc::new(CallCounters::default());
+        let provider = CountingDirectionsProvider::new(Arc::new(Fixed), Arc::clone(&counters));
+        let points = [
+            Coordinates::new(48.85, 2.35).unwrap(),
+            Coordinates::new(48.86, 2.36).unwrap(),
+        ];
+        let costing = CostingOptions::default();
+        let surface = SurfacePreference::default();
+        provider
+            .directions(&points, &TransportMode::Walk, &costing, surface)
+            .await
+            .unwrap();
+
+        let before = (counters.directions_calls(), counters.poi_queries());
+        for _ in 0..4 {
+            provider
+                .directions(&points, &TransportMode::Walk, &costing, surface)
+                .await
+                .unwrap();
+        }
+        let cost = ScenarioCost::since(&counters, before, Duration::from_millis(900));
+        assert_eq!(cost.directions_calls, 4);
+        assert_eq!(cost.poi_queries, 0);
+        assert_eq!(
+            cost.per_run(2),
+            CostPerRun {
+                directions_calls: 2.0,
+                poi_queries: 0.0,
+                wall_clock_ms: 450.0,
+            }
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Synthetic request mixes and reporting for load tests.
+//!
+//! Requests are drawn from a [`LoadProfile`]: a start point scattered around a
+//! weighted choice of cities, plus weighted distance, mode and category mixes.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::constants::METERS_PER_DEGREE;
+use crate::evaluation::replay::Distribution;
+use crate::models::route::LoopRouteRequest;
+use crate::models::{Coordinates, PoiCategory, RoutePreferences, TransportMode};
+
+/// A city that start points are scattered around
+#[derive(Debug, Clone)]
+pub struct CitySpec {
+    pub name: &'static str,
+    pub center: Coordinates,
+    /// Start points fall uniformly within this radius of the center
+    pub radius_km: f64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadProfile {
+    pub cities: Vec<CitySpec>,
+    /// Walking distances (km) with weights; cycling modes scale them
+    pub distances_km: Vec<(f64, f64)>,
+    pub cycling_distance_factor: f64,
+    pub modes: Vec<(TransportMode, f64)>,
+    /// `None` uses the mode's default categories
+    pub category_mixes: Vec<(Option<Vec<PoiCategory>>, f64)>,
+}
+
+impl Default for LoadProfile {
+    /// Cities from the evaluation scenarios, weighted roughly by expected traffic
+    fn default() -> Self {
+        let city = |name, lat, lng, radius_km, weight| CitySpec {
+            name,
+            center: Coordinates { lat, lng },
+            radius_km,
+            weight,
+        };
+        LoadProfile {
+            cities: vec![
+                city("paris", 48.8566, 2.3522, 5.0, 0.45),
+                city("prague", 50.0755, 14.4378, 4.0, 0.2),
+                city("rennes", 48.1173, -1.6778, 3.0, 0.15),
+                city("angers", 47.4784, -0.5632, 3.0, 0.1),
+                city("monaco", 43.7384, 7.4246, 1.0, 0.1),
+            ],
+            distances_km: vec![
+                (2.0, 0.2),
+                (3.0, 0.25),
+                (5.0, 0.3),
+                (8.0, 0.15),
+                (12.0, 0.1),
+            ],
+            cycling_distance_factor: 3.0,
+            modes: vec![
+                (TransportMode::Walk, 0.6),
+                (TransportMode::Bike, 0.2),
+                (TransportMode::RoadBike, 0.05),
+                (TransportMode::Gravel, 0.05),
+                (TransportMode::DogWalk, 0.1),
+            ],
+            category_mixes: vec![
+                (None, 0.6),
+                (
+                    Some(vec![
+                        PoiCategory::Monument,
+                        PoiCategory::Historic,
+                        PoiCategory::Museum,
+                    ]),
+                    0.2,
+                ),
+                (
+                    Some(vec![
+                        PoiCategory::Park,
+                        PoiCategory::Viewpoint,
+                        PoiCategory::Waterfront,
+                    ]),
+                    0.15,
+                ),
+                (Some(vec![PoiCategory::Cafe, PoiCategory::Market]), 0.05),
+            ],
+        }
+    }
+}
+
+impl LoadProfile {
+    pub fn sample_request<R: Rng>(&self, rng: &mut R) -> LoopRouteRequest {
+        let city = pick(rng, &self.cities, |c| c.weight);
+        let mode = pick(rng, &self.modes, |(_, w)| *w)
+            .map(|(m, _)| m.clone())
+            .unwrap_or_default();
+        let mut distance_km = pick(rng, &self.distances_km, |(_, w)| *w)
+            .map(|(d, _)| *d)
+            .unwrap_or(5.0);
+        if mode.is_cycling() {
+            distance_km *= self.cycling_distance_factor;
+        }
+        let poi_categories = pick(rng, &self.category_mixes, |(_, w)| *w)
+            .and_then(|(categories, _)| categories.clone());
+
+        let start_point = match city {
+            Some(city) => scatter(rng, &city.center, city.radius_km),
+            None => Coordinates { lat: 0.0, lng: 0.0 },
+        };
+
+        LoopRouteRequest {
+            start_point,
+            distance_km: Some(distance_km.min(50.0)),
+            distance_tolerance: distance_km * 0.2,
+            mode,
+            preferences: RoutePreferences {
+                poi_categories,
+                ..Default::default()
+            },
+            depart_at: None,
+            arrive_by: None,
+        }
+    }
+}
+
+/// Weighted random choice; `None` when there is nothing to pick from
+fn pick<'a, T, R: Rng>(rng: &mut R, items: &'a [T], weight: impl Fn(&T) -> f64) -> Option<&'a T> {
+    let total: f64 = items.iter().map(&weight).sum();
+    if total <= 0.0 {
+        return items.first();
+    }
+    let mut roll = rng.gen::<f64>() * total;
+    for item in items {
+        roll -= weight(item);
+        if roll <= 0.0 {
+            return Some(item);
+        }
+    }
+    items.last()
+}
+
+/// Uniform random point within `radius_km` of `center`
+fn scatter<R: Rng>(rng: &mut R, center: &Coordinates, radius_km: f64) -> Coordinates {
+    let distance_km = radius_km * rng.gen::<f64>().sqrt();
+    let bearing = rng.gen::<f64>() * std::f64::consts::TAU;
+    let distance_m = distance_km * 1000.0;
+    let dlat = distance_m * bearing.cos() / METERS_PER_DEGREE;
+    let dlng = distance_m * bearing.sin() / (METERS_PER_DEGREE * center.lat.to_radians().cos());
+    Coordinates {
+        lat: center.lat + dlat,
+        lng: center.lng + dlng,
+    }
+}
+
+/// How a single load test request ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadOutcome {
+    Ok,
+    Status(u16),
+    Timeout,
+    Connection,
+}
+
+impl LoadOutcome {
+    fn error_key(&self) -> Option<String> {
+        match self {
+            LoadOutcome::Ok => None,
+            LoadOutcome::Status(code) => Some(format!("http_{}", code)),
+            LoadOutcome::Timeout => Some("timeout".to_string()),
+            LoadOutcome::Connection => Some("connection".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadSample {
+    pub latency_ms: u64,
+    pub outcome: LoadOutcome,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadReport {
+    pub requests: usize,
+    pub duration_secs: f64,
+    pub target_rps: f64,
+    pub achieved_rps: f64,
+    pub success_rate: f64,
+    /// Latency of successful requests
+    pub latency_ms: Option<Distribution>,
+    /// Failed requests by kind (e.g. "http_502", "timeout")
+    pub errors: BTreeMap<String, usize>,
+}
+
+impl LoadReport {
+    pub fn new(samples: &[LoadSample], target_rps: f64, elapsed: Duration) -> Self {
+        let mut errors = BTreeMap::new();
+        let mut latencies = Vec::new();
+        for sample in samples {
+            match sample.outcome.error_key() {
+                Some(key) => *errors.entry(key).or_insert(0) += 1,
+                None => latencies.push(sample.latency_ms as f64),
+            }
+        }
+        let duration_secs = elapsed.as_secs_f64();
+
+        LoadReport {
+            requests: samples.len(),
+            duration_secs,
+            target_rps,
+            achieved_rps: if duration_secs > 0.0 {
+                samples.len() as f64 / duration_secs
+            } else {
+                0.0
+            },
+            success_rate: if samples.is_empty() {
+                0.0
+            } else {
+                latencies.len() as f64 / samples.len() as f64
+            },
+            latency_ms: Distribution::from_samples(&latencies),
+            errors,
+        }
+    }
+}
+
+pub fn format_load_report(report: &LoadReport) -> String {
+    let mut out = format!(
+        "\n{} requests in {:.1}s ({:.2} rps achieved, {:.2} target)\n",
+        report.requests, report.duration_secs, report.achieved_rps, report.target_rps
+    );
+    out.push_str(&format!(
+        "  success rate:  {:.1}%\n",
+        report.success_rate * 100.0
+    ));
+    if let Some(ref latency) = report.latency_ms {
+        out.push_str(&format!(
+            "  latency (ms):  mean {:.0}  p50 {:.0}  p90 {:.0}  p99 {:.0}\n",
+            latency.mean, latency.p50, latency.p90, latency.p99
+        ));
+    }
+    if !report.errors.is_empty() {
+        out.push_str("  errors:\n");
+        for (kind, count) in &report.errors {
+            out.push_str(&format!("    {:<14} {}\n", kind, count));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_sampled_requests_are_valid_and_near_a_city() {
+        let profile = LoadProfile::default();
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..200 {
+            let request = profile.sample_request(&mut rng);
+            assert!(request.validate().is_ok(), "{:?}", request);
+            let near_city = profile
+                .cities
+                .iter()
+                .any(|c| request.start_point.distance_to(&c.center) <= c.radius_km + 0.01);
+            assert!(near_city, "{:?}", request.start_point);
+        }
+    }
+
+    #[test]
+    fn test_pick_respects_weights() {
+        let items = [("never", 0.0), ("always", 1.0)];
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            assert_eq!(pick(&mut rng, &items, |(_, w)| *w).unwrap().0, "always");
+        }
+        let empty: [(&str, f64); 0] = [];
+        assert!(pick(&mut rng, &empty, |(_, w)| *w).is_none());
+    }
+
+    #[test]
+    fn test_report_breaks_down_errors() {
+        let samples = vec![
+            LoadSample {
+                latency_ms: 100,
+                outcome: LoadOutcome::Ok,
+            },
+            LoadSample {
+                latency_ms: 300,
+                outcome: LoadOutcome::Ok,
+            },
+            LoadSample {
+                latency_ms: 5,
+                outcome: LoadOutcome::Status(502),
+            },
+            LoadSample {
+                latency_ms: 30_000,
+                outcome: LoadOutcome::Timeout,
+            },
+        ];
+        let report = LoadReport::new(&samples, 2.0, Duration::from_secs(2));
+        assert_eq!(report.achieved_rps, 2.0);
+        assert_eq!(report.success_rate, 0.5);
+        assert_eq!(report.latency_ms.as_ref().unwrap().count, 2);
+        assert_eq!(report.errors.get("http_502"), Some(&1));
+        assert_eq!(report.errors.get("timeout"), Some(&1));
+        assert!(format_load_report(&report).contains("http_502"));
+    }
+}
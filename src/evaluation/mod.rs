@@ -1,14 +1,25 @@
-pub mod baseline;
-pub mod scenarios;
+pub(crate) mod baseline;
+pub(crate) mod baseline_store;
+pub(crate) mod cost;
+pub mod load;
+pub mod replay;
+pub(crate) mod report;
+pub(crate) mod scenarios;
+pub mod shadow;
+pub(crate) mod significance;
 
 use serde::{Deserialize, Serialize};
 
-use crate::models::{Coordinates, Route, TransportMode};
+use crate::models::{Coordinates, QualityTier, Route, TransportMode};
 use crate::services::route_generator::route_metrics::{PoiDensityContext, RouteMetrics};
 
 pub use baseline::{
-    compare, format_comparison_report, load_baseline, save_baseline, Baseline, ComparisonReport,
+    compare, diff_baselines, format_comparison_report, format_github_annotations, load_baseline,
+    save_baseline, Baseline, ComparisonReport, RegressionGate,
 };
+pub use baseline_store::{BaselineStore, DEFAULT_BASELINE_NAME};
+pub use cost::{CallCounters, CountingDirectionsProvider, CountingPoiRepository, ScenarioCost};
+pub use report::{Report, ReportFormat};
 pub use scenarios::default_scenarios;
 
 /// A test scenario for route evaluation
@@ -29,6 +40,44 @@ pub struct ScenarioResult {
     pub total_routes: usize,
     pub success_rate: f32,
     pub metrics_agg: Option<MetricsAggregate>,
+    #[serde(default)]
+    pub tier_counts: QualityTierCounts,
+    /// API calls and wall-clock spent across all runs
+    #[serde(default)]
+    pub cost: ScenarioCost,
+}
+
+/// Number of routes landing in each quality tier
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualityTierCounts {
+    pub gold: usize,
+    pub silver: usize,
+    pub bronze: usize,
+    pub untiered: usize,
+}
+
+impl QualityTierCounts {
+    pub fn from_routes(routes: &[&Route]) -> Self {
+        let mut counts = Self::default();
+        for route in routes {
+            match route.quality_tier {
+                Some(QualityTier::Gold) => counts.gold += 1,
+                Some(QualityTier::Silver) => counts.silver += 1,
+                Some(QualityTier::Bronze) => counts.bronze += 1,
+                None => counts.untiered += 1,
+            }
+        }
+        counts
+    }
+
+    /// Fraction of routes that earned any tier (0.0 when there are no routes)
+    pub fn tiered_rate(&self) -> f32 {
+        let total = self.gold + self.silver + self.bronze + self.untiered;
+        if total == 0 {
+            return 0.0;
+        }
+        (self.gold + self.silver + self.bronze) as f32 / total as f32
+    }
 }
 
 /// Statistical aggregates for each metric dimension
@@ -123,68 +172,7 @@ fn stat_summary(values: &[f32]) -> StatSummary {
     }
 }
 
-/// Format a single scenario result for display
-pub fn format_scenario_result(result: &ScenarioResult) -> String {
-    let mut out = String::new();
-
-    out.push_str(&format!(
-        "\n{} ({} runs, {} routes total)\n",
-        result.scenario.name, result.runs, result.total_routes,
-    ));
-
-    if let Some(ref agg) = result.metrics_agg {
-        out.push_str(&format!(
-            "  circularity:      {:.2} +/- {:.2}\n",
-            agg.circularity.mean, agg.circularity.std_dev,
-        ));
-        out.push_str(&format!(
-            "  convexity:        {:.2} +/- {:.2}\n",
-            agg.convexity.mean, agg.convexity.std_dev,
-        ));
-        out.push_str(&format!(
-            "  path_overlap:     {:.0}% +/- {:.0}%\n",
-            agg.path_overlap_pct.mean * 100.0,
-            agg.path_overlap_pct.std_dev * 100.0,
-        ));
-        out.push_str(&format!(
-            "  poi_density:      {:.1}/km +/- {:.1}\n",
-            agg.poi_density_per_km.mean, agg.poi_density_per_km.std_dev,
-        ));
-        out.push_str(&format!(
-            "  category_entropy: {:.2} +/- {:.2}\n",
-            agg.category_entropy.mean, agg.category_entropy.std_dev,
-        ));
-        out.push_str(&format!(
-            "  landmark_coverage:{:.2} +/- {:.2}\n",
-            agg.landmark_coverage.mean, agg.landmark_coverage.std_dev,
-        ));
-        out.push_str(&format!(
-            "  distance_accuracy:{:.2} +/- {:.2}\n",
-            agg.distance_accuracy.mean, agg.distance_accuracy.std_dev,
-        ));
-        out.push_str(&format!(
-            "  route_score:      {:.1} +/- {:.1}\n",
-            agg.route_score.mean, agg.route_score.std_dev,
-        ));
-    } else {
-        out.push_str("  (no routes with metrics)\n");
-    }
-
-    out.push_str(&format!(
-        "  success_rate:     {:.0}%\n",
-        result.success_rate * 100.0,
-    ));
-
-    out
-}
-
 /// Format the full evaluation report
 pub fn format_report(results: &[ScenarioResult]) -> String {
-    let mut report = String::from("=== Route Quality Evaluation Report ===\n");
-
-    for result in results {
-        report.push_str(&format_scenario_result(result));
-    }
-
-    report
+    Report::evaluation(results).render_text()
 }
@@ -0,0 +1,314 @@
+//! Replay of request corpora written by the request logger
+//! (see [`crate::services::request_log`]).
+//!
+//! Each logged request is re-run against a target and the latency and route
+//! metric distributions are compared with the recorded originals.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::services::request_log::{LoggedRoute, RequestLogRecord};
+
+/// Parse an NDJSON corpus. Malformed lines are skipped and counted.
+pub fn parse_corpus(content: &str) -> (Vec<RequestLogRecord>, usize) {
+    let mut records = Vec::new();
+    let mut skipped = 0;
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(_) => skipped += 1,
+        }
+    }
+    (records, skipped)
+}
+
+pub fn load_corpus(path: &Path) -> Result<(Vec<RequestLogRecord>, usize), String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    Ok(parse_corpus(&content))
+}
+
+/// Result of replaying one request
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayOutcome {
+    pub latency_ms: u64,
+    pub routes: Vec<LoggedRoute>,
+    pub error: Option<String>,
+}
+
+/// One request's result, recorded or replayed
+struct RunView<'a> {
+    target_distance_km: Option<f64>,
+    /// `None` when the latency isn't comparable (recorded cache hits)
+    latency_ms: Option<u64>,
+    routes: &'a [LoggedRoute],
+    failed: bool,
+}
+
+impl<'a> RunView<'a> {
+    fn best_route(&self) -> Option<&'a LoggedRoute> {
+        self.routes.iter().max_by(|a, b| {
+            a.score
+                .partial_cmp(&b.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+}
+
+/// Mean and percentiles of a sample
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+impl Distribution {
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = (p * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+        Some(Distribution {
+            count: sorted.len(),
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+        })
+    }
+}
+
+/// Distributions for one side (recorded or replayed) of the comparison
+#[derive(Debug, Clone, Serialize)]
+pub struct SideSummary {
+    pub requests: usize,
+    pub success_rate: f64,
+    pub latency_ms: Option<Distribution>,
+    /// Score of the best route per request
+    pub best_score: Option<Distribution>,
+    /// Relative distance error of the best route per request
+    pub distance_error: Option<Distribution>,
+    pub circularity: Option<Distribution>,
+}
+
+impl SideSummary {
+    fn from_runs(runs: &[RunView]) -> Self {
+        let best: Vec<(&RunView, &LoggedRoute)> = runs
+            .iter()
+            .filter_map(|run| run.best_route().map(|route| (run, route)))
+            .collect();
+        let successes = runs.iter().filter(|r| !r.failed).count();
+
+        SideSummary {
+            requests: runs.len(),
+            success_rate: if runs.is_empty() {
+                0.0
+            } else {
+                successes as f64 / runs.len() as f64
+            },
+            latency_ms: Distribution::from_samples(
+                &runs
+                    .iter()
+                    .filter_map(|r| r.latency_ms.map(|ms| ms as f64))
+                    .collect::<Vec<_>>(),
+            ),
+            best_score: Distribution::from_samples(
+                &best.iter().map(|(_, r)| r.score as f64).collect::<Vec<_>>(),
+            ),
+            distance_error: Distribution::from_samples(
+                &best
+                    .iter()
+                    .filter_map(|(run, route)| {
+                        let target = run.target_distance_km.filter(|t| *t > 0.0)?;
+                        Some((route.distance_km - target).abs() / target)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            circularity: Distribution::from_samples(
+                &best
+                    .iter()
+                    .filter_map(|(_, r)| r.metrics.as_ref().map(|m| m.circularity as f64))
+                    .collect::<Vec<_>>(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub original: SideSummary,
+    pub replay: SideSummary,
+}
+
+impl ReplayReport {
+    /// Compare recorded results with replay outcomes (paired by index)
+    pub fn new(records: &[RequestLogRecord], outcomes: &[ReplayOutcome]) -> Self {
+        let original: Vec<RunView> = records
+            .iter()
+            .map(|r| RunView {
+                target_distance_km: r.request.target_distance_km(),
+                latency_ms: (!r.cache_hit).then_some(r.latency_ms),
+                routes: &r.routes,
+                failed: r.error.is_some(),
+            })
+            .collect();
+        let replay: Vec<RunView> = records
+            .iter()
+            .zip(outcomes)
+            .map(|(r, o)| RunView {
+                target_distance_km: r.request.target_distance_km(),
+                latency_ms: Some(o.latency_ms),
+                routes: &o.routes,
+                failed: o.error.is_some(),
+            })
+            .collect();
+
+        ReplayReport {
+            original: SideSummary::from_runs(&original),
+            replay: SideSummary::from_runs(&replay),
+        }
+    }
+}
+
+/// Format a replay report as a side-by-side table
+pub fn format_replay_report(report: &ReplayReport) -> String {
+    let mut out = format!("\n{:<22} {:>12} {:>12}\n", "", "original", "replay");
+    out.push_str(&format!(
+        "{:<22} {:>12} {:>12}\n",
+        "requests", report.original.requests, report.replay.requests
+    ));
+    out.push_str(&format!(
+        "{:<22} {:>11.1}% {:>11.1}%\n",
+        "success rate",
+        report.original.success_rate * 100.0,
+        report.replay.success_rate * 100.0
+    ));
+
+    type Field = fn(&SideSummary) -> &Option<Distribution>;
+    type Stat = fn(&Distribution) -> f64;
+    // (label, distribution, statistic, decimals)
+    let rows: [(&str, Field, Stat, usize); 7] = [
+        ("latency p50 (ms)", |s| &s.latency_ms, |d| d.p50, 0),
+        ("latency p90 (ms)", |s| &s.latency_ms, |d| d.p90, 0),
+        ("latency p99 (ms)", |s| &s.latency_ms, |d| d.p99, 0),
+        ("best score mean", |s| &s.best_score, |d| d.mean, 2),
+        ("best score p50", |s| &s.best_score, |d| d.p50, 2),
+        ("distance error mean", |s| &s.distance_error, |d| d.mean, 3),
+        ("circularity mean", |s| &s.circularity, |d| d.mean, 2),
+    ];
+    for (label, field, stat, precision) in rows {
+        let cell = |side: &SideSummary| {
+            field(side)
+                .as_ref()
+                .map(|d| format!("{:.*}", precision, stat(d)))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        out.push_str(&format!(
+            "{:<22} {:>12} {:>12}\n",
+            label,
+            cell(&report.original),
+            cell(&report.replay)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::route::LoopRouteRequest;
+    use crate::models::{Coordinates, TransportMode};
+
+    fn logged(distance_km: f64, score: f32) -> LoggedRoute {
+        LoggedRoute {
+            distance_km,
+            estimated_duration_minutes: 60,
+            score,
+            poi_count: 3,
+            snapped_poi_count: 0,
+            quality_tier: None,
+            metrics: None,
+        }
+    }
+
+    fn record(cache_hit: bool, latency_ms: u64, routes: Vec<LoggedRoute>) -> RequestLogRecord {
+        let request = LoopRouteRequest {
+            start_point: Coordinates::new(48.857, 2.352).unwrap(),
+            distance_km: Some(5.0),
+            distance_tolerance: 0.5,
+            mode: TransportMode::Walk,
+            preferences: Default::default(),
+            depart_at: None,
+            arrive_by: None,
+        };
+        let mut record = RequestLogRecord::new("/routes/loop", &request, Ok(&[]), cache_hit, 0);
+        record.latency_ms = latency_ms;
+        record.routes = routes;
+        record
+    }
+
+    #[test]
+    fn test_distribution_percentiles() {
+        let samples: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+        let d = Distribution::from_samples(&samples).unwrap();
+        assert_eq!(d.count, 100);
+        assert_eq!(d.p50, 50.0);
+        assert_eq!(d.p90, 90.0);
+        assert_eq!(d.p99, 99.0);
+        assert!((d.mean - 50.5).abs() < 1e-9);
+        assert_eq!(Distribution::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn test_parse_corpus_skips_malformed_lines() {
+        let line = serde_json::to_string(&record(false, 100, vec![])).unwrap();
+        let content = format!("{}\nnot json\n\n{}\n", line, line);
+        let (records, skipped) = parse_corpus(&content);
+        assert_eq!(records.len(), 2);
+        assert_eq!(skipped, 1);
+    }
+
+    #[test]
+    fn test_report_compares_sides() {
+        let records = vec![
+            record(false, 1000, vec![logged(5.5, 6.0), logged(5.0, 7.0)]),
+            // Cached: latency is not comparable
+            record(true, 2, vec![logged(4.0, 5.0)]),
+        ];
+        let outcomes = vec![
+            ReplayOutcome {
+                latency_ms: 800,
+                routes: vec![logged(5.0, 8.0)],
+                error: None,
+            },
+            ReplayOutcome {
+                latency_ms: 900,
+                routes: vec![],
+                error: Some("no route".to_string()),
+            },
+        ];
+
+        let report = ReplayReport::new(&records, &outcomes);
+        assert_eq!(report.original.latency_ms.as_ref().unwrap().count, 1);
+        assert_eq!(report.original.success_rate, 1.0);
+        assert_eq!(report.replay.success_rate, 0.5);
+        // Best route per request: 7.0 and 5.0 recorded, 8.0 replayed
+        assert_eq!(report.original.best_score.as_ref().unwrap().mean, 6.0);
+        assert_eq!(report.replay.best_score.as_ref().unwrap().mean, 8.0);
+        assert_eq!(report.replay.distance_error.as_ref().unwrap().mean, 0.0);
+
+        let text = format_replay_report(&report);
+        assert!(text.contains("success rate"));
+        assert!(text.contains("50.0%"));
+    }
+}
@@ -0,0 +1,725 @@
+//! Structured evaluation and comparison reports.
+//!
+//! A [`Report`] holds what a report says (facts, tables, notes and a
+//! verdict) with numbers kept as numbers, and renders it as console text,
+//! Markdown (for PR comments and job summaries) or JSON (for dashboards and
+//! CI steps that shouldn't have to parse console output).
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::ser::{SerializeMap, SerializeStruct};
+use serde::{Serialize, Serializer};
+
+use super::baseline::ComparisonReport;
+use super::ScenarioResult;
+
+/// How a [`Report`] is rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Markdown,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "txt" => Ok(ReportFormat::Text),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!(
+                "Invalid report format: '{}'; use text, markdown or json",
+                s
+            )),
+        }
+    }
+}
+
+/// One value in a report. Text and Markdown show it rounded; JSON gets the
+/// number itself (percentages as fractions).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Count(usize),
+    Number {
+        value: f32,
+        decimals: usize,
+    },
+    /// A fraction shown as a percentage; `signed` prefixes gains with `+`
+    Percent {
+        fraction: f32,
+        decimals: usize,
+        signed: bool,
+    },
+    /// A number shown as given rather than rounded (e.g. a configured level)
+    Exact(f32),
+    /// No value (e.g. a change against a zero baseline)
+    Missing,
+}
+
+impl Value {
+    pub fn text(s: impl Into<String>) -> Self {
+        Value::Text(s.into())
+    }
+
+    pub fn number(value: f32, decimals: usize) -> Self {
+        Value::Number { value, decimals }
+    }
+
+    pub fn percent(fraction: f32, decimals: usize) -> Self {
+        Value::Percent {
+            fraction,
+            decimals,
+            signed: false,
+        }
+    }
+
+    pub fn change(fraction: f32, decimals: usize) -> Self {
+        Value::Percent {
+            fraction,
+            decimals,
+            signed: true,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Text(s) => f.write_str(s),
+            Value::Count(n) => write!(f, "{}", n),
+            Value::Number { value, decimals } => write!(f, "{:.*}", decimals, value),
+            Value::Exact(value) => write!(f, "{}", value),
+            Value::Percent {
+                fraction,
+                decimals,
+                signed: true,
+            } => write!(f, "{:+.*}%", decimals, fraction * 100.0),
+            Value::Percent {
+                fraction, decimals, ..
+            } => write!(f, "{:.*}%", decimals, fraction * 100.0),
+            Value::Missing => f.write_str("-"),
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Text(s) => serializer.serialize_str(s),
+            Value::Count(n) => serializer.serialize_u64(*n as u64),
+            Value::Number { value, .. } | Value::Exact(value) => serializer.serialize_f32(*value),
+            Value::Percent { fraction, .. } => serializer.serialize_f32(*fraction),
+            Value::Missing => serializer.serialize_none(),
+        }
+    }
+}
+
+/// A labelled value; `key` names it in JSON, `label` everywhere else
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fact {
+    pub key: String,
+    pub label: String,
+    pub value: Value,
+}
+
+impl Fact {
+    pub fn new(key: &str, label: &str, value: Value) -> Self {
+        Fact {
+            key: key.to_string(),
+            label: label.to_string(),
+            value,
+        }
+    }
+}
+
+/// Table column; `key` names the cell in each JSON row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub key: String,
+    pub label: String,
+}
+
+impl Column {
+    pub fn new(key: &str, label: &str) -> Self {
+        Column {
+            key: key.to_string(),
+            label: label.to_string(),
+        }
+    }
+}
+
+/// A block of the report: facts, then a table, then notes (all optional)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Section {
+    pub heading: String,
+    pub facts: Vec<Fact>,
+    pub columns: Vec<Column>,
+    /// One value per column
+    pub rows: Vec<Vec<Value>>,
+    pub notes: Vec<String>,
+}
+
+/// Overall outcome, for reports that have one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Verdict {
+    pub passed: bool,
+    pub summary: String,
+}
+
+/// A format-agnostic report; see the module docs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub title: String,
+    #[serde(serialize_with = "serialize_facts")]
+    pub facts: Vec<Fact>,
+    pub sections: Vec<Section>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+}
+
+fn serialize_facts<S: Serializer>(facts: &[Fact], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(facts.len()))?;
+    for fact in facts {
+        map.serialize_entry(&fact.key, &fact.value)?;
+    }
+    map.end()
+}
+
+/// JSON facts as an object, and rows as objects keyed by column
+struct Facts<'a>(&'a [Fact]);
+
+impl Serialize for Facts<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_facts(self.0, serializer)
+    }
+}
+
+struct Row<'a> {
+    columns: &'a [Column],
+    values: &'a [Value],
+}
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for (column, value) in self.columns.iter().zip(self.values) {
+            map.serialize_entry(&column.key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for Section {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .map(|values| Row {
+                columns: &self.columns,
+                values,
+            })
+            .collect();
+        let mut section = serializer.serialize_struct("Section", 4)?;
+        section.serialize_field("heading", &self.heading)?;
+        section.serialize_field("facts", &Facts(&self.facts))?;
+        section.serialize_field("rows", &rows)?;
+        section.serialize_field("notes", &self.notes)?;
+        section.end()
+    }
+}
+
+// ── Rendering ───────────────────────────────────────────────
+
+impl Report {
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Text => self.render_text(),
+            ReportFormat::Markdown => self.render_markdown(),
+            ReportFormat::Json => self.render_json(),
+        }
+    }
+
+    /// Console text: aligned facts and tables
+    pub fn render_text(&self) -> String {
+        let mut out = format!("=== {} ===\n", self.title);
+        push_text_facts(&mut out, &self.facts, "");
+
+        for section in &self.sections {
+            out.push_str(&format!("\n{}\n", section.heading));
+            push_text_facts(&mut out, &section.facts, "  ");
+            if !section.rows.is_empty() {
+                let header: Vec<String> = section.columns.iter().map(|c| c.label.clone()).collect();
+                let cells: Vec<Vec<String>> = section
+                    .rows
+                    .iter()
+                    .map(|row| row.iter().map(Value::to_string).collect())
+                    .collect();
+                let widths: Vec<usize> = (0..header.len())
+                    .map(|i| {
+                        cells
+                            .iter()
+                            .filter_map(|row| row.get(i))
+                            .chain(std::iter::once(&header[i]))
+                            .map(|cell| cell.chars().count())
+                            .max()
+                            .unwrap_or(0)
+                    })
+                    .collect();
+                for row in std::iter::once(&header).chain(&cells) {
+                    let line: Vec<String> = row
+                        .iter()
+                        .zip(&widths)
+                        .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                        .collect();
+                    out.push_str(&format!("  {}\n", line.join("  ").trim_end()));
+                }
+            }
+            for note in &section.notes {
+                out.push_str(&format!("  - {}\n", note));
+            }
+        }
+
+        if let Some(ref verdict) = self.verdict {
+            out.push_str(&format!("\nRESULT: {}\n", verdict.summary));
+        }
+        out
+    }
+
+    /// GitHub-flavoured Markdown, e.g. for `$GITHUB_STEP_SUMMARY`
+    pub fn render_markdown(&self) -> String {
+        let mut out = format!("## {}\n", escape_markdown(&self.title));
+        push_markdown_facts(&mut out, &self.facts);
+
+        for section in &self.sections {
+            out.push_str(&format!("\n### {}\n", escape_markdown(&section.heading)));
+            push_markdown_facts(&mut out, &section.facts);
+            if !section.rows.is_empty() {
+                let header: Vec<String> = section
+                    .columns
+                    .iter()
+                    .map(|c| escape_markdown(&c.label))
+                    .collect();
+                out.push_str(&format!("\n| {} |\n", header.join(" | ")));
+                out.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+                for row in &section.rows {
+                    let cells: Vec<String> = row
+                        .iter()
+                        .map(|v| escape_markdown(&v.to_string()))
+                        .collect();
+                    out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+            }
+            if !section.notes.is_empty() {
+                out.push('\n');
+                for note in &section.notes {
+                    out.push_str(&format!("- {}\n", escape_markdown(note)));
+                }
+            }
+        }
+
+        if let Some(ref verdict) = self.verdict {
+            let outcome = if verdict.passed { "PASS" } else { "FAIL" };
+            out.push_str(&format!(
+                "\n**{}:** {}\n",
+                outcome,
+                escape_markdown(&verdict.summary)
+            ));
+        }
+        out
+    }
+
+    pub fn render_json(&self) -> String {
+        // Keys are strings and floats serialize as null when non-finite
+        let json = serde_json::to_string_pretty(self).expect("reports always serialize");
+        format!("{}\n", json)
+    }
+}
+
+fn push_text_facts(out: &mut String, facts: &[Fact], indent: &str) {
+    let width = facts
+        .iter()
+        .map(|f| f.label.chars().count() + 1)
+        .max()
+        .unwrap_or(0);
+    for fact in facts {
+        out.push_str(&format!(
+            "{indent}{:<width$} {}\n",
+            format!("{}:", fact.label),
+            fact.value,
+            width = width
+        ));
+    }
+}
+
+fn push_markdown_facts(out: &mut String, facts: &[Fact]) {
+    if facts.is_empty() {
+        return;
+    }
+    out.push('\n');
+    for fact in facts {
+        out.push_str(&format!(
+            "- **{}:** {}\n",
+            escape_markdown(&fact.label),
+            escape_markdown(&fact.value.to_string())
+        ));
+    }
+}
+
+/// Keeps scenario names and the like from breaking tables or emphasis
+fn escape_markdown(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '|' | '*' | '`' | '[' | ']' | '<' | '>' | '#' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// ── Builders ────────────────────────────────────────────────
+
+impl Report {
+    /// The `evaluate` results: one section of metric means and spreads per
+    /// scenario
+    pub fn evaluation(results: &[ScenarioResult]) -> Self {
+        let sections = results
+            .iter()
+            .map(|result| {
+                let tiers = &result.tier_counts;
+                let cost = result.cost.per_run(result.runs);
+                let mut section = Section {
+                    heading: format!(
+                        "{} ({} runs, {} routes total)",
+                        result.scenario.name, result.runs, result.total_routes
+                    ),
+                    facts: vec![
+                        Fact::new("scenario", "scenario", Value::text(&result.scenario.name)),
+                        Fact::new("runs", "runs", Value::Count(result.runs)),
+                        Fact::new(
+                            "total_routes",
+                            "total_routes",
+                            Value::Count(result.total_routes),
+                        ),
+                        Fact::new(
+                            "success_rate",
+                            "success_rate",
+                            Value::percent(result.success_rate, 0),
+                        ),
+                        Fact::new("gold", "gold", Value::Count(tiers.gold)),
+                        Fact::new("silver", "silver", Value::Count(tiers.silver)),
+                        Fact::new("bronze", "bronze", Value::Count(tiers.bronze)),
+                        Fact::new("untiered", "untiered", Value::Count(tiers.untiered)),
+                        Fact::new(
+                            "directions_calls_per_run",
+                            "directions_calls_per_run",
+                            Value::number(cost.directions_calls, 1),
+                        ),
+                        Fact::new(
+                            "poi_queries_per_run",
+                            "poi_queries_per_run",
+                            Value::number(cost.poi_queries, 1),
+                        ),
+                        Fact::new(
+                            "wall_clock_ms_per_run",
+                            "wall_clock_ms_per_run",
+                            Value::number(cost.wall_clock_ms, 0),
+                        ),
+                    ],
+                    columns: vec![
+                        Column::new("metric", "metric"),
+                        Column::new("mean", "mean"),
+                        Column::new("std_dev", "std_dev"),
+                    ],
+                    ..Section::default()
+                };
+
+                match result.metrics_agg {
+                    Some(ref agg) => {
+                        let number = |name: &str, mean: f32, std_dev: f32, decimals: usize| {
+                            vec![
+                                Value::text(name),
+                                Value::number(mean, decimals),
+                                Value::number(std_dev, decimals),
+                            ]
+                        };
+                        section.rows = vec![
+                            number(
+                                "circularity",
+                                agg.circularity.mean,
+                                agg.circularity.std_dev,
+                                2,
+                            ),
+                            number("convexity", agg.convexity.mean, agg.convexity.std_dev, 2),
+                            vec![
+                                Value::text("path_overlap_pct"),
+                                Value::percent(agg.path_overlap_pct.mean, 0),
+                                Value::percent(agg.path_overlap_pct.std_dev, 0),
+                            ],
+                            number(
+                                "poi_density_per_km",
+                                agg.poi_density_per_km.mean,
+                                agg.poi_density_per_km.std_dev,
+                                1,
+                            ),
+                            number(
+                                "category_entropy",
+                                agg.category_entropy.mean,
+                                agg.category_entropy.std_dev,
+                                2,
+                            ),
+                            number(
+                                "landmark_coverage",
+                                agg.landmark_coverage.mean,
+                                agg.landmark_coverage.std_dev,
+                                2,
+                            ),
+                            number(
+                                "distance_accuracy",
+                                agg.distance_accuracy.mean,
+                                agg.distance_accuracy.std_dev,
+                                2,
+                            ),
+                            number(
+                                "route_score",
+                                agg.route_score.mean,
+                                agg.route_score.std_dev,
+                                1,
+                            ),
+                        ];
+                    }
+                    None => section.notes.push("no routes with metrics".to_string()),
+                }
+                section
+            })
+            .collect();
+
+        Report {
+            title: "Route Quality Evaluation Report".to_string(),
+            facts: vec![Fact::new(
+                "scenarios",
+                "Scenarios",
+                Value::Count(results.len()),
+            )],
+            sections,
+            verdict: None,
+        }
+    }
+
+    /// A baseline comparison: every metric against the baseline, with the
+    /// pass/fail verdict `evaluate --check` exits on
+    pub fn comparison(report: &ComparisonReport) -> Self {
+        let mut sections: Vec<Section> = report
+            .scenario_comparisons
+            .iter()
+            .map(|sc| Section {
+                heading: format!("Scenario: {} ({} runs)", sc.name, sc.runs),
+                facts: vec![
+                    Fact::new("scenario", "scenario", Value::text(&sc.name)),
+                    Fact::new("runs", "runs", Value::Count(sc.runs)),
+                    Fact::new("regressions", "regressions", Value::Count(sc.regressions)),
+                ],
+                columns: vec![
+                    Column::new("metric", "metric"),
+                    Column::new("current", "current"),
+                    Column::new("baseline", "baseline"),
+                    Column::new("change_pct", "change"),
+                    Column::new("p_value", "p"),
+                    Column::new("status", "status"),
+                ],
+                rows: sc
+                    .metric_comparisons
+                    .iter()
+                    .map(|mc| {
+                        let change = if mc.baseline.abs() < f32::EPSILON {
+                            Value::Missing
+                        } else {
+                            Value::change(mc.change_pct, 1)
+                        };
+                        let status = if mc.regressed { "REGRESSION" } else { "ok" };
+                        vec![
+                            Value::text(&mc.name),
+                            Value::number(mc.current, 2),
+                            Value::number(mc.baseline, 2),
+                            change,
+                            mc.p_value.map_or(Value::Missing, |p| Value::number(p, 3)),
+                            Value::text(status),
+                        ]
+                    })
+                    .collect(),
+                notes: Vec::new(),
+            })
+            .collect();
+
+        if !report.new_scenarios.is_empty() {
+            sections.push(Section {
+                heading: "New scenarios (no baseline)".to_string(),
+                notes: report.new_scenarios.clone(),
+                ..Section::default()
+            });
+        }
+
+        let scenario_count = report.scenario_comparisons.len() + report.new_scenarios.len();
+        let summary = if report.total_regressions > 0 {
+            format!(
+                "{} regression(s) detected across {} scenarios",
+                report.total_regressions, scenario_count
+            )
+        } else {
+            format!(
+                "No regressions detected across {} scenarios",
+                scenario_count
+            )
+        };
+
+        Report {
+            title: "Baseline Comparison".to_string(),
+            facts: vec![
+                Fact::new(
+                    "baseline_timestamp",
+                    "Baseline saved",
+                    Value::text(&report.baseline_timestamp),
+                ),
+                Fact::new(
+                    "threshold",
+                    "Regression threshold",
+                    Value::percent(report.threshold, 0),
+                ),
+                Fact::new(
+                    "significance_level",
+                    "Significance level",
+                    Value::Exact(report.significance_level),
+                ),
+                Fact::new(
+                    "total_regressions",
+                    "Regressions",
+                    Value::Count(report.total_regressions),
+                ),
+            ],
+            sections,
+            verdict: Some(Verdict {
+                passed: report.total_regressions == 0,
+                summary,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::baseline::{MetricComparison, ScenarioComparison};
+
+    fn comparison() -> ComparisonReport {
+        ComparisonReport {
+            baseline_timestamp: "2026-01-01T00:00:00Z".to_string(),
+            threshold: 0.15,
+            significance_level: 0.001,
+            scenario_comparisons: vec![ScenarioComparison {
+                name: "monaco_walk_5km".to_string(),
+                runs: 3,
+                metric_comparisons: vec![
+                    MetricComparison {
+                        name: "circularity".to_string(),
+                        current: 0.6,
+                        baseline: 0.8,
+                        change_pct: -0.25,
+                        regressed: true,
+                        p_value: Some(0.0004),
+                    },
+                    MetricComparison {
+                        name: "poi_density".to_string(),
+                        current: 1.5,
+                        baseline: 0.0,
+                        change_pct: 0.0,
+                        regressed: false,
+                        p_value: None,
+                    },
+                ],
+                regressions: 1,
+            }],
+            total_regressions: 1,
+            new_scenarios: vec!["paris|bike".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!(
+            "md".parse::<ReportFormat>().unwrap(),
+            ReportFormat::Markdown
+        );
+        assert_eq!("JSON".parse::<ReportFormat>().unwrap(), ReportFormat::Json);
+        assert!("html".parse::<ReportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_comparison_renders_as_text_and_markdown() {
+        let report = Report::comparison(&comparison());
+
+        let text = report.render(ReportFormat::Text);
+        assert!(text.starts_with("=== Baseline Comparison ===\n"));
+        assert!(text.contains("Regression threshold: 15%"));
+        let level = text.lines().find(|l| l.starts_with("Significance level:"));
+        assert!(level.unwrap().ends_with(" 0.001"));
+        let row = text.lines().find(|l| l.contains("circularity")).unwrap();
+        assert_eq!(
+            row.split_whitespace().collect::<Vec<_>>(),
+            [
+                "circularity",
+                "0.60",
+                "0.80",
+                "-25.0%",
+                "0.000",
+                "REGRESSION"
+            ]
+        );
+        let row = text.lines().find(|l| l.contains("poi_density")).unwrap();
+        assert_eq!(
+            row.split_whitespace().collect::<Vec<_>>(),
+            ["poi_density", "1.50", "0.00", "-", "-", "ok"]
+        );
+        assert!(text.ends_with("\nRESULT: 1 regression(s) detected across 2 scenarios\n"));
+
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.contains("| metric | current | baseline | change | p | status |\n"));
+        assert!(markdown.contains("| circularity | 0.60 | 0.80 | -25.0% | 0.000 | REGRESSION |\n"));
+        assert!(markdown.contains("- paris\\|bike\n"));
+        assert!(markdown.ends_with("**FAIL:** 1 regression(s) detected across 2 scenarios\n"));
+    }
+
+    #[test]
+    fn test_comparison_json_keeps_numbers() {
+        let report = Report::comparison(&comparison());
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(ReportFormat::Json)).unwrap();
+
+        assert_eq!(json["facts"]["threshold"], serde_json::json!(0.15));
+        assert_eq!(
+            json["facts"]["significance_level"],
+            serde_json::json!(0.001)
+        );
+        assert_eq!(json["sections"][0]["facts"]["scenario"], "monaco_walk_5km");
+        assert_eq!(json["sections"][0]["facts"]["runs"], 3);
+        assert_eq!(json["facts"]["total_regressions"], 1);
+        assert_eq!(json["verdict"]["passed"], false);
+        let rows = &json["sections"][0]["rows"];
+        assert_eq!(rows[0]["metric"], "circularity");
+        assert_eq!(rows[0]["current"], serde_json::json!(0.6));
+        assert_eq!(rows[0]["change_pct"], serde_json::json!(-0.25));
+        assert_eq!(rows[0]["p_value"], serde_json::json!(0.0004));
+        assert_eq!(rows[0]["status"], "REGRESSION");
+        assert!(rows[1]["change_pct"].is_null());
+        assert!(rows[1]["p_value"].is_null());
+        assert_eq!(json["sections"][1]["notes"][0], "paris|bike");
+    }
+}
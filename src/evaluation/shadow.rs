@@ -0,0 +1,160 @@
+//! Shadow-mode evaluation on live traffic.
+//!
+//! A sample of generated requests is re-run with a candidate generator in a
+//! background task. The response never waits on it; both best routes and their
+//! metric deltas are written to the evaluation tables for offline comparison.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sqlx::PgPool;
+
+use crate::db::queries;
+use crate::models::evaluation::{EvaluatedRoute, ShadowComparison};
+use crate::models::{Coordinates, Route, RoutePreferences, TransportMode};
+use crate::services::privacy::LocationPrivacy;
+use crate::services::route_generator::RouteGenerator;
+
+/// Everything the candidate needs to replay a loop request
+#[derive(Debug, Clone)]
+pub struct ShadowRequest {
+    pub start: Coordinates,
+    pub distance_km: f64,
+    pub distance_tolerance: f64,
+    pub mode: TransportMode,
+    pub preferences: RoutePreferences,
+}
+
+pub struct ShadowRunner {
+    candidate: RouteGenerator,
+    pool: PgPool,
+    sample_rate: f64,
+    primary_strategy: String,
+    candidate_strategy: String,
+    privacy: Option<LocationPrivacy>,
+}
+
+impl ShadowRunner {
+    pub fn new(
+        candidate: RouteGenerator,
+        pool: PgPool,
+        sample_rate: f64,
+        primary_strategy: String,
+        candidate_strategy: String,
+    ) -> Self {
+        ShadowRunner {
+            candidate,
+            pool,
+            sample_rate,
+            primary_strategy,
+            candidate_strategy,
+            privacy: None,
+        }
+    }
+
+    /// Store jittered start points instead of the exact ones
+    pub fn with_location_privacy(mut self, privacy: Option<LocationPrivacy>) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    /// Roll for whether the current request gets shadowed
+    pub fn should_sample(&self) -> bool {
+        rand::random::<f64>() < self.sample_rate
+    }
+
+    /// Run the candidate in the background and store the comparison.
+    /// Failures are logged, never surfaced to the caller.
+    pub fn spawn(
+        self: &Arc<Self>,
+        request: ShadowRequest,
+        primary_routes: Vec<Route>,
+        primary_latency: Duration,
+    ) {
+        let runner = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(e) = runner.run(request, primary_routes, primary_latency).await {
+                tracing::warn!(error = %e, "Shadow evaluation failed: {}", e);
+            }
+        });
+    }
+
+    async fn run(
+        &self,
+        request: ShadowRequest,
+        primary_routes: Vec<Route>,
+        primary_latency: Duration,
+    ) -> Result<(), sqlx::Error> {
+        let Some(primary) = best_route(&primary_routes) else {
+            return Ok(());
+        };
+
+        let started = Instant::now();
+        let candidate_result = self
+            .candidate
+            .generate_loop_route(
+                request.start,
+                request.distance_km,
+                request.distance_tolerance,
+                &request.mode,
+                &request.preferences,
+            )
+            .await;
+        let candidate_latency = started.elapsed();
+
+        // Both rows share one jittered start so they stay comparable
+        let stored_start = self
+            .privacy
+            .map_or(request.start, |p| p.fuzz(&request.start));
+        let evaluate = |route: &Route, strategy: &str| {
+            EvaluatedRoute::from_route(
+                route,
+                &stored_start,
+                request.distance_km,
+                &request.mode,
+                strategy,
+            )
+        };
+        let primary_eval = evaluate(primary, &self.primary_strategy);
+        queries::insert_evaluated_route(&self.pool, &primary_eval).await?;
+
+        let candidate_eval = match candidate_result {
+            Ok(ref routes) => best_route(routes)
+                .map(|route| evaluate(route, &self.candidate_strategy))
+                .ok_or_else(|| "Candidate returned no routes".to_string()),
+            Err(ref e) => Err(e.to_string()),
+        };
+        if let Ok(ref eval) = candidate_eval {
+            queries::insert_evaluated_route(&self.pool, eval).await?;
+        }
+
+        let latency_ms_delta =
+            candidate_latency.as_millis() as i64 - primary_latency.as_millis() as i64;
+        let comparison = ShadowComparison::new(
+            &primary_eval,
+            candidate_eval.as_ref().map_err(|e| e.clone()),
+            &self.candidate_strategy,
+            i32::try_from(latency_ms_delta).ok(),
+        );
+        queries::insert_shadow_comparison(&self.pool, &comparison).await?;
+
+        tracing::info!(
+            primary = %self.primary_strategy,
+            candidate = %self.candidate_strategy,
+            score_delta = ?comparison.score_delta,
+            candidate_error = ?comparison.candidate_error,
+            "Shadow evaluation stored: {} vs {}",
+            self.primary_strategy,
+            self.candidate_strategy
+        );
+        Ok(())
+    }
+}
+
+fn best_route(routes: &[Route]) -> Option<&Route> {
+    routes.iter().max_by(|a, b| {
+        a.score
+            .partial_cmp(&b.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    })
+}
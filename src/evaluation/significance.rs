@@ -0,0 +1,201 @@
+//! Significance tests for baseline comparisons: is a metric's change bigger
+//! than its run-to-run noise? Welch's t-test for metric means, a
+//! two-proportion z-test for success rates.
+
+/// Significance level below which a change counts as real
+pub const DEFAULT_SIGNIFICANCE_LEVEL: f32 = 0.05;
+
+/// Mean, standard deviation and sample count of one side of a comparison
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub n: usize,
+}
+
+/// Two-sided p-value of Welch's t-test between `a` and `b`. `None` when
+/// either side has fewer than two samples.
+pub fn welch_p_value(a: Sample, b: Sample) -> Option<f64> {
+    if a.n < 2 || b.n < 2 {
+        return None;
+    }
+    let va = a.std_dev * a.std_dev / a.n as f64;
+    let vb = b.std_dev * b.std_dev / b.n as f64;
+    let se2 = va + vb;
+    if se2 <= 0.0 {
+        // No spread on either side: any difference is certain
+        return Some(if a.mean == b.mean { 1.0 } else { 0.0 });
+    }
+    let t = (a.mean - b.mean) / se2.sqrt();
+    let df = se2 * se2 / (va * va / (a.n - 1) as f64 + vb * vb / (b.n - 1) as f64);
+    Some(student_t_two_sided(t, df))
+}
+
+/// Two-sided p-value of the pooled two-proportion z-test between success
+/// rates `a` and `b` over `n_a` and `n_b` runs. `None` when either side has
+/// no runs.
+pub fn two_proportion_p_value(a: f64, n_a: usize, b: f64, n_b: usize) -> Option<f64> {
+    if n_a == 0 || n_b == 0 {
+        return None;
+    }
+    let (n_a, n_b) = (n_a as f64, n_b as f64);
+    let pooled = (a * n_a + b * n_b) / (n_a + n_b);
+    let se2 = pooled * (1.0 - pooled) * (1.0 / n_a + 1.0 / n_b);
+    if se2 <= 0.0 {
+        // Every run on both sides succeeded (or failed): the rates are equal
+        return Some(1.0);
+    }
+    let z = (a - b) / se2.sqrt();
+    Some(erfc(z.abs() / std::f64::consts::SQRT_2).clamp(0.0, 1.0))
+}
+
+/// Complementary error function, accurate to 1.2e-7 (Numerical Recipes
+/// `erfcc`)
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.5 * x.abs());
+    let poly = -x * x - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let result = t * poly.exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+/// P(|T| >= |t|) for Student's t with `df` degrees of freedom
+fn student_t_two_sided(t: f64, df: f64) -> f64 {
+    let x = df / (df + t * t);
+    regularized_incomplete_beta(x, df / 2.0, 0.5).clamp(0.0, 1.0)
+}
+
+/// I_x(a, b) via its continued fraction (Numerical Recipes `betai`)
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    let front = ln_front.exp();
+    // The continued fraction converges fastest below the distribution mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 200;
+    const EPSILON: f64 = 1e-12;
+    const TINY: f64 = 1e-300;
+
+    let guard = |v: f64| if v.abs() < TINY { TINY } else { v };
+    let (qab, qap, qam) = (a + b, a + 1.0, a - 1.0);
+    let mut c = 1.0;
+    let mut d = 1.0 / guard(1.0 - qab * x / qap);
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        let even = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 / guard(1.0 + even * d);
+        c = guard(1.0 + even / c);
+        h *= d * c;
+        let odd = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 / guard(1.0 + odd * d);
+        c = guard(1.0 + odd / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// Lanczos approximation of ln Γ(x) for x > 0
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000_000_000_190_015;
+    for (i, coefficient) in COEFFICIENTS.iter().enumerate() {
+        series += coefficient / (x + 1.0 + i as f64);
+    }
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(mean: f64, std_dev: f64, n: usize) -> Sample {
+        Sample { mean, std_dev, n }
+    }
+
+    #[test]
+    fn test_student_t_reference_values() {
+        // Two-sided critical values from t tables
+        assert!((student_t_two_sided(2.776, 4.0) - 0.05).abs() < 1e-3);
+        assert!((student_t_two_sided(2.228, 10.0) - 0.05).abs() < 1e-3);
+        assert!((student_t_two_sided(1.96, 1e6) - 0.05).abs() < 1e-3);
+        assert!((student_t_two_sided(0.0, 5.0) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_welch_p_value() {
+        // Large drop relative to the noise
+        let p = welch_p_value(sample(0.80, 0.02, 10), sample(0.50, 0.03, 10)).unwrap();
+        assert!(p < 1e-6, "{p}");
+        // Same drop, but noisy and only three runs each
+        let p = welch_p_value(sample(0.80, 0.30, 3), sample(0.50, 0.30, 3)).unwrap();
+        assert!(p > 0.2, "{p}");
+        // Textbook example: t = -2.22, df = 24.9, p = 0.036
+        let p = welch_p_value(sample(20.0, 4.0, 15), sample(23.0, 3.4, 15)).unwrap();
+        assert!((p - 0.036).abs() < 0.005, "{p}");
+
+        assert_eq!(
+            welch_p_value(sample(1.0, 0.1, 1), sample(0.5, 0.1, 5)),
+            None
+        );
+        assert_eq!(
+            welch_p_value(sample(1.0, 0.0, 3), sample(1.0, 0.0, 3)),
+            Some(1.0)
+        );
+        assert_eq!(
+            welch_p_value(sample(1.0, 0.0, 3), sample(0.5, 0.0, 3)),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_two_proportion_p_value() {
+        // 2-sided normal critical value
+        assert!((erfc(1.96 / std::f64::consts::SQRT_2) - 0.05).abs() < 1e-3);
+        // 100% of 10 runs against 70% of 10: z = 1.88, p = 0.06
+        let p = two_proportion_p_value(0.7, 10, 1.0, 10).unwrap();
+        assert!((p - 0.060).abs() < 0.005, "{p}");
+        // Same rates over many more runs
+        let p = two_proportion_p_value(0.7, 100, 1.0, 100).unwrap();
+        assert!(p < 1e-6, "{p}");
+
+        assert_eq!(two_proportion_p_value(1.0, 5, 1.0, 5), Some(1.0));
+        assert_eq!(two_proportion_p_value(0.5, 0, 1.0, 5), None);
+    }
+}
@@ -1,32 +1,96 @@
-// Library exports for testing and reusability
+//! Loop routes through points of interest.
+//!
+//! # Library API
+//!
+//! The items re-exported at the crate root are the stable API, covered by
+//! semver: [`RouteGenerator`] with its configuration, the traits it reads
+//! its data through ([`PoiRepository`], [`DirectionsProvider`],
+//! [`RouteCache`]), the route and POI models with their builders, and the
+//! route quality metrics.
+//!
+//! The modules below are public for the server, the bundled binaries,
+//! benches and integration tests, and hidden from the docs: their layout
+//! can change in any release. Code that only needs modules inside the crate
+//! keeps them `pub(crate)`.
 
+#[doc(hidden)]
 pub mod cache;
+#[doc(hidden)]
 pub mod config;
+#[doc(hidden)]
 pub mod constants;
+#[doc(hidden)]
 pub mod db;
-pub mod error;
+pub(crate) mod error;
+#[doc(hidden)]
 pub mod evaluation;
 #[cfg(feature = "mobile")]
 pub mod ffi;
 #[cfg(feature = "mobile")]
 pub mod mobile;
+#[doc(hidden)]
 pub mod models;
-#[cfg(feature = "sqlite")]
+#[doc(hidden)]
 pub mod osm;
+#[doc(hidden)]
 pub mod routes;
+#[doc(hidden)]
+pub mod scheduler;
+#[doc(hidden)]
 pub mod services;
+#[doc(hidden)]
+pub mod storage;
 
-// Re-export commonly used types
-pub use cache::{CacheStats, RouteCache, RoutePreferencesHash};
-pub use error::{AppError, Result};
+// Generator and its data sources
+pub use config::RouteGeneratorConfig;
+pub use db::PoiRepository;
+pub use services::directions::DirectionsProvider;
+pub use services::mapbox::{DirectionsLeg, DirectionsResponse};
+pub use services::route_generator::RouteGenerator;
+
+// Models
+pub use models::{
+    BoundingBox, Coordinates, CostingOptions, Departure, DurationEstimates, MustInclude, Poi,
+    PoiBuilder, PoiCategory, QualityTier, RoadProfile, Route, RouteBuilder, RoutePoi,
+    RoutePreferences, RouteStrength, RouteTimeline, SnappedPoi, SurfacePreference, SurfaceSplit,
+    TimeBreakdown, TransportMode,
+};
+
+// Metrics
+pub use services::route_generator::metrics_explanation::MetricsExplained;
+pub use services::route_generator::route_metrics::RouteMetrics;
+
+// Caching and errors
+pub use cache::{CacheFilter, CacheKeyPrecision, CacheStats, RouteCache, RoutePreferencesHash};
+pub use error::{AppError, GenerationFailure, Result};
 
 // App state for sharing across the application
-use services::route_generator::RouteGenerator;
+use evaluation::shadow::ShadowRunner;
+use services::dependency_guard::DependencyGuard;
+use services::events::EventBus;
+use services::privacy::LocationPrivacy;
+use services::request_log::RequestLogger;
+use services::tenant::TenantRegistry;
 use std::sync::Arc;
 
+#[doc(hidden)]
 pub struct AppState {
     pub poi_repo: Arc<dyn db::PoiRepository>,
     pub route_generator: RouteGenerator,
     /// Optional cache service - None only in tests
     pub cache: Option<Arc<dyn RouteCache>>,
+    /// Candidate strategy run on sampled requests - None unless shadow mode is enabled
+    pub shadow: Option<Arc<ShadowRunner>>,
+    /// Sampled NDJSON request log - None unless `REQUEST_LOG_PATH` is set
+    pub request_log: Option<RequestLogger>,
+    /// Start-point jitter/rounding - None unless `LOCATION_PRIVACY` is enabled
+    pub privacy: Option<LocationPrivacy>,
+    /// API key -> tenant mapping and usage - None unless `TENANT_API_KEYS` is set
+    pub tenants: Option<Arc<TenantRegistry>>,
+    /// Dependency health and fallback policy
+    pub guard: Arc<DependencyGuard>,
+    /// Route lifecycle events for decoupled subscribers
+    pub events: EventBus,
+    /// Large artifact storage - None unless `ARTIFACT_STORE` is set
+    pub artifacts: Option<Arc<dyn storage::ObjectStore>>,
 }
@@ -1,15 +1,36 @@
 use axum::Router;
-use easyroute::cache::{MemoryCacheService, RedisCacheService, RouteCache};
-use easyroute::config::Config;
+use easyroute::cache::{MemoryCacheService, RedisCacheService, RouteCache, TieredCacheService};
+use easyroute::config::{Config, RequestLogSinkConfig, RouteGeneratorConfig};
 use easyroute::constants::DEFAULT_MEMORY_CACHE_MAX_ENTRIES;
-use easyroute::db::PgPoiRepository;
+use easyroute::db::{PgPoiRepository, PoiRepository};
+use easyroute::evaluation::shadow::ShadowRunner;
+use easyroute::routes::share::ShareSettings;
+use easyroute::scheduler::tasks::{
+    EvaluationRetentionTask, GeometryBackfillTask, MetricRecomputeTask, SavedRouteConsistencyTask,
+    SnapRadiusTuningTask,
+};
+use easyroute::scheduler::Scheduler;
+use easyroute::services::dependency_guard::{Dependency, DependencyGuard};
+use easyroute::services::directions::DirectionsProvider;
+use easyroute::services::elevation::{
+    CachedElevationProvider, ElevationProvider, OpenElevationClient,
+};
+use easyroute::services::environment::{EnvironmentalLayer, GridLayer};
+use easyroute::services::events::EventBus;
 use easyroute::services::mapbox::{AuthMode, MapboxClient};
+use easyroute::services::osrm::OsrmClient;
 use easyroute::services::poi_service::PoiService;
+use easyroute::services::poi_snapshot::PoiSnapshots;
+use easyroute::services::privacy::LocationPrivacy;
+use easyroute::services::request_log::{FileSink, ObjectStoreSink, RequestLogSink, RequestLogger};
 use easyroute::services::route_generator::RouteGenerator;
 use easyroute::services::snapping_service::SnappingService;
+use easyroute::services::tenant::TenantRegistry;
+use easyroute::services::timeouts::TimeoutPoiRepository;
+use easyroute::services::valhalla::ValhallaClient;
 use easyroute::AppState;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
+use std::time::Duration;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -27,49 +48,117 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load configuration
     let config = Config::from_env().map_err(|e| format!("Failed to load configuration: {}", e))?;
 
+    // `--check-config`: validate the configuration and exit without connecting anywhere
+    let check_only = std::env::args().any(|arg| arg == "--check-config");
+    if let Err(issues) = config.validate() {
+        for issue in &issues {
+            tracing::error!(
+                field = issue.field,
+                "Invalid configuration: {}",
+                issue.message
+            );
+        }
+        return Err(format!("Invalid configuration ({} issue(s))", issues.len()).into());
+    }
+    if check_only {
+        tracing::info!("Configuration is valid");
+        return Ok(());
+    }
+
     tracing::info!("Starting EasyRoute API server");
     tracing::info!("Configuration loaded successfully");
 
-    // Create database connection pool
-    tracing::info!("Connecting to database...");
-    let db_pool = easyroute::db::create_pool(&config.database_url).await?;
-    tracing::info!("Database connection established");
+    // Create database connection pool, unless POIs come from a region file
+    // Requests get a pool bounded by POSTGRES_TIMEOUT_MS; migrations,
+    // background tasks and admin endpoints get their own, unbounded one.
+    let (db_pool, task_pool) = if config.is_offline() {
+        tracing::info!("Offline mode: running without PostgreSQL");
+        (None, None)
+    } else {
+        tracing::info!("Connecting to database...");
+        let task_pool =
+            easyroute::db::create_pool(&config.database_url, config.database_task_pool_size)
+                .await?;
+        let db_pool = easyroute::db::create_budgeted_pool(
+            &config.database_url,
+            Duration::from_millis(config.degradation.postgres_timeout_ms),
+            config.database_pool_size,
+        )
+        .await?;
+        tracing::info!("Database connection established");
 
-    // Run migrations
-    tracing::info!("Running database migrations...");
-    sqlx::migrate!("./migrations").run(&db_pool).await?;
-    tracing::info!("Database migrations completed");
+        // Run migrations
+        tracing::info!("Running database migrations...");
+        sqlx::migrate!("./migrations").run(&task_pool).await?;
+        tracing::info!("Database migrations completed");
+        (Some(db_pool), Some(task_pool))
+    };
 
-    // Initialize cache: try Redis, fall back to in-memory
+    // Dependency fallback policy, shared by the cache setup and the pipeline
+    let guard = Arc::new(DependencyGuard::new(config.degradation.clone()));
+
+    // Initialize cache: an in-memory tier, backed by Redis when configured and
+    // reachable (in-memory alone under FALLBACK_REDIS=degrade)
+    let memory_cache = || {
+        MemoryCacheService::new(config.route_cache_ttl, DEFAULT_MEMORY_CACHE_MAX_ENTRIES)
+            .with_key_precision(config.cache_key_precision())
+    };
     let cache: Arc<dyn RouteCache> = if let Some(ref redis_url) = config.redis_url {
         tracing::info!("Connecting to Redis cache...");
         match RedisCacheService::new(redis_url, config.route_cache_ttl).await {
             Ok(redis_cache) => {
-                tracing::info!("Redis cache connection established");
-                Arc::new(redis_cache)
-            }
-            Err(e) => {
-                tracing::warn!(
-                    "Failed to connect to Redis: {}. Falling back to in-memory cache.",
-                    e
+                tracing::info!(
+                    compression = %config.cache_compression,
+                    "Redis cache connection established ({} compression)",
+                    config.cache_compression
                 );
-                Arc::new(MemoryCacheService::new(
-                    config.route_cache_ttl,
-                    DEFAULT_MEMORY_CACHE_MAX_ENTRIES,
+                Arc::new(TieredCacheService::new(
+                    memory_cache(),
+                    Arc::new(
+                        redis_cache
+                            .with_key_precision(config.cache_key_precision())
+                            .with_compression(config.cache_compression)
+                            .with_op_timeout(Duration::from_millis(
+                                config.degradation.redis_timeout_ms,
+                            )),
+                    ),
                 ))
             }
+            Err(e) => {
+                guard.report_failure(Dependency::Redis, &e);
+                if !guard.degrades(Dependency::Redis) {
+                    return Err(format!("Redis unavailable and FALLBACK_REDIS=fail: {}", e).into());
+                }
+                Arc::new(memory_cache())
+            }
         }
     } else {
         tracing::info!("Redis URL not configured. Using in-memory cache.");
-        Arc::new(MemoryCacheService::new(
-            config.route_cache_ttl,
-            DEFAULT_MEMORY_CACHE_MAX_ENTRIES,
-        ))
+        Arc::new(memory_cache())
     };
 
     // Initialize services
-    let poi_repo: Arc<dyn easyroute::db::PoiRepository> =
-        Arc::new(PgPoiRepository::new(db_pool.clone()));
+    let poi_repo: Arc<dyn PoiRepository> = match (&db_pool, &config.region_db_path) {
+        (Some(db_pool), _) => {
+            let pois_partitioned = easyroute::db::queries::pois_partitioned(db_pool).await?;
+            if pois_partitioned {
+                tracing::info!("POI table is partitioned, enabling partition pruning");
+            }
+            Arc::new(TimeoutPoiRepository::new(
+                Arc::new(
+                    PgPoiRepository::new(db_pool.clone())
+                        .with_partition_pruning(pois_partitioned)
+                        .with_geohash_prefilter(config.poi_geohash_prefilter),
+                ),
+                Duration::from_millis(config.degradation.postgres_timeout_ms),
+            ))
+        }
+        (None, Some(region_path)) => match config.region_sync {
+            Some(ref sync) => synced_region_repository(&config, region_path, sync).await?,
+            None => region_repository(region_path).await?,
+        },
+        (None, None) => unreachable!("offline mode without REGION_DB_PATH"),
+    };
     let mapbox_client = if let Some(ref base_url) = config.mapbox_base_url {
         MapboxClient::with_config(
             config.mapbox_api_key.clone(),
@@ -79,36 +168,286 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else {
         MapboxClient::new(config.mapbox_api_key.clone())
     };
-    let poi_service = PoiService::new(poi_repo.clone());
-    let snapping_service = SnappingService::new(poi_repo.clone());
-    let route_generator = RouteGenerator::new(
-        mapbox_client,
-        poi_service,
-        snapping_service,
-        config.snap_radius_m,
-        config.route_generator.clone(),
-    );
+    // Fault injection for resilience testing
+    let (poi_repo, mapbox_client) = match config.chaos {
+        Some(ref chaos) => inject_faults(chaos, poi_repo, mapbox_client),
+        None => (poi_repo, mapbox_client),
+    };
+    let environmental_layer: Option<Arc<dyn EnvironmentalLayer>> = config
+        .environmental_layer
+        .as_ref()
+        .and_then(|layer_config| {
+            GridLayer::from_asc_file(
+                std::path::Path::new(&layer_config.path),
+                &layer_config.name,
+                layer_config.min_value,
+                layer_config.max_value,
+            )
+            .map(|layer| Arc::new(layer) as Arc<dyn EnvironmentalLayer>)
+            .map_err(|e| {
+                tracing::warn!(
+                    error = %e,
+                    "Failed to load environmental layer, exposure metrics disabled: {}",
+                    e
+                )
+            })
+            .ok()
+        });
+    let elevation_provider: Option<Arc<dyn ElevationProvider>> =
+        config.elevation.as_ref().map(|elevation_config| {
+            let client = OpenElevationClient::new(elevation_config.api_url.clone());
+            Arc::new(CachedElevationProvider::new(Arc::new(client))) as Arc<dyn ElevationProvider>
+        });
+    let directions_provider: Option<Arc<dyn DirectionsProvider>> =
+        match (&config.valhalla, &config.osrm) {
+            (Some(valhalla_config), _) => {
+                Some(Arc::new(ValhallaClient::new(valhalla_config.url.clone())))
+            }
+            (None, Some(osrm_config)) => Some(Arc::new(OsrmClient::new(osrm_config.url.clone()))),
+            (None, None) => None,
+        };
+    let privacy = config.privacy.as_ref().map(|privacy_config| {
+        tracing::info!(
+            jitter_m = privacy_config.jitter_m,
+            log_decimals = privacy_config.log_decimals,
+            "Location privacy enabled: stored start points jittered by up to {:.0} m",
+            privacy_config.jitter_m
+        );
+        LocationPrivacy::new(privacy_config.jitter_m, privacy_config.log_decimals)
+    });
+    // Busiest cities keep their POIs in memory, refreshed in the background
+    let poi_snapshots = config.warm_cities.as_ref().map(|warm_config| {
+        tracing::info!(
+            cities = warm_config.cities.len(),
+            refresh_secs = warm_config.refresh_secs,
+            "Warm city POI snapshots enabled for {} cities",
+            warm_config.cities.len()
+        );
+        let snapshots = Arc::new(PoiSnapshots::new(warm_config));
+        Arc::clone(&snapshots).spawn_refresh(
+            poi_repo.clone(),
+            Duration::from_secs(warm_config.refresh_secs),
+        );
+        snapshots
+    });
+    let build_generator = |generator_config: RouteGeneratorConfig| {
+        let route_generator = RouteGenerator::new(
+            mapbox_client.clone(),
+            PoiService::new(poi_repo.clone())
+                .with_location_privacy(privacy)
+                .with_snapshots(poi_snapshots.clone()),
+            SnappingService::new(poi_repo.clone()),
+            config.snap_radius_m,
+            generator_config,
+        );
+        let route_generator = route_generator.with_dependency_guard(Arc::clone(&guard));
+        let route_generator = match directions_provider {
+            Some(ref provider) => route_generator.with_directions_provider(Arc::clone(provider)),
+            None => route_generator,
+        };
+        let route_generator = match environmental_layer {
+            Some(ref layer) => route_generator.with_environmental_layer(Arc::clone(layer)),
+            None => route_generator,
+        };
+        match elevation_provider {
+            Some(ref provider) => route_generator.with_elevation_provider(Arc::clone(provider)),
+            None => route_generator,
+        }
+    };
+    let route_generator = build_generator(config.route_generator.clone());
+
+    // Shadow mode: a candidate generator replays a sample of requests
+    let shadow = config
+        .shadow
+        .as_ref()
+        .zip(db_pool.clone())
+        .map(|(shadow_config, db_pool)| {
+            let candidate_config = shadow_config.candidate_config(&config.route_generator);
+            let candidate_strategy = candidate_config.strategy_label();
+            tracing::info!(
+                sample_rate = shadow_config.sample_rate,
+                candidate = %candidate_strategy,
+                "Shadow mode enabled: {:.0}% of requests also run {}",
+                shadow_config.sample_rate * 100.0,
+                candidate_strategy
+            );
+            Arc::new(
+                ShadowRunner::new(
+                    build_generator(candidate_config),
+                    db_pool,
+                    shadow_config.sample_rate,
+                    config.route_generator.strategy_label(),
+                    candidate_strategy,
+                )
+                .with_location_privacy(privacy),
+            )
+        });
+
+    // Periodic background tasks, with run history and cross-instance locking.
+    // All of them maintain Postgres tables, so offline mode has none.
+    if let Some(ref db_pool) = task_pool {
+        let mut scheduler =
+            Scheduler::new(config.scheduler.clone()).with_run_history(db_pool.clone());
+        if let Some(days) = config.evaluation_retention_days {
+            tracing::info!(days, "Evaluation data retention: {} days", days);
+            scheduler.register(Arc::new(EvaluationRetentionTask::new(
+                db_pool.clone(),
+                days,
+            )));
+        }
+        scheduler.register(Arc::new(SnapRadiusTuningTask::new(
+            db_pool.clone(),
+            config.snap_radius_m,
+        )));
+        scheduler.register(Arc::new(GeometryBackfillTask::new(db_pool.clone())));
+        scheduler.register(Arc::new(MetricRecomputeTask::new(db_pool.clone())));
+        scheduler.register(Arc::new(SavedRouteConsistencyTask::new(
+            db_pool.clone(),
+            poi_repo.clone(),
+        )));
+        scheduler.spawn();
+    }
+
+    // Multi-tenant mode: API keys map to tenant namespaces
+    let tenants = config.tenants.as_ref().map(|tenant_config| {
+        let registry = TenantRegistry::from_config(tenant_config);
+        tracing::info!(
+            tenants = registry.tenant_count(),
+            "Multi-tenant mode: {} tenants, API key required",
+            registry.tenant_count()
+        );
+        Arc::new(registry)
+    });
+
+    // Artifact storage (traces, exports, previews) served through signed URLs
+    let artifacts = config
+        .artifact_store
+        .as_ref()
+        .map(easyroute::storage::from_config)
+        .transpose()?;
+    if let Some(ref store) = artifacts {
+        tracing::info!(
+            backend = store.backend_name(),
+            "Artifact storage: {}",
+            store.backend_name()
+        );
+    }
+
+    // Sampled request/response log for offline replay
+    let request_log = match config.request_log {
+        Some(ref log_config) => {
+            let sink: Arc<dyn RequestLogSink> = match log_config.sink {
+                RequestLogSinkConfig::File(ref path) => {
+                    tracing::info!(
+                        path = %path,
+                        sample_rate = log_config.sample_rate,
+                        "Request logging enabled: {}",
+                        path
+                    );
+                    Arc::new(FileSink::new(path))
+                }
+                RequestLogSinkConfig::ObjectStore { ref prefix } => {
+                    let store = artifacts
+                        .clone()
+                        .ok_or("REQUEST_LOG_STORE_PREFIX needs ARTIFACT_STORE")?;
+                    tracing::info!(
+                        prefix = %prefix,
+                        sample_rate = log_config.sample_rate,
+                        "Request logging enabled: artifact store under {}/",
+                        prefix
+                    );
+                    Arc::new(ObjectStoreSink::new(store, prefix.clone()))
+                }
+            };
+            Some(RequestLogger::new(sink, log_config.sample_rate))
+        }
+        None => None,
+    };
+
+    // Lifecycle events, fanned out across instances over Redis when configured
+    let mut events = EventBus::default();
+    if let (Some(channel), Some(redis_url)) = (&config.event_fanout_channel, &config.redis_url) {
+        match events.clone().with_redis_fanout(redis_url, channel).await {
+            Ok(fanned_out) => {
+                tracing::info!(channel = %channel, "Lifecycle events fanned out on Redis channel {}", channel);
+                events = fanned_out;
+            }
+            Err(e) => tracing::warn!(error = %e, "Lifecycle event fanout disabled: {}", e),
+        }
+    }
+
+    // Operator cache invalidations reach this instance's in-process tier
+    easyroute::routes::admin::spawn_invalidation_listener(Arc::clone(&cache), &events);
 
     // Create application state
     let state = Arc::new(AppState {
         poi_repo,
         route_generator,
-        cache: Some(cache),
+        cache: Some(Arc::clone(&cache)),
+        shadow,
+        request_log,
+        privacy,
+        tenants: tenants.clone(),
+        guard,
+        events: events.clone(),
+        artifacts,
     });
 
-    // Build router with CORS and tracing
+    tracing::info!(
+        preset = %config.cors.preset,
+        origins = ?config.cors.allowed_origins,
+        credentials = config.cors.allow_credentials,
+        "CORS: {} preset",
+        config.cors.preset
+    );
+
+    // Build router with CORS, gzip compression and tracing
+    let mut api = easyroute::routes::create_router(state);
+    match db_pool {
+        Some(ref db_pool) => {
+            let mut pg_router = easyroute::routes::create_pg_router(db_pool.clone())
+                .layer(axum::Extension(events.clone()))
+                .layer(axum::Extension(ShareSettings {
+                    ttl_hours: config.share_link_ttl_hours,
+                }));
+            // Saved routes and share links are kept per tenant
+            if let Some(ref tenants) = tenants {
+                pg_router = pg_router.layer(axum::Extension(Arc::clone(tenants)));
+            }
+            api = api.merge(pg_router);
+        }
+        None => {
+            tracing::info!("Offline mode: evaluation, saved route and share endpoints are disabled")
+        }
+    }
+    if let Some(ref token) = config.admin_token {
+        tracing::info!("Admin endpoints enabled under /admin");
+        api = api.merge(easyroute::routes::admin::create_admin_router(
+            Arc::clone(&cache),
+            events.clone(),
+            token.clone(),
+        ));
+        // Exports scan whole tables: no request time budget
+        if let Some(ref task_pool) = task_pool {
+            api = api.merge(easyroute::routes::admin::create_admin_pg_router(
+                task_pool.clone(),
+                token.clone(),
+            ));
+        }
+    }
+    if let Some(db_pool) = db_pool.filter(|_| config.api_key_auth) {
+        tracing::info!("API key auth enabled for /routes/*");
+        api = api.layer(axum::middleware::from_fn_with_state(
+            db_pool,
+            easyroute::routes::auth::require_api_key,
+        ));
+    }
     let app = Router::new()
-        .nest(
-            "/api/v1",
-            easyroute::routes::create_router(state)
-                .merge(easyroute::routes::create_pg_router(db_pool)),
-        )
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
+        .nest("/api/v1", api)
+        .layer(easyroute::routes::cors::cors_layer(&config.cors))
+        .layer(axum::middleware::from_fn(
+            easyroute::routes::compression::gzip_responses,
+        ))
         .layer(TraceLayer::new_for_http());
 
     // Start server
@@ -120,3 +459,95 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// POIs from a SQLite region database (`REGION_DB_PATH`)
+#[cfg(feature = "sqlite")]
+async fn region_repository(
+    region_path: &str,
+) -> Result<Arc<dyn PoiRepository>, Box<dyn std::error::Error>> {
+    use easyroute::db::{SqlitePoiRepository, SqliteReadPoolConfig};
+
+    tracing::info!("Opening region database: {}", region_path);
+    let pool = SqlitePoiRepository::open_read_pool(region_path, &SqliteReadPoolConfig::server())
+        .await
+        .map_err(|e| format!("Failed to open region DB '{}': {}", region_path, e))?;
+    let poi_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pois")
+        .fetch_one(&pool)
+        .await?;
+    tracing::info!(poi_count, "Region database has {} POIs", poi_count);
+    Ok(Arc::new(SqlitePoiRepository::new(pool)))
+}
+
+/// POIs from the newest build of the `REGION_DB_PATH` region, fetched from
+/// `REGION_CATALOG_URL` and swapped when a newer one is published
+#[cfg(feature = "sqlite")]
+async fn synced_region_repository(
+    config: &Config,
+    region_path: &str,
+    sync_config: &easyroute::config::RegionSyncConfig,
+) -> Result<Arc<dyn PoiRepository>, Box<dyn std::error::Error>> {
+    use easyroute::services::region_sync::RegionSync;
+
+    let region_id = config
+        .region_id()
+        .ok_or("REGION_DB_PATH file name is not a region id")?;
+    tracing::info!(
+        region = region_id,
+        catalog = %sync_config.catalog_url,
+        "Syncing region database from catalog"
+    );
+    let sync = RegionSync::start(region_path, region_id, sync_config)
+        .await
+        .map_err(|e| format!("Failed to load region '{}': {}", region_id, e))?;
+    let repository = sync.repository();
+    sync.spawn(Duration::from_secs(sync_config.interval_secs));
+    Ok(repository)
+}
+
+/// Config validation rejects `REGION_DB_PATH` in builds without `sqlite`
+#[cfg(not(feature = "sqlite"))]
+async fn region_repository(
+    _region_path: &str,
+) -> Result<Arc<dyn PoiRepository>, Box<dyn std::error::Error>> {
+    Err("REGION_DB_PATH requires the `sqlite` feature".into())
+}
+
+#[cfg(not(feature = "sqlite"))]
+async fn synced_region_repository(
+    _config: &Config,
+    region_path: &str,
+    _sync_config: &easyroute::config::RegionSyncConfig,
+) -> Result<Arc<dyn PoiRepository>, Box<dyn std::error::Error>> {
+    region_repository(region_path).await
+}
+
+/// Wrap the POI repository and Mapbox client with `chaos`'s fault injector
+#[cfg(feature = "chaos")]
+fn inject_faults(
+    chaos: &easyroute::config::ChaosConfig,
+    poi_repo: Arc<dyn PoiRepository>,
+    mapbox_client: MapboxClient,
+) -> (Arc<dyn PoiRepository>, MapboxClient) {
+    use easyroute::services::chaos::{FaultInjector, FaultyPoiRepository};
+
+    tracing::warn!(
+        seed = chaos.seed,
+        mapbox = ?chaos.mapbox,
+        postgres = ?chaos.postgres,
+        "CHAOS_ENABLED: injecting faults into Mapbox and Postgres calls, do not use in production"
+    );
+    let faults = Arc::new(FaultInjector::new(chaos.clone()));
+    let poi_repo: Arc<dyn PoiRepository> =
+        Arc::new(FaultyPoiRepository::new(poi_repo, Arc::clone(&faults)));
+    (poi_repo, mapbox_client.with_fault_injector(faults))
+}
+
+/// Config validation rejects `CHAOS_ENABLED` in builds without `chaos`
+#[cfg(not(feature = "chaos"))]
+fn inject_faults(
+    _chaos: &easyroute::config::ChaosConfig,
+    poi_repo: Arc<dyn PoiRepository>,
+    mapbox_client: MapboxClient,
+) -> (Arc<dyn PoiRepository>, MapboxClient) {
+    (poi_repo, mapbox_client)
+}
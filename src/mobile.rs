@@ -1,7 +1,7 @@
 use crate::cache::MemoryCacheService;
 use crate::config::RouteGeneratorConfig;
 use crate::constants::DEFAULT_MEMORY_CACHE_MAX_ENTRIES;
-use crate::db::SqlitePoiRepository;
+use crate::db::{SqlitePoiRepository, SqliteReadPoolConfig};
 use crate::services::mapbox::{AuthMode, MapboxClient};
 use crate::services::poi_service::PoiService;
 use crate::services::route_generator::RouteGenerator;
@@ -13,8 +13,6 @@ use axum::http::{header, Request, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Router;
 use rust_embed::Embed;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::oneshot;
 use tower_http::cors::{Any, CorsLayer};
@@ -71,25 +69,18 @@ pub struct ServerHandle {
 pub async fn start_server(
     config: ServerConfig,
 ) -> Result<ServerHandle, Box<dyn std::error::Error + Send + Sync>> {
-    // Open SQLite pool with mobile-tuned pragmas
-    let opts = SqliteConnectOptions::from_str(&format!("sqlite:{}", config.region_db_path))?
-        .create_if_missing(false)
-        .pragma("journal_mode", "WAL")
-        .pragma("mmap_size", "33554432") // 32 MB (mobile)
-        .pragma("cache_size", "-8192"); // 8 MB
-
-    let pool = SqlitePoolOptions::new()
-        .max_connections(2)
-        .connect_with(opts)
-        .await
-        .map_err(|e| {
-            format!(
-                "Failed to open region DB '{}': {}",
-                config.region_db_path, e
-            )
-        })?;
-
-    SqlitePoiRepository::create_schema(&pool).await?;
+    // Read-only SQLite pool with mobile-tuned pragmas
+    let pool = SqlitePoiRepository::open_read_pool(
+        &config.region_db_path,
+        &SqliteReadPoolConfig::mobile(),
+    )
+    .await
+    .map_err(|e| {
+        format!(
+            "Failed to open region DB '{}': {}",
+            config.region_db_path, e
+        )
+    })?;
 
     // In-memory cache
     let cache = Arc::new(MemoryCacheService::new(
@@ -121,6 +112,13 @@ pub async fn start_server(
         poi_repo,
         route_generator,
         cache: Some(cache),
+        shadow: None,
+        request_log: None,
+        privacy: None,
+        tenants: None,
+        guard: Default::default(),
+        events: Default::default(),
+        artifacts: None,
     });
 
     // Router: API + embedded static fallback
@@ -0,0 +1,132 @@
+//! API keys stored in Postgres (`api_keys`), checked by the auth middleware
+//! when `API_KEY_AUTH` is on. Keys are shown once at creation; only their
+//! SHA-256 is stored.
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::constants::{API_KEY_PREFIX, API_KEY_RANDOM_LEN};
+use crate::services::tenant::TenantId;
+
+/// What a key may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApiScope {
+    /// Read saved routes
+    #[serde(rename = "routes:read")]
+    RoutesRead,
+    /// Generate, save and share routes
+    #[serde(rename = "routes:write")]
+    RoutesWrite,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 2] = [ApiScope::RoutesRead, ApiScope::RoutesWrite];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::RoutesRead => "routes:read",
+            ApiScope::RoutesWrite => "routes:write",
+        }
+    }
+}
+
+impl std::str::FromStr for ApiScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ApiScope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| format!("Invalid scope: {}. Use 'routes:read' or 'routes:write'", s))
+    }
+}
+
+impl std::fmt::Display for ApiScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The key a request authenticated with, added to its extensions
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: String,
+    /// Tenant the key's requests belong to
+    pub tenant: TenantId,
+    /// Unknown scope names in the database are dropped
+    pub scopes: Vec<ApiScope>,
+}
+
+impl ApiKey {
+    pub fn allows(&self, scope: ApiScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// A stored key as listed by the `apikey` CLI (never the key itself)
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ApiKeySummary {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub user_id: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub request_count: i64,
+    pub last_used_at: Option<String>,
+    pub created_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+/// A fresh random key, e.g. `er_4kq…`
+pub fn generate_key() -> String {
+    let random: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(API_KEY_RANDOM_LEN)
+        .map(char::from)
+        .collect();
+    format!("{}{}", API_KEY_PREFIX, random)
+}
+
+/// Hex SHA-256 of `key`, as stored in `api_keys.key_hash`
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Leading characters kept to tell keys apart in listings
+pub fn key_prefix(key: &str) -> String {
+    key.chars().take(API_KEY_PREFIX.len() + 4).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys_and_hashes() {
+        let key = generate_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + API_KEY_RANDOM_LEN);
+        assert_ne!(key, generate_key());
+
+        let hash = hash_key(&key);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_key(&key));
+        assert_eq!(
+            hash_key("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(key_prefix(&key), key[..7]);
+    }
+
+    #[test]
+    fn test_scope_names() {
+        for scope in ApiScope::ALL {
+            assert_eq!(scope.as_str().parse::<ApiScope>().unwrap(), scope);
+        }
+        assert!("admin".parse::<ApiScope>().is_err());
+    }
+}
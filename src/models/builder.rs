@@ -0,0 +1,328 @@
+//! Fluent builders for [`Route`] and [`Poi`], for tests and for crates
+//! embedding easyroute: set the fields that matter, the rest get defaults.
+//!
+//! ```
+//! use easyroute::{Coordinates, Poi, PoiCategory, Route};
+//!
+//! let start = Coordinates::new(48.8566, 2.3522).unwrap();
+//! let louvre = Poi::builder()
+//!     .name("Louvre")
+//!     .category(PoiCategory::Museum)
+//!     .coordinates(Coordinates::new(48.8606, 2.3376).unwrap())
+//!     .build();
+//! let route = Route::builder()
+//!     .path(vec![start, louvre.coordinates, start])
+//!     .waypoint(louvre)
+//!     .score(7.5)
+//!     .build();
+//! assert_eq!(route.pois[0].order_in_route, 1);
+//! ```
+
+use super::{
+    Coordinates, Poi, PoiCategory, RoadProfile, Route, RoutePoi, SnappedPoi, SpeedModels,
+    TransportMode,
+};
+use uuid::Uuid;
+
+/// Popularity of a built POI unless set
+const DEFAULT_POPULARITY: f32 = 50.0;
+
+impl Poi {
+    /// A [`PoiBuilder`]: an unnamed monument at (0, 0), popularity 50
+    pub fn builder() -> PoiBuilder {
+        PoiBuilder::default()
+    }
+}
+
+impl Route {
+    /// A [`RouteBuilder`]: an empty walking route with no path
+    pub fn builder() -> RouteBuilder {
+        RouteBuilder::default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PoiBuilder {
+    poi: Poi,
+}
+
+impl Default for PoiBuilder {
+    fn default() -> Self {
+        PoiBuilder {
+            poi: Poi::new(
+                "Unnamed POI".to_string(),
+                PoiCategory::Monument,
+                Coordinates { lat: 0.0, lng: 0.0 },
+                DEFAULT_POPULARITY,
+            ),
+        }
+    }
+}
+
+impl PoiBuilder {
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.poi.id = id;
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.poi.name = name.into();
+        self
+    }
+
+    pub fn category(mut self, category: PoiCategory) -> Self {
+        self.poi.category = category;
+        self
+    }
+
+    pub fn coordinates(mut self, coordinates: Coordinates) -> Self {
+        self.poi.coordinates = coordinates;
+        self
+    }
+
+    /// Clamped to 0-100, as in [`Poi::new`]
+    pub fn popularity(mut self, popularity_score: f32) -> Self {
+        self.poi.popularity_score = popularity_score.clamp(0.0, 100.0);
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.poi.description = Some(description.into());
+        self
+    }
+
+    pub fn visit_minutes(mut self, minutes: u32) -> Self {
+        self.poi.estimated_visit_duration_minutes = Some(minutes);
+        self
+    }
+
+    pub fn osm_id(mut self, osm_id: i64) -> Self {
+        self.poi.osm_id = Some(osm_id);
+        self
+    }
+
+    pub fn build(self) -> Poi {
+        self.poi
+    }
+}
+
+/// Builds a [`Route`]. Unless set, the distance is the length of the path,
+/// the duration that distance at the mode's default
+/// [`SpeedModel`](super::SpeedModel), and each waypoint's distance from
+/// start the straight-line distance through the waypoints before it.
+#[derive(Debug, Clone, Default)]
+pub struct RouteBuilder {
+    id: Option<Uuid>,
+    distance_km: Option<f64>,
+    duration_minutes: Option<u32>,
+    mode: TransportMode,
+    duration_estimates: bool,
+    path: Vec<Coordinates>,
+    waypoints: Vec<(Poi, Option<f64>)>,
+    snapped_pois: Vec<SnappedPoi>,
+    amenities: Vec<SnappedPoi>,
+    road_profile: Option<RoadProfile>,
+    elevation_gain_m: Option<f32>,
+    score: f32,
+}
+
+impl RouteBuilder {
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn distance_km(mut self, distance_km: f64) -> Self {
+        self.distance_km = Some(distance_km);
+        self
+    }
+
+    pub fn duration_minutes(mut self, minutes: u32) -> Self {
+        self.duration_minutes = Some(minutes);
+        self
+    }
+
+    /// Mode used for the default duration, and for `duration_estimates`
+    pub fn mode(mut self, mode: TransportMode) -> Self {
+        self.mode = mode;
+        self.duration_estimates = true;
+        self
+    }
+
+    pub fn path(mut self, path: Vec<Coordinates>) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Append a waypoint POI; its order follows the ones before it
+    pub fn waypoint(mut self, poi: Poi) -> Self {
+        self.waypoints.push((poi, None));
+        self
+    }
+
+    /// Append a waypoint POI at a given distance from start
+    pub fn waypoint_at(mut self, poi: Poi, distance_from_start_km: f64) -> Self {
+        self.waypoints.push((poi, Some(distance_from_start_km)));
+        self
+    }
+
+    pub fn snapped_poi(mut self, snapped: SnappedPoi) -> Self {
+        self.snapped_pois.push(snapped);
+        self
+    }
+
+    pub fn amenity(mut self, amenity: SnappedPoi) -> Self {
+        self.amenities.push(amenity);
+        self
+    }
+
+    /// Also sets `step_free` and `surface_split`, as
+    /// [`Route::with_road_profile`] does
+    pub fn road_profile(mut self, profile: RoadProfile) -> Self {
+        self.road_profile = Some(profile);
+        self
+    }
+
+    pub fn elevation_gain_m(mut self, meters: f32) -> Self {
+        self.elevation_gain_m = Some(meters);
+        self
+    }
+
+    pub fn score(mut self, score: f32) -> Self {
+        self.score = score;
+        self
+    }
+
+    pub fn build(self) -> Route {
+        let distance_km = self
+            .distance_km
+            .unwrap_or_else(|| path_length_km(&self.path));
+        let speeds = SpeedModels::default();
+        let duration_minutes = self.duration_minutes.unwrap_or_else(|| {
+            speeds
+                .for_mode(&self.mode)
+                .minutes(distance_km, None)
+                .round() as u32
+        });
+
+        let mut previous = self.path.first().copied();
+        let mut along_km = 0.0;
+        let pois = self
+            .waypoints
+            .into_iter()
+            .enumerate()
+            .map(|(i, (poi, at_km))| {
+                along_km += previous.map_or(0.0, |p| p.distance_to(&poi.coordinates));
+                previous = Some(poi.coordinates);
+                let distance_from_start_km = at_km.unwrap_or(along_km);
+                RoutePoi::new(poi, i as u32 + 1, distance_from_start_km)
+            })
+            .collect();
+
+        let mut route = Route::new(distance_km, duration_minutes, self.path, pois)
+            .with_snapped_pois(self.snapped_pois)
+            .with_road_profile(self.road_profile);
+        if self.duration_estimates {
+            route = route.with_duration_estimates(&self.mode, &speeds);
+        }
+        if let Some(id) = self.id {
+            route.id = id;
+        }
+        route.amenities = self.amenities;
+        route.elevation_gain_m = self.elevation_gain_m;
+        route.score = self.score;
+        route
+    }
+}
+
+fn path_length_km(path: &[Coordinates]) -> f64 {
+    path.windows(2).map(|w| w[0].distance_to(&w[1])).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coord(lat: f64, lng: f64) -> Coordinates {
+        Coordinates::new(lat, lng).unwrap()
+    }
+
+    #[test]
+    fn test_poi_builder_defaults_and_overrides() {
+        let poi = Poi::builder().build();
+        assert_eq!(poi.category, PoiCategory::Monument);
+        assert_eq!(poi.popularity_score, DEFAULT_POPULARITY);
+        assert!(poi.description.is_none());
+
+        let poi = Poi::builder()
+            .name("Louvre")
+            .category(PoiCategory::Museum)
+            .popularity(140.0)
+            .visit_minutes(90)
+            .osm_id(42)
+            .build();
+        assert_eq!(poi.name, "Louvre");
+        assert_eq!(poi.popularity_score, 100.0);
+        assert_eq!(poi.visit_minutes(), 90);
+        assert_eq!(poi.osm_id, Some(42));
+    }
+
+    #[test]
+    fn test_route_builder_derives_distance_duration_and_order() {
+        let start = coord(48.0, 2.0);
+        let north = coord(48.01, 2.0);
+        let east = coord(48.0, 2.015);
+        let route = Route::builder()
+            .path(vec![start, north, east, start])
+            .waypoint(Poi::builder().coordinates(north).build())
+            .waypoint(Poi::builder().coordinates(east).build())
+            .build();
+
+        let expected_km =
+            start.distance_to(&north) + north.distance_to(&east) + east.distance_to(&start);
+        assert!((route.distance_km - expected_km).abs() < 1e-9);
+        let walk_minutes = SpeedModels::default().walking.minutes(expected_km, None);
+        assert_eq!(
+            route.estimated_duration_minutes,
+            walk_minutes.round() as u32
+        );
+        assert!(route.duration_estimates.is_none());
+
+        let orders: Vec<u32> = route.pois.iter().map(|p| p.order_in_route).collect();
+        assert_eq!(orders, vec![1, 2]);
+        assert!((route.pois[0].distance_from_start_km - start.distance_to(&north)).abs() < 1e-9);
+        assert!(
+            (route.pois[1].distance_from_start_km
+                - start.distance_to(&north)
+                - north.distance_to(&east))
+            .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_route_builder_explicit_values_win() {
+        let profile = RoadProfile {
+            paved_fraction: 1.0,
+            busy_road_fraction: 0.0,
+            smooth_fraction: 1.0,
+            steps_count: 0,
+            max_incline_pct: None,
+        };
+        let route = Route::builder()
+            .distance_km(5.0)
+            .duration_minutes(40)
+            .mode(TransportMode::Bike)
+            .waypoint_at(Poi::builder().build(), 2.5)
+            .road_profile(profile)
+            .score(8.0)
+            .build();
+        assert_eq!(route.distance_km, 5.0);
+        assert_eq!(route.estimated_duration_minutes, 40);
+        assert!(route.duration_estimates.is_some());
+        assert_eq!(route.pois[0].distance_from_start_km, 2.5);
+        assert_eq!(route.step_free, Some(true));
+        assert!(route.surface_split.is_some());
+        assert_eq!(route.score, 8.0);
+    }
+}
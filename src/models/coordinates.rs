@@ -1,4 +1,31 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::constants::METERS_PER_DEGREE;
+
+/// Floor for cos(lat) when converting meters to degrees of longitude, so
+/// offsets stay finite near the poles (reached at ~89.4°)
+const MIN_LNG_SCALE: f64 = 0.01;
+
+/// Most decimals a response can ask coordinates to keep (~0.1 m), and
+/// what compact coordinates keep by default
+pub const SERIALIZED_DECIMALS: u32 = 6;
+
+/// Wrap a longitude into [-180, 180)
+pub fn wrap_lng(lng: f64) -> f64 {
+    if (-180.0..180.0).contains(&lng) {
+        lng
+    } else {
+        (lng + 180.0).rem_euclid(360.0) - 180.0
+    }
+}
+
+/// Signed longitude difference from `from` to `to` the short way round, in
+/// [-180, 180). Use this instead of `to - from` so paths crossing the
+/// antimeridian do not appear to span the whole globe.
+pub fn lng_delta(from: f64, to: f64) -> f64 {
+    wrap_lng(to - from)
+}
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct Coordinates {
@@ -40,6 +67,17 @@ impl Coordinates {
         EARTH_RADIUS_KM * c
     }
 
+    /// Point `north_m` meters north and `east_m` meters east of this one
+    /// (equirectangular; fine for the few kilometers routes span). Latitude is
+    /// clamped at the poles and longitude wrapped across the antimeridian.
+    pub fn offset_by(&self, north_m: f64, east_m: f64) -> Coordinates {
+        let lng_scale = self.lat.to_radians().cos().max(MIN_LNG_SCALE);
+        Coordinates {
+            lat: (self.lat + north_m / METERS_PER_DEGREE).clamp(-90.0, 90.0),
+            lng: wrap_lng(self.lng + east_m / (METERS_PER_DEGREE * lng_scale)),
+        }
+    }
+
     /// Round coordinates to specified decimal places for caching
     pub fn round(&self, decimal_places: u32) -> Self {
         let multiplier = 10_f64.powi(decimal_places as i32);
@@ -61,10 +99,14 @@ impl Coordinates {
         }
 
         // Calculate projection parameter t (0 to 1 represents point on segment)
-        // Using dot product in lat/lng space (approximation, but good enough for short segments)
-        let dx = p2.lng - p1.lng;
+        // Dot product in locally scaled lat/lng space (approximation, but good
+        // enough for short segments); longitudes shrink with cos(lat)
+        let lng_scale = p1.lat.to_radians().cos().max(MIN_LNG_SCALE);
+        let dlng = lng_delta(p1.lng, p2.lng);
+        let dx = dlng * lng_scale;
         let dy = p2.lat - p1.lat;
-        let t = ((self.lng - p1.lng) * dx + (self.lat - p1.lat) * dy) / (dx * dx + dy * dy);
+        let t = (lng_delta(p1.lng, self.lng) * lng_scale * dx + (self.lat - p1.lat) * dy)
+            / (dx * dx + dy * dy);
 
         // Clamp t to [0, 1] to stay on the segment
         let t_clamped = t.clamp(0.0, 1.0);
@@ -72,7 +114,7 @@ impl Coordinates {
         // Calculate the closest point on the segment
         let closest = Coordinates {
             lat: p1.lat + t_clamped * dy,
-            lng: p1.lng + t_clamped * dx,
+            lng: wrap_lng(p1.lng + t_clamped * dlng),
         };
 
         (self.distance_to(&closest), t_clamped)
@@ -113,6 +155,82 @@ impl Coordinates {
     }
 }
 
+/// How a response writes coordinates: `{"lat", "lng"}` objects (default)
+/// or compact `[lat, lng]` arrays, optionally rounded. Applied to the
+/// serialized response only, so routes keep full precision in caches and
+/// storage. Query: `?coords=object|compact&precision=N`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CoordinateFormat {
+    pub compact: bool,
+    /// Decimals kept; full precision when `None`. Compact arrays default
+    /// to [`SERIALIZED_DECIMALS`].
+    pub decimals: Option<u32>,
+}
+
+impl CoordinateFormat {
+    /// From the `coords` and `precision` query parameters
+    pub fn parse(coords: Option<&str>, precision: Option<u32>) -> Result<Self, String> {
+        let compact = match coords.map(str::to_lowercase).as_deref() {
+            None | Some("object") => false,
+            Some("compact") => true,
+            Some(other) => {
+                return Err(format!(
+                    "Invalid coords: '{}' (expected object or compact)",
+                    other
+                ))
+            }
+        };
+        if let Some(precision) = precision.filter(|&p| p > SERIALIZED_DECIMALS) {
+            return Err(format!(
+                "Invalid precision: {} (must be between 0 and {})",
+                precision, SERIALIZED_DECIMALS
+            ));
+        }
+        Ok(CoordinateFormat {
+            compact,
+            decimals: precision.or(compact.then_some(SERIALIZED_DECIMALS)),
+        })
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == CoordinateFormat::default()
+    }
+
+    /// Rewrite every serialized [`Coordinates`] (an object with exactly
+    /// numeric `lat` and `lng`) inside `value`
+    pub fn apply(&self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                if let Some((lat, lng)) = as_coordinates(fields) {
+                    let (lat, lng) = match self.decimals {
+                        Some(decimals) => {
+                            let rounded = Coordinates { lat, lng }.round(decimals);
+                            (rounded.lat, rounded.lng)
+                        }
+                        None => (lat, lng),
+                    };
+                    *value = if self.compact {
+                        json!([lat, lng])
+                    } else {
+                        json!({ "lat": lat, "lng": lng })
+                    };
+                } else {
+                    fields.values_mut().for_each(|field| self.apply(field));
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.apply(item)),
+            _ => {}
+        }
+    }
+}
+
+fn as_coordinates(fields: &Map<String, Value>) -> Option<(f64, f64)> {
+    if fields.len() != 2 {
+        return None;
+    }
+    Some((fields.get("lat")?.as_f64()?, fields.get("lng")?.as_f64()?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,6 +260,43 @@ mod tests {
         assert_eq!(rounded.lng, 2.352);
     }
 
+    #[test]
+    fn test_coordinate_format() {
+        let coords = Coordinates::new(48.85661234567, 2.35222987654).unwrap();
+        let response = || json!({"start": coords, "path": [coords], "distance_km": 5.123456789});
+
+        // Exact by default
+        let mut exact = response();
+        CoordinateFormat::parse(None, None)
+            .unwrap()
+            .apply(&mut exact);
+        assert_eq!(exact, response());
+        let back: Coordinates = serde_json::from_value(exact["start"].clone()).unwrap();
+        assert_eq!(back, coords);
+
+        let mut rounded = response();
+        CoordinateFormat::parse(Some("object"), Some(4))
+            .unwrap()
+            .apply(&mut rounded);
+        assert_eq!(rounded["start"], json!({"lat": 48.8566, "lng": 2.3522}));
+
+        let mut compact = response();
+        CoordinateFormat::parse(Some("compact"), None)
+            .unwrap()
+            .apply(&mut compact);
+        assert_eq!(
+            compact,
+            json!({
+                "start": [48.856612, 2.35223],
+                "path": [[48.856612, 2.35223]],
+                "distance_km": 5.123456789
+            })
+        );
+
+        assert!(CoordinateFormat::parse(Some("geojson"), None).is_err());
+        assert!(CoordinateFormat::parse(None, Some(SERIALIZED_DECIMALS + 1)).is_err());
+    }
+
     #[test]
     fn test_distance_to_segment() {
         let p1 = Coordinates::new(48.8566, 2.3522).unwrap();
@@ -162,6 +317,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_wrap_lng_and_delta() {
+        assert_eq!(wrap_lng(179.5), 179.5);
+        assert_eq!(wrap_lng(180.0), -180.0);
+        assert!((wrap_lng(181.0) - -179.0).abs() < 1e-9);
+        assert!((wrap_lng(-540.5) - 179.5).abs() < 1e-9);
+        // Across the antimeridian the short way is 0.2°, not 359.8°
+        assert!((lng_delta(179.9, -179.9) - 0.2).abs() < 1e-9);
+        assert!((lng_delta(-179.9, 179.9) + 0.2).abs() < 1e-9);
+        assert!((lng_delta(2.0, 3.0) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_offset_wraps_and_clamps() {
+        // Fiji: 2 km east of 179.99°E lands just past the antimeridian
+        let fiji = Coordinates::new(-17.0, 179.99).unwrap();
+        let east = fiji.offset_by(0.0, 2000.0);
+        assert!(east.lng < -179.9, "{:?}", east);
+        assert!((fiji.distance_to(&east) - 2.0).abs() < 0.05);
+
+        // Svalbard: offsets stay finite and valid
+        let svalbard = Coordinates::new(89.99, 15.0).unwrap();
+        let north = svalbard.offset_by(5000.0, 5000.0);
+        assert_eq!(north.lat, 90.0);
+        assert!(Coordinates::new(north.lat, north.lng).is_ok());
+    }
+
+    #[test]
+    fn test_segment_distance_across_antimeridian() {
+        let west = Coordinates::new(-17.0, 179.99).unwrap();
+        let east = Coordinates::new(-17.0, -179.99).unwrap();
+        // On the short segment through 180°, not the long one through 0°
+        let on_line = Coordinates::new(-17.0, 180.0).unwrap();
+        let (dist, t) = on_line.distance_to_segment(&west, &east);
+        assert!(dist < 0.01, "dist={dist}");
+        assert!((t - 0.5).abs() < 0.01, "t={t}");
+
+        let path = vec![west, east];
+        let (dist, _, along) = on_line.distance_to_linestring(&path).unwrap();
+        assert!(dist < 0.01);
+        assert!(along < west.distance_to(&east));
+    }
+
     #[test]
     fn test_distance_to_linestring() {
         // Create a simple path: 3 points in a line
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Fine-grained routing costs for walking and cycling. Honoured by the
+/// Valhalla directions backend; Mapbox has no equivalent and ignores them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostingOptions {
+    /// Cycling: keep to paths and quiet streets where possible
+    #[serde(default)]
+    pub avoid_highways: bool,
+    /// Willingness to take ferries, 0 (avoid) to 1 (favor); backend default
+    /// when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_ferries: Option<f32>,
+    /// Walking: cost multiplier for dedicated walkways, below 1 favors them
+    /// and above 1 avoids them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub walkway_factor: Option<f32>,
+}
+
+impl CostingOptions {
+    pub fn is_default(&self) -> bool {
+        *self == CostingOptions::default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self
+            .use_ferries
+            .is_some_and(|use_ferries| !(0.0..=1.0).contains(&use_ferries))
+        {
+            return Err("costing.use_ferries must be between 0 and 1".to_string());
+        }
+        if self
+            .walkway_factor
+            .is_some_and(|factor| factor <= 0.0 || !factor.is_finite())
+        {
+            return Err("costing.walkway_factor must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_costing_defaults_and_validation() {
+        let costing: CostingOptions = serde_json::from_str("{}").unwrap();
+        assert!(costing.is_default());
+        assert!(costing.validate().is_ok());
+
+        let costing: CostingOptions =
+            serde_json::from_str(r#"{"avoid_highways": true, "walkway_factor": 0.5}"#).unwrap();
+        assert!(costing.avoid_highways);
+        assert!(costing.validate().is_ok());
+
+        let ferries = CostingOptions {
+            use_ferries: Some(1.5),
+            ..Default::default()
+        };
+        assert!(ferries.validate().is_err());
+        let walkways = CostingOptions {
+            walkway_factor: Some(0.0),
+            ..Default::default()
+        };
+        assert!(walkways.validate().is_err());
+    }
+}
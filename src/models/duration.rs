@@ -0,0 +1,128 @@
+use crate::models::{SpeedModels, TransportMode};
+use serde::{Deserialize, Serialize};
+
+/// Estimated durations for each travel profile on the same route geometry,
+/// so clients can switch profiles without regenerating the route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DurationEstimates {
+    pub walking_minutes: u32,
+    pub running_minutes: u32,
+    /// `None` when the geometry may use paths closed to bikes (walk routes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cycling_minutes: Option<u32>,
+}
+
+impl DurationEstimates {
+    /// Estimate per-profile durations from the per-mode speed models,
+    /// slowed by `elevation_gain_m` when it is known.
+    /// The backend's own estimate (`backend_minutes`) is kept for the route's mode.
+    /// Cycling times only ever come from the backend, since bike access varies by path.
+    pub fn from_speed_model(
+        distance_km: f64,
+        elevation_gain_m: Option<f32>,
+        mode: &TransportMode,
+        backend_minutes: u32,
+        speeds: &SpeedModels,
+    ) -> Self {
+        let walking_minutes = speeds
+            .walking
+            .minutes(distance_km, elevation_gain_m)
+            .round() as u32;
+        let running_minutes = speeds
+            .running
+            .minutes(distance_km, elevation_gain_m)
+            .round() as u32;
+
+        if mode.is_cycling() {
+            DurationEstimates {
+                walking_minutes,
+                running_minutes,
+                cycling_minutes: Some(backend_minutes),
+            }
+        } else {
+            DurationEstimates {
+                walking_minutes: backend_minutes,
+                running_minutes,
+                cycling_minutes: None,
+            }
+        }
+    }
+
+    pub fn with_cycling_minutes(mut self, cycling_minutes: Option<u32>) -> Self {
+        self.cycling_minutes = cycling_minutes;
+        self
+    }
+}
+
+/// Split of a route's `estimated_duration_minutes` when visit time is
+/// included (`include_visit_time`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeBreakdown {
+    /// Moving time along the path (walking, running or riding)
+    pub travel_minutes: u32,
+    /// Time spent at the route's waypoint POIs
+    pub visit_minutes: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimates(
+        distance_km: f64,
+        mode: &TransportMode,
+        backend_minutes: u32,
+    ) -> DurationEstimates {
+        DurationEstimates::from_speed_model(
+            distance_km,
+            None,
+            mode,
+            backend_minutes,
+            &SpeedModels::default(),
+        )
+    }
+
+    #[test]
+    fn test_walk_route_keeps_backend_walking_time() {
+        let estimates = estimates(10.0, &TransportMode::Walk, 130);
+        assert_eq!(estimates.walking_minutes, 130);
+        assert_eq!(estimates.running_minutes, 60);
+        assert_eq!(estimates.cycling_minutes, None);
+    }
+
+    #[test]
+    fn test_bike_route_derives_foot_profiles() {
+        let estimates = estimates(10.0, &TransportMode::Bike, 35);
+        assert_eq!(estimates.cycling_minutes, Some(35));
+        assert_eq!(estimates.walking_minutes, 120);
+        assert_eq!(estimates.running_minutes, 60);
+    }
+
+    #[test]
+    fn test_derived_profiles_slow_down_on_climbs() {
+        let speeds = SpeedModels::default();
+        let hilly = DurationEstimates::from_speed_model(
+            10.0,
+            Some(200.0),
+            &TransportMode::Bike,
+            45,
+            &speeds,
+        );
+        assert_eq!(
+            hilly.walking_minutes,
+            (120.0 + 2.0 * speeds.walking.climb_min_per_100m) as u32
+        );
+        assert_eq!(
+            hilly.running_minutes,
+            (60.0 + 2.0 * speeds.running.climb_min_per_100m) as u32
+        );
+        assert_eq!(hilly.cycling_minutes, Some(45));
+    }
+
+    #[test]
+    fn test_cycling_omitted_from_json_when_unknown() {
+        let estimates = estimates(5.0, &TransportMode::Walk, 60);
+        let json = serde_json::to_value(estimates).unwrap();
+        assert!(json.get("cycling_minutes").is_none());
+    }
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::constants::{STORED_PATH_POLYLINE_PRECISION, STORED_PATH_TOLERANCE_M};
+use crate::models::{polyline, Coordinates, Route, RoutePoi, SnappedPoi, TransportMode};
+use crate::services::path_preview::{path_preview, simplify_path};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvaluatedRoute {
     pub id: Uuid,
@@ -22,11 +26,183 @@ pub struct EvaluatedRoute {
     pub system_score: f32,
     pub poi_density_context: Option<String>,
     pub scoring_strategy: String,
+    /// Simplified path for thumbnails (absent for routes stored before previews)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<PathPreview>,
+    /// Stored geometry; only loaded by the geometry endpoint and recompute
+    /// jobs, never part of list responses
+    #[serde(skip)]
+    pub geometry: Option<StoredGeometry>,
     pub created_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ratings: Option<Vec<RouteRating>>,
 }
 
+/// Thumbnail-sized path: a few dozen points at most
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathPreview {
+    /// `[min_lng, min_lat, max_lng, max_lat]` of the full path
+    pub bbox: [f64; 4],
+    /// Encoded polyline (precision 5) of the simplified path
+    pub polyline: String,
+    /// Points in `polyline`
+    pub points: usize,
+}
+
+/// Path and POIs kept with an evaluated route (`evaluated_routes.path`), so it
+/// can be re-inspected and its metrics recomputed when the metric code changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredGeometry {
+    /// Encoded polyline (precision 6) of the path, simplified to within
+    /// `STORED_PATH_TOLERANCE_M`
+    pub polyline: String,
+    /// Points in `polyline`
+    pub points: usize,
+    #[serde(default)]
+    pub pois: Vec<RoutePoi>,
+    #[serde(default)]
+    pub snapped_pois: Vec<SnappedPoi>,
+}
+
+impl StoredGeometry {
+    pub fn from_route(route: &Route) -> Self {
+        let path = simplify_path(&route.path, STORED_PATH_TOLERANCE_M);
+        StoredGeometry {
+            polyline: polyline::encode(&path, STORED_PATH_POLYLINE_PRECISION),
+            points: path.len(),
+            pois: route.pois.clone(),
+            snapped_pois: route.snapped_pois.clone(),
+        }
+    }
+
+    /// The stored path, decoded
+    pub fn path(&self) -> Result<Vec<Coordinates>, String> {
+        polyline::decode(&self.polyline, STORED_PATH_POLYLINE_PRECISION)?
+            .into_iter()
+            .map(|[lng, lat]| Coordinates::new(lat, lng))
+            .collect()
+    }
+
+    /// Rebuild enough of the original route (`id`, `distance_km` long) to
+    /// recompute its metrics
+    pub fn to_route(&self, id: Uuid, distance_km: f64) -> Result<Route, String> {
+        let mut route = Route::new(distance_km, 0, self.path()?, self.pois.clone());
+        route.id = id;
+        route.snapped_pois = self.snapped_pois.clone();
+        Ok(route)
+    }
+}
+
+/// Position in the evaluated-route listing (`created_at DESC, id DESC`),
+/// carried by list cursors
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationCursor {
+    pub created_at: String,
+    pub id: Uuid,
+}
+
+impl EvaluatedRoute {
+    /// Snapshot a generated route for storage in `evaluated_routes`
+    pub fn from_route(
+        route: &Route,
+        start: &Coordinates,
+        target_distance_km: f64,
+        mode: &TransportMode,
+        scoring_strategy: &str,
+    ) -> Self {
+        let metrics = route.metrics.as_ref();
+        EvaluatedRoute {
+            id: route.id,
+            start_lat: start.lat,
+            start_lng: start.lng,
+            target_distance_km,
+            transport_mode: mode.to_string(),
+            actual_distance_km: route.distance_km,
+            duration_minutes: route.estimated_duration_minutes as i32,
+            poi_names: route.pois.iter().map(|p| p.poi.name.clone()).collect(),
+            poi_count: route.pois.len() as i32,
+            snapped_poi_count: route.snapped_pois.len() as i32,
+            circularity: metrics.map(|m| m.circularity),
+            convexity: metrics.map(|m| m.convexity),
+            path_overlap_pct: metrics.map(|m| m.path_overlap_pct),
+            poi_density_per_km: metrics.map(|m| m.poi_density_per_km),
+            category_entropy: metrics.map(|m| m.category_entropy),
+            landmark_coverage: metrics.map(|m| m.landmark_coverage),
+            system_score: route.score,
+            poi_density_context: metrics.map(|m| m.poi_density_context.to_string()),
+            scoring_strategy: scoring_strategy.to_string(),
+            preview: path_preview(&route.path),
+            geometry: Some(StoredGeometry::from_route(route)),
+            created_at: None,
+            ratings: None,
+        }
+    }
+
+    /// Relative distance error vs. the requested target (0.1 = 10% off)
+    pub fn distance_error(&self) -> f32 {
+        if self.target_distance_km <= 0.0 {
+            return 0.0;
+        }
+        ((self.actual_distance_km - self.target_distance_km).abs() / self.target_distance_km) as f32
+    }
+}
+
+/// Primary vs. candidate outcome of one shadowed request.
+/// Deltas are candidate minus primary; `None` when either side lacks the metric.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShadowComparison {
+    pub primary_route_id: Uuid,
+    pub candidate_route_id: Option<Uuid>,
+    pub primary_strategy: String,
+    pub candidate_strategy: String,
+    pub candidate_error: Option<String>,
+    pub score_delta: Option<f32>,
+    pub distance_error_delta: Option<f32>,
+    pub circularity_delta: Option<f32>,
+    pub convexity_delta: Option<f32>,
+    pub path_overlap_pct_delta: Option<f32>,
+    pub poi_density_per_km_delta: Option<f32>,
+    pub category_entropy_delta: Option<f32>,
+    pub landmark_coverage_delta: Option<f32>,
+    pub latency_ms_delta: Option<i32>,
+}
+
+impl ShadowComparison {
+    /// Compare the best primary route with the candidate's best route (or its error)
+    pub fn new(
+        primary: &EvaluatedRoute,
+        candidate: Result<&EvaluatedRoute, String>,
+        candidate_strategy: &str,
+        latency_ms_delta: Option<i32>,
+    ) -> Self {
+        let (candidate, candidate_error) = match candidate {
+            Ok(route) => (Some(route), None),
+            Err(e) => (None, Some(e)),
+        };
+        let delta = |metric: fn(&EvaluatedRoute) -> Option<f32>| {
+            let c = candidate.and_then(metric)?;
+            Some(c - metric(primary)?)
+        };
+
+        ShadowComparison {
+            primary_route_id: primary.id,
+            candidate_route_id: candidate.map(|c| c.id),
+            primary_strategy: primary.scoring_strategy.clone(),
+            candidate_strategy: candidate_strategy.to_string(),
+            candidate_error,
+            score_delta: delta(|r| Some(r.system_score)),
+            distance_error_delta: delta(|r| Some(r.distance_error())),
+            circularity_delta: delta(|r| r.circularity),
+            convexity_delta: delta(|r| r.convexity),
+            path_overlap_pct_delta: delta(|r| r.path_overlap_pct),
+            poi_density_per_km_delta: delta(|r| r.poi_density_per_km),
+            category_entropy_delta: delta(|r| r.category_entropy),
+            landmark_coverage_delta: delta(|r| r.landmark_coverage),
+            latency_ms_delta: candidate.and(latency_ms_delta),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RouteRating {
     pub id: Uuid,
@@ -92,5 +268,167 @@ pub struct MetricCorrelation {
 pub struct EvaluationStats {
     pub total_routes: i64,
     pub total_ratings: i64,
+    /// Metric code version the correlations use (absent: metrics stored at
+    /// generation time)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics_version: Option<i32>,
     pub correlations: Vec<MetricCorrelation>,
+    pub agreement: RatingAgreement,
+}
+
+/// How consistently human raters score the same routes
+#[derive(Debug, Clone, Serialize)]
+pub struct RatingAgreement {
+    /// Routes with at least one rating
+    pub rated_routes: usize,
+    /// Routes rated at least twice; only these inform agreement
+    pub multiply_rated_routes: usize,
+    /// Distinct `rater_id`s
+    pub raters: usize,
+    /// Ratings submitted without a `rater_id`
+    pub anonymous_ratings: usize,
+    /// Mean of per-route overall rating variances
+    pub mean_rating_variance: Option<f64>,
+    /// Agreement on each rating dimension, overall first
+    pub dimensions: Vec<DimensionAgreement>,
+    /// Overall-rating alpha reaches the usual 0.667 bar for drawing
+    /// tentative conclusions from the ratings
+    pub reliable: bool,
+    /// Multiply rated routes with the most spread-out overall ratings
+    pub most_disputed: Vec<RouteRatingSpread>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DimensionAgreement {
+    /// `overall`, `shape`, `scenicness` or `variety`
+    pub dimension: String,
+    /// Krippendorff's alpha, interval metric (1 = perfect, 0 = chance)
+    pub krippendorff_alpha: Option<f64>,
+    /// Quadratic-weighted kappa over pooled rating pairs (Cohen's kappa
+    /// generalized to varying raters)
+    pub weighted_kappa: Option<f64>,
+    /// Share of rating pairs on the same route that are identical
+    pub exact_agreement: Option<f64>,
+    /// Share of rating pairs on the same route at most one point apart
+    pub within_one_agreement: Option<f64>,
+    /// Ratings on multiply rated routes that entered the computation
+    pub pairable_ratings: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteRatingSpread {
+    pub route_id: Uuid,
+    pub rating_count: usize,
+    /// Distinct raters, counting each anonymous rating as its own rater
+    pub rater_count: usize,
+    pub mean_rating: f64,
+    /// Sample variance of the overall rating
+    pub rating_variance: f64,
+}
+
+/// An evaluated route suggested for human rating, with why it was picked
+#[derive(Debug, Clone, Serialize)]
+pub struct RatingCandidate {
+    pub route_id: Uuid,
+    pub transport_mode: String,
+    pub system_score: f32,
+    /// Ratings the route already has
+    pub rating_count: i64,
+    /// Mean overall rating of the nearest rated routes in metric space
+    pub predicted_rating: Option<f64>,
+    /// 0-1, how sparsely rated the route's metric region is
+    pub novelty: f64,
+    /// 0-1, gap between `predicted_rating` and the system score's rating
+    pub disagreement: f64,
+    /// Blend of novelty and disagreement the candidates are ranked by
+    pub priority: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluated(score: f32, actual_km: f64, circularity: Option<f32>) -> EvaluatedRoute {
+        EvaluatedRoute {
+            id: Uuid::new_v4(),
+            start_lat: 48.0,
+            start_lng: 2.0,
+            target_distance_km: 5.0,
+            transport_mode: "walk".to_string(),
+            actual_distance_km: actual_km,
+            duration_minutes: 60,
+            poi_names: vec![],
+            poi_count: 0,
+            snapped_poi_count: 0,
+            circularity,
+            convexity: None,
+            path_overlap_pct: None,
+            poi_density_per_km: None,
+            category_entropy: None,
+            landmark_coverage: None,
+            system_score: score,
+            poi_density_context: None,
+            scoring_strategy: "advanced/v1".to_string(),
+            preview: None,
+            geometry: None,
+            created_at: None,
+            ratings: None,
+        }
+    }
+
+    #[test]
+    fn test_shadow_comparison_deltas_are_candidate_minus_primary() {
+        let primary = evaluated(6.0, 5.5, Some(0.5));
+        let candidate = evaluated(7.0, 5.0, None);
+        let cmp = ShadowComparison::new(&primary, Ok(&candidate), "advanced/v2", Some(-20));
+
+        assert_eq!(cmp.candidate_route_id, Some(candidate.id));
+        assert_eq!(cmp.candidate_strategy, "advanced/v2");
+        assert_eq!(cmp.score_delta, Some(1.0));
+        assert!((cmp.distance_error_delta.unwrap() + 0.1).abs() < 1e-6);
+        // Candidate lacks the metric
+        assert_eq!(cmp.circularity_delta, None);
+        assert_eq!(cmp.latency_ms_delta, Some(-20));
+    }
+
+    #[test]
+    fn test_stored_geometry_round_trip() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let path: Vec<_> = (0..50)
+            .map(|i| {
+                let angle = i as f64 / 49.0 * std::f64::consts::TAU;
+                Coordinates::new(
+                    start.lat + 0.01 * angle.sin(),
+                    start.lng + 0.01 * angle.cos(),
+                )
+                .unwrap()
+            })
+            .collect();
+        let route = Route::new(4.2, 50, path.clone(), vec![]);
+
+        let evaluated = EvaluatedRoute::from_route(&route, &start, 4.0, &TransportMode::Walk, "x");
+        let geometry = evaluated.geometry.as_ref().unwrap();
+        assert_eq!(geometry.points, 50);
+        let rebuilt = geometry.to_route(evaluated.id, 4.2).unwrap();
+        assert_eq!(rebuilt.id, route.id);
+        for (a, b) in rebuilt.path.iter().zip(&path) {
+            assert!((a.lat - b.lat).abs() < 1e-6 && (a.lng - b.lng).abs() < 1e-6);
+        }
+        // Not part of API responses
+        assert!(serde_json::to_value(&evaluated)
+            .unwrap()
+            .get("geometry")
+            .is_none());
+    }
+
+    #[test]
+    fn test_shadow_comparison_records_candidate_failure() {
+        let primary = evaluated(6.0, 5.0, Some(0.5));
+        let cmp =
+            ShadowComparison::new(&primary, Err("no route".to_string()), "simple/v1", Some(15));
+        assert_eq!(cmp.candidate_route_id, None);
+        assert_eq!(cmp.candidate_error.as_deref(), Some("no route"));
+        assert_eq!(cmp.score_delta, None);
+        assert_eq!(cmp.latency_ms_delta, None);
+    }
 }
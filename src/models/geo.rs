@@ -1,6 +1,16 @@
+use crate::constants::METERS_PER_DEGREE;
+use crate::models::coordinates::{lng_delta, wrap_lng};
 use crate::models::Coordinates;
+use serde::{Deserialize, Serialize};
 
 /// Axis-aligned bounding box in geographic coordinates.
+///
+/// Latitudes are clamped to [-90, 90]. Longitudes are continuous rather than
+/// wrapped: a box straddling the antimeridian has `min_lng < -180` or
+/// `max_lng > 180`, and a box reaching a pole spans all of [-180, 180].
+/// Queries against stored coordinates go through
+/// [`split_at_antimeridian`](Self::split_at_antimeridian).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct BoundingBox {
     pub min_lat: f64,
     pub max_lat: f64,
@@ -9,52 +19,134 @@ pub struct BoundingBox {
 }
 
 impl BoundingBox {
+    /// Parse `min_lng,min_lat,max_lng,max_lat` (the GeoJSON/OSM order), as
+    /// query parameters and CLI flags give it
+    pub fn parse(bbox: &str) -> Result<BoundingBox, String> {
+        let invalid = || {
+            format!(
+                "Invalid bbox '{}' (expected min_lng,min_lat,max_lng,max_lat)",
+                bbox
+            )
+        };
+        let values = bbox
+            .split(',')
+            .map(|v| v.trim().parse::<f64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<f64>, String>>()?;
+        let [min_lng, min_lat, max_lng, max_lat] = values[..] else {
+            return Err(invalid());
+        };
+        if !values.iter().all(|v| v.is_finite())
+            || !(-90.0..=90.0).contains(&min_lat)
+            || !(-90.0..=90.0).contains(&max_lat)
+            || !(-180.0..=180.0).contains(&min_lng)
+            || !(-180.0..=180.0).contains(&max_lng)
+        {
+            return Err(invalid());
+        }
+        if min_lat >= max_lat || min_lng >= max_lng {
+            return Err(
+                "bbox must have min < max on both axes (boxes across the antimeridian are not supported)"
+                    .to_string(),
+            );
+        }
+        Ok(BoundingBox {
+            min_lat,
+            max_lat,
+            min_lng,
+            max_lng,
+        })
+    }
+
+    /// Whether the box straddles the antimeridian. Besides continuous
+    /// longitudes beyond ±180, `min_lng > max_lng` (the GeoJSON convention,
+    /// e.g. 170..-170) also counts.
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.min_lng < -180.0 || self.max_lng > 180.0 || self.min_lng > self.max_lng
+    }
+
+    /// The box as one or two boxes with longitudes inside [-180, 180], for
+    /// queries against stored coordinates. A box crossing the antimeridian
+    /// becomes its eastern part (up to 180) and its western part (from -180).
+    pub fn split_at_antimeridian(&self) -> Vec<BoundingBox> {
+        let part = |min_lng: f64, max_lng: f64| BoundingBox {
+            min_lng,
+            max_lng,
+            ..*self
+        };
+        if !self.crosses_antimeridian() {
+            return vec![*self];
+        }
+        let (min_lng, max_lng) = if self.min_lng > self.max_lng {
+            (self.min_lng, self.max_lng + 360.0)
+        } else {
+            (self.min_lng, self.max_lng)
+        };
+        if max_lng - min_lng >= 360.0 {
+            return vec![part(-180.0, 180.0)];
+        }
+        let west_edge = wrap_lng(min_lng);
+        let east_edge = wrap_lng(max_lng);
+        vec![part(west_edge, 180.0), part(-180.0, east_edge)]
+    }
+
     /// Compute a bounding box around a center point with a radius in meters.
     pub fn from_center_radius(center: &Coordinates, radius_m: f64) -> Self {
-        let lat_delta = radius_m / 111_000.0;
-        let lng_delta = if center.lat.abs() > 85.0 {
-            lat_delta
+        Self::around(center.lat, center.lat, center.lng, center.lng, radius_m)
+    }
+
+    /// Box around `[min_lat, max_lat] x [min_lng, max_lng]` grown by `buffer_m`.
+    /// The longitude buffer is sized at the most poleward latitude of the box,
+    /// where a meter spans the most degrees; if that reaches a pole, or the
+    /// buffer covers every longitude, the box spans them all.
+    fn around(min_lat: f64, max_lat: f64, min_lng: f64, max_lng: f64, buffer_m: f64) -> Self {
+        let lat_buffer = buffer_m / METERS_PER_DEGREE;
+        let min_lat = (min_lat - lat_buffer).max(-90.0);
+        let max_lat = (max_lat + lat_buffer).min(90.0);
+
+        let poleward_lat = min_lat.abs().max(max_lat.abs());
+        let lng_buffer = if poleward_lat >= 90.0 {
+            f64::INFINITY
         } else {
-            radius_m / (111_000.0 * center.lat.to_radians().cos())
+            buffer_m / (METERS_PER_DEGREE * poleward_lat.to_radians().cos())
         };
+        if max_lng - min_lng + 2.0 * lng_buffer >= 360.0 {
+            return BoundingBox {
+                min_lat,
+                max_lat,
+                min_lng: -180.0,
+                max_lng: 180.0,
+            };
+        }
 
         BoundingBox {
-            min_lat: center.lat - lat_delta,
-            max_lat: center.lat + lat_delta,
-            min_lng: center.lng - lng_delta,
-            max_lng: center.lng + lng_delta,
+            min_lat,
+            max_lat,
+            min_lng: min_lng - lng_buffer,
+            max_lng: max_lng + lng_buffer,
         }
     }
 
     /// Compute a bounding box that encloses a path with a buffer in meters.
+    /// Longitudes are followed step by step from the first point, so a path
+    /// crossing the antimeridian gets a narrow box rather than a global band.
     pub fn from_path_with_buffer(path: &[Coordinates], buffer_m: f64) -> Self {
         let mut min_lat = f64::INFINITY;
         let mut max_lat = f64::NEG_INFINITY;
         let mut min_lng = f64::INFINITY;
         let mut max_lng = f64::NEG_INFINITY;
 
+        let mut lng = path.first().map_or(0.0, |c| c.lng);
+        let mut prev_lng = lng;
         for coord in path {
+            lng += lng_delta(prev_lng, coord.lng);
+            prev_lng = coord.lng;
             min_lat = min_lat.min(coord.lat);
             max_lat = max_lat.max(coord.lat);
-            min_lng = min_lng.min(coord.lng);
-            max_lng = max_lng.max(coord.lng);
+            min_lng = min_lng.min(lng);
+            max_lng = max_lng.max(lng);
         }
 
-        let lat_buffer = buffer_m / 111_000.0;
-        let mid_lat = (min_lat + max_lat) / 2.0;
-
-        let lng_buffer = if mid_lat.abs() > 85.0 {
-            lat_buffer
-        } else {
-            buffer_m / (111_000.0 * mid_lat.to_radians().cos())
-        };
-
-        BoundingBox {
-            min_lat: min_lat - lat_buffer,
-            max_lat: max_lat + lat_buffer,
-            min_lng: min_lng - lng_buffer,
-            max_lng: max_lng + lng_buffer,
-        }
+        Self::around(min_lat, max_lat, min_lng, max_lng, buffer_m)
     }
 }
 
@@ -66,6 +158,17 @@ mod tests {
         Coordinates::new(lat, lng).unwrap()
     }
 
+    #[test]
+    fn test_parse() {
+        let bbox = BoundingBox::parse("2.35,48.85,2.36,48.86").unwrap();
+        assert_eq!((bbox.min_lng, bbox.min_lat), (2.35, 48.85));
+        assert_eq!((bbox.max_lng, bbox.max_lat), (2.36, 48.86));
+        assert!(BoundingBox::parse("2.35,48.85,2.36").is_err());
+        assert!(BoundingBox::parse("2.35,48.85,2.36,abc").is_err());
+        assert!(BoundingBox::parse("2.36,48.85,2.35,48.86").is_err());
+        assert!(BoundingBox::parse("2.35,95,2.36,96").is_err());
+    }
+
     #[test]
     fn path_bbox_single_segment() {
         let path = vec![c(48.85, 2.35), c(48.86, 2.36)];
@@ -81,7 +184,7 @@ mod tests {
         let path = vec![c(48.85, 2.35), c(48.86, 2.36)];
         let buffer_m = 1000.0;
         let bbox = BoundingBox::from_path_with_buffer(&path, buffer_m);
-        let lat_buffer = buffer_m / 111_000.0;
+        let lat_buffer = buffer_m / METERS_PER_DEGREE;
         assert!((bbox.min_lat - (48.85 - lat_buffer)).abs() < 1e-10);
         assert!((bbox.max_lat - (48.86 + lat_buffer)).abs() < 1e-10);
     }
@@ -89,7 +192,7 @@ mod tests {
     #[test]
     fn path_bbox_longitude_buffer_widens_at_higher_latitude() {
         let buffer_m = 1000.0;
-        let lat_buffer = buffer_m / 111_000.0;
+        let lat_buffer = buffer_m / METERS_PER_DEGREE;
 
         let path_eq = vec![c(1.0, 10.0), c(1.0, 10.0)];
         let bbox_eq = BoundingBox::from_path_with_buffer(&path_eq, buffer_m);
@@ -108,15 +211,39 @@ mod tests {
     }
 
     #[test]
-    fn path_bbox_near_poles_fallback() {
+    fn path_bbox_near_poles_covers_buffer() {
+        // Svalbard-like latitude: a degree of longitude is only ~7.7 km, so
+        // the longitude buffer must be far wider than the latitude buffer
         let path = vec![c(86.0, 10.0), c(86.0, 10.0)];
         let bbox = BoundingBox::from_path_with_buffer(&path, 1000.0);
-        let lat_buffer = 1000.0 / 111_000.0;
+        let lat_buffer = 1000.0 / METERS_PER_DEGREE;
         let lng_buffer = bbox.max_lng - 10.0;
-        assert!(
-            (lng_buffer - lat_buffer).abs() < 1e-10,
-            "lng_buffer={lng_buffer}, lat_buffer={lat_buffer}"
-        );
+        assert!(lng_buffer > 10.0 * lat_buffer, "lng_buffer={lng_buffer}");
+        let east_edge = c(86.0, bbox.max_lng);
+        assert!(c(86.0, 10.0).distance_to(&east_edge) >= 1.0);
+    }
+
+    #[test]
+    fn bbox_reaching_pole_spans_all_longitudes() {
+        let bbox = BoundingBox::from_center_radius(&c(89.995, 15.0), 2000.0);
+        assert_eq!(bbox.max_lat, 90.0);
+        assert_eq!((bbox.min_lng, bbox.max_lng), (-180.0, 180.0));
+
+        let bbox = BoundingBox::from_path_with_buffer(&[c(-89.99, 0.0), c(-89.98, 90.0)], 2000.0);
+        assert_eq!(bbox.min_lat, -90.0);
+        assert_eq!((bbox.min_lng, bbox.max_lng), (-180.0, 180.0));
+    }
+
+    #[test]
+    fn bbox_across_antimeridian_stays_narrow() {
+        // Fiji: the box extends past 180 instead of wrapping round the globe
+        let bbox = BoundingBox::from_center_radius(&c(-17.0, 179.99), 5000.0);
+        assert!(bbox.min_lng > 179.0 && bbox.max_lng > 180.0, "{:?}", bbox);
+
+        let path = vec![c(-17.0, 179.98), c(-17.0, -179.99), c(-17.01, -179.97)];
+        let bbox = BoundingBox::from_path_with_buffer(&path, 0.0);
+        assert!((bbox.min_lng - 179.98).abs() < 1e-9, "{:?}", bbox);
+        assert!((bbox.max_lng - 180.03).abs() < 1e-9, "{:?}", bbox);
     }
 
     #[test]
@@ -133,7 +260,7 @@ mod tests {
     fn center_radius_basic() {
         let center = c(48.8566, 2.3522);
         let bbox = BoundingBox::from_center_radius(&center, 1000.0);
-        let lat_delta = 1000.0 / 111_000.0;
+        let lat_delta = 1000.0 / METERS_PER_DEGREE;
         assert!((bbox.min_lat - (48.8566 - lat_delta)).abs() < 1e-10);
         assert!((bbox.max_lat - (48.8566 + lat_delta)).abs() < 1e-10);
         assert!(bbox.min_lng < 2.3522);
@@ -144,11 +271,95 @@ mod tests {
     fn center_radius_near_poles() {
         let center = c(86.0, 10.0);
         let bbox = BoundingBox::from_center_radius(&center, 1000.0);
-        let lat_delta = 1000.0 / 111_000.0;
-        let lng_delta = bbox.max_lng - 10.0;
-        assert!(
-            (lng_delta - lat_delta).abs() < 1e-10,
-            "near-pole: lng_delta={lng_delta}, lat_delta={lat_delta}"
+        // Every point within the radius is inside the box
+        for bearing in 0..36 {
+            let angle = (bearing as f64 * 10.0).to_radians();
+            let p = center.offset_by(999.0 * angle.cos(), 999.0 * angle.sin());
+            assert!(
+                (bbox.min_lng..=bbox.max_lng).contains(&p.lng)
+                    && (bbox.min_lat..=bbox.max_lat).contains(&p.lat),
+                "{:?} outside {:?}",
+                p,
+                bbox
+            );
+        }
+    }
+
+    fn lng_ranges(bbox: &BoundingBox) -> Vec<(f64, f64)> {
+        bbox.split_at_antimeridian()
+            .iter()
+            .map(|b| {
+                (
+                    (b.min_lng * 1e6).round() / 1e6,
+                    (b.max_lng * 1e6).round() / 1e6,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_leaves_ordinary_boxes_alone() {
+        let bbox = BoundingBox::from_center_radius(&c(48.8566, 2.3522), 5000.0);
+        assert!(!bbox.crosses_antimeridian());
+        assert_eq!(bbox.split_at_antimeridian(), vec![bbox]);
+    }
+
+    #[test]
+    fn split_at_antimeridian_both_sides() {
+        let east_overflow = BoundingBox {
+            min_lat: -17.1,
+            max_lat: -16.9,
+            min_lng: 179.9,
+            max_lng: 180.2,
+        };
+        assert!(east_overflow.crosses_antimeridian());
+        assert_eq!(
+            lng_ranges(&east_overflow),
+            vec![(179.9, 180.0), (-180.0, -179.8)]
+        );
+        let parts = east_overflow.split_at_antimeridian();
+        assert!(parts
+            .iter()
+            .all(|p| p.min_lat == -17.1 && p.max_lat == -16.9));
+
+        let west_overflow = BoundingBox {
+            min_lng: -180.3,
+            max_lng: -179.5,
+            ..east_overflow
+        };
+        assert_eq!(
+            lng_ranges(&west_overflow),
+            vec![(179.7, 180.0), (-180.0, -179.5)]
         );
+
+        // GeoJSON-style min > max
+        let geojson = BoundingBox {
+            min_lng: 179.9,
+            max_lng: -179.8,
+            ..east_overflow
+        };
+        assert_eq!(lng_ranges(&geojson), lng_ranges(&east_overflow));
+
+        let everything = BoundingBox {
+            min_lng: -200.0,
+            max_lng: 170.0,
+            ..east_overflow
+        };
+        assert_eq!(lng_ranges(&everything), vec![(-180.0, 180.0)]);
+    }
+
+    #[test]
+    fn split_center_radius_box_covers_both_sides() {
+        let bbox = BoundingBox::from_center_radius(&c(-17.0, 179.99), 5000.0);
+        let parts = bbox.split_at_antimeridian();
+        assert_eq!(parts.len(), 2);
+        let contains = |p: &Coordinates| {
+            parts.iter().any(|b| {
+                (b.min_lat..=b.max_lat).contains(&p.lat) && (b.min_lng..=b.max_lng).contains(&p.lng)
+            })
+        };
+        assert!(contains(&c(-17.0, 179.98)));
+        assert!(contains(&c(-17.0, -179.98)));
+        assert!(!contains(&c(-17.0, 0.0)));
     }
 }
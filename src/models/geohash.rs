@@ -0,0 +1,161 @@
+//! Geohash encoding and cell coverage.
+//!
+//! Matches PostGIS `ST_GeoHash`, so prefixes computed here can be compared with
+//! values computed in SQL (partition keys, indexed geohash columns).
+
+use crate::models::{BoundingBox, Coordinates};
+
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Geohash of `point` with `precision` characters.
+pub fn encode(point: &Coordinates, precision: usize) -> String {
+    let (mut lat_range, mut lng_range) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut hash = String::with_capacity(precision);
+    let mut bits = 0u8;
+    let mut bit_count = 0;
+    // Bits alternate, starting with longitude
+    let mut even = true;
+
+    while hash.len() < precision {
+        let (range, value) = if even {
+            (&mut lng_range, point.lng)
+        } else {
+            (&mut lat_range, point.lat)
+        };
+        let mid = (range.0 + range.1) / 2.0;
+        bits <<= 1;
+        if value >= mid {
+            bits |= 1;
+            range.0 = mid;
+        } else {
+            range.1 = mid;
+        }
+        even = !even;
+
+        bit_count += 1;
+        if bit_count == 5 {
+            hash.push(BASE32[bits as usize] as char);
+            bits = 0;
+            bit_count = 0;
+        }
+    }
+    hash
+}
+
+/// Cell size in degrees (lat, lng) at `precision` characters.
+pub fn cell_size_deg(precision: usize) -> (f64, f64) {
+    let total_bits = 5 * precision as i32;
+    let lng_bits = (total_bits + 1) / 2;
+    let lat_bits = total_bits / 2;
+    (180.0 / 2f64.powi(lat_bits), 360.0 / 2f64.powi(lng_bits))
+}
+
+/// Geohash cells of `precision` characters that intersect `bbox`, or `None`
+/// when more than `max_cells` would be needed (callers then skip the geohash
+/// filter). A box crossing the antimeridian is covered on both sides.
+pub fn covering_cells(
+    bbox: &BoundingBox,
+    precision: usize,
+    max_cells: usize,
+) -> Option<Vec<String>> {
+    if bbox.crosses_antimeridian() {
+        let mut cells = Vec::new();
+        for part in bbox.split_at_antimeridian() {
+            cells.extend(covering_cells(&part, precision, max_cells)?);
+        }
+        cells.sort();
+        cells.dedup();
+        return (cells.len() <= max_cells).then_some(cells);
+    }
+    let (cell_lat, cell_lng) = cell_size_deg(precision);
+    let min_lat = bbox.min_lat.max(-90.0);
+    let max_lat = bbox.max_lat.min(90.0);
+
+    // Cell indices; inclusive so points on a shared edge are covered either way
+    let row = |lat: f64| ((lat + 90.0) / cell_lat).floor() as i64;
+    let col = |lng: f64| ((lng + 180.0) / cell_lng).floor() as i64;
+    let max_row = (180.0 / cell_lat) as i64 - 1;
+    let max_col = (360.0 / cell_lng) as i64 - 1;
+    let rows = row(min_lat).max(0)..=row(max_lat).min(max_row);
+    let cols = col(bbox.min_lng).max(0)..=col(bbox.max_lng).min(max_col);
+
+    let count = (rows.end() - rows.start() + 1) * (cols.end() - cols.start() + 1);
+    if count <= 0 || count as usize > max_cells {
+        return None;
+    }
+
+    let mut cells = Vec::with_capacity(count as usize);
+    for r in rows {
+        for c in cols.clone() {
+            let center = Coordinates {
+                lat: -90.0 + (r as f64 + 0.5) * cell_lat,
+                lng: -180.0 + (c as f64 + 0.5) * cell_lng,
+            };
+            cells.push(encode(&center, precision));
+        }
+    }
+    Some(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(lat: f64, lng: f64) -> Coordinates {
+        Coordinates::new(lat, lng).unwrap()
+    }
+
+    #[test]
+    fn encodes_known_geohashes() {
+        // Published reference values (same as ST_GeoHash)
+        assert_eq!(encode(&c(48.8584, 2.2945), 7), "u09tunq");
+        assert_eq!(encode(&c(57.64911, 10.40744), 11), "u4pruydqqvj");
+        assert_eq!(encode(&c(-33.8568, 151.2153), 5), "r3gx2");
+        assert_eq!(encode(&c(48.8584, 2.2945), 1), "u");
+    }
+
+    #[test]
+    fn cell_sizes_halve_alternately() {
+        assert_eq!(cell_size_deg(1), (45.0, 45.0));
+        assert_eq!(cell_size_deg(2), (5.625, 11.25));
+    }
+
+    #[test]
+    fn covering_cells_include_point_cells() {
+        let center = c(48.8566, 2.3522);
+        let bbox = BoundingBox::from_center_radius(&center, 3000.0);
+        let cells = covering_cells(&bbox, 5, 64).unwrap();
+        assert!(cells.contains(&encode(&center, 5)));
+        for corner in [c(bbox.min_lat, bbox.min_lng), c(bbox.max_lat, bbox.max_lng)] {
+            assert!(cells.contains(&encode(&corner, 5)));
+        }
+    }
+
+    #[test]
+    fn covering_cells_span_partition_boundary() {
+        // Greenwich: 1-char cells "g" and "u" meet at lng 0
+        let bbox = BoundingBox::from_center_radius(&c(51.48, 0.0), 5000.0);
+        let mut cells = covering_cells(&bbox, 1, 8).unwrap();
+        cells.sort();
+        assert_eq!(cells, vec!["g", "u"]);
+    }
+
+    #[test]
+    fn covering_cells_across_antimeridian() {
+        // Fiji: 1-char cells "r" (east of 135°E) and "2" (west of 135°W)
+        let bbox = BoundingBox::from_center_radius(&c(-17.0, 179.99), 5000.0);
+        let mut cells = covering_cells(&bbox, 1, 8).unwrap();
+        cells.sort();
+        assert_eq!(cells, vec!["2", "r"]);
+        for side in [c(-17.0, 179.98), c(-17.0, -179.98)] {
+            let cells = covering_cells(&bbox, 5, 64).unwrap();
+            assert!(cells.contains(&encode(&side, 5)));
+        }
+    }
+
+    #[test]
+    fn covering_cells_gives_up_when_too_many() {
+        let bbox = BoundingBox::from_center_radius(&c(48.8566, 2.3522), 50_000.0);
+        assert!(covering_cells(&bbox, 6, 16).is_none());
+    }
+}
@@ -1,12 +1,36 @@
-pub mod coordinates;
-pub mod distance;
+pub mod api_key;
+pub(crate) mod builder;
+pub(crate) mod coordinates;
+pub(crate) mod costing;
+pub(crate) mod distance;
+pub(crate) mod duration;
 pub mod evaluation;
-pub mod geo;
-pub mod poi;
+pub(crate) mod geo;
+pub mod geohash;
+pub(crate) mod poi;
+pub mod poi_anomaly;
+pub(crate) mod polyline;
+pub(crate) mod quality;
+pub(crate) mod road_profile;
 pub mod route;
+pub mod saved_route;
+pub(crate) mod snap_feedback;
+pub(crate) mod speed;
+pub(crate) mod staged_route;
+pub(crate) mod timeline;
+pub(crate) mod transport_mode;
 
-pub use coordinates::Coordinates;
+pub use builder::{PoiBuilder, RouteBuilder};
+pub use coordinates::{CoordinateFormat, Coordinates};
+pub use costing::CostingOptions;
 pub use distance::{DistanceKm, DistanceMeters, RadiusMeters};
+pub use duration::{DurationEstimates, TimeBreakdown};
 pub use geo::BoundingBox;
 pub use poi::{Poi, PoiCategory};
-pub use route::{Route, RoutePoi, RoutePreferences, SnappedPoi, TransportMode};
+pub use quality::{QualityTier, RouteStrength};
+pub use road_profile::{RoadProfile, SurfacePreference, SurfaceSplit};
+pub use route::{MustInclude, Route, RoutePoi, RoutePreferences, SnappedPoi};
+pub use speed::{SpeedModel, SpeedModels};
+pub use staged_route::{RouteStage, StageLayout, StagedRoute};
+pub use timeline::{Departure, RouteTimeline};
+pub use transport_mode::TransportMode;
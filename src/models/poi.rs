@@ -40,6 +40,113 @@ pub enum PoiCategory {
     Brewery,
     Theatre,
     Library,
+
+    // Pets
+    #[serde(rename = "dog_park")]
+    DogPark,
+
+    // Amenities (returned separately, never used as waypoints)
+    #[serde(rename = "drinking_water")]
+    DrinkingWater,
+    Toilets,
+
+    // Overnight stays (stage ends of multi-day routes, never waypoints)
+    Accommodation,
+
+    // A point a request pinned by coordinates (`must_include`), not a mapped place
+    Waypoint,
+}
+
+impl PoiCategory {
+    /// Every category, in declaration order
+    pub const ALL: [PoiCategory; 29] = [
+        PoiCategory::Monument,
+        PoiCategory::Viewpoint,
+        PoiCategory::Park,
+        PoiCategory::Museum,
+        PoiCategory::Restaurant,
+        PoiCategory::Cafe,
+        PoiCategory::Historic,
+        PoiCategory::Cultural,
+        PoiCategory::Waterfront,
+        PoiCategory::Waterfall,
+        PoiCategory::NatureReserve,
+        PoiCategory::Church,
+        PoiCategory::Castle,
+        PoiCategory::Bridge,
+        PoiCategory::Tower,
+        PoiCategory::Plaza,
+        PoiCategory::Fountain,
+        PoiCategory::Market,
+        PoiCategory::Artwork,
+        PoiCategory::Lighthouse,
+        PoiCategory::Winery,
+        PoiCategory::Brewery,
+        PoiCategory::Theatre,
+        PoiCategory::Library,
+        PoiCategory::DogPark,
+        PoiCategory::DrinkingWater,
+        PoiCategory::Toilets,
+        PoiCategory::Accommodation,
+        PoiCategory::Waypoint,
+    ];
+
+    /// Practical stops surfaced in a route's `amenities` list
+    pub const AMENITIES: &'static [PoiCategory] =
+        &[PoiCategory::DrinkingWater, PoiCategory::Toilets];
+
+    /// Whether this is a practical amenity rather than a point of interest
+    pub fn is_amenity(&self) -> bool {
+        Self::AMENITIES.contains(self)
+    }
+
+    /// Hotels, hostels, huts and campsites
+    pub fn is_accommodation(&self) -> bool {
+        *self == PoiCategory::Accommodation
+    }
+
+    /// Something worth routing through, as opposed to amenities and
+    /// accommodation
+    pub fn is_point_of_interest(&self) -> bool {
+        !self.is_amenity() && !self.is_accommodation()
+    }
+
+    /// Green or open-air spaces (used for green coverage scoring)
+    pub fn is_green(&self) -> bool {
+        matches!(
+            self,
+            PoiCategory::Park
+                | PoiCategory::NatureReserve
+                | PoiCategory::Waterfront
+                | PoiCategory::DogPark
+        )
+    }
+
+    /// Spots worth reaching around sunset (`golden_hour` routing)
+    pub fn is_golden_hour_spot(&self) -> bool {
+        matches!(self, PoiCategory::Viewpoint | PoiCategory::Waterfront)
+    }
+
+    /// Typical visit length in minutes, for POIs without their own estimate
+    pub fn typical_visit_minutes(&self) -> u32 {
+        match self {
+            PoiCategory::Museum => 90,
+            PoiCategory::Castle => 120,
+            PoiCategory::Monument => 30,
+            PoiCategory::Viewpoint => 20,
+            PoiCategory::Park => 60,
+            PoiCategory::Church => 30,
+            PoiCategory::Theatre => 180,
+            PoiCategory::Library => 45,
+            PoiCategory::Cultural => 60,
+            PoiCategory::Waterfall => 30,
+            PoiCategory::Market => 45,
+            PoiCategory::DrinkingWater | PoiCategory::Toilets => 5,
+            // Passed through, not visited
+            PoiCategory::Waypoint => 0,
+            _ => 30,
+        }
+    }
 }
 
 impl fmt::Display for PoiCategory {
@@ -74,6 +181,15 @@ impl fmt::Display for PoiCategory {
             PoiCategory::Brewery => "brewery",
             PoiCategory::Theatre => "theatre",
             PoiCategory::Library => "library",
+            // Pets
+            PoiCategory::DogPark => "dog_park",
+            // Amenities
+            PoiCategory::DrinkingWater => "drinking_water",
+            PoiCategory::Toilets => "toilets",
+            // Overnight stays
+            PoiCategory::Accommodation => "accommodation",
+            // Pinned points
+            PoiCategory::Waypoint => "waypoint",
         };
         write!(f, "{}", s)
     }
@@ -113,6 +229,15 @@ impl FromStr for PoiCategory {
             "brewery" => Ok(PoiCategory::Brewery),
             "theatre" => Ok(PoiCategory::Theatre),
             "library" => Ok(PoiCategory::Library),
+            // Pets
+            "dog_park" => Ok(PoiCategory::DogPark),
+            // Amenities
+            "drinking_water" => Ok(PoiCategory::DrinkingWater),
+            "toilets" => Ok(PoiCategory::Toilets),
+            // Overnight stays
+            "accommodation" => Ok(PoiCategory::Accommodation),
+            // Pinned points
+            "waypoint" => Ok(PoiCategory::Waypoint),
             _ => Err(format!("Invalid POI category: {}", s)),
         }
     }
@@ -160,12 +285,29 @@ impl Poi {
             self.popularity_score
         }
     }
+
+    /// Minutes spent at this POI: its own estimate, or the category's typical visit
+    pub fn visit_minutes(&self) -> u32 {
+        self.estimated_visit_duration_minutes
+            .unwrap_or_else(|| self.category.typical_visit_minutes())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_all_categories_round_trip() {
+        for (i, category) in PoiCategory::ALL.iter().enumerate() {
+            assert_eq!(
+                category.to_string().parse::<PoiCategory>().as_ref(),
+                Ok(category)
+            );
+            assert_eq!(PoiCategory::ALL.iter().position(|c| c == category), Some(i));
+        }
+    }
+
     #[test]
     fn test_poi_category_parsing() {
         assert_eq!(
@@ -179,6 +321,39 @@ mod tests {
         assert!("invalid".parse::<PoiCategory>().is_err());
     }
 
+    #[test]
+    fn test_amenity_categories() {
+        let water: PoiCategory = serde_json::from_str(r#""drinking_water""#).unwrap();
+        assert_eq!(water, PoiCategory::DrinkingWater);
+        assert_eq!(water.to_string(), "drinking_water");
+        assert_eq!(
+            "toilets".parse::<PoiCategory>().unwrap(),
+            PoiCategory::Toilets
+        );
+        assert!(water.is_amenity());
+        assert!(!PoiCategory::Fountain.is_amenity());
+    }
+
+    #[test]
+    fn test_accommodation_is_not_a_point_of_interest() {
+        let hut: PoiCategory = serde_json::from_str(r#""accommodation""#).unwrap();
+        assert_eq!(hut, PoiCategory::Accommodation);
+        assert_eq!(hut.to_string().parse::<PoiCategory>().unwrap(), hut);
+        assert!(hut.is_accommodation() && !hut.is_amenity());
+        assert!(!hut.is_point_of_interest());
+        assert!(!PoiCategory::Toilets.is_point_of_interest());
+        assert!(PoiCategory::Castle.is_point_of_interest());
+    }
+
+    #[test]
+    fn test_visit_minutes_falls_back_to_category() {
+        let coords = Coordinates::new(48.8566, 2.3522).unwrap();
+        let mut museum = Poi::new("Louvre".to_string(), PoiCategory::Museum, coords, 90.0);
+        assert_eq!(museum.visit_minutes(), 90);
+        museum.estimated_visit_duration_minutes = Some(180);
+        assert_eq!(museum.visit_minutes(), 180);
+    }
+
     #[test]
     fn test_quality_score() {
         let poi = Poi::new(
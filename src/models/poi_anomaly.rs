@@ -0,0 +1,156 @@
+//! Data problems `poi_doctor` finds in the `pois` table, and the repairs
+//! it plans for them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::models::PoiCategory;
+use crate::osm;
+
+/// Why a POI was flagged
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoiAnomaly {
+    /// Outside the bounding box the database is meant to cover
+    OutsideRegion { lat: f64, lng: f64 },
+    /// Empty or whitespace-only name
+    EmptyName,
+    /// Same OSM id and name as `kept`, e.g. a feature imported both as a
+    /// node and as an area
+    DuplicateOsmId { osm_id: i64, kept: Uuid },
+    /// A category no OSM tag maps to (see [`osm::style_categories`]),
+    /// including strings that aren't a category at all
+    UnsupportedCategory,
+}
+
+impl fmt::Display for PoiAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoiAnomaly::OutsideRegion { lat, lng } => {
+                write!(f, "outside region at ({:.5}, {:.5})", lat, lng)
+            }
+            PoiAnomaly::EmptyName => f.write_str("empty name"),
+            PoiAnomaly::DuplicateOsmId { osm_id, kept } => {
+                write!(f, "duplicate of osm_id {} (kept {})", osm_id, kept)
+            }
+            PoiAnomaly::UnsupportedCategory => f.write_str("category has no tag mapping"),
+        }
+    }
+}
+
+/// One flagged row
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlaggedPoi {
+    pub id: Uuid,
+    pub name: String,
+    /// As stored, which may not parse as a [`PoiCategory`]
+    pub category: String,
+    pub anomaly: PoiAnomaly,
+}
+
+impl FlaggedPoi {
+    /// Unnamed amenities get their category's fallback name, as the
+    /// importer would have given them; everything else is deleted
+    pub fn repair(&self) -> PoiRepair {
+        let fallback = self
+            .category
+            .parse::<PoiCategory>()
+            .ok()
+            .and_then(|c| osm::fallback_name(&c));
+        match (&self.anomaly, fallback) {
+            (PoiAnomaly::EmptyName, Some(name)) => PoiRepair::Rename(name.to_string()),
+            _ => PoiRepair::Delete,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoiRepair {
+    Delete,
+    Rename(String),
+}
+
+/// Repairs for a set of flagged POIs, one per POI. A POI flagged for
+/// several reasons is deleted if any of them calls for it.
+#[derive(Debug, Default, PartialEq)]
+pub struct RepairPlan {
+    pub deletes: Vec<Uuid>,
+    pub renames: Vec<(Uuid, String)>,
+}
+
+impl RepairPlan {
+    pub fn new(flagged: &[FlaggedPoi]) -> Self {
+        let mut repairs: BTreeMap<Uuid, PoiRepair> = BTreeMap::new();
+        for poi in flagged {
+            let repair = poi.repair();
+            match repairs.get(&poi.id) {
+                Some(PoiRepair::Delete) => {}
+                _ => {
+                    repairs.insert(poi.id, repair);
+                }
+            }
+        }
+
+        let mut plan = RepairPlan::default();
+        for (id, repair) in repairs {
+            match repair {
+                PoiRepair::Delete => plan.deletes.push(id),
+                PoiRepair::Rename(name) => plan.renames.push((id, name)),
+            }
+        }
+        plan
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deletes.is_empty() && self.renames.is_empty()
+    }
+
+    /// POIs the plan touches
+    pub fn len(&self) -> usize {
+        self.deletes.len() + self.renames.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flagged(id: Uuid, category: &str, anomaly: PoiAnomaly) -> FlaggedPoi {
+        FlaggedPoi {
+            id,
+            name: String::new(),
+            category: category.to_string(),
+            anomaly,
+        }
+    }
+
+    #[test]
+    fn unnamed_amenities_are_renamed() {
+        let poi = flagged(Uuid::new_v4(), "toilets", PoiAnomaly::EmptyName);
+        assert_eq!(poi.repair(), PoiRepair::Rename("Toilets".to_string()));
+
+        let poi = flagged(Uuid::new_v4(), "museum", PoiAnomaly::EmptyName);
+        assert_eq!(poi.repair(), PoiRepair::Delete);
+    }
+
+    #[test]
+    fn delete_wins_over_rename() {
+        let id = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let plan = RepairPlan::new(&[
+            flagged(id, "toilets", PoiAnomaly::EmptyName),
+            flagged(
+                id,
+                "toilets",
+                PoiAnomaly::OutsideRegion { lat: 0.0, lng: 0.0 },
+            ),
+            flagged(other, "drinking_water", PoiAnomaly::EmptyName),
+        ]);
+
+        assert_eq!(plan.deletes, vec![id]);
+        assert_eq!(plan.renames, vec![(other, "Drinking water".to_string())]);
+        assert_eq!(plan.len(), 2);
+        assert!(RepairPlan::new(&[]).is_empty());
+    }
+}
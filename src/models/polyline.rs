@@ -0,0 +1,107 @@
+//! Encoded polyline format (Google's algorithm): zigzag varint deltas of
+//! scaled latitude/longitude. Precision 1e5 is the common default; Valhalla
+//! uses 1e6.
+
+use crate::models::Coordinates;
+
+/// Encode `points` at `precision` (e.g. `1e5` for 5 decimal places)
+pub fn encode(points: &[Coordinates], precision: f64) -> String {
+    let mut encoded = String::new();
+    let (mut prev_lat, mut prev_lng) = (0_i64, 0_i64);
+    for point in points {
+        let lat = (point.lat * precision).round() as i64;
+        let lng = (point.lng * precision).round() as i64;
+        push_delta(&mut encoded, lat - prev_lat);
+        push_delta(&mut encoded, lng - prev_lng);
+        (prev_lat, prev_lng) = (lat, lng);
+    }
+    encoded
+}
+
+fn push_delta(encoded: &mut String, delta: i64) {
+    let mut value = if delta < 0 { !(delta << 1) } else { delta << 1 };
+    while value >= 0x20 {
+        encoded.push((((value & 0x1f) | 0x20) as u8 + 63) as char);
+        value >>= 5;
+    }
+    encoded.push((value as u8 + 63) as char);
+}
+
+/// Decode an encoded polyline into `[lng, lat]` pairs
+pub fn decode(encoded: &str, precision: f64) -> Result<Vec<[f64; 2]>, String> {
+    let mut points = Vec::new();
+    let mut bytes = encoded.bytes();
+    let (mut lat, mut lng) = (0_i64, 0_i64);
+
+    while let Some(dlat) = next_delta(&mut bytes)? {
+        let dlng = next_delta(&mut bytes)?.ok_or("truncated polyline")?;
+        lat += dlat;
+        lng += dlng;
+        points.push([lng as f64 / precision, lat as f64 / precision]);
+    }
+    Ok(points)
+}
+
+/// Next zigzag-encoded varint, `None` at the end of the input
+fn next_delta(bytes: &mut std::str::Bytes) -> Result<Option<i64>, String> {
+    let mut result = 0_i64;
+    let mut shift = 0;
+    loop {
+        let Some(byte) = bytes.next() else {
+            return if shift == 0 {
+                Ok(None)
+            } else {
+                Err("truncated polyline".to_string())
+            };
+        };
+        let chunk = (byte as i64) - 63;
+        if !(0..64).contains(&chunk) || shift > 60 {
+            return Err("invalid polyline".to_string());
+        }
+        result |= (chunk & 0x1f) << shift;
+        shift += 5;
+        if chunk < 0x20 {
+            break;
+        }
+    }
+    Ok(Some(if result & 1 == 1 {
+        !(result >> 1)
+    } else {
+        result >> 1
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference example from Google's polyline documentation
+    const REFERENCE: &str = "_p~iF~ps|U_ulLnnqC_mqNvxq`@";
+
+    #[test]
+    fn test_decode_reference() {
+        assert_eq!(
+            decode(REFERENCE, 1e5).unwrap(),
+            vec![[-120.2, 38.5], [-120.95, 40.7], [-126.453, 43.252]]
+        );
+        assert!(decode("_p~iF~ps|U_ulL", 1e5).is_err());
+        assert_eq!(decode("", 1e5).unwrap(), Vec::<[f64; 2]>::new());
+    }
+
+    #[test]
+    fn test_encode_reference_and_round_trip() {
+        let points = [
+            Coordinates::new(38.5, -120.2).unwrap(),
+            Coordinates::new(40.7, -120.95).unwrap(),
+            Coordinates::new(43.252, -126.453).unwrap(),
+        ];
+        assert_eq!(encode(&points, 1e5), REFERENCE);
+
+        let paris = [
+            Coordinates::new(48.856614, 2.352222).unwrap(),
+            Coordinates::new(48.857614, 2.353222).unwrap(),
+        ];
+        let decoded = decode(&encode(&paris, 1e6), 1e6).unwrap();
+        assert_eq!(decoded, vec![[2.352222, 48.856614], [2.353222, 48.857614]]);
+    }
+}
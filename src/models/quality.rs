@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Quality badge assigned to a scored route
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum QualityTier {
+    Gold,
+    Silver,
+    Bronze,
+}
+
+impl fmt::Display for QualityTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QualityTier::Gold => write!(f, "gold"),
+            QualityTier::Silver => write!(f, "silver"),
+            QualityTier::Bronze => write!(f, "bronze"),
+        }
+    }
+}
+
+/// The objective a route wins on within a Pareto route set
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteStrength {
+    /// Closest to the requested distance
+    DistanceAccuracy,
+    /// Best-rated POIs
+    PoiQuality,
+    /// Roundest loop with the least backtracking
+    Shape,
+}
+
+impl fmt::Display for RouteStrength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteStrength::DistanceAccuracy => write!(f, "distance_accuracy"),
+            RouteStrength::PoiQuality => write!(f, "poi_quality"),
+            RouteStrength::Shape => write!(f, "shape"),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::constants::STEP_FREE_MAX_INCLINE_PCT;
+use serde::{Deserialize, Serialize};
+
+/// Preferred surface for a route
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SurfacePreference {
+    /// Paved roads and paths only, as far as possible
+    Paved,
+    /// No preference
+    #[default]
+    Mixed,
+    /// Tracks and trails; mostly-paved routes are rejected
+    Trail,
+}
+
+impl SurfacePreference {
+    pub fn is_mixed(&self) -> bool {
+        *self == SurfacePreference::Mixed
+    }
+}
+
+/// Estimated length of a route on paved and unpaved surfaces
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SurfaceSplit {
+    pub paved_km: f64,
+    pub unpaved_km: f64,
+}
+
+/// Share of a route's length on different kinds of road, derived from way data
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RoadProfile {
+    /// Fraction (0-1) on paved surfaces
+    pub paved_fraction: f32,
+    /// Fraction (0-1) on busy roads (primary, secondary, trunk, ...)
+    pub busy_road_fraction: f32,
+    /// Fraction (0-1) on smooth surfaces (paved, no cobbles)
+    pub smooth_fraction: f32,
+    /// Number of `highway=steps` ways along the route
+    pub steps_count: u32,
+    /// Steepest tagged grade (percent) along the route, if any way has incline data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_incline_pct: Option<f32>,
+}
+
+impl RoadProfile {
+    /// `distance_km` split by the paved share
+    pub fn surface_split(&self, distance_km: f64) -> SurfaceSplit {
+        let paved_km = distance_km * f64::from(self.paved_fraction.clamp(0.0, 1.0));
+        SurfaceSplit {
+            paved_km,
+            unpaved_km: distance_km - paved_km,
+        }
+    }
+
+    /// No steps and no grade steeper than a ramp allows
+    pub fn is_step_free(&self) -> bool {
+        self.steps_count == 0
+            && self
+                .max_incline_pct
+                .map_or(true, |incline| incline <= STEP_FREE_MAX_INCLINE_PCT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(steps_count: u32, max_incline_pct: Option<f32>) -> RoadProfile {
+        RoadProfile {
+            paved_fraction: 1.0,
+            busy_road_fraction: 0.0,
+            smooth_fraction: 1.0,
+            steps_count,
+            max_incline_pct,
+        }
+    }
+
+    #[test]
+    fn test_is_step_free() {
+        assert!(profile(0, None).is_step_free());
+        assert!(profile(0, Some(STEP_FREE_MAX_INCLINE_PCT)).is_step_free());
+        assert!(!profile(1, None).is_step_free());
+        assert!(!profile(0, Some(12.0)).is_step_free());
+    }
+
+    #[test]
+    fn test_surface_split() {
+        let mut profile = profile(0, None);
+        profile.paved_fraction = 0.75;
+        let split = profile.surface_split(8.0);
+        assert!((split.paved_km - 6.0).abs() < 1e-9);
+        assert!((split.unpaved_km - 2.0).abs() < 1e-9);
+    }
+}
@@ -1,57 +1,96 @@
-use crate::models::{Coordinates, Poi, PoiCategory};
+use crate::constants::{
+    MAX_POI_SEPARATION_DISTANCE_RATIO, MUST_INCLUDE_MAX_POINTS, PAVED_SURFACE_MIN_FRACTION,
+    RUN_PACE_RANGE_MIN_PER_KM, STEP_FREE_MIN_SMOOTH_FRACTION, TRAIL_SURFACE_MAX_PAVED_FRACTION,
+};
+use crate::models::saved_route::StaleReason;
+use crate::models::{
+    Coordinates, CostingOptions, Departure, DurationEstimates, Poi, PoiCategory, QualityTier,
+    RoadProfile, RouteStrength, RouteTimeline, SpeedModels, SurfacePreference, SurfaceSplit,
+    TimeBreakdown, TransportMode,
+};
+use crate::services::route_generator::metrics_explanation::MetricsExplained;
 use crate::services::route_generator::route_metrics::RouteMetrics;
 use serde::{Deserialize, Serialize};
-use std::fmt;
-use std::str::FromStr;
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
-#[serde(rename_all = "lowercase")]
-pub enum TransportMode {
-    #[default]
-    Walk,
-    Bike,
-}
-
-impl TransportMode {
-    /// Returns the Mapbox profile name for this transport mode
-    pub fn mapbox_profile(&self) -> &str {
-        match self {
-            TransportMode::Walk => "walking",
-            TransportMode::Bike => "cycling",
-        }
-    }
-}
-
-impl fmt::Display for TransportMode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            TransportMode::Walk => write!(f, "walk"),
-            TransportMode::Bike => write!(f, "bike"),
-        }
-    }
-}
-
-impl FromStr for TransportMode {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "walk" | "walking" => Ok(TransportMode::Walk),
-            "bike" | "cycling" | "bicycle" => Ok(TransportMode::Bike),
-            _ => Err(format!("Invalid transport mode: '{}'", s)),
-        }
-    }
+/// A place the route must pass through: a known POI by ID, or any point
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MustInclude {
+    Poi(Uuid),
+    Point(Coordinates),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutePreferences {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub poi_categories: Option<Vec<PoiCategory>>,
+    /// Categories to drop from the default mix (applied to discovery and snapping)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excluded_poi_categories: Option<Vec<PoiCategory>>,
     #[serde(default)]
     pub hidden_gems: bool,
     #[serde(default = "default_max_alternatives")]
     pub max_alternatives: u32,
+    /// Per-request override of the minimum spacing (km) between waypoints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poi_min_separation_km: Option<f64>,
+    /// Running pace in minutes per km (`run` mode; default 6:00/km)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pace_min_per_km: Option<f64>,
+    /// Minimum share (0-1) of the route on paved surfaces; cycling modes only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_paved_fraction: Option<f32>,
+    /// Maximum share (0-1) of the route on busy roads (primary and above)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_busy_road_fraction: Option<f32>,
+    /// Paved, trail or no preference: steers the directions backend (Valhalla)
+    /// and bounds the route's paved share
+    #[serde(default, skip_serializing_if = "SurfacePreference::is_mixed")]
+    pub surface: SurfacePreference,
+    /// Score green coverage (parks, water, dog areas) instead of category variety
+    #[serde(default)]
+    pub prefer_green: bool,
+    /// Stroller/wheelchair friendly: no steps, no steep grades, smooth surfaces
+    #[serde(default)]
+    pub step_free: bool,
+    /// Penalize routes through noisy/polluted areas (needs an environmental layer)
+    #[serde(default)]
+    pub minimize_exposure: bool,
+    /// Penalize routes climbing more than this many meters (needs elevation data)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_elevation_gain_m: Option<f32>,
+    /// Penalize hilly routes in proportion to their climb per km (needs elevation data)
+    #[serde(default)]
+    pub prefer_flat: bool,
+    /// Directions backend costs (avoid highways, ferries, walkway preference)
+    #[serde(default, skip_serializing_if = "CostingOptions::is_default")]
+    pub costing: CostingOptions,
+    /// Add time spent at waypoint POIs to `estimated_duration_minutes`
+    #[serde(default)]
+    pub include_visit_time: bool,
+    /// Order waypoints so viewpoints and waterfronts are reached around
+    /// sunset. Without `depart_at`/`arrive_by` the route ends at sunset.
+    #[serde(default)]
+    pub golden_hour: bool,
+    /// The request's `depart_at`/`arrive_by`, filled in by the handler for
+    /// `golden_hour` planning
+    #[serde(skip)]
+    pub departure: Option<Departure>,
+    /// Return the Pareto frontier over distance accuracy, POI quality and
+    /// shape instead of the top scorers, each labeled with its strength
+    #[serde(default)]
+    pub pareto: bool,
+    /// Seed for the random variation between alternatives; the same request
+    /// and seed reproduce the same routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// POIs or points every route passes through; waypoints are chosen
+    /// around them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub must_include: Vec<MustInclude>,
 }
 
 fn default_max_alternatives() -> u32 {
@@ -62,17 +101,101 @@ impl Default for RoutePreferences {
     fn default() -> Self {
         RoutePreferences {
             poi_categories: None,
+            excluded_poi_categories: None,
             hidden_gems: false,
             max_alternatives: default_max_alternatives(),
+            poi_min_separation_km: None,
+            pace_min_per_km: None,
+            min_paved_fraction: None,
+            max_busy_road_fraction: None,
+            surface: SurfacePreference::default(),
+            prefer_green: false,
+            step_free: false,
+            minimize_exposure: false,
+            max_elevation_gain_m: None,
+            prefer_flat: false,
+            costing: CostingOptions::default(),
+            include_visit_time: false,
+            golden_hour: false,
+            departure: None,
+            pareto: false,
+            seed: None,
+            must_include: Vec::new(),
         }
     }
 }
 
+impl RoutePreferences {
+    /// Fill in the mode's defaults for anything the request left unset
+    pub fn with_mode_defaults(&self, mode: &TransportMode) -> RoutePreferences {
+        let mut prefs = self.clone();
+        if prefs.poi_categories.is_none() {
+            prefs.poi_categories = mode.default_poi_categories().map(|cats| cats.to_vec());
+        }
+        if prefs.min_paved_fraction.is_none() {
+            prefs.min_paved_fraction = match prefs.surface {
+                SurfacePreference::Paved => Some(PAVED_SURFACE_MIN_FRACTION),
+                // An explicit trail preference overrides the mode's paved
+                // default (road bikes)
+                SurfacePreference::Trail => None,
+                SurfacePreference::Mixed => mode.default_min_paved_fraction(),
+            };
+        }
+        if prefs.poi_min_separation_km.is_none() {
+            prefs.poi_min_separation_km = mode.default_poi_min_separation_km();
+        }
+        if prefs.max_busy_road_fraction.is_none() {
+            prefs.max_busy_road_fraction = mode.default_max_busy_road_fraction();
+        }
+        prefs.prefer_green |= mode.prefers_green();
+        prefs
+    }
+
+    /// Minimum smooth-surface share required by these preferences
+    pub fn min_smooth_fraction(&self) -> Option<f32> {
+        self.step_free.then_some(STEP_FREE_MIN_SMOOTH_FRACTION)
+    }
+
+    /// Maximum paved share allowed by these preferences
+    pub fn max_paved_fraction(&self) -> Option<f32> {
+        (self.surface == SurfacePreference::Trail).then_some(TRAIL_SURFACE_MAX_PAVED_FRACTION)
+    }
+
+    /// Whether generated routes need way data (surface, road class, steps) to be checked
+    pub fn needs_road_profile(&self) -> bool {
+        self.min_paved_fraction.is_some()
+            || self.max_busy_road_fraction.is_some()
+            || self.step_free
+            || !self.surface.is_mixed()
+    }
+
+    /// Whether the user asked to leave this category out of the route
+    pub fn excludes(&self, category: &PoiCategory) -> bool {
+        self.excluded_poi_categories
+            .as_ref()
+            .is_some_and(|excluded| excluded.contains(category))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Route {
     pub id: Uuid,
+    /// Hash of the geometry and waypoints ([`Route::compute_content_hash`]):
+    /// equal for the same route generated twice, unlike `id`. Empty for
+    /// routes stored before it existed.
+    #[serde(default)]
+    pub content_hash: String,
     pub distance_km: f64,
     pub estimated_duration_minutes: u32,
+    /// Durations for walking, running and cycling on this same geometry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_estimates: Option<DurationEstimates>,
+    /// Travel vs. visit time, when `estimated_duration_minutes` includes visits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_breakdown: Option<TimeBreakdown>,
+    /// Clock times at each POI, when the request gave `depart_at` or `arrive_by`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeline: Option<RouteTimeline>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub elevation_gain_m: Option<f32>,
     /// GeoJSON LineString path
@@ -81,11 +204,43 @@ pub struct Route {
     pub pois: Vec<RoutePoi>,
     /// POIs near the route path but not used as waypoints
     pub snapped_pois: Vec<SnappedPoi>,
+    /// Drinking water and toilets along the path (long routes only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub amenities: Vec<SnappedPoi>,
+    /// Paved and busy-road shares of the route, when way data is available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub road_profile: Option<RoadProfile>,
+    /// No steps or steep grades along the route (`None` without way data)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_free: Option<bool>,
+    /// Estimated paved/unpaved kilometers (`None` without way data)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surface_split: Option<SurfaceSplit>,
+    /// Mean noise/pollution exposure along the path (0 = clean, 1 = worst),
+    /// when an environmental layer covers the route
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environmental_exposure: Option<f32>,
     /// Route quality score (0-10)
     pub score: f32,
+    /// Quality badge derived from score and metrics (`None` below bronze)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_tier: Option<QualityTier>,
+    /// What this route does best, for routes from a Pareto route set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strength: Option<RouteStrength>,
     /// Computed route quality metrics
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metrics: Option<RouteMetrics>,
+    /// Human-readable interpretation of `metrics`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_explained: Option<MetricsExplained>,
+    /// Set on saved routes whose waypoints have since been deleted or moved:
+    /// clients should offer to regenerate. Not part of the stored route.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+    /// Why the route is `stale`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stale_reasons: Vec<StaleReason>,
 }
 
 impl Route {
@@ -95,19 +250,120 @@ impl Route {
         path: Vec<Coordinates>,
         pois: Vec<RoutePoi>,
     ) -> Self {
+        let content_hash = content_hash(&path, &pois);
         Route {
             id: Uuid::new_v4(),
+            content_hash,
             distance_km,
             estimated_duration_minutes,
+            duration_estimates: None,
+            time_breakdown: None,
+            timeline: None,
             elevation_gain_m: None,
             path,
             pois,
             snapped_pois: Vec::new(),
+            amenities: Vec::new(),
+            road_profile: None,
+            step_free: None,
+            surface_split: None,
+            environmental_exposure: None,
             score: 0.0, // Will be calculated later
+            quality_tier: None,
+            strength: None,
             metrics: None,
+            metrics_explained: None,
+            stale: false,
+            stale_reasons: Vec::new(),
         }
     }
 
+    /// Flag the route stale for `reasons` (none clears the flag)
+    pub fn mark_stale(&mut self, reasons: Vec<StaleReason>) {
+        self.stale = !reasons.is_empty();
+        self.stale_reasons = reasons;
+    }
+
+    /// Hash of `path` and the waypoint POIs' positions, in order, at ~0.1 m
+    /// precision. Recompute after changing either.
+    pub fn compute_content_hash(&self) -> String {
+        content_hash(&self.path, &self.pois)
+    }
+
+    /// Attach per-profile durations derived from `speeds` for `mode`,
+    /// including any climbing in `elevation_gain_m`
+    pub fn with_duration_estimates(mut self, mode: &TransportMode, speeds: &SpeedModels) -> Self {
+        self.duration_estimates = Some(DurationEstimates::from_speed_model(
+            self.distance_km,
+            self.elevation_gain_m,
+            mode,
+            self.estimated_duration_minutes,
+            speeds,
+        ));
+        self
+    }
+
+    /// Timing as the request asked: a run at the runner's pace (or the
+    /// running model's) with brief stops, then visit time if
+    /// `include_visit_time`
+    pub fn with_requested_timing(
+        mut self,
+        mode: &TransportMode,
+        preferences: &RoutePreferences,
+        speeds: &SpeedModels,
+    ) -> Self {
+        if *mode == TransportMode::Run {
+            let runner = speeds
+                .running
+                .with_pace_min_per_km(preferences.pace_min_per_km);
+            self.estimated_duration_minutes = runner
+                .minutes(self.distance_km, self.elevation_gain_m)
+                .round() as u32;
+            if let Some(estimates) = self.duration_estimates.as_mut() {
+                estimates.running_minutes = self.estimated_duration_minutes;
+            }
+            for route_poi in &mut self.pois {
+                route_poi.poi.estimated_visit_duration_minutes =
+                    Some(mode.visit_minutes(&route_poi.poi));
+            }
+        }
+        if preferences.include_visit_time {
+            self.with_visit_time()
+        } else {
+            self
+        }
+    }
+
+    /// Add the waypoint POIs' visit durations to `estimated_duration_minutes`,
+    /// keeping the split in `time_breakdown`. `duration_estimates` stay
+    /// travel-only. Applying it twice has no further effect.
+    pub fn with_visit_time(mut self) -> Self {
+        if self.time_breakdown.is_some() {
+            return self;
+        }
+        let visit_minutes = self.pois.iter().map(|p| p.poi.visit_minutes()).sum();
+        self.time_breakdown = Some(TimeBreakdown {
+            travel_minutes: self.estimated_duration_minutes,
+            visit_minutes,
+        });
+        self.estimated_duration_minutes += visit_minutes;
+        self
+    }
+
+    pub fn with_timeline(mut self, departure: Departure) -> Self {
+        self.timeline = Some(RouteTimeline::plan(&self, departure));
+        self
+    }
+
+    /// Attach way-derived road data and the step-free flag and surface split
+    /// it implies
+    pub fn with_road_profile(mut self, profile: Option<RoadProfile>) -> Self {
+        self.step_free = profile.map(|p| p.is_step_free());
+        self.surface_split = profile.map(|p| p.surface_split(self.distance_km));
+        self.road_profile = profile;
+        self
+    }
+
     pub fn with_snapped_pois(mut self, snapped_pois: Vec<SnappedPoi>) -> Self {
         self.snapped_pois = snapped_pois;
         self
@@ -154,15 +410,31 @@ impl SnappedPoi {
 
 // Request/Response types for API endpoints
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoopRouteRequest {
     pub start_point: Coordinates,
-    pub distance_km: f64,
+    /// Target distance; optional for modes with a default (see `target_distance_km`)
+    #[serde(default)]
+    pub distance_km: Option<f64>,
     #[serde(default = "default_distance_tolerance")]
     pub distance_tolerance: f64,
     pub mode: TransportMode,
     #[serde(default)]
     pub preferences: RoutePreferences,
+    /// Plan clock times from this departure (RFC 3339)
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub depart_at: Option<OffsetDateTime>,
+    /// Plan clock times to be back by this time (RFC 3339)
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub arrive_by: Option<OffsetDateTime>,
 }
 
 fn default_distance_tolerance() -> f64 {
@@ -170,90 +442,430 @@ fn default_distance_tolerance() -> f64 {
 }
 
 impl LoopRouteRequest {
+    /// Departure or arrival time to plan POI arrival times for, if any
+    pub fn departure(&self) -> Option<Departure> {
+        self.depart_at
+            .map(Departure::DepartAt)
+            .or(self.arrive_by.map(Departure::ArriveBy))
+    }
+
+    /// Requested distance, falling back to the mode's default
+    pub fn target_distance_km(&self) -> Option<f64> {
+        self.distance_km.or_else(|| self.mode.default_distance_km())
+    }
+
     pub fn validate(&self) -> Result<(), String> {
-        if !(0.5..=50.0).contains(&self.distance_km) {
+        let distance_km = self
+            .target_distance_km()
+            .ok_or_else(|| format!("distance_km is required for mode '{}'", self.mode))?;
+        if !(0.5..=50.0).contains(&distance_km) {
             return Err("distance_km must be between 0.5 and 50".to_string());
         }
-        if self.distance_tolerance < 0.0 || self.distance_tolerance > self.distance_km {
+        if self.distance_tolerance < 0.0 || self.distance_tolerance > distance_km {
             return Err(
                 "distance_tolerance must be positive and less than distance_km".to_string(),
             );
         }
+        if self.depart_at.is_some() && self.arrive_by.is_some() {
+            return Err("depart_at and arrive_by are mutually exclusive".to_string());
+        }
+        if let Some(fraction) = self.preferences.min_paved_fraction {
+            if !(0.0..=1.0).contains(&fraction) {
+                return Err("min_paved_fraction must be between 0 and 1".to_string());
+            }
+            if self
+                .preferences
+                .max_paved_fraction()
+                .is_some_and(|max| fraction > max)
+            {
+                return Err(format!(
+                    "min_paved_fraction can't exceed {} with surface: trail",
+                    TRAIL_SURFACE_MAX_PAVED_FRACTION
+                ));
+            }
+        }
+        if let Some(fraction) = self.preferences.max_busy_road_fraction {
+            if !(0.0..=1.0).contains(&fraction) {
+                return Err("max_busy_road_fraction must be between 0 and 1".to_string());
+            }
+        }
+        if self
+            .preferences
+            .max_elevation_gain_m
+            .is_some_and(|gain| gain < 0.0)
+        {
+            return Err("max_elevation_gain_m must not be negative".to_string());
+        }
+        self.preferences.costing.validate()?;
+        let (fastest, slowest) = RUN_PACE_RANGE_MIN_PER_KM;
+        if self
+            .preferences
+            .pace_min_per_km
+            .is_some_and(|pace| !(fastest..=slowest).contains(&pace))
+        {
+            return Err(format!(
+                "pace_min_per_km must be between {} and {}",
+                fastest, slowest
+            ));
+        }
+        if self.preferences.must_include.len() > MUST_INCLUDE_MAX_POINTS {
+            return Err(format!(
+                "must_include takes at most {} POIs or points",
+                MUST_INCLUDE_MAX_POINTS
+            ));
+        }
+        if let Some(separation) = self.preferences.poi_min_separation_km {
+            let max_separation = distance_km * MAX_POI_SEPARATION_DISTANCE_RATIO;
+            if !(0.0..=max_separation).contains(&separation) {
+                return Err(format!(
+                    "poi_min_separation_km must be between 0 and {:.2} for a {}km route",
+                    max_separation, distance_km
+                ));
+            }
+        }
         Ok(())
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct RouteResponse {
     pub routes: Vec<Route>,
 }
 
+/// First 128 bits of a SHA-256 over coordinates in micro-degrees, hex-encoded
+fn content_hash(path: &[Coordinates], pois: &[RoutePoi]) -> String {
+    let mut hasher = Sha256::new();
+    // The path length keeps points from moving between path and waypoints
+    hasher.update((path.len() as u64).to_le_bytes());
+    let points = path.iter().chain(pois.iter().map(|p| &p.poi.coordinates));
+    for c in points {
+        hasher.update(((c.lat * 1e6).round() as i64).to_le_bytes());
+        hasher.update(((c.lng * 1e6).round() as i64).to_le_bytes());
+    }
+    hex::encode(&hasher.finalize()[..16])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::{RUN_MAX_VISIT_MINUTES, RUN_POI_MIN_SEPARATION_KM};
 
     #[test]
     fn test_loop_route_request_validation() {
         let mut req = LoopRouteRequest {
             start_point: Coordinates::new(48.8566, 2.3522).unwrap(),
-            distance_km: 5.0,
+            distance_km: Some(5.0),
+            distance_tolerance: 0.5,
+            mode: TransportMode::Walk,
+            preferences: RoutePreferences::default(),
+            depart_at: None,
+            arrive_by: None,
+        };
+
+        assert!(req.validate().is_ok());
+
+        req.distance_km = Some(0.1); // Too short
+        assert!(req.validate().is_err());
+
+        req.distance_km = Some(100.0); // Too long
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_loop_route_request_separation_validation() {
+        let mut req = LoopRouteRequest {
+            start_point: Coordinates::new(48.8566, 2.3522).unwrap(),
+            distance_km: Some(6.0),
             distance_tolerance: 0.5,
             mode: TransportMode::Walk,
             preferences: RoutePreferences::default(),
+            depart_at: None,
+            arrive_by: None,
         };
 
+        req.preferences.poi_min_separation_km = Some(0.1);
+        assert!(req.validate().is_ok());
+
+        req.preferences.poi_min_separation_km = Some(2.0); // Exactly a third
         assert!(req.validate().is_ok());
 
-        req.distance_km = 0.1; // Too short
+        req.preferences.poi_min_separation_km = Some(2.5);
         assert!(req.validate().is_err());
 
-        req.distance_km = 100.0; // Too long
+        req.preferences.poi_min_separation_km = Some(-0.1);
         assert!(req.validate().is_err());
     }
 
     #[test]
-    fn test_transport_mode_mapbox_profile() {
-        assert_eq!(TransportMode::Walk.mapbox_profile(), "walking");
-        assert_eq!(TransportMode::Bike.mapbox_profile(), "cycling");
+    fn test_loop_route_request_departure() {
+        let mut req: LoopRouteRequest = serde_json::from_str(
+            r#"{"start_point": {"lat": 48.8566, "lng": 2.3522}, "distance_km": 5.0,
+                "mode": "walk", "depart_at": "2024-06-01T18:00:00+02:00"}"#,
+        )
+        .unwrap();
+        let depart_at = req.depart_at.unwrap();
+        assert_eq!(depart_at.unix_timestamp(), 1717257600);
+        assert_eq!(req.departure(), Some(Departure::DepartAt(depart_at)));
+        assert!(req.validate().is_ok());
+
+        req.arrive_by = Some(depart_at);
+        assert!(req.validate().is_err());
+        req.depart_at = None;
+        assert_eq!(req.departure(), Some(Departure::ArriveBy(depart_at)));
     }
 
     #[test]
-    fn test_transport_mode_display() {
-        assert_eq!(TransportMode::Walk.to_string(), "walk");
-        assert_eq!(TransportMode::Bike.to_string(), "bike");
+    fn test_dog_walk_defaults() {
+        let mut req = LoopRouteRequest {
+            start_point: Coordinates::new(48.8566, 2.3522).unwrap(),
+            distance_km: None,
+            distance_tolerance: 0.5,
+            mode: TransportMode::DogWalk,
+            preferences: RoutePreferences::default(),
+            depart_at: None,
+            arrive_by: None,
+        };
+        assert_eq!(req.target_distance_km(), Some(3.0));
+        assert!(req.validate().is_ok());
+
+        let prefs = req.preferences.with_mode_defaults(&req.mode);
+        assert!(prefs.prefer_green);
+        assert_eq!(prefs.max_busy_road_fraction, Some(0.2));
+        assert!(prefs
+            .poi_categories
+            .as_ref()
+            .unwrap()
+            .contains(&PoiCategory::DogPark));
+
+        // Other modes still require an explicit distance
+        req.mode = TransportMode::Walk;
+        assert!(req.validate().is_err());
+
+        req.preferences.max_busy_road_fraction = Some(1.5);
+        req.distance_km = Some(5.0);
+        assert!(req.validate().is_err());
     }
 
     #[test]
-    fn test_transport_mode_from_str() {
-        assert_eq!(
-            "walk".parse::<TransportMode>().unwrap(),
-            TransportMode::Walk
+    fn test_route_preferences_excludes() {
+        let prefs: RoutePreferences =
+            serde_json::from_str(r#"{"excluded_poi_categories": ["cafe", "restaurant"]}"#).unwrap();
+
+        assert!(prefs.excludes(&PoiCategory::Cafe));
+        assert!(prefs.excludes(&PoiCategory::Restaurant));
+        assert!(!prefs.excludes(&PoiCategory::Park));
+        assert!(!RoutePreferences::default().excludes(&PoiCategory::Cafe));
+    }
+
+    #[test]
+    fn test_content_hash_identifies_geometry_and_waypoints() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let end = Coordinates::new(48.8600, 2.3600).unwrap();
+        let park = Poi::new("Park".to_string(), PoiCategory::Park, end, 70.0);
+        let route = |path: Vec<Coordinates>, pois: Vec<RoutePoi>| Route::new(5.0, 60, path, pois);
+
+        let today = route(vec![start, end], vec![RoutePoi::new(park.clone(), 1, 0.5)]);
+        let yesterday = route(vec![start, end], vec![RoutePoi::new(park.clone(), 1, 0.5)]);
+        assert_ne!(today.id, yesterday.id);
+        assert_eq!(today.content_hash, yesterday.content_hash);
+        assert_eq!(today.content_hash.len(), 32);
+        assert_eq!(today.compute_content_hash(), today.content_hash);
+
+        // Float noise below ~0.1 m doesn't change it; geometry or waypoints do
+        let jittered = Coordinates::new(start.lat + 1e-9, start.lng).unwrap();
+        let noisy = route(
+            vec![jittered, end],
+            vec![RoutePoi::new(park.clone(), 1, 0.5)],
         );
-        assert_eq!(
-            "WALK".parse::<TransportMode>().unwrap(),
-            TransportMode::Walk
+        assert_eq!(noisy.content_hash, today.content_hash);
+        let reversed = route(vec![end, start], vec![RoutePoi::new(park.clone(), 1, 0.5)]);
+        assert_ne!(reversed.content_hash, today.content_hash);
+        let no_waypoint = route(vec![start, end, end], vec![]);
+        assert_ne!(
+            no_waypoint.content_hash,
+            route(vec![start, end], vec![RoutePoi::new(park, 1, 0.5)]).content_hash
         );
-        assert_eq!(
-            "walking".parse::<TransportMode>().unwrap(),
-            TransportMode::Walk
+
+        // Routes stored before the hash existed still deserialize
+        let mut json = serde_json::to_value(&today).unwrap();
+        json.as_object_mut().unwrap().remove("content_hash");
+        let old: Route = serde_json::from_value(json).unwrap();
+        assert_eq!(old.content_hash, "");
+        assert_eq!(old.compute_content_hash(), today.content_hash);
+    }
+
+    #[test]
+    fn test_with_visit_time_splits_duration() {
+        let coords = Coordinates::new(48.8566, 2.3522).unwrap();
+        let mut museum = Poi::new("Museum".to_string(), PoiCategory::Museum, coords, 80.0);
+        museum.estimated_visit_duration_minutes = Some(45);
+        let viewpoint = Poi::new("View".to_string(), PoiCategory::Viewpoint, coords, 60.0);
+        let route = Route::new(
+            5.0,
+            70,
+            vec![coords],
+            vec![
+                RoutePoi::new(museum, 1, 1.0),
+                RoutePoi::new(viewpoint, 2, 3.0),
+            ],
         );
+
+        let route = route.with_visit_time();
+        // 45 min own estimate + 20 min typical viewpoint visit
         assert_eq!(
-            "bike".parse::<TransportMode>().unwrap(),
-            TransportMode::Bike
+            route.time_breakdown,
+            Some(TimeBreakdown {
+                travel_minutes: 70,
+                visit_minutes: 65
+            })
         );
+        assert_eq!(route.estimated_duration_minutes, 135);
+        assert_eq!(route.with_visit_time().estimated_duration_minutes, 135);
+
+        let prefs: RoutePreferences =
+            serde_json::from_str(r#"{"include_visit_time": true}"#).unwrap();
+        assert!(prefs.include_visit_time);
+        assert!(!RoutePreferences::default().include_visit_time);
+    }
+
+    #[test]
+    fn test_run_timing_uses_pace_and_brief_stops() {
+        let coords = Coordinates::new(48.8566, 2.3522).unwrap();
+        let museum = Poi::new("Museum".to_string(), PoiCategory::Museum, coords, 80.0);
+        let route = Route::new(10.0, 125, vec![coords], vec![RoutePoi::new(museum, 1, 4.0)])
+            .with_duration_estimates(&TransportMode::Run, &SpeedModels::default());
+        let prefs = RoutePreferences {
+            pace_min_per_km: Some(5.5),
+            include_visit_time: true,
+            ..Default::default()
+        };
+
+        let run = route.clone().with_requested_timing(
+            &TransportMode::Run,
+            &prefs,
+            &SpeedModels::default(),
+        );
+        let estimates = run.duration_estimates.unwrap();
+        assert_eq!(estimates.running_minutes, 55);
+        // The backend's walking time stays as the walking estimate
+        assert_eq!(estimates.walking_minutes, 125);
         assert_eq!(
-            "cycling".parse::<TransportMode>().unwrap(),
-            TransportMode::Bike
+            run.time_breakdown,
+            Some(TimeBreakdown {
+                travel_minutes: 55,
+                visit_minutes: RUN_MAX_VISIT_MINUTES
+            })
         );
+
+        let walk =
+            route.with_requested_timing(&TransportMode::Walk, &prefs, &SpeedModels::default());
+        assert_eq!(walk.time_breakdown.unwrap().travel_minutes, 125);
+        assert!(walk.time_breakdown.unwrap().visit_minutes > RUN_MAX_VISIT_MINUTES);
+    }
+
+    #[test]
+    fn test_run_mode_defaults_and_pace_validation() {
         assert_eq!(
-            "bicycle".parse::<TransportMode>().unwrap(),
-            TransportMode::Bike
+            "running".parse::<TransportMode>().unwrap(),
+            TransportMode::Run
         );
-        assert!("invalid".parse::<TransportMode>().is_err());
+        assert_eq!(TransportMode::Run.to_string(), "run");
+        assert_eq!(TransportMode::Run.mapbox_profile(), "walking");
+
+        let prefs = RoutePreferences::default().with_mode_defaults(&TransportMode::Run);
+        assert_eq!(prefs.poi_min_separation_km, Some(RUN_POI_MIN_SEPARATION_KM));
+
+        let mut req: LoopRouteRequest = serde_json::from_str(
+            r#"{"start_point": {"lat": 48.8566, "lng": 2.3522}, "distance_km": 8.0,
+                "mode": "run", "preferences": {"pace_min_per_km": 4.75}}"#,
+        )
+        .unwrap();
+        assert!(req.validate().is_ok());
+        req.preferences.pace_min_per_km = Some(1.0);
+        assert!(req.validate().is_err());
+        req.preferences.pace_min_per_km = Some(30.0);
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_preferences_with_mode_defaults() {
+        let prefs = RoutePreferences::default().with_mode_defaults(&TransportMode::RoadBike);
+        assert!(prefs
+            .poi_categories
+            .as_ref()
+            .unwrap()
+            .contains(&PoiCategory::Cafe));
+        assert_eq!(prefs.min_paved_fraction, Some(0.9));
+
+        // Explicit choices win over mode defaults
+        let explicit = RoutePreferences {
+            poi_categories: Some(vec![PoiCategory::Museum]),
+            min_paved_fraction: Some(0.5),
+            ..Default::default()
+        }
+        .with_mode_defaults(&TransportMode::RoadBike);
+        assert_eq!(explicit.poi_categories, Some(vec![PoiCategory::Museum]));
+        assert_eq!(explicit.min_paved_fraction, Some(0.5));
+
+        let walk = RoutePreferences::default().with_mode_defaults(&TransportMode::Walk);
+        assert!(walk.poi_categories.is_none());
+        assert!(walk.min_paved_fraction.is_none());
+        assert!(!walk.needs_road_profile());
     }
 
     #[test]
-    fn test_transport_mode_default() {
-        assert_eq!(TransportMode::default(), TransportMode::Walk);
+    fn test_surface_preference() {
+        let prefs: RoutePreferences = serde_json::from_str(r#"{"surface": "paved"}"#).unwrap();
+        let paved = prefs.with_mode_defaults(&TransportMode::Walk);
+        assert_eq!(paved.min_paved_fraction, Some(PAVED_SURFACE_MIN_FRACTION));
+        assert!(paved.needs_road_profile());
+        // Only the request's costing options are echoed back
+        assert!(serde_json::to_value(&paved)
+            .unwrap()
+            .get("costing")
+            .is_none());
+
+        let trail = RoutePreferences {
+            surface: SurfacePreference::Trail,
+            ..Default::default()
+        }
+        .with_mode_defaults(&TransportMode::Gravel);
+        assert_eq!(
+            trail.max_paved_fraction(),
+            Some(TRAIL_SURFACE_MAX_PAVED_FRACTION)
+        );
+        assert!(trail.min_paved_fraction.is_none());
+        // Trail overrides the road bike's paved default
+        let road_trail = RoutePreferences {
+            surface: SurfacePreference::Trail,
+            ..Default::default()
+        }
+        .with_mode_defaults(&TransportMode::RoadBike);
+        assert!(road_trail.min_paved_fraction.is_none());
+
+        // An explicit paved minimum above what trail allows can't be met
+        let mut req = LoopRouteRequest {
+            start_point: Coordinates::new(48.8566, 2.3522).unwrap(),
+            distance_km: Some(20.0),
+            distance_tolerance: 2.0,
+            mode: TransportMode::RoadBike,
+            preferences: RoutePreferences {
+                surface: SurfacePreference::Trail,
+                min_paved_fraction: Some(0.9),
+                ..Default::default()
+            },
+            depart_at: None,
+            arrive_by: None,
+        };
+        assert!(req.validate().is_err());
+        req.preferences.min_paved_fraction = Some(TRAIL_SURFACE_MAX_PAVED_FRACTION);
+        assert!(req.validate().is_ok());
+
+        let mixed = RoutePreferences::default();
+        assert_eq!(mixed.surface, SurfacePreference::Mixed);
+        assert!(serde_json::to_value(&mixed)
+            .unwrap()
+            .get("surface")
+            .is_none());
     }
 }
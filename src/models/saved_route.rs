@@ -0,0 +1,257 @@
+//! Routes bookmarked by users (`saved_routes`), so they can be re-fetched
+//! without regenerating.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::constants::{
+    SAVED_ROUTE_MAX_NAME_LEN, SAVED_ROUTE_MAX_USER_LEN, SAVED_ROUTE_POI_MOVED_M,
+};
+use crate::models::{Poi, Route};
+
+/// A route as saved by one user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedRoute {
+    pub route_id: Uuid,
+    pub user: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub saved_at: String,
+    pub route: Route,
+}
+
+/// Body of `POST /routes/{id}/save`: the route as returned by
+/// `POST /routes/loop`, and who is saving it
+#[derive(Debug, Deserialize)]
+pub struct SaveRouteRequest {
+    pub user: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub route: Route,
+}
+
+impl SaveRouteRequest {
+    pub fn validate(&self, route_id: Uuid) -> Result<(), String> {
+        if self.route.id != route_id {
+            return Err("route.id does not match the route in the path".to_string());
+        }
+        validate_user(&self.user)?;
+        if self
+            .name
+            .as_ref()
+            .is_some_and(|name| name.chars().count() > SAVED_ROUTE_MAX_NAME_LEN)
+        {
+            return Err(format!(
+                "name must be at most {} characters",
+                SAVED_ROUTE_MAX_NAME_LEN
+            ));
+        }
+        if self.route.path.len() < 2 {
+            return Err("route.path must have at least 2 points".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn validate_user(user: &str) -> Result<(), String> {
+    if user.trim().is_empty() || user.chars().count() > SAVED_ROUTE_MAX_USER_LEN {
+        return Err(format!(
+            "user must be 1 to {} characters",
+            SAVED_ROUTE_MAX_USER_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Body of `POST /routes/{id}/share`
+#[derive(Debug, Default, Deserialize)]
+pub struct ShareRouteRequest {
+    /// Whose saved copy to share; required without API key auth
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Link lifetime; defaults to (and is capped at) `SHARE_LINK_TTL_HOURS`
+    #[serde(default)]
+    pub expires_in_hours: Option<u32>,
+}
+
+/// A short link to a saved route, readable without auth until it expires
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteShare {
+    pub token: String,
+    pub route_id: Uuid,
+    /// Path of `GET /share/{token}`
+    pub url: String,
+    pub expires_at: String,
+}
+
+/// Why a saved route no longer matches the POI data it was generated from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum StaleReason {
+    /// A waypoint POI has been deleted
+    PoiRemoved { poi_id: Uuid, name: String },
+    /// A waypoint POI is now `moved_m` meters from where the route visits it
+    PoiMoved {
+        poi_id: Uuid,
+        name: String,
+        moved_m: f64,
+    },
+}
+
+/// Check `route`'s waypoints against `current`, the same POIs as they are
+/// now (deleted ones missing). Empty when the route still matches.
+///
+/// Waypoints are matched by ID, then by `osm_id`: a data refresh re-imports
+/// changed OSM objects under new IDs, so a POI only counts as removed when
+/// its OSM object is gone too.
+pub fn stale_reasons(route: &Route, current: &[Poi]) -> Vec<StaleReason> {
+    let by_id: HashMap<Uuid, &Poi> = current.iter().map(|poi| (poi.id, poi)).collect();
+    let by_osm_id: HashMap<i64, &Poi> = current
+        .iter()
+        .filter_map(|poi| Some((poi.osm_id?, poi)))
+        .collect();
+    route
+        .pois
+        .iter()
+        .filter_map(|waypoint| {
+            let poi = &waypoint.poi;
+            let now = by_id
+                .get(&poi.id)
+                .or_else(|| poi.osm_id.and_then(|osm_id| by_osm_id.get(&osm_id)));
+            let Some(now) = now else {
+                return Some(StaleReason::PoiRemoved {
+                    poi_id: poi.id,
+                    name: poi.name.clone(),
+                });
+            };
+            let moved_m = poi.coordinates.distance_to(&now.coordinates) * 1000.0;
+            (moved_m > SAVED_ROUTE_POI_MOVED_M).then(|| StaleReason::PoiMoved {
+                poi_id: poi.id,
+                name: poi.name.clone(),
+                moved_m: moved_m.round(),
+            })
+        })
+        .collect()
+}
+
+/// Position in a user's saved-route listing (`saved_at DESC, route_id DESC`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedRouteCursor {
+    pub saved_at: String,
+    pub route_id: Uuid,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Coordinates, PoiCategory, RoutePoi};
+
+    fn request() -> SaveRouteRequest {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let via = Coordinates::new(48.86, 2.35).unwrap();
+        SaveRouteRequest {
+            user: "alice".to_string(),
+            name: Some("Sunday loop".to_string()),
+            route: Route::new(5.0, 60, vec![start, via, start], vec![]),
+        }
+    }
+
+    #[test]
+    fn test_save_request_validation() {
+        let req = request();
+        assert!(req.validate(req.route.id).is_ok());
+        assert!(req.validate(Uuid::new_v4()).is_err());
+
+        let mut blank_user = request();
+        blank_user.user = "  ".to_string();
+        assert!(blank_user.validate(blank_user.route.id).is_err());
+
+        let mut long_name = request();
+        long_name.name = Some("x".repeat(SAVED_ROUTE_MAX_NAME_LEN + 1));
+        assert!(long_name.validate(long_name.route.id).is_err());
+
+        let mut no_path = request();
+        no_path.route.path.truncate(1);
+        assert!(no_path.validate(no_path.route.id).is_err());
+    }
+
+    #[test]
+    fn test_stale_reasons() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let waypoint = |name: &str, lat: f64| {
+            let poi = Poi::new(
+                name.to_string(),
+                PoiCategory::Monument,
+                Coordinates::new(lat, 2.35).unwrap(),
+                50.0,
+            );
+            RoutePoi::new(poi, 1, 1.0)
+        };
+        let pois = vec![
+            waypoint("Kept", 48.860),
+            waypoint("Nudged", 48.862),
+            waypoint("Moved", 48.864),
+            waypoint("Removed", 48.866),
+        ];
+        let route = Route::new(5.0, 60, vec![start, start], pois.clone());
+        assert!(stale_reasons(
+            &route,
+            &pois.iter().map(|p| p.poi.clone()).collect::<Vec<_>>()
+        )
+        .is_empty());
+
+        let mut nudged = pois[1].poi.clone();
+        nudged.coordinates = Coordinates::new(48.8624, 2.35).unwrap(); // ~45 m
+        let mut moved = pois[2].poi.clone();
+        moved.coordinates = Coordinates::new(48.866, 2.35).unwrap(); // ~220 m
+        let current = vec![pois[0].poi.clone(), nudged, moved];
+
+        let reasons = stale_reasons(&route, &current);
+        assert_eq!(reasons.len(), 2);
+        assert!(matches!(
+            &reasons[0],
+            StaleReason::PoiMoved { name, moved_m, .. } if name == "Moved" && (200.0..250.0).contains(moved_m)
+        ));
+        assert_eq!(
+            reasons[1],
+            StaleReason::PoiRemoved {
+                poi_id: pois[3].poi.id,
+                name: "Removed".to_string(),
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&reasons[1]).unwrap()["reason"],
+            "poi_removed"
+        );
+    }
+
+    #[test]
+    fn test_stale_reasons_match_reimported_pois_by_osm_id() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let waypoint = |name: &str, osm_id: i64| {
+            let mut poi = Poi::new(
+                name.to_string(),
+                PoiCategory::Monument,
+                Coordinates::new(48.86, 2.35).unwrap(),
+                50.0,
+            );
+            poi.osm_id = Some(osm_id);
+            RoutePoi::new(poi, 1, 1.0)
+        };
+        let pois = vec![waypoint("Reimported", 1), waypoint("Deleted", 2)];
+        let route = Route::new(5.0, 60, vec![start, start], pois.clone());
+
+        // A refresh gave the first POI a new ID; the second OSM object is gone
+        let mut reimported = pois[0].poi.clone();
+        reimported.id = Uuid::new_v4();
+        let reasons = stale_reasons(&route, &[reimported]);
+        assert_eq!(
+            reasons,
+            vec![StaleReason::PoiRemoved {
+                poi_id: pois[1].poi.id,
+                name: "Deleted".to_string(),
+            }]
+        );
+    }
+}
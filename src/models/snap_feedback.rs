@@ -0,0 +1,131 @@
+//! Client feedback on snapped POIs and the snap radius suggestions derived
+//! from it (see [`snap_tuning`](crate::services::snap_tuning)).
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::SNAP_FEEDBACK_MAX_EVENTS;
+use crate::models::TransportMode;
+
+/// What a user did with one snapped POI shown along their route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapFeedbackEvent {
+    pub mode: TransportMode,
+    /// `distance_from_path_m` of the snapped POI in the route response
+    pub distance_from_path_m: f32,
+    /// Opened the POI details
+    #[serde(default)]
+    pub tapped: bool,
+    /// Kept the POI on the route (added as a stop, not dismissed)
+    #[serde(default)]
+    pub kept: bool,
+}
+
+impl SnapFeedbackEvent {
+    pub fn engaged(&self) -> bool {
+        self.tapped || self.kept
+    }
+}
+
+/// Body of `POST /telemetry/snaps`
+#[derive(Debug, Deserialize)]
+pub struct SnapFeedbackRequest {
+    pub events: Vec<SnapFeedbackEvent>,
+}
+
+impl SnapFeedbackRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.events.is_empty() {
+            return Err("events must not be empty".to_string());
+        }
+        if self.events.len() > SNAP_FEEDBACK_MAX_EVENTS {
+            return Err(format!(
+                "at most {} events per request",
+                SNAP_FEEDBACK_MAX_EVENTS
+            ));
+        }
+        if self
+            .events
+            .iter()
+            .any(|e| !e.distance_from_path_m.is_finite() || e.distance_from_path_m < 0.0)
+        {
+            return Err("distance_from_path_m must be a non-negative number".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Shown and engaged counts for snapped POIs in one distance band
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapDistanceBucket {
+    /// Upper edge of the band; the lower edge is the previous bucket's
+    pub max_distance_m: f64,
+    pub shown: i64,
+    pub engaged: i64,
+}
+
+impl SnapDistanceBucket {
+    pub fn engagement_rate(&self) -> f64 {
+        if self.shown == 0 {
+            0.0
+        } else {
+            self.engaged as f64 / self.shown as f64
+        }
+    }
+}
+
+/// Suggested snap radius for one transport mode
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapRadiusRecommendation {
+    pub transport_mode: String,
+    /// `SNAP_RADIUS_M` when the suggestion was computed
+    pub current_radius_m: f64,
+    /// `None` when there was too little feedback
+    pub recommended_radius_m: Option<f64>,
+    pub samples: i64,
+    pub buckets: Vec<SnapDistanceBucket>,
+    pub computed_at: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(distance_from_path_m: f32) -> SnapFeedbackEvent {
+        SnapFeedbackEvent {
+            mode: TransportMode::Walk,
+            distance_from_path_m,
+            tapped: false,
+            kept: true,
+        }
+    }
+
+    #[test]
+    fn test_feedback_request_validation() {
+        let ok = SnapFeedbackRequest {
+            events: vec![event(12.5)],
+        };
+        assert!(ok.validate().is_ok());
+        assert!(ok.events[0].engaged());
+
+        let empty = SnapFeedbackRequest { events: vec![] };
+        assert!(empty.validate().is_err());
+
+        let negative = SnapFeedbackRequest {
+            events: vec![event(-1.0)],
+        };
+        assert!(negative.validate().is_err());
+
+        let too_many = SnapFeedbackRequest {
+            events: vec![event(1.0); SNAP_FEEDBACK_MAX_EVENTS + 1],
+        };
+        assert!(too_many.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_feedback_event() {
+        let e: SnapFeedbackEvent =
+            serde_json::from_str(r#"{"mode": "road_bike", "distance_from_path_m": 40}"#).unwrap();
+        assert_eq!(e.mode, TransportMode::RoadBike);
+        assert!(!e.engaged());
+    }
+}
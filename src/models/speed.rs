@@ -0,0 +1,109 @@
+use crate::constants::{
+    CYCLING_CLIMB_MIN_PER_100M, CYCLING_SPEED_KMH, RUNNING_CLIMB_MIN_PER_100M, RUNNING_SPEED_KMH,
+    WALKING_CLIMB_MIN_PER_100M, WALKING_SPEED_KMH,
+};
+use crate::models::TransportMode;
+
+/// How fast one profile moves: a flat-ground speed, slowed on climbs by a
+/// fixed time per 100 m of ascent (Naismith's rule for walkers)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedModel {
+    pub flat_kmh: f64,
+    /// Extra minutes per 100 m climbed
+    pub climb_min_per_100m: f64,
+}
+
+impl SpeedModel {
+    /// Same climb penalty, at a pace (min/km) instead of the flat speed
+    pub fn with_pace_min_per_km(self, pace: Option<f64>) -> Self {
+        match pace {
+            Some(pace) if pace > 0.0 => SpeedModel {
+                flat_kmh: 60.0 / pace,
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// Minutes to cover `distance_km`, climbing `elevation_gain_m` on the way
+    pub fn minutes(&self, distance_km: f64, elevation_gain_m: Option<f32>) -> f64 {
+        let climb_m = elevation_gain_m.map_or(0.0, |m| f64::from(m.max(0.0)));
+        distance_km / self.flat_kmh * 60.0 + climb_m / 100.0 * self.climb_min_per_100m
+    }
+}
+
+/// A [`SpeedModel`] for each family of [`TransportMode`]s. Defaults come
+/// from the `*_SPEED_KMH` and `*_CLIMB_MIN_PER_100M` constants;
+/// [`RouteGeneratorConfig::speed_models`] applies the configured overrides.
+///
+/// [`RouteGeneratorConfig::speed_models`]: crate::config::RouteGeneratorConfig::speed_models
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedModels {
+    /// Walks and dog walks
+    pub walking: SpeedModel,
+    pub running: SpeedModel,
+    /// Every bike mode
+    pub cycling: SpeedModel,
+}
+
+impl Default for SpeedModels {
+    fn default() -> Self {
+        SpeedModels {
+            walking: SpeedModel {
+                flat_kmh: WALKING_SPEED_KMH,
+                climb_min_per_100m: WALKING_CLIMB_MIN_PER_100M,
+            },
+            running: SpeedModel {
+                flat_kmh: RUNNING_SPEED_KMH,
+                climb_min_per_100m: RUNNING_CLIMB_MIN_PER_100M,
+            },
+            cycling: SpeedModel {
+                flat_kmh: CYCLING_SPEED_KMH,
+                climb_min_per_100m: CYCLING_CLIMB_MIN_PER_100M,
+            },
+        }
+    }
+}
+
+impl SpeedModels {
+    pub fn for_mode(&self, mode: &TransportMode) -> SpeedModel {
+        if mode.is_cycling() {
+            self.cycling
+        } else if *mode == TransportMode::Run {
+            self.running
+        } else {
+            self.walking
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_climbs_add_time() {
+        let walking = SpeedModels::default().walking;
+        assert_eq!(walking.minutes(10.0, None), 120.0);
+        assert_eq!(walking.minutes(10.0, Some(0.0)), 120.0);
+        assert_eq!(
+            walking.minutes(10.0, Some(300.0)),
+            120.0 + 3.0 * WALKING_CLIMB_MIN_PER_100M
+        );
+        // Elevation noise never makes a route faster
+        assert_eq!(walking.minutes(10.0, Some(-50.0)), 120.0);
+    }
+
+    #[test]
+    fn test_models_by_mode_and_pace() {
+        let models = SpeedModels::default();
+        assert_eq!(models.for_mode(&TransportMode::DogWalk), models.walking);
+        assert_eq!(models.for_mode(&TransportMode::Run), models.running);
+        assert_eq!(models.for_mode(&TransportMode::Gravel), models.cycling);
+
+        let runner = models.running.with_pace_min_per_km(Some(5.0));
+        assert_eq!(runner.flat_kmh, 12.0);
+        assert_eq!(runner.climb_min_per_100m, models.running.climb_min_per_100m);
+        assert_eq!(models.running.with_pace_min_per_km(None), models.running);
+    }
+}
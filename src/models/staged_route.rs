@@ -0,0 +1,54 @@
+//! Multi-day routes: a long target distance split into daily stages that
+//! start and end at accommodation.
+
+use crate::models::{Poi, Route};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How the days fit together
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageLayout {
+    /// Daily loops from one base (the accommodation nearest the start)
+    #[default]
+    Loops,
+    /// A point-to-point trek, each day ending at the next night's stay
+    Segments,
+}
+
+/// One day of a staged route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteStage {
+    /// 1-based
+    pub day: u32,
+    pub distance_km: f64,
+    /// Where the day ends: the base for loops, the next stay for segments.
+    /// `None` for loops from a start with no accommodation nearby.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accommodation: Option<Poi>,
+    pub route: Route,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedRoute {
+    pub id: Uuid,
+    pub layout: StageLayout,
+    pub total_distance_km: f64,
+    pub stages: Vec<RouteStage>,
+}
+
+impl StagedRoute {
+    pub fn new(layout: StageLayout, stages: Vec<RouteStage>) -> Self {
+        StagedRoute {
+            id: Uuid::new_v4(),
+            layout,
+            total_distance_km: stages.iter().map(|s| s.distance_km).sum(),
+            stages,
+        }
+    }
+
+    /// Distance of each day, in order
+    pub fn daily_distances_km(&self) -> Vec<f64> {
+        self.stages.iter().map(|s| s.distance_km).collect()
+    }
+}
@@ -0,0 +1,205 @@
+//! Clock times along a route for a given departure (`depart_at`) or arrival
+//! (`arrive_by`) time.
+//!
+//! Moving time is spread along the path at the route's own pace (its travel
+//! estimate over its distance); each waypoint POI adds its visit duration.
+//! Snapped POIs are passed by without stopping. Anything that depends on the
+//! time a POI is reached (opening hours, daylight) should read
+//! [`TimedStop::arrive_at`].
+
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::models::Route;
+
+/// When the user wants to be on their way, or back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Departure {
+    DepartAt(OffsetDateTime),
+    ArriveBy(OffsetDateTime),
+}
+
+/// One POI along the route with the clock time it is reached
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimedStop {
+    pub poi_id: Uuid,
+    pub name: String,
+    /// Waypoint (visited) or snapped (passed by)
+    pub waypoint: bool,
+    /// Distance along the path from the start
+    pub distance_from_start_km: f64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub arrive_at: OffsetDateTime,
+    /// After the visit, for waypoints; `arrive_at` for snapped POIs
+    #[serde(with = "time::serde::rfc3339")]
+    pub leave_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteTimeline {
+    #[serde(with = "time::serde::rfc3339")]
+    pub depart_at: OffsetDateTime,
+    /// Back at the start, visits included
+    #[serde(with = "time::serde::rfc3339")]
+    pub return_at: OffsetDateTime,
+    /// Waypoints and snapped POIs in the order they are reached
+    pub stops: Vec<TimedStop>,
+}
+
+impl RouteTimeline {
+    pub fn plan(route: &Route, departure: Departure) -> Self {
+        let travel_minutes = route
+            .time_breakdown
+            .map_or(route.estimated_duration_minutes, |b| b.travel_minutes)
+            as f64;
+        let minutes_per_km = if route.distance_km > 0.0 {
+            travel_minutes / route.distance_km
+        } else {
+            0.0
+        };
+
+        // (km along the path, visit minutes, stop without times)
+        let mut points: Vec<(f64, u32, TimedStop)> = Vec::new();
+        let mut waypoints: Vec<_> = route.pois.iter().collect();
+        waypoints.sort_by_key(|p| p.order_in_route);
+        let mut previous_km = 0.0_f64;
+        for route_poi in waypoints {
+            // Waypoints are reached in order, even where the path doubles back
+            let along_km = route_poi
+                .poi
+                .coordinates
+                .distance_to_linestring(&route.path)
+                .map_or(route_poi.distance_from_start_km, |(_, _, along)| along)
+                .max(previous_km);
+            previous_km = along_km;
+            points.push((
+                along_km,
+                route_poi.poi.visit_minutes(),
+                unscheduled(route_poi.poi.id, &route_poi.poi.name, true, along_km),
+            ));
+        }
+        for snapped in &route.snapped_pois {
+            points.push((
+                snapped.distance_from_start_km,
+                0,
+                unscheduled(
+                    snapped.poi.id,
+                    &snapped.poi.name,
+                    false,
+                    snapped.distance_from_start_km,
+                ),
+            ));
+        }
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let visit_total: u32 = points.iter().map(|(_, visit, _)| visit).sum();
+        let total = minutes(travel_minutes + visit_total as f64);
+        let depart_at = match departure {
+            Departure::DepartAt(at) => at,
+            Departure::ArriveBy(by) => by - total,
+        };
+
+        let mut visited = 0.0;
+        let stops = points
+            .into_iter()
+            .map(|(along_km, visit, mut stop)| {
+                stop.arrive_at = depart_at + minutes(along_km * minutes_per_km + visited);
+                stop.leave_at = stop.arrive_at + minutes(visit as f64);
+                visited += visit as f64;
+                stop
+            })
+            .collect();
+
+        RouteTimeline {
+            depart_at,
+            return_at: depart_at + total,
+            stops,
+        }
+    }
+}
+
+fn unscheduled(poi_id: Uuid, name: &str, waypoint: bool, along_km: f64) -> TimedStop {
+    TimedStop {
+        poi_id,
+        name: name.to_string(),
+        waypoint,
+        distance_from_start_km: along_km,
+        arrive_at: OffsetDateTime::UNIX_EPOCH,
+        leave_at: OffsetDateTime::UNIX_EPOCH,
+    }
+}
+
+/// Whole seconds, so serialized times stay readable
+fn minutes(m: f64) -> Duration {
+    Duration::seconds((m * 60.0).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Coordinates, Poi, PoiCategory, RoutePoi, SnappedPoi};
+    use time::{Date, Month};
+
+    fn at(hour: u8, minute: u8) -> OffsetDateTime {
+        Date::from_calendar_date(2024, Month::June, 1)
+            .unwrap()
+            .with_hms(hour, minute, 0)
+            .unwrap()
+            .assume_utc()
+    }
+
+    /// 6 km out and back along a meridian, 60 min of moving time. A museum
+    /// (90 min visit) at the far end, a viewpoint passed 1.5 km in.
+    fn route() -> Route {
+        let start = Coordinates::new(48.0, 2.0).unwrap();
+        let far = Coordinates::new(48.0 + 3.0 / 111.0, 2.0).unwrap();
+        let mut museum = Poi::new("Museum".to_string(), PoiCategory::Museum, far, 80.0);
+        museum.estimated_visit_duration_minutes = Some(90);
+        let view = Poi::new(
+            "View".to_string(),
+            PoiCategory::Viewpoint,
+            Coordinates::new(48.0 + 1.5 / 111.0, 2.0).unwrap(),
+            60.0,
+        );
+        Route::new(
+            6.0,
+            60,
+            vec![start, far, start],
+            vec![RoutePoi::new(museum, 1, 3.0)],
+        )
+        .with_snapped_pois(vec![SnappedPoi::new(view, 1.5, 20.0)])
+    }
+
+    #[test]
+    fn test_depart_at_times_each_stop() {
+        let timeline = RouteTimeline::plan(&route(), Departure::DepartAt(at(18, 0)));
+        let names: Vec<_> = timeline.stops.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["View", "Museum"]);
+        assert_eq!(timeline.stops[0].arrive_at, at(18, 15));
+        assert_eq!(timeline.stops[0].leave_at, timeline.stops[0].arrive_at);
+        // 3 km at 10 min/km, then 90 min inside
+        assert!((timeline.stops[1].distance_from_start_km - 3.0).abs() < 0.05);
+        assert_eq!(
+            timeline.stops[1].leave_at - timeline.stops[1].arrive_at,
+            Duration::minutes(90)
+        );
+        assert_eq!(timeline.return_at, at(20, 30));
+    }
+
+    #[test]
+    fn test_arrive_by_works_backwards() {
+        let timeline = RouteTimeline::plan(&route(), Departure::ArriveBy(at(20, 30)));
+        assert_eq!(timeline.depart_at, at(18, 0));
+        assert_eq!(timeline.return_at, at(20, 30));
+    }
+
+    #[test]
+    fn test_visit_time_in_duration_is_not_counted_twice() {
+        let route = route();
+        let start = Departure::DepartAt(at(18, 0));
+        let plain = RouteTimeline::plan(&route, start);
+        let with_visits = RouteTimeline::plan(&route.with_visit_time(), start);
+        assert_eq!(plain, with_visits);
+    }
+}
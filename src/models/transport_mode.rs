@@ -0,0 +1,256 @@
+use crate::constants::{RUN_MAX_VISIT_MINUTES, RUN_POI_MIN_SEPARATION_KM};
+use crate::models::{Poi, PoiCategory};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportMode {
+    #[default]
+    Walk,
+    Bike,
+    /// Road cycling: paved surfaces, scenic stops, no ferries
+    RoadBike,
+    /// Gravel cycling: unpaved tracks welcome, nature-oriented stops
+    Gravel,
+    /// Short walks through green space, away from busy roads
+    DogWalk,
+    /// Running: walking paths at the request's pace, with brief stops
+    Run,
+}
+
+/// Default POI mix for road cyclists (used when no categories are requested)
+const ROAD_BIKE_DEFAULT_CATEGORIES: &[PoiCategory] = &[
+    PoiCategory::Viewpoint,
+    PoiCategory::Cafe,
+    PoiCategory::Castle,
+    PoiCategory::Lighthouse,
+    PoiCategory::Bridge,
+    PoiCategory::Tower,
+    PoiCategory::Monument,
+    PoiCategory::Church,
+];
+
+/// Default POI mix for gravel riders (used when no categories are requested)
+const GRAVEL_DEFAULT_CATEGORIES: &[PoiCategory] = &[
+    PoiCategory::NatureReserve,
+    PoiCategory::Waterfall,
+    PoiCategory::Viewpoint,
+    PoiCategory::Waterfront,
+    PoiCategory::Park,
+    PoiCategory::Historic,
+    PoiCategory::Castle,
+    PoiCategory::Winery,
+];
+
+/// Default POI mix for dog walks (used when no categories are requested)
+const DOG_WALK_DEFAULT_CATEGORIES: &[PoiCategory] = &[
+    PoiCategory::DogPark,
+    PoiCategory::Park,
+    PoiCategory::NatureReserve,
+    PoiCategory::Waterfront,
+    PoiCategory::DrinkingWater,
+];
+
+impl TransportMode {
+    /// The mode's name as sent in requests
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransportMode::Walk => "walk",
+            TransportMode::Bike => "bike",
+            TransportMode::RoadBike => "road_bike",
+            TransportMode::Gravel => "gravel",
+            TransportMode::DogWalk => "dog_walk",
+            TransportMode::Run => "run",
+        }
+    }
+
+    /// Returns the Mapbox profile name for this transport mode
+    pub fn mapbox_profile(&self) -> &str {
+        match self {
+            TransportMode::Walk | TransportMode::DogWalk | TransportMode::Run => "walking",
+            TransportMode::Bike | TransportMode::RoadBike | TransportMode::Gravel => "cycling",
+        }
+    }
+
+    /// Mapbox `exclude` flags for this mode, if any
+    pub fn mapbox_exclude(&self) -> Option<&'static str> {
+        match self {
+            TransportMode::RoadBike => Some("ferry"),
+            TransportMode::Gravel => Some("motorway"),
+            _ => None,
+        }
+    }
+
+    /// Whether this mode is routed with a cycling profile
+    pub fn is_cycling(&self) -> bool {
+        matches!(
+            self,
+            TransportMode::Bike | TransportMode::RoadBike | TransportMode::Gravel
+        )
+    }
+
+    /// POI categories used when the request doesn't specify any
+    pub fn default_poi_categories(&self) -> Option<&'static [PoiCategory]> {
+        match self {
+            TransportMode::RoadBike => Some(ROAD_BIKE_DEFAULT_CATEGORIES),
+            TransportMode::Gravel => Some(GRAVEL_DEFAULT_CATEGORIES),
+            TransportMode::DogWalk => Some(DOG_WALK_DEFAULT_CATEGORIES),
+            _ => None,
+        }
+    }
+
+    /// Minimum paved fraction used when the request doesn't specify one
+    pub fn default_min_paved_fraction(&self) -> Option<f32> {
+        match self {
+            TransportMode::RoadBike => Some(0.9),
+            _ => None,
+        }
+    }
+
+    /// Maximum busy-road fraction used when the request doesn't specify one
+    pub fn default_max_busy_road_fraction(&self) -> Option<f32> {
+        match self {
+            TransportMode::DogWalk => Some(0.2),
+            _ => None,
+        }
+    }
+
+    /// Waypoint spacing used when the request doesn't specify one
+    pub fn default_poi_min_separation_km(&self) -> Option<f64> {
+        match self {
+            TransportMode::Run => Some(RUN_POI_MIN_SEPARATION_KM),
+            _ => None,
+        }
+    }
+
+    /// Minutes spent at `poi` when stopping there in this mode
+    pub fn visit_minutes(&self, poi: &Poi) -> u32 {
+        match self {
+            TransportMode::Run => poi.visit_minutes().min(RUN_MAX_VISIT_MINUTES),
+            _ => poi.visit_minutes(),
+        }
+    }
+
+    /// Whether routes default to favouring green coverage over category variety
+    pub fn prefers_green(&self) -> bool {
+        matches!(self, TransportMode::DogWalk)
+    }
+
+    /// Target distance used when the request omits `distance_km`
+    pub fn default_distance_km(&self) -> Option<f64> {
+        match self {
+            TransportMode::DogWalk => Some(3.0),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TransportMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TransportMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "walk" | "walking" => Ok(TransportMode::Walk),
+            "bike" | "cycling" | "bicycle" => Ok(TransportMode::Bike),
+            "road_bike" | "roadbike" | "road" => Ok(TransportMode::RoadBike),
+            "gravel" | "gravel_bike" => Ok(TransportMode::Gravel),
+            "dog_walk" | "dogwalk" | "dog" => Ok(TransportMode::DogWalk),
+            "run" | "running" | "jog" => Ok(TransportMode::Run),
+            _ => Err(format!("Invalid transport mode: '{}'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_mode_mapbox_profile() {
+        assert_eq!(TransportMode::Walk.mapbox_profile(), "walking");
+        assert_eq!(TransportMode::Bike.mapbox_profile(), "cycling");
+    }
+
+    #[test]
+    fn test_transport_mode_display() {
+        assert_eq!(TransportMode::Walk.to_string(), "walk");
+        assert_eq!(TransportMode::Bike.to_string(), "bike");
+    }
+
+    #[test]
+    fn test_transport_mode_from_str() {
+        assert_eq!(
+            "walk".parse::<TransportMode>().unwrap(),
+            TransportMode::Walk
+        );
+        assert_eq!(
+            "WALK".parse::<TransportMode>().unwrap(),
+            TransportMode::Walk
+        );
+        assert_eq!(
+            "walking".parse::<TransportMode>().unwrap(),
+            TransportMode::Walk
+        );
+        assert_eq!(
+            "bike".parse::<TransportMode>().unwrap(),
+            TransportMode::Bike
+        );
+        assert_eq!(
+            "cycling".parse::<TransportMode>().unwrap(),
+            TransportMode::Bike
+        );
+        assert_eq!(
+            "bicycle".parse::<TransportMode>().unwrap(),
+            TransportMode::Bike
+        );
+        assert!("invalid".parse::<TransportMode>().is_err());
+    }
+
+    #[test]
+    fn test_cycling_modes_share_profile() {
+        for mode in [
+            TransportMode::Bike,
+            TransportMode::RoadBike,
+            TransportMode::Gravel,
+        ] {
+            assert_eq!(mode.mapbox_profile(), "cycling");
+            assert!(mode.is_cycling());
+            assert_eq!(mode.to_string().parse::<TransportMode>().unwrap(), mode);
+        }
+        assert!(!TransportMode::Walk.is_cycling());
+        assert_eq!(TransportMode::RoadBike.mapbox_exclude(), Some("ferry"));
+        assert_eq!(TransportMode::Gravel.mapbox_exclude(), Some("motorway"));
+        assert_eq!(TransportMode::Bike.mapbox_exclude(), None);
+    }
+
+    #[test]
+    fn test_transport_mode_serde_names() {
+        let mode: TransportMode = serde_json::from_str(r#""road_bike""#).unwrap();
+        assert_eq!(mode, TransportMode::RoadBike);
+        assert_eq!(
+            serde_json::to_string(&TransportMode::Gravel).unwrap(),
+            r#""gravel""#
+        );
+    }
+
+    #[test]
+    fn test_dog_walk_default_categories() {
+        let categories = TransportMode::DogWalk.default_poi_categories().unwrap();
+        assert!(categories.contains(&PoiCategory::DogPark));
+        assert!(categories.contains(&PoiCategory::DrinkingWater));
+        assert!(!categories.contains(&PoiCategory::Fountain));
+    }
+
+    #[test]
+    fn test_transport_mode_default() {
+        assert_eq!(TransportMode::default(), TransportMode::Walk);
+    }
+}
@@ -6,7 +6,7 @@
 
 use std::collections::HashMap;
 
-use crate::models::PoiCategory;
+use crate::models::{Coordinates, PoiCategory};
 
 /// Collect an osmpbf tag iterator into a borrowed `HashMap`.
 pub fn collect_tags<'a>(
@@ -50,6 +50,7 @@ pub fn determine_category(tags: &HashMap<&str, &str>) -> Option<PoiCategory> {
         match *v {
             "park" | "garden" => return Some(PoiCategory::Park),
             "nature_reserve" => return Some(PoiCategory::NatureReserve),
+            "dog_park" => return Some(PoiCategory::DogPark),
             _ => {}
         }
     }
@@ -100,9 +101,37 @@ pub fn determine_category(tags: &HashMap<&str, &str>) -> Option<PoiCategory> {
         return Some(PoiCategory::Plaza);
     }
 
+    // Dog-friendly green areas and paths not covered above (e.g. meadows,
+    // off-leash trails). Elsewhere `dog=yes` only means dogs are allowed.
+    if matches!(tags.get("dog"), Some(&"yes") | Some(&"unleashed")) && is_green_area_or_path(tags) {
+        return Some(PoiCategory::DogPark);
+    }
+
     None
 }
 
+/// Parks, green land cover and footpaths, where a `dog` tag marks somewhere
+/// to walk a dog
+fn is_green_area_or_path(tags: &HashMap<&str, &str>) -> bool {
+    matches!(
+        tags.get("leisure"),
+        Some(&"park") | Some(&"common") | Some(&"recreation_ground")
+    ) || matches!(
+        tags.get("landuse"),
+        Some(&"meadow")
+            | Some(&"grass")
+            | Some(&"forest")
+            | Some(&"recreation_ground")
+            | Some(&"village_green")
+    ) || matches!(
+        tags.get("natural"),
+        Some(&"grassland") | Some(&"heath") | Some(&"wood") | Some(&"scrub")
+    ) || matches!(
+        tags.get("highway"),
+        Some(&"path") | Some(&"footway") | Some(&"track") | Some(&"bridleway")
+    )
+}
+
 fn map_tourism(value: &str) -> Option<PoiCategory> {
     match value {
         "monument" => Some(PoiCategory::Monument),
@@ -110,6 +139,8 @@ fn map_tourism(value: &str) -> Option<PoiCategory> {
         "museum" => Some(PoiCategory::Museum),
         "attraction" => Some(PoiCategory::Cultural),
         "artwork" => Some(PoiCategory::Artwork),
+        "hotel" | "guest_house" | "hostel" | "motel" | "chalet" | "alpine_hut"
+        | "wilderness_hut" | "camp_site" => Some(PoiCategory::Accommodation),
         _ => None,
     }
 }
@@ -137,10 +168,67 @@ fn map_amenity(value: &str, tags: &HashMap<&str, &str>) -> Option<PoiCategory> {
         "library" => Some(PoiCategory::Library),
         "fountain" => Some(PoiCategory::Fountain),
         "marketplace" => Some(PoiCategory::Market),
+        "drinking_water" => Some(PoiCategory::DrinkingWater),
+        "toilets" => Some(PoiCategory::Toilets),
+        _ => None,
+    }
+}
+
+/// Display name for categories that are imported even when untagged
+/// (amenities are rarely named in OSM).
+pub fn fallback_name(category: &PoiCategory) -> Option<&'static str> {
+    match category {
+        PoiCategory::DrinkingWater => Some("Drinking water"),
+        PoiCategory::Toilets => Some("Toilets"),
         _ => None,
     }
 }
 
+/// The osm2pgsql style `osm/import_osm.sh` imports Postgres databases with
+const OSM2PGSQL_STYLE: &str = include_str!("../../osm/osm_poi_style.lua");
+
+/// Category names the osm2pgsql style can assign: the values of its
+/// `category_mappings` table and the literals `determine_category` returns.
+fn style_category_names() -> impl Iterator<Item = &'static str> {
+    let mut in_mappings = false;
+    OSM2PGSQL_STYLE.lines().filter_map(move |line| {
+        let line = line.trim();
+        if line.starts_with("local category_mappings = {") {
+            in_mappings = true;
+            return None;
+        }
+        let literal = if in_mappings {
+            if line == "}" {
+                in_mappings = false;
+                return None;
+            }
+            line.split_once('=')?.1
+        } else {
+            line.strip_prefix("return ")?
+        };
+        literal.trim().strip_prefix('\'')?.split('\'').next()
+    })
+}
+
+/// Categories a Postgres import can produce, read from the osm2pgsql style.
+/// [`determine_category`] mirrors the same rules for SQLite region builds.
+/// POIs in other categories can't have come from an import.
+pub fn style_categories() -> Vec<PoiCategory> {
+    let names: Vec<&str> = style_category_names().collect();
+    PoiCategory::ALL
+        .into_iter()
+        .filter(|category| names.contains(&category.to_string().as_str()))
+        .collect()
+}
+
+/// POI name from the `name` tag, or the category's fallback name.
+pub fn poi_name(tags: &HashMap<&str, &str>, category: &PoiCategory) -> Option<String> {
+    tags.get("name")
+        .copied()
+        .or_else(|| fallback_name(category))
+        .map(str::to_string)
+}
+
 // ---------------------------------------------------------------------------
 // Popularity
 // ---------------------------------------------------------------------------
@@ -182,10 +270,12 @@ pub fn calculate_popularity(tags: &HashMap<&str, &str>) -> f32 {
         score += 5.0;
     }
 
-    // Star rating
+    // Star rating ("NaN" and "inf" parse as floats too)
     if let Some(stars_str) = tags.get("stars") {
         if let Ok(stars) = stars_str.parse::<f32>() {
-            score += stars * 2.0;
+            if stars.is_finite() {
+                score += stars * 2.0;
+            }
         }
     }
 
@@ -205,6 +295,28 @@ pub fn calculate_popularity(tags: &HashMap<&str, &str>) -> f32 {
     score.clamp(0.0, 100.0)
 }
 
+// ---------------------------------------------------------------------------
+// Way centroids
+// ---------------------------------------------------------------------------
+
+/// Centroid of a closed way's resolved nodes, as `(lat, lon)` pairs.
+///
+/// The closing node repeats the first and is counted once. `None` without
+/// nodes or if the mean isn't a valid coordinate.
+pub fn way_centroid(nodes: &[(f64, f64)]) -> Option<Coordinates> {
+    let nodes = match nodes {
+        [first, rest @ .., last] if first == last && !rest.is_empty() => &nodes[..nodes.len() - 1],
+        _ => nodes,
+    };
+    if nodes.is_empty() {
+        return None;
+    }
+    let n = nodes.len() as f64;
+    let lat = nodes.iter().map(|(lat, _)| lat).sum::<f64>() / n;
+    let lon = nodes.iter().map(|(_, lon)| lon).sum::<f64>() / n;
+    Coordinates::new(lat, lon).ok()
+}
+
 // ---------------------------------------------------------------------------
 // Duration
 // ---------------------------------------------------------------------------
@@ -218,20 +330,7 @@ pub fn estimate_duration(tags: &HashMap<&str, &str>, category: &PoiCategory) ->
         }
     }
 
-    match category {
-        PoiCategory::Museum => 90,
-        PoiCategory::Castle => 120,
-        PoiCategory::Monument => 30,
-        PoiCategory::Viewpoint => 20,
-        PoiCategory::Park => 60,
-        PoiCategory::Church => 30,
-        PoiCategory::Theatre => 180,
-        PoiCategory::Library => 45,
-        PoiCategory::Cultural => 60,
-        PoiCategory::Waterfall => 30,
-        PoiCategory::Market => 45,
-        _ => 30,
-    }
+    category.typical_visit_minutes()
 }
 
 // ---------------------------------------------------------------------------
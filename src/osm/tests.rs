@@ -62,6 +62,42 @@ fn leisure_park() {
     assert_eq!(determine_category(&t), Some(PoiCategory::Park));
 }
 
+#[test]
+fn leisure_dog_park() {
+    let t = tags(&[("leisure", "dog_park"), ("name", "Bark Meadow")]);
+    assert_eq!(determine_category(&t), Some(PoiCategory::DogPark));
+}
+
+#[test]
+fn dog_friendly_area_maps_to_dog_park() {
+    let t = tags(&[
+        ("landuse", "meadow"),
+        ("dog", "unleashed"),
+        ("name", "Off-leash field"),
+    ]);
+    assert_eq!(determine_category(&t), Some(PoiCategory::DogPark));
+
+    let trail = tags(&[("highway", "path"), ("dog", "yes"), ("name", "Dog trail")]);
+    assert_eq!(determine_category(&trail), Some(PoiCategory::DogPark));
+
+    // Explicit categories win over the dog tag
+    let park = tags(&[("leisure", "park"), ("dog", "yes"), ("name", "City Park")]);
+    assert_eq!(determine_category(&park), Some(PoiCategory::Park));
+}
+
+#[test]
+fn dog_tag_elsewhere_is_not_a_dog_park() {
+    for other in [
+        ("shop", "pet"),
+        ("amenity", "restaurant"),
+        ("building", "yes"),
+        ("highway", "residential"),
+    ] {
+        let t = tags(&[other, ("dog", "yes"), ("name", "Dog-friendly place")]);
+        assert_eq!(determine_category(&t), None, "{:?}", other);
+    }
+}
+
 #[test]
 fn leisure_garden() {
     let t = tags(&[("leisure", "garden"), ("name", "Botanical Garden")]);
@@ -125,6 +161,56 @@ fn tourism_priority_over_historic() {
     assert_eq!(determine_category(&t), Some(PoiCategory::Viewpoint));
 }
 
+// -- style_categories --
+
+#[test]
+fn style_assigns_only_known_categories() {
+    for name in style_category_names() {
+        assert!(
+            name.parse::<PoiCategory>().is_ok(),
+            "unknown category {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn style_categories_match_determine_category() {
+    // One tag per category the osm2pgsql style can assign
+    let examples = [
+        (PoiCategory::Monument, ("tourism", "monument")),
+        (PoiCategory::Viewpoint, ("tourism", "viewpoint")),
+        (PoiCategory::Park, ("leisure", "park")),
+        (PoiCategory::Museum, ("tourism", "museum")),
+        (PoiCategory::Historic, ("historic", "ruins")),
+        (PoiCategory::Cultural, ("tourism", "attraction")),
+        (PoiCategory::Waterfront, ("natural", "beach")),
+        (PoiCategory::Waterfall, ("natural", "waterfall")),
+        (PoiCategory::NatureReserve, ("leisure", "nature_reserve")),
+        (PoiCategory::Church, ("building", "church")),
+        (PoiCategory::Castle, ("historic", "castle")),
+        (PoiCategory::Tower, ("man_made", "tower")),
+        (PoiCategory::Plaza, ("place", "square")),
+        (PoiCategory::Fountain, ("amenity", "fountain")),
+        (PoiCategory::Market, ("amenity", "marketplace")),
+        (PoiCategory::Artwork, ("tourism", "artwork")),
+        (PoiCategory::Lighthouse, ("man_made", "lighthouse")),
+        (PoiCategory::Winery, ("craft", "winery")),
+        (PoiCategory::Brewery, ("craft", "brewery")),
+        (PoiCategory::Theatre, ("amenity", "theatre")),
+        (PoiCategory::Library, ("amenity", "library")),
+        (PoiCategory::DogPark, ("leisure", "dog_park")),
+        (PoiCategory::DrinkingWater, ("amenity", "drinking_water")),
+        (PoiCategory::Toilets, ("amenity", "toilets")),
+        (PoiCategory::Accommodation, ("tourism", "hotel")),
+    ];
+    for (category, tag) in &examples {
+        assert_eq!(determine_category(&tags(&[*tag])).as_ref(), Some(category));
+    }
+    let expected: Vec<_> = examples.into_iter().map(|(category, _)| category).collect();
+    assert_eq!(style_categories(), expected);
+}
+
 // -- calculate_popularity --
 
 #[test]
@@ -207,6 +293,34 @@ fn score_clamped_to_100() {
     assert!((calculate_popularity(&t) - 100.0).abs() < f32::EPSILON);
 }
 
+#[test]
+fn popularity_ignores_non_finite_stars() {
+    for stars in ["NaN", "inf", "-inf"] {
+        let t = tags(&[("tourism", "hotel"), ("stars", stars)]);
+        assert!(
+            (calculate_popularity(&t) - 40.0).abs() < f32::EPSILON,
+            "{stars}"
+        );
+    }
+}
+
+// -- way_centroid --
+
+#[test]
+fn centroid_counts_closing_node_once() {
+    let square = [(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0), (0.0, 0.0)];
+    let c = way_centroid(&square).unwrap();
+    assert!((c.lat - 1.0).abs() < 1e-12 && (c.lng - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn centroid_of_nothing_or_garbage_is_none() {
+    assert!(way_centroid(&[]).is_none());
+    assert!(way_centroid(&[(f64::NAN, 0.0), (1.0, 1.0)]).is_none());
+    assert!(way_centroid(&[(400.0, 0.0), (400.0, 0.0)]).is_none());
+    assert!(way_centroid(&[(f64::MAX, 0.0), (f64::MAX, 1.0), (1.0, 1.0)]).is_none());
+}
+
 // -- estimate_duration --
 
 #[test]
@@ -254,3 +368,39 @@ fn description_none_when_empty() {
     let t = tags(&[("name", "X")]);
     assert!(build_description(&t).is_none());
 }
+
+#[test]
+fn amenity_drinking_water_and_toilets() {
+    let water = tags(&[("amenity", "drinking_water")]);
+    assert_eq!(determine_category(&water), Some(PoiCategory::DrinkingWater));
+    assert_eq!(
+        poi_name(&water, &PoiCategory::DrinkingWater).as_deref(),
+        Some("Drinking water")
+    );
+
+    let toilets = tags(&[("amenity", "toilets"), ("name", "Park WC")]);
+    assert_eq!(determine_category(&toilets), Some(PoiCategory::Toilets));
+    assert_eq!(
+        poi_name(&toilets, &PoiCategory::Toilets).as_deref(),
+        Some("Park WC")
+    );
+    assert_eq!(estimate_duration(&toilets, &PoiCategory::Toilets), 5);
+}
+
+#[test]
+fn accommodation_tags() {
+    for value in ["hotel", "hostel", "alpine_hut", "camp_site"] {
+        let t = tags(&[("tourism", value), ("name", "Refuge")]);
+        assert_eq!(
+            determine_category(&t),
+            Some(PoiCategory::Accommodation),
+            "{value}"
+        );
+    }
+}
+
+#[test]
+fn unnamed_regular_poi_has_no_name() {
+    let t = tags(&[("tourism", "viewpoint")]);
+    assert_eq!(poi_name(&t, &PoiCategory::Viewpoint), None);
+}
@@ -0,0 +1,160 @@
+//! Operator endpoints, mounted only when `ADMIN_TOKEN` is set. Requests must
+//! carry the token as `x-api-key` or `Authorization: Bearer <token>`.
+
+use axum::{
+    extract::{Query, Request, State},
+    middleware::{self, Next},
+    response::Response,
+    routing::{delete, get},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::cache::{CacheFilter, RouteCache};
+use crate::db::queries;
+use crate::error::{AppError, Result};
+use crate::models::api_key::hash_key;
+use crate::models::BoundingBox;
+use crate::routes::evaluation;
+use crate::services::events::{EventBus, LifecycleEvent};
+use crate::services::tenant::api_key;
+use tokio::sync::broadcast::error::RecvError;
+
+/// State of the cache admin routes: invalidations are applied to `cache`
+/// and announced on `events` for the other instances
+#[derive(Clone)]
+pub struct CacheAdmin {
+    pub cache: Arc<dyn RouteCache>,
+    pub events: EventBus,
+}
+
+/// Admin routes over `cache`, guarded by `token`
+pub fn create_admin_router(cache: Arc<dyn RouteCache>, events: EventBus, token: String) -> Router {
+    Router::new()
+        .route("/admin/cache", delete(invalidate_cache))
+        .with_state(CacheAdmin { cache, events })
+        .layer(middleware::from_fn_with_state(
+            Arc::new(hash_key(&token)),
+            require_admin_token,
+        ))
+}
+
+/// Admin routes over Postgres data (evaluations, snap feedback), guarded by
+/// `token`
+pub fn create_admin_pg_router(pool: PgPool, token: String) -> Router {
+    Router::new()
+        .route(
+            "/admin/evaluations/export",
+            get(evaluation::export_evaluations),
+        )
+        .route("/admin/snap-radius", get(snap_radius_report))
+        .with_state(pool)
+        .layer(middleware::from_fn_with_state(
+            Arc::new(hash_key(&token)),
+            require_admin_token,
+        ))
+}
+
+/// Middleware: reject requests without the admin token (401 / 403).
+/// Compares hashes so the check doesn't leak the token through timing.
+async fn require_admin_token(
+    State(token_hash): State<Arc<String>>,
+    request: Request,
+    next: Next,
+) -> Result<Response> {
+    let token = api_key(request.headers())
+        .ok_or_else(|| AppError::Unauthorized("Missing admin token".to_string()))?;
+    if hash_key(token) != *token_hash {
+        return Err(AppError::Forbidden("Invalid admin token".to_string()));
+    }
+    Ok(next.run(request).await)
+}
+
+/// GET /admin/snap-radius - Snap radius suggested per transport mode from
+/// client feedback, next to the radius in use when it was computed
+async fn snap_radius_report(State(pool): State<PgPool>) -> Result<Json<Value>> {
+    let recommendations = queries::list_snap_recommendations(&pool).await?;
+    Ok(Json(json!({ "recommendations": recommendations })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvalidateParams {
+    /// Key prefix, e.g. `poi:region` or `route:loop`
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// `min_lng,min_lat,max_lng,max_lat`
+    #[serde(default)]
+    pub bbox: Option<String>,
+}
+
+/// DELETE /admin/cache?prefix=&bbox= - purge cached entries, e.g. POI
+/// regions after re-importing OSM data. Entries must match every given
+/// parameter; at least one is required. The shared tier is purged here;
+/// every instance's in-process tier through a `CacheInvalidated` event.
+pub async fn invalidate_cache(
+    State(admin): State<CacheAdmin>,
+    Query(params): Query<InvalidateParams>,
+) -> Result<Json<Value>> {
+    if params.prefix.is_none() && params.bbox.is_none() {
+        return Err(AppError::InvalidRequest(
+            "Give a prefix, a bbox or both".to_string(),
+        ));
+    }
+    let filter = CacheFilter {
+        prefix: params.prefix,
+        region: params
+            .bbox
+            .as_deref()
+            .map(BoundingBox::parse)
+            .transpose()
+            .map_err(AppError::InvalidRequest)?,
+    };
+    let invalidated = match filter {
+        CacheFilter {
+            prefix: None,
+            region: Some(bbox),
+        } => admin.cache.invalidate_region(bbox).await,
+        _ => admin.cache.invalidate(&filter).await,
+    };
+    tracing::info!(
+        prefix = ?filter.prefix,
+        region = ?filter.region,
+        invalidated,
+        "Admin cache invalidation: {} entries",
+        invalidated
+    );
+    admin
+        .events
+        .publish(LifecycleEvent::CacheInvalidated { filter });
+    Ok(Json(json!({ "invalidated": invalidated })))
+}
+
+/// Drop this instance's in-process copies of the entries every
+/// `CacheInvalidated` event on `events` names, including events other
+/// instances fan out over Redis
+pub fn spawn_invalidation_listener(cache: Arc<dyn RouteCache>, events: &EventBus) {
+    let mut receiver = events.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let LifecycleEvent::CacheInvalidated { ref filter } = *event {
+                        let dropped = cache.invalidate_local(filter).await;
+                        tracing::debug!(dropped, "Dropped {} locally cached entries", dropped);
+                    }
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!(
+                        missed,
+                        "Cache invalidation listener missed {} events",
+                        missed
+                    )
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
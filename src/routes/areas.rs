@@ -0,0 +1,58 @@
+use crate::constants::{SUGGEST_HISTORY_LIMIT, SUGGEST_HISTORY_RADIUS_M};
+use crate::db::queries;
+use crate::error::AppError;
+use crate::models::{Coordinates, TransportMode};
+use crate::services::distance_suggestion::{self, DistanceSample, DistanceSuggestion};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestDistanceParams {
+    pub lat: f64,
+    pub lng: f64,
+    /// Transport mode (`walk`, `walking`, `bike`, ...), default walking
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+/// GET /areas/suggest - Loop distance range most likely to give good routes
+/// from a point, from POI density and past generations nearby
+pub async fn suggest_distance(
+    State(pool): State<PgPool>,
+    Query(params): Query<SuggestDistanceParams>,
+) -> Result<Json<DistanceSuggestion>, AppError> {
+    let center = Coordinates::new(params.lat, params.lng).map_err(AppError::InvalidRequest)?;
+    let mode: TransportMode = match params.mode.as_deref() {
+        Some(mode) => mode.parse().map_err(AppError::InvalidRequest)?,
+        None => TransportMode::Walk,
+    };
+
+    let distances = distance_suggestion::candidate_distances(&mode);
+    let radii_m: Vec<f64> = distances
+        .iter()
+        .map(|&km| distance_suggestion::reach_radius_m(km))
+        .collect();
+    let pois_in_reach = queries::count_pois_within_radii(&pool, &center, &radii_m).await?;
+    let history: Vec<DistanceSample> = queries::generation_history_near(
+        &pool,
+        &center,
+        &mode.to_string(),
+        SUGGEST_HISTORY_RADIUS_M,
+        SUGGEST_HISTORY_LIMIT,
+    )
+    .await?
+    .into_iter()
+    .map(|(target_distance_km, score)| DistanceSample {
+        target_distance_km,
+        score,
+    })
+    .collect();
+
+    distance_suggestion::suggest(distances, &pois_in_reach, &history)
+        .map(Json)
+        .ok_or_else(|| AppError::NoPoisFound("No POIs within reach of this point".to_string()))
+}
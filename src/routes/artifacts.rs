@@ -0,0 +1,49 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::error::{AppError, Result};
+use crate::storage::content_type_for_key;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct SignedUrlQuery {
+    pub expires: u64,
+    pub signature: String,
+}
+
+/// GET /artifacts/{*key}?expires=&signature=
+/// Serve an artifact through a signed URL issued by the local directory store
+pub async fn get_artifact(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    Query(query): Query<SignedUrlQuery>,
+) -> Result<Response> {
+    let store = state
+        .artifacts
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Artifact storage is not configured".to_string()))?;
+    if !store.verify_signature(&key, query.expires, &query.signature) {
+        return Err(AppError::Forbidden(
+            "Invalid or expired artifact link".to_string(),
+        ));
+    }
+    let data = store
+        .get(&key)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Artifact '{}' not found", key)))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type_for_key(&key)),
+            // The URL carries the expiry, so the content never changes under it
+            (header::CACHE_CONTROL, "private, max-age=3600, immutable"),
+        ],
+        data,
+    )
+        .into_response())
+}
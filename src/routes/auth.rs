@@ -0,0 +1,152 @@
+//! API key authentication for `/routes/*` (`API_KEY_AUTH`).
+//!
+//! Keys live in Postgres (`api_keys`) with per-key scopes: reads need
+//! `routes:read`, everything else `routes:write`. Other endpoints, such as
+//! `/debug/health` and `/share/{token}`, stay open. The authenticated
+//! [`ApiKey`] is added to the request extensions, and handlers acting for a
+//! user check it belongs to that user ([`authorize_user`]). Each key also
+//! stores its tenant, which the `TenantId` extractor reads from it.
+
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use sqlx::PgPool;
+
+use crate::db::queries;
+use crate::error::AppError;
+use crate::models::api_key::{hash_key, ApiKey, ApiScope};
+use crate::models::saved_route::validate_user;
+use crate::services::tenant::api_key;
+
+/// Scope needed for `method` on `path` (relative to `/api/v1`), `None` for
+/// endpoints that don't require a key
+pub fn required_scope(method: &Method, path: &str) -> Option<ApiScope> {
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+    if path != "/routes" && !path.starts_with("/routes/") {
+        return None;
+    }
+    if method == Method::GET || method == Method::HEAD {
+        Some(ApiScope::RoutesRead)
+    } else {
+        Some(ApiScope::RoutesWrite)
+    }
+}
+
+/// Middleware: reject requests to protected endpoints without a valid key
+/// carrying the needed scope (401 / 403)
+pub async fn require_api_key(
+    State(pool): State<PgPool>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(scope) = required_scope(request.method(), request.uri().path()) else {
+        return Ok(next.run(request).await);
+    };
+    let key = api_key(request.headers())
+        .ok_or_else(|| AppError::Unauthorized("Missing API key".to_string()))?;
+    let key = queries::authenticate_api_key(&pool, &hash_key(key))
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid API key".to_string()))?;
+    if !key.allows(scope) {
+        return Err(AppError::Forbidden(format!(
+            "API key lacks the {} scope",
+            scope
+        )));
+    }
+    request.extensions_mut().insert(key);
+    Ok(next.run(request).await)
+}
+
+/// Reject (403) a request acting for `user` with another user's key. Without
+/// a key (`API_KEY_AUTH` off) the request is trusted.
+pub fn authorize_user(key: Option<&ApiKey>, user: &str) -> Result<(), AppError> {
+    match key {
+        Some(key) if key.user_id != user => Err(AppError::Forbidden(
+            "API key belongs to another user".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The user a request acts for: the key's user, or without a key the
+/// `user` the request names (400 if it names none). A key and a different
+/// `user` is rejected as in [`authorize_user`].
+pub fn acting_user(key: Option<&ApiKey>, user: Option<&str>) -> Result<String, AppError> {
+    let user = user.map(str::trim);
+    match (key, user) {
+        (Some(key), Some(user)) => {
+            authorize_user(Some(key), user)?;
+            Ok(key.user_id.clone())
+        }
+        (Some(key), None) => Ok(key.user_id.clone()),
+        (None, Some(user)) => {
+            validate_user(user).map_err(AppError::InvalidRequest)?;
+            Ok(user.to_string())
+        }
+        (None, None) => Err(AppError::InvalidRequest("user is required".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(
+            required_scope(&Method::POST, "/routes/loop"),
+            Some(ApiScope::RoutesWrite)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/api/v1/routes"),
+            Some(ApiScope::RoutesRead)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/routes/8f0e/save"),
+            Some(ApiScope::RoutesRead)
+        );
+        assert_eq!(required_scope(&Method::GET, "/debug/health"), None);
+        assert_eq!(required_scope(&Method::GET, "/share/abc"), None);
+        assert_eq!(required_scope(&Method::GET, "/routesx"), None);
+    }
+
+    #[test]
+    fn test_authorize_user() {
+        let key = ApiKey {
+            id: uuid::Uuid::new_v4(),
+            user_id: "alice".to_string(),
+            tenant: Default::default(),
+            scopes: vec![ApiScope::RoutesRead],
+        };
+        assert!(authorize_user(Some(&key), "alice").is_ok());
+        assert!(matches!(
+            authorize_user(Some(&key), "bob"),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(authorize_user(None, "bob").is_ok());
+    }
+
+    #[test]
+    fn test_acting_user() {
+        let key = ApiKey {
+            id: uuid::Uuid::new_v4(),
+            user_id: "alice".to_string(),
+            tenant: Default::default(),
+            scopes: vec![ApiScope::RoutesRead],
+        };
+        assert_eq!(acting_user(Some(&key), None).unwrap(), "alice");
+        assert_eq!(acting_user(Some(&key), Some("alice")).unwrap(), "alice");
+        assert!(matches!(
+            acting_user(Some(&key), Some("bob")),
+            Err(AppError::Forbidden(_))
+        ));
+        assert_eq!(acting_user(None, Some(" bob ")).unwrap(), "bob");
+        assert!(matches!(
+            acting_user(None, None),
+            Err(AppError::InvalidRequest(_))
+        ));
+    }
+}
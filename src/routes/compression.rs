@@ -0,0 +1,190 @@
+//! Gzip response compression.
+//!
+//! Buffers compressible responses (JSON, text) and gzips them when the client
+//! accepts it. Route responses are built in memory anyway, so buffering costs
+//! nothing extra; event streams and streamed CSV exports are left alone.
+
+use axum::body::{to_bytes, Body};
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::Write;
+
+use crate::constants::COMPRESSION_MIN_BODY_BYTES;
+
+/// Appended to strong ETags of gzipped responses: the compressed body is a
+/// different representation, so it must not share the identity tag.
+pub const GZIP_ETAG_SUFFIX: &str = "-gzip";
+
+/// Middleware for `axum::middleware::from_fn`
+pub async fn gzip_responses(request: Request, next: Next) -> Response {
+    let accepts_gzip = accepts_gzip(request.headers());
+    let response = next.run(request).await;
+    if !accepts_gzip || !is_compressible(&response) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to buffer response for compression");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    parts
+        .headers
+        .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    if bytes.len() < COMPRESSION_MIN_BODY_BYTES {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+
+    let mut encoder = GzEncoder::new(Vec::with_capacity(bytes.len() / 4), Compression::fast());
+    let compressed = match encoder.write_all(&bytes).and_then(|_| encoder.finish()) {
+        Ok(compressed) => compressed,
+        Err(e) => {
+            tracing::warn!(error = %e, "Gzip compression failed, sending uncompressed");
+            return Response::from_parts(parts, Body::from(bytes));
+        }
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    if let Some(etag) = gzip_etag(&parts.headers) {
+        parts.headers.insert(header::ETAG, etag);
+    }
+    Response::from_parts(parts, Body::from(compressed))
+}
+
+/// Whether `Accept-Encoding` allows gzip (explicitly or via `*`) with q > 0
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+            let name = params.next().unwrap_or_default();
+            let q = params
+                .find_map(|p| p.strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            matches!(name, "gzip" | "x-gzip" | "*") && q > 0.0
+        })
+}
+
+fn is_compressible(response: &Response) -> bool {
+    if matches!(
+        response.status(),
+        StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED
+    ) || response.headers().contains_key(header::CONTENT_ENCODING)
+    {
+        return false;
+    }
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    content_type.starts_with("application/json")
+        || content_type.contains("+json")
+        || (content_type.starts_with("text/")
+            && !content_type.starts_with("text/event-stream")
+            && !content_type.starts_with("text/csv"))
+}
+
+/// Strong ETag with the gzip suffix; weak ETags already allow any encoding
+fn gzip_etag(headers: &HeaderMap) -> Option<HeaderValue> {
+    let etag = headers.get(header::ETAG)?.to_str().ok()?;
+    if etag.starts_with("W/") {
+        return None;
+    }
+    let base = etag.strip_suffix('"')?;
+    HeaderValue::from_str(&format!("{}{}\"", base, GZIP_ETAG_SUFFIX)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/large",
+                get(|| async {
+                    (
+                        [(header::ETAG, "\"route-v1-r0\"")],
+                        axum::Json(vec!["waypoint"; 500]),
+                    )
+                }),
+            )
+            .route("/small", get(|| async { axum::Json("ok") }))
+            .layer(middleware::from_fn(gzip_responses))
+    }
+
+    async fn get_with(path: &str, accept_encoding: Option<&str>) -> Response {
+        let mut request = Request::builder().uri(path);
+        if let Some(accept_encoding) = accept_encoding {
+            request = request.header(header::ACCEPT_ENCODING, accept_encoding);
+        }
+        app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_accepts_gzip() {
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::ACCEPT_ENCODING,
+                HeaderValue::from_str(value).unwrap(),
+            );
+            headers
+        };
+        assert!(accepts_gzip(&headers("gzip, deflate, br")));
+        assert!(accepts_gzip(&headers("br;q=1.0, gzip;q=0.8")));
+        assert!(accepts_gzip(&headers("*")));
+        assert!(!accepts_gzip(&headers("gzip;q=0")));
+        assert!(!accepts_gzip(&headers("br, identity")));
+        assert!(!accepts_gzip(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_large_json_is_gzipped() {
+        let response = get_with("/large", Some("gzip")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+        assert_eq!(response.headers()[header::ETAG], "\"route-v1-r0-gzip\"");
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut json = String::new();
+        GzDecoder::new(&body[..]).read_to_string(&mut json).unwrap();
+        let waypoints: Vec<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(waypoints.len(), 500);
+    }
+
+    #[tokio::test]
+    async fn test_uncompressed_without_gzip_or_when_small() {
+        let identity = get_with("/large", None).await;
+        assert!(!identity.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(identity.headers()[header::ETAG], "\"route-v1-r0\"");
+
+        let small = get_with("/small", Some("gzip")).await;
+        assert!(!small.headers().contains_key(header::CONTENT_ENCODING));
+        let body = to_bytes(small.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"\"ok\"");
+    }
+}
@@ -0,0 +1,96 @@
+//! Builds the tower-http CORS layer from [`CorsConfig`].
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use std::time::Duration;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+use crate::config::CorsConfig;
+
+/// CORS layer for `config`. Entries that don't parse are skipped (they are
+/// reported by `Config::validate`). Panics, like `CorsLayer`, when credentials
+/// are combined with a wildcard; `Config::validate` rejects that too.
+pub fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let origins: AllowOrigin = match config.allowed_origins {
+        None => Any.into(),
+        Some(ref origins) => AllowOrigin::list(
+            origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        ),
+    };
+    let methods: AllowMethods = match config.allowed_methods {
+        None => Any.into(),
+        Some(ref methods) => AllowMethods::list(
+            methods
+                .iter()
+                .filter_map(|method| method.parse::<Method>().ok()),
+        ),
+    };
+    let headers: AllowHeaders = match config.allowed_headers {
+        None => Any.into(),
+        Some(ref headers) => AllowHeaders::list(
+            headers
+                .iter()
+                .filter_map(|name| name.parse::<HeaderName>().ok()),
+        ),
+    };
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(config.allow_credentials)
+        .expose_headers([header::ETAG])
+        .max_age(Duration::from_secs(config.max_age_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CorsPreset;
+    use axum::{body::Body, extract::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn preflight(config: &CorsConfig, origin: &str) -> axum::response::Response {
+        let app = Router::new()
+            .route("/pois", get(|| async { "ok" }))
+            .layer(cors_layer(config));
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/pois")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_production_allows_listed_origins_only() {
+        let config = CorsConfig {
+            allowed_origins: Some(vec!["https://app.example.com".to_string()]),
+            allow_credentials: true,
+            ..CorsConfig::default()
+        };
+
+        let allowed = preflight(&config, "https://app.example.com").await;
+        let headers = allowed.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+
+        let denied = preflight(&config, "https://evil.example.com").await;
+        assert!(!denied
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[tokio::test]
+    async fn test_development_allows_any_origin() {
+        let config = CorsConfig::preset(CorsPreset::Development);
+        let response = preflight(&config, "http://localhost:5173").await;
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+}
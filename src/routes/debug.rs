@@ -1,4 +1,5 @@
 use crate::db::queries;
+use crate::error::AppError;
 use crate::AppState;
 use axum::{extract::State, Json};
 use serde_json::{json, Value};
@@ -33,19 +34,55 @@ pub async fn health_check(State(state): State<Arc<AppState>>) -> Json<Value> {
                 "backend": cache.backend_name(),
                 "hits": stats.hits,
                 "misses": stats.misses,
-                "hit_rate": format!("{:.1}%", stats.hit_rate)
+                "hit_rate": format!("{:.1}%", stats.hit_rate),
+                "stale_entries": stats.stale_entries,
+                "decode_failures": stats.decode_failures
             });
         } else {
-            status["checks"]["cache"] = json!({"status": "error", "backend": cache.backend_name(), "message": "Cache connection failed"});
+            let message = if cache.get_stats().await.disabled {
+                "Cache disabled after repeated failures"
+            } else {
+                "Cache connection failed"
+            };
+            status["checks"]["cache"] =
+                json!({"status": "error", "backend": cache.backend_name(), "message": message});
             status["status"] = json!("degraded");
         }
     } else {
         status["checks"]["cache"] = json!({"status": "not_configured"});
     }
 
+    // Failed generation attempts by reason, since startup
+    status["checks"]["generation_failures"] = json!(state.route_generator.failure_stats());
+
+    // Fallback policy and recently failed dependencies
+    status["checks"]["dependencies"] = state.guard.status();
+
     Json(status)
 }
 
+/// GET /debug/cache - Cache backend, its state and counters (works with any backend).
+/// `disabled` means Redis failed repeatedly and is skipped until a background
+/// probe sees it answer again.
+pub async fn cache_status(State(state): State<Arc<AppState>>) -> Json<Value> {
+    let Some(ref cache) = state.cache else {
+        return Json(json!({"status": "not_configured"}));
+    };
+    let stats = cache.get_stats().await;
+    let status = if stats.disabled {
+        "disabled"
+    } else if stats.connected {
+        "ok"
+    } else {
+        "error"
+    };
+    Json(json!({
+        "status": status,
+        "backend": cache.backend_name(),
+        "stats": stats
+    }))
+}
+
 /// GET /debug/coverage - Return convex hull of all POIs as GeoJSON (PostgreSQL only)
 pub async fn data_coverage(State(pool): State<PgPool>) -> Json<Value> {
     match queries::get_poi_coverage(&pool).await {
@@ -63,3 +100,9 @@ pub async fn data_coverage(State(pool): State<PgPool>) -> Json<Value> {
         })),
     }
 }
+
+/// GET /debug/tasks - Recent scheduled task runs, newest first (PostgreSQL only)
+pub async fn task_runs(State(pool): State<PgPool>) -> Result<Json<Value>, AppError> {
+    let runs = queries::recent_task_runs(&pool, 50).await?;
+    Ok(Json(json!({ "runs": runs })))
+}
@@ -0,0 +1,126 @@
+//! Strong ETags and conditional GETs for route resources.
+//!
+//! A route's content is fixed once generated, so its ETag is derived from the
+//! route id and [`ROUTE_ALGORITHM_VERSION`] rather than by hashing the body.
+//! A revision covers parts that can change afterwards: a counter when they
+//! only grow (e.g. ratings), or a [`revision_hash`] of their content.
+
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::constants::ROUTE_ALGORITHM_VERSION;
+use crate::routes::compression::GZIP_ETAG_SUFFIX;
+
+/// Quoted strong ETag for a route at a given revision
+pub fn route_etag(id: &Uuid, revision: u64) -> String {
+    format!("\"{}-v{}-r{}\"", id, ROUTE_ALGORITHM_VERSION, revision)
+}
+
+/// Revision derived from the serialized `value`, for parts of a route that
+/// can change in place
+pub fn revision_hash<T: Serialize>(value: &T) -> u64 {
+    xxhash_rust::xxh3::xxh3_64(&serde_json::to_vec(value).unwrap_or_default())
+}
+
+/// Whether the request's `If-None-Match` matches `etag`. Uses weak comparison
+/// as RFC 9110 requires, and treats the gzip variant of a tag as the same tag.
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let wanted = opaque_tag(etag);
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || opaque_tag(tag) == wanted)
+}
+
+/// Tag without the weak prefix, quotes and gzip suffix
+fn opaque_tag(tag: &str) -> &str {
+    let tag = tag.trim_start_matches("W/").trim_matches('"');
+    tag.strip_suffix(GZIP_ETAG_SUFFIX).unwrap_or(tag)
+}
+
+/// JSON response with an ETag, or `304 Not Modified` when the client already
+/// has this version. Clients must revalidate before reusing a cached copy.
+pub fn json_with_etag<T: Serialize>(
+    request_headers: &HeaderMap,
+    etag: String,
+    body: &T,
+) -> Response {
+    let mut response = if if_none_match(request_headers, &etag) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        Json(body).into_response()
+    };
+
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, value);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(if_none_match: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(if_none_match).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_route_etag_changes_with_revision() {
+        let id = Uuid::new_v4();
+        assert_eq!(route_etag(&id, 0), route_etag(&id, 0));
+        assert_ne!(route_etag(&id, 0), route_etag(&id, 1));
+        assert!(route_etag(&id, 0).starts_with('"'));
+    }
+
+    #[test]
+    fn test_revision_hash_changes_with_content() {
+        let removed = serde_json::json!([{ "reason": "poi_removed", "name": "A" }]);
+        let moved = serde_json::json!([{ "reason": "poi_moved", "name": "B" }]);
+        assert_eq!(revision_hash(&removed), revision_hash(&removed.clone()));
+        assert_ne!(revision_hash(&removed), revision_hash(&moved));
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = route_etag(&Uuid::new_v4(), 2);
+        let gzip_etag = format!("{}{}\"", etag.trim_end_matches('"'), GZIP_ETAG_SUFFIX);
+
+        assert!(if_none_match(&headers(&etag), &etag));
+        assert!(if_none_match(&headers(&format!("W/{}", etag)), &etag));
+        assert!(if_none_match(&headers(&gzip_etag), &etag));
+        assert!(if_none_match(
+            &headers(&format!("\"other\", {}", etag)),
+            &etag
+        ));
+        assert!(if_none_match(&headers("*"), &etag));
+
+        assert!(!if_none_match(&headers("\"other\""), &etag));
+        assert!(!if_none_match(&HeaderMap::new(), &etag));
+    }
+
+    #[test]
+    fn test_json_with_etag_not_modified() {
+        let etag = route_etag(&Uuid::new_v4(), 0);
+        let fresh = json_with_etag(&HeaderMap::new(), etag.clone(), &serde_json::json!({}));
+        assert_eq!(fresh.status(), StatusCode::OK);
+        assert_eq!(fresh.headers()[header::ETAG], etag.as_str());
+
+        let cached = json_with_etag(&headers(&etag), etag.clone(), &serde_json::json!({}));
+        assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(cached.headers()[header::ETAG], etag.as_str());
+        assert_eq!(cached.headers()[header::CACHE_CONTROL], "no-cache");
+    }
+}
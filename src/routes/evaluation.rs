@@ -1,14 +1,26 @@
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    Json,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
 use serde::Deserialize;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::db::queries;
+use crate::constants::{
+    EVALUATION_EXPORT_PAGE_SIZE, SAMPLER_CANDIDATE_POOL, SAMPLER_MAX_RATINGS_PER_ROUTE,
+};
+use crate::db::queries::{self, EvaluationExportRow};
 use crate::error::AppError;
-use crate::models::evaluation::{EvaluationStats, RatingRequest};
+use crate::models::evaluation::{
+    EvaluationCursor, EvaluationStats, RatingCandidate, RatingRequest,
+};
+use crate::routes::{etag, pagination};
+use crate::services::evaluation_export::{self, ExportFormat};
+use crate::services::events::{EventBus, LifecycleEvent};
+use crate::services::{rating_agreement, rating_sampler};
 
 #[derive(Deserialize)]
 pub struct ListParams {
@@ -16,36 +28,222 @@ pub struct ListParams {
     pub limit: i64,
     #[serde(default)]
     pub offset: i64,
+    /// `next_cursor` from the previous page; replaces `offset`
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Comma-separated fields to keep on each route
+    #[serde(default)]
+    pub fields: Option<String>,
 }
 
 fn default_limit() -> i64 {
     20
 }
 
-/// GET /api/v1/evaluations - List evaluated routes
+/// GET /api/v1/evaluations - List evaluated routes, newest first.
+/// Pages by `cursor` (stable as routes are added) or by `offset`.
 pub async fn list_evaluations(
     State(pool): State<PgPool>,
     Query(params): Query<ListParams>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let limit = params.limit.clamp(1, 100);
-    let routes = queries::list_evaluated_routes(&pool, limit, params.offset).await?;
+    let after = params
+        .cursor
+        .as_deref()
+        .map(pagination::decode_cursor::<EvaluationCursor>)
+        .transpose()?;
+    if after.is_some() && params.offset != 0 {
+        return Err(AppError::InvalidRequest(
+            "cursor and offset cannot be combined".to_string(),
+        ));
+    }
+
+    let mut routes =
+        queries::list_evaluated_routes(&pool, limit + 1, params.offset, after.as_ref()).await?;
+    let next_cursor = if routes.len() as i64 > limit {
+        routes.truncate(limit as usize);
+        routes.last().and_then(|last| {
+            last.created_at.clone().map(|created_at| {
+                pagination::encode_cursor(&EvaluationCursor {
+                    created_at,
+                    id: last.id,
+                })
+            })
+        })
+    } else {
+        None
+    };
+    let fields = pagination::FieldSelection::parse(params.fields.as_deref());
 
     Ok(Json(serde_json::json!({
-        "routes": routes,
+        "routes": fields.project(&routes)?,
         "limit": limit,
         "offset": params.offset,
+        "next_cursor": next_cursor,
     })))
 }
 
-/// GET /api/v1/evaluations/:id - Get route detail with metrics and ratings
+#[derive(Deserialize)]
+pub struct ExportParams {
+    /// `csv` (default) or `parquet`
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// GET /api/v1/admin/evaluations/export - Every evaluated route with its
+/// metrics and averaged ratings, as CSV (streamed) or Parquet. Mounted by
+/// `admin::create_admin_pg_router`, behind the admin token.
+pub async fn export_evaluations(
+    State(pool): State<PgPool>,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, AppError> {
+    let format: ExportFormat = params
+        .format
+        .as_deref()
+        .unwrap_or("csv")
+        .parse()
+        .map_err(AppError::InvalidRequest)?;
+    let body = match format {
+        ExportFormat::Csv => Body::from_stream(csv_export_stream(pool)),
+        ExportFormat::Parquet => Body::from(parquet_export(&pool).await?),
+    };
+    let disposition = format!(
+        "attachment; filename=\"evaluations.{}\"",
+        format.file_extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Header, then one chunk per page of rows. A database error mid-export
+/// aborts the response, so clients see a truncated transfer, not bad data.
+fn csv_export_stream(
+    pool: PgPool,
+) -> impl futures::Stream<Item = Result<String, sqlx::Error>> + Send + 'static {
+    let header = futures::stream::once(async { Ok(evaluation_export::csv_header()) });
+    let pages = futures::stream::try_unfold(
+        (pool, None::<EvaluationCursor>, false),
+        |(pool, after, done)| async move {
+            if done {
+                return Ok(None);
+            }
+            let mut rows = queries::export_evaluated_routes(
+                &pool,
+                after.as_ref(),
+                EVALUATION_EXPORT_PAGE_SIZE,
+            )
+            .await?;
+            evaluation_export::round_start_points(&mut rows);
+            if rows.is_empty() {
+                return Ok(None);
+            }
+            let done = (rows.len() as i64) < EVALUATION_EXPORT_PAGE_SIZE;
+            let next = rows.last().map(export_cursor);
+            Ok(Some((
+                evaluation_export::csv_rows(&rows),
+                (pool, next, done),
+            )))
+        },
+    );
+    futures::StreamExt::chain(header, pages)
+}
+
+fn export_cursor(row: &EvaluationExportRow) -> EvaluationCursor {
+    EvaluationCursor {
+        created_at: row.created_at.clone(),
+        id: row.id,
+    }
+}
+
+#[cfg(feature = "parquet")]
+async fn parquet_export(pool: &PgPool) -> Result<Vec<u8>, AppError> {
+    let mut rows = Vec::new();
+    loop {
+        let mut page = queries::export_evaluated_routes(
+            pool,
+            rows.last().map(export_cursor).as_ref(),
+            EVALUATION_EXPORT_PAGE_SIZE,
+        )
+        .await?;
+        evaluation_export::round_start_points(&mut page);
+        let last_page = (page.len() as i64) < EVALUATION_EXPORT_PAGE_SIZE;
+        rows.extend(page);
+        if last_page {
+            break;
+        }
+    }
+    evaluation_export::to_parquet(&rows).map_err(AppError::Internal)
+}
+
+#[cfg(not(feature = "parquet"))]
+async fn parquet_export(_pool: &PgPool) -> Result<Vec<u8>, AppError> {
+    Err(AppError::InvalidRequest(
+        "Parquet export is not available in this build (enable the `parquet` feature)".to_string(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct NextParams {
+    #[serde(default = "default_next_limit")]
+    pub limit: usize,
+    /// Skip routes this rater has already rated
+    #[serde(default)]
+    pub rater_id: Option<String>,
+}
+
+fn default_next_limit() -> usize {
+    5
+}
+
+/// GET /api/v1/evaluations/next - Routes most worth rating next: those in
+/// sparsely rated metric regions, or whose system score disagrees with the
+/// ratings of similar routes
+pub async fn next_to_rate(
+    State(pool): State<PgPool>,
+    Query(params): Query<NextParams>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let limit = params.limit.clamp(1, 50);
+    let candidates = queries::list_rating_candidates(
+        &pool,
+        params.rater_id.as_deref(),
+        SAMPLER_MAX_RATINGS_PER_ROUTE,
+        SAMPLER_CANDIDATE_POOL,
+    )
+    .await?;
+    let rated = queries::list_rated_routes(&pool).await?;
+    let routes: Vec<RatingCandidate> = rating_sampler::next_to_rate(&candidates, &rated, limit);
+
+    Ok(Json(serde_json::json!({
+        "routes": routes,
+        "candidates_considered": candidates.len(),
+        "rated_routes": rated.len(),
+    })))
+}
+
+/// GET /api/v1/evaluations/:id - Get route detail with metrics and ratings.
+/// Sends an ETag (revised on each new rating) and honors `If-None-Match`.
 pub async fn get_evaluation(
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, AppError> {
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
     let route = queries::get_evaluated_route(&pool, id).await?;
 
     match route {
-        Some(r) => Ok(Json(serde_json::json!(r))),
+        Some(r) => {
+            let revision = r.ratings.as_ref().map_or(0, |ratings| ratings.len() as u64);
+            Ok(etag::json_with_etag(
+                &headers,
+                etag::route_etag(&r.id, revision),
+                &r,
+            ))
+        }
         None => Err(AppError::NotFound(format!(
             "Evaluated route {} not found",
             id
@@ -53,10 +251,36 @@ pub async fn get_evaluation(
     }
 }
 
+/// GET /api/v1/evaluations/:id/geometry - Stored (simplified) path and POIs
+/// of an evaluated route. 404 for routes stored before geometry was kept
+/// that the backfill could not recover.
+pub async fn get_evaluation_geometry(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let geometry = queries::get_evaluated_route_geometry(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Evaluated route {} not found", id)))?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Evaluated route {} has no stored geometry", id))
+        })?;
+    let path = geometry.path().map_err(AppError::Internal)?;
+
+    Ok(Json(serde_json::json!({
+        "id": id,
+        "points": geometry.points,
+        "path": path.iter().map(|c| [c.lng, c.lat]).collect::<Vec<_>>(),
+        "pois": geometry.pois,
+        "snapped_pois": geometry.snapped_pois,
+    })))
+}
+
 /// POST /api/v1/evaluations/:id/ratings - Submit a rating
+/// Publishes `route_rated` when the router has an [`EventBus`] extension
 pub async fn submit_rating(
     State(pool): State<PgPool>,
     Path(route_id): Path<Uuid>,
+    events: Option<Extension<EventBus>>,
     Json(req): Json<RatingRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     req.validate().map_err(AppError::InvalidRequest)?;
@@ -82,22 +306,42 @@ pub async fn submit_rating(
     )
     .await?;
 
+    if let Some(Extension(events)) = events {
+        events.publish(LifecycleEvent::RouteRated {
+            route_id,
+            rating_id,
+            overall_rating: req.overall_rating,
+        });
+    }
+
     Ok(Json(serde_json::json!({
         "id": rating_id,
         "route_id": route_id,
     })))
 }
 
-/// GET /api/v1/evaluations/stats - Correlation stats
+#[derive(Deserialize)]
+pub struct StatsParams {
+    /// Correlate metrics recomputed with this metric code version instead of
+    /// those stored at generation time
+    #[serde(default)]
+    pub metrics_version: Option<i32>,
+}
+
+/// GET /api/v1/evaluations/stats - Correlation and inter-rater agreement stats
 pub async fn evaluation_stats(
     State(pool): State<PgPool>,
+    Query(params): Query<StatsParams>,
 ) -> Result<Json<EvaluationStats>, AppError> {
     let (total_routes, total_ratings) = queries::get_evaluation_counts(&pool).await?;
-    let correlations = queries::get_correlation_data(&pool).await?;
+    let correlations = queries::get_correlation_data(&pool, params.metrics_version).await?;
+    let ratings = queries::list_all_ratings(&pool).await?;
 
     Ok(Json(EvaluationStats {
         total_routes,
         total_ratings,
+        metrics_version: params.metrics_version,
         correlations,
+        agreement: rating_agreement::rating_agreement(&ratings),
     }))
 }
@@ -0,0 +1,217 @@
+//! GeoJSON rendering of generated routes, negotiated with
+//! `Accept: application/geo+json` or `?format=geojson`.
+//!
+//! Each route becomes a LineString feature carrying the route's other fields
+//! as properties, followed by Point features for its waypoint POIs, snapped
+//! POIs and amenities. `feature_type` tells them apart and `route_id` links
+//! points to their route.
+
+use axum::http::{header, HeaderMap};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::error::{AppError, Result};
+use crate::models::{CoordinateFormat, Coordinates, Route};
+
+pub const GEOJSON_CONTENT_TYPE: &str = "application/geo+json";
+
+/// Route fields rendered as geometry or separate features rather than
+/// properties
+const ROUTE_GEOMETRY_FIELDS: [&str; 4] = ["path", "pois", "snapped_pois", "amenities"];
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FormatParams {
+    /// `json` (default) or `geojson`; overrides the Accept header
+    #[serde(default)]
+    pub format: Option<String>,
+    /// `object` (default) or `compact` `[lat, lng]` arrays; JSON only
+    #[serde(default)]
+    pub coords: Option<String>,
+    /// Decimals coordinates keep; JSON only
+    #[serde(default)]
+    pub precision: Option<u32>,
+}
+
+impl FormatParams {
+    pub fn coordinate_format(&self) -> Result<CoordinateFormat> {
+        CoordinateFormat::parse(self.coords.as_deref(), self.precision)
+            .map_err(AppError::InvalidRequest)
+    }
+}
+
+/// Whether to answer with GeoJSON: `?format=` wins, otherwise the Accept
+/// header must list `application/geo+json`
+pub fn wants_geojson(headers: &HeaderMap, params: &FormatParams) -> Result<bool> {
+    match params.format.as_deref().map(str::to_lowercase).as_deref() {
+        Some("geojson") => Ok(true),
+        Some("json") => Ok(false),
+        Some(other) => Err(AppError::InvalidRequest(format!(
+            "Invalid format: '{}' (expected json or geojson)",
+            other
+        ))),
+        None => Ok(headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|range| range.split(';').next())
+            .any(|media_type| media_type.trim().eq_ignore_ascii_case(GEOJSON_CONTENT_TYPE))),
+    }
+}
+
+/// All `routes` as one FeatureCollection
+pub fn feature_collection(routes: &[Route]) -> Value {
+    let mut features = Vec::new();
+    for (index, route) in routes.iter().enumerate() {
+        let mut properties = properties_of(route, &ROUTE_GEOMETRY_FIELDS);
+        properties.insert("feature_type".into(), json!("route"));
+        properties.insert("route_index".into(), json!(index));
+        features.push(json!({
+            "type": "Feature",
+            "id": route.id,
+            "geometry": {
+                "type": "LineString",
+                "coordinates": route.path.iter().map(position).collect::<Vec<_>>(),
+            },
+            "properties": properties,
+        }));
+
+        for poi in &route.pois {
+            features.push(point_feature("waypoint", route, &poi.poi.coordinates, poi));
+        }
+        for poi in &route.snapped_pois {
+            features.push(point_feature(
+                "snapped_poi",
+                route,
+                &poi.poi.coordinates,
+                poi,
+            ));
+        }
+        for poi in &route.amenities {
+            features.push(point_feature("amenity", route, &poi.poi.coordinates, poi));
+        }
+    }
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+fn point_feature<T: Serialize>(
+    feature_type: &str,
+    route: &Route,
+    coordinates: &Coordinates,
+    poi: &T,
+) -> Value {
+    let mut properties = properties_of(poi, &["coordinates"]);
+    properties.insert("feature_type".into(), json!(feature_type));
+    properties.insert("route_id".into(), json!(route.id));
+    json!({
+        "type": "Feature",
+        "geometry": { "type": "Point", "coordinates": position(coordinates) },
+        "properties": properties,
+    })
+}
+
+/// GeoJSON positions are longitude first
+fn position(coordinates: &Coordinates) -> Value {
+    json!([coordinates.lng, coordinates.lat])
+}
+
+fn properties_of<T: Serialize>(item: &T, skip: &[&str]) -> Map<String, Value> {
+    match serde_json::to_value(item) {
+        Ok(Value::Object(mut object)) => {
+            for field in skip {
+                object.remove(*field);
+            }
+            object
+        }
+        _ => Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Poi, PoiCategory, RoutePoi, SnappedPoi};
+    use axum::http::HeaderValue;
+
+    fn route() -> Route {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let museum = Poi::new(
+            "Louvre".to_string(),
+            PoiCategory::Museum,
+            Coordinates::new(48.8606, 2.3376).unwrap(),
+            95.0,
+        );
+        let park = Poi::new(
+            "Tuileries".to_string(),
+            PoiCategory::Park,
+            Coordinates::new(48.8635, 2.3275).unwrap(),
+            80.0,
+        );
+        let mut route = Route::new(
+            3.2,
+            40,
+            vec![start, museum.coordinates, start],
+            vec![RoutePoi::new(museum, 1, 1.6)],
+        );
+        route.snapped_pois = vec![SnappedPoi::new(park, 1.2, 40.0)];
+        route
+    }
+
+    #[test]
+    fn test_feature_collection_shape() {
+        let route = route();
+        let collection = feature_collection(std::slice::from_ref(&route));
+        assert_eq!(collection["type"], "FeatureCollection");
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features.len(), 3);
+
+        let line = &features[0];
+        assert_eq!(line["geometry"]["type"], "LineString");
+        assert_eq!(line["geometry"]["coordinates"][0], json!([2.3522, 48.8566]));
+        assert_eq!(line["properties"]["feature_type"], "route");
+        assert_eq!(line["properties"]["distance_km"], 3.2);
+        assert!(line["properties"].get("path").is_none());
+        assert!(line["properties"].get("pois").is_none());
+
+        let waypoint = &features[1];
+        assert_eq!(
+            waypoint["geometry"]["coordinates"],
+            json!([2.3376, 48.8606])
+        );
+        assert_eq!(waypoint["properties"]["feature_type"], "waypoint");
+        assert_eq!(waypoint["properties"]["name"], "Louvre");
+        assert_eq!(waypoint["properties"]["order_in_route"], 1);
+        assert_eq!(waypoint["properties"]["route_id"], json!(route.id));
+        assert!(waypoint["properties"].get("coordinates").is_none());
+        assert_eq!(features[2]["properties"]["feature_type"], "snapped_poi");
+    }
+
+    #[test]
+    fn test_negotiation() {
+        let mut headers = HeaderMap::new();
+        let none = FormatParams::default();
+        assert!(!wants_geojson(&headers, &none).unwrap());
+
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/json;q=0.5, application/geo+json"),
+        );
+        assert!(wants_geojson(&headers, &none).unwrap());
+
+        let json_param = FormatParams {
+            format: Some("json".to_string()),
+            ..FormatParams::default()
+        };
+        assert!(!wants_geojson(&headers, &json_param).unwrap());
+        let geojson_param = FormatParams {
+            format: Some("GeoJSON".to_string()),
+            ..FormatParams::default()
+        };
+        assert!(wants_geojson(&HeaderMap::new(), &geojson_param).unwrap());
+        let bad = FormatParams {
+            format: Some("kml".to_string()),
+            ..FormatParams::default()
+        };
+        assert!(wants_geojson(&headers, &bad).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use crate::constants::{HEATMAP_DEFAULT_CELL_M, HEATMAP_MAX_POIS};
+use crate::error::{AppError, Result};
+use crate::models::BoundingBox;
+use crate::routes::geojson::GEOJSON_CONTENT_TYPE;
+use crate::services::heatmap::{self, HeatmapGrid};
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Deserialize)]
+pub struct HeatmapParams {
+    /// `min_lng,min_lat,max_lng,max_lat`
+    pub bbox: String,
+    /// Cell edge in meters (default: 100)
+    #[serde(default)]
+    pub cell_m: Option<f64>,
+}
+
+/// GET /heatmap?bbox= - POI density and popularity per grid cell, as GeoJSON
+pub async fn poi_heatmap(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HeatmapParams>,
+) -> Result<Response> {
+    let bbox = BoundingBox::parse(&params.bbox).map_err(AppError::InvalidRequest)?;
+    let cell_m = params.cell_m.unwrap_or(HEATMAP_DEFAULT_CELL_M);
+    let grid = HeatmapGrid::new(bbox, cell_m)?;
+
+    let pois = state
+        .poi_repo
+        .find_in_bbox(
+            bbox.min_lat,
+            bbox.max_lat,
+            bbox.min_lng,
+            bbox.max_lng,
+            None,
+            HEATMAP_MAX_POIS,
+        )
+        .await?;
+    let truncated = pois.len() as i64 >= HEATMAP_MAX_POIS;
+    let cells = grid.aggregate(&pois);
+
+    tracing::info!(
+        "Heatmap: bbox={}, cell={}m, {} POIs in {} cells{}",
+        params.bbox,
+        cell_m,
+        pois.len(),
+        cells.len(),
+        if truncated { " (truncated)" } else { "" }
+    );
+
+    Ok((
+        [(header::CONTENT_TYPE, GEOJSON_CONTENT_TYPE)],
+        Json(heatmap::feature_collection(&cells, cell_m, truncated)),
+    )
+        .into_response())
+}
@@ -1,71 +1,434 @@
-use crate::cache::{self, RoutePreferencesHash};
+use crate::cache::{self, CacheTtl, RoutePreferencesHash};
 use crate::error::{AppError, Result};
+use crate::evaluation::shadow::ShadowRequest;
 use crate::models::route::{LoopRouteRequest, RouteResponse};
+use crate::models::{CoordinateFormat, Departure, Route};
+use crate::routes::geojson::{self, FormatParams, GEOJSON_CONTENT_TYPE};
+use crate::services::dependency_guard::Dependency;
+use crate::services::events::LifecycleEvent;
+use crate::services::request_log::RequestLogRecord;
+use crate::services::route_generator::progress::{GenerationEvent, ProgressSink};
+use crate::services::solar;
+use crate::services::tenant::{tenant_cache_key, TenantId, TenantUsage};
 use crate::AppState;
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap},
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::Instant;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+
+/// Endpoint names recorded in the request log
+const LOOP_ENDPOINT: &str = "/routes/loop";
+const STREAM_ENDPOINT: &str = "/routes/loop/stream";
 
 /// POST /routes/loop
-/// Generate loop routes that start and end at the same point
+/// Generate loop routes that start and end at the same point. Answers with a
+/// GeoJSON FeatureCollection for `Accept: application/geo+json` or
+/// `?format=geojson`.
 pub async fn create_loop_route(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<LoopRouteRequest>,
-) -> Result<Json<RouteResponse>> {
-    // Validate request
+    tenant: TenantId,
+    Query(format): Query<FormatParams>,
+    headers: HeaderMap,
+    Json(mut request): Json<LoopRouteRequest>,
+) -> Result<Response> {
+    record_usage(&state, &tenant, |u| u.route_requests += 1);
+    let as_geojson = geojson::wants_geojson(&headers, &format)?;
+    let coordinates = format.coordinate_format()?;
+    let distance_km = prepare_request(&state, &mut request)?;
+    let cache_key = loop_cache_key(&state, &tenant, &request, distance_km);
+
+    // Check cache first
+    let started = Instant::now();
+    if let Some(routes) = cached_routes(
+        &state,
+        LOOP_ENDPOINT,
+        &tenant,
+        &request,
+        &cache_key,
+        started,
+    )
+    .await
+    {
+        return Ok(respond(routes, as_geojson, coordinates));
+    }
+
+    // Cache miss: dependencies under a cached-only policy must be up
+    state.guard.admit(Dependency::Postgres)?;
+    state
+        .guard
+        .admit(state.route_generator.routing_dependency())?;
+
+    // Generate routes
+    let result = state
+        .route_generator
+        .generate_loop_route(
+            request.start_point,
+            distance_km,
+            request.distance_tolerance,
+            &request.mode,
+            &request.preferences,
+        )
+        .await;
+    let routes = finish_generation(
+        &state,
+        LOOP_ENDPOINT,
+        &tenant,
+        &request,
+        distance_km,
+        &cache_key,
+        result,
+        started,
+    )
+    .await?;
+    Ok(respond(routes, as_geojson, coordinates))
+}
+
+/// POST /routes/loop/stream
+/// Same request as `/routes/loop`, answered with Server-Sent Events while
+/// generation runs: `poi_discovery`, `tolerance_level` and `route_candidate`
+/// as they happen, then `done` with the usual route response (or `error`
+/// with the usual error body). Validation and admission errors are plain
+/// JSON responses, before the stream starts. Generation stops if the client
+/// disconnects.
+pub async fn stream_loop_route(
+    State(state): State<Arc<AppState>>,
+    tenant: TenantId,
+    Json(mut request): Json<LoopRouteRequest>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>> {
+    record_usage(&state, &tenant, |u| u.route_requests += 1);
+    let distance_km = prepare_request(&state, &mut request)?;
+    let cache_key = loop_cache_key(&state, &tenant, &request, distance_km);
+    let started = Instant::now();
+    let cached = cached_routes(
+        &state,
+        STREAM_ENDPOINT,
+        &tenant,
+        &request,
+        &cache_key,
+        started,
+    )
+    .await;
+    if cached.is_none() {
+        state.guard.admit(Dependency::Postgres)?;
+        state
+            .guard
+            .admit(state.route_generator.routing_dependency())?;
+    }
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let generation = tokio::spawn(async move {
+        if let Some(routes) = cached {
+            return Ok(routes);
+        }
+        let progress = ProgressSink::new(sender);
+        let generate = state.route_generator.generate_loop_route_with_progress(
+            request.start_point,
+            distance_km,
+            request.distance_tolerance,
+            &request.mode,
+            &request.preferences,
+            &progress,
+        );
+        // A client that disconnects drops the progress receiver: stop
+        // generating (and calling the backends) for nobody
+        let result = tokio::select! {
+            result = generate => result,
+            () = progress.closed() => {
+                tracing::debug!("Stream client disconnected, generation stopped");
+                return Err(AppError::Internal("Client disconnected".to_string()));
+            }
+        };
+        finish_generation(
+            &state,
+            STREAM_ENDPOINT,
+            &tenant,
+            &request,
+            distance_km,
+            &cache_key,
+            result,
+            started,
+        )
+        .await
+    });
+
+    // The progress channel closes when generation drops its sink
+    let progress = stream::unfold(receiver, |mut receiver| async move {
+        let event = receiver.recv().await?;
+        Some((progress_event(&event), receiver))
+    });
+    let outcome = stream::once(async move {
+        match generation.await {
+            Ok(Ok(routes)) => json_event("done", &RouteResponse { routes }),
+            Ok(Err(e)) => error_event(e).await,
+            Err(e) => error_event(AppError::Internal(format!("Generation task failed: {e}"))).await,
+        }
+    });
+    Ok(Sse::new(progress.chain(outcome).map(Ok)).keep_alive(KeepAlive::default()))
+}
+
+fn progress_event(event: &GenerationEvent) -> Event {
+    json_event(event.name(), event)
+}
+
+fn json_event(name: &str, data: &impl Serialize) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(data)
+        .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()))
+}
+
+/// The body `/routes/loop` would have answered `error` with, plus its status
+async fn error_event(error: AppError) -> Event {
+    let response = error.into_response();
+    let status = response.status().as_u16();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok());
+    let mut data = body.unwrap_or_else(|| serde_json::json!({}));
+    data["status"] = status.into();
+    json_event("error", &data)
+}
+
+/// Validate `request` and apply its departure. Returns the target distance.
+fn prepare_request(state: &AppState, request: &mut LoopRouteRequest) -> Result<f64> {
     request.validate().map_err(AppError::InvalidRequest)?;
+    request.preferences.departure = request.departure();
+    let distance_km = request
+        .target_distance_km()
+        .ok_or_else(|| AppError::InvalidRequest("distance_km is required".to_string()))?;
 
+    let logged_start = state
+        .privacy
+        .map_or(request.start_point, |p| p.for_logs(&request.start_point));
     tracing::info!(
-        lat = request.start_point.lat,
-        lng = request.start_point.lng,
-        distance_km = request.distance_km,
+        lat = logged_start.lat,
+        lng = logged_start.lng,
+        distance_km,
         mode = %request.mode.mapbox_profile(),
         tolerance_km = request.distance_tolerance,
         "Loop route request: ({:.4}, {:.4}), {:.1}km, mode={}, tolerance={:.2}km",
-        request.start_point.lat, request.start_point.lng,
-        request.distance_km, request.mode.mapbox_profile(), request.distance_tolerance
+        logged_start.lat, logged_start.lng,
+        distance_km, request.mode.mapbox_profile(), request.distance_tolerance
     );
+    Ok(distance_km)
+}
 
-    // Build cache key
+fn loop_cache_key(
+    state: &AppState,
+    tenant: &TenantId,
+    request: &LoopRouteRequest,
+    distance_km: f64,
+) -> String {
     let prefs_hash = RoutePreferencesHash::new(
         request.preferences.poi_categories.as_deref(),
         request.preferences.hidden_gems,
+    )
+    .with_excluded_categories(request.preferences.excluded_poi_categories.as_deref())
+    .with_min_separation_km(request.preferences.poi_min_separation_km)
+    .with_pace_min_per_km(request.preferences.pace_min_per_km)
+    .with_min_paved_fraction(request.preferences.min_paved_fraction)
+    .with_max_busy_road_fraction(request.preferences.max_busy_road_fraction)
+    .with_surface(request.preferences.surface)
+    .with_prefer_green(request.preferences.prefer_green)
+    .with_step_free(request.preferences.step_free)
+    .with_minimize_exposure(request.preferences.minimize_exposure)
+    .with_max_elevation_gain_m(request.preferences.max_elevation_gain_m)
+    .with_prefer_flat(request.preferences.prefer_flat)
+    .with_costing(&request.preferences.costing)
+    .with_include_visit_time(request.preferences.include_visit_time)
+    .with_golden_hour(
+        request.preferences.golden_hour,
+        request.preferences.departure,
+    )
+    .with_pareto(request.preferences.pareto)
+    .with_seed(request.preferences.seed)
+    .with_must_include(&request.preferences.must_include);
+    // Cycling modes share a Mapbox profile, so key on the mode itself
+    tenant_cache_key(
+        tenant,
+        &cache::loop_route_cache_key(
+            &request.start_point,
+            distance_km,
+            request.mode.as_str(),
+            &prefs_hash,
+            &state
+                .cache
+                .as_ref()
+                .map(|cache| cache.key_precision())
+                .unwrap_or_default(),
+        ),
+    )
+}
+
+/// Cached routes for `cache_key`, with their timelines
+async fn cached_routes(
+    state: &AppState,
+    endpoint: &str,
+    tenant: &TenantId,
+    request: &LoopRouteRequest,
+    cache_key: &str,
+    started: Instant,
+) -> Option<Vec<Route>> {
+    let cached_routes = state.cache.as_ref()?.get_cached_routes(cache_key).await?;
+    tracing::info!(
+        "Cache hit for loop route: {} routes returned",
+        cached_routes.len()
     );
-    let cache_key = cache::loop_route_cache_key(
-        &request.start_point,
-        request.distance_km,
-        request.mode.mapbox_profile(),
-        &prefs_hash,
+    log_sample(state, endpoint, request, Ok(&cached_routes), true, started);
+    record_usage(state, tenant, |u| u.cache_hits += 1);
+    Some(with_timelines(cached_routes, request))
+}
+
+/// Everything after a generation attempt: request log, usage, dependency
+/// health, lifecycle event, shadow sampling and caching. Returns the routes
+/// with their timelines.
+#[allow(clippy::too_many_arguments)]
+async fn finish_generation(
+    state: &AppState,
+    endpoint: &str,
+    tenant: &TenantId,
+    request: &LoopRouteRequest,
+    distance_km: f64,
+    cache_key: &str,
+    result: Result<Vec<Route>>,
+    started: Instant,
+) -> Result<Vec<Route>> {
+    log_sample(
+        state,
+        endpoint,
+        request,
+        result.as_deref().map_err(|e| e.to_string()),
+        false,
+        started,
     );
+    let routes = result.map_err(|e| {
+        state
+            .guard
+            .report_generation_error(&e, state.route_generator.routing_dependency());
+        record_usage(state, tenant, |u| u.failed_requests += 1);
+        e
+    })?;
+    state
+        .guard
+        .report_success(state.route_generator.routing_dependency());
+    record_usage(state, tenant, |u| u.routes_generated += routes.len() as u64);
+    state.events.publish(LifecycleEvent::RouteGenerated {
+        tenant: tenant.clone(),
+        route_ids: routes.iter().map(|r| r.id).collect(),
+        mode: request.mode.clone(),
+        distance_km,
+    });
 
-    // Check cache first
-    if let Some(ref cache) = state.cache {
-        if let Some(cached_routes) = cache.get_cached_routes(&cache_key).await {
-            tracing::info!(
-                "Cache hit for loop route: {} routes returned",
-                cached_routes.len()
+    // Shadow mode: replay a sample of requests with the candidate strategy in the background
+    if let Some(ref shadow) = state.shadow {
+        if shadow.should_sample() {
+            shadow.spawn(
+                ShadowRequest {
+                    start: request.start_point,
+                    distance_km,
+                    distance_tolerance: request.distance_tolerance,
+                    mode: request.mode.clone(),
+                    preferences: request.preferences.clone(),
+                },
+                routes.clone(),
+                started.elapsed(),
             );
-            return Ok(Json(RouteResponse {
-                routes: cached_routes,
-            }));
         }
     }
 
-    // Generate routes
-    let routes = state
-        .route_generator
-        .generate_loop_route(
-            request.start_point,
-            request.distance_km,
-            request.distance_tolerance,
-            &request.mode,
-            &request.preferences,
-        )
-        .await?;
-
     // Cache the results
     if let Some(ref cache) = state.cache {
-        cache.cache_routes(&cache_key, &routes).await;
+        cache
+            .cache_routes(cache_key, &routes, CacheTtl::for_routes(&routes))
+            .await;
+    }
+
+    Ok(with_timelines(routes, request))
+}
+
+fn respond(routes: Vec<Route>, as_geojson: bool, coordinates: CoordinateFormat) -> Response {
+    if as_geojson {
+        (
+            [(header::CONTENT_TYPE, GEOJSON_CONTENT_TYPE)],
+            Json(geojson::feature_collection(&routes)),
+        )
+            .into_response()
+    } else if coordinates.is_default() {
+        Json(RouteResponse { routes }).into_response()
+    } else {
+        match serde_json::to_value(RouteResponse { routes }) {
+            Ok(mut response) => {
+                coordinates.apply(&mut response);
+                Json(response).into_response()
+            }
+            Err(e) => {
+                AppError::Internal(format!("Failed to serialize routes: {}", e)).into_response()
+            }
+        }
+    }
+}
+
+/// Clock times depend on the request, not the route, so they are added
+/// after caching. Golden-hour routes without a requested time are planned
+/// to end at the next sunset.
+fn with_timelines(routes: Vec<Route>, request: &LoopRouteRequest) -> Vec<Route> {
+    let departure = request.departure().or_else(|| {
+        request.preferences.golden_hour.then(|| {
+            solar::next_sunset(OffsetDateTime::now_utc(), &request.start_point)
+                .map(Departure::ArriveBy)
+        })?
+    });
+    match departure {
+        Some(departure) => routes
+            .into_iter()
+            .map(|route| route.with_timeline(departure))
+            .collect(),
+        None => routes,
+    }
+}
+
+/// Write a scrubbed record of a request to `endpoint` to the request log,
+/// if enabled and sampled. In privacy mode the start point is jittered
+/// before the usual rounding.
+fn log_sample(
+    state: &AppState,
+    endpoint: &str,
+    request: &LoopRouteRequest,
+    outcome: std::result::Result<&[Route], String>,
+    cache_hit: bool,
+    started: Instant,
+) {
+    if let Some(ref logger) = state.request_log {
+        if logger.should_sample() {
+            let mut request = request.clone();
+            if let Some(ref privacy) = state.privacy {
+                privacy.scrub_request(&mut request);
+            }
+            logger.log(&RequestLogRecord::new(
+                endpoint,
+                &request,
+                outcome,
+                cache_hit,
+                started.elapsed().as_millis() as u64,
+            ));
+        }
     }
+}
 
-    Ok(Json(RouteResponse { routes }))
+/// Update the tenant's usage counters (multi-tenant mode only)
+fn record_usage(state: &AppState, tenant: &TenantId, update: impl FnOnce(&mut TenantUsage)) {
+    if let Some(ref tenants) = state.tenants {
+        tenants.record(tenant, update);
+    }
 }
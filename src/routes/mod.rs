@@ -1,7 +1,21 @@
-pub mod debug;
-pub mod evaluation;
-pub mod loop_route;
-pub mod pois;
+pub mod admin;
+pub(crate) mod areas;
+pub(crate) mod artifacts;
+pub mod auth;
+pub mod compression;
+pub mod cors;
+pub(crate) mod debug;
+pub(crate) mod etag;
+pub(crate) mod evaluation;
+pub(crate) mod geojson;
+pub(crate) mod heatmap;
+pub(crate) mod loop_route;
+pub(crate) mod pagination;
+pub(crate) mod pois;
+pub(crate) mod saved_routes;
+pub mod share;
+pub(crate) mod telemetry;
+pub(crate) mod usage;
 
 use axum::{
     routing::{get, post},
@@ -16,18 +30,36 @@ use crate::AppState;
 pub fn create_router(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/routes/loop", post(loop_route::create_loop_route))
+        .route("/routes/loop/stream", post(loop_route::stream_loop_route))
         .route("/pois", get(pois::query_pois))
+        .route("/heatmap", get(heatmap::poi_heatmap))
         .route("/debug/health", get(debug::health_check))
+        .route("/debug/cache", get(debug::cache_status))
+        .route("/usage", get(usage::tenant_usage))
+        .route("/artifacts/{*key}", get(artifacts::get_artifact))
         .with_state(state)
 }
 
 /// PostgreSQL-only routes (evaluation + PostGIS coverage)
 pub fn create_pg_router(pool: PgPool) -> Router {
     Router::new()
+        .route("/areas/suggest", get(areas::suggest_distance))
         .route("/debug/coverage", get(debug::data_coverage))
+        .route("/debug/tasks", get(debug::task_runs))
         .route("/evaluations", get(evaluation::list_evaluations))
         .route("/evaluations/stats", get(evaluation::evaluation_stats))
+        .route("/evaluations/next", get(evaluation::next_to_rate))
         .route("/evaluations/{id}", get(evaluation::get_evaluation))
+        .route(
+            "/evaluations/{id}/geometry",
+            get(evaluation::get_evaluation_geometry),
+        )
         .route("/evaluations/{id}/ratings", post(evaluation::submit_rating))
+        .route("/routes", get(saved_routes::list_saved_routes))
+        .route("/routes/{id}", get(saved_routes::get_route))
+        .route("/routes/{id}/save", post(saved_routes::save_route))
+        .route("/routes/{id}/share", post(share::share_route))
+        .route("/share/{token}", get(share::get_shared_route))
+        .route("/telemetry/snaps", post(telemetry::record_snap_feedback))
         .with_state(pool)
 }
@@ -0,0 +1,118 @@
+//! Cursor pagination and `?fields=` sparse fieldsets for list endpoints.
+//!
+//! A cursor is the sort key of the last item on a page, serialized to JSON and
+//! hex-encoded so clients treat it as opaque. Each endpoint defines its own key
+//! type; pages stay stable when rows are added ahead of the cursor, unlike
+//! offsets.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{AppError, Result};
+
+/// Opaque cursor for `key`
+pub fn encode_cursor<K: Serialize>(key: &K) -> String {
+    hex::encode(serde_json::to_vec(key).unwrap_or_default())
+}
+
+/// Sort key from a cursor returned by [`encode_cursor`]
+pub fn decode_cursor<K: DeserializeOwned>(cursor: &str) -> Result<K> {
+    hex::decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| AppError::InvalidRequest("Invalid cursor".to_string()))
+}
+
+/// Top-level fields a client asked for with `?fields=a,b`; all fields when
+/// the parameter is absent or empty
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldSelection(Option<Vec<String>>);
+
+impl FieldSelection {
+    pub fn parse(fields: Option<&str>) -> Self {
+        let names: Vec<String> = fields
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        FieldSelection((!names.is_empty()).then_some(names))
+    }
+
+    /// Serialize `items`, keeping only the selected fields of each object.
+    /// Unknown field names are ignored.
+    pub fn project<T: Serialize>(&self, items: &[T]) -> Result<Vec<Value>> {
+        items
+            .iter()
+            .map(|item| {
+                let value = serde_json::to_value(item)
+                    .map_err(|e| AppError::Internal(format!("Serialization failed: {}", e)))?;
+                Ok(self.apply(value))
+            })
+            .collect()
+    }
+
+    fn apply(&self, value: Value) -> Value {
+        match (&self.0, value) {
+            (Some(names), Value::Object(mut object)) => Value::Object(
+                names
+                    .iter()
+                    .filter_map(|name| object.remove_entry(name.as_str()))
+                    .collect(),
+            ),
+            (_, value) => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Key {
+        created_at: String,
+        id: Uuid,
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let key = Key {
+            created_at: "2024-06-01 12:00:00.123456+00".to_string(),
+            id: Uuid::new_v4(),
+        };
+        let cursor = encode_cursor(&key);
+        assert!(cursor.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(decode_cursor::<Key>(&cursor).unwrap(), key);
+    }
+
+    #[test]
+    fn test_invalid_cursor_is_a_bad_request() {
+        assert!(matches!(
+            decode_cursor::<Key>("not-hex"),
+            Err(AppError::InvalidRequest(_))
+        ));
+        assert!(decode_cursor::<Key>(&hex::encode("{}")).is_err());
+    }
+
+    #[test]
+    fn test_field_selection() {
+        let items = vec![json!({"id": 1, "name": "a", "score": 2.0})];
+        let all = FieldSelection::parse(None).project(&items).unwrap();
+        assert_eq!(all, items);
+        assert_eq!(
+            FieldSelection::parse(Some(" , ")),
+            FieldSelection::default()
+        );
+
+        let some = FieldSelection::parse(Some("id, score,missing"))
+            .project(&items)
+            .unwrap();
+        assert_eq!(some, vec![json!({"id": 1, "score": 2.0})]);
+    }
+}
@@ -1,5 +1,7 @@
+use crate::constants::POI_PAGINATION_MAX_DEPTH;
 use crate::error::{AppError, Result};
 use crate::models::{Coordinates, Poi, PoiCategory};
+use crate::routes::pagination::{self, FieldSelection};
 use crate::AppState;
 use axum::{
     extract::{Query, State},
@@ -7,6 +9,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use uuid::Uuid;
 
 /// Query parameters for POI search
 #[derive(Debug, Deserialize)]
@@ -24,6 +27,21 @@ pub struct PoiQueryParams {
     /// Maximum number of results (default: 50, max: 200)
     #[serde(default = "default_limit")]
     pub limit: u32,
+    /// `next_cursor` from the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Comma-separated fields to keep on each POI
+    #[serde(default)]
+    pub fields: Option<String>,
+}
+
+/// Position in the nearest-first listing: the last POI returned and how
+/// many came before it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoiCursor {
+    pub distance_m: f64,
+    pub id: Uuid,
+    pub seen: u32,
 }
 
 fn default_radius() -> f64 {
@@ -91,12 +109,15 @@ impl PoiQueryParams {
 /// Response for POI queries
 #[derive(Debug, Serialize)]
 pub struct PoiResponse {
-    /// List of POIs found
-    pub pois: Vec<Poi>,
-    /// Total count of results
+    /// POIs on this page, nearest first (only the requested `fields`)
+    pub pois: Vec<serde_json::Value>,
+    /// Number of POIs on this page
     pub count: usize,
     /// Search parameters used
     pub query: PoiQueryInfo,
+    /// Cursor for the next page, when there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -106,7 +127,7 @@ pub struct PoiQueryInfo {
     pub categories: Option<Vec<String>>,
 }
 
-/// GET /pois - Query POIs within a radius
+/// GET /pois - Query POIs within a radius, nearest first, paged by `cursor`
 pub async fn query_pois(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PoiQueryParams>,
@@ -129,7 +150,21 @@ pub async fn query_pois(
         params.limit
     );
 
-    // Query database
+    let after = params
+        .cursor
+        .as_deref()
+        .map(pagination::decode_cursor::<PoiCursor>)
+        .transpose()?;
+    let seen = after.as_ref().map_or(0, |cursor| cursor.seen);
+    if seen + params.limit > POI_PAGINATION_MAX_DEPTH {
+        return Err(AppError::InvalidRequest(format!(
+            "Cannot page past the nearest {} POIs; narrow radius_km",
+            POI_PAGINATION_MAX_DEPTH
+        )));
+    }
+
+    // Query database. The store orders by distance; re-reading everything
+    // up to this page lets the keyset skip what earlier pages returned.
     let radius_meters = params.radius_km * 1000.0;
     let pois = state
         .poi_repo
@@ -137,17 +172,19 @@ pub async fn query_pois(
             &center,
             radius_meters,
             categories.as_deref(),
-            params.limit as i64,
+            (seen + params.limit + 1) as i64,
         )
         .await?;
+    let (page, next_cursor) = page_after(&center, pois, after.as_ref(), params.limit as usize);
 
-    let count = pois.len();
+    let count = page.len();
 
     tracing::info!("POI query returned {} results", count);
 
     Ok(Json(PoiResponse {
-        pois,
+        pois: FieldSelection::parse(params.fields.as_deref()).project(&page)?,
         count,
+        next_cursor,
         query: PoiQueryInfo {
             center,
             radius_km: params.radius_km,
@@ -156,6 +193,37 @@ pub async fn query_pois(
     }))
 }
 
+/// Up to `limit` POIs after `after` in (distance, id) order, and the cursor
+/// for the page after them
+fn page_after(
+    center: &Coordinates,
+    pois: Vec<Poi>,
+    after: Option<&PoiCursor>,
+    limit: usize,
+) -> (Vec<Poi>, Option<String>) {
+    let mut keyed: Vec<(f64, Poi)> = pois
+        .into_iter()
+        .map(|poi| (center.distance_to(&poi.coordinates) * 1000.0, poi))
+        .filter(|(distance_m, poi)| {
+            after.map_or(true, |cursor| {
+                (*distance_m, poi.id) > (cursor.distance_m, cursor.id)
+            })
+        })
+        .collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.id.cmp(&b.1.id)));
+
+    let has_more = keyed.len() > limit;
+    keyed.truncate(limit);
+    let next_cursor = keyed.last().filter(|_| has_more).map(|(distance_m, poi)| {
+        pagination::encode_cursor(&PoiCursor {
+            distance_m: *distance_m,
+            id: poi.id,
+            seen: after.map_or(0, |cursor| cursor.seen) + limit as u32,
+        })
+    });
+    (keyed.into_iter().map(|(_, poi)| poi).collect(), next_cursor)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,6 +237,8 @@ mod tests {
             radius_km: 5.0,
             categories: None,
             limit: 50,
+            cursor: None,
+            fields: None,
         };
         assert!(params.validate().is_ok());
 
@@ -179,6 +249,8 @@ mod tests {
             radius_km: 5.0,
             categories: None,
             limit: 50,
+            cursor: None,
+            fields: None,
         };
         assert!(params.validate().is_err());
 
@@ -189,6 +261,8 @@ mod tests {
             radius_km: 5.0,
             categories: None,
             limit: 50,
+            cursor: None,
+            fields: None,
         };
         assert!(params.validate().is_err());
 
@@ -199,6 +273,8 @@ mod tests {
             radius_km: 50.0,
             categories: None,
             limit: 50,
+            cursor: None,
+            fields: None,
         };
         assert!(params.validate().is_err());
 
@@ -209,6 +285,8 @@ mod tests {
             radius_km: 5.0,
             categories: None,
             limit: 500,
+            cursor: None,
+            fields: None,
         };
         assert!(params.validate().is_err());
     }
@@ -222,6 +300,8 @@ mod tests {
             radius_km: 5.0,
             categories: None,
             limit: 50,
+            cursor: None,
+            fields: None,
         };
         assert!(params.parse_categories().unwrap().is_none());
 
@@ -232,6 +312,8 @@ mod tests {
             radius_km: 5.0,
             categories: Some("".to_string()),
             limit: 50,
+            cursor: None,
+            fields: None,
         };
         assert!(params.parse_categories().unwrap().is_none());
 
@@ -242,6 +324,8 @@ mod tests {
             radius_km: 5.0,
             categories: Some("monument".to_string()),
             limit: 50,
+            cursor: None,
+            fields: None,
         };
         let cats = params.parse_categories().unwrap().unwrap();
         assert_eq!(cats.len(), 1);
@@ -254,6 +338,8 @@ mod tests {
             radius_km: 5.0,
             categories: Some("monument, park, museum".to_string()),
             limit: 50,
+            cursor: None,
+            fields: None,
         };
         let cats = params.parse_categories().unwrap().unwrap();
         assert_eq!(cats.len(), 3);
@@ -265,7 +351,41 @@ mod tests {
             radius_km: 5.0,
             categories: Some("invalid_category".to_string()),
             limit: 50,
+            cursor: None,
+            fields: None,
         };
         assert!(params.parse_categories().is_err());
     }
+
+    #[test]
+    fn test_pages_cover_every_poi_once() {
+        let center = Coordinates::new(48.8566, 2.3522).unwrap();
+        let pois: Vec<Poi> = (0..5)
+            .map(|i| {
+                // Two POIs at each distance, so the id breaks ties
+                let lat = 48.8566 + (i / 2) as f64 * 0.001;
+                let coords = Coordinates::new(lat, 2.3522).unwrap();
+                Poi::new(format!("poi {}", i), PoiCategory::Park, coords, 50.0)
+            })
+            .collect();
+
+        let (first, cursor) = page_after(&center, pois.clone(), None, 2);
+        let cursor: PoiCursor = pagination::decode_cursor(&cursor.unwrap()).unwrap();
+        assert_eq!(cursor.seen, 2);
+        let (second, cursor) = page_after(&center, pois.clone(), Some(&cursor), 2);
+        let cursor: PoiCursor = pagination::decode_cursor(&cursor.unwrap()).unwrap();
+        let (third, last) = page_after(&center, pois.clone(), Some(&cursor), 2);
+        assert!(last.is_none());
+
+        let mut ids: Vec<Uuid> = first
+            .iter()
+            .chain(&second)
+            .chain(&third)
+            .map(|poi| poi.id)
+            .collect();
+        assert_eq!(ids.len(), 5);
+        ids.dedup();
+        assert_eq!(ids.len(), 5);
+        assert!(first[0].coordinates.lat <= third[0].coordinates.lat);
+    }
 }
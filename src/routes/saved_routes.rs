@@ -0,0 +1,146 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Response,
+    Extension, Json,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::error::AppError;
+use crate::models::api_key::ApiKey;
+use crate::models::saved_route::{self, SaveRouteRequest, SavedRoute, SavedRouteCursor};
+use crate::routes::auth::{acting_user, authorize_user};
+use crate::routes::{etag, pagination};
+use crate::services::tenant::TenantId;
+
+/// POST /api/v1/routes/:id/save - Bookmark a generated route for a user.
+/// Saving the same route again, even regenerated under a new id, only
+/// updates its name and returns the existing bookmark. The route is stored
+/// as the client sent it; server-side readers that reuse it (the geometry
+/// backfill) check it against their own record first. With API key auth,
+/// only the key's user can save as `user`. Bookmarks belong to the caller's
+/// tenant.
+pub async fn save_route(
+    State(pool): State<PgPool>,
+    Path(route_id): Path<Uuid>,
+    tenant: TenantId,
+    key: Option<Extension<ApiKey>>,
+    Json(mut req): Json<SaveRouteRequest>,
+) -> Result<Json<SavedRoute>, AppError> {
+    req.validate(route_id).map_err(AppError::InvalidRequest)?;
+    authorize_user(key.as_deref(), req.user.trim())?;
+    // Clients may send routes from before the hash existed, or edit it
+    req.route.content_hash = req.route.compute_content_hash();
+    // Staleness comes from the consistency check, not the client
+    req.route.mark_stale(Vec::new());
+    let saved = queries::save_route(
+        &pool,
+        &tenant,
+        req.user.trim(),
+        req.name.as_deref(),
+        &req.route,
+    )
+    .await?;
+    Ok(Json(saved))
+}
+
+#[derive(Deserialize)]
+pub struct SavedRouteParams {
+    /// Whose copy to read; required without API key auth
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+/// GET /api/v1/routes/:id?user= - The caller's saved copy of a route,
+/// flagged `stale` (with `stale_reasons`) once its waypoints have changed.
+/// Sends an ETag and honors `If-None-Match`. With API key auth, the user is
+/// the key's.
+pub async fn get_route(
+    State(pool): State<PgPool>,
+    Path(route_id): Path<Uuid>,
+    tenant: TenantId,
+    key: Option<Extension<ApiKey>>,
+    Query(params): Query<SavedRouteParams>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let user = acting_user(key.as_deref(), params.user.as_deref())?;
+    match queries::get_saved_route(&pool, &tenant, &user, route_id).await? {
+        Some(route) => {
+            Ok(etag::json_with_etag(
+                &headers,
+                // Staleness findings are the only part of a saved route that changes
+                etag::route_etag(&route.id, etag::revision_hash(&route.stale_reasons)),
+                &route,
+            ))
+        }
+        None => Err(AppError::NotFound(format!(
+            "Saved route {} not found",
+            route_id
+        ))),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ListSavedParams {
+    pub user: String,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    /// `next_cursor` from the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Comma-separated fields to keep on each saved route
+    #[serde(default)]
+    pub fields: Option<String>,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+/// GET /api/v1/routes?user= - A user's saved routes, most recent first.
+/// With API key auth, only the key's own.
+pub async fn list_saved_routes(
+    State(pool): State<PgPool>,
+    tenant: TenantId,
+    key: Option<Extension<ApiKey>>,
+    Query(params): Query<ListSavedParams>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    saved_route::validate_user(&params.user).map_err(AppError::InvalidRequest)?;
+    authorize_user(key.as_deref(), params.user.trim())?;
+    let limit = params.limit.clamp(1, 100);
+    let after = params
+        .cursor
+        .as_deref()
+        .map(pagination::decode_cursor::<SavedRouteCursor>)
+        .transpose()?;
+
+    let mut routes = queries::list_saved_routes(
+        &pool,
+        &tenant,
+        params.user.trim(),
+        limit + 1,
+        after.as_ref(),
+    )
+    .await?;
+    let next_cursor = if routes.len() as i64 > limit {
+        routes.truncate(limit as usize);
+        routes.last().map(|last| {
+            pagination::encode_cursor(&SavedRouteCursor {
+                saved_at: last.saved_at.clone(),
+                route_id: last.route_id,
+            })
+        })
+    } else {
+        None
+    };
+    let fields = pagination::FieldSelection::parse(params.fields.as_deref());
+
+    Ok(Json(serde_json::json!({
+        "routes": fields.project(&routes)?,
+        "limit": limit,
+        "next_cursor": next_cursor,
+    })))
+}
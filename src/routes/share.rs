@@ -0,0 +1,126 @@
+//! Short share links to saved routes: `POST /routes/{id}/share` mints a
+//! token, `GET /share/{token}` serves the route to anyone holding it until
+//! the link expires.
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::constants::{DEFAULT_SHARE_LINK_TTL_HOURS, SHARE_TOKEN_LEN};
+use crate::db::queries;
+use crate::error::AppError;
+use crate::models::api_key::ApiKey;
+use crate::models::saved_route::{RouteShare, ShareRouteRequest};
+use crate::models::Route;
+use crate::routes::auth::acting_user;
+use crate::services::tenant::TenantId;
+
+/// Token collisions are astronomically rare; retry a few times anyway
+const TOKEN_ATTEMPTS: usize = 3;
+
+/// Share link settings, added to the router as an extension
+#[derive(Debug, Clone, Copy)]
+pub struct ShareSettings {
+    /// Lifetime of a link, and the most a request can ask for
+    pub ttl_hours: u32,
+}
+
+impl Default for ShareSettings {
+    fn default() -> Self {
+        ShareSettings {
+            ttl_hours: DEFAULT_SHARE_LINK_TTL_HOURS,
+        }
+    }
+}
+
+fn new_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(SHARE_TOKEN_LEN)
+        .map(char::from)
+        .collect()
+}
+
+/// POST /api/v1/routes/:id/share - Create a short link to the caller's saved
+/// copy of a route. With API key auth, the user is the key's.
+pub async fn share_route(
+    State(pool): State<PgPool>,
+    Path(route_id): Path<Uuid>,
+    tenant: TenantId,
+    key: Option<Extension<ApiKey>>,
+    settings: Option<Extension<ShareSettings>>,
+    body: Option<Json<ShareRouteRequest>>,
+) -> Result<Json<RouteShare>, AppError> {
+    let settings = settings.map(|Extension(s)| s).unwrap_or_default();
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let ttl_hours = match req.expires_in_hours {
+        Some(0) => {
+            return Err(AppError::InvalidRequest(
+                "expires_in_hours must be at least 1".to_string(),
+            ))
+        }
+        Some(hours) => hours.min(settings.ttl_hours),
+        None => settings.ttl_hours,
+    };
+
+    let user = acting_user(key.as_deref(), req.user.as_deref())?;
+    if queries::get_saved_route(&pool, &tenant, &user, route_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFound(format!(
+            "Saved route {} not found (save it before sharing)",
+            route_id
+        )));
+    }
+
+    for _ in 0..TOKEN_ATTEMPTS {
+        let token = new_token();
+        if let Some(expires_at) =
+            queries::create_route_share(&pool, &token, &tenant, &user, route_id, ttl_hours).await?
+        {
+            return Ok(Json(RouteShare {
+                url: format!("/api/v1/share/{}", token),
+                token,
+                route_id,
+                expires_at,
+            }));
+        }
+    }
+    Err(AppError::Internal(
+        "Could not allocate a share token".to_string(),
+    ))
+}
+
+/// GET /api/v1/share/:token - The shared route; no auth required. The link
+/// records the sharer's tenant, so it serves their copy under any tenant.
+pub async fn get_shared_route(
+    State(pool): State<PgPool>,
+    Path(token): Path<String>,
+) -> Result<Json<Route>, AppError> {
+    if token.len() != SHARE_TOKEN_LEN || !token.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(AppError::NotFound("Share link not found".to_string()));
+    }
+    queries::get_shared_route(&pool, &token)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("Share link not found or expired".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_short_and_url_safe() {
+        let token = new_token();
+        assert_eq!(token.len(), SHARE_TOKEN_LEN);
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(token, new_token());
+    }
+}
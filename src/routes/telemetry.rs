@@ -0,0 +1,17 @@
+use crate::db::queries;
+use crate::error::AppError;
+use crate::models::snap_feedback::SnapFeedbackRequest;
+use axum::{extract::State, Json};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+/// POST /telemetry/snaps - Record what users did with snapped POIs
+/// (input to snap radius tuning)
+pub async fn record_snap_feedback(
+    State(pool): State<PgPool>,
+    Json(req): Json<SnapFeedbackRequest>,
+) -> Result<Json<Value>, AppError> {
+    req.validate().map_err(AppError::InvalidRequest)?;
+    let recorded = queries::insert_snap_feedback(&pool, &req.events).await?;
+    Ok(Json(json!({ "recorded": recorded })))
+}
@@ -0,0 +1,22 @@
+use axum::{extract::State, Json};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::error::{AppError, Result};
+use crate::services::tenant::TenantId;
+use crate::AppState;
+
+/// GET /usage
+/// Usage counters for the caller's tenant (multi-tenant mode only)
+pub async fn tenant_usage(
+    State(state): State<Arc<AppState>>,
+    tenant: TenantId,
+) -> Result<Json<Value>> {
+    let tenants = state.tenants.as_ref().ok_or_else(|| {
+        AppError::NotFound("Usage accounting requires TENANT_API_KEYS".to_string())
+    })?;
+    Ok(Json(json!({
+        "tenant": tenant,
+        "usage": tenants.usage(&tenant),
+    })))
+}
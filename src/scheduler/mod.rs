@@ -0,0 +1,258 @@
+//! Periodic background work.
+//!
+//! Tasks implement [`ScheduledTask`] and are registered on a [`Scheduler`],
+//! which gives each one its own loop. Each run is delayed by a random jitter
+//! so replicas do not fire together. A task never overlaps itself: the loop
+//! only schedules the next run after the current one finishes, and with run
+//! history enabled a Postgres advisory lock keeps other instances from
+//! running it at the same time. Runs are recorded in `scheduled_task_runs`.
+
+pub(crate) mod schedule;
+pub mod tasks;
+
+pub use schedule::Schedule;
+
+use crate::config::SchedulerConfig;
+use crate::db::queries;
+use crate::error::Result;
+use async_trait::async_trait;
+use rand::Rng;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+use tokio::task::JoinHandle;
+
+/// Names accepted in `SCHEDULE_<NAME>` overrides
+pub const TASK_NAMES: &[&str] = &[
+    tasks::EVALUATION_RETENTION,
+    tasks::SNAP_RADIUS_TUNING,
+    tasks::GEOMETRY_BACKFILL,
+    tasks::METRIC_RECOMPUTE,
+];
+
+#[async_trait]
+pub trait ScheduledTask: Send + Sync {
+    /// Lowercase identifier, also the `SCHEDULE_<NAME>` override key
+    fn name(&self) -> &'static str;
+    fn default_schedule(&self) -> Schedule;
+    /// Do one run; the summary goes to the run history
+    async fn run(&self) -> Result<String>;
+}
+
+struct Entry {
+    task: Arc<dyn ScheduledTask>,
+    schedule: Schedule,
+}
+
+pub struct Scheduler {
+    config: SchedulerConfig,
+    entries: Vec<Entry>,
+    history: Option<PgPool>,
+}
+
+impl Scheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        Scheduler {
+            config,
+            entries: Vec::new(),
+            history: None,
+        }
+    }
+
+    /// Record runs and take advisory locks in Postgres
+    pub fn with_run_history(mut self, pool: PgPool) -> Self {
+        self.history = Some(pool);
+        self
+    }
+
+    /// Add a task on its configured schedule (or its default). Tasks disabled
+    /// with `SCHEDULE_<NAME>=off` are dropped.
+    pub fn register(&mut self, task: Arc<dyn ScheduledTask>) {
+        let schedule = match self.config.schedules.get(task.name()) {
+            Some(None) => {
+                tracing::info!(task = task.name(), "Scheduled task disabled");
+                return;
+            }
+            Some(Some(schedule)) => schedule.clone(),
+            None => task.default_schedule(),
+        };
+        tracing::info!(
+            task = task.name(),
+            schedule = %schedule,
+            "Scheduled task {}: {}",
+            task.name(),
+            schedule
+        );
+        self.entries.push(Entry { task, schedule });
+    }
+
+    pub fn task_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Start one loop per task
+    pub fn spawn(self) -> Vec<JoinHandle<()>> {
+        let max_jitter = Duration::from_secs(self.config.max_jitter_secs);
+        self.entries
+            .into_iter()
+            .map(|entry| {
+                let history = self.history.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let jitter = if max_jitter.is_zero() {
+                            Duration::ZERO
+                        } else {
+                            rand::thread_rng().gen_range(Duration::ZERO..=max_jitter)
+                        };
+                        let delay = entry.schedule.delay_after(OffsetDateTime::now_utc());
+                        tokio::time::sleep(delay + jitter).await;
+                        run_once(entry.task.as_ref(), history.as_ref()).await;
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// One run: lock, record, run, record. Failures are logged, never propagated,
+/// so the loop keeps its schedule.
+async fn run_once(task: &dyn ScheduledTask, history: Option<&PgPool>) {
+    let name = task.name();
+    let Some(pool) = history else {
+        let started = Instant::now();
+        log_outcome(name, &task.run().await, started);
+        return;
+    };
+
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            tracing::warn!(task = name, error = %e, "Scheduled task skipped: no database connection");
+            return;
+        }
+    };
+    match queries::try_lock_task(&mut conn, name).await {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::debug!(task = name, "Scheduled task running elsewhere, skipped");
+            return;
+        }
+        Err(e) => {
+            tracing::warn!(task = name, error = %e, "Scheduled task skipped: lock failed");
+            return;
+        }
+    }
+
+    let run_id = queries::insert_task_run(pool, name)
+        .await
+        .map_err(|e| tracing::warn!(task = name, error = %e, "Failed to record task run"))
+        .ok();
+    let started = Instant::now();
+    let outcome = task.run().await;
+    log_outcome(name, &outcome, started);
+    if let Some(run_id) = run_id {
+        let (succeeded, detail) = match outcome {
+            Ok(summary) => (true, summary),
+            Err(e) => (false, e.to_string()),
+        };
+        if let Err(e) = queries::finish_task_run(pool, run_id, succeeded, &detail).await {
+            tracing::warn!(task = name, error = %e, "Failed to record task run");
+        }
+    }
+
+    if let Err(e) = queries::unlock_task(&mut conn, name).await {
+        // Closing the connection releases the lock
+        tracing::warn!(task = name, error = %e, "Failed to release task lock");
+        conn.detach();
+    }
+}
+
+fn log_outcome(name: &str, outcome: &Result<String>, started: Instant) {
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match outcome {
+        Ok(summary) => tracing::info!(task = name, elapsed_ms, "Scheduled task done: {}", summary),
+        Err(e) => tracing::warn!(task = name, elapsed_ms, error = %e, "Scheduled task failed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counter {
+        runs: Arc<AtomicUsize>,
+        running: Arc<AtomicUsize>,
+        overlapped: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl ScheduledTask for Counter {
+        fn name(&self) -> &'static str {
+            "counter"
+        }
+
+        fn default_schedule(&self) -> Schedule {
+            Schedule::Every(Duration::from_millis(5))
+        }
+
+        async fn run(&self) -> Result<String> {
+            if self.running.fetch_add(1, Ordering::SeqCst) > 0 {
+                self.overlapped.fetch_add(1, Ordering::SeqCst);
+            }
+            // Slower than the interval
+            tokio::time::sleep(Duration::from_millis(15)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(format!("run {}", self.runs.fetch_add(1, Ordering::SeqCst)))
+        }
+    }
+
+    fn counter() -> (Arc<Counter>, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let overlapped = Arc::new(AtomicUsize::new(0));
+        let task = Arc::new(Counter {
+            runs: runs.clone(),
+            running: Arc::new(AtomicUsize::new(0)),
+            overlapped: overlapped.clone(),
+        });
+        (task, runs, overlapped)
+    }
+
+    fn config(schedules: &[(&str, Option<Schedule>)]) -> SchedulerConfig {
+        SchedulerConfig {
+            schedules: schedules
+                .iter()
+                .map(|(name, schedule)| (name.to_string(), schedule.clone()))
+                .collect::<BTreeMap<_, _>>(),
+            max_jitter_secs: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tasks_run_repeatedly_without_overlap() {
+        let (task, runs, overlapped) = counter();
+        let mut scheduler = Scheduler::new(config(&[]));
+        scheduler.register(task);
+        let handles = scheduler.spawn();
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        handles.iter().for_each(|h| h.abort());
+        assert!(runs.load(Ordering::SeqCst) >= 3);
+        assert_eq!(overlapped.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_config_overrides_and_disables() {
+        let (task, _, _) = counter();
+        let mut scheduler = Scheduler::new(config(&[("counter", None)]));
+        scheduler.register(task.clone());
+        assert_eq!(scheduler.task_count(), 0);
+
+        let hourly: Schedule = "@hourly".parse().unwrap();
+        let mut scheduler = Scheduler::new(config(&[("counter", Some(hourly.clone()))]));
+        scheduler.register(task);
+        assert_eq!(scheduler.entries[0].schedule, hourly);
+    }
+}
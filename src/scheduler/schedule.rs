@@ -0,0 +1,272 @@
+//! Cron-like schedule definitions.
+//!
+//! Accepted forms:
+//! - five cron fields, `minute hour day-of-month month day-of-week`, each `*`,
+//!   a value, a range `a-b`, a step `*/n` or `a-b/n`, or a comma list of those
+//!   (UTC; day-of-week 0-7 with 0 and 7 both Sunday)
+//! - `@hourly`, `@daily`, `@weekly`, `@monthly`
+//! - `@every <n><s|m|h|d>`, a fixed interval from the previous run
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(CronSpec),
+}
+
+/// Parsed cron fields as bitsets
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSpec {
+    source: String,
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    /// Day-of-month and day-of-week were both restricted: either may match
+    either_day: bool,
+}
+
+impl Schedule {
+    /// Time from `now` until the next run
+    pub fn delay_after(&self, now: OffsetDateTime) -> Duration {
+        match self {
+            Schedule::Every(interval) => *interval,
+            Schedule::Cron(spec) => {
+                let next = spec.next_after(now);
+                Duration::from_secs_f64((next - now).as_seconds_f64().max(0.0))
+            }
+        }
+    }
+}
+
+impl CronSpec {
+    /// First matching minute strictly after `now`
+    pub fn next_after(&self, now: OffsetDateTime) -> OffsetDateTime {
+        let now = now.to_offset(time::UtcOffset::UTC);
+        let mut t = now
+            .replace_second(0)
+            .unwrap()
+            .replace_nanosecond(0)
+            .unwrap()
+            + time::Duration::minutes(1);
+        // Every field set is non-empty, so a match exists within ~4 years
+        // (Feb 29); the bound only guards against bugs
+        for _ in 0..(5 * 366 * 24 * 60) {
+            if !self.day_matches(t) {
+                t = t.replace_time(time::Time::MIDNIGHT) + time::Duration::days(1);
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.replace_minute(0).unwrap() + time::Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += time::Duration::minutes(1);
+            } else {
+                return t;
+            }
+        }
+        t
+    }
+
+    fn day_matches(&self, t: OffsetDateTime) -> bool {
+        if self.months & (1 << u8::from(t.month())) == 0 {
+            return false;
+        }
+        let dom = self.days_of_month & (1 << t.day()) != 0;
+        let dow = self.days_of_week & (1 << t.weekday().number_days_from_sunday()) != 0;
+        if self.either_day {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+}
+
+/// Bitset of the values `field` selects within `min..=max`
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid step in '{}'", part))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else {
+            let parse = |s: &str| {
+                s.parse::<u32>()
+                    .ok()
+                    .filter(|v| (min..=max).contains(v))
+                    .ok_or_else(|| format!("'{}' is not in {}-{}", s, min, max))
+            };
+            match range.split_once('-') {
+                Some((lo, hi)) => (parse(lo)?, parse(hi)?),
+                None if step > 1 => (parse(range)?, max),
+                None => {
+                    let v = parse(range)?;
+                    (v, v)
+                }
+            }
+        };
+        if lo > hi {
+            return Err(format!("empty range '{}'", range));
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            bits |= 1 << v;
+        }
+    }
+    Ok(bits)
+}
+
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = s.split_at(s.len().saturating_sub(1));
+    let n: u64 = number
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("invalid interval '{}'", s))?;
+    let secs = match unit {
+        "s" => n,
+        "m" => n * 60,
+        "h" => n * 3600,
+        "d" => n * 86400,
+        _ => return Err(format!("invalid interval unit in '{}' (s, m, h or d)", s)),
+    };
+    Ok(Duration::from_secs(secs))
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let cron = match s {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            _ => match s.strip_prefix("@every ") {
+                Some(interval) => return parse_interval(interval).map(Schedule::Every),
+                None => s,
+            },
+        };
+
+        let fields: Vec<&str> = cron.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err(format!(
+                "invalid schedule '{}': expected 5 cron fields, @hourly/@daily/@weekly/@monthly or @every <interval>",
+                s
+            ));
+        };
+        let err = |e: String| format!("invalid schedule '{}': {}", s, e);
+        let mut days_of_week = parse_field(dow, 0, 7).map_err(err)? as u8;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & 0x7f;
+        }
+        Ok(Schedule::Cron(CronSpec {
+            source: s.to_string(),
+            minutes: parse_field(minute, 0, 59).map_err(err)?,
+            hours: parse_field(hour, 0, 23).map_err(err)? as u32,
+            days_of_month: parse_field(dom, 1, 31).map_err(err)? as u32,
+            months: parse_field(month, 1, 12).map_err(err)? as u16,
+            days_of_week,
+            either_day: !dom.starts_with('*') && !dow.starts_with('*'),
+        }))
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Every(interval) => write!(f, "@every {}s", interval.as_secs()),
+            Schedule::Cron(spec) => write!(f, "{}", spec.source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Date, Month, Time};
+
+    fn at(year: i32, month: Month, day: u8, hour: u8, minute: u8) -> OffsetDateTime {
+        Date::from_calendar_date(year, month, day)
+            .unwrap()
+            .with_time(Time::from_hms(hour, minute, 0).unwrap())
+            .assume_utc()
+    }
+
+    fn next(schedule: &str, now: OffsetDateTime) -> OffsetDateTime {
+        match schedule.parse::<Schedule>().unwrap() {
+            Schedule::Cron(spec) => spec.next_after(now),
+            Schedule::Every(_) => panic!("not a cron schedule"),
+        }
+    }
+
+    #[test]
+    fn test_next_run_times() {
+        let now = at(2024, Month::March, 15, 10, 30); // a Friday
+        assert_eq!(next("@hourly", now), at(2024, Month::March, 15, 11, 0));
+        assert_eq!(
+            next("*/15 * * * *", now),
+            at(2024, Month::March, 15, 10, 45)
+        );
+        assert_eq!(next("0 3 * * *", now), at(2024, Month::March, 16, 3, 0));
+        assert_eq!(next("30 10 * * *", now), at(2024, Month::March, 16, 10, 30));
+        // Sunday, given as 0 or 7
+        assert_eq!(next("0 0 * * 0", now), at(2024, Month::March, 17, 0, 0));
+        assert_eq!(next("0 0 * * 7", now), at(2024, Month::March, 17, 0, 0));
+        assert_eq!(next("@monthly", now), at(2024, Month::April, 1, 0, 0));
+        assert_eq!(next("0 0 29 2 *", now), at(2028, Month::February, 29, 0, 0));
+        // Both day fields restricted: either matches (the 20th or a Monday)
+        assert_eq!(next("0 12 20 * 1", now), at(2024, Month::March, 18, 12, 0));
+    }
+
+    #[test]
+    fn test_intervals() {
+        assert_eq!(
+            "@every 90s".parse::<Schedule>().unwrap(),
+            Schedule::Every(Duration::from_secs(90))
+        );
+        let hourly: Schedule = "@every 1h".parse().unwrap();
+        assert_eq!(
+            hourly.delay_after(OffsetDateTime::now_utc()),
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn test_invalid_schedules() {
+        for s in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "@every 0m",
+            "@every 5w",
+            "@yearly",
+        ] {
+            assert!(s.parse::<Schedule>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for s in ["0 3 * * *", "@every 3600s"] {
+            assert_eq!(s.parse::<Schedule>().unwrap().to_string(), s);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Built-in scheduled tasks.
+
+use super::{Schedule, ScheduledTask};
+use crate::constants::{
+    EVALUATION_RETENTION_SWEEP_INTERVAL_SECS, GEOMETRY_BACKFILL_BATCH_SIZE,
+    GEOMETRY_BACKFILL_INTERVAL_SECS, METRIC_RECOMPUTE_BATCH_SIZE, METRIC_RECOMPUTE_INTERVAL_SECS,
+    ROUTE_METRICS_VERSION, SAVED_ROUTE_CHECK_BATCH_SIZE, SAVED_ROUTE_CHECK_INTERVAL_SECS,
+    SNAP_FEEDBACK_BUCKET_M, SNAP_FEEDBACK_MAX_DISTANCE_M, SNAP_TUNING_INTERVAL_SECS,
+    SNAP_TUNING_LOOKBACK_DAYS,
+};
+use crate::db::{queries, PoiRepository};
+use crate::error::Result;
+use crate::models::evaluation::StoredGeometry;
+use crate::models::saved_route;
+use crate::services::route_generator::route_metrics::RouteMetrics;
+use crate::services::snap_tuning;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const EVALUATION_RETENTION: &str = "evaluation_retention";
+pub const SNAP_RADIUS_TUNING: &str = "snap_radius_tuning";
+pub const GEOMETRY_BACKFILL: &str = "geometry_backfill";
+pub const METRIC_RECOMPUTE: &str = "metric_recompute";
+pub const SAVED_ROUTE_CONSISTENCY: &str = "saved_route_consistency";
+
+/// Purge evaluated routes (with their ratings and shadow comparisons) older
+/// than `EVALUATION_RETENTION_DAYS`
+pub struct EvaluationRetentionTask {
+    pool: PgPool,
+    days: u32,
+}
+
+impl EvaluationRetentionTask {
+    pub fn new(pool: PgPool, days: u32) -> Self {
+        EvaluationRetentionTask { pool, days }
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for EvaluationRetentionTask {
+    fn name(&self) -> &'static str {
+        EVALUATION_RETENTION
+    }
+
+    fn default_schedule(&self) -> Schedule {
+        Schedule::Every(Duration::from_secs(
+            EVALUATION_RETENTION_SWEEP_INTERVAL_SECS,
+        ))
+    }
+
+    async fn run(&self) -> Result<String> {
+        let deleted = queries::delete_evaluations_older_than(&self.pool, self.days).await?;
+        Ok(format!(
+            "purged {} evaluated routes older than {} days",
+            deleted, self.days
+        ))
+    }
+}
+
+/// Suggest a snap radius per transport mode from recent client feedback on
+/// snapped POIs (see [`snap_tuning`]); results go to
+/// `snap_radius_recommendations`
+pub struct SnapRadiusTuningTask {
+    pool: PgPool,
+    current_radius_m: f64,
+}
+
+impl SnapRadiusTuningTask {
+    pub fn new(pool: PgPool, current_radius_m: f64) -> Self {
+        SnapRadiusTuningTask {
+            pool,
+            current_radius_m,
+        }
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for SnapRadiusTuningTask {
+    fn name(&self) -> &'static str {
+        SNAP_RADIUS_TUNING
+    }
+
+    fn default_schedule(&self) -> Schedule {
+        Schedule::Every(Duration::from_secs(SNAP_TUNING_INTERVAL_SECS))
+    }
+
+    async fn run(&self) -> Result<String> {
+        let counts = queries::snap_feedback_counts(
+            &self.pool,
+            SNAP_FEEDBACK_BUCKET_M,
+            SNAP_FEEDBACK_MAX_DISTANCE_M,
+            SNAP_TUNING_LOOKBACK_DAYS,
+        )
+        .await?;
+
+        let mut by_mode: BTreeMap<String, Vec<(i32, i64, i64)>> = BTreeMap::new();
+        for (mode, bucket, shown, engaged) in counts {
+            by_mode
+                .entry(mode)
+                .or_default()
+                .push((bucket, shown, engaged));
+        }
+
+        let mut summary = Vec::new();
+        for (mode, counts) in &by_mode {
+            let rec = snap_tuning::recommend(mode, self.current_radius_m, counts);
+            queries::upsert_snap_recommendation(&self.pool, &rec).await?;
+            summary.push(match rec.recommended_radius_m {
+                Some(radius) => format!("{} {:.0} m", mode, radius),
+                None => format!("{} n/a ({} samples)", mode, rec.samples),
+            });
+        }
+        if summary.is_empty() {
+            return Ok("no snap feedback yet".to_string());
+        }
+        Ok(format!(
+            "suggested snap radii (current {:.0} m): {}",
+            self.current_radius_m,
+            summary.join(", ")
+        ))
+    }
+}
+
+/// Give evaluated routes stored without geometry the path of a saved copy of
+/// the same route (`saved_routes`), so they can be re-inspected and take part
+/// in metric recomputation. Routes nobody saved, or saved only as copies that
+/// don't match the evaluated route, stay without geometry.
+pub struct GeometryBackfillTask {
+    pool: PgPool,
+}
+
+impl GeometryBackfillTask {
+    pub fn new(pool: PgPool) -> Self {
+        GeometryBackfillTask { pool }
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for GeometryBackfillTask {
+    fn name(&self) -> &'static str {
+        GEOMETRY_BACKFILL
+    }
+
+    fn default_schedule(&self) -> Schedule {
+        Schedule::Every(Duration::from_secs(GEOMETRY_BACKFILL_INTERVAL_SECS))
+    }
+
+    async fn run(&self) -> Result<String> {
+        let sources =
+            queries::list_geometry_backfill_sources(&self.pool, GEOMETRY_BACKFILL_BATCH_SIZE)
+                .await?;
+        let mut filled = 0;
+        for (id, route) in &sources {
+            let geometry = StoredGeometry::from_route(route);
+            if queries::backfill_evaluated_route_geometry(&self.pool, *id, &geometry).await? {
+                filled += 1;
+            }
+        }
+        let missing = queries::count_routes_missing_geometry(&self.pool).await?;
+        Ok(format!(
+            "filled geometry for {} evaluated routes from saved copies, {} still without",
+            filled, missing
+        ))
+    }
+}
+
+/// Recompute `RouteMetrics` with the current metric code for evaluated routes
+/// with stored geometry, into `evaluated_route_metrics` under
+/// `ROUTE_METRICS_VERSION`. Routes whose geometry can't be read are recorded
+/// as failed for that version rather than retried. Each run handles one
+/// batch; once every route has current metrics, runs are no-ops until the
+/// version is bumped.
+pub struct MetricRecomputeTask {
+    pool: PgPool,
+}
+
+impl MetricRecomputeTask {
+    pub fn new(pool: PgPool) -> Self {
+        MetricRecomputeTask { pool }
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for MetricRecomputeTask {
+    fn name(&self) -> &'static str {
+        METRIC_RECOMPUTE
+    }
+
+    fn default_schedule(&self) -> Schedule {
+        Schedule::Every(Duration::from_secs(METRIC_RECOMPUTE_INTERVAL_SECS))
+    }
+
+    async fn run(&self) -> Result<String> {
+        let sources = queries::list_routes_for_recompute(
+            &self.pool,
+            ROUTE_METRICS_VERSION,
+            METRIC_RECOMPUTE_BATCH_SIZE,
+        )
+        .await?;
+
+        let mut recomputed = 0;
+        let mut unreadable = 0;
+        for source in &sources {
+            let route = match source
+                .geometry
+                .as_ref()
+                .map_err(Clone::clone)
+                .and_then(|g| g.to_route(source.id, source.actual_distance_km))
+            {
+                Ok(route) => route,
+                Err(e) => {
+                    tracing::warn!(route_id = %source.id, error = %e, "Unreadable stored geometry");
+                    // Recorded so the route isn't retried until the version changes
+                    queries::mark_recompute_failed(
+                        &self.pool,
+                        source.id,
+                        ROUTE_METRICS_VERSION,
+                        &e,
+                    )
+                    .await?;
+                    unreadable += 1;
+                    continue;
+                }
+            };
+            // The area POI count only sets the density context, which is
+            // not a recomputed metric
+            let metrics = RouteMetrics::compute(&route, 0);
+            queries::upsert_recomputed_metrics(
+                &self.pool,
+                source.id,
+                ROUTE_METRICS_VERSION,
+                &metrics,
+            )
+            .await?;
+            recomputed += 1;
+        }
+        Ok(format!(
+            "recomputed metrics v{} for {} evaluated routes, {} with unreadable geometry",
+            ROUTE_METRICS_VERSION, recomputed, unreadable
+        ))
+    }
+}
+
+/// Check saved routes' waypoints against the current POI data, flagging
+/// routes whose POIs were deleted or moved as stale (served with `stale:
+/// true` and the reasons) and clearing the flag once they match again.
+/// Each run checks one batch, least recently checked first.
+pub struct SavedRouteConsistencyTask {
+    pool: PgPool,
+    pois: Arc<dyn PoiRepository>,
+}
+
+impl SavedRouteConsistencyTask {
+    pub fn new(pool: PgPool, pois: Arc<dyn PoiRepository>) -> Self {
+        SavedRouteConsistencyTask { pool, pois }
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for SavedRouteConsistencyTask {
+    fn name(&self) -> &'static str {
+        SAVED_ROUTE_CONSISTENCY
+    }
+
+    fn default_schedule(&self) -> Schedule {
+        Schedule::Every(Duration::from_secs(SAVED_ROUTE_CHECK_INTERVAL_SECS))
+    }
+
+    async fn run(&self) -> Result<String> {
+        let routes =
+            queries::list_saved_routes_to_check(&self.pool, SAVED_ROUTE_CHECK_BATCH_SIZE).await?;
+        let mut stale = 0;
+        for (tenant, user, route_id, route) in &routes {
+            let ids: Vec<_> = route.pois.iter().map(|waypoint| waypoint.poi.id).collect();
+            let mut current = self.pois.find_by_ids(&ids).await?;
+            // Re-imported POIs get new IDs; look the rest up by OSM ID
+            let osm_ids: Vec<_> = route
+                .pois
+                .iter()
+                .filter(|waypoint| !current.iter().any(|poi| poi.id == waypoint.poi.id))
+                .filter_map(|waypoint| waypoint.poi.osm_id)
+                .collect();
+            if !osm_ids.is_empty() {
+                current.extend(self.pois.find_by_osm_ids(&osm_ids).await?);
+            }
+            let reasons = saved_route::stale_reasons(route, &current);
+            if !reasons.is_empty() {
+                stale += 1;
+            }
+            queries::mark_saved_route_checked(&self.pool, tenant, user, *route_id, &reasons)
+                .await?;
+        }
+        Ok(format!(
+            "checked {} saved routes, {} stale",
+            routes.len(),
+            stale
+        ))
+    }
+}
@@ -0,0 +1,318 @@
+//! Fault injection for resilience testing.
+//!
+//! A [`FaultInjector`] sits in front of Mapbox directions calls (see
+//! [`MapboxClient::with_fault_injector`](crate::services::mapbox::MapboxClient::with_fault_injector))
+//! and POI queries (see [`FaultyPoiRepository`]) and, at the configured
+//! rates, adds latency, hangs then times out, or fails the call the way a 5xx
+//! or dropped connection would. The dice are seeded, so a given seed and call
+//! sequence always injects the same faults; `fail_first` makes exact
+//! scenarios ("every tolerance level fails, the geometric loop succeeds")
+//! easy to script in integration tests.
+//!
+//! Overpass is not covered: POIs come from the local OSM import and the
+//! Overpass client is archived.
+
+use crate::config::{ChaosConfig, FaultRates};
+use crate::db::PoiRepository;
+use crate::error::{AppError, Result};
+use crate::models::road_profile::RoadProfile;
+use crate::models::route::SnappedPoi;
+use crate::models::{Coordinates, Poi, PoiCategory};
+use crate::services::dependency_guard::Dependency;
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// What happens to one call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Fails immediately, like a 5xx response or a reset connection
+    Error,
+    /// Hangs for the chaos timeout, then fails
+    Timeout,
+}
+
+#[derive(Default)]
+struct Counters {
+    calls: AtomicU64,
+    injected: AtomicU64,
+}
+
+pub struct FaultInjector {
+    config: ChaosConfig,
+    rng: Mutex<StdRng>,
+    mapbox: Counters,
+    postgres: Counters,
+}
+
+impl FaultInjector {
+    pub fn new(config: ChaosConfig) -> Self {
+        FaultInjector {
+            rng: Mutex::new(StdRng::seed_from_u64(config.seed)),
+            config,
+            mapbox: Counters::default(),
+            postgres: Counters::default(),
+        }
+    }
+
+    fn rates(&self, dependency: Dependency) -> Option<&FaultRates> {
+        match dependency {
+            Dependency::Mapbox => Some(&self.config.mapbox),
+            Dependency::Postgres => Some(&self.config.postgres),
+            Dependency::Redis | Dependency::Valhalla | Dependency::Osrm => None,
+        }
+    }
+
+    fn counters(&self, dependency: Dependency) -> Option<&Counters> {
+        match dependency {
+            Dependency::Mapbox => Some(&self.mapbox),
+            Dependency::Postgres => Some(&self.postgres),
+            Dependency::Redis | Dependency::Valhalla | Dependency::Osrm => None,
+        }
+    }
+
+    /// Roll the dice for the next call to `dependency` (no sleeping)
+    pub fn decide(&self, dependency: Dependency) -> Option<Fault> {
+        let (rates, counters) = self.rates(dependency).zip(self.counters(dependency))?;
+        let call = counters.calls.fetch_add(1, Ordering::SeqCst);
+        let fault = if call < rates.fail_first {
+            Some(Fault::Error)
+        } else if rates.error_rate > 0.0 || rates.timeout_rate > 0.0 {
+            let roll: f64 = self.rng.lock().unwrap().gen();
+            if roll < rates.error_rate {
+                Some(Fault::Error)
+            } else if roll < rates.error_rate + rates.timeout_rate {
+                Some(Fault::Timeout)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        if fault.is_some() {
+            counters.injected.fetch_add(1, Ordering::SeqCst);
+        }
+        fault
+    }
+
+    /// Call before talking to `dependency`: sleeps for the configured latency
+    /// and returns the injected error, if any
+    pub async fn before_call(&self, dependency: Dependency) -> Result<()> {
+        let Some(rates) = self.rates(dependency) else {
+            return Ok(());
+        };
+        if rates.latency_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(rates.latency_ms)).await;
+        }
+        let Some(fault) = self.decide(dependency) else {
+            return Ok(());
+        };
+        if fault == Fault::Timeout {
+            tokio::time::sleep(Duration::from_millis(self.config.timeout_ms)).await;
+        }
+        tracing::debug!(
+            dependency = dependency.name(),
+            fault = ?fault,
+            "Injected {:?} into {} call",
+            fault,
+            dependency.name()
+        );
+        Err(injected_error(dependency, fault))
+    }
+
+    /// Calls to `dependency` seen so far
+    pub fn calls(&self, dependency: Dependency) -> u64 {
+        self.counters(dependency)
+            .map_or(0, |c| c.calls.load(Ordering::SeqCst))
+    }
+
+    /// Calls to `dependency` that were failed on purpose
+    pub fn injected(&self, dependency: Dependency) -> u64 {
+        self.counters(dependency)
+            .map_or(0, |c| c.injected.load(Ordering::SeqCst))
+    }
+}
+
+/// The error the real client would have produced
+fn injected_error(dependency: Dependency, fault: Fault) -> AppError {
+    match (dependency, fault) {
+        (Dependency::Mapbox, Fault::Error) => {
+            AppError::MapboxApi("HTTP 503 Service Unavailable: injected fault".to_string())
+        }
+        (Dependency::Mapbox, Fault::Timeout) => {
+            AppError::MapboxApi("Request failed: operation timed out (injected)".to_string())
+        }
+        (_, Fault::Error) => AppError::Database(sqlx::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "injected fault",
+        ))),
+        (_, Fault::Timeout) => AppError::Database(sqlx::Error::PoolTimedOut),
+    }
+}
+
+/// POI repository decorator failing queries per the injector's Postgres rates
+pub struct FaultyPoiRepository {
+    inner: Arc<dyn PoiRepository>,
+    faults: Arc<FaultInjector>,
+}
+
+impl FaultyPoiRepository {
+    pub fn new(inner: Arc<dyn PoiRepository>, faults: Arc<FaultInjector>) -> Self {
+        FaultyPoiRepository { inner, faults }
+    }
+}
+
+#[async_trait]
+impl PoiRepository for FaultyPoiRepository {
+    async fn find_within_radius(
+        &self,
+        center: &Coordinates,
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        self.faults.before_call(Dependency::Postgres).await?;
+        self.inner
+            .find_within_radius(center, radius_meters, categories, limit)
+            .await
+    }
+
+    async fn find_in_bbox(
+        &self,
+        min_lat: f64,
+        max_lat: f64,
+        min_lng: f64,
+        max_lng: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        self.faults.before_call(Dependency::Postgres).await?;
+        self.inner
+            .find_in_bbox(min_lat, max_lat, min_lng, max_lng, categories, limit)
+            .await
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Poi>> {
+        self.faults.before_call(Dependency::Postgres).await?;
+        self.inner.find_by_ids(ids).await
+    }
+
+    async fn find_by_osm_ids(&self, osm_ids: &[i64]) -> Result<Vec<Poi>> {
+        self.faults.before_call(Dependency::Postgres).await?;
+        self.inner.find_by_osm_ids(osm_ids).await
+    }
+
+    async fn insert(&self, poi: &Poi) -> Result<Uuid> {
+        self.faults.before_call(Dependency::Postgres).await?;
+        self.inner.insert(poi).await
+    }
+
+    async fn count(&self) -> Result<i64> {
+        self.faults.before_call(Dependency::Postgres).await?;
+        self.inner.count().await
+    }
+
+    async fn road_profile(&self, path: &[Coordinates]) -> Result<Option<RoadProfile>> {
+        self.faults.before_call(Dependency::Postgres).await?;
+        self.inner.road_profile(path).await
+    }
+
+    async fn find_near_path(
+        &self,
+        path: &[Coordinates],
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Option<Vec<SnappedPoi>>> {
+        self.faults.before_call(Dependency::Postgres).await?;
+        self.inner
+            .find_near_path(path, radius_meters, categories, limit)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn injector(mapbox: FaultRates) -> FaultInjector {
+        FaultInjector::new(ChaosConfig {
+            seed: 42,
+            timeout_ms: 1,
+            mapbox,
+            ..Default::default()
+        })
+    }
+
+    fn decisions(injector: &FaultInjector, n: usize) -> Vec<Option<Fault>> {
+        (0..n)
+            .map(|_| injector.decide(Dependency::Mapbox))
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_injects_same_faults() {
+        let rates = FaultRates {
+            error_rate: 0.3,
+            timeout_rate: 0.2,
+            ..Default::default()
+        };
+        let first = decisions(&injector(rates.clone()), 200);
+        assert_eq!(first, decisions(&injector(rates), 200));
+
+        let errors = first.iter().filter(|f| **f == Some(Fault::Error)).count();
+        let timeouts = first.iter().filter(|f| **f == Some(Fault::Timeout)).count();
+        assert!((40..=80).contains(&errors), "{errors} errors");
+        assert!((20..=60).contains(&timeouts), "{timeouts} timeouts");
+    }
+
+    #[test]
+    fn test_fail_first_then_rates() {
+        let injector = injector(FaultRates {
+            fail_first: 3,
+            ..Default::default()
+        });
+        assert_eq!(
+            decisions(&injector, 5),
+            vec![
+                Some(Fault::Error),
+                Some(Fault::Error),
+                Some(Fault::Error),
+                None,
+                None
+            ]
+        );
+        assert_eq!(injector.calls(Dependency::Mapbox), 5);
+        assert_eq!(injector.injected(Dependency::Mapbox), 3);
+        // Postgres has no faults configured
+        assert_eq!(injector.decide(Dependency::Postgres), None);
+    }
+
+    #[tokio::test]
+    async fn test_injected_errors_look_like_real_failures() {
+        let injector = FaultInjector::new(ChaosConfig {
+            timeout_ms: 1,
+            mapbox: FaultRates {
+                timeout_rate: 1.0,
+                ..Default::default()
+            },
+            postgres: FaultRates {
+                error_rate: 1.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mapbox = injector.before_call(Dependency::Mapbox).await.unwrap_err();
+        assert!(mapbox.to_string().contains("timed out"));
+        let postgres = injector
+            .before_call(Dependency::Postgres)
+            .await
+            .unwrap_err();
+        assert_eq!(Dependency::of_error(&postgres), Some(Dependency::Postgres));
+        assert!(injector.before_call(Dependency::Redis).await.is_ok());
+    }
+}
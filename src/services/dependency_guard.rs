@@ -0,0 +1,257 @@
+//! Dependency health and fallback policy, consulted by the request pipeline.
+//!
+//! Failures are reported here instead of being handled where they happen:
+//! the guard marks the dependency down for a cooldown, logs the transition
+//! once, and answers "may this request proceed?" according to the configured
+//! [`FallbackPolicy`].
+
+use crate::config::{DegradationConfig, FallbackPolicy};
+use crate::error::{AppError, GenerationFailure, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dependency {
+    Redis,
+    Postgres,
+    Mapbox,
+    Valhalla,
+    Osrm,
+}
+
+impl Dependency {
+    pub const ALL: [Dependency; 5] = [
+        Dependency::Redis,
+        Dependency::Postgres,
+        Dependency::Mapbox,
+        Dependency::Valhalla,
+        Dependency::Osrm,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dependency::Redis => "redis",
+            Dependency::Postgres => "postgres",
+            Dependency::Mapbox => "mapbox",
+            Dependency::Valhalla => "valhalla",
+            Dependency::Osrm => "osrm",
+        }
+    }
+
+    /// The dependency an error came from, if any
+    pub fn of_error(error: &AppError) -> Option<Dependency> {
+        match error {
+            AppError::Database(_) => Some(Dependency::Postgres),
+            AppError::MapboxApi(_) => Some(Dependency::Mapbox),
+            AppError::Cache(_) => Some(Dependency::Redis),
+            AppError::Timeout { dependency, .. } | AppError::RoutingBackend { dependency, .. } => {
+                Some(*dependency)
+            }
+            _ => None,
+        }
+    }
+}
+
+pub struct DependencyGuard {
+    config: DegradationConfig,
+    /// Dependencies seen failing, with the time of the last failure
+    failures: Mutex<HashMap<Dependency, Instant>>,
+}
+
+impl DependencyGuard {
+    pub fn new(config: DegradationConfig) -> Self {
+        DependencyGuard {
+            config,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn policy(&self, dependency: Dependency) -> FallbackPolicy {
+        match dependency {
+            Dependency::Redis => self.config.redis,
+            Dependency::Postgres => self.config.postgres,
+            Dependency::Mapbox => self.config.mapbox,
+            Dependency::Valhalla => self.config.valhalla,
+            Dependency::Osrm => self.config.osrm,
+        }
+    }
+
+    /// Failed within the cooldown
+    pub fn is_down(&self, dependency: Dependency) -> bool {
+        let cooldown = Duration::from_secs(self.config.down_cooldown_secs);
+        self.failures
+            .lock()
+            .unwrap()
+            .get(&dependency)
+            .is_some_and(|failed_at| failed_at.elapsed() < cooldown)
+    }
+
+    pub fn report_failure(&self, dependency: Dependency, error: &dyn std::fmt::Display) {
+        let was_down = self.is_down(dependency);
+        self.failures
+            .lock()
+            .unwrap()
+            .insert(dependency, Instant::now());
+        if !was_down {
+            tracing::warn!(
+                dependency = dependency.name(),
+                policy = %self.policy(dependency),
+                error = %error,
+                "{} unavailable, fallback policy: {}",
+                dependency.name(),
+                self.policy(dependency)
+            );
+        }
+    }
+
+    pub fn report_success(&self, dependency: Dependency) {
+        if self.failures.lock().unwrap().remove(&dependency).is_some() {
+            tracing::info!(
+                dependency = dependency.name(),
+                "{} recovered",
+                dependency.name()
+            );
+        }
+    }
+
+    /// Report the dependency behind `error` (if any) as failed
+    pub fn report_error(&self, error: &AppError) {
+        if let Some(dependency) = Dependency::of_error(error) {
+            self.report_failure(dependency, error);
+        }
+    }
+
+    /// Like `report_error`, for a failed generation: an unavailable routing
+    /// backend is charged to `routing`, the backend that served the request
+    pub fn report_generation_error(&self, error: &AppError, routing: Dependency) {
+        match error {
+            AppError::GenerationFailed(GenerationFailure::BackendUnavailable) => {
+                self.report_failure(routing, error)
+            }
+            _ => self.report_error(error),
+        }
+    }
+
+    /// Whether an uncached request may go ahead: under `cached-only`, not
+    /// while the dependency is down
+    pub fn admit(&self, dependency: Dependency) -> Result<()> {
+        if self.policy(dependency) == FallbackPolicy::CachedOnly && self.is_down(dependency) {
+            return Err(AppError::ServiceUnavailable(format!(
+                "{} is unavailable; only cached routes are served",
+                dependency.name()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether failures of `dependency` should be absorbed with reduced
+    /// functionality instead of failing the request
+    pub fn degrades(&self, dependency: Dependency) -> bool {
+        self.policy(dependency) == FallbackPolicy::Degrade
+    }
+
+    /// Policy and state per dependency, for the health endpoint
+    pub fn status(&self) -> Value {
+        Dependency::ALL
+            .iter()
+            .map(|d| {
+                (
+                    d.name().to_string(),
+                    json!({
+                        "policy": self.policy(*d).to_string(),
+                        "down": self.is_down(*d),
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+impl Default for DependencyGuard {
+    fn default() -> Self {
+        DependencyGuard::new(DegradationConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard(mapbox: FallbackPolicy) -> DependencyGuard {
+        DependencyGuard::new(DegradationConfig {
+            mapbox,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_cached_only_rejects_while_down() {
+        let guard = guard(FallbackPolicy::CachedOnly);
+        assert!(guard.admit(Dependency::Mapbox).is_ok());
+
+        guard.report_error(&AppError::MapboxApi("timeout".into()));
+        assert!(guard.is_down(Dependency::Mapbox));
+        assert!(matches!(
+            guard.admit(Dependency::Mapbox),
+            Err(AppError::ServiceUnavailable(_))
+        ));
+        // Other dependencies are unaffected
+        assert!(guard.admit(Dependency::Postgres).is_ok());
+
+        guard.report_success(Dependency::Mapbox);
+        assert!(guard.admit(Dependency::Mapbox).is_ok());
+    }
+
+    #[test]
+    fn test_fail_policy_always_admits() {
+        let guard = guard(FallbackPolicy::Fail);
+        guard.report_failure(Dependency::Mapbox, &"502");
+        assert!(guard.is_down(Dependency::Mapbox));
+        assert!(guard.admit(Dependency::Mapbox).is_ok());
+    }
+
+    #[test]
+    fn test_down_expires_after_cooldown() {
+        let guard = DependencyGuard::new(DegradationConfig {
+            mapbox: FallbackPolicy::CachedOnly,
+            down_cooldown_secs: 0,
+            ..Default::default()
+        });
+        guard.report_failure(Dependency::Mapbox, &"502");
+        assert!(!guard.is_down(Dependency::Mapbox));
+        assert!(guard.admit(Dependency::Mapbox).is_ok());
+    }
+
+    #[test]
+    fn test_errors_map_to_dependencies() {
+        assert_eq!(
+            Dependency::of_error(&AppError::Database(sqlx::Error::PoolTimedOut)),
+            Some(Dependency::Postgres)
+        );
+        assert_eq!(
+            Dependency::of_error(&AppError::InvalidRequest("x".into())),
+            None
+        );
+        let status = DependencyGuard::default().status();
+        assert_eq!(status["redis"]["policy"], "degrade");
+        assert_eq!(status["mapbox"]["down"], false);
+    }
+
+    #[test]
+    fn test_backend_unavailable_is_charged_to_the_routing_backend() {
+        let guard = DependencyGuard::default();
+        let outage = AppError::GenerationFailed(GenerationFailure::BackendUnavailable);
+        guard.report_generation_error(&outage, Dependency::Valhalla);
+        assert!(guard.is_down(Dependency::Valhalla));
+        assert!(!guard.is_down(Dependency::Mapbox));
+
+        guard.report_error(&AppError::RoutingBackend {
+            dependency: Dependency::Valhalla,
+            message: "HTTP 502".into(),
+        });
+        assert!(!guard.is_down(Dependency::Mapbox));
+    }
+}
@@ -0,0 +1,50 @@
+//! Routing backends that turn waypoints into a road-following path.
+//! Mapbox is the default; Valhalla (`VALHALLA_URL`) adds costing options.
+
+use crate::error::Result;
+use crate::models::{Coordinates, CostingOptions, SurfacePreference, TransportMode};
+use crate::services::dependency_guard::Dependency;
+use crate::services::mapbox::{DirectionsResponse, MapboxClient};
+use async_trait::async_trait;
+
+/// A directions API routing through waypoints in order
+#[async_trait]
+pub trait DirectionsProvider: Send + Sync {
+    /// Short identifier reported in logs
+    fn name(&self) -> &str;
+
+    /// Dependency charged with this backend's outages, for its fallback
+    /// policy and health. Defaults to Mapbox, whose errors
+    /// (`AppError::MapboxApi`) custom backends usually return.
+    fn dependency(&self) -> Dependency {
+        Dependency::Mapbox
+    }
+
+    /// Route through `waypoints` for `mode`, preferring `surface`. Backends
+    /// without an equivalent for the surface or some of the `costing`
+    /// options ignore them.
+    async fn directions(
+        &self,
+        waypoints: &[Coordinates],
+        mode: &TransportMode,
+        costing: &CostingOptions,
+        surface: SurfacePreference,
+    ) -> Result<DirectionsResponse>;
+}
+
+#[async_trait]
+impl DirectionsProvider for MapboxClient {
+    fn name(&self) -> &str {
+        "mapbox"
+    }
+
+    async fn directions(
+        &self,
+        waypoints: &[Coordinates],
+        mode: &TransportMode,
+        _costing: &CostingOptions,
+        _surface: SurfacePreference,
+    ) -> Result<DirectionsResponse> {
+        self.get_directions(waypoints, mode).await
+    }
+}
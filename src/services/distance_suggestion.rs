@@ -0,0 +1,191 @@
+//! Loop distances likely to give good routes from a start point.
+//!
+//! Each candidate distance gets a 0-1 score: POI density times past quality.
+//! Density is the share of the POIs a loop needs that lie within waypoint
+//! reach ([`SUGGEST_REACH_RATIO`] of its distance). Quality is the mean system
+//! score of past generations near the point at that distance, shrunk towards
+//! the area's overall mean when there are few of them; without any history
+//! the score is density alone.
+
+use crate::constants::{
+    SUGGEST_CYCLING_DISTANCES_KM, SUGGEST_HISTORY_PRIOR, SUGGEST_MIN_POIS, SUGGEST_POIS_PER_KM,
+    SUGGEST_RANGE_MARGIN, SUGGEST_REACH_RATIO, SUGGEST_RUN_DISTANCES_KM, SUGGEST_WALK_DISTANCES_KM,
+};
+use crate::models::TransportMode;
+use serde::Serialize;
+
+/// One past generation near the start point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistanceSample {
+    pub target_distance_km: f64,
+    /// System score (0-10)
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CandidateDistance {
+    pub distance_km: f64,
+    /// POIs within waypoint reach of the start
+    pub pois_in_reach: i64,
+    /// Past generations closest to this distance
+    pub samples: usize,
+    /// Their mean system score (0-10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_score: Option<f32>,
+    /// 0-1, higher is better
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DistanceSuggestion {
+    pub recommended_km: f64,
+    pub min_km: f64,
+    pub max_km: f64,
+    pub candidates: Vec<CandidateDistance>,
+}
+
+/// Distances worth scoring for `mode`, shortest first
+pub fn candidate_distances(mode: &TransportMode) -> &'static [f64] {
+    match mode {
+        TransportMode::Walk | TransportMode::DogWalk => &SUGGEST_WALK_DISTANCES_KM,
+        TransportMode::Run => &SUGGEST_RUN_DISTANCES_KM,
+        TransportMode::Bike | TransportMode::RoadBike | TransportMode::Gravel => {
+            &SUGGEST_CYCLING_DISTANCES_KM
+        }
+    }
+}
+
+/// Radius (m) around the start that serves waypoints for a loop of `distance_km`
+pub fn reach_radius_m(distance_km: f64) -> f64 {
+    distance_km * SUGGEST_REACH_RATIO * 1000.0
+}
+
+/// Score `distances` (shortest first) given the POI count within each one's
+/// reach and past generations nearby. `None` when no distance has any POIs.
+pub fn suggest(
+    distances: &[f64],
+    pois_in_reach: &[i64],
+    history: &[DistanceSample],
+) -> Option<DistanceSuggestion> {
+    if distances.is_empty() || pois_in_reach.iter().all(|&count| count == 0) {
+        return None;
+    }
+
+    let mut scores_by_distance: Vec<Vec<f32>> = vec![Vec::new(); distances.len()];
+    for sample in history {
+        if let Some(nearest) = nearest_index(distances, sample.target_distance_km) {
+            scores_by_distance[nearest].push(sample.score);
+        }
+    }
+
+    let area_mean = (!history.is_empty())
+        .then(|| history.iter().map(|s| s.score as f64).sum::<f64>() / history.len() as f64);
+
+    let candidates: Vec<CandidateDistance> = distances
+        .iter()
+        .zip(pois_in_reach)
+        .zip(scores_by_distance)
+        .map(|((&distance_km, &pois), scores)| {
+            let needed = (distance_km * SUGGEST_POIS_PER_KM).max(SUGGEST_MIN_POIS);
+            let density = (pois as f64 / needed).min(1.0);
+            let mean_score =
+                (!scores.is_empty()).then(|| scores.iter().sum::<f32>() / scores.len() as f32);
+            let quality = area_mean.map_or(1.0, |area_mean| {
+                let total: f64 = scores.iter().map(|&s| s as f64).sum();
+                let shrunk = (total + SUGGEST_HISTORY_PRIOR * area_mean)
+                    / (scores.len() as f64 + SUGGEST_HISTORY_PRIOR);
+                (shrunk / 10.0).clamp(0.0, 1.0)
+            });
+            CandidateDistance {
+                distance_km,
+                pois_in_reach: pois,
+                samples: scores.len(),
+                mean_score,
+                score: density * quality,
+            }
+        })
+        .collect();
+
+    // Ties go to the shorter distance
+    let best = candidates.iter().enumerate().fold(0, |best, (i, c)| {
+        if c.score > candidates[best].score {
+            i
+        } else {
+            best
+        }
+    });
+    let good = |i: &usize| candidates[*i].score >= candidates[best].score - SUGGEST_RANGE_MARGIN;
+    let low = (0..best).rev().take_while(good).last().unwrap_or(best);
+    let high = (best + 1..candidates.len())
+        .take_while(good)
+        .last()
+        .unwrap_or(best);
+
+    Some(DistanceSuggestion {
+        recommended_km: candidates[best].distance_km,
+        min_km: candidates[low].distance_km,
+        max_km: candidates[high].distance_km,
+        candidates,
+    })
+}
+
+fn nearest_index(distances: &[f64], km: f64) -> Option<usize> {
+    distances
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            (*a - km)
+                .abs()
+                .partial_cmp(&(*b - km).abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISTANCES: [f64; 5] = [2.0, 4.0, 6.0, 8.0, 10.0];
+
+    fn sample(target_distance_km: f64, score: f32) -> DistanceSample {
+        DistanceSample {
+            target_distance_km,
+            score,
+        }
+    }
+
+    #[test]
+    fn test_sparse_area_suggests_longer_loops() {
+        // Too few POIs close in; enough from 6 km on
+        let suggestion = suggest(&DISTANCES, &[1, 3, 10, 14, 20], &[]).unwrap();
+        assert_eq!(suggestion.recommended_km, 6.0);
+        assert_eq!((suggestion.min_km, suggestion.max_km), (6.0, 10.0));
+    }
+
+    #[test]
+    fn test_history_ranks_well_supplied_distances() {
+        let mut history: Vec<DistanceSample> = (0..20).map(|_| sample(9.5, 3.0)).collect();
+        history.extend((0..20).map(|_| sample(4.2, 9.0)));
+        let suggestion = suggest(&DISTANCES, &[50, 50, 50, 50, 50], &history).unwrap();
+        assert_eq!(suggestion.recommended_km, 4.0);
+        assert_eq!(suggestion.candidates[1].samples, 20);
+        assert_eq!(suggestion.candidates[4].mean_score, Some(3.0));
+        assert!(suggestion.max_km < 10.0);
+    }
+
+    #[test]
+    fn test_no_pois_no_suggestion() {
+        assert_eq!(suggest(&DISTANCES, &[0, 0, 0, 0, 0], &[]), None);
+    }
+
+    #[test]
+    fn test_candidate_distances_by_mode() {
+        assert_eq!(candidate_distances(&TransportMode::DogWalk)[0], 1.0);
+        assert_eq!(
+            candidate_distances(&TransportMode::RoadBike).last(),
+            Some(&50.0)
+        );
+        assert_eq!(reach_radius_m(10.0), 3500.0);
+    }
+}
@@ -0,0 +1,321 @@
+//! Terrain elevation along route paths, for `Route.elevation_gain_m` and the
+//! `prefer_flat` / `max_elevation_gain_m` preferences.
+
+use crate::constants::{
+    ELEVATION_CACHE_CAPACITY, ELEVATION_CACHE_CELL_DEG, ELEVATION_MAX_SAMPLES,
+    ELEVATION_NOISE_THRESHOLD_M, ELEVATION_REQUEST_TIMEOUT_SECS, ELEVATION_SAMPLE_SPACING_M,
+};
+use crate::error::{AppError, Result};
+use crate::models::Coordinates;
+use crate::services::environment::sample_path;
+use async_trait::async_trait;
+use moka::future::Cache;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A source of terrain heights (digital elevation model)
+#[async_trait]
+pub trait ElevationProvider: Send + Sync {
+    /// Short identifier reported in logs
+    fn name(&self) -> &str;
+
+    /// Height in meters at each point, `None` where the model has no data
+    async fn elevations(&self, points: &[Coordinates]) -> Result<Vec<Option<f32>>>;
+}
+
+/// Total climb along `path` in meters, sampled every
+/// `ELEVATION_SAMPLE_SPACING_M` (coarser on long paths, to stay within
+/// `ELEVATION_MAX_SAMPLES`). `None` when fewer than two samples have data.
+pub async fn elevation_gain_along(
+    provider: &dyn ElevationProvider,
+    path: &[Coordinates],
+) -> Result<Option<f32>> {
+    let length_m: f64 = path
+        .windows(2)
+        .map(|w| w[0].distance_to(&w[1]) * 1000.0)
+        .sum();
+    let spacing_m = ELEVATION_SAMPLE_SPACING_M.max(length_m / (ELEVATION_MAX_SAMPLES - 1) as f64);
+    let samples = sample_path(path, spacing_m);
+    if samples.len() < 2 {
+        return Ok(None);
+    }
+    let profile: Vec<f32> = provider
+        .elevations(&samples)
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+    Ok((profile.len() >= 2).then(|| cumulative_gain(&profile)))
+}
+
+/// Sum of climbs, only counting changes of at least
+/// `ELEVATION_NOISE_THRESHOLD_M` so DEM noise on flat ground doesn't add up
+/// to phantom hills
+fn cumulative_gain(profile: &[f32]) -> f32 {
+    let mut gain = 0.0;
+    let mut reference = profile[0];
+    for &height in &profile[1..] {
+        if height - reference >= ELEVATION_NOISE_THRESHOLD_M {
+            gain += height - reference;
+            reference = height;
+        } else if reference - height >= ELEVATION_NOISE_THRESHOLD_M {
+            reference = height;
+        }
+    }
+    gain
+}
+
+/// Remembers heights per `ELEVATION_CACHE_CELL_DEG` grid cell. Candidate
+/// routes from one start share their first streets, so most of their samples
+/// are already known; the rest go to `inner` in a single lookup.
+pub struct CachedElevationProvider {
+    inner: Arc<dyn ElevationProvider>,
+    heights: Cache<(i64, i64), Option<f32>>,
+}
+
+impl CachedElevationProvider {
+    pub fn new(inner: Arc<dyn ElevationProvider>) -> Self {
+        CachedElevationProvider {
+            inner,
+            heights: Cache::new(ELEVATION_CACHE_CAPACITY),
+        }
+    }
+
+    fn cell(point: &Coordinates) -> (i64, i64) {
+        (
+            (point.lat / ELEVATION_CACHE_CELL_DEG).round() as i64,
+            (point.lng / ELEVATION_CACHE_CELL_DEG).round() as i64,
+        )
+    }
+}
+
+#[async_trait]
+impl ElevationProvider for CachedElevationProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn elevations(&self, points: &[Coordinates]) -> Result<Vec<Option<f32>>> {
+        let cells: Vec<_> = points.iter().map(Self::cell).collect();
+        let mut known = HashMap::new();
+        let mut queued = HashSet::new();
+        let mut missing = Vec::new();
+        for (point, &cell) in points.iter().zip(&cells) {
+            if known.contains_key(&cell) || queued.contains(&cell) {
+                continue;
+            }
+            match self.heights.get(&cell).await {
+                Some(height) => {
+                    known.insert(cell, height);
+                }
+                None => {
+                    queued.insert(cell);
+                    missing.push(*point);
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let fetched = self.inner.elevations(&missing).await?;
+            for (point, height) in missing.iter().zip(fetched) {
+                let cell = Self::cell(point);
+                self.heights.insert(cell, height).await;
+                known.insert(cell, height);
+            }
+        }
+        Ok(cells.iter().map(|cell| known[cell]).collect())
+    }
+}
+
+/// Client for the Open-Elevation lookup API (`POST /api/v1/lookup`), also
+/// served by self-hosted instances over SRTM or Copernicus data
+pub struct OpenElevationClient {
+    client: Client,
+    base_url: String,
+}
+
+#[derive(Serialize)]
+struct LookupRequest {
+    locations: Vec<LookupLocation>,
+}
+
+#[derive(Serialize)]
+struct LookupLocation {
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Deserialize)]
+struct LookupResponse {
+    results: Vec<LookupResult>,
+}
+
+#[derive(Deserialize)]
+struct LookupResult {
+    elevation: Option<f32>,
+}
+
+impl OpenElevationClient {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(ELEVATION_REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+        OpenElevationClient {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl ElevationProvider for OpenElevationClient {
+    fn name(&self) -> &str {
+        "open-elevation"
+    }
+
+    async fn elevations(&self, points: &[Coordinates]) -> Result<Vec<Option<f32>>> {
+        let request = LookupRequest {
+            locations: points
+                .iter()
+                .map(|p| LookupLocation {
+                    latitude: p.lat,
+                    longitude: p.lng,
+                })
+                .collect(),
+        };
+        let unavailable = |e: reqwest::Error| {
+            AppError::ServiceUnavailable(format!("Elevation API request failed: {}", e))
+        };
+        let response: LookupResponse = self
+            .client
+            .post(format!("{}/api/v1/lookup", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(unavailable)?
+            .json()
+            .await
+            .map_err(unavailable)?;
+
+        if response.results.len() != points.len() {
+            return Err(AppError::ServiceUnavailable(format!(
+                "Elevation API returned {} results for {} points",
+                response.results.len(),
+                points.len()
+            )));
+        }
+        Ok(response.results.into_iter().map(|r| r.elevation).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Terrain rising 1 m per 0.001° of latitude north of 48°
+    struct Slope;
+
+    #[async_trait]
+    impl ElevationProvider for Slope {
+        fn name(&self) -> &str {
+            "slope"
+        }
+
+        async fn elevations(&self, points: &[Coordinates]) -> Result<Vec<Option<f32>>> {
+            Ok(points
+                .iter()
+                .map(|p| Some(((p.lat - 48.0) * 1000.0) as f32))
+                .collect())
+        }
+    }
+
+    /// Counts the points it is asked for
+    #[derive(Default)]
+    struct Counting {
+        looked_up: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ElevationProvider for Counting {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        async fn elevations(&self, points: &[Coordinates]) -> Result<Vec<Option<f32>>> {
+            self.looked_up
+                .fetch_add(points.len(), std::sync::atomic::Ordering::Relaxed);
+            Slope.elevations(points).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_only_looks_up_new_cells() {
+        let counting = Arc::new(Counting::default());
+        let cached = CachedElevationProvider::new(counting.clone());
+        let point = |lat: f64| Coordinates::new(lat, 2.0).unwrap();
+
+        let first = cached
+            .elevations(&[point(48.001), point(48.002), point(48.001)])
+            .await
+            .unwrap();
+        assert_eq!(first, vec![Some(1.0), Some(2.0), Some(1.0)]);
+        assert_eq!(
+            counting
+                .looked_up
+                .load(std::sync::atomic::Ordering::Relaxed),
+            2
+        );
+
+        // A point a few meters away falls in an already known cell
+        let second = cached
+            .elevations(&[point(48.002_01), point(48.003)])
+            .await
+            .unwrap();
+        assert_eq!(second, vec![Some(2.0), Some(3.0)]);
+        assert_eq!(
+            counting
+                .looked_up
+                .load(std::sync::atomic::Ordering::Relaxed),
+            3
+        );
+    }
+
+    #[test]
+    fn test_cumulative_gain_ignores_noise() {
+        assert_eq!(cumulative_gain(&[100.0, 101.0, 99.5, 101.0, 100.0]), 0.0);
+        assert_eq!(cumulative_gain(&[100.0, 110.0, 90.0, 95.0]), 15.0);
+        // Slow steady climbs still count
+        assert_eq!(cumulative_gain(&[0.0, 2.0, 4.0, 6.0, 8.0]), 8.0);
+    }
+
+    #[tokio::test]
+    async fn test_gain_along_out_and_back() {
+        let start = Coordinates::new(48.0, 2.0).unwrap();
+        let top = Coordinates::new(48.05, 2.0).unwrap();
+        let gain = elevation_gain_along(&Slope, &[start, top, start])
+            .await
+            .unwrap()
+            .unwrap();
+        // Climb 50 m, descend for free
+        assert!(
+            (gain - 50.0).abs() <= ELEVATION_NOISE_THRESHOLD_M,
+            "{}",
+            gain
+        );
+        assert_eq!(elevation_gain_along(&Slope, &[start]).await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_lookup_response_parsing() {
+        let body = r#"{"results":[{"latitude":48.0,"longitude":2.0,"elevation":35},
+            {"latitude":0.0,"longitude":0.0,"elevation":null}]}"#;
+        let response: LookupResponse = serde_json::from_str(body).unwrap();
+        let heights: Vec<_> = response.results.iter().map(|r| r.elevation).collect();
+        assert_eq!(heights, vec![Some(35.0), None]);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::constants::ENV_EXPOSURE_SAMPLE_SPACING_M;
+use crate::models::coordinates::{lng_delta, wrap_lng};
+use crate::models::Coordinates;
+use std::path::Path;
+
+/// A spatial layer of environmental exposure (noise, air pollution, ...).
+/// Values are normalized to 0.0 (clean/quiet) – 1.0 (worst) so different
+/// sources can be swapped without retuning scoring.
+pub trait EnvironmentalLayer: Send + Sync {
+    /// Short identifier reported in logs (e.g. "noise", "no2")
+    fn name(&self) -> &str;
+
+    /// Normalized exposure at a point, or `None` outside the layer's coverage
+    fn exposure_at(&self, point: &Coordinates) -> Option<f32>;
+
+    /// Mean exposure along a path, sampled every `ENV_EXPOSURE_SAMPLE_SPACING_M`.
+    /// Returns `None` when no sample falls inside the layer's coverage.
+    fn exposure_along(&self, path: &[Coordinates]) -> Option<f32> {
+        let samples: Vec<f32> = sample_path(path, ENV_EXPOSURE_SAMPLE_SPACING_M)
+            .iter()
+            .filter_map(|p| self.exposure_at(p))
+            .collect();
+        if samples.is_empty() {
+            return None;
+        }
+        Some(samples.iter().sum::<f32>() / samples.len() as f32)
+    }
+}
+
+/// Points along `path` spaced roughly `spacing_m` apart, including both ends
+pub(crate) fn sample_path(path: &[Coordinates], spacing_m: f64) -> Vec<Coordinates> {
+    let mut samples: Vec<Coordinates> = path.first().copied().into_iter().collect();
+    for w in path.windows(2) {
+        let segment_m = w[0].distance_to(&w[1]) * 1000.0;
+        let steps = (segment_m / spacing_m).ceil().max(1.0) as usize;
+        for i in 1..=steps {
+            let t = i as f64 / steps as f64;
+            samples.push(Coordinates {
+                lat: w[0].lat + (w[1].lat - w[0].lat) * t,
+                lng: wrap_lng(w[0].lng + lng_delta(w[0].lng, w[1].lng) * t),
+            });
+        }
+    }
+    samples
+}
+
+/// Raster layer loaded from an ESRI ASCII grid (`.asc`) in WGS84 degrees,
+/// the export format of most EU noise maps and interpolated air-quality grids.
+/// Raw cell values are scaled linearly from `[min_value, max_value]` to 0–1.
+pub struct GridLayer {
+    name: String,
+    ncols: usize,
+    nrows: usize,
+    /// Lower-left corner of the grid
+    xll: f64,
+    yll: f64,
+    cell_size: f64,
+    nodata: Option<f64>,
+    /// Row-major cell values, first row is the northernmost
+    values: Vec<f64>,
+    min_value: f64,
+    max_value: f64,
+}
+
+impl GridLayer {
+    pub fn from_asc_file(
+        path: &Path,
+        name: &str,
+        min_value: f64,
+        max_value: f64,
+    ) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        Self::from_asc_str(&content, name, min_value, max_value)
+    }
+
+    pub fn from_asc_str(
+        content: &str,
+        name: &str,
+        min_value: f64,
+        max_value: f64,
+    ) -> Result<Self, String> {
+        if max_value <= min_value {
+            return Err("max_value must be greater than min_value".to_string());
+        }
+
+        let mut header = std::collections::HashMap::new();
+        let mut values = Vec::new();
+        for line in content.lines() {
+            let mut tokens = line.split_whitespace().peekable();
+            let Some(first) = tokens.peek() else { continue };
+            if first.parse::<f64>().is_err() {
+                let key = first.to_lowercase();
+                tokens.next();
+                let value: f64 = tokens
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| format!("Invalid grid header line: '{}'", line))?;
+                header.insert(key, value);
+            } else {
+                for token in tokens {
+                    values.push(
+                        token
+                            .parse()
+                            .map_err(|_| format!("Invalid grid value: '{}'", token))?,
+                    );
+                }
+            }
+        }
+
+        let get = |key: &str| {
+            header
+                .get(key)
+                .copied()
+                .ok_or_else(|| format!("Missing grid header '{}'", key))
+        };
+        let ncols = get("ncols")? as usize;
+        let nrows = get("nrows")? as usize;
+        let cell_size = get("cellsize")?;
+        // Centre-registered grids give the centre of the lower-left cell
+        let xll = match get("xllcorner") {
+            Ok(x) => x,
+            Err(_) => get("xllcenter")? - cell_size / 2.0,
+        };
+        let yll = match get("yllcorner") {
+            Ok(y) => y,
+            Err(_) => get("yllcenter")? - cell_size / 2.0,
+        };
+
+        if values.len() != ncols * nrows {
+            return Err(format!(
+                "Grid has {} values, expected {} ({}x{})",
+                values.len(),
+                ncols * nrows,
+                ncols,
+                nrows
+            ));
+        }
+
+        Ok(GridLayer {
+            name: name.to_string(),
+            ncols,
+            nrows,
+            xll,
+            yll,
+            cell_size,
+            nodata: header.get("nodata_value").copied(),
+            values,
+            min_value,
+            max_value,
+        })
+    }
+}
+
+impl EnvironmentalLayer for GridLayer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn exposure_at(&self, point: &Coordinates) -> Option<f32> {
+        let col = ((point.lng - self.xll) / self.cell_size).floor();
+        let row_from_bottom = ((point.lat - self.yll) / self.cell_size).floor();
+        if col < 0.0
+            || row_from_bottom < 0.0
+            || col >= self.ncols as f64
+            || row_from_bottom >= self.nrows as f64
+        {
+            return None;
+        }
+        let row = self.nrows - 1 - row_from_bottom as usize;
+        let value = self.values[row * self.ncols + col as usize];
+        if self.nodata == Some(value) {
+            return None;
+        }
+        let normalized = (value - self.min_value) / (self.max_value - self.min_value);
+        Some(normalized.clamp(0.0, 1.0) as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 3x2 grid over lng [2.0, 2.3], lat [48.0, 48.2]; north row first
+    const GRID: &str = "ncols 3
+nrows 2
+xllcorner 2.0
+yllcorner 48.0
+cellsize 0.1
+NODATA_value -9999
+80 70 -9999
+40 50 60
+";
+
+    fn layer() -> GridLayer {
+        GridLayer::from_asc_str(GRID, "noise", 40.0, 80.0).unwrap()
+    }
+
+    fn c(lat: f64, lng: f64) -> Coordinates {
+        Coordinates::new(lat, lng).unwrap()
+    }
+
+    #[test]
+    fn test_grid_lookup_and_normalization() {
+        let layer = layer();
+        assert_eq!(layer.exposure_at(&c(48.15, 2.05)), Some(1.0));
+        assert_eq!(layer.exposure_at(&c(48.05, 2.05)), Some(0.0));
+        assert_eq!(layer.exposure_at(&c(48.05, 2.15)), Some(0.25));
+        // NODATA cell and outside the grid
+        assert_eq!(layer.exposure_at(&c(48.15, 2.25)), None);
+        assert_eq!(layer.exposure_at(&c(47.9, 2.05)), None);
+    }
+
+    #[test]
+    fn test_exposure_along_path_averages_covered_samples() {
+        let layer = layer();
+        // Entirely within the quiet south-west cell
+        let quiet = vec![c(48.01, 2.01), c(48.02, 2.02), c(48.01, 2.03)];
+        assert_eq!(layer.exposure_along(&quiet), Some(0.0));
+
+        // Outside coverage
+        let outside = vec![c(47.5, 1.5), c(47.6, 1.6)];
+        assert_eq!(layer.exposure_along(&outside), None);
+    }
+
+    #[test]
+    fn test_grid_rejects_malformed_input() {
+        assert!(GridLayer::from_asc_str("ncols 2\nnrows 2\n1 2 3\n", "x", 0.0, 1.0).is_err());
+        assert!(GridLayer::from_asc_str(GRID, "x", 80.0, 40.0).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! CSV and Parquet encodings of the evaluation export
+//! (`GET /api/v1/admin/evaluations/export`).
+//!
+//! CSV is written page by page so the handler can stream it. Parquet needs
+//! the whole file to write its footer and is only available with the
+//! `parquet` feature. Start points are rounded to
+//! `EVALUATION_EXPORT_COORD_DECIMALS` in both.
+
+use std::str::FromStr;
+
+use crate::constants::EVALUATION_EXPORT_COORD_DECIMALS;
+use crate::db::queries::EvaluationExportRow;
+
+/// Column order shared by both formats
+pub const EXPORT_COLUMNS: [&str; 24] = [
+    "id",
+    "created_at",
+    "start_lat",
+    "start_lng",
+    "target_distance_km",
+    "transport_mode",
+    "actual_distance_km",
+    "duration_minutes",
+    "poi_count",
+    "snapped_poi_count",
+    "circularity",
+    "convexity",
+    "path_overlap_pct",
+    "poi_density_per_km",
+    "category_entropy",
+    "landmark_coverage",
+    "system_score",
+    "poi_density_context",
+    "scoring_strategy",
+    "rating_count",
+    "avg_overall_rating",
+    "avg_shape_rating",
+    "avg_scenicness_rating",
+    "avg_variety_rating",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(format!(
+                "Invalid export format: '{}' (expected csv or parquet)",
+                s
+            )),
+        }
+    }
+}
+
+/// Round each row's start point to `EVALUATION_EXPORT_COORD_DECIMALS`
+pub fn round_start_points(rows: &mut [EvaluationExportRow]) {
+    let factor = 10f64.powi(EVALUATION_EXPORT_COORD_DECIMALS);
+    for row in rows {
+        row.start_lat = (row.start_lat * factor).round() / factor;
+        row.start_lng = (row.start_lng * factor).round() / factor;
+    }
+}
+
+/// Header line, newline-terminated
+pub fn csv_header() -> String {
+    EXPORT_COLUMNS.join(",") + "\n"
+}
+
+/// CSV lines for `rows`, each newline-terminated. Missing values are empty.
+pub fn csv_rows(rows: &[EvaluationExportRow]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let fields = [
+            row.id.to_string(),
+            csv_text(&row.created_at),
+            row.start_lat.to_string(),
+            row.start_lng.to_string(),
+            row.target_distance_km.to_string(),
+            csv_text(&row.transport_mode),
+            row.actual_distance_km.to_string(),
+            row.duration_minutes.to_string(),
+            row.poi_count.to_string(),
+            row.snapped_poi_count.to_string(),
+            optional(row.circularity),
+            optional(row.convexity),
+            optional(row.path_overlap_pct),
+            optional(row.poi_density_per_km),
+            optional(row.category_entropy),
+            optional(row.landmark_coverage),
+            row.system_score.to_string(),
+            row.poi_density_context
+                .as_deref()
+                .map(csv_text)
+                .unwrap_or_default(),
+            csv_text(&row.scoring_strategy),
+            row.rating_count.to_string(),
+            optional(row.avg_overall_rating),
+            optional(row.avg_shape_rating),
+            optional(row.avg_scenicness_rating),
+            optional(row.avg_variety_rating),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// RFC 4180 quoting for fields containing separators, quotes or newlines
+fn csv_text(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Snappy-compressed Parquet file with one row group
+#[cfg(feature = "parquet")]
+pub fn to_parquet(rows: &[EvaluationExportRow]) -> Result<Vec<u8>, String> {
+    use arrow_array::{
+        ArrayRef, Float32Array, Float64Array, Int32Array, Int64Array, RecordBatch, StringArray,
+    };
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    use std::sync::Arc;
+
+    fn strings<'a>(values: impl Iterator<Item = Option<&'a str>>) -> ArrayRef {
+        Arc::new(values.collect::<StringArray>())
+    }
+    fn f64s(values: impl Iterator<Item = Option<f64>>) -> ArrayRef {
+        Arc::new(values.collect::<Float64Array>())
+    }
+    fn f32s(values: impl Iterator<Item = Option<f32>>) -> ArrayRef {
+        Arc::new(values.collect::<Float32Array>())
+    }
+    fn i32s(values: impl Iterator<Item = Option<i32>>) -> ArrayRef {
+        Arc::new(values.collect::<Int32Array>())
+    }
+
+    let ids: Vec<String> = rows.iter().map(|r| r.id.to_string()).collect();
+    let columns: Vec<ArrayRef> = vec![
+        strings(ids.iter().map(|id| Some(id.as_str()))),
+        strings(rows.iter().map(|r| Some(r.created_at.as_str()))),
+        f64s(rows.iter().map(|r| Some(r.start_lat))),
+        f64s(rows.iter().map(|r| Some(r.start_lng))),
+        f64s(rows.iter().map(|r| Some(r.target_distance_km))),
+        strings(rows.iter().map(|r| Some(r.transport_mode.as_str()))),
+        f64s(rows.iter().map(|r| Some(r.actual_distance_km))),
+        i32s(rows.iter().map(|r| Some(r.duration_minutes))),
+        i32s(rows.iter().map(|r| Some(r.poi_count))),
+        i32s(rows.iter().map(|r| Some(r.snapped_poi_count))),
+        f32s(rows.iter().map(|r| r.circularity)),
+        f32s(rows.iter().map(|r| r.convexity)),
+        f32s(rows.iter().map(|r| r.path_overlap_pct)),
+        f32s(rows.iter().map(|r| r.poi_density_per_km)),
+        f32s(rows.iter().map(|r| r.category_entropy)),
+        f32s(rows.iter().map(|r| r.landmark_coverage)),
+        f32s(rows.iter().map(|r| Some(r.system_score))),
+        strings(rows.iter().map(|r| r.poi_density_context.as_deref())),
+        strings(rows.iter().map(|r| Some(r.scoring_strategy.as_str()))),
+        Arc::new(
+            rows.iter()
+                .map(|r| Some(r.rating_count))
+                .collect::<Int64Array>(),
+        ),
+        f64s(rows.iter().map(|r| r.avg_overall_rating)),
+        f64s(rows.iter().map(|r| r.avg_shape_rating)),
+        f64s(rows.iter().map(|r| r.avg_scenicness_rating)),
+        f64s(rows.iter().map(|r| r.avg_variety_rating)),
+    ];
+    let batch = RecordBatch::try_from_iter(EXPORT_COLUMNS.iter().zip(columns))
+        .map_err(|e| format!("Failed to build export batch: {}", e))?;
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(properties))
+        .map_err(|e| format!("Failed to start Parquet file: {}", e))?;
+    writer
+        .write(&batch)
+        .and_then(|_| writer.close().map(|_| ()))
+        .map_err(|e| format!("Failed to write Parquet file: {}", e))?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn row() -> EvaluationExportRow {
+        EvaluationExportRow {
+            id: Uuid::nil(),
+            created_at: "2024-06-01 12:00:00+00".to_string(),
+            start_lat: 48.8566,
+            start_lng: 2.3522,
+            target_distance_km: 5.0,
+            transport_mode: "walk".to_string(),
+            actual_distance_km: 5.2,
+            duration_minutes: 62,
+            poi_count: 3,
+            snapped_poi_count: 4,
+            circularity: Some(0.8),
+            convexity: None,
+            path_overlap_pct: Some(0.1),
+            poi_density_per_km: Some(1.5),
+            category_entropy: Some(0.9),
+            landmark_coverage: Some(0.5),
+            system_score: 7.5,
+            poi_density_context: Some("urban".to_string()),
+            scoring_strategy: "simple, v2".to_string(),
+            rating_count: 2,
+            avg_overall_rating: Some(4.5),
+            avg_shape_rating: None,
+            avg_scenicness_rating: Some(4.0),
+            avg_variety_rating: None,
+        }
+    }
+
+    #[test]
+    fn test_csv_rows_match_header() {
+        let header = csv_header();
+        let body = csv_rows(&[row()]);
+        assert_eq!(header.trim_end().split(',').count(), EXPORT_COLUMNS.len());
+        assert!(body.starts_with("00000000-0000-0000-0000-000000000000,2024-06-01"));
+        // Quoted field keeps its comma; missing values are empty
+        assert!(body.contains(",\"simple, v2\",2,4.5,,4,\n"));
+        assert!(body.contains(",0.8,,0.1,"));
+    }
+
+    #[test]
+    fn test_start_points_are_rounded() {
+        let mut rows = [row()];
+        round_start_points(&mut rows);
+        assert_eq!((rows[0].start_lat, rows[0].start_lng), (48.857, 2.352));
+        assert!(csv_rows(&rows).contains(",48.857,2.352,5,"));
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        assert_eq!(csv_text("plain"), "plain");
+        assert_eq!(csv_text("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_text("a\nb"), "\"a\nb\"");
+    }
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!("CSV".parse::<ExportFormat>(), Ok(ExportFormat::Csv));
+        assert_eq!("parquet".parse::<ExportFormat>(), Ok(ExportFormat::Parquet));
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_round_trip() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let bytes = to_parquet(&[row(), row()]).unwrap();
+        let reader = SerializedFileReader::new(axum::body::Bytes::from(bytes)).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 2);
+        assert_eq!(
+            metadata.file_metadata().schema_descr().num_columns(),
+            EXPORT_COLUMNS.len()
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Route lifecycle events on an in-process broadcast channel.
+//!
+//! Producers call [`EventBus::publish`] and never wait on consumers;
+//! subsystems (analytics, webhooks, cache invalidation) call
+//! [`EventBus::subscribe`] and receive every event published after that. With
+//! Redis fanout enabled, events are also sent to a pub/sub channel and events
+//! from other instances are re-broadcast locally, so subscribers see the whole
+//! deployment's events.
+
+use crate::cache::CacheFilter;
+use crate::error::{AppError, Result};
+use crate::models::TransportMode;
+use crate::services::tenant::TenantId;
+use futures::StreamExt;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LifecycleEvent {
+    /// Routes were generated (not served from cache)
+    RouteGenerated {
+        tenant: TenantId,
+        route_ids: Vec<Uuid>,
+        mode: TransportMode,
+        distance_km: f64,
+    },
+    /// A human rating was stored for an evaluated route
+    RouteRated {
+        route_id: Uuid,
+        rating_id: Uuid,
+        overall_rating: i16,
+    },
+    /// POIs were loaded into a database or region file
+    PoiImported { source: String, count: usize },
+    /// Cached routes matching `pattern` were dropped
+    CachePurged { pattern: String, keys: usize },
+    /// An operator invalidated cache entries; instances drop their
+    /// in-process copies of the entries `filter` matches
+    CacheInvalidated { filter: CacheFilter },
+}
+
+impl LifecycleEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LifecycleEvent::RouteGenerated { .. } => "route_generated",
+            LifecycleEvent::RouteRated { .. } => "route_rated",
+            LifecycleEvent::PoiImported { .. } => "poi_imported",
+            LifecycleEvent::CachePurged { .. } => "cache_purged",
+            LifecycleEvent::CacheInvalidated { .. } => "cache_invalidated",
+        }
+    }
+}
+
+/// Message on the Redis channel; `origin` lets an instance skip its own events
+#[derive(Debug, Serialize, Deserialize)]
+struct FanoutMessage {
+    origin: Uuid,
+    event: LifecycleEvent,
+}
+
+struct RedisFanout {
+    connection: ConnectionManager,
+    channel: String,
+    origin: Uuid,
+}
+
+/// Cheap to clone; clones share the channel.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Arc<LifecycleEvent>>,
+    fanout: Option<Arc<RedisFanout>>,
+}
+
+impl EventBus {
+    /// Subscribers lagging more than `capacity` events behind miss the oldest
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        EventBus {
+            sender,
+            fanout: None,
+        }
+    }
+
+    /// Also publish to the Redis `channel` and re-broadcast events other
+    /// instances publish there
+    pub async fn with_redis_fanout(mut self, redis_url: &str, channel: &str) -> Result<Self> {
+        let client = redis::Client::open(redis_url)
+            .map_err(|e| AppError::Cache(format!("Failed to create Redis client: {}", e)))?;
+        let connection = ConnectionManager::new(client.clone())
+            .await
+            .map_err(|e| AppError::Cache(format!("Failed to connect to Redis: {}", e)))?;
+        let mut pubsub = client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| AppError::Cache(format!("Failed to open Redis pub/sub: {}", e)))?;
+        pubsub
+            .subscribe(channel)
+            .await
+            .map_err(|e| AppError::Cache(format!("Failed to subscribe to {}: {}", channel, e)))?;
+
+        let origin = Uuid::new_v4();
+        let sender = self.sender.clone();
+        let channel_name = channel.to_string();
+        tokio::spawn(async move {
+            let mut messages = pubsub.into_on_message();
+            while let Some(message) = messages.next().await {
+                match serde_json::from_slice::<FanoutMessage>(message.get_payload_bytes()) {
+                    Ok(fanout) if fanout.origin != origin => {
+                        let _ = sender.send(Arc::new(fanout.event));
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Ignoring malformed lifecycle event"),
+                }
+            }
+            tracing::warn!(channel = %channel_name, "Redis event subscription ended");
+        });
+
+        self.fanout = Some(Arc::new(RedisFanout {
+            connection,
+            channel: channel.to_string(),
+            origin,
+        }));
+        Ok(self)
+    }
+
+    /// Deliver `event` to current subscribers (and Redis, in the background).
+    /// Never blocks and never fails: an event nobody listens to is dropped.
+    pub fn publish(&self, event: LifecycleEvent) {
+        tracing::debug!(event = event.name(), "Lifecycle event");
+        if let Some(ref fanout) = self.fanout {
+            let fanout = Arc::clone(fanout);
+            let message = FanoutMessage {
+                origin: fanout.origin,
+                event: event.clone(),
+            };
+            tokio::spawn(async move {
+                let Ok(payload) = serde_json::to_string(&message) else {
+                    return;
+                };
+                let mut conn = fanout.connection.clone();
+                let result: redis::RedisResult<()> = conn.publish(&fanout.channel, payload).await;
+                if let Err(e) = result {
+                    tracing::warn!(error = %e, "Failed to fan out lifecycle event");
+                }
+            });
+        }
+        let _ = self.sender.send(Arc::new(event));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LifecycleEvent>> {
+        self.sender.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::new(crate::constants::EVENT_BUS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rated() -> LifecycleEvent {
+        LifecycleEvent::RouteRated {
+            route_id: Uuid::nil(),
+            rating_id: Uuid::nil(),
+            overall_rating: 4,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_events() {
+        let bus = EventBus::new(16);
+        let mut first = bus.subscribe();
+        let mut second = bus.clone().subscribe();
+        assert_eq!(bus.subscriber_count(), 2);
+
+        bus.publish(rated());
+        assert_eq!(*first.recv().await.unwrap(), rated());
+        assert_eq!(*second.recv().await.unwrap(), rated());
+    }
+
+    #[test]
+    fn test_publish_without_subscribers_is_dropped() {
+        let bus = EventBus::new(16);
+        bus.publish(rated());
+        // Late subscribers only see later events
+        let mut late = bus.subscribe();
+        assert!(late.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_events_serialize_with_type_tag() {
+        let event = LifecycleEvent::CachePurged {
+            pattern: "route:loop:*".to_string(),
+            keys: 3,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "cache_purged");
+        assert_eq!(json["type"], event.name());
+        let back: LifecycleEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(back, event);
+    }
+}
@@ -0,0 +1,224 @@
+//! POI "interestingness" grid over a bounding box (`GET /heatmap`), so a
+//! frontend can show which neighborhoods have the POIs good loops need
+//! before asking for one.
+//!
+//! POIs are binned into square cells of `cell_m` meters (the longitude step
+//! sized at the box's middle latitude). Each non-empty cell reports its POI
+//! count, density and mean popularity, and a 0-1 `score`: its summed
+//! popularity relative to the highest cell in the box. Amenities (water,
+//! toilets) are left out; they don't make a route interesting.
+
+use crate::constants::{HEATMAP_CELL_RANGE_M, HEATMAP_MAX_CELLS, METERS_PER_DEGREE};
+use crate::error::{AppError, Result};
+use crate::models::{BoundingBox, Poi, PoiCategory};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+
+/// Grid of `cell_m`-meter cells over a bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeatmapGrid {
+    bbox: BoundingBox,
+    cell_m: f64,
+    lat_step: f64,
+    lng_step: f64,
+    rows: usize,
+    cols: usize,
+}
+
+impl HeatmapGrid {
+    /// Fails if `cell_m` is out of range or the box needs more than
+    /// [`HEATMAP_MAX_CELLS`] cells
+    pub fn new(bbox: BoundingBox, cell_m: f64) -> Result<Self> {
+        let (min_cell, max_cell) = HEATMAP_CELL_RANGE_M;
+        if !(min_cell..=max_cell).contains(&cell_m) {
+            return Err(AppError::InvalidRequest(format!(
+                "cell_m must be between {} and {}",
+                min_cell, max_cell
+            )));
+        }
+        let mid_lat = (bbox.min_lat + bbox.max_lat) / 2.0;
+        let lat_step = cell_m / METERS_PER_DEGREE;
+        let lng_step = cell_m / (METERS_PER_DEGREE * mid_lat.to_radians().cos().max(1e-6));
+        let rows = ((bbox.max_lat - bbox.min_lat) / lat_step).ceil().max(1.0);
+        let cols = ((bbox.max_lng - bbox.min_lng) / lng_step).ceil().max(1.0);
+        if rows * cols > HEATMAP_MAX_CELLS as f64 {
+            return Err(AppError::InvalidRequest(format!(
+                "bbox covers {:.0} cells of {}m, more than {}; use a smaller bbox or a larger cell_m",
+                rows * cols,
+                cell_m,
+                HEATMAP_MAX_CELLS
+            )));
+        }
+        Ok(HeatmapGrid {
+            bbox,
+            cell_m,
+            lat_step,
+            lng_step,
+            rows: rows as usize,
+            cols: cols as usize,
+        })
+    }
+
+    /// `(row, col)` of the cell holding `poi`, `None` outside the box
+    fn cell_of(&self, poi: &Poi) -> Option<(usize, usize)> {
+        let lat = poi.coordinates.lat;
+        let lng = poi.coordinates.lng;
+        if !(self.bbox.min_lat..=self.bbox.max_lat).contains(&lat)
+            || !(self.bbox.min_lng..=self.bbox.max_lng).contains(&lng)
+        {
+            return None;
+        }
+        let row = ((lat - self.bbox.min_lat) / self.lat_step) as usize;
+        let col = ((lng - self.bbox.min_lng) / self.lng_step) as usize;
+        Some((row.min(self.rows - 1), col.min(self.cols - 1)))
+    }
+
+    /// Non-empty cells, south-west first
+    pub fn aggregate(&self, pois: &[Poi]) -> Vec<HeatmapCell> {
+        let mut bins: BTreeMap<(usize, usize), Vec<&Poi>> = BTreeMap::new();
+        for poi in pois.iter().filter(|p| !p.category.is_amenity()) {
+            if let Some(cell) = self.cell_of(poi) {
+                bins.entry(cell).or_default().push(poi);
+            }
+        }
+
+        let cell_km2 = (self.cell_m / 1000.0).powi(2);
+        let weight =
+            |pois: &[&Poi]| -> f64 { pois.iter().map(|p| p.popularity_score as f64 / 100.0).sum() };
+        let max_weight = bins.values().map(|pois| weight(pois)).fold(0.0, f64::max);
+
+        bins.into_iter()
+            .map(|((row, col), pois)| {
+                let categories: HashSet<&PoiCategory> = pois.iter().map(|p| &p.category).collect();
+                let min_lat = self.bbox.min_lat + row as f64 * self.lat_step;
+                let min_lng = self.bbox.min_lng + col as f64 * self.lng_step;
+                HeatmapCell {
+                    min_lat,
+                    min_lng,
+                    max_lat: (min_lat + self.lat_step).min(self.bbox.max_lat),
+                    max_lng: (min_lng + self.lng_step).min(self.bbox.max_lng),
+                    poi_count: pois.len(),
+                    density_per_km2: pois.len() as f64 / cell_km2,
+                    mean_popularity: pois.iter().map(|p| p.popularity_score).sum::<f32>()
+                        / pois.len() as f32,
+                    category_count: categories.len(),
+                    score: if max_weight > 0.0 {
+                        weight(&pois) / max_weight
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapCell {
+    pub min_lat: f64,
+    pub min_lng: f64,
+    pub max_lat: f64,
+    pub max_lng: f64,
+    pub poi_count: usize,
+    pub density_per_km2: f64,
+    /// 0-100
+    pub mean_popularity: f32,
+    /// Distinct POI categories in the cell
+    pub category_count: usize,
+    /// 0-1, relative to the highest cell in the box
+    pub score: f64,
+}
+
+/// `cells` as a FeatureCollection of Polygon features; `truncated` is set
+/// when the POI query hit its limit, so sparse-looking cells may be
+/// undercounted
+pub fn feature_collection(cells: &[HeatmapCell], cell_m: f64, truncated: bool) -> Value {
+    let features: Vec<Value> = cells
+        .iter()
+        .map(|cell| {
+            json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[
+                        [cell.min_lng, cell.min_lat],
+                        [cell.max_lng, cell.min_lat],
+                        [cell.max_lng, cell.max_lat],
+                        [cell.min_lng, cell.max_lat],
+                        [cell.min_lng, cell.min_lat],
+                    ]],
+                },
+                "properties": {
+                    "poi_count": cell.poi_count,
+                    "density_per_km2": cell.density_per_km2,
+                    "mean_popularity": cell.mean_popularity,
+                    "category_count": cell.category_count,
+                    "score": cell.score,
+                },
+            })
+        })
+        .collect();
+    json!({
+        "type": "FeatureCollection",
+        "cell_m": cell_m,
+        "truncated": truncated,
+        "features": features,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Coordinates;
+
+    fn poi(lat: f64, lng: f64, category: PoiCategory, popularity: f32) -> Poi {
+        Poi::builder()
+            .category(category)
+            .coordinates(Coordinates::new(lat, lng).unwrap())
+            .popularity(popularity)
+            .build()
+    }
+
+    fn paris() -> BoundingBox {
+        BoundingBox::parse("2.35,48.85,2.36,48.86").unwrap()
+    }
+
+    #[test]
+    fn test_grid_size_is_bounded() {
+        let grid = HeatmapGrid::new(paris(), 100.0).unwrap();
+        // ~1.1km by ~0.73km
+        assert_eq!((grid.rows, grid.cols), (12, 8));
+        assert!(HeatmapGrid::new(paris(), 1.0).is_err());
+        let country = BoundingBox::parse("-5,42,8,51").unwrap();
+        assert!(HeatmapGrid::new(country, 100.0).is_err());
+    }
+
+    #[test]
+    fn test_cells_aggregate_and_score() {
+        let grid = HeatmapGrid::new(paris(), 100.0).unwrap();
+        let pois = vec![
+            poi(48.8501, 2.3501, PoiCategory::Museum, 80.0),
+            poi(48.8502, 2.3502, PoiCategory::Park, 60.0),
+            poi(48.8595, 2.3595, PoiCategory::Park, 70.0),
+            poi(48.8596, 2.3596, PoiCategory::DrinkingWater, 50.0),
+            // Outside the box
+            poi(48.87, 2.35, PoiCategory::Museum, 90.0),
+        ];
+
+        let cells = grid.aggregate(&pois);
+        assert_eq!(cells.len(), 2);
+        let (busy, quiet) = (&cells[0], &cells[1]);
+        assert_eq!(busy.poi_count, 2);
+        assert_eq!(busy.category_count, 2);
+        assert_eq!(busy.mean_popularity, 70.0);
+        assert_eq!(busy.score, 1.0);
+        assert!((busy.density_per_km2 - 200.0).abs() < 1e-9);
+        assert_eq!(quiet.poi_count, 1);
+        assert!((quiet.score - 0.7 / 1.4).abs() < 1e-9);
+
+        let geojson = feature_collection(&cells, 100.0, false);
+        assert_eq!(geojson["features"].as_array().unwrap().len(), 2);
+        let ring = &geojson["features"][0]["geometry"]["coordinates"][0];
+        assert_eq!(ring[0], ring[4]);
+    }
+}
@@ -1,7 +1,14 @@
+use crate::constants::{DIRECTIONS_MAX_POINT_JUMP_M, DIRECTIONS_MIN_DISTANCE_M};
 use crate::error::{AppError, Result};
 use crate::models::{Coordinates, TransportMode};
+#[cfg(feature = "chaos")]
+use crate::services::chaos::FaultInjector;
+#[cfg(feature = "chaos")]
+use crate::services::dependency_guard::Dependency;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "chaos")]
+use std::sync::Arc;
 
 const MAPBOX_DIRECTIONS_BASE_URL: &str = "https://api.mapbox.com/directions/v5/mapbox";
 
@@ -20,6 +27,8 @@ pub struct MapboxClient {
     api_key: String,
     base_url: String,
     auth_mode: AuthMode,
+    #[cfg(feature = "chaos")]
+    faults: Option<Arc<FaultInjector>>,
 }
 
 impl MapboxClient {
@@ -29,6 +38,8 @@ impl MapboxClient {
             api_key,
             base_url: MAPBOX_DIRECTIONS_BASE_URL.to_string(),
             auth_mode: AuthMode::DirectToken,
+            #[cfg(feature = "chaos")]
+            faults: None,
         }
     }
 
@@ -38,9 +49,19 @@ impl MapboxClient {
             api_key,
             base_url,
             auth_mode,
+            #[cfg(feature = "chaos")]
+            faults: None,
         }
     }
 
+    /// Inject latency, timeouts and 5xx errors into directions calls
+    /// (resilience testing only)
+    #[cfg(feature = "chaos")]
+    pub fn with_fault_injector(mut self, faults: Arc<FaultInjector>) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
     /// Get directions between waypoints
     /// Returns the route with full geometry, distance, and duration
     pub async fn get_directions(
@@ -61,6 +82,11 @@ impl MapboxClient {
             ));
         }
 
+        #[cfg(feature = "chaos")]
+        if let Some(ref faults) = self.faults {
+            faults.before_call(Dependency::Mapbox).await?;
+        }
+
         // Format coordinates as "lng,lat;lng,lat;..."
         let coordinates_str = waypoints
             .iter()
@@ -88,6 +114,10 @@ impl MapboxClient {
             ("steps", "false"),
         ]);
 
+        if let Some(exclude) = mode.mapbox_exclude() {
+            request = request.query(&[("exclude", exclude)]);
+        }
+
         match self.auth_mode {
             AuthMode::DirectToken => {
                 request = request.query(&[("access_token", &self.api_key)]);
@@ -144,10 +174,28 @@ impl MapboxClient {
             "Mapbox response: {:.2}km, {:.0}min, {} path points",
             route.distance / 1000.0, route.duration / 60.0, route.geometry.coordinates.len()
         );
-        Ok(DirectionsResponse {
+        DirectionsResponse {
             distance_meters: route.distance,
             duration_seconds: route.duration,
             geometry: route.geometry.coordinates.clone(),
+            legs: route
+                .legs
+                .iter()
+                .map(|leg| DirectionsLeg {
+                    distance_meters: leg.distance,
+                    duration_seconds: leg.duration,
+                })
+                .collect(),
+        }
+        .sanitized()
+        .map_err(|e| {
+            tracing::warn!(
+                error = %e,
+                waypoints = waypoints.len(),
+                "Rejected degenerate directions geometry: {}",
+                e
+            );
+            AppError::MapboxApi(format!("Degenerate route geometry: {}", e))
         })
     }
 }
@@ -166,6 +214,14 @@ struct MapboxRoute {
     distance: f64, // meters
     duration: f64, // seconds
     geometry: MapboxGeometry,
+    #[serde(default)]
+    legs: Vec<MapboxLeg>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MapboxLeg {
+    distance: f64, // meters
+    duration: f64, // seconds
 }
 
 #[derive(Debug, Deserialize)]
@@ -184,6 +240,15 @@ pub struct DirectionsResponse {
     pub duration_seconds: f64,
     /// GeoJSON coordinates as [lng, lat] pairs
     pub geometry: Vec<[f64; 2]>,
+    /// One leg per consecutive waypoint pair (empty if the backend omits legs)
+    pub legs: Vec<DirectionsLeg>,
+}
+
+/// Distance and duration between two consecutive waypoints
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DirectionsLeg {
+    pub distance_meters: f64,
+    pub duration_seconds: f64,
 }
 
 impl DirectionsResponse {
@@ -195,6 +260,49 @@ impl DirectionsResponse {
         (self.duration_seconds / 60.0).round() as u32
     }
 
+    /// Validate and repair the geometry before metrics and scoring consume it.
+    /// Repairs: drops invalid coordinates and consecutive duplicate points.
+    /// Rejects: non-positive distance, fewer than 2 distinct points, and jumps
+    /// longer than `DIRECTIONS_MAX_POINT_JUMP_M` between consecutive points.
+    pub fn sanitized(mut self) -> std::result::Result<Self, String> {
+        if !self.distance_meters.is_finite() || self.distance_meters < DIRECTIONS_MIN_DISTANCE_M {
+            return Err(format!("route distance is {}m", self.distance_meters));
+        }
+
+        let original_len = self.geometry.len();
+        self.geometry
+            .retain(|p| Coordinates::new(p[1], p[0]).is_ok());
+        self.geometry.dedup();
+        if self.geometry.len() < original_len {
+            tracing::debug!(
+                removed = original_len - self.geometry.len(),
+                "Removed {} invalid or duplicate geometry points",
+                original_len - self.geometry.len()
+            );
+        }
+
+        if self.geometry.len() < 2 {
+            return Err(format!("{} distinct geometry points", self.geometry.len()));
+        }
+
+        let coords = self.to_coordinates();
+        if let Some((i, jump_m)) = coords
+            .windows(2)
+            .map(|w| w[0].distance_to(&w[1]) * 1000.0)
+            .enumerate()
+            .find(|(_, d)| *d > DIRECTIONS_MAX_POINT_JUMP_M)
+        {
+            return Err(format!(
+                "{:.0}m jump between points {} and {}",
+                jump_m,
+                i,
+                i + 1
+            ));
+        }
+
+        Ok(self)
+    }
+
     /// Convert GeoJSON coordinates to our Coordinates type
     pub fn to_coordinates(&self) -> Vec<Coordinates> {
         self.geometry
@@ -226,12 +334,58 @@ mod tests {
         assert!(matches!(client.auth_mode, AuthMode::BearerHeader));
     }
 
+    fn response(geometry: Vec<[f64; 2]>) -> DirectionsResponse {
+        DirectionsResponse {
+            distance_meters: 1000.0,
+            duration_seconds: 600.0,
+            geometry,
+            legs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_sanitized_removes_duplicates_and_invalid_points() {
+        let sanitized = response(vec![
+            [2.35, 48.85],
+            [2.35, 48.85],
+            [200.0, 48.85],
+            [2.351, 48.851],
+            [2.351, 48.851],
+            [2.352, 48.852],
+        ])
+        .sanitized()
+        .unwrap();
+        assert_eq!(
+            sanitized.geometry,
+            vec![[2.35, 48.85], [2.351, 48.851], [2.352, 48.852]]
+        );
+    }
+
+    #[test]
+    fn test_sanitized_rejects_degenerate_geometry() {
+        // Zero-length route
+        let mut zero = response(vec![[2.35, 48.85], [2.351, 48.851]]);
+        zero.distance_meters = 0.0;
+        assert!(zero.sanitized().is_err());
+
+        // All points collapse to one
+        assert!(response(vec![[2.35, 48.85], [2.35, 48.85]])
+            .sanitized()
+            .is_err());
+
+        // Jump of ~70km between consecutive points
+        assert!(response(vec![[2.35, 48.85], [2.351, 48.851], [3.3, 48.85]])
+            .sanitized()
+            .is_err());
+    }
+
     #[test]
     fn test_directions_response_conversions() {
         let response = DirectionsResponse {
             distance_meters: 5240.0,
             duration_seconds: 3720.0,
             geometry: vec![[2.3522, 48.8566], [2.2945, 48.8584]],
+            legs: vec![],
         };
 
         assert_eq!(response.distance_km(), 5.24);
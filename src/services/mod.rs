@@ -1,7 +1,31 @@
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod dependency_guard;
+pub mod directions;
+pub(crate) mod distance_suggestion;
+pub mod elevation;
+pub mod environment;
+pub(crate) mod evaluation_export;
+pub mod events;
+pub(crate) mod heatmap;
 pub mod mapbox;
+pub mod osrm;
+pub(crate) mod path_preview;
 // Overpass API modules archived - using local OSM database only
 // pub mod overpass;
 // pub mod overpass_tags;
 pub mod poi_service;
+pub mod poi_snapshot;
+pub mod privacy;
+pub(crate) mod rating_agreement;
+pub(crate) mod rating_sampler;
+#[cfg(feature = "sqlite")]
+pub mod region_sync;
+pub mod request_log;
 pub mod route_generator;
+pub(crate) mod snap_tuning;
 pub mod snapping_service;
+pub(crate) mod solar;
+pub mod tenant;
+pub mod timeouts;
+pub mod valhalla;
@@ -0,0 +1,237 @@
+//! Client for an OSRM routing server (`GET /route/v1`), used for directions
+//! in offline deployments. OSRM has no per-request costing, so
+//! [`CostingOptions`] are ignored; the profile follows the transport mode.
+
+use crate::constants::OSRM_REQUEST_TIMEOUT_SECS;
+use crate::error::{AppError, GenerationFailure, Result};
+use crate::models::{polyline, Coordinates, CostingOptions, SurfacePreference, TransportMode};
+use crate::services::dependency_guard::Dependency;
+use crate::services::directions::DirectionsProvider;
+use crate::services::mapbox::{DirectionsLeg, DirectionsResponse};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Requested as `geometries=polyline6`
+const SHAPE_PRECISION: f64 = 1e6;
+
+/// `code`s for waypoints OSRM can't route between: no path found, no road
+/// near a waypoint
+const UNROUTABLE_CODES: [&str; 2] = ["NoRoute", "NoSegment"];
+
+pub struct OsrmClient {
+    client: Client,
+    base_url: String,
+}
+
+#[derive(Deserialize)]
+struct RouteResponse {
+    code: String,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    routes: Vec<OsrmRoute>,
+}
+
+#[derive(Deserialize)]
+struct OsrmRoute {
+    /// Meters
+    distance: f64,
+    /// Seconds
+    duration: f64,
+    geometry: String,
+    legs: Vec<OsrmLeg>,
+}
+
+#[derive(Deserialize)]
+struct OsrmLeg {
+    distance: f64,
+    duration: f64,
+}
+
+impl OsrmClient {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(OSRM_REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+        OsrmClient {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+/// `/route/v1/{profile}/{lng,lat;...}` URL for `waypoints`
+fn route_url(base_url: &str, waypoints: &[Coordinates], mode: &TransportMode) -> String {
+    let profile = if mode.is_cycling() { "bike" } else { "foot" };
+    let coordinates: Vec<String> = waypoints
+        .iter()
+        .map(|c| format!("{},{}", c.lng, c.lat))
+        .collect();
+    format!(
+        "{}/route/v1/{}/{}?overview=full&geometries=polyline6",
+        base_url,
+        profile,
+        coordinates.join(";")
+    )
+}
+
+fn backend_error(message: String) -> AppError {
+    AppError::RoutingBackend {
+        dependency: Dependency::Osrm,
+        message,
+    }
+}
+
+/// A routing failure for the attempt when OSRM found no path, an outage
+/// otherwise
+fn error_for(status: reqwest::StatusCode, code: String, message: Option<String>) -> AppError {
+    let message = message.unwrap_or_else(|| code.clone());
+    if UNROUTABLE_CODES.contains(&code.as_str()) {
+        tracing::debug!(code = %code, "OSRM found no route: {}", message);
+        return AppError::GenerationFailed(GenerationFailure::Unroutable);
+    }
+    tracing::warn!(status = %status, "OSRM error {}: {}", status, message);
+    backend_error(format!("HTTP {}: {}", status, message))
+}
+
+fn to_directions(route: OsrmRoute) -> std::result::Result<DirectionsResponse, String> {
+    DirectionsResponse {
+        distance_meters: route.distance,
+        duration_seconds: route.duration,
+        geometry: polyline::decode(&route.geometry, SHAPE_PRECISION)?,
+        legs: route
+            .legs
+            .iter()
+            .map(|leg| DirectionsLeg {
+                distance_meters: leg.distance,
+                duration_seconds: leg.duration,
+            })
+            .collect(),
+    }
+    .sanitized()
+}
+
+#[async_trait]
+impl DirectionsProvider for OsrmClient {
+    fn name(&self) -> &str {
+        "osrm"
+    }
+
+    fn dependency(&self) -> Dependency {
+        Dependency::Osrm
+    }
+
+    async fn directions(
+        &self,
+        waypoints: &[Coordinates],
+        mode: &TransportMode,
+        _costing: &CostingOptions,
+        _surface: SurfacePreference,
+    ) -> Result<DirectionsResponse> {
+        if waypoints.len() < 2 {
+            return Err(AppError::InvalidRequest(
+                "At least 2 waypoints required".to_string(),
+            ));
+        }
+
+        let unavailable = |e: reqwest::Error| backend_error(format!("Request failed: {}", e));
+        let response = self
+            .client
+            .get(route_url(&self.base_url, waypoints, mode))
+            .send()
+            .await
+            .map_err(unavailable)?;
+
+        // OSRM answers 400 with a JSON `code` for unroutable requests
+        let status = response.status();
+        let body: RouteResponse = response.json().await.map_err(unavailable)?;
+        if body.code != "Ok" {
+            return Err(error_for(status, body.code, body.message));
+        }
+
+        let route = body
+            .routes
+            .into_iter()
+            .next()
+            .ok_or_else(|| backend_error("No route in response".to_string()))?;
+        to_directions(route).map_err(|e| {
+            tracing::warn!(
+                error = %e,
+                waypoints = waypoints.len(),
+                "Rejected OSRM route geometry: {}",
+                e
+            );
+            backend_error(format!("Degenerate route: {}", e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_url() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let end = Coordinates::new(48.86, 2.36).unwrap();
+
+        assert_eq!(
+            route_url("http://osrm:5000", &[start, end], &TransportMode::Walk),
+            "http://osrm:5000/route/v1/foot/2.3522,48.8566;2.36,48.86?overview=full&geometries=polyline6"
+        );
+        assert!(
+            route_url("http://osrm:5000", &[start, end], &TransportMode::Gravel)
+                .contains("/route/v1/bike/")
+        );
+    }
+
+    #[test]
+    fn test_route_response_conversion() {
+        let body = r#"{"code": "Ok", "routes": [{
+            "distance": 300.0, "duration": 216.0,
+            "geometry": "o`~d|AocqnCo}@o}@o}@n}@",
+            "legs": [{"distance": 150.0, "duration": 108.0}, {"distance": 150.0, "duration": 108.0}]
+        }]}"#;
+        let response: RouteResponse = serde_json::from_str(body).unwrap();
+        let directions = to_directions(response.routes.into_iter().next().unwrap()).unwrap();
+        assert_eq!(directions.distance_meters, 300.0);
+        assert_eq!(directions.duration_minutes(), 4);
+        assert_eq!(directions.legs.len(), 2);
+        assert_eq!(
+            directions.geometry,
+            vec![[2.3522, 48.8566], [2.3532, 48.8576], [2.3522, 48.8586]]
+        );
+    }
+
+    #[test]
+    fn test_error_response_parses() {
+        let body = r#"{"code": "NoRoute", "message": "Impossible route between points"}"#;
+        let response: RouteResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(response.code, "NoRoute");
+        assert!(response.routes.is_empty());
+    }
+
+    #[test]
+    fn test_no_route_is_a_routing_failure() {
+        for code in UNROUTABLE_CODES {
+            assert!(matches!(
+                error_for(reqwest::StatusCode::BAD_REQUEST, code.to_string(), None),
+                AppError::GenerationFailed(GenerationFailure::Unroutable)
+            ));
+        }
+        assert!(matches!(
+            error_for(
+                reqwest::StatusCode::BAD_REQUEST,
+                "InvalidQuery".to_string(),
+                Some("Query string malformed".to_string())
+            ),
+            AppError::RoutingBackend {
+                dependency: Dependency::Osrm,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+//! Compact path previews for evaluated routes: a Douglas-Peucker simplified
+//! encoded polyline plus bounding box, small enough for a rating UI to draw
+//! many thumbnails from one list response. The same simplification, at a
+//! fixed tolerance in meters, shrinks the geometry stored for evaluated
+//! routes.
+
+use crate::constants::{
+    METERS_PER_DEGREE, PATH_PREVIEW_MAX_POINTS, PATH_PREVIEW_POLYLINE_PRECISION,
+    PATH_PREVIEW_TOLERANCE_FRACTION,
+};
+use crate::models::evaluation::PathPreview;
+use crate::models::{polyline, Coordinates};
+
+/// Preview of `path`, `None` for paths with fewer than two points
+pub fn path_preview(path: &[Coordinates]) -> Option<PathPreview> {
+    if path.len() < 2 {
+        return None;
+    }
+    let (mut min_lng, mut min_lat) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_lng, mut max_lat) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for point in path {
+        min_lng = min_lng.min(point.lng);
+        min_lat = min_lat.min(point.lat);
+        max_lng = max_lng.max(point.lng);
+        max_lat = max_lat.max(point.lat);
+    }
+
+    let lng_scale = ((min_lat + max_lat) / 2.0).to_radians().cos();
+    let planar = to_plane(path, lng_scale);
+    let diagonal = ((max_lng - min_lng) * lng_scale).hypot(max_lat - min_lat);
+
+    // Tighten the tolerance only as far as the point budget allows
+    let mut tolerance = diagonal * PATH_PREVIEW_TOLERANCE_FRACTION;
+    let mut kept = simplify(&planar, tolerance);
+    while kept.len() > PATH_PREVIEW_MAX_POINTS && tolerance > 0.0 {
+        tolerance *= 2.0;
+        kept = simplify(&planar, tolerance);
+    }
+    let points: Vec<Coordinates> = kept.iter().map(|&i| path[i]).collect();
+
+    Some(PathPreview {
+        bbox: [min_lng, min_lat, max_lng, max_lat],
+        polyline: polyline::encode(&points, PATH_PREVIEW_POLYLINE_PRECISION),
+        points: points.len(),
+    })
+}
+
+/// `path` without the points that deviate less than `tolerance_m` from the
+/// simplified line; endpoints are always kept
+pub fn simplify_path(path: &[Coordinates], tolerance_m: f64) -> Vec<Coordinates> {
+    if path.len() < 3 {
+        return path.to_vec();
+    }
+    let mean_lat = path.iter().map(|p| p.lat).sum::<f64>() / path.len() as f64;
+    let planar = to_plane(path, mean_lat.to_radians().cos());
+    simplify(&planar, tolerance_m / METERS_PER_DEGREE)
+        .into_iter()
+        .map(|i| path[i])
+        .collect()
+}
+
+/// Work in a local plane where a degree of longitude is as long as a degree
+/// of latitude, so a tolerance means the same in both axes
+fn to_plane(path: &[Coordinates], lng_scale: f64) -> Vec<[f64; 2]> {
+    path.iter().map(|p| [p.lng * lng_scale, p.lat]).collect()
+}
+
+/// Indices of the points Douglas-Peucker keeps at `tolerance`, endpoints
+/// included
+fn simplify(points: &[[f64; 2]], tolerance: f64) -> Vec<usize> {
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    // Explicit stack: long GPS traces would overflow a recursive version
+    let mut spans = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = spans.pop() {
+        let farthest = (first + 1..last)
+            .map(|i| (i, segment_distance(points[i], points[first], points[last])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, distance)) = farthest {
+            if distance > tolerance {
+                keep[i] = true;
+                spans.push((first, i));
+                spans.push((i, last));
+            }
+        }
+    }
+    (0..points.len()).filter(|&i| keep[i]).collect()
+}
+
+fn segment_distance(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((p[0] - a[0]) * dx + (p[1] - a[1]) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (p[0] - (a[0] + t * dx)).hypot(p[1] - (a[1] + t * dy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(lat: f64, lng: f64) -> Coordinates {
+        Coordinates::new(lat, lng).unwrap()
+    }
+
+    #[test]
+    fn test_collinear_points_are_dropped() {
+        let path: Vec<_> = (0..=10)
+            .map(|i| point(48.85 + i as f64 * 0.001, 2.35))
+            .collect();
+        let preview = path_preview(&path).unwrap();
+        assert_eq!(preview.points, 2);
+        assert_eq!(preview.bbox, [2.35, 48.85, 2.35, 48.86]);
+        let decoded = polyline::decode(&preview.polyline, PATH_PREVIEW_POLYLINE_PRECISION).unwrap();
+        assert_eq!(decoded, vec![[2.35, 48.85], [2.35, 48.86]]);
+    }
+
+    #[test]
+    fn test_loop_keeps_its_corners_within_budget() {
+        // A square loop traced with many points per side
+        let corners = [(48.85, 2.35), (48.85, 2.36), (48.86, 2.36), (48.86, 2.35)];
+        let mut path = Vec::new();
+        for side in 0..4 {
+            let (a, b) = (corners[side], corners[(side + 1) % 4]);
+            for step in 0..500 {
+                let t = step as f64 / 500.0;
+                path.push(point(a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t));
+            }
+        }
+        path.push(path[0]);
+
+        let preview = path_preview(&path).unwrap();
+        assert_eq!(preview.points, 5);
+        assert!(preview.polyline.len() < 40, "{}", preview.polyline);
+    }
+
+    #[test]
+    fn test_point_budget_is_enforced() {
+        // Zigzag where every point matters at the default tolerance
+        let path: Vec<_> = (0..1000)
+            .map(|i| point(48.85 + (i % 2) as f64 * 0.01, 2.35 + i as f64 * 0.0001))
+            .collect();
+        let preview = path_preview(&path).unwrap();
+        assert!(preview.points <= PATH_PREVIEW_MAX_POINTS);
+        assert!(path_preview(&path[..1]).is_none());
+    }
+
+    #[test]
+    fn test_simplify_path_tolerance_in_meters() {
+        // Points wobbling ~1 m off a straight east-west line
+        let path: Vec<_> = (0..=100)
+            .map(|i| point(48.85 + (i % 2) as f64 * 0.00001, 2.35 + i as f64 * 0.0001))
+            .collect();
+        assert_eq!(simplify_path(&path, 5.0).len(), 2);
+        assert_eq!(simplify_path(&path, 0.5).len(), path.len());
+        assert_eq!(simplify_path(&path[..2], 5.0), path[..2].to_vec());
+    }
+}
@@ -1,19 +1,42 @@
 use crate::db::PoiRepository;
 use crate::error::{AppError, Result};
 use crate::models::{Coordinates, Poi, PoiCategory};
+use crate::services::poi_snapshot::PoiSnapshots;
+use crate::services::privacy::LocationPrivacy;
 use std::sync::Arc;
+use uuid::Uuid;
 
+/// POI lookups over any [`PoiRepository`] backend, served from in-memory
+/// city snapshots where they cover the search
 pub struct PoiService {
     repo: Arc<dyn PoiRepository>,
+    privacy: Option<LocationPrivacy>,
+    snapshots: Option<Arc<PoiSnapshots>>,
 }
 
 impl PoiService {
     pub fn new(repo: Arc<dyn PoiRepository>) -> Self {
-        PoiService { repo }
+        PoiService {
+            repo,
+            privacy: None,
+            snapshots: None,
+        }
+    }
+
+    /// Answer searches inside a warm city from its snapshot
+    pub fn with_snapshots(mut self, snapshots: Option<Arc<PoiSnapshots>>) -> Self {
+        self.snapshots = snapshots;
+        self
+    }
+
+    /// Round search centers (usually the start point) in logs and errors
+    pub fn with_location_privacy(mut self, privacy: Option<LocationPrivacy>) -> Self {
+        self.privacy = privacy;
+        self
     }
 
     /// Find POIs within a radius, with optional category filtering
-    /// Uses only the local PostgreSQL/PostGIS database with imported OSM data
+    /// Uses only the repository's imported OSM data (PostGIS or a SQLite region)
     pub async fn find_pois(
         &self,
         center: &Coordinates,
@@ -23,11 +46,29 @@ impl PoiService {
     ) -> Result<Vec<Poi>> {
         let radius_meters = radius_km * 1000.0;
 
-        // Query database for POIs
-        let db_pois = self
-            .repo
-            .find_within_radius(center, radius_meters, categories, limit as i64)
-            .await?;
+        // Warm cities are served from memory, everything else from the database
+        let snapshot = self
+            .snapshots
+            .as_ref()
+            .and_then(|snapshots| snapshots.covering(center, radius_meters));
+        let db_pois = match snapshot {
+            Some(snapshot) => {
+                tracing::debug!(
+                    city = %snapshot.name,
+                    age_secs = snapshot.age().as_secs(),
+                    "Serving POIs from the {} snapshot",
+                    snapshot.name
+                );
+                snapshot.find_within_radius(center, radius_meters, categories, limit)
+            }
+            None => {
+                self.repo
+                    .find_within_radius(center, radius_meters, categories, limit as i64)
+                    .await?
+            }
+        };
+
+        let logged = self.privacy.map_or(*center, |p| p.for_logs(center));
 
         // Check if we found any POIs
         if !db_pois.is_empty() {
@@ -35,8 +76,8 @@ impl PoiService {
                 "Found {} POIs in database within {:.1}km of ({:.4}, {:.4})",
                 db_pois.len(),
                 radius_km,
-                center.lat,
-                center.lng
+                logged.lat,
+                logged.lng
             );
             return Ok(db_pois.into_iter().take(limit).collect());
         }
@@ -45,18 +86,23 @@ impl PoiService {
         tracing::warn!(
             "No POIs found in database within {:.1}km of ({:.4}, {:.4})",
             radius_km,
-            center.lat,
-            center.lng
+            logged.lat,
+            logged.lng
         );
 
         Err(AppError::NoPoisFound(format!(
             "No POIs found in database within {:.1}km of coordinates ({:.4}, {:.4}). \
              This area may not be covered by the current OSM import. \
              Try a different location or contact support to request data import for this region.",
-            radius_km, center.lat, center.lng
+            radius_km, logged.lat, logged.lng
         )))
     }
 
+    /// POIs by ID; unknown IDs are skipped
+    pub async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Poi>> {
+        self.repo.find_by_ids(ids).await
+    }
+
     /// Score and filter POIs based on preferences
     pub fn select_top_pois(&self, pois: Vec<Poi>, hidden_gems: bool, max_count: usize) -> Vec<Poi> {
         let mut scored_pois: Vec<(f32, Poi)> = pois
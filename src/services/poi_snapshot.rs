@@ -0,0 +1,402 @@
+//! Read-only in-memory POI snapshots of the busiest cities (`WARM_CITIES`).
+//!
+//! Each configured city is loaded from the [`PoiRepository`] into a static
+//! 2-d tree and reloaded every `WARM_CITIES_REFRESH_SECS`. [`PoiService`]
+//! answers radius searches that fall entirely inside a city from its
+//! snapshot, with no database round-trip, and goes to the repository for
+//! everything else.
+//!
+//! [`PoiService`]: crate::services::poi_service::PoiService
+
+use crate::config::{WarmCitiesConfig, WarmCity};
+use crate::constants::WARM_CITY_MAX_POIS;
+use crate::db::PoiRepository;
+use crate::models::{BoundingBox, Coordinates, Poi, PoiCategory};
+use std::cmp::Ordering;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Static 2-d tree over POI coordinates, stored implicitly: the middle
+/// element of every subslice splits it on latitude (even depths) or
+/// longitude (odd depths)
+struct KdTree {
+    pois: Vec<Poi>,
+}
+
+fn axis_value(poi: &Poi, depth: usize) -> f64 {
+    if depth % 2 == 0 {
+        poi.coordinates.lat
+    } else {
+        poi.coordinates.lng
+    }
+}
+
+impl KdTree {
+    fn new(mut pois: Vec<Poi>) -> Self {
+        Self::build(&mut pois, 0);
+        KdTree { pois }
+    }
+
+    fn build(pois: &mut [Poi], depth: usize) {
+        if pois.len() <= 1 {
+            return;
+        }
+        let mid = pois.len() / 2;
+        pois.select_nth_unstable_by(mid, |a, b| {
+            axis_value(a, depth).total_cmp(&axis_value(b, depth))
+        });
+        let (below, above) = pois.split_at_mut(mid);
+        Self::build(below, depth + 1);
+        Self::build(&mut above[1..], depth + 1);
+    }
+
+    /// POIs inside `bbox`, whose longitudes must be within [-180, 180]
+    fn in_bbox<'a>(&'a self, bbox: &BoundingBox, found: &mut Vec<&'a Poi>) {
+        Self::search(&self.pois, 0, bbox, found);
+    }
+
+    fn search<'a>(pois: &'a [Poi], depth: usize, bbox: &BoundingBox, found: &mut Vec<&'a Poi>) {
+        if pois.is_empty() {
+            return;
+        }
+        let mid = pois.len() / 2;
+        let poi = &pois[mid];
+        let c = &poi.coordinates;
+        if (bbox.min_lat..=bbox.max_lat).contains(&c.lat)
+            && (bbox.min_lng..=bbox.max_lng).contains(&c.lng)
+        {
+            found.push(poi);
+        }
+
+        let split = axis_value(poi, depth);
+        let (min, max) = if depth % 2 == 0 {
+            (bbox.min_lat, bbox.max_lat)
+        } else {
+            (bbox.min_lng, bbox.max_lng)
+        };
+        if min <= split {
+            Self::search(&pois[..mid], depth + 1, bbox, found);
+        }
+        if split <= max {
+            Self::search(&pois[mid + 1..], depth + 1, bbox, found);
+        }
+    }
+}
+
+/// Every POI within a city's radius, as of `loaded_at`
+pub struct CitySnapshot {
+    pub name: String,
+    center: Coordinates,
+    radius_m: f64,
+    tree: KdTree,
+    loaded_at: Instant,
+}
+
+impl CitySnapshot {
+    pub fn new(name: String, center: Coordinates, radius_m: f64, pois: Vec<Poi>) -> Self {
+        CitySnapshot {
+            name,
+            center,
+            radius_m,
+            tree: KdTree::new(pois),
+            loaded_at: Instant::now(),
+        }
+    }
+
+    pub fn poi_count(&self) -> usize {
+        self.tree.pois.len()
+    }
+
+    pub fn age(&self) -> Duration {
+        self.loaded_at.elapsed()
+    }
+
+    /// Whether the search circle lies entirely inside the snapshot's
+    pub fn covers(&self, center: &Coordinates, radius_m: f64) -> bool {
+        self.center.distance_to(center) * 1000.0 + radius_m <= self.radius_m
+    }
+
+    /// Same results as [`PoiRepository::find_within_radius`]: POIs within
+    /// `radius_m`, optionally of `categories`, nearest first
+    pub fn find_within_radius(
+        &self,
+        center: &Coordinates,
+        radius_m: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: usize,
+    ) -> Vec<Poi> {
+        let mut candidates = Vec::new();
+        for part in BoundingBox::from_center_radius(center, radius_m).split_at_antimeridian() {
+            self.tree.in_bbox(&part, &mut candidates);
+        }
+
+        let mut results: Vec<(f64, &Poi)> = candidates
+            .into_iter()
+            .filter(|poi| categories.map_or(true, |cats| cats.contains(&poi.category)))
+            .filter_map(|poi| {
+                let dist_m = center.distance_to(&poi.coordinates) * 1000.0;
+                (dist_m <= radius_m).then_some((dist_m, poi))
+            })
+            .collect();
+        results.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        results
+            .into_iter()
+            .take(limit)
+            .map(|(_, poi)| poi.clone())
+            .collect()
+    }
+}
+
+/// The snapshots of every warm city, swapped in whole on each refresh
+pub struct PoiSnapshots {
+    cities: Vec<WarmCity>,
+    snapshots: RwLock<Vec<Arc<CitySnapshot>>>,
+}
+
+impl PoiSnapshots {
+    /// Empty until the first [`refresh`](Self::refresh)
+    pub fn new(config: &WarmCitiesConfig) -> Self {
+        PoiSnapshots {
+            cities: config.cities.clone(),
+            snapshots: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// The snapshot of a city the search circle lies in, if any
+    pub fn covering(&self, center: &Coordinates, radius_m: f64) -> Option<Arc<CitySnapshot>> {
+        self.snapshots
+            .read()
+            .unwrap()
+            .iter()
+            .find(|snapshot| snapshot.covers(center, radius_m))
+            .cloned()
+    }
+
+    /// Reload every city from `repo`. A city that fails to load keeps its
+    /// previous snapshot; one reaching [`WARM_CITY_MAX_POIS`] is dropped, as
+    /// its snapshot would be missing POIs.
+    pub async fn refresh(&self, repo: &dyn PoiRepository) {
+        let previous = self.snapshots.read().unwrap().clone();
+        let mut snapshots = Vec::with_capacity(self.cities.len());
+        for city in &self.cities {
+            let Ok(center) = Coordinates::new(city.lat, city.lng) else {
+                continue;
+            };
+            let radius_m = city.radius_km * 1000.0;
+            let started = Instant::now();
+            match repo
+                .find_within_radius(&center, radius_m, None, WARM_CITY_MAX_POIS)
+                .await
+            {
+                Ok(pois) if pois.len() as i64 >= WARM_CITY_MAX_POIS => {
+                    tracing::warn!(
+                        city = %city.name,
+                        "Warm city {} has over {} POIs, serving it from the database",
+                        city.name,
+                        WARM_CITY_MAX_POIS
+                    );
+                }
+                Ok(pois) => {
+                    tracing::info!(
+                        city = %city.name,
+                        pois = pois.len(),
+                        "Loaded {} POIs for warm city {} in {}ms",
+                        pois.len(),
+                        city.name,
+                        started.elapsed().as_millis()
+                    );
+                    snapshots.push(Arc::new(CitySnapshot::new(
+                        city.name.clone(),
+                        center,
+                        radius_m,
+                        pois,
+                    )));
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        city = %city.name,
+                        error = %e,
+                        "Failed to refresh warm city {}, keeping the previous snapshot: {}",
+                        city.name,
+                        e
+                    );
+                    snapshots.extend(previous.iter().find(|s| s.name == city.name).cloned());
+                }
+            }
+        }
+        *self.snapshots.write().unwrap() = snapshots;
+    }
+
+    /// Refresh now, then every `every`, for as long as the process runs
+    pub fn spawn_refresh(self: Arc<Self>, repo: Arc<dyn PoiRepository>, every: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                self.refresh(repo.as_ref()).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{AppError, Result};
+    use async_trait::async_trait;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use uuid::Uuid;
+
+    fn poi_at(lat: f64, lng: f64, category: PoiCategory) -> Poi {
+        Poi::new(
+            format!("{:.4},{:.4}", lat, lng),
+            category,
+            Coordinates::new(lat, lng).unwrap(),
+            50.0,
+        )
+    }
+
+    fn scattered_pois(count: usize) -> Vec<Poi> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..count)
+            .map(|i| {
+                let category = if i % 3 == 0 {
+                    PoiCategory::Park
+                } else {
+                    PoiCategory::Museum
+                };
+                poi_at(
+                    48.80 + rng.gen::<f64>() * 0.12,
+                    2.25 + rng.gen::<f64>() * 0.20,
+                    category,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_a_linear_scan() {
+        let pois = scattered_pois(2_000);
+        let center = Coordinates::new(48.8566, 2.3522).unwrap();
+        let snapshot = CitySnapshot::new("paris".to_string(), center, 10_000.0, pois.clone());
+
+        let search = Coordinates::new(48.87, 2.33).unwrap();
+        let categories = [PoiCategory::Park];
+        let found = snapshot.find_within_radius(&search, 1_500.0, Some(&categories), usize::MAX);
+
+        let mut expected: Vec<&Poi> = pois
+            .iter()
+            .filter(|p| p.category == PoiCategory::Park)
+            .filter(|p| search.distance_to(&p.coordinates) * 1000.0 <= 1_500.0)
+            .collect();
+        expected.sort_by(|a, b| {
+            search
+                .distance_to(&a.coordinates)
+                .total_cmp(&search.distance_to(&b.coordinates))
+        });
+        let ids = |pois: Vec<&Poi>| pois.into_iter().map(|p| p.id).collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(ids(found.iter().collect()), ids(expected));
+
+        assert_eq!(
+            snapshot.find_within_radius(&search, 1_500.0, None, 5).len(),
+            5
+        );
+    }
+
+    #[test]
+    fn covers_only_circles_inside_the_city() {
+        let center = Coordinates::new(48.8566, 2.3522).unwrap();
+        let snapshot = CitySnapshot::new("paris".to_string(), center, 10_000.0, Vec::new());
+
+        assert!(snapshot.covers(&center, 10_000.0));
+        assert!(!snapshot.covers(&center, 10_001.0));
+        let edge = Coordinates::new(48.93, 2.3522).unwrap();
+        assert!(!snapshot.covers(&edge, 2_000.0));
+    }
+
+    /// Serves fixed POIs, or fails while `failing` is set
+    struct FixedRepository {
+        pois: Vec<Poi>,
+        failing: AtomicBool,
+    }
+
+    #[async_trait]
+    impl PoiRepository for FixedRepository {
+        async fn find_within_radius(
+            &self,
+            _center: &Coordinates,
+            _radius_meters: f64,
+            _categories: Option<&[PoiCategory]>,
+            _limit: i64,
+        ) -> Result<Vec<Poi>> {
+            if self.failing.load(Ordering::Relaxed) {
+                return Err(AppError::Database(sqlx::Error::PoolTimedOut));
+            }
+            Ok(self.pois.clone())
+        }
+
+        async fn find_in_bbox(
+            &self,
+            _min_lat: f64,
+            _max_lat: f64,
+            _min_lng: f64,
+            _max_lng: f64,
+            _categories: Option<&[PoiCategory]>,
+            _limit: i64,
+        ) -> Result<Vec<Poi>> {
+            Ok(Vec::new())
+        }
+
+        async fn find_by_ids(&self, _ids: &[Uuid]) -> Result<Vec<Poi>> {
+            Ok(Vec::new())
+        }
+
+        async fn find_by_osm_ids(&self, _osm_ids: &[i64]) -> Result<Vec<Poi>> {
+            Ok(Vec::new())
+        }
+
+        async fn insert(&self, _poi: &Poi) -> Result<Uuid> {
+            Ok(Uuid::new_v4())
+        }
+
+        async fn count(&self) -> Result<i64> {
+            Ok(self.pois.len() as i64)
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_refresh_keeps_the_previous_snapshot() {
+        let snapshots = PoiSnapshots::new(&WarmCitiesConfig {
+            cities: vec![WarmCity {
+                name: "paris".to_string(),
+                lat: 48.8566,
+                lng: 2.3522,
+                radius_km: 10.0,
+            }],
+            refresh_secs: 900,
+        });
+        let center = Coordinates::new(48.8566, 2.3522).unwrap();
+        let repo = FixedRepository {
+            pois: scattered_pois(100),
+            failing: AtomicBool::new(false),
+        };
+        assert!(snapshots.covering(&center, 1_000.0).is_none());
+
+        snapshots.refresh(&repo).await;
+        let snapshot = snapshots.covering(&center, 1_000.0).unwrap();
+        assert_eq!(snapshot.poi_count(), 100);
+        assert!(snapshots.covering(&center, 20_000.0).is_none());
+
+        repo.failing.store(true, Ordering::Relaxed);
+        snapshots.refresh(&repo).await;
+        assert_eq!(
+            snapshots.covering(&center, 1_000.0).unwrap().poi_count(),
+            100
+        );
+    }
+}
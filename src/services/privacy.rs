@@ -0,0 +1,140 @@
+//! Start-location privacy.
+//!
+//! Start points are often users' homes. With privacy mode on, the exact start
+//! is only used to generate the route; anything that outlives the request
+//! (evaluation tables, request log) gets a jittered point, and log lines get a
+//! coarsely rounded one.
+
+use rand::Rng;
+
+use crate::constants::METERS_PER_DEGREE;
+use crate::models::coordinates::wrap_lng;
+use crate::models::route::{LoopRouteRequest, MustInclude};
+use crate::models::Coordinates;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocationPrivacy {
+    /// Stored points are moved up to this far from the real start
+    pub jitter_m: f64,
+    /// Decimal places kept in log lines (2 ≈ 1.1 km)
+    pub log_decimals: i32,
+}
+
+impl LocationPrivacy {
+    pub fn new(jitter_m: f64, log_decimals: i32) -> Self {
+        LocationPrivacy {
+            jitter_m,
+            log_decimals,
+        }
+    }
+
+    /// Point to store instead of `point`: uniformly random within `jitter_m`
+    pub fn fuzz(&self, point: &Coordinates) -> Coordinates {
+        self.fuzz_with(point, &mut rand::thread_rng())
+    }
+
+    pub fn fuzz_with<R: Rng>(&self, point: &Coordinates, rng: &mut R) -> Coordinates {
+        let distance_m = self.jitter_m * rng.gen::<f64>().sqrt();
+        let bearing = rng.gen::<f64>() * std::f64::consts::TAU;
+        // Near the poles a meter of longitude is many degrees; cap the scale
+        let lng_scale = point.lat.to_radians().cos().max(0.01);
+        Coordinates {
+            lat: (point.lat + distance_m * bearing.cos() / METERS_PER_DEGREE).clamp(-90.0, 90.0),
+            lng: wrap_lng(point.lng + distance_m * bearing.sin() / (METERS_PER_DEGREE * lng_scale)),
+        }
+    }
+
+    /// Point to write to logs and telemetry: rounded to `log_decimals`
+    pub fn for_logs(&self, point: &Coordinates) -> Coordinates {
+        let factor = 10f64.powi(self.log_decimals);
+        Coordinates {
+            lat: (point.lat * factor).round() / factor,
+            lng: (point.lng * factor).round() / factor,
+        }
+    }
+
+    /// Jitter and round every point of `request` (the start and pinned
+    /// points) before it's logged
+    pub fn scrub_request(&self, request: &mut LoopRouteRequest) {
+        let scrub = |point: &Coordinates| self.for_logs(&self.fuzz(point));
+        request.start_point = scrub(&request.start_point);
+        for pin in &mut request.preferences.must_include {
+            if let MustInclude::Point(point) = pin {
+                *point = scrub(point);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_fuzz_stays_within_jitter_radius() {
+        let privacy = LocationPrivacy::new(100.0, 2);
+        let home = Coordinates::new(48.856_614, 2.352_222).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let mut moved = 0;
+        for _ in 0..500 {
+            let fuzzed = privacy.fuzz_with(&home, &mut rng);
+            let distance_m = home.distance_to(&fuzzed) * 1000.0;
+            assert!(distance_m <= 101.0, "moved {:.1} m", distance_m);
+            if distance_m > 10.0 {
+                moved += 1;
+            }
+        }
+        // Uniform over the disc: ~99% of points land beyond 10 m
+        assert!(moved > 450);
+    }
+
+    #[test]
+    fn test_fuzz_stays_valid_at_the_edges() {
+        let privacy = LocationPrivacy::new(500.0, 2);
+        let mut rng = StdRng::seed_from_u64(5);
+        for point in [
+            Coordinates::new(89.9999, 10.0).unwrap(),
+            Coordinates::new(0.0, 179.9999).unwrap(),
+        ] {
+            for _ in 0..100 {
+                let fuzzed = privacy.fuzz_with(&point, &mut rng);
+                assert!(Coordinates::new(fuzzed.lat, fuzzed.lng).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_for_logs_rounds() {
+        let privacy = LocationPrivacy::new(100.0, 2);
+        let rounded = privacy.for_logs(&Coordinates::new(48.856_614, 2.352_222).unwrap());
+        assert_eq!(rounded.lat, 48.86);
+        assert_eq!(rounded.lng, 2.35);
+    }
+
+    #[test]
+    fn test_scrub_request_covers_pinned_points() {
+        let privacy = LocationPrivacy::new(100.0, 2);
+        let mut request: LoopRouteRequest = serde_json::from_value(serde_json::json!({
+            "start_point": {"lat": 48.856_614, "lng": 2.352_222},
+            "distance_km": 5.0,
+            "mode": "walk",
+            "preferences": {"must_include": [{"lat": 48.861_234, "lng": 2.335_678}]}
+        }))
+        .unwrap();
+        privacy.scrub_request(&mut request);
+
+        let rounded = |point: &Coordinates| {
+            [point.lat, point.lng]
+                .iter()
+                .all(|v| (v * 100.0 - (v * 100.0).round()).abs() < 1e-9)
+        };
+        assert!(rounded(&request.start_point));
+        match request.preferences.must_include[0] {
+            MustInclude::Point(ref point) => assert!(rounded(point)),
+            MustInclude::Poi(_) => panic!("pinned point became a POI"),
+        }
+    }
+}
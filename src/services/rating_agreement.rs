@@ -0,0 +1,262 @@
+//! Inter-rater agreement over human route ratings.
+//!
+//! Routes are the units and ratings the values. Krippendorff's alpha handles
+//! any number of raters per route and missing ratings; the weighted kappa
+//! pools every pair of ratings on the same route, weighting each route's
+//! pairs so every rating counts once, and compares them with the pooled
+//! rating distribution.
+
+use std::collections::{BTreeMap, HashSet};
+
+use uuid::Uuid;
+
+use crate::constants::{AGREEMENT_MOST_DISPUTED_LIMIT, AGREEMENT_RELIABLE_ALPHA};
+use crate::models::evaluation::{
+    DimensionAgreement, RatingAgreement, RouteRating, RouteRatingSpread,
+};
+
+type Dimension = (&'static str, fn(&RouteRating) -> Option<i16>);
+
+const DIMENSIONS: [Dimension; 4] = [
+    ("overall", |r| Some(r.overall_rating)),
+    ("shape", |r| r.shape_rating),
+    ("scenicness", |r| r.scenicness_rating),
+    ("variety", |r| r.variety_rating),
+];
+
+/// Ratings are on a 1-5 scale
+const SCALE_MIN: i16 = 1;
+const SCALE_POINTS: usize = 5;
+
+pub fn rating_agreement(ratings: &[RouteRating]) -> RatingAgreement {
+    let mut by_route: BTreeMap<Uuid, Vec<&RouteRating>> = BTreeMap::new();
+    for rating in ratings {
+        by_route.entry(rating.route_id).or_default().push(rating);
+    }
+
+    let spreads: Vec<RouteRatingSpread> = by_route
+        .iter()
+        .filter(|(_, route_ratings)| route_ratings.len() >= 2)
+        .map(|(route_id, route_ratings)| spread(*route_id, route_ratings))
+        .collect();
+    let mean_rating_variance = (!spreads.is_empty())
+        .then(|| spreads.iter().map(|s| s.rating_variance).sum::<f64>() / spreads.len() as f64);
+
+    let dimensions: Vec<DimensionAgreement> = DIMENSIONS
+        .iter()
+        .map(|(name, extract)| {
+            let units: Vec<Vec<i16>> = by_route
+                .values()
+                .map(|route_ratings| route_ratings.iter().filter_map(|r| extract(r)).collect())
+                .filter(|values: &Vec<i16>| values.len() >= 2)
+                .collect();
+            dimension_agreement(name, &units)
+        })
+        .collect();
+    let reliable = dimensions[0]
+        .krippendorff_alpha
+        .is_some_and(|alpha| alpha >= AGREEMENT_RELIABLE_ALPHA);
+
+    let mut most_disputed = spreads.clone();
+    most_disputed.sort_by(|a, b| b.rating_variance.total_cmp(&a.rating_variance));
+    most_disputed.truncate(AGREEMENT_MOST_DISPUTED_LIMIT);
+
+    RatingAgreement {
+        rated_routes: by_route.len(),
+        multiply_rated_routes: spreads.len(),
+        raters: ratings
+            .iter()
+            .filter_map(|r| r.rater_id.as_deref())
+            .collect::<HashSet<_>>()
+            .len(),
+        anonymous_ratings: ratings.iter().filter(|r| r.rater_id.is_none()).count(),
+        mean_rating_variance,
+        dimensions,
+        reliable,
+        most_disputed,
+    }
+}
+
+fn spread(route_id: Uuid, ratings: &[&RouteRating]) -> RouteRatingSpread {
+    let values: Vec<f64> = ratings.iter().map(|r| r.overall_rating as f64).collect();
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let named: HashSet<&str> = ratings
+        .iter()
+        .filter_map(|r| r.rater_id.as_deref())
+        .collect();
+    let anonymous = ratings.iter().filter(|r| r.rater_id.is_none()).count();
+    RouteRatingSpread {
+        route_id,
+        rating_count: ratings.len(),
+        rater_count: named.len() + anonymous,
+        mean_rating: mean,
+        rating_variance: variance,
+    }
+}
+
+/// Agreement over `units`, each the ratings one route received (2 or more)
+fn dimension_agreement(name: &str, units: &[Vec<i16>]) -> DimensionAgreement {
+    // Coincidence matrix: every ordered pair of ratings within a route,
+    // weighted 1 / (m - 1) so each rating contributes a total weight of 1
+    let mut coincidences = [[0.0_f64; SCALE_POINTS]; SCALE_POINTS];
+    for values in units {
+        let weight = 1.0 / (values.len() - 1) as f64;
+        for (i, &a) in values.iter().enumerate() {
+            for (j, &b) in values.iter().enumerate() {
+                if i != j {
+                    coincidences[scale_index(a)][scale_index(b)] += weight;
+                }
+            }
+        }
+    }
+    let n: f64 = coincidences.iter().flatten().sum();
+    let marginals: Vec<f64> = coincidences.iter().map(|row| row.iter().sum()).collect();
+
+    // Squared distances between ratings: observed within routes, and
+    // expected from the pooled distribution
+    let mut observed = 0.0;
+    let mut expected = 0.0;
+    let mut exact = 0.0;
+    let mut within_one = 0.0;
+    for (a, row) in coincidences.iter().enumerate() {
+        for (b, &count) in row.iter().enumerate() {
+            let distance = (a as f64 - b as f64).powi(2);
+            observed += count * distance;
+            expected += marginals[a] * marginals[b] * distance;
+            if a == b {
+                exact += count;
+            }
+            if a.abs_diff(b) <= 1 {
+                within_one += count;
+            }
+        }
+    }
+
+    // The two differ only in alpha's small-sample correction (n - 1 for n).
+    // Both are undefined when every rating is the same.
+    let pairable = n.round() as usize;
+    let has_pairs = pairable >= 2;
+    let varied = has_pairs && expected > 0.0;
+    let krippendorff_alpha = varied.then(|| 1.0 - (n - 1.0) * observed / expected);
+    let weighted_kappa = varied.then(|| 1.0 - n * observed / expected);
+
+    DimensionAgreement {
+        dimension: name.to_string(),
+        krippendorff_alpha,
+        weighted_kappa,
+        exact_agreement: has_pairs.then(|| exact / n),
+        within_one_agreement: has_pairs.then(|| within_one / n),
+        pairable_ratings: pairable,
+    }
+}
+
+fn scale_index(rating: i16) -> usize {
+    (rating - SCALE_MIN).clamp(0, SCALE_POINTS as i16 - 1) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(route_id: Uuid, rater: Option<&str>, overall: i16) -> RouteRating {
+        RouteRating {
+            id: Uuid::new_v4(),
+            route_id,
+            overall_rating: overall,
+            shape_rating: None,
+            scenicness_rating: None,
+            variety_rating: None,
+            comment: None,
+            rater_id: rater.map(str::to_string),
+            created_at: None,
+        }
+    }
+
+    /// Each route gets one rating per value
+    fn ratings(values_per_route: &[&[i16]]) -> Vec<RouteRating> {
+        values_per_route
+            .iter()
+            .flat_map(|values| {
+                let route = Uuid::new_v4();
+                values
+                    .iter()
+                    .enumerate()
+                    .map(move |(i, &v)| rating(route, Some(["a", "b", "c"][i]), v))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_perfect_agreement() {
+        let agreement = rating_agreement(&ratings(&[&[5, 5], &[2, 2, 2], &[4, 4]]));
+        let overall = &agreement.dimensions[0];
+        assert_eq!(overall.krippendorff_alpha, Some(1.0));
+        assert_eq!(overall.weighted_kappa, Some(1.0));
+        assert_eq!(overall.exact_agreement, Some(1.0));
+        assert_eq!(overall.pairable_ratings, 7);
+        assert!(agreement.reliable);
+        assert_eq!(agreement.mean_rating_variance, Some(0.0));
+    }
+
+    #[test]
+    fn test_krippendorff_reference_value() {
+        // n = 4 values; the split route contributes 2 ordered pairs 1 apart.
+        // Pooled values 1,1,4,5 give 102 over all ordered pairs of values.
+        let agreement = rating_agreement(&ratings(&[&[1, 1], &[4, 5]]));
+        let overall = &agreement.dimensions[0];
+        let alpha = overall.krippendorff_alpha.unwrap();
+        assert!(
+            (alpha - (1.0 - 3.0 * 2.0 / 102.0)).abs() < 1e-9,
+            "{}",
+            alpha
+        );
+        assert_eq!(overall.exact_agreement, Some(0.5));
+        assert_eq!(overall.within_one_agreement, Some(1.0));
+    }
+
+    #[test]
+    fn test_disagreement_is_unreliable() {
+        let agreement = rating_agreement(&ratings(&[&[1, 5], &[5, 1], &[3, 3]]));
+        let overall = &agreement.dimensions[0];
+        assert!(overall.krippendorff_alpha.unwrap() < 0.0);
+        assert!(!agreement.reliable);
+        assert_eq!(agreement.most_disputed[0].rating_variance, 8.0);
+        assert_eq!(agreement.most_disputed.last().unwrap().rating_variance, 0.0);
+    }
+
+    #[test]
+    fn test_counts_and_missing_dimensions() {
+        let route = Uuid::new_v4();
+        let mut all = vec![
+            rating(route, Some("a"), 4),
+            rating(route, None, 3),
+            rating(route, None, 4),
+            rating(Uuid::new_v4(), Some("b"), 2),
+        ];
+        all[0].shape_rating = Some(4);
+        let agreement = rating_agreement(&all);
+        assert_eq!(agreement.rated_routes, 2);
+        assert_eq!(agreement.multiply_rated_routes, 1);
+        assert_eq!(agreement.raters, 2);
+        assert_eq!(agreement.anonymous_ratings, 2);
+        assert_eq!(agreement.most_disputed[0].rater_count, 3);
+        // A single shape rating can't be compared with anything
+        let shape = &agreement.dimensions[1];
+        assert_eq!(shape.pairable_ratings, 0);
+        assert_eq!(shape.krippendorff_alpha, None);
+    }
+
+    #[test]
+    fn test_no_ratings() {
+        let agreement = rating_agreement(&[]);
+        assert_eq!(agreement.rated_routes, 0);
+        assert_eq!(agreement.mean_rating_variance, None);
+        assert!(!agreement.reliable);
+        assert!(agreement
+            .dimensions
+            .iter()
+            .all(|d| d.krippendorff_alpha.is_none()));
+    }
+}
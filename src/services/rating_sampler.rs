@@ -0,0 +1,193 @@
+//! Picks which evaluated routes human raters should see next
+//! (`GET /api/v1/evaluations/next`).
+//!
+//! Each route is placed in a metric space (shape, overlap, POI density and
+//! variety). Its priority blends two kinds of uncertainty about it:
+//! - novelty: how far it sits from already rated routes of the same mode,
+//!   i.e. how sparsely rated its metric region is
+//! - disagreement: how far the rating predicted from its rated neighbours
+//!   is from what the system score implies
+//!
+//! Routes scoring high on either teach the most per rating.
+
+use uuid::Uuid;
+
+use crate::constants::{
+    SAMPLER_DENSITY_SCALE, SAMPLER_NEIGHBORS, SAMPLER_NOVELTY_RADIUS, SAMPLER_NOVELTY_WEIGHT,
+};
+use crate::db::queries::EvaluationExportRow;
+use crate::models::evaluation::RatingCandidate;
+
+/// Metric coordinates, each scaled to 0-1. Missing metrics sit mid-range.
+fn features(row: &EvaluationExportRow) -> [f64; 6] {
+    let metric = |value: Option<f32>| value.map_or(0.5, |v| (v as f64).clamp(0.0, 1.0));
+    [
+        metric(row.circularity),
+        metric(row.convexity),
+        metric(row.path_overlap_pct),
+        metric(row.poi_density_per_km.map(|d| d / SAMPLER_DENSITY_SCALE)),
+        metric(row.category_entropy),
+        metric(row.landmark_coverage),
+    ]
+}
+
+fn distance(a: &[f64; 6], b: &[f64; 6]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// The 1-5 rating a system score (0-10) corresponds to
+fn score_as_rating(system_score: f32) -> f64 {
+    1.0 + 4.0 * (system_score as f64 / 10.0).clamp(0.0, 1.0)
+}
+
+/// Rank `candidates` for rating, highest priority first, keeping `limit`.
+/// `rated` are routes with at least one rating; a candidate that is itself
+/// rated is not its own neighbour.
+pub fn next_to_rate(
+    candidates: &[EvaluationExportRow],
+    rated: &[EvaluationExportRow],
+    limit: usize,
+) -> Vec<RatingCandidate> {
+    let rated: Vec<(Uuid, &str, [f64; 6], f64)> = rated
+        .iter()
+        .filter_map(|row| {
+            row.avg_overall_rating
+                .map(|rating| (row.id, row.transport_mode.as_str(), features(row), rating))
+        })
+        .collect();
+
+    let mut ranked: Vec<RatingCandidate> = candidates
+        .iter()
+        .map(|candidate| {
+            let position = features(candidate);
+            let mut neighbours: Vec<(f64, f64)> = rated
+                .iter()
+                .filter(|(id, mode, _, _)| *id != candidate.id && *mode == candidate.transport_mode)
+                .map(|(_, _, point, rating)| (distance(&position, point), *rating))
+                .collect();
+            neighbours.sort_by(|a, b| a.0.total_cmp(&b.0));
+            neighbours.truncate(SAMPLER_NEIGHBORS);
+
+            // With no rated neighbours the region is wholly unexplored and
+            // there is nothing to disagree with
+            let (novelty, predicted_rating) = if neighbours.is_empty() {
+                (1.0, None)
+            } else {
+                let n = neighbours.len() as f64;
+                let mean_distance = neighbours.iter().map(|(d, _)| d).sum::<f64>() / n;
+                let predicted = neighbours.iter().map(|(_, r)| r).sum::<f64>() / n;
+                (
+                    (mean_distance / SAMPLER_NOVELTY_RADIUS).min(1.0),
+                    Some(predicted),
+                )
+            };
+            let disagreement = predicted_rating.map_or(0.0, |predicted| {
+                ((predicted - score_as_rating(candidate.system_score)).abs() / 4.0).min(1.0)
+            });
+
+            RatingCandidate {
+                route_id: candidate.id,
+                transport_mode: candidate.transport_mode.clone(),
+                system_score: candidate.system_score,
+                rating_count: candidate.rating_count,
+                predicted_rating,
+                novelty,
+                disagreement,
+                priority: SAMPLER_NOVELTY_WEIGHT * novelty
+                    + (1.0 - SAMPLER_NOVELTY_WEIGHT) * disagreement,
+            }
+        })
+        .collect();
+
+    // Fewer existing ratings first among equals
+    ranked.sort_by(|a, b| {
+        b.priority
+            .total_cmp(&a.priority)
+            .then(a.rating_count.cmp(&b.rating_count))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(circularity: f32, system_score: f32, avg_rating: Option<f64>) -> EvaluationExportRow {
+        EvaluationExportRow {
+            id: Uuid::new_v4(),
+            created_at: "2024-06-01 12:00:00+00".to_string(),
+            start_lat: 48.8566,
+            start_lng: 2.3522,
+            target_distance_km: 5.0,
+            transport_mode: "walk".to_string(),
+            actual_distance_km: 5.0,
+            duration_minutes: 60,
+            poi_count: 3,
+            snapped_poi_count: 0,
+            circularity: Some(circularity),
+            convexity: Some(0.8),
+            path_overlap_pct: Some(0.1),
+            poi_density_per_km: Some(1.0),
+            category_entropy: Some(0.7),
+            landmark_coverage: Some(0.5),
+            system_score,
+            poi_density_context: None,
+            scoring_strategy: "simple".to_string(),
+            rating_count: avg_rating.map_or(0, |_| 1),
+            avg_overall_rating: avg_rating,
+            avg_shape_rating: None,
+            avg_scenicness_rating: None,
+            avg_variety_rating: None,
+        }
+    }
+
+    #[test]
+    fn test_unexplored_region_ranks_first() {
+        let rated: Vec<_> = (0..5).map(|_| row(0.8, 7.5, Some(4.0))).collect();
+        let familiar = row(0.8, 7.5, None);
+        let unexplored = row(0.1, 7.5, None);
+        let ranked = next_to_rate(&[familiar.clone(), unexplored.clone()], &rated, 10);
+        assert_eq!(ranked[0].route_id, unexplored.id);
+        assert!(ranked[0].novelty > ranked[1].novelty);
+        // 7.5 maps to a rating of 4, matching the neighbours
+        assert_eq!(ranked[1].novelty, 0.0);
+        assert_eq!(ranked[1].disagreement, 0.0);
+    }
+
+    #[test]
+    fn test_score_disagreement_raises_priority() {
+        // Neighbours rate the region poorly but the system scores highly
+        let rated: Vec<_> = (0..5).map(|_| row(0.8, 7.5, Some(1.5))).collect();
+        let overrated = row(0.8, 9.5, None);
+        let consistent = row(0.8, 1.0, None);
+        let ranked = next_to_rate(&[consistent, overrated.clone()], &rated, 1);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].route_id, overrated.id);
+        assert_eq!(ranked[0].predicted_rating, Some(1.5));
+        assert!(ranked[0].disagreement > 0.5);
+    }
+
+    #[test]
+    fn test_neighbours_share_transport_mode_and_exclude_self() {
+        let mut bike = row(0.8, 7.5, Some(4.0));
+        bike.transport_mode = "bike".to_string();
+        let rated_walk = row(0.8, 7.5, Some(4.0));
+        let rated = [bike, rated_walk];
+        let ranked = next_to_rate(&rated[1..], &rated, 10);
+        assert_eq!(ranked[0].predicted_rating, None);
+        assert_eq!(ranked[0].novelty, 1.0);
+    }
+
+    #[test]
+    fn test_cold_start_prefers_unrated_routes() {
+        let once = row(0.8, 5.0, Some(3.0));
+        let never = row(0.8, 5.0, None);
+        let ranked = next_to_rate(&[once, never.clone()], &[], 10);
+        assert_eq!(ranked[0].route_id, never.id);
+    }
+}
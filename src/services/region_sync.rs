@@ -0,0 +1,868 @@
+//! Keeps an offline server's region database current.
+//!
+//! With `REGION_CATALOG_URL` set, the server looks its region up in the
+//! region proxy's catalog (`GET /v1/regions`) at startup and every
+//! `REGION_SYNC_INTERVAL_SECS`. A build newer than the one being served is
+//! downloaded next to `REGION_DB_PATH`, checked against the SHA-256 the
+//! catalog publishes for the whole file (hashed as it streams in, before
+//! SQLite ever opens it), verified against `REGION_TRUSTED_KEY` when one is
+//! set, and swapped in behind a [`SwappablePoiRepository`] without a restart.
+//!
+//! Files are never replaced in place: SQLite in WAL mode keeps `-wal` and
+//! `-shm` files named after the database, so renaming over an open file
+//! would corrupt its readers. Each build gets its own `<id>.<stamp>.db`
+//! file instead, and the newest one is picked up again after a restart.
+//! `REGION_DB_PATH` itself is never written or deleted.
+
+use crate::config::RegionSyncConfig;
+use crate::constants::{REGION_CATALOG_TIMEOUT_SECS, REGION_DOWNLOAD_STALL_SECS};
+use crate::db::sqlite_repo::parse_verifying_key;
+use crate::db::{PoiRepository, RegionIntegrityError, SqlitePoiRepository, SqliteReadPoolConfig};
+use crate::error::Result;
+use crate::models::road_profile::RoadProfile;
+use crate::models::route::SnappedPoi;
+use crate::models::{Coordinates, Poi, PoiCategory};
+use async_trait::async_trait;
+use ed25519_dalek::VerifyingKey;
+use reqwest::{header, Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use thiserror::Error;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Suffix of a build being downloaded, before it is verified
+const PARTIAL_SUFFIX: &str = ".download";
+
+#[derive(Error, Debug)]
+pub enum RegionSyncError {
+    #[error("Region catalog request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Region catalog returned HTTP {0}")]
+    Status(StatusCode),
+
+    #[error("Region '{0}' has no build in the catalog")]
+    NotInCatalog(String),
+
+    #[error("Region '{0}' has no SHA-256 in the catalog")]
+    MissingChecksum(String),
+
+    #[error("Downloaded region SHA-256 {actual} does not match the catalog's {expected}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Downloaded region failed verification: {0}")]
+    Integrity(#[from] RegionIntegrityError),
+
+    #[error("Invalid REGION_TRUSTED_KEY: {0}")]
+    TrustedKey(String),
+
+    #[error("Region file error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Region database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+// ---------------------------------------------------------------------------
+// Swappable repository
+// ---------------------------------------------------------------------------
+
+/// POI repository whose backing repository can be replaced while serving.
+/// A query runs to completion on the repository it started on.
+pub struct SwappablePoiRepository {
+    current: RwLock<Arc<dyn PoiRepository>>,
+}
+
+impl SwappablePoiRepository {
+    pub fn new(inner: Arc<dyn PoiRepository>) -> Self {
+        SwappablePoiRepository {
+            current: RwLock::new(inner),
+        }
+    }
+
+    pub fn current(&self) -> Arc<dyn PoiRepository> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Serve from `inner` from now on, returning the previous repository
+    pub fn swap(&self, inner: Arc<dyn PoiRepository>) -> Arc<dyn PoiRepository> {
+        std::mem::replace(&mut *self.current.write().unwrap(), inner)
+    }
+}
+
+#[async_trait]
+impl PoiRepository for SwappablePoiRepository {
+    async fn find_within_radius(
+        &self,
+        center: &Coordinates,
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        self.current()
+            .find_within_radius(center, radius_meters, categories, limit)
+            .await
+    }
+
+    async fn find_in_bbox(
+        &self,
+        min_lat: f64,
+        max_lat: f64,
+        min_lng: f64,
+        max_lng: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        self.current()
+            .find_in_bbox(min_lat, max_lat, min_lng, max_lng, categories, limit)
+            .await
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Poi>> {
+        self.current().find_by_ids(ids).await
+    }
+
+    async fn find_by_osm_ids(&self, osm_ids: &[i64]) -> Result<Vec<Poi>> {
+        self.current().find_by_osm_ids(osm_ids).await
+    }
+
+    async fn insert(&self, poi: &Poi) -> Result<Uuid> {
+        self.current().insert(poi).await
+    }
+
+    async fn count(&self) -> Result<i64> {
+        self.current().count().await
+    }
+
+    async fn road_profile(&self, path: &[Coordinates]) -> Result<Option<RoadProfile>> {
+        self.current().road_profile(path).await
+    }
+
+    async fn find_near_path(
+        &self,
+        path: &[Coordinates],
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Option<Vec<SnappedPoi>>> {
+        self.current()
+            .find_near_path(path, radius_meters, categories, limit)
+            .await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Catalog client
+// ---------------------------------------------------------------------------
+
+/// One region in the proxy's `GET /v1/regions` listing
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogRegion {
+    pub id: String,
+    /// RFC 3339, from the region's `build_date` meta
+    pub build_date: String,
+    /// Hex SHA-256 of the whole file
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Catalog {
+    regions: Vec<CatalogRegion>,
+}
+
+/// Client for a region proxy (`src/bin/proxy.rs`)
+pub struct RegionCatalogClient {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl RegionCatalogClient {
+    pub fn new(base_url: &str, api_key: Option<String>) -> Self {
+        // No overall timeout: downloads may take minutes, but a stalled
+        // body stream fails instead of hanging the sync
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(REGION_CATALOG_TIMEOUT_SECS))
+            .read_timeout(Duration::from_secs(REGION_DOWNLOAD_STALL_SECS))
+            .build()
+            .unwrap_or_default();
+        RegionCatalogClient {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        let request = self.client.get(format!("{}{}", self.base_url, path));
+        match self.api_key {
+            Some(ref key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    /// The catalog entry for region `id`, if listed
+    pub async fn region(
+        &self,
+        id: &str,
+    ) -> std::result::Result<Option<CatalogRegion>, RegionSyncError> {
+        let response = self
+            .get("/v1/regions")
+            .timeout(Duration::from_secs(REGION_CATALOG_TIMEOUT_SECS))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(RegionSyncError::Status(response.status()));
+        }
+        let catalog: Catalog = response.json().await?;
+        Ok(catalog.regions.into_iter().find(|region| region.id == id))
+    }
+
+    /// Download region `id` into `dest`, resuming from a partial file left
+    /// by an interrupted download. Returns the hex SHA-256 of the whole
+    /// file, hashed as it is written.
+    pub async fn download(
+        &self,
+        id: &str,
+        dest: &Path,
+    ) -> std::result::Result<String, RegionSyncError> {
+        let offset = tokio::fs::metadata(dest).await.map_or(0, |m| m.len());
+        let mut request = self.get(&format!("/v1/regions/{}/download", id));
+        if offset > 0 {
+            request = request.header(header::RANGE, format!("bytes={}-", offset));
+        }
+        let mut response = request.send().await?;
+        let append = match response.status() {
+            StatusCode::PARTIAL_CONTENT => true,
+            StatusCode::OK => false,
+            // Nothing past the end: the partial file is already complete
+            // (the checksum catches it if it is not)
+            StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => {
+                let mut hasher = Sha256::new();
+                hash_file(dest, &mut hasher).await?;
+                return Ok(hex::encode(hasher.finalize()));
+            }
+            status => return Err(RegionSyncError::Status(status)),
+        };
+
+        let mut hasher = Sha256::new();
+        if append {
+            hash_file(dest, &mut hasher).await?;
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(dest)
+            .await?;
+        while let Some(chunk) = response.chunk().await? {
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+        }
+        file.sync_all().await?;
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+/// Feed the contents of `path` to `hasher`
+async fn hash_file(path: &Path, hasher: &mut Sha256) -> std::io::Result<()> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Local builds
+// ---------------------------------------------------------------------------
+
+/// A region file on disk and the build date recorded in it
+#[derive(Debug, Clone, PartialEq)]
+struct LocalBuild {
+    path: PathBuf,
+    build_date: Option<String>,
+}
+
+/// Whether `candidate` was built after `current`. Unparseable dates never
+/// count as newer; a file without one is older than any dated build.
+fn is_newer(candidate: &str, current: Option<&str>) -> bool {
+    let parse = |date: &str| OffsetDateTime::parse(date, &Rfc3339).ok();
+    match (parse(candidate), current.and_then(parse)) {
+        (Some(candidate), Some(current)) => candidate > current,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// `<id>.<digits of the build date>.db`
+fn build_file_name(id: &str, build_date: &str) -> String {
+    let stamp: String = build_date.chars().filter(char::is_ascii_digit).collect();
+    format!("{}.{}.db", id, stamp)
+}
+
+/// Whether `file_name` is a downloaded build of region `id` (or, with
+/// `partial`, one still being downloaded)
+fn is_build_file(file_name: &str, id: &str, partial: bool) -> bool {
+    let name = if partial {
+        file_name.strip_suffix(PARTIAL_SUFFIX)
+    } else {
+        Some(file_name)
+    };
+    name.and_then(|n| n.strip_prefix(id))
+        .and_then(|n| n.strip_prefix('.'))
+        .and_then(|n| n.strip_suffix(".db"))
+        .is_some_and(|stamp| !stamp.is_empty() && stamp.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(PARTIAL_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Delete a region file with its WAL sidecars
+fn remove_region_files(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut name = path.as_os_str().to_owned();
+        name.push(suffix);
+        if let Err(e) = std::fs::remove_file(&name) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(path = ?name, error = %e, "Failed to remove old region file");
+            }
+        }
+    }
+}
+
+/// `build_date` from a region file's `region_meta`, without opening it for writes
+async fn read_build_date(path: &Path) -> std::result::Result<Option<String>, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", path.display()))?
+        .create_if_missing(false)
+        .read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?;
+    let build_date = sqlx::query_scalar("SELECT value FROM region_meta WHERE key = 'build_date'")
+        .fetch_optional(&pool)
+        .await;
+    pool.close().await;
+    build_date
+}
+
+/// Where region builds come from and land
+struct RegionSource {
+    catalog: RegionCatalogClient,
+    region_id: String,
+    /// `REGION_DB_PATH`; downloaded builds go in the same directory
+    base_path: PathBuf,
+    trusted_key: Option<VerifyingKey>,
+}
+
+impl RegionSource {
+    fn dir(&self) -> &Path {
+        match self.base_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        }
+    }
+
+    /// Files in the region directory, keeping those `matches` accepts
+    fn region_files(
+        &self,
+        matches: impl Fn(&str) -> bool,
+    ) -> std::result::Result<Vec<PathBuf>, RegionSyncError> {
+        let mut files = Vec::new();
+        let entries = match std::fs::read_dir(self.dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(&matches)
+            {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+
+    /// The newest of `REGION_DB_PATH` and the builds downloaded next to it
+    async fn newest_local(&self) -> std::result::Result<Option<LocalBuild>, RegionSyncError> {
+        let mut candidates =
+            self.region_files(|name| is_build_file(name, &self.region_id, false))?;
+        if self.base_path.exists() {
+            candidates.push(self.base_path.clone());
+        }
+
+        let mut newest: Option<LocalBuild> = None;
+        for path in candidates {
+            let build_date = match read_build_date(&path).await {
+                Ok(date) => date,
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "Skipping unreadable region file");
+                    continue;
+                }
+            };
+            let replaces = match (&newest, &build_date) {
+                (None, _) => true,
+                (Some(best), Some(date)) => is_newer(date, best.build_date.as_deref()),
+                (Some(_), None) => false,
+            };
+            if replaces {
+                newest = Some(LocalBuild { path, build_date });
+            }
+        }
+        Ok(newest)
+    }
+
+    /// The catalog's build of the region, when it is newer than `current`
+    async fn newer_build(
+        &self,
+        current: Option<&str>,
+    ) -> std::result::Result<Option<CatalogRegion>, RegionSyncError> {
+        let region = self
+            .catalog
+            .region(&self.region_id)
+            .await?
+            .ok_or_else(|| RegionSyncError::NotInCatalog(self.region_id.clone()))?;
+        Ok(is_newer(&region.build_date, current).then_some(region))
+    }
+
+    /// Download `region` and check it against the catalog's SHA-256 and
+    /// the trusted key, returning the verified file
+    async fn fetch(
+        &self,
+        region: &CatalogRegion,
+    ) -> std::result::Result<LocalBuild, RegionSyncError> {
+        let expected = region
+            .sha256
+            .as_deref()
+            .ok_or_else(|| RegionSyncError::MissingChecksum(self.region_id.clone()))?;
+        let path = self
+            .dir()
+            .join(build_file_name(&self.region_id, &region.build_date));
+        let partial = partial_path(&path);
+        // Only the build being fetched can be resumed
+        for stale in self.region_files(|name| is_build_file(name, &self.region_id, true))? {
+            if stale != partial {
+                remove_region_files(&stale);
+            }
+        }
+
+        tokio::fs::create_dir_all(self.dir()).await?;
+        tracing::info!(
+            region = %self.region_id,
+            build_date = %region.build_date,
+            path = %path.display(),
+            "Downloading region"
+        );
+        let actual = self.catalog.download(&self.region_id, &partial).await?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            remove_region_files(&partial);
+            return Err(RegionSyncError::ChecksumMismatch {
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+        if let Err(e) = self.verify_signature(&partial).await {
+            remove_region_files(&partial);
+            return Err(e);
+        }
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(LocalBuild {
+            path,
+            build_date: Some(region.build_date.clone()),
+        })
+    }
+
+    /// Check a downloaded file's signature against the trusted key, if any.
+    /// Only opens the file once its SHA-256 matched the catalog.
+    async fn verify_signature(&self, path: &Path) -> std::result::Result<(), RegionSyncError> {
+        let Some(ref key) = self.trusted_key else {
+            return Ok(());
+        };
+        let pool = open_region(path).await?;
+        let result = SqlitePoiRepository::new(pool.clone())
+            .verify_integrity(Some(key))
+            .await;
+        pool.close().await;
+        Ok(result?)
+    }
+
+    /// Delete downloaded builds other than `keep`. Only call once queries
+    /// on them have drained.
+    fn prune(&self, keep: &Path) -> std::result::Result<(), RegionSyncError> {
+        for path in self.region_files(|name| is_build_file(name, &self.region_id, false))? {
+            if path != keep {
+                tracing::info!(path = %path.display(), "Removing superseded region build");
+                remove_region_files(&path);
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn open_region(path: &Path) -> std::result::Result<SqlitePool, sqlx::Error> {
+    SqlitePoiRepository::open_read_pool(&path.to_string_lossy(), &SqliteReadPoolConfig::server())
+        .await
+}
+
+// ---------------------------------------------------------------------------
+// Sync
+// ---------------------------------------------------------------------------
+
+/// The served region and the catalog it is updated from
+pub struct RegionSync {
+    source: RegionSource,
+    repository: Arc<SwappablePoiRepository>,
+    active: Mutex<LocalBuild>,
+    /// Held for a whole sync, so overlapping syncs don't download into the
+    /// same partial file
+    syncing: Mutex<()>,
+}
+
+impl RegionSync {
+    /// Open the newest region available, downloading it first when the
+    /// catalog has a build newer than every local file. With a local file,
+    /// an unreachable catalog or failed download only logs a warning.
+    pub async fn start(
+        region_path: &str,
+        region_id: &str,
+        config: &RegionSyncConfig,
+    ) -> std::result::Result<Arc<Self>, RegionSyncError> {
+        let trusted_key = config
+            .trusted_key
+            .as_deref()
+            .map(parse_verifying_key)
+            .transpose()
+            .map_err(RegionSyncError::TrustedKey)?;
+        let source = RegionSource {
+            catalog: RegionCatalogClient::new(&config.catalog_url, config.api_key.clone()),
+            region_id: region_id.to_string(),
+            base_path: PathBuf::from(region_path),
+            trusted_key,
+        };
+
+        let local = source.newest_local().await?;
+        let current = local.as_ref().and_then(|l| l.build_date.as_deref());
+        let update = match source.newer_build(current).await {
+            Ok(Some(region)) => source.fetch(&region).await.map(Some),
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+        let build = match (update, local) {
+            (Ok(Some(build)), _) => build,
+            (Ok(None), Some(local)) => local,
+            (Ok(None), None) => return Err(RegionSyncError::NotInCatalog(region_id.to_string())),
+            (Err(e), Some(local)) => {
+                tracing::warn!(error = %e, "Region update failed, serving the local build");
+                local
+            }
+            (Err(e), None) => return Err(e),
+        };
+        source.prune(&build.path)?;
+
+        let pool = open_region(&build.path).await?;
+        tracing::info!(
+            path = %build.path.display(),
+            build_date = build.build_date.as_deref().unwrap_or("unknown"),
+            "Serving region database"
+        );
+        let repository = Arc::new(SwappablePoiRepository::new(Arc::new(
+            SqlitePoiRepository::new(pool),
+        )));
+        Ok(Arc::new(RegionSync {
+            source,
+            repository,
+            active: Mutex::new(build),
+            syncing: Mutex::new(()),
+        }))
+    }
+
+    /// The repository queries should go through; it follows every swap
+    pub fn repository(&self) -> Arc<dyn PoiRepository> {
+        self.repository.clone()
+    }
+
+    /// Build date of the region being served
+    pub async fn build_date(&self) -> Option<String> {
+        self.active.lock().await.build_date.clone()
+    }
+
+    /// Check the catalog once and swap in a newer build. Returns whether
+    /// the served region changed. The download runs without holding
+    /// `active`, which is only locked to read it and for the swap.
+    pub async fn sync_once(&self) -> std::result::Result<bool, RegionSyncError> {
+        let _syncing = self.syncing.lock().await;
+        let (path, build_date) = {
+            let active = self.active.lock().await;
+            (active.path.clone(), active.build_date.clone())
+        };
+        // Builds replaced by the previous sync have drained by now
+        self.source.prune(&path)?;
+
+        let Some(region) = self.source.newer_build(build_date.as_deref()).await? else {
+            return Ok(false);
+        };
+        let build = self.source.fetch(&region).await?;
+        let pool = open_region(&build.path).await?;
+
+        let mut active = self.active.lock().await;
+        self.repository
+            .swap(Arc::new(SqlitePoiRepository::new(pool)));
+        tracing::info!(
+            path = %build.path.display(),
+            build_date = %region.build_date,
+            previous = active.build_date.as_deref().unwrap_or("unknown"),
+            "Swapped in a newer region build"
+        );
+        *active = build;
+        Ok(true)
+    }
+
+    /// Check the catalog every `every`, starting one interval from now
+    pub fn spawn(self: Arc<Self>, every: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.sync_once().await {
+                    tracing::warn!(error = %e, "Region sync failed");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path as UrlPath, State};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_build_dates_compare_as_timestamps() {
+        assert!(is_newer(
+            "2026-10-17T12:00:00Z",
+            Some("2026-10-17T11:59:59.5Z")
+        ));
+        assert!(!is_newer(
+            "2026-10-17T12:00:00Z",
+            Some("2026-10-17T12:00:00Z")
+        ));
+        // Same instant, different offsets
+        assert!(!is_newer(
+            "2026-10-17T14:00:00+02:00",
+            Some("2026-10-17T12:00:00Z")
+        ));
+        assert!(is_newer("2026-10-17T12:00:00Z", None));
+        assert!(is_newer("2026-10-17T12:00:00Z", Some("unknown")));
+        assert!(!is_newer("unknown", None));
+    }
+
+    #[test]
+    fn test_build_file_names() {
+        let name = build_file_name("monaco", "2026-10-17T12:00:00.25Z");
+        assert_eq!(name, "monaco.2026101712000025.db");
+        assert!(is_build_file(&name, "monaco", false));
+        assert!(is_build_file(&format!("{}.download", name), "monaco", true));
+        assert!(!is_build_file(
+            &format!("{}.download", name),
+            "monaco",
+            false
+        ));
+        assert!(!is_build_file("monaco.db", "monaco", false));
+        assert!(!is_build_file("monaco-east.20261017.db", "monaco", false));
+        assert!(!is_build_file("monaco.latest.db", "monaco", false));
+    }
+
+    /// A checksummed region file, as `build_region` writes it. Returns its SHA-256.
+    async fn write_region(path: &Path, build_date: &str, pois: usize) -> String {
+        let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", path.display()))
+            .unwrap()
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .unwrap();
+        SqlitePoiRepository::create_schema(&pool).await.unwrap();
+        let repo = SqlitePoiRepository::new(pool.clone());
+        for i in 0..pois {
+            let coordinates = Coordinates::new(43.73 + i as f64 * 0.001, 7.42).unwrap();
+            repo.insert(&Poi::new(
+                format!("P{}", i),
+                PoiCategory::Park,
+                coordinates,
+                50.0,
+            ))
+            .await
+            .unwrap();
+        }
+        repo.set_meta("build_date", build_date).await.unwrap();
+        repo.write_checksum(None).await.unwrap();
+        pool.close().await;
+        let mut hasher = Sha256::new();
+        hash_file(path, &mut hasher).await.unwrap();
+        hex::encode(hasher.finalize())
+    }
+
+    /// Build date and SHA-256 the fake proxy advertises
+    type Listing = Arc<RwLock<(String, String)>>;
+
+    /// The proxy's catalog and download endpoints, serving one file
+    async fn serve_catalog(file: PathBuf, listing: Listing) -> String {
+        async fn regions(State((_, listing)): State<(PathBuf, Listing)>) -> Json<Value> {
+            let (build_date, sha256) = listing.read().unwrap().clone();
+            Json(json!({"regions": [{
+                "id": "monaco",
+                "name": "monaco",
+                "build_date": build_date,
+                "sha256": sha256,
+            }]}))
+        }
+        async fn download(
+            State((file, _)): State<(PathBuf, Listing)>,
+            UrlPath(id): UrlPath<String>,
+        ) -> Vec<u8> {
+            assert_eq!(id, "monaco");
+            std::fs::read(file).unwrap()
+        }
+
+        let app = Router::new()
+            .route("/v1/regions", get(regions))
+            .route("/v1/regions/{id}/download", get(download))
+            .with_state((file, listing));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_downloads_then_swaps_in_newer_builds() {
+        let root = std::env::temp_dir().join(format!("easyroute-region-sync-{}", Uuid::new_v4()));
+        let upstream = root.join("upstream.db");
+        std::fs::create_dir_all(&root).unwrap();
+        let sha256 = write_region(&upstream, "2026-10-01T00:00:00Z", 2).await;
+        let listing = Arc::new(RwLock::new(("2026-10-01T00:00:00Z".to_string(), sha256)));
+        let config = RegionSyncConfig {
+            catalog_url: serve_catalog(upstream.clone(), listing.clone()).await,
+            api_key: None,
+            interval_secs: 3600,
+            trusted_key: None,
+        };
+
+        // No local file: the build is downloaded before serving
+        let base = root.join("served").join("monaco.db");
+        let base_str = base.to_str().unwrap();
+        let sync = RegionSync::start(base_str, "monaco", &config)
+            .await
+            .unwrap();
+        let repo = sync.repository();
+        assert_eq!(repo.count().await.unwrap(), 2);
+        assert!(!sync.sync_once().await.unwrap());
+
+        // A newer build replaces it in place
+        std::fs::remove_file(&upstream).unwrap();
+        let sha256 = write_region(&upstream, "2026-10-08T00:00:00Z", 3).await;
+        *listing.write().unwrap() = ("2026-10-08T00:00:00Z".to_string(), sha256);
+        assert!(sync.sync_once().await.unwrap());
+        assert_eq!(repo.count().await.unwrap(), 3);
+        assert_eq!(
+            sync.build_date().await.as_deref(),
+            Some("2026-10-08T00:00:00Z")
+        );
+
+        // A restart keeps the newest build, and the next sync prunes the old one
+        let restarted = RegionSync::start(base_str, "monaco", &config)
+            .await
+            .unwrap();
+        assert_eq!(restarted.repository().count().await.unwrap(), 3);
+        let builds = restarted
+            .source
+            .region_files(|name| is_build_file(name, "monaco", false))
+            .unwrap();
+        assert_eq!(builds.len(), 1);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_downloads_not_matching_the_catalog() {
+        let root = std::env::temp_dir().join(format!("easyroute-region-sync-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let upstream = root.join("upstream.db");
+        let sha256 = write_region(&upstream, "2026-10-01T00:00:00Z", 2).await;
+        let base = root.join("monaco.db");
+        write_region(&base, "2026-09-01T00:00:00Z", 1).await;
+
+        let listing = Arc::new(RwLock::new((
+            "2026-10-01T00:00:00Z".to_string(),
+            sha256.clone(),
+        )));
+        let catalog_url = serve_catalog(upstream, listing).await;
+        let source = RegionSource {
+            catalog: RegionCatalogClient::new(&catalog_url, None),
+            region_id: "monaco".to_string(),
+            base_path: base.clone(),
+            trusted_key: None,
+        };
+        let mut region = CatalogRegion {
+            id: "monaco".to_string(),
+            build_date: "2026-10-01T00:00:00Z".to_string(),
+            sha256: Some("00".repeat(32)),
+        };
+        assert!(matches!(
+            source.fetch(&region).await,
+            Err(RegionSyncError::ChecksumMismatch { .. })
+        ));
+
+        // An intact download that is not signed by the trusted key
+        region.sha256 = Some(sha256);
+        let key = crate::db::sqlite_repo::parse_signing_key(&"01".repeat(32)).unwrap();
+        let source = RegionSource {
+            trusted_key: Some(key.verifying_key()),
+            ..source
+        };
+        assert!(matches!(
+            source.fetch(&region).await,
+            Err(RegionSyncError::Integrity(RegionIntegrityError::Unsigned))
+        ));
+        // Nothing half-verified is left behind, and the local file still serves
+        assert!(source
+            .region_files(|name| name != "monaco.db" && name != "upstream.db")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            source.newest_local().await.unwrap().map(|b| b.path),
+            Some(base)
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,469 @@
+//! Sampled request/response logging.
+//!
+//! A fraction of loop requests is written as NDJSON (one [`RequestLogRecord`]
+//! per line) to build a corpus for offline replay and new evaluation
+//! scenarios. Records are scrubbed before they leave the handler: coordinates
+//! are rounded and route paths are left out. They go to a local file
+//! ([`FileSink`]) or, batched into NDJSON objects, to the artifact store
+//! ([`ObjectStoreSink`]).
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::constants::{
+    REQUEST_LOG_CHANNEL_CAPACITY, REQUEST_LOG_COORD_DECIMALS, REQUEST_LOG_FLUSH_INTERVAL_SECS,
+    REQUEST_LOG_OBJECT_MAX_LINES,
+};
+use crate::models::route::{LoopRouteRequest, MustInclude};
+use crate::models::{Coordinates, QualityTier, Route};
+use crate::services::route_generator::route_metrics::RouteMetrics;
+use crate::storage::ObjectStore;
+
+/// Destination for serialized records (one JSON document per line)
+#[async_trait]
+pub trait RequestLogSink: Send + Sync {
+    async fn write_lines(&self, lines: &[String]) -> std::io::Result<()>;
+
+    /// Write out anything buffered. Called every
+    /// `REQUEST_LOG_FLUSH_INTERVAL_SECS` and when the logger shuts down.
+    async fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Appends records to a local NDJSON file
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileSink { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl RequestLogSink for FileSink {
+    async fn write_lines(&self, lines: &[String]) -> std::io::Result<()> {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        let mut buf = lines.join("\n");
+        buf.push('\n');
+        file.write_all(buf.as_bytes()).await?;
+        file.flush().await
+    }
+}
+
+/// Buffers records and uploads them to an [`ObjectStore`] as NDJSON objects
+/// of up to `REQUEST_LOG_OBJECT_MAX_LINES` lines, keyed
+/// `<prefix>/<date>/<unix ms>-<id>.ndjson`. A batch whose upload fails is
+/// dropped.
+pub struct ObjectStoreSink {
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    buffer: Mutex<Vec<String>>,
+}
+
+impl ObjectStoreSink {
+    pub fn new(store: Arc<dyn ObjectStore>, prefix: impl Into<String>) -> Self {
+        ObjectStoreSink {
+            store,
+            prefix: prefix.into(),
+            buffer: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn object_key(&self) -> String {
+        let now = OffsetDateTime::now_utc();
+        format!(
+            "{}/{}/{}-{}.ndjson",
+            self.prefix,
+            now.date(),
+            now.unix_timestamp_nanos() / 1_000_000,
+            Uuid::new_v4().simple()
+        )
+    }
+
+    async fn upload(&self, lines: Vec<String>) -> std::io::Result<()> {
+        let mut body = lines.join("\n");
+        body.push('\n');
+        self.store
+            .put(
+                &self.object_key(),
+                body.into_bytes(),
+                "application/x-ndjson",
+            )
+            .await
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+    }
+
+    /// Take the buffered lines if at least `min` are waiting
+    fn take(&self, min: usize) -> Option<Vec<String>> {
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        (!buffer.is_empty() && buffer.len() >= min).then(|| std::mem::take(&mut *buffer))
+    }
+}
+
+#[async_trait]
+impl RequestLogSink for ObjectStoreSink {
+    async fn write_lines(&self, lines: &[String]) -> std::io::Result<()> {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(lines);
+        match self.take(REQUEST_LOG_OBJECT_MAX_LINES) {
+            Some(batch) => self.upload(batch).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn flush(&self) -> std::io::Result<()> {
+        match self.take(1) {
+            Some(batch) => self.upload(batch).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// One logged request with a summary of what was returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestLogRecord {
+    pub id: Uuid,
+    pub timestamp: String,
+    pub endpoint: String,
+    pub request: LoopRouteRequest,
+    pub cache_hit: bool,
+    pub latency_ms: u64,
+    /// Error message when the request failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub routes: Vec<LoggedRoute>,
+}
+
+/// Route summary without the path geometry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedRoute {
+    pub distance_km: f64,
+    pub estimated_duration_minutes: u32,
+    pub score: f32,
+    pub poi_count: usize,
+    pub snapped_poi_count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_tier: Option<QualityTier>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<RouteMetrics>,
+}
+
+impl From<&Route> for LoggedRoute {
+    fn from(route: &Route) -> Self {
+        LoggedRoute {
+            distance_km: route.distance_km,
+            estimated_duration_minutes: route.estimated_duration_minutes,
+            score: route.score,
+            poi_count: route.pois.len(),
+            snapped_poi_count: route.snapped_pois.len(),
+            quality_tier: route.quality_tier,
+            metrics: route.metrics.clone(),
+        }
+    }
+}
+
+impl RequestLogRecord {
+    /// Build a scrubbed record: the start point and pinned points are
+    /// rounded to `REQUEST_LOG_COORD_DECIMALS` and only route summaries are
+    /// kept.
+    pub fn new(
+        endpoint: &str,
+        request: &LoopRouteRequest,
+        outcome: std::result::Result<&[Route], String>,
+        cache_hit: bool,
+        latency_ms: u64,
+    ) -> Self {
+        let mut request = request.clone();
+        request.start_point = round_coordinates(&request.start_point);
+        for pin in &mut request.preferences.must_include {
+            if let MustInclude::Point(point) = pin {
+                *point = round_coordinates(point);
+            }
+        }
+        let (routes, error) = match outcome {
+            Ok(routes) => (routes.iter().map(LoggedRoute::from).collect(), None),
+            Err(e) => (vec![], Some(e)),
+        };
+
+        RequestLogRecord {
+            id: Uuid::new_v4(),
+            timestamp: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_else(|_| "unknown".to_string()),
+            endpoint: endpoint.to_string(),
+            request,
+            cache_hit,
+            latency_ms,
+            error,
+            routes,
+        }
+    }
+}
+
+fn round_coordinates(point: &Coordinates) -> Coordinates {
+    let factor = 10f64.powi(REQUEST_LOG_COORD_DECIMALS);
+    Coordinates {
+        lat: (point.lat * factor).round() / factor,
+        lng: (point.lng * factor).round() / factor,
+    }
+}
+
+/// Samples records and hands them to a background writer so logging never
+/// delays a response. Records are dropped (with a warning) if the writer
+/// falls behind.
+pub struct RequestLogger {
+    sample_rate: f64,
+    tx: mpsc::Sender<String>,
+}
+
+impl RequestLogger {
+    /// Start the background writer. Must be called within a Tokio runtime.
+    pub fn new(sink: Arc<dyn RequestLogSink>, sample_rate: f64) -> Self {
+        let (tx, mut rx) = mpsc::channel::<String>(REQUEST_LOG_CHANNEL_CAPACITY);
+        tokio::spawn(async move {
+            let mut batch = Vec::new();
+            let mut flush_interval =
+                tokio::time::interval(Duration::from_secs(REQUEST_LOG_FLUSH_INTERVAL_SECS));
+            flush_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    received = rx.recv_many(&mut batch, REQUEST_LOG_CHANNEL_CAPACITY) => {
+                        if received == 0 {
+                            break;
+                        }
+                        if let Err(e) = sink.write_lines(&batch).await {
+                            tracing::warn!(error = %e, count = batch.len(), "Failed to write request log: {}", e);
+                        }
+                        batch.clear();
+                    }
+                    _ = flush_interval.tick() => {
+                        if let Err(e) = sink.flush().await {
+                            tracing::warn!(error = %e, "Failed to flush request log: {}", e);
+                        }
+                    }
+                }
+            }
+            if let Err(e) = sink.flush().await {
+                tracing::warn!(error = %e, "Failed to flush request log: {}", e);
+            }
+        });
+        RequestLogger { sample_rate, tx }
+    }
+
+    /// Roll for whether the current request gets logged
+    pub fn should_sample(&self) -> bool {
+        rand::random::<f64>() < self.sample_rate
+    }
+
+    pub fn log(&self, record: &RequestLogRecord) {
+        let line = match serde_json::to_string(record) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to serialize request log record: {}", e);
+                return;
+            }
+        };
+        if self.tx.try_send(line).is_err() {
+            tracing::warn!("Request log writer is behind, dropping record");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use crate::models::TransportMode;
+    use std::collections::BTreeMap;
+
+    #[derive(Default)]
+    struct MemorySink {
+        lines: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl RequestLogSink for MemorySink {
+        async fn write_lines(&self, lines: &[String]) -> std::io::Result<()> {
+            self.lines.lock().unwrap().extend_from_slice(lines);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MemoryStore {
+        objects: Mutex<BTreeMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl ObjectStore for MemoryStore {
+        async fn put(&self, key: &str, data: Vec<u8>, _content_type: &str) -> Result<()> {
+            crate::storage::validate_key(key)?;
+            self.objects.lock().unwrap().insert(key.to_string(), data);
+            Ok(())
+        }
+        async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            Ok(self.objects.lock().unwrap().get(key).cloned())
+        }
+        async fn delete(&self, key: &str) -> Result<()> {
+            self.objects.lock().unwrap().remove(key);
+            Ok(())
+        }
+        fn signed_url(&self, key: &str, _expires_in: Duration) -> Result<String> {
+            Ok(format!("memory://{}", key))
+        }
+        fn backend_name(&self) -> &'static str {
+            "memory"
+        }
+    }
+
+    fn request() -> LoopRouteRequest {
+        LoopRouteRequest {
+            start_point: Coordinates::new(48.856_614, 2.352_222).unwrap(),
+            distance_km: Some(5.0),
+            distance_tolerance: 0.5,
+            mode: TransportMode::Walk,
+            preferences: Default::default(),
+            depart_at: None,
+            arrive_by: None,
+        }
+    }
+
+    #[test]
+    fn test_record_rounds_start_point() {
+        let record = RequestLogRecord::new("/routes/loop", &request(), Ok(&[]), false, 120);
+        assert_eq!(record.request.start_point.lat, 48.857);
+        assert_eq!(record.request.start_point.lng, 2.352);
+        assert_eq!(record.error, None);
+    }
+
+    #[test]
+    fn test_record_rounds_pinned_points() {
+        let mut request = request();
+        let poi = Uuid::new_v4();
+        request.preferences.must_include = vec![
+            MustInclude::Point(Coordinates::new(48.860_611, 2.337_644).unwrap()),
+            MustInclude::Poi(poi),
+        ];
+        let record = RequestLogRecord::new("/routes/loop", &request, Ok(&[]), false, 120);
+        match record.request.preferences.must_include[..] {
+            [MustInclude::Point(point), MustInclude::Poi(id)] => {
+                assert_eq!((point.lat, point.lng), (48.861, 2.338));
+                assert_eq!(id, poi);
+            }
+            ref other => panic!("unexpected pins: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_record_round_trips_as_json_line() {
+        let record = RequestLogRecord::new(
+            "/routes/loop",
+            &request(),
+            Err("no POIs".to_string()),
+            false,
+            40,
+        );
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(!line.contains('\n'));
+        let parsed: RequestLogRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.error.as_deref(), Some("no POIs"));
+        assert_eq!(parsed.request.distance_km, Some(5.0));
+    }
+
+    #[tokio::test]
+    async fn test_logger_writes_records_to_sink() {
+        let sink = Arc::new(MemorySink::default());
+        let logger = RequestLogger::new(sink.clone(), 1.0);
+        assert!(logger.should_sample());
+
+        let record = RequestLogRecord::new("/routes/loop", &request(), Ok(&[]), true, 3);
+        logger.log(&record);
+        for _ in 0..50 {
+            if !sink.lines.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let lines = sink.lines.lock().unwrap();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains(&record.id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_object_store_sink_batches_lines() {
+        let store = Arc::new(MemoryStore::default());
+        let sink = ObjectStoreSink::new(store.clone(), "logs/requests");
+        let lines: Vec<String> = (0..REQUEST_LOG_OBJECT_MAX_LINES + 2)
+            .map(|i| format!("{{\"n\":{}}}", i))
+            .collect();
+
+        sink.write_lines(&lines[..2]).await.unwrap();
+        assert!(store.objects.lock().unwrap().is_empty());
+        sink.write_lines(&lines[2..REQUEST_LOG_OBJECT_MAX_LINES])
+            .await
+            .unwrap();
+        sink.write_lines(&lines[REQUEST_LOG_OBJECT_MAX_LINES..])
+            .await
+            .unwrap();
+        assert_eq!(store.objects.lock().unwrap().len(), 1);
+        sink.flush().await.unwrap();
+        sink.flush().await.unwrap();
+
+        let objects = store.objects.lock().unwrap();
+        assert_eq!(objects.len(), 2);
+        let mut written = Vec::new();
+        for (key, data) in objects.iter() {
+            assert!(key.starts_with("logs/requests/"), "{}", key);
+            assert!(key.ends_with(".ndjson"), "{}", key);
+            let body = String::from_utf8(data.clone()).unwrap();
+            assert!(body.ends_with('\n'));
+            written.extend(body.lines().map(str::to_string));
+        }
+        written.sort();
+        let mut expected = lines;
+        expected.sort();
+        assert_eq!(written, expected);
+    }
+
+    #[tokio::test]
+    async fn test_logger_flushes_object_store_sink_on_shutdown() {
+        let store = Arc::new(MemoryStore::default());
+        let logger = RequestLogger::new(
+            Arc::new(ObjectStoreSink::new(store.clone(), "requests")),
+            1.0,
+        );
+        let record = RequestLogRecord::new("/routes/loop", &request(), Ok(&[]), false, 9);
+        logger.log(&record);
+        drop(logger);
+        for _ in 0..50 {
+            if !store.objects.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let objects = store.objects.lock().unwrap();
+        assert_eq!(objects.len(), 1);
+        let body = String::from_utf8(objects.values().next().unwrap().clone()).unwrap();
+        let parsed: RequestLogRecord = serde_json::from_str(body.trim_end()).unwrap();
+        assert_eq!(parsed.id, record.id);
+    }
+}
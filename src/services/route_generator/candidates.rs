@@ -0,0 +1,102 @@
+use super::geometry::angle_from_start;
+use crate::models::{Coordinates, Poi, RoutePreferences};
+
+/// Candidate POIs with the start-relative values that scoring needs, stored
+/// as flat vectors indexed like `pois`.
+///
+/// None of these depend on the tolerance level, attempt, retry or
+/// already-selected POIs, so the index is built once per request after
+/// discovery and shared by every selection pass. Scoring refers to candidates
+/// by index instead of cloning `Poi`s.
+pub struct CandidateIndex<'a> {
+    pub start: Coordinates,
+    pub pois: &'a [Poi],
+    /// Haversine distance from `start` (km)
+    pub distances_km: Vec<f64>,
+    /// Bearing-like angle from `start` (radians, -PI..PI), see [`angle_from_start`]
+    pub angles: Vec<f64>,
+    /// `Poi::quality_score` under the request's `hidden_gems` preference, 0-1
+    pub quality: Vec<f32>,
+    /// The first `pinned` POIs are the request's `must_include` places,
+    /// selected as waypoints on every attempt
+    pub pinned: usize,
+}
+
+impl<'a> CandidateIndex<'a> {
+    pub fn new(start: &Coordinates, pois: &'a [Poi], preferences: &RoutePreferences) -> Self {
+        let mut distances_km = Vec::with_capacity(pois.len());
+        let mut angles = Vec::with_capacity(pois.len());
+        let mut quality = Vec::with_capacity(pois.len());
+        for poi in pois {
+            distances_km.push(start.distance_to(&poi.coordinates));
+            angles.push(angle_from_start(start, &poi.coordinates));
+            quality.push(poi.quality_score(preferences.hidden_gems) / 100.0);
+        }
+
+        CandidateIndex {
+            start: *start,
+            pois,
+            distances_km,
+            angles,
+            quality,
+            pinned: 0,
+        }
+    }
+
+    /// Treat the first `count` POIs as pinned
+    pub fn with_pinned(mut self, count: usize) -> Self {
+        self.pinned = count.min(self.pois.len());
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.pois.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pois.is_empty()
+    }
+
+    pub fn coordinates(&self, index: usize) -> &Coordinates {
+        &self.pois[index].coordinates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PoiCategory;
+
+    #[test]
+    fn test_precomputed_values_match_per_poi_computation() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let pois = vec![
+            Poi::new(
+                "North".to_string(),
+                PoiCategory::Park,
+                Coordinates::new(48.8666, 2.3522).unwrap(),
+                80.0,
+            ),
+            Poi::new(
+                "East".to_string(),
+                PoiCategory::Museum,
+                Coordinates::new(48.8566, 2.3722).unwrap(),
+                30.0,
+            ),
+        ];
+        let preferences = RoutePreferences {
+            hidden_gems: true,
+            ..Default::default()
+        };
+
+        let index = CandidateIndex::new(&start, &pois, &preferences);
+        assert_eq!(index.len(), 2);
+        for (i, poi) in pois.iter().enumerate() {
+            assert_eq!(index.distances_km[i], start.distance_to(&poi.coordinates));
+            assert_eq!(index.angles[i], angle_from_start(&start, &poi.coordinates));
+        }
+        // Hidden gems invert popularity
+        assert!((index.quality[0] - 0.2).abs() < 1e-6);
+        assert!((index.quality[1] - 0.7).abs() < 1e-6);
+    }
+}
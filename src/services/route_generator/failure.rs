@@ -0,0 +1,210 @@
+//! Typed reasons for failed generation attempts: tallied per request so the
+//! API can say why no route came back, and counted across requests.
+
+use crate::error::{AppError, GenerationFailure};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Failed attempts of one request, by reason
+#[derive(Debug, Default)]
+pub struct FailureTally {
+    no_pois_in_area: u64,
+    out_of_tolerance: u64,
+    backend_unavailable: u64,
+    shape_rejected: u64,
+    unroutable: u64,
+    /// Achieved distance closest to the target among out-of-tolerance attempts
+    best_km: Option<f64>,
+    /// The last typed failure was a backend error
+    ended_by_outage: bool,
+}
+
+impl FailureTally {
+    pub fn record(&mut self, error: &AppError, target_distance_km: f64) {
+        let failure = Self::classify(error);
+        if failure.is_some() {
+            self.ended_by_outage = failure == Some(GenerationFailure::BackendUnavailable);
+        }
+        match failure {
+            Some(GenerationFailure::NoPoisInArea) => self.no_pois_in_area += 1,
+            Some(GenerationFailure::AllAttemptsOutOfTolerance { best_km }) => {
+                self.out_of_tolerance += 1;
+                self.best_km = Some(closest_km(self.best_km, best_km, target_distance_km));
+            }
+            Some(GenerationFailure::BackendUnavailable) => self.backend_unavailable += 1,
+            Some(GenerationFailure::ShapeRejected) => self.shape_rejected += 1,
+            Some(GenerationFailure::Unroutable) => self.unroutable += 1,
+            None => {}
+        }
+    }
+
+    fn classify(error: &AppError) -> Option<GenerationFailure> {
+        match error {
+            AppError::GenerationFailed(failure) => Some(*failure),
+            AppError::MapboxApi(_)
+            | AppError::RoutingBackend { .. }
+            | AppError::ServiceUnavailable(_) => Some(GenerationFailure::BackendUnavailable),
+            _ => None,
+        }
+    }
+
+    /// The most actionable reason seen: a backend error that ended the run,
+    /// then a near miss on distance, a shape rejection, waypoints the
+    /// backend can't route between, and too few POIs;
+    /// earlier backend errors only explain the failure when nothing else
+    /// went wrong
+    pub fn explain(&self) -> Option<GenerationFailure> {
+        if self.ended_by_outage {
+            Some(GenerationFailure::BackendUnavailable)
+        } else if let Some(best_km) = self.best_km {
+            Some(GenerationFailure::AllAttemptsOutOfTolerance { best_km })
+        } else if self.shape_rejected > 0 {
+            Some(GenerationFailure::ShapeRejected)
+        } else if self.unroutable > 0 {
+            Some(GenerationFailure::Unroutable)
+        } else if self.no_pois_in_area > 0 {
+            Some(GenerationFailure::NoPoisInArea)
+        } else if self.backend_unavailable > 0 {
+            Some(GenerationFailure::BackendUnavailable)
+        } else {
+            None
+        }
+    }
+
+    /// Replace `error` with the typed reason, when there is one
+    pub fn into_error(self, error: AppError) -> AppError {
+        match self.explain() {
+            Some(failure) => AppError::GenerationFailed(failure),
+            None => error,
+        }
+    }
+}
+
+/// Whichever of `best` and `km` is closer to the target
+pub fn closest_km(best: Option<f64>, km: f64, target_distance_km: f64) -> f64 {
+    match best {
+        Some(best) if (best - target_distance_km).abs() <= (km - target_distance_km).abs() => best,
+        _ => km,
+    }
+}
+
+/// Failed attempts across all requests, by reason
+#[derive(Debug, Default)]
+pub struct FailureCounts {
+    no_pois_in_area: AtomicU64,
+    out_of_tolerance: AtomicU64,
+    backend_unavailable: AtomicU64,
+    shape_rejected: AtomicU64,
+    unroutable: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FailureStats {
+    pub no_pois_in_area: u64,
+    pub all_attempts_out_of_tolerance: u64,
+    pub backend_unavailable: u64,
+    pub shape_rejected: u64,
+    pub unroutable: u64,
+}
+
+impl FailureCounts {
+    pub fn add(&self, tally: &FailureTally) {
+        self.no_pois_in_area
+            .fetch_add(tally.no_pois_in_area, Ordering::Relaxed);
+        self.out_of_tolerance
+            .fetch_add(tally.out_of_tolerance, Ordering::Relaxed);
+        self.backend_unavailable
+            .fetch_add(tally.backend_unavailable, Ordering::Relaxed);
+        self.shape_rejected
+            .fetch_add(tally.shape_rejected, Ordering::Relaxed);
+        self.unroutable
+            .fetch_add(tally.unroutable, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> FailureStats {
+        FailureStats {
+            no_pois_in_area: self.no_pois_in_area.load(Ordering::Relaxed),
+            all_attempts_out_of_tolerance: self.out_of_tolerance.load(Ordering::Relaxed),
+            backend_unavailable: self.backend_unavailable.load(Ordering::Relaxed),
+            shape_rejected: self.shape_rejected.load(Ordering::Relaxed),
+            unroutable: self.unroutable.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn out_of_tolerance(best_km: f64) -> AppError {
+        AppError::GenerationFailed(GenerationFailure::AllAttemptsOutOfTolerance { best_km })
+    }
+
+    #[test]
+    fn test_tally_keeps_closest_distance() {
+        let mut tally = FailureTally::default();
+        tally.record(&out_of_tolerance(8.0), 5.0);
+        tally.record(&out_of_tolerance(4.0), 5.0);
+        tally.record(
+            &AppError::GenerationFailed(GenerationFailure::ShapeRejected),
+            5.0,
+        );
+        assert_eq!(
+            tally.explain(),
+            Some(GenerationFailure::AllAttemptsOutOfTolerance { best_km: 4.0 })
+        );
+    }
+
+    #[test]
+    fn test_backend_errors_explain_only_when_alone() {
+        let mut tally = FailureTally::default();
+        tally.record(&AppError::MapboxApi("timeout".into()), 5.0);
+        assert_eq!(tally.explain(), Some(GenerationFailure::BackendUnavailable));
+        tally.record(
+            &AppError::GenerationFailed(GenerationFailure::NoPoisInArea),
+            5.0,
+        );
+        assert_eq!(tally.explain(), Some(GenerationFailure::NoPoisInArea));
+    }
+
+    #[test]
+    fn test_outage_that_ends_the_run_wins() {
+        let mut tally = FailureTally::default();
+        for _ in 0..3 {
+            tally.record(
+                &AppError::GenerationFailed(GenerationFailure::ShapeRejected),
+                5.0,
+            );
+        }
+        tally.record(&AppError::ServiceUnavailable("mapbox".into()), 5.0);
+        assert_eq!(tally.explain(), Some(GenerationFailure::BackendUnavailable));
+        assert!(matches!(
+            tally.into_error(AppError::ServiceUnavailable("mapbox".into())),
+            AppError::GenerationFailed(GenerationFailure::BackendUnavailable)
+        ));
+    }
+
+    #[test]
+    fn test_untyped_errors_pass_through() {
+        let mut tally = FailureTally::default();
+        tally.record(&AppError::Internal("boom".into()), 5.0);
+        assert!(matches!(
+            tally.into_error(AppError::Internal("boom".into())),
+            AppError::Internal(_)
+        ));
+    }
+
+    #[test]
+    fn test_counts_accumulate_tallies() {
+        let counts = FailureCounts::default();
+        let mut tally = FailureTally::default();
+        tally.record(&out_of_tolerance(8.0), 5.0);
+        tally.record(&AppError::MapboxApi("down".into()), 5.0);
+        counts.add(&tally);
+        counts.add(&tally);
+        let stats = counts.stats();
+        assert_eq!(stats.all_attempts_out_of_tolerance, 2);
+        assert_eq!(stats.backend_unavailable, 2);
+        assert_eq!(stats.shape_rejected, 0);
+    }
+}
@@ -1,6 +1,10 @@
+use super::variation;
 use crate::error::Result;
-use crate::models::{Coordinates, Route, TransportMode};
-use crate::services::mapbox::MapboxClient;
+use crate::models::{Coordinates, CostingOptions, Route, SurfacePreference, TransportMode};
+use crate::services::directions::DirectionsProvider;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use std::sync::Arc;
 
 /// Number of waypoints for geometric loop (reduced from 6 to prevent over-constraining Mapbox)
 const GEOMETRIC_LOOP_NUM_WAYPOINTS: usize = 4;
@@ -13,12 +17,12 @@ const ROTATION_JITTER_RAD: f64 = 0.35;
 
 /// Handles generation of geometric loop routes when POIs are unavailable
 pub struct GeometricLoopGenerator {
-    mapbox_client: MapboxClient,
+    directions: Arc<dyn DirectionsProvider>,
 }
 
 impl GeometricLoopGenerator {
-    pub fn new(mapbox_client: MapboxClient) -> Self {
-        Self { mapbox_client }
+    pub fn new(directions: Arc<dyn DirectionsProvider>) -> Self {
+        Self { directions }
     }
 
     /// Generate a geometric loop when POIs are unavailable
@@ -28,6 +32,9 @@ impl GeometricLoopGenerator {
         start: Coordinates,
         target_distance_km: f64,
         mode: &TransportMode,
+        costing: &CostingOptions,
+        surface: SurfacePreference,
+        seed: Option<u64>,
     ) -> Result<Route> {
         tracing::info!(
             "Generating geometric loop (no POIs) for {}km route",
@@ -36,53 +43,18 @@ impl GeometricLoopGenerator {
 
         // Calculate base radius: circumference = 2*pi*r, so r = target / (2*pi)
         let base_radius_km = target_distance_km / std::f64::consts::TAU;
-        let base_radius_deg = base_radius_km / 111.0;
 
-        // Use a deterministic seed based on start coordinates and target distance
-        // for reproducible but varied results
-        let seed = ((start.lat * 1000.0).abs() as u64)
+        // The stream depends on start coordinates and target distance, so
+        // results are reproducible but vary between requests
+        let stream = ((start.lat * 1000.0).abs() as u64)
             .wrapping_mul(31)
             .wrapping_add((start.lng * 1000.0).abs() as u64)
             .wrapping_mul(37)
             .wrapping_add((target_distance_km * 100.0) as u64);
-
-        // Rotation jitter: slight offset to circle orientation
-        let rotation_offset =
-            pseudo_random_f64(seed, 0) * ROTATION_JITTER_RAD * 2.0 - ROTATION_JITTER_RAD;
+        let mut rng = variation::seeded_rng(seed, stream);
 
         let mut waypoints = vec![start];
-
-        for i in 0..GEOMETRIC_LOOP_NUM_WAYPOINTS {
-            let base_angle =
-                (i as f64 / GEOMETRIC_LOOP_NUM_WAYPOINTS as f64) * std::f64::consts::TAU;
-            let angle = base_angle + rotation_offset;
-
-            // Per-waypoint radius jitter: ±15% of base radius
-            let jitter =
-                pseudo_random_f64(seed, i + 1) * RADIUS_JITTER_RANGE * 2.0 - RADIUS_JITTER_RANGE;
-            let radius_deg = base_radius_deg * (1.0 + jitter);
-
-            let lat_offset = radius_deg * angle.cos();
-            let lng_offset = radius_deg * angle.sin() / start.lat.to_radians().cos();
-
-            let waypoint_lat = start.lat + lat_offset;
-            let waypoint_lng = start.lng + lng_offset;
-
-            match Coordinates::new(waypoint_lat, waypoint_lng) {
-                Ok(waypoint) => waypoints.push(waypoint),
-                Err(_) => {
-                    tracing::warn!(
-                        index = i,
-                        lat = waypoint_lat,
-                        lng = waypoint_lng,
-                        "Geometric loop: invalid waypoint {} coordinates ({}, {}), skipping",
-                        i,
-                        waypoint_lat,
-                        waypoint_lng
-                    );
-                }
-            }
-        }
+        waypoints.extend(circle_waypoints(&start, base_radius_km, &mut rng));
 
         waypoints.push(start); // Return to start
 
@@ -92,8 +64,11 @@ impl GeometricLoopGenerator {
             base_radius_km
         );
 
-        // Get directions from Mapbox to snap to actual roads
-        let directions = self.mapbox_client.get_directions(&waypoints, mode).await?;
+        // Get directions to snap to actual roads
+        let directions = self
+            .directions
+            .directions(&waypoints, mode, costing, surface)
+            .await?;
 
         tracing::info!(
             "Geometric loop generated: {:.2}km (target: {}km)",
@@ -111,51 +86,80 @@ impl GeometricLoopGenerator {
     }
 }
 
-/// Simple deterministic pseudo-random number generator
-/// Returns a value in [0.0, 1.0)
-fn pseudo_random_f64(seed: u64, index: usize) -> f64 {
-    let mut x = seed
-        .wrapping_add(index as u64)
-        .wrapping_mul(6364136223846793005);
-    x = x.wrapping_add(1442695040888963407);
-    x ^= x >> 33;
-    x = x.wrapping_mul(0xff51afd7ed558ccd);
-    x ^= x >> 33;
-    (x as f64) / (u64::MAX as f64)
+/// Waypoints evenly spaced on a circle around `start`, with random rotation
+/// and per-waypoint radius jitter
+fn circle_waypoints(
+    start: &Coordinates,
+    base_radius_km: f64,
+    rng: &mut SmallRng,
+) -> Vec<Coordinates> {
+    // Rotation jitter: slight offset to circle orientation
+    let rotation_offset = rng.gen_range(-ROTATION_JITTER_RAD..ROTATION_JITTER_RAD);
+
+    let mut waypoints = Vec::with_capacity(GEOMETRIC_LOOP_NUM_WAYPOINTS);
+    for i in 0..GEOMETRIC_LOOP_NUM_WAYPOINTS {
+        let base_angle = (i as f64 / GEOMETRIC_LOOP_NUM_WAYPOINTS as f64) * std::f64::consts::TAU;
+        let angle = base_angle + rotation_offset;
+
+        // Per-waypoint radius jitter: ±15% of base radius
+        let jitter = rng.gen_range(-RADIUS_JITTER_RANGE..RADIUS_JITTER_RANGE);
+        let radius_m = base_radius_km * 1000.0 * (1.0 + jitter);
+
+        // Wraps across the antimeridian and stays finite near the poles
+        let offset = start.offset_by(radius_m * angle.cos(), radius_m * angle.sin());
+        let (waypoint_lat, waypoint_lng) = (offset.lat, offset.lng);
+
+        match Coordinates::new(waypoint_lat, waypoint_lng) {
+            Ok(waypoint) => waypoints.push(waypoint),
+            Err(_) => {
+                tracing::warn!(
+                    index = i,
+                    lat = waypoint_lat,
+                    lng = waypoint_lng,
+                    "Geometric loop: invalid waypoint {} coordinates ({}, {}), skipping",
+                    i,
+                    waypoint_lat,
+                    waypoint_lng
+                );
+            }
+        }
+    }
+    waypoints
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn pseudo_random_in_range() {
-        for seed in [0, 1, 42, 999, u64::MAX] {
-            for idx in 0..20 {
-                let v = pseudo_random_f64(seed, idx);
-                assert!((0.0..1.0).contains(&v), "seed={seed}, idx={idx}, v={v}");
-            }
-        }
+    fn start() -> Coordinates {
+        Coordinates::new(48.8566, 2.3522).unwrap()
     }
 
     #[test]
-    fn pseudo_random_deterministic() {
-        let a = pseudo_random_f64(42, 7);
-        let b = pseudo_random_f64(42, 7);
-        assert_eq!(a, b);
+    fn circle_waypoints_stay_within_jitter() {
+        let mut rng = variation::seeded_rng(Some(42), 0);
+        let waypoints = circle_waypoints(&start(), 2.0, &mut rng);
+        assert_eq!(waypoints.len(), GEOMETRIC_LOOP_NUM_WAYPOINTS);
+        for waypoint in &waypoints {
+            let radius_km = start().distance_to(waypoint);
+            assert!(
+                (radius_km - 2.0).abs() <= 2.0 * RADIUS_JITTER_RANGE + 0.01,
+                "radius={radius_km}"
+            );
+        }
     }
 
     #[test]
-    fn pseudo_random_varies_with_index() {
-        let a = pseudo_random_f64(42, 0);
-        let b = pseudo_random_f64(42, 1);
-        assert_ne!(a, b);
+    fn circle_waypoints_deterministic() {
+        let a = circle_waypoints(&start(), 2.0, &mut variation::seeded_rng(Some(42), 7));
+        let b = circle_waypoints(&start(), 2.0, &mut variation::seeded_rng(Some(42), 7));
+        assert_eq!(a, b);
     }
 
     #[test]
-    fn pseudo_random_varies_with_seed() {
-        let a = pseudo_random_f64(1, 0);
-        let b = pseudo_random_f64(2, 0);
+    fn circle_waypoints_vary_with_seed() {
+        let a = circle_waypoints(&start(), 2.0, &mut variation::seeded_rng(Some(1), 0));
+        let b = circle_waypoints(&start(), 2.0, &mut variation::seeded_rng(Some(2), 0));
         assert_ne!(a, b);
     }
 }
@@ -1,11 +1,29 @@
+use crate::models::coordinates::lng_delta;
 use crate::models::Coordinates;
 
+/// `points` with longitudes made continuous around the first point, so a
+/// shape straddling the antimeridian stays one planar shape (longitudes may
+/// leave [-180, 180])
+pub fn unwrap_lngs(points: &[Coordinates]) -> Vec<Coordinates> {
+    let Some(first) = points.first() else {
+        return Vec::new();
+    };
+    points
+        .iter()
+        .map(|p| Coordinates {
+            lat: p.lat,
+            lng: first.lng + lng_delta(first.lng, p.lng),
+        })
+        .collect()
+}
+
 /// Compute signed area of a polygon using the Shoelace formula
 /// Uses lat/lng as approximate planar coordinates (fine for small areas)
 pub fn shoelace_area(points: &[Coordinates]) -> f64 {
     if points.len() < 3 {
         return 0.0;
     }
+    let points = unwrap_lngs(points);
 
     let mut area = 0.0;
     let n = points.len();
@@ -23,7 +41,7 @@ pub fn path_length(path: &[Coordinates]) -> f64 {
     path.windows(2)
         .map(|w| {
             let dlat = w[1].lat - w[0].lat;
-            let dlng = w[1].lng - w[0].lng;
+            let dlng = lng_delta(w[0].lng, w[1].lng);
             (dlat * dlat + dlng * dlng).sqrt()
         })
         .sum()
@@ -56,35 +74,34 @@ pub fn point_to_segment_distance_deg(
     seg_start: &Coordinates,
     seg_end: &Coordinates,
 ) -> f64 {
-    let dx = seg_end.lng - seg_start.lng;
+    // Relative to `seg_start`, so segments across the antimeridian stay short
+    let dx = lng_delta(seg_start.lng, seg_end.lng);
     let dy = seg_end.lat - seg_start.lat;
     let len_sq = dx * dx + dy * dy;
+    let px = lng_delta(seg_start.lng, point.lng);
+    let py = point.lat - seg_start.lat;
 
     if len_sq < 1e-20 {
-        let dlat = point.lat - seg_start.lat;
-        let dlng = point.lng - seg_start.lng;
-        return (dlat * dlat + dlng * dlng).sqrt();
+        return (px * px + py * py).sqrt();
     }
 
-    let t = ((point.lng - seg_start.lng) * dx + (point.lat - seg_start.lat) * dy) / len_sq;
-    let t = t.clamp(0.0, 1.0);
-
-    let proj_lng = seg_start.lng + t * dx;
-    let proj_lat = seg_start.lat + t * dy;
+    let t = ((px * dx + py * dy) / len_sq).clamp(0.0, 1.0);
 
-    let dlat = point.lat - proj_lat;
-    let dlng = point.lng - proj_lng;
+    let dlat = py - t * dy;
+    let dlng = px - t * dx;
     (dlat * dlat + dlng * dlng).sqrt()
 }
 
-/// Compute convex hull using Andrew's monotone chain algorithm
+/// Compute convex hull using Andrew's monotone chain algorithm.
+/// Longitudes are unwrapped around the first point, so hull points of a set
+/// straddling the antimeridian may lie outside [-180, 180].
 pub fn convex_hull(points: &[Coordinates]) -> Vec<Coordinates> {
     if points.len() < 3 {
         return points.to_vec();
     }
 
     // Sort by lng, then lat
-    let mut sorted: Vec<Coordinates> = points.to_vec();
+    let mut sorted: Vec<Coordinates> = unwrap_lngs(points);
     sorted.sort_by(|a, b| {
         a.lng
             .partial_cmp(&b.lng)
@@ -141,7 +158,7 @@ pub fn convex_hull_area(points: &[Coordinates]) -> f64 {
 
 /// Calculate the angle (in radians, -PI to PI) from `start` to `target`
 pub fn angle_from_start(start: &Coordinates, target: &Coordinates) -> f64 {
-    let dx = target.lng - start.lng;
+    let dx = lng_delta(start.lng, target.lng);
     let dy = target.lat - start.lat;
     dy.atan2(dx)
 }
@@ -351,4 +368,29 @@ mod tests {
         let south = angle_from_start(&origin, &c(-1.0, 0.0));
         assert!((south + PI / 2.0).abs() < 1e-10, "south={south}");
     }
+
+    // --- antimeridian ---
+
+    #[test]
+    fn geometry_is_continuous_across_antimeridian() {
+        // The same 0.2° square, once around 0° and once around 180°
+        let square = |lng: f64| {
+            [(-17.1, -0.1), (-17.1, 0.1), (-16.9, 0.1), (-16.9, -0.1)]
+                .iter()
+                .map(|(lat, dlng)| c(*lat, crate::models::coordinates::wrap_lng(lng + dlng)))
+                .collect::<Vec<_>>()
+        };
+        let greenwich = square(0.0);
+        let fiji = square(180.0);
+
+        assert!((shoelace_area(&fiji).abs() - shoelace_area(&greenwich).abs()).abs() < 1e-9);
+        assert!((convex_hull_area(&fiji) - 0.04).abs() < 1e-9);
+        assert!((path_length(&fiji) - path_length(&greenwich)).abs() < 1e-9);
+
+        // 179.9°E -> 179.9°W is a short hop east
+        let east = angle_from_start(&c(-17.0, 179.9), &c(-17.0, -179.9));
+        assert!(east.abs() < 1e-9, "east={east}");
+        let d = point_to_segment_distance_deg(&c(-17.0, 180.0), &fiji[0], &fiji[1]);
+        assert!((d - 0.1).abs() < 1e-9, "d={d}");
+    }
 }
@@ -0,0 +1,230 @@
+//! Partial re-routing of a single loop leg.
+//!
+//! When a route overshoots its distance tolerance, usually one leg is to blame:
+//! the router had to detour around a river, rail line or private area. Instead of
+//! discarding the whole attempt, the worst leg can be re-queried through an extra
+//! sub-waypoint and spliced back into the route.
+
+use crate::constants::{LEG_REPAIR_MAX_SUB_WAYPOINT_DETOUR, LEG_REPAIR_MIN_DETOUR_RATIO};
+use crate::models::{Coordinates, Poi};
+use crate::services::mapbox::DirectionsResponse;
+
+use super::geometry::segment_length_m;
+
+/// Leg with the largest road-distance / straight-line ratio, if it detours
+/// by at least `LEG_REPAIR_MIN_DETOUR_RATIO`. `waypoints` are the loop's
+/// waypoints including the start at both ends.
+pub fn worst_detour_leg(
+    waypoints: &[Coordinates],
+    directions: &DirectionsResponse,
+) -> Option<usize> {
+    if directions.legs.len() + 1 != waypoints.len() {
+        return None;
+    }
+
+    directions
+        .legs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, leg)| {
+            let straight_m = segment_length_m(&waypoints[i], &waypoints[i + 1]);
+            if straight_m <= 0.0 {
+                return None;
+            }
+            Some((i, leg.distance_meters / straight_m))
+        })
+        .filter(|(_, ratio)| *ratio >= LEG_REPAIR_MIN_DETOUR_RATIO)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i)
+}
+
+/// Unused candidate closest to the straight line between the leg's endpoints.
+/// Candidates whose straight-line detour exceeds `LEG_REPAIR_MAX_SUB_WAYPOINT_DETOUR`
+/// times the leg's length are ignored.
+pub fn pick_sub_waypoint<'a>(
+    from: &Coordinates,
+    to: &Coordinates,
+    candidates: &'a [Poi],
+    used: &[Poi],
+) -> Option<&'a Poi> {
+    let direct_m = segment_length_m(from, to);
+    let via_m = |poi: &Poi| {
+        segment_length_m(from, &poi.coordinates) + segment_length_m(&poi.coordinates, to)
+    };
+
+    candidates
+        .iter()
+        .filter(|poi| !used.iter().any(|u| u.id == poi.id))
+        .map(|poi| (poi, via_m(poi)))
+        .filter(|(_, via)| *via <= direct_m * LEG_REPAIR_MAX_SUB_WAYPOINT_DETOUR)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(poi, _)| poi)
+}
+
+/// Replace leg `leg` of `directions` with `replacement` (a route between the
+/// same two waypoints). The leg's geometry is located from cumulative leg
+/// distances, so it works without per-leg geometry from the backend.
+pub fn splice_leg(
+    directions: &DirectionsResponse,
+    leg: usize,
+    replacement: &DirectionsResponse,
+) -> Option<DirectionsResponse> {
+    let old_leg = directions.legs.get(leg)?;
+    let start_m: f64 = directions.legs[..leg]
+        .iter()
+        .map(|l| l.distance_meters)
+        .sum();
+    let end_m = start_m + old_leg.distance_meters;
+
+    let start_idx = geometry_index_at(&directions.geometry, start_m, directions.distance_meters)?;
+    let end_idx = geometry_index_at(&directions.geometry, end_m, directions.distance_meters)?;
+    if end_idx <= start_idx {
+        return None;
+    }
+
+    let mut geometry = directions.geometry[..start_idx].to_vec();
+    geometry.extend_from_slice(&replacement.geometry);
+    geometry.extend_from_slice(&directions.geometry[end_idx + 1..]);
+
+    let mut legs = directions.legs[..leg].to_vec();
+    legs.extend_from_slice(&replacement.legs);
+    legs.extend_from_slice(&directions.legs[leg + 1..]);
+
+    Some(DirectionsResponse {
+        distance_meters: directions.distance_meters - old_leg.distance_meters
+            + replacement.distance_meters,
+        duration_seconds: directions.duration_seconds - old_leg.duration_seconds
+            + replacement.duration_seconds,
+        geometry,
+        legs,
+    })
+}
+
+/// Index of the geometry point closest to `distance_m` along the route.
+/// Geometry lengths are rescaled to the backend's total distance so the
+/// haversine approximation doesn't drift from the reported leg distances.
+fn geometry_index_at(geometry: &[[f64; 2]], distance_m: f64, total_m: f64) -> Option<usize> {
+    if geometry.len() < 2 {
+        return None;
+    }
+    let to_coord = |p: &[f64; 2]| Coordinates {
+        lat: p[1],
+        lng: p[0],
+    };
+
+    let mut cumulative = vec![0.0];
+    for w in geometry.windows(2) {
+        let last = *cumulative.last().unwrap_or(&0.0);
+        cumulative.push(last + segment_length_m(&to_coord(&w[0]), &to_coord(&w[1])));
+    }
+    let geometry_m = *cumulative.last().unwrap_or(&0.0);
+    if geometry_m <= 0.0 || total_m <= 0.0 {
+        return None;
+    }
+    let scale = geometry_m / total_m;
+
+    cumulative
+        .iter()
+        .enumerate()
+        .min_by(|a, b| {
+            (a.1 - distance_m * scale)
+                .abs()
+                .partial_cmp(&(b.1 - distance_m * scale).abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PoiCategory;
+    use crate::services::mapbox::DirectionsLeg;
+
+    fn c(lat: f64, lng: f64) -> Coordinates {
+        Coordinates::new(lat, lng).unwrap()
+    }
+
+    fn leg(distance_meters: f64) -> DirectionsLeg {
+        DirectionsLeg {
+            distance_meters,
+            duration_seconds: distance_meters,
+        }
+    }
+
+    fn poi(name: &str, coords: Coordinates) -> Poi {
+        Poi::new(name.to_string(), PoiCategory::Park, coords, 50.0)
+    }
+
+    #[test]
+    fn test_worst_detour_leg() {
+        let waypoints = vec![c(48.0, 2.0), c(48.01, 2.0), c(48.0, 2.0)];
+        let straight = segment_length_m(&waypoints[0], &waypoints[1]);
+        let directions = DirectionsResponse {
+            distance_meters: 0.0,
+            duration_seconds: 0.0,
+            geometry: vec![],
+            legs: vec![leg(straight * 1.1), leg(straight * 3.0)],
+        };
+        assert_eq!(worst_detour_leg(&waypoints, &directions), Some(1));
+
+        // No leg detours enough to be worth repairing
+        let direct = DirectionsResponse {
+            legs: vec![leg(straight * 1.1), leg(straight * 1.2)],
+            ..directions.clone()
+        };
+        assert_eq!(worst_detour_leg(&waypoints, &direct), None);
+
+        // Leg count doesn't match waypoints
+        let missing = DirectionsResponse {
+            legs: vec![],
+            ..directions
+        };
+        assert_eq!(worst_detour_leg(&waypoints, &missing), None);
+    }
+
+    #[test]
+    fn test_pick_sub_waypoint_prefers_straight_line() {
+        let from = c(48.0, 2.0);
+        let to = c(48.0, 2.02);
+        let on_line = poi("On line", c(48.0005, 2.01));
+        let off_line = poi("Off line", c(48.003, 2.01));
+        let far = poi("Far", c(48.05, 2.01));
+        let candidates = vec![off_line.clone(), on_line.clone(), far];
+
+        let picked = pick_sub_waypoint(&from, &to, &candidates, &[]).unwrap();
+        assert_eq!(picked.id, on_line.id);
+
+        // Already-used POIs are skipped
+        let picked = pick_sub_waypoint(&from, &to, &candidates, &[on_line]).unwrap();
+        assert_eq!(picked.id, off_line.id);
+    }
+
+    #[test]
+    fn test_splice_leg_replaces_geometry_and_totals() {
+        // Straight east-west line: leg 0 covers the first half, leg 1 the second
+        let geometry: Vec<[f64; 2]> = (0..=10).map(|i| [2.0 + i as f64 * 0.001, 48.0]).collect();
+        let to_coord = |p: &[f64; 2]| c(p[1], p[0]);
+        let half_m = segment_length_m(&to_coord(&geometry[0]), &to_coord(&geometry[5]));
+        let directions = DirectionsResponse {
+            distance_meters: half_m * 2.0,
+            duration_seconds: half_m * 2.0,
+            geometry: geometry.clone(),
+            legs: vec![leg(half_m), leg(half_m)],
+        };
+
+        let replacement = DirectionsResponse {
+            distance_meters: 100.0,
+            duration_seconds: 100.0,
+            geometry: vec![geometry[5], [2.0075, 48.0001], geometry[10]],
+            legs: vec![leg(60.0), leg(40.0)],
+        };
+
+        let spliced = splice_leg(&directions, 1, &replacement).unwrap();
+        assert_eq!(spliced.geometry.len(), 5 + 3);
+        assert_eq!(spliced.geometry[5], geometry[5]);
+        assert_eq!(spliced.legs.len(), 3);
+        assert!((spliced.distance_meters - (half_m + 100.0)).abs() < 1e-6);
+        assert!(splice_leg(&directions, 5, &replacement).is_none());
+    }
+}
@@ -0,0 +1,200 @@
+use super::route_metrics::RouteMetrics;
+use crate::models::Route;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Circularity thresholds (isoperimetric ratio) for shape labels
+const SHAPE_VERY_ROUND: f32 = 0.6;
+const SHAPE_ROUND: f32 = 0.4;
+const SHAPE_ELONGATED: f32 = 0.2;
+
+/// Overlap fraction thresholds for backtracking labels
+const BACKTRACK_MINIMAL: f32 = 0.05;
+const BACKTRACK_SOME: f32 = 0.15;
+const BACKTRACK_NOTICEABLE: f32 = 0.3;
+
+/// Landmark coverage thresholds (average popularity / 100)
+const LANDMARKS_FAMOUS: f32 = 0.7;
+const LANDMARKS_MIXED: f32 = 0.4;
+
+/// POIs per km thresholds for density labels
+const DENSITY_PACKED: f32 = 3.0;
+const DENSITY_STEADY: f32 = 1.0;
+
+/// A human-readable label plus a one-line explanation for one metric
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MetricExplanation {
+    pub label: String,
+    pub explanation: String,
+}
+
+impl MetricExplanation {
+    fn new(label: impl Into<String>, explanation: impl Into<String>) -> Self {
+        MetricExplanation {
+            label: label.into(),
+            explanation: explanation.into(),
+        }
+    }
+}
+
+/// Server-side interpretation of `RouteMetrics` so clients don't reimplement thresholds
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MetricsExplained {
+    pub shape: MetricExplanation,
+    pub variety: MetricExplanation,
+    pub backtracking: MetricExplanation,
+    pub landmarks: MetricExplanation,
+    pub poi_density: MetricExplanation,
+}
+
+impl MetricsExplained {
+    pub fn new(route: &Route, metrics: &RouteMetrics) -> Self {
+        MetricsExplained {
+            shape: explain_shape(metrics.circularity),
+            variety: explain_variety(route, metrics.category_entropy),
+            backtracking: explain_backtracking(metrics.path_overlap_pct),
+            landmarks: explain_landmarks(route, metrics.landmark_coverage),
+            poi_density: explain_poi_density(metrics.poi_density_per_km),
+        }
+    }
+}
+
+fn explain_shape(circularity: f32) -> MetricExplanation {
+    if circularity >= SHAPE_VERY_ROUND {
+        MetricExplanation::new("very round", "A true loop with little doubling back.")
+    } else if circularity >= SHAPE_ROUND {
+        MetricExplanation::new("round", "A loop with a slightly stretched outline.")
+    } else if circularity >= SHAPE_ELONGATED {
+        MetricExplanation::new("elongated", "A narrow loop that covers a long, thin area.")
+    } else {
+        MetricExplanation::new("narrow", "Close to an out-and-back route.")
+    }
+}
+
+fn explain_variety(route: &Route, entropy: f32) -> MetricExplanation {
+    let categories: HashSet<_> = route
+        .pois
+        .iter()
+        .map(|rp| &rp.poi.category)
+        .chain(route.snapped_pois.iter().map(|sp| &sp.poi.category))
+        .collect();
+
+    let label = match categories.len() {
+        1 => "1 category".to_string(),
+        n => format!("{} categories", n),
+    };
+    let explanation = match categories.len() {
+        0 => "No points of interest along this route.",
+        1 => "Every stop is the same kind of place.",
+        _ if entropy >= 0.8 => "An even mix of different kinds of places.",
+        _ => "A mix of places, with one kind dominating.",
+    };
+    MetricExplanation::new(label, explanation)
+}
+
+fn explain_backtracking(overlap: f32) -> MetricExplanation {
+    if overlap < BACKTRACK_MINIMAL {
+        MetricExplanation::new("minimal", "You rarely walk the same street twice.")
+    } else if overlap < BACKTRACK_SOME {
+        MetricExplanation::new("some", "A few short stretches are repeated.")
+    } else if overlap < BACKTRACK_NOTICEABLE {
+        MetricExplanation::new("noticeable", "Parts of the route retrace earlier streets.")
+    } else {
+        MetricExplanation::new("heavy", "Much of the route retraces earlier streets.")
+    }
+}
+
+fn explain_landmarks(route: &Route, coverage: f32) -> MetricExplanation {
+    if route.pois.is_empty() {
+        MetricExplanation::new("none", "This route has no waypoint landmarks.")
+    } else if coverage >= LANDMARKS_FAMOUS {
+        MetricExplanation::new("famous", "Stops at well-known highlights.")
+    } else if coverage >= LANDMARKS_MIXED {
+        MetricExplanation::new("mixed", "A blend of popular spots and quieter places.")
+    } else {
+        MetricExplanation::new("off the beaten path", "Mostly lesser-known places.")
+    }
+}
+
+fn explain_poi_density(per_km: f32) -> MetricExplanation {
+    let explanation = format!("About {:.1} points of interest per km.", per_km);
+    if per_km >= DENSITY_PACKED {
+        MetricExplanation::new("packed", explanation)
+    } else if per_km >= DENSITY_STEADY {
+        MetricExplanation::new("steady", explanation)
+    } else {
+        MetricExplanation::new("sparse", explanation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::route::{RoutePoi, SnappedPoi};
+    use crate::models::{Coordinates, Poi, PoiCategory};
+    use crate::services::route_generator::route_metrics::PoiDensityContext;
+
+    fn make_poi(category: PoiCategory, popularity: f32) -> Poi {
+        Poi::new(
+            "Test".to_string(),
+            category,
+            Coordinates::new(48.8566, 2.3522).unwrap(),
+            popularity,
+        )
+    }
+
+    fn make_metrics(circularity: f32, overlap: f32, coverage: f32) -> RouteMetrics {
+        RouteMetrics {
+            circularity,
+            convexity: 0.9,
+            path_overlap_pct: overlap,
+            poi_density_per_km: 2.0,
+            category_entropy: 0.9,
+            landmark_coverage: coverage,
+            poi_density_context: PoiDensityContext::Dense,
+        }
+    }
+
+    #[test]
+    fn test_shape_and_backtracking_labels() {
+        let route = Route::new(5.0, 60, vec![], vec![]);
+
+        let round = MetricsExplained::new(&route, &make_metrics(0.7, 0.01, 0.0));
+        assert_eq!(round.shape.label, "very round");
+        assert_eq!(round.backtracking.label, "minimal");
+
+        let narrow = MetricsExplained::new(&route, &make_metrics(0.1, 0.5, 0.0));
+        assert_eq!(narrow.shape.label, "narrow");
+        assert_eq!(narrow.backtracking.label, "heavy");
+    }
+
+    #[test]
+    fn test_variety_counts_waypoint_and_snapped_categories() {
+        let route = Route::new(
+            5.0,
+            60,
+            vec![],
+            vec![
+                RoutePoi::new(make_poi(PoiCategory::Park, 80.0), 1, 1.0),
+                RoutePoi::new(make_poi(PoiCategory::Museum, 80.0), 2, 2.0),
+            ],
+        )
+        .with_snapped_pois(vec![
+            SnappedPoi::new(make_poi(PoiCategory::Cafe, 50.0), 1.5, 20.0),
+            SnappedPoi::new(make_poi(PoiCategory::Park, 50.0), 3.0, 20.0),
+        ]);
+
+        let explained = MetricsExplained::new(&route, &make_metrics(0.5, 0.1, 0.8));
+        assert_eq!(explained.variety.label, "3 categories");
+        assert_eq!(explained.landmarks.label, "famous");
+        assert_eq!(explained.poi_density.label, "steady");
+    }
+
+    #[test]
+    fn test_landmarks_without_waypoints() {
+        let route = Route::new(5.0, 60, vec![], vec![]);
+        let explained = MetricsExplained::new(&route, &make_metrics(0.5, 0.1, 0.0));
+        assert_eq!(explained.landmarks.label, "none");
+        assert_eq!(explained.variety.label, "0 categories");
+    }
+}
@@ -1,20 +1,41 @@
+pub mod candidates;
+pub(crate) mod failure;
 mod geometric_loop;
-pub mod geometry;
+pub(crate) mod geometry;
+mod leg_repair;
+pub mod metrics_explanation;
+mod pareto;
+mod pinned;
+pub mod progress;
+mod road_constraints;
 pub mod route_metrics;
 mod route_scoring;
-mod scoring_strategy;
+pub mod scoring_strategy;
+mod stages;
 mod tolerance_strategy;
+mod tsp;
+pub mod variation;
 mod waypoint_selection;
 
 use crate::config::RouteGeneratorConfig;
 use crate::constants::*;
-use crate::error::Result;
+use crate::db::PoiRepository;
+use crate::error::{AppError, Result};
 use crate::models::{Coordinates, Poi, Route, RoutePreferences, TransportMode};
+use crate::services::dependency_guard::{Dependency, DependencyGuard};
+use crate::services::directions::DirectionsProvider;
+use crate::services::elevation::{self, ElevationProvider};
+use crate::services::environment::EnvironmentalLayer;
 use crate::services::mapbox::MapboxClient;
 use crate::services::poi_service::PoiService;
 use crate::services::snapping_service::SnappingService;
+use std::sync::Arc;
 
+use candidates::CandidateIndex;
+use failure::{FailureCounts, FailureStats, FailureTally};
 use geometric_loop::GeometricLoopGenerator;
+use metrics_explanation::MetricsExplained;
+use progress::{GenerationEvent, ProgressSink};
 use route_metrics::RouteMetrics;
 use route_scoring::RouteScorer;
 use tolerance_strategy::ToleranceStrategy;
@@ -25,8 +46,13 @@ pub struct RouteGenerator {
     snapping_service: SnappingService,
     snap_radius_m: f64,
     config: RouteGeneratorConfig,
+    directions: Arc<dyn DirectionsProvider>,
     geometric_loop_generator: GeometricLoopGenerator,
     tolerance_strategy: ToleranceStrategy,
+    environmental_layer: Option<Arc<dyn EnvironmentalLayer>>,
+    elevation_provider: Option<Arc<dyn ElevationProvider>>,
+    dependency_guard: Option<Arc<DependencyGuard>>,
+    failure_counts: Arc<FailureCounts>,
 }
 
 impl RouteGenerator {
@@ -36,14 +62,47 @@ impl RouteGenerator {
         snapping_service: SnappingService,
         snap_radius_m: f64,
         config: RouteGeneratorConfig,
+    ) -> Self {
+        Self::assemble(
+            Arc::new(mapbox_client),
+            poi_service,
+            snapping_service,
+            snap_radius_m,
+            config,
+        )
+    }
+
+    /// A generator over caller-supplied data sources (in-memory POIs, a
+    /// custom router...), without Postgres or Mapbox. POIs are snapped
+    /// within `DEFAULT_SNAP_RADIUS_METERS`.
+    pub fn from_parts(
+        directions: Arc<dyn DirectionsProvider>,
+        pois: Arc<dyn PoiRepository>,
+        config: RouteGeneratorConfig,
+    ) -> Self {
+        Self::assemble(
+            directions,
+            PoiService::new(Arc::clone(&pois)),
+            SnappingService::new(pois),
+            DEFAULT_SNAP_RADIUS_METERS,
+            config,
+        )
+    }
+
+    fn assemble(
+        directions: Arc<dyn DirectionsProvider>,
+        poi_service: PoiService,
+        snapping_service: SnappingService,
+        snap_radius_m: f64,
+        config: RouteGeneratorConfig,
     ) -> Self {
         let waypoint_selector = WaypointSelector::new(config.clone());
         let route_scorer =
             RouteScorer::new(snapping_service.clone(), snap_radius_m, config.clone());
-        let geometric_loop_generator = GeometricLoopGenerator::new(mapbox_client.clone());
+        let geometric_loop_generator = GeometricLoopGenerator::new(Arc::clone(&directions));
         let tolerance_strategy = ToleranceStrategy::new(
             config.clone(),
-            mapbox_client,
+            Arc::clone(&directions),
             waypoint_selector,
             route_scorer,
         );
@@ -53,17 +112,72 @@ impl RouteGenerator {
             snapping_service,
             snap_radius_m,
             config,
+            directions,
             geometric_loop_generator,
             tolerance_strategy,
+            environmental_layer: None,
+            elevation_provider: None,
+            dependency_guard: None,
+            failure_counts: Arc::new(FailureCounts::default()),
         }
     }
 
+    /// Consult the fallback policy when the POI database fails: with
+    /// `FALLBACK_POSTGRES=degrade` the request gets a geometric loop instead
+    /// of an error.
+    pub fn with_dependency_guard(mut self, guard: Arc<DependencyGuard>) -> Self {
+        self.dependency_guard = Some(guard);
+        self
+    }
+
+    /// Attach a noise/air-quality layer: routes get an exposure metric, and
+    /// `minimize_exposure` requests penalize exposed routes.
+    pub fn with_environmental_layer(mut self, layer: Arc<dyn EnvironmentalLayer>) -> Self {
+        tracing::info!(layer = layer.name(), "Environmental layer enabled");
+        self.tolerance_strategy
+            .set_environmental_layer(Arc::clone(&layer));
+        self.environmental_layer = Some(layer);
+        self
+    }
+
+    /// Look up terrain heights: routes get `elevation_gain_m`, and
+    /// `prefer_flat` / `max_elevation_gain_m` requests penalize climbing.
+    pub fn with_elevation_provider(mut self, provider: Arc<dyn ElevationProvider>) -> Self {
+        tracing::info!(provider = provider.name(), "Elevation lookups enabled");
+        self.tolerance_strategy
+            .set_elevation_provider(Arc::clone(&provider));
+        self.elevation_provider = Some(provider);
+        self
+    }
+
+    /// Route with another directions backend instead of Mapbox (e.g.
+    /// Valhalla, which honours `preferences.costing`).
+    pub fn with_directions_provider(mut self, provider: Arc<dyn DirectionsProvider>) -> Self {
+        tracing::info!(provider = provider.name(), "Directions provider selected");
+        self.geometric_loop_generator = GeometricLoopGenerator::new(Arc::clone(&provider));
+        self.tolerance_strategy
+            .set_directions_provider(Arc::clone(&provider));
+        self.directions = provider;
+        self
+    }
+
+    /// The dependency behind the directions backend in use
+    pub fn routing_dependency(&self) -> Dependency {
+        self.directions.dependency()
+    }
+
+    /// Failed generation attempts since startup, by reason
+    pub fn failure_stats(&self) -> FailureStats {
+        self.failure_counts.stats()
+    }
+
     /// Enhance a geometric fallback route with snapped POIs and quality metrics.
     /// Snapping failure is non-fatal — the route is always returned.
     async fn enhance_geometric_route(
         &self,
         mut route: Route,
         target_distance_km: f64,
+        mode: &TransportMode,
         preferences: &RoutePreferences,
         area_poi_count: usize,
     ) -> Route {
@@ -74,6 +188,7 @@ impl RouteGenerator {
                 &[],
                 self.snap_radius_m,
                 preferences.poi_categories.as_deref(),
+                preferences.excluded_poi_categories.as_deref(),
             )
             .await
         {
@@ -92,16 +207,45 @@ impl RouteGenerator {
             }
         }
 
+        if route.distance_km >= self.config.amenities_min_distance_km {
+            match self
+                .snapping_service
+                .find_amenities(&route.path, self.snap_radius_m)
+                .await
+            {
+                Ok(amenities) => route.amenities = amenities,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to find amenities, continuing without");
+                }
+            }
+        }
+
+        if let Some(ref layer) = self.environmental_layer {
+            route.environmental_exposure = layer.exposure_along(&route.path);
+        }
+
+        if let Some(ref provider) = self.elevation_provider {
+            match elevation::elevation_gain_along(provider.as_ref(), &route.path).await {
+                Ok(gain) => route.elevation_gain_m = gain,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to look up elevation, continuing without");
+                }
+            }
+        }
+        route = route.with_duration_estimates(mode, &self.config.speed_models());
+
         let metrics = RouteMetrics::compute_with_threshold(
             &route,
             area_poi_count,
             self.config.metrics_overlap_threshold_m,
         );
+        route.metrics_explained = Some(MetricsExplained::new(&route, &metrics));
         route.metrics = Some(metrics);
 
         route.score = self
             .tolerance_strategy
             .score_route(&route, target_distance_km, preferences);
+        route.quality_tier = self.tolerance_strategy.quality_tier(&route);
 
         route
     }
@@ -196,7 +340,7 @@ impl RouteGenerator {
             ) as usize
         };
 
-        let raw_pois = self
+        let found = self
             .poi_service
             .find_pois(
                 start,
@@ -204,7 +348,37 @@ impl RouteGenerator {
                 preferences.poi_categories.as_deref(),
                 poi_limit,
             )
-            .await?;
+            .await;
+        let mut raw_pois = match (found, &self.dependency_guard) {
+            (Ok(pois), guard) => {
+                if let Some(guard) = guard {
+                    guard.report_success(Dependency::Postgres);
+                }
+                pois
+            }
+            (Err(e @ AppError::Database(_)), Some(guard))
+                if guard.degrades(Dependency::Postgres) =>
+            {
+                guard.report_error(&e);
+                return Ok(None);
+            }
+            (Err(e), _) => return Err(e),
+        };
+
+        // Amenities are returned separately and must not compete for waypoint
+        // slots; accommodation only anchors staged routes
+        raw_pois.retain(|poi| poi.category.is_point_of_interest());
+
+        if preferences.excluded_poi_categories.is_some() {
+            let before = raw_pois.len();
+            raw_pois.retain(|poi| !preferences.excludes(&poi.category));
+            tracing::debug!(
+                excluded = before - raw_pois.len(),
+                categories = ?preferences.excluded_poi_categories,
+                "Dropped {} POIs in excluded categories",
+                before - raw_pois.len()
+            );
+        }
 
         if raw_pois.is_empty() {
             tracing::warn!(
@@ -255,14 +429,16 @@ impl RouteGenerator {
 
     /// Try generating routes at progressively relaxed tolerance levels.
     /// Returns routes on success, or empty vec if all levels exhausted.
+    #[allow(clippy::too_many_arguments)]
     async fn try_tolerance_levels(
         &self,
-        start: &Coordinates,
+        candidates: &CandidateIndex<'_>,
         target_distance_km: f64,
         distance_tolerance: f64,
         mode: &TransportMode,
-        candidate_pois: &[Poi],
         preferences: &RoutePreferences,
+        tally: &mut FailureTally,
+        progress: &ProgressSink,
     ) -> Vec<Route> {
         let relaxed_str = format!(
             "relaxed (±{}%)",
@@ -285,32 +461,34 @@ impl RouteGenerator {
             ),
         ];
 
-        let max_alternatives = preferences.max_alternatives.clamp(
-            crate::constants::MIN_ALTERNATIVES_FOR_SUCCESS,
-            crate::constants::MAX_ALTERNATIVES_CLAMP,
-        ) as usize;
+        let attempts = tolerance_strategy::attempts_per_level(preferences);
 
         for (level_index, (tolerance, tolerance_name)) in tolerance_levels.iter().enumerate() {
             tracing::info!(
                 tolerance_name = tolerance_name,
                 tolerance_km = %format!("{:.2}", tolerance),
                 target_km = %format!("{:.1}", target_distance_km),
-                candidates = candidate_pois.len(),
+                candidates = candidates.len(),
                 "Trying {} tolerance: {:.1}km ± {:.2}km",
                 tolerance_name, target_distance_km, tolerance
             );
+            progress.emit(GenerationEvent::ToleranceLevel {
+                level: tolerance_name.to_string(),
+                tolerance_km: Some(*tolerance),
+            });
 
-            let seed_offset = level_index * max_alternatives;
+            let seed_offset = level_index * attempts;
             let routes = self
                 .tolerance_strategy
                 .try_generate_routes_with_tolerance(
-                    start,
+                    candidates,
                     target_distance_km,
                     *tolerance,
                     mode,
-                    candidate_pois,
                     preferences,
                     seed_offset,
+                    tally,
+                    progress,
                 )
                 .await;
 
@@ -337,34 +515,41 @@ impl RouteGenerator {
     }
 
     /// Last-resort POI-based attempt: accept any route within ±100% of target distance.
+    #[allow(clippy::too_many_arguments)]
     async fn try_extreme_tolerance(
         &self,
-        start: &Coordinates,
+        candidates: &CandidateIndex<'_>,
         target_distance_km: f64,
         mode: &TransportMode,
-        candidate_pois: &[Poi],
         preferences: &RoutePreferences,
         seed_offset: usize,
+        tally: &mut FailureTally,
+        progress: &ProgressSink,
     ) -> Vec<Route> {
         let extreme_tolerance = target_distance_km; // ±100%
         tracing::warn!(
             tolerance_km = %format!("{:.2}", extreme_tolerance),
             target_km = %format!("{:.1}", target_distance_km),
-            candidates = candidate_pois.len(),
+            candidates = candidates.len(),
             "All tolerance levels exhausted, trying extreme tolerance (±100%): {:.1}km ± {:.2}km",
             target_distance_km, extreme_tolerance
         );
+        progress.emit(GenerationEvent::ToleranceLevel {
+            level: "extreme (±100%)".to_string(),
+            tolerance_km: Some(extreme_tolerance),
+        });
 
         let routes = self
             .tolerance_strategy
             .try_generate_routes_with_tolerance(
-                start,
+                candidates,
                 target_distance_km,
                 extreme_tolerance,
                 mode,
-                candidate_pois,
                 preferences,
                 seed_offset,
+                tally,
+                progress,
             )
             .await;
 
@@ -390,39 +575,137 @@ impl RouteGenerator {
         mode: &TransportMode,
         preferences: &RoutePreferences,
     ) -> Result<Vec<Route>> {
-        tracing::info!(
-            "Generating loop route from {:?}, target: {}km",
+        self.generate_loop_route_with_progress(
             start,
-            target_distance_km
-        );
+            target_distance_km,
+            distance_tolerance,
+            mode,
+            preferences,
+            &ProgressSink::disabled(),
+        )
+        .await
+    }
 
-        // Step 1: Discover and filter POIs
-        let candidate_pois = match self
-            .discover_and_filter_pois(&start, target_distance_km, preferences)
-            .await?
-        {
-            Some(pois) => pois,
+    /// [`generate_loop_route`](Self::generate_loop_route), reporting POI
+    /// discovery, each tolerance level tried and each route candidate to
+    /// `progress` as it happens
+    pub async fn generate_loop_route_with_progress(
+        &self,
+        start: Coordinates,
+        target_distance_km: f64,
+        distance_tolerance: f64,
+        mode: &TransportMode,
+        preferences: &RoutePreferences,
+        progress: &ProgressSink,
+    ) -> Result<Vec<Route>> {
+        // The start point itself is logged (privacy-aware) by the caller
+        tracing::info!("Generating loop route, target: {}km", target_distance_km);
+        let preferences = &preferences.with_mode_defaults(mode);
+        let routes = self
+            .generate_routes(
+                start,
+                target_distance_km,
+                distance_tolerance,
+                mode,
+                preferences,
+                progress,
+            )
+            .await?;
+        let speeds = self.config.speed_models();
+        Ok(routes
+            .into_iter()
+            .map(|route| route.with_requested_timing(mode, preferences, &speeds))
+            .collect())
+    }
+
+    /// The generation ladder: tolerance levels, extreme tolerance, then a
+    /// geometric loop. `preferences` already carry the mode's defaults.
+    /// Failed attempts are counted, and explain the error when even the
+    /// geometric loop fails.
+    async fn generate_routes(
+        &self,
+        start: Coordinates,
+        target_distance_km: f64,
+        distance_tolerance: f64,
+        mode: &TransportMode,
+        preferences: &RoutePreferences,
+        progress: &ProgressSink,
+    ) -> Result<Vec<Route>> {
+        let mut tally = FailureTally::default();
+        let result = self
+            .run_generation_ladder(
+                start,
+                target_distance_km,
+                distance_tolerance,
+                mode,
+                preferences,
+                &mut tally,
+                progress,
+            )
+            .await;
+        if let Err(ref e) = result {
+            tally.record(e, target_distance_km);
+        }
+        self.failure_counts.add(&tally);
+        result.map_err(|e| tally.into_error(e))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn run_generation_ladder(
+        &self,
+        start: Coordinates,
+        target_distance_km: f64,
+        distance_tolerance: f64,
+        mode: &TransportMode,
+        preferences: &RoutePreferences,
+        tally: &mut FailureTally,
+        progress: &ProgressSink,
+    ) -> Result<Vec<Route>> {
+        // Step 1: Discover and filter POIs; pinned places lead the candidates
+        let (pinned, discovered) = self
+            .discover_with_pinned(&start, target_distance_km, distance_tolerance, preferences)
+            .await?;
+        progress.emit(GenerationEvent::PoiDiscovery {
+            candidates: discovered.as_ref().map_or(0, Vec::len),
+        });
+        let candidate_pois = match discovered {
+            Some(pois) => pinned::with_pinned_first(&pinned, pois),
+            None if !pinned.is_empty() => pinned.clone(),
             None => {
+                progress.emit(geometric_level());
                 let route = self
                     .geometric_loop_generator
-                    .generate_geometric_loop(start, target_distance_km, mode)
+                    .generate_geometric_loop(
+                        start,
+                        target_distance_km,
+                        mode,
+                        &preferences.costing,
+                        preferences.surface,
+                        preferences.seed,
+                    )
                     .await?;
                 let route = self
-                    .enhance_geometric_route(route, target_distance_km, preferences, 0)
+                    .enhance_geometric_route(route, target_distance_km, mode, preferences, 0)
                     .await;
                 return Ok(vec![route]);
             }
         };
 
+        // Per-POI distances, angles and quality don't depend on the tolerance
+        // level, so compute them once for all levels
+        let candidates =
+            CandidateIndex::new(&start, &candidate_pois, preferences).with_pinned(pinned.len());
+
         // Step 2: Try progressively relaxed tolerance levels
         let routes = self
             .try_tolerance_levels(
-                &start,
+                &candidates,
                 target_distance_km,
                 distance_tolerance,
                 mode,
-                &candidate_pois,
                 preferences,
+                tally,
+                progress,
             )
             .await;
         if !routes.is_empty() {
@@ -430,43 +713,67 @@ impl RouteGenerator {
         }
 
         // Step 3: Extreme tolerance (±100%)
-        let max_alternatives = preferences.max_alternatives.clamp(
-            crate::constants::MIN_ALTERNATIVES_FOR_SUCCESS,
-            crate::constants::MAX_ALTERNATIVES_CLAMP,
-        ) as usize;
-        let seed_offset = 3 * max_alternatives; // After 3 normal tolerance levels
+        let attempts = tolerance_strategy::attempts_per_level(preferences);
+        let seed_offset = 3 * attempts; // After 3 normal tolerance levels
         let routes = self
             .try_extreme_tolerance(
-                &start,
+                &candidates,
                 target_distance_km,
                 mode,
-                &candidate_pois,
                 preferences,
                 seed_offset,
+                tally,
+                progress,
             )
             .await;
         if !routes.is_empty() {
             return Ok(routes);
         }
 
-        // Step 4: Final fallback — geometric loop
+        // Step 4: Final fallback — geometric loop, which can't pass through pinned places
+        if !pinned.is_empty() {
+            return Err(AppError::RouteGeneration(
+                "No loop through the must_include places fits the requested distance".to_string(),
+            ));
+        }
         tracing::warn!(
             candidates = candidate_pois.len(),
             target_km = %format!("{:.1}", target_distance_km),
             "All tolerance levels exhausted with {} candidates for {:.1}km target, falling back to geometric loop",
             candidate_pois.len(), target_distance_km
         );
+        progress.emit(geometric_level());
         let route = self
             .geometric_loop_generator
-            .generate_geometric_loop(start, target_distance_km, mode)
+            .generate_geometric_loop(
+                start,
+                target_distance_km,
+                mode,
+                &preferences.costing,
+                preferences.surface,
+                preferences.seed,
+            )
             .await?;
         let route = self
-            .enhance_geometric_route(route, target_distance_km, preferences, candidate_pois.len())
+            .enhance_geometric_route(
+                route,
+                target_distance_km,
+                mode,
+                preferences,
+                candidate_pois.len(),
+            )
             .await;
         Ok(vec![route])
     }
 }
 
+fn geometric_level() -> GenerationEvent {
+    GenerationEvent::ToleranceLevel {
+        level: "geometric".to_string(),
+        tolerance_km: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -486,40 +793,29 @@ mod tests {
 
     #[test]
     fn test_route_scoring_logic() {
-        use crate::models::{Poi, Route, RoutePoi};
-
-        let route = Route {
-            id: uuid::Uuid::new_v4(),
-            distance_km: 5.1, // Close to target of 5.0
-            estimated_duration_minutes: 75,
-            elevation_gain_m: None,
-            path: vec![],
-            pois: vec![
-                RoutePoi::new(
-                    Poi::new(
-                        "Test 1".to_string(),
-                        PoiCategory::Monument,
-                        Coordinates::new(48.8566, 2.3522).unwrap(),
-                        80.0,
-                    ),
-                    1,
-                    1.7,
-                ),
-                RoutePoi::new(
-                    Poi::new(
-                        "Test 2".to_string(),
-                        PoiCategory::Park,
-                        Coordinates::new(48.8570, 2.3530).unwrap(),
-                        70.0,
-                    ),
-                    2,
-                    3.4,
-                ),
-            ],
-            snapped_pois: vec![],
-            score: 0.0,
-            metrics: None,
-        };
+        use crate::models::{Poi, Route};
+
+        let route = Route::builder()
+            .distance_km(5.1) // Close to target of 5.0
+            .duration_minutes(75)
+            .waypoint_at(
+                Poi::builder()
+                    .name("Test 1")
+                    .coordinates(Coordinates::new(48.8566, 2.3522).unwrap())
+                    .popularity(80.0)
+                    .build(),
+                1.7,
+            )
+            .waypoint_at(
+                Poi::builder()
+                    .name("Test 2")
+                    .category(PoiCategory::Park)
+                    .coordinates(Coordinates::new(48.8570, 2.3530).unwrap())
+                    .popularity(70.0)
+                    .build(),
+                3.4,
+            )
+            .build();
 
         // Test that route has expected properties
         assert_eq!(route.pois.len(), 2);
@@ -0,0 +1,219 @@
+//! Pareto route sets: instead of the top scorers, keep the alternatives that
+//! no other alternative beats on every objective (distance accuracy, POI
+//! quality, shape), and label each with the objective it does best.
+
+use super::route_scoring::RouteScorer;
+use crate::models::{Route, RoutePreferences, RouteStrength};
+
+/// Objective differences below this are ties, so near-identical routes don't
+/// dominate each other on noise
+const TIE_EPSILON: f32 = 1e-3;
+
+const STRENGTHS: [RouteStrength; 3] = [
+    RouteStrength::DistanceAccuracy,
+    RouteStrength::PoiQuality,
+    RouteStrength::Shape,
+];
+
+/// Per-route objectives, each 0-1 and higher-is-better
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Objectives {
+    pub distance_accuracy: f32,
+    pub poi_quality: f32,
+    /// Mean of roundness ((circularity + convexity) / 2) and path diversity
+    /// (1 - overlap); 0 without metrics
+    pub shape: f32,
+}
+
+impl Objectives {
+    pub fn of(route: &Route, target_distance_km: f64, hidden_gems: bool) -> Self {
+        let shape = route.metrics.as_ref().map_or(0.0, |m| {
+            ((m.circularity + m.convexity) / 2.0 + (1.0 - m.path_overlap_pct)) / 2.0
+        });
+        Objectives {
+            distance_accuracy: RouteScorer::distance_accuracy(route, target_distance_km),
+            poi_quality: RouteScorer::avg_poi_quality(route, hidden_gems),
+            shape,
+        }
+    }
+
+    /// In `STRENGTHS` order
+    fn values(&self) -> [f32; 3] {
+        [self.distance_accuracy, self.poi_quality, self.shape]
+    }
+
+    /// At least as good on every objective and strictly better on one
+    pub fn dominates(&self, other: &Objectives) -> bool {
+        let pairs = self.values().into_iter().zip(other.values());
+        let mut better = false;
+        for (mine, theirs) in pairs {
+            if mine + TIE_EPSILON < theirs {
+                return false;
+            }
+            better |= mine > theirs + TIE_EPSILON;
+        }
+        better
+    }
+}
+
+/// Keep the non-dominated routes, labeled with their strength. `routes` are
+/// expected best score first; that order is kept. When the frontier has more
+/// than `limit` routes, the best route on each objective is kept first and
+/// the rest are filled in by score.
+pub fn pareto_front(
+    routes: Vec<Route>,
+    target_distance_km: f64,
+    preferences: &RoutePreferences,
+    limit: usize,
+) -> Vec<Route> {
+    let objectives: Vec<Objectives> = routes
+        .iter()
+        .map(|r| Objectives::of(r, target_distance_km, preferences.hidden_gems))
+        .collect();
+    let mut front: Vec<(Route, Objectives)> = routes
+        .into_iter()
+        .zip(objectives.iter().copied())
+        .filter(|(_, mine)| !objectives.iter().any(|other| other.dominates(mine)))
+        .collect();
+
+    let best: Vec<f32> = (0..STRENGTHS.len())
+        .map(|k| front.iter().map(|(_, o)| o.values()[k]).fold(0.0, f32::max))
+        .collect();
+
+    let mut keep = vec![front.len() <= limit; front.len()];
+    if front.len() > limit {
+        for (k, best) in best.iter().enumerate() {
+            if let Some(champion) = front
+                .iter()
+                .position(|(_, o)| o.values()[k] + TIE_EPSILON >= *best)
+            {
+                keep[champion] = true;
+            }
+        }
+        let mut kept = keep.iter().filter(|k| **k).count();
+        for slot in keep.iter_mut() {
+            if kept >= limit {
+                break;
+            }
+            if !*slot {
+                *slot = true;
+                kept += 1;
+            }
+        }
+    }
+
+    let mut keep = keep.into_iter();
+    front.retain(|_| keep.next().unwrap_or(false));
+    front
+        .into_iter()
+        .map(|(mut route, objectives)| {
+            route.strength = Some(strength(&objectives, &best));
+            route
+        })
+        .collect()
+}
+
+/// The objective where the route comes closest to the frontier's best
+fn strength(objectives: &Objectives, best: &[f32]) -> RouteStrength {
+    let values = objectives.values();
+    let mut strongest = 0;
+    let mut strongest_ratio = f32::MIN;
+    for (k, (value, best)) in values.iter().zip(best).enumerate() {
+        let ratio = if *best > 0.0 { value / best } else { 0.0 };
+        if ratio > strongest_ratio + TIE_EPSILON {
+            strongest = k;
+            strongest_ratio = ratio;
+        }
+    }
+    STRENGTHS[strongest]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Coordinates, Poi, PoiCategory, RoutePoi};
+    use crate::services::route_generator::route_metrics::{PoiDensityContext, RouteMetrics};
+
+    /// A 5 km target; `shape` sets circularity and convexity with no overlap
+    fn route(distance_km: f64, popularity: f32, shape: f32) -> Route {
+        let start = Coordinates::new(48.85, 2.35).unwrap();
+        let poi = Poi::new("P".to_string(), PoiCategory::Park, start, popularity);
+        let mut route = Route::new(
+            distance_km,
+            60,
+            vec![start],
+            vec![RoutePoi::new(poi, 1, 1.0)],
+        );
+        route.metrics = Some(RouteMetrics {
+            circularity: shape,
+            convexity: shape,
+            path_overlap_pct: 0.0,
+            poi_density_per_km: 0.0,
+            category_entropy: 0.0,
+            landmark_coverage: 0.0,
+            poi_density_context: PoiDensityContext::Sparse,
+        });
+        route
+    }
+
+    fn strengths(routes: &[Route]) -> Vec<RouteStrength> {
+        routes.iter().filter_map(|r| r.strength).collect()
+    }
+
+    #[test]
+    fn test_dominance_needs_one_strict_improvement() {
+        let a = Objectives {
+            distance_accuracy: 0.9,
+            poi_quality: 0.5,
+            shape: 0.5,
+        };
+        let b = Objectives {
+            distance_accuracy: 0.8,
+            ..a
+        };
+        assert!(a.dominates(&b));
+        assert!(!b.dominates(&a));
+        assert!(!a.dominates(&a));
+        let trade_off = Objectives {
+            poi_quality: 0.9,
+            ..b
+        };
+        assert!(!a.dominates(&trade_off));
+        assert!(!trade_off.dominates(&a));
+    }
+
+    #[test]
+    fn test_front_drops_dominated_and_labels_strengths() {
+        let routes = vec![
+            route(5.0, 50.0, 0.5),  // exact distance
+            route(6.0, 95.0, 0.5),  // best POIs
+            route(6.0, 50.0, 0.95), // roundest
+            route(6.0, 40.0, 0.4),  // beaten by all of the above
+        ];
+        let front = pareto_front(routes, 5.0, &RoutePreferences::default(), 5);
+        assert_eq!(
+            strengths(&front),
+            vec![
+                RouteStrength::DistanceAccuracy,
+                RouteStrength::PoiQuality,
+                RouteStrength::Shape
+            ]
+        );
+    }
+
+    #[test]
+    fn test_limit_keeps_each_objective_champion() {
+        let routes = vec![
+            route(5.2, 60.0, 0.6),
+            route(5.3, 65.0, 0.55),
+            route(5.4, 70.0, 0.5),
+            route(5.0, 10.0, 0.1),
+            route(7.0, 99.0, 0.1),
+            route(8.0, 10.0, 0.99),
+        ];
+        let front = pareto_front(routes, 5.0, &RoutePreferences::default(), 4);
+        assert_eq!(front.len(), 4);
+        let distances: Vec<f64> = front.iter().map(|r| r.distance_km).collect();
+        assert_eq!(distances, vec![5.2, 5.0, 7.0, 8.0]);
+    }
+}
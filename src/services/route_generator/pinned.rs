@@ -0,0 +1,182 @@
+//! `must_include`: places every route passes through. Pinned POIs and points
+//! lead the candidate list, and waypoint selection fills the remaining slots
+//! around them.
+
+use super::tsp;
+use super::waypoint_selection::WaypointSelector;
+use super::RouteGenerator;
+use crate::error::{AppError, Result};
+use crate::models::{Coordinates, MustInclude, Poi, PoiCategory, RoutePreferences};
+
+impl RouteGenerator {
+    /// Resolve the pinned places, then discover POIs around `start`. With
+    /// pinned places an area without POIs is no error: they may make a loop
+    /// on their own.
+    pub(super) async fn discover_with_pinned(
+        &self,
+        start: &Coordinates,
+        target_distance_km: f64,
+        distance_tolerance: f64,
+        preferences: &RoutePreferences,
+    ) -> Result<(Vec<Poi>, Option<Vec<Poi>>)> {
+        let pinned = self
+            .resolve_must_include(start, target_distance_km, distance_tolerance, preferences)
+            .await?;
+        let discovered = match self
+            .discover_and_filter_pois(start, target_distance_km, preferences)
+            .await
+        {
+            Err(AppError::NoPoisFound(_)) if !pinned.is_empty() => Some(Vec::new()),
+            result => result?,
+        };
+        Ok((pinned, discovered))
+    }
+
+    /// `preferences.must_include` as POIs, in request order: IDs looked up,
+    /// bare points as `Waypoint` POIs. Fails if an ID is unknown or the
+    /// shortest loop through them can't fit `target_distance_km` plus
+    /// `distance_tolerance`.
+    async fn resolve_must_include(
+        &self,
+        start: &Coordinates,
+        target_distance_km: f64,
+        distance_tolerance: f64,
+        preferences: &RoutePreferences,
+    ) -> Result<Vec<Poi>> {
+        if preferences.must_include.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<_> = preferences
+            .must_include
+            .iter()
+            .filter_map(|pin| match pin {
+                MustInclude::Poi(id) => Some(*id),
+                MustInclude::Point(_) => None,
+            })
+            .collect();
+        let found = if ids.is_empty() {
+            Vec::new()
+        } else {
+            self.poi_service.find_by_ids(&ids).await?
+        };
+
+        let mut pinned = Vec::with_capacity(preferences.must_include.len());
+        for (i, pin) in preferences.must_include.iter().enumerate() {
+            let poi = match pin {
+                MustInclude::Poi(id) => found
+                    .iter()
+                    .find(|poi| poi.id == *id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        AppError::InvalidRequest(format!("must_include: unknown POI {id}"))
+                    })?,
+                MustInclude::Point(coordinates) => pinned_point(i, *coordinates),
+            };
+            if !pinned.iter().any(|p: &Poi| p.id == poi.id) {
+                pinned.push(poi);
+            }
+        }
+
+        let min_loop_km = shortest_loop_km(start, &pinned);
+        if min_loop_km > target_distance_km + distance_tolerance {
+            return Err(AppError::InvalidRequest(format!(
+                "must_include needs at least {:.1}km even in straight lines, more than the \
+                 requested {:.1}km (±{:.1}km); increase the distance or pin fewer places",
+                min_loop_km, target_distance_km, distance_tolerance
+            )));
+        }
+
+        tracing::debug!(
+            pinned = pinned.len(),
+            min_loop_km = %format!("{:.2}", min_loop_km),
+            "Routing through {} pinned places",
+            pinned.len()
+        );
+        Ok(pinned)
+    }
+}
+
+/// A bare point as a POI, so it can take a waypoint slot
+fn pinned_point(index: usize, coordinates: Coordinates) -> Poi {
+    let mut poi = Poi::new(
+        format!("Pinned point {}", index + 1),
+        PoiCategory::Waypoint,
+        coordinates,
+        0.0,
+    );
+    poi.estimated_visit_duration_minutes = Some(0);
+    poi
+}
+
+/// Straight-line length of the shortest loop from `start` through `pois`:
+/// a lower bound on any routed loop through them
+fn shortest_loop_km(start: &Coordinates, pois: &[Poi]) -> f64 {
+    if pois.is_empty() {
+        return 0.0;
+    }
+    // Always solved: the clockwise order alone can overestimate the bound
+    let clockwise = WaypointSelector::order_pois_clockwise(start, pois);
+    let ordered = tsp::shortest_loop_order(start, &clockwise, 0);
+    let mut points = vec![*start];
+    points.extend(ordered.iter().map(|poi| poi.coordinates));
+    points.push(*start);
+    points.windows(2).map(|w| w[0].distance_to(&w[1])).sum()
+}
+
+/// Candidates for selection: the pinned POIs first, then the discovered
+/// ones minus any already pinned
+pub(super) fn with_pinned_first(pinned: &[Poi], discovered: Vec<Poi>) -> Vec<Poi> {
+    let mut candidates = pinned.to_vec();
+    candidates.extend(
+        discovered
+            .into_iter()
+            .filter(|poi| !pinned.iter().any(|p| p.id == poi.id)),
+    );
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poi_at(name: &str, lat: f64, lng: f64) -> Poi {
+        Poi::new(
+            name.to_string(),
+            PoiCategory::Monument,
+            Coordinates::new(lat, lng).unwrap(),
+            50.0,
+        )
+    }
+
+    #[test]
+    fn test_shortest_loop_is_a_lower_bound() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        // ~1.1km north and ~1.5km east
+        let north = poi_at("North", 48.8666, 2.3522);
+        let east = poi_at("East", 48.8566, 2.3722);
+
+        let out_and_back = shortest_loop_km(&start, std::slice::from_ref(&north));
+        assert!((out_and_back - 2.0 * start.distance_to(&north.coordinates)).abs() < 1e-9);
+
+        let triangle = shortest_loop_km(&start, &[north.clone(), east.clone()]);
+        let expected = start.distance_to(&north.coordinates)
+            + north.coordinates.distance_to(&east.coordinates)
+            + east.coordinates.distance_to(&start);
+        assert!((triangle - expected).abs() < 1e-9);
+        assert_eq!(shortest_loop_km(&start, &[]), 0.0);
+    }
+
+    #[test]
+    fn test_pinned_lead_and_are_not_repeated() {
+        let pinned = vec![poi_at("Pinned", 48.86, 2.35)];
+        let other = poi_at("Other", 48.85, 2.36);
+        let candidates = with_pinned_first(&pinned, vec![other.clone(), pinned[0].clone()]);
+        let names: Vec<&str> = candidates.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Pinned", "Other"]);
+
+        let point = pinned_point(1, Coordinates::new(48.87, 2.34).unwrap());
+        assert_eq!(point.category, PoiCategory::Waypoint);
+        assert_eq!(point.visit_minutes(), 0);
+    }
+}
@@ -0,0 +1,115 @@
+//! Milestones of one generation, for clients that show live progress
+//! (`POST /routes/loop/stream`). Generation takes seconds across tolerance
+//! levels; the sink is a no-op for everyone else.
+
+use serde::Serialize;
+use tokio::sync::mpsc::UnboundedSender;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum GenerationEvent {
+    /// Candidate POIs selected (0 when the area has none and generation
+    /// falls back to a geometric loop)
+    PoiDiscovery { candidates: usize },
+    /// Generation moved to the next rung of the ladder. `tolerance_km` is
+    /// absent for the geometric fallback.
+    ToleranceLevel {
+        level: String,
+        tolerance_km: Option<f64>,
+    },
+    /// A loop within tolerance, before scoring and ranking
+    RouteCandidate {
+        route_id: Uuid,
+        distance_km: f64,
+        poi_count: usize,
+    },
+}
+
+impl GenerationEvent {
+    /// SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            GenerationEvent::PoiDiscovery { .. } => "poi_discovery",
+            GenerationEvent::ToleranceLevel { .. } => "tolerance_level",
+            GenerationEvent::RouteCandidate { .. } => "route_candidate",
+        }
+    }
+}
+
+/// Where a generation reports its progress
+#[derive(Debug, Clone, Default)]
+pub struct ProgressSink {
+    sender: Option<UnboundedSender<GenerationEvent>>,
+}
+
+impl ProgressSink {
+    pub fn new(sender: UnboundedSender<GenerationEvent>) -> Self {
+        ProgressSink {
+            sender: Some(sender),
+        }
+    }
+
+    /// Progress nobody listens to
+    pub fn disabled() -> Self {
+        ProgressSink::default()
+    }
+
+    /// Report `event`. Sending to a listener that went away is a no-op;
+    /// callers that should stop then wait on [`closed`](Self::closed).
+    pub fn emit(&self, event: GenerationEvent) {
+        if let Some(ref sender) = self.sender {
+            let _ = sender.send(event);
+        }
+    }
+
+    /// Resolves once the listener has gone away; never for a disabled sink
+    pub async fn closed(&self) {
+        match self.sender {
+            Some(ref sender) => sender.closed().await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_events_reach_the_listener_only() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let sink = ProgressSink::new(sender);
+        let event = GenerationEvent::ToleranceLevel {
+            level: "normal".to_string(),
+            tolerance_km: Some(0.5),
+        };
+        sink.emit(event.clone());
+        assert_eq!(receiver.try_recv().unwrap(), event);
+        assert_eq!(event.name(), "tolerance_level");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"level": "normal", "tolerance_km": 0.5})
+        );
+
+        // Nobody listening: emitting is a no-op
+        drop(receiver);
+        sink.emit(GenerationEvent::PoiDiscovery { candidates: 3 });
+        ProgressSink::disabled().emit(GenerationEvent::PoiDiscovery { candidates: 3 });
+    }
+
+    #[tokio::test]
+    async fn test_closed_once_the_listener_goes_away() {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let sink = ProgressSink::new(sender);
+        let closed = tokio::time::timeout(Duration::from_millis(10), sink.closed());
+        assert!(closed.await.is_err());
+        drop(receiver);
+        sink.closed().await;
+
+        let disabled = ProgressSink::disabled();
+        let closed = tokio::time::timeout(Duration::from_millis(10), disabled.closed());
+        assert!(closed.await.is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Road-profile constraints (paved share, busy roads, steps, trail surfaces)
+//! checked against each generated route.
+
+use crate::models::{Route, RoutePreferences};
+
+/// Check road-profile constraints (paved share, busy roads, steps). Routes without
+/// way data are accepted, since the constraint can't be evaluated.
+pub(super) fn road_profile_violation(
+    route: &Route,
+    preferences: &RoutePreferences,
+) -> Option<String> {
+    let profile = route.road_profile?;
+    if let Some(min) = preferences.min_paved_fraction {
+        if profile.paved_fraction < min {
+            return Some(format!(
+                "{:.0}% paved, need {:.0}%",
+                profile.paved_fraction * 100.0,
+                min * 100.0
+            ));
+        }
+    }
+    if let Some(max) = preferences.max_paved_fraction() {
+        if profile.paved_fraction > max {
+            return Some(format!(
+                "{:.0}% paved, trail routes allow {:.0}%",
+                profile.paved_fraction * 100.0,
+                max * 100.0
+            ));
+        }
+    }
+    if let Some(max) = preferences.max_busy_road_fraction {
+        if profile.busy_road_fraction > max {
+            return Some(format!(
+                "{:.0}% on busy roads, allowed {:.0}%",
+                profile.busy_road_fraction * 100.0,
+                max * 100.0
+            ));
+        }
+    }
+    if preferences.step_free {
+        if !profile.is_step_free() {
+            return Some(format!(
+                "{} flight(s) of steps, max incline {:.0}%",
+                profile.steps_count,
+                profile.max_incline_pct.unwrap_or(0.0)
+            ));
+        }
+        if let Some(min) = preferences.min_smooth_fraction() {
+            if profile.smooth_fraction < min {
+                return Some(format!(
+                    "{:.0}% smooth surfaces, need {:.0}%",
+                    profile.smooth_fraction * 100.0,
+                    min * 100.0
+                ));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::road_profile::{RoadProfile, SurfacePreference};
+
+    #[test]
+    fn test_road_profile_violation() {
+        let mut route = Route::new(5.0, 60, vec![], vec![]);
+        let prefs = RoutePreferences {
+            min_paved_fraction: Some(0.9),
+            max_busy_road_fraction: Some(0.2),
+            ..Default::default()
+        };
+
+        // No way data: constraints can't be checked, route is accepted
+        assert!(road_profile_violation(&route, &prefs).is_none());
+
+        route.road_profile = Some(RoadProfile {
+            paved_fraction: 0.95,
+            busy_road_fraction: 0.1,
+            smooth_fraction: 0.9,
+            steps_count: 0,
+            max_incline_pct: None,
+        });
+        assert!(road_profile_violation(&route, &prefs).is_none());
+
+        route.road_profile = Some(RoadProfile {
+            paved_fraction: 0.6,
+            busy_road_fraction: 0.1,
+            smooth_fraction: 0.9,
+            steps_count: 0,
+            max_incline_pct: None,
+        });
+        assert!(road_profile_violation(&route, &prefs).is_some());
+
+        route.road_profile = Some(RoadProfile {
+            paved_fraction: 0.95,
+            busy_road_fraction: 0.5,
+            smooth_fraction: 0.9,
+            steps_count: 0,
+            max_incline_pct: None,
+        });
+        assert!(road_profile_violation(&route, &prefs).is_some());
+    }
+
+    #[test]
+    fn test_step_free_violation() {
+        let prefs = RoutePreferences {
+            step_free: true,
+            ..Default::default()
+        };
+        let profile = RoadProfile {
+            paved_fraction: 1.0,
+            busy_road_fraction: 0.0,
+            smooth_fraction: 0.95,
+            steps_count: 0,
+            max_incline_pct: Some(4.0),
+        };
+        let route = Route::new(5.0, 60, vec![], vec![]).with_road_profile(Some(profile));
+        assert_eq!(route.step_free, Some(true));
+        assert!(road_profile_violation(&route, &prefs).is_none());
+
+        let stairs = Route::new(5.0, 60, vec![], vec![]).with_road_profile(Some(RoadProfile {
+            steps_count: 2,
+            ..profile
+        }));
+        assert_eq!(stairs.step_free, Some(false));
+        assert!(road_profile_violation(&stairs, &prefs).is_some());
+
+        let cobbles = Route::new(5.0, 60, vec![], vec![]).with_road_profile(Some(RoadProfile {
+            smooth_fraction: 0.5,
+            ..profile
+        }));
+        assert!(road_profile_violation(&cobbles, &prefs).is_some());
+    }
+
+    #[test]
+    fn test_trail_surface_violation() {
+        let prefs = RoutePreferences {
+            surface: SurfacePreference::Trail,
+            ..Default::default()
+        };
+        let profile = RoadProfile {
+            paved_fraction: 0.3,
+            busy_road_fraction: 0.0,
+            smooth_fraction: 0.3,
+            steps_count: 0,
+            max_incline_pct: None,
+        };
+        let trail = Route::new(5.0, 60, vec![], vec![]).with_road_profile(Some(profile));
+        assert!(road_profile_violation(&trail, &prefs).is_none());
+        let split = trail.surface_split.unwrap();
+        assert!((split.unpaved_km - 3.5).abs() < 1e-6);
+
+        let tarmac = Route::new(5.0, 60, vec![], vec![]).with_road_profile(Some(RoadProfile {
+            paved_fraction: 0.9,
+            ..profile
+        }));
+        assert!(road_profile_violation(&tarmac, &prefs).is_some());
+    }
+}
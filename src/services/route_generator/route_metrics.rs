@@ -1,6 +1,7 @@
 use super::geometry::{
-    convex_hull, min_segment_distance, path_length, segment_length_m, shoelace_area,
+    convex_hull, min_segment_distance, path_length, segment_length_m, shoelace_area, unwrap_lngs,
 };
+use crate::constants::METERS_PER_DEGREE;
 use crate::models::{Coordinates, PoiCategory, Route};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -139,8 +140,12 @@ fn compute_path_overlap(path: &[Coordinates], threshold_m: f64) -> f32 {
         return 0.0;
     }
 
+    // Continuous longitudes, so segments crossing the antimeridian span one
+    // grid column range instead of the whole globe
+    let path = &unwrap_lngs(path);
+
     // Convert threshold from meters to approximate degrees
-    let threshold_deg = threshold_m / 111_000.0;
+    let threshold_deg = threshold_m / METERS_PER_DEGREE;
 
     // Build spatial index: grid cells -> list of segment indices
     let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
@@ -193,7 +198,7 @@ fn compute_path_overlap(path: &[Coordinates], threshold_m: f64) -> f32 {
                         let dist = min_segment_distance(p1, p2, q1, q2);
 
                         // Convert distance to meters (approximate)
-                        let dist_m = dist * 111_000.0;
+                        let dist_m = dist * METERS_PER_DEGREE;
                         if dist_m < threshold_m {
                             is_overlapping = true;
                             break 'outer;
@@ -343,17 +348,7 @@ mod tests {
         pois: Vec<RoutePoi>,
         snapped: Vec<SnappedPoi>,
     ) -> Route {
-        Route {
-            id: uuid::Uuid::new_v4(),
-            distance_km: 5.0,
-            estimated_duration_minutes: 75,
-            elevation_gain_m: None,
-            path,
-            pois,
-            snapped_pois: snapped,
-            score: 0.0,
-            metrics: None,
-        }
+        Route::new(5.0, 75, path, pois).with_snapped_pois(snapped)
     }
 
     fn make_poi(name: &str, category: PoiCategory, popularity: f32) -> Poi {
@@ -443,6 +438,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_shape_metrics_across_antimeridian() {
+        // A circle around 180° (Fiji), with longitudes wrapped into range
+        let wrapped: Vec<Coordinates> = make_circle_path(-17.0, 0.0, 0.01, 100)
+            .iter()
+            .map(|p| make_coord(p.lat, crate::models::coordinates::wrap_lng(p.lng + 180.0)))
+            .collect();
+        assert!(wrapped.iter().any(|p| p.lng < 0.0) && wrapped.iter().any(|p| p.lng > 0.0));
+
+        assert!(compute_circularity(&wrapped) > 0.9);
+        assert!(compute_convexity(&wrapped) > 0.9);
+        assert!(compute_path_overlap(&wrapped, 25.0) < 0.05);
+    }
+
     #[test]
     fn test_poi_density() {
         assert_eq!(compute_poi_density(10, 5.0), 2.0);
@@ -1,10 +1,18 @@
 use crate::config::RouteGeneratorConfig;
+use crate::constants::{
+    ELEVATION_FLAT_SCORE_PENALTY, ELEVATION_HILLY_GAIN_M_PER_KM,
+    ELEVATION_OVER_LIMIT_SCORE_PENALTY, ENV_EXPOSURE_SCORE_PENALTY,
+};
 use crate::error::Result;
-use crate::models::{Poi, Route, RoutePoi, RoutePreferences};
+use crate::models::{Poi, QualityTier, Route, RoutePoi, RoutePreferences, TransportMode};
+use crate::services::elevation::{self, ElevationProvider};
+use crate::services::environment::EnvironmentalLayer;
 use crate::services::mapbox::DirectionsResponse;
 use crate::services::snapping_service::SnappingService;
 use std::collections::HashSet;
+use std::sync::Arc;
 
+use super::metrics_explanation::MetricsExplained;
 use super::route_metrics::RouteMetrics;
 
 /// Handles route quality scoring and route object construction
@@ -12,6 +20,8 @@ pub struct RouteScorer {
     snapping_service: SnappingService,
     snap_radius_m: f64,
     config: RouteGeneratorConfig,
+    environmental_layer: Option<Arc<dyn EnvironmentalLayer>>,
+    elevation_provider: Option<Arc<dyn ElevationProvider>>,
 }
 
 impl RouteScorer {
@@ -24,14 +34,25 @@ impl RouteScorer {
             snapping_service,
             snap_radius_m,
             config,
+            environmental_layer: None,
+            elevation_provider: None,
         }
     }
 
+    pub fn set_environmental_layer(&mut self, layer: Arc<dyn EnvironmentalLayer>) {
+        self.environmental_layer = Some(layer);
+    }
+
+    pub fn set_elevation_provider(&mut self, provider: Arc<dyn ElevationProvider>) {
+        self.elevation_provider = Some(provider);
+    }
+
     /// Build Route object from directions response and selected POIs
     pub async fn build_route(
         &self,
         directions: DirectionsResponse,
         pois: Vec<Poi>,
+        mode: &TransportMode,
         preferences: &RoutePreferences,
         area_poi_count: usize,
     ) -> Result<Route> {
@@ -57,6 +78,7 @@ impl RouteScorer {
                 &route.pois,
                 self.snap_radius_m,
                 preferences.poi_categories.as_deref(),
+                preferences.excluded_poi_categories.as_deref(),
             )
             .await
         {
@@ -75,11 +97,47 @@ impl RouteScorer {
             }
         }
 
+        if mode.is_cycling() || preferences.needs_road_profile() {
+            match self.snapping_service.road_profile(&route.path).await {
+                Ok(profile) => route = route.with_road_profile(profile),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to estimate road profile: {}", e);
+                }
+            }
+        }
+
+        if route.distance_km >= self.config.amenities_min_distance_km {
+            match self
+                .snapping_service
+                .find_amenities(&route.path, self.snap_radius_m)
+                .await
+            {
+                Ok(amenities) => route.amenities = amenities,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to find amenities: {}", e);
+                }
+            }
+        }
+
+        if let Some(ref layer) = self.environmental_layer {
+            route.environmental_exposure = layer.exposure_along(&route.path);
+        }
+
+        if let Some(ref provider) = self.elevation_provider {
+            match elevation::elevation_gain_along(provider.as_ref(), &route.path).await {
+                Ok(gain) => route.elevation_gain_m = gain,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to look up elevation");
+                }
+            }
+        }
+
         let metrics = RouteMetrics::compute_with_threshold(
             &route,
             area_poi_count,
             self.config.metrics_overlap_threshold_m,
         );
+        route.metrics_explained = Some(MetricsExplained::new(&route, &metrics));
         route.metrics = Some(metrics);
 
         Ok(route)
@@ -88,27 +146,88 @@ impl RouteScorer {
     /// Calculate route quality score (0-10)
     /// V1: distance accuracy, POI count, POI quality, category diversity
     /// V2: adds route shape (circularity + convexity) and path diversity (1 - overlap)
+    /// Both subtract an exposure penalty when the user asks to minimize exposure,
+    /// and climbing penalties for `prefer_flat` and `max_elevation_gain_m`.
     pub fn calculate_route_score(
         &self,
         route: &Route,
         target_distance_km: f64,
         preferences: &RoutePreferences,
     ) -> f32 {
-        if self.config.scoring_version >= 2 {
+        let score = if self.config.scoring_version >= 2 {
             self.calculate_route_score_v2(route, target_distance_km, preferences)
         } else {
             self.calculate_route_score_v1(route, target_distance_km, preferences)
+        };
+        (score
+            - Self::exposure_penalty(route, preferences)
+            - Self::elevation_penalty(route, preferences))
+        .clamp(0.0, 10.0)
+    }
+
+    /// Score penalty for climbing (0 unless requested and measured)
+    fn elevation_penalty(route: &Route, preferences: &RoutePreferences) -> f32 {
+        let Some(gain_m) = route.elevation_gain_m else {
+            return 0.0;
+        };
+        let mut penalty = 0.0;
+        if preferences.prefer_flat && route.distance_km > 0.0 {
+            let gain_per_km = gain_m / route.distance_km as f32;
+            penalty += ELEVATION_FLAT_SCORE_PENALTY
+                * (gain_per_km / ELEVATION_HILLY_GAIN_M_PER_KM).min(1.0);
+        }
+        if let Some(max_gain_m) = preferences.max_elevation_gain_m {
+            if gain_m > max_gain_m {
+                let excess = (gain_m - max_gain_m) / max_gain_m.max(1.0);
+                penalty += ELEVATION_OVER_LIMIT_SCORE_PENALTY * excess.min(1.0);
+            }
+        }
+        penalty
+    }
+
+    /// Score penalty for environmental exposure (0 unless requested and measured)
+    fn exposure_penalty(route: &Route, preferences: &RoutePreferences) -> f32 {
+        match route.environmental_exposure {
+            Some(exposure) if preferences.minimize_exposure => {
+                ENV_EXPOSURE_SCORE_PENALTY * exposure
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Classify a scored route into a quality tier using the configured thresholds.
+    /// Gold and silver also require limited overlap; gold additionally needs a round shape.
+    /// Routes without metrics can reach bronze at most.
+    pub fn classify_quality_tier(&self, route: &Route) -> Option<QualityTier> {
+        let c = &self.config;
+        let low_overlap = route
+            .metrics
+            .as_ref()
+            .is_some_and(|m| m.path_overlap_pct <= c.quality_tier_max_overlap);
+        let round = route
+            .metrics
+            .as_ref()
+            .is_some_and(|m| m.circularity >= c.quality_tier_gold_min_circularity);
+
+        if route.score >= c.quality_tier_gold_score && low_overlap && round {
+            Some(QualityTier::Gold)
+        } else if route.score >= c.quality_tier_silver_score && low_overlap {
+            Some(QualityTier::Silver)
+        } else if route.score >= c.quality_tier_bronze_score {
+            Some(QualityTier::Bronze)
+        } else {
+            None
         }
     }
 
     /// Distance accuracy score: 1.0 for perfect match, 0.0 for 100%+ error
-    fn distance_accuracy(route: &Route, target_distance_km: f64) -> f32 {
+    pub(super) fn distance_accuracy(route: &Route, target_distance_km: f64) -> f32 {
         let error_ratio = (route.distance_km - target_distance_km).abs() / target_distance_km;
         (1.0 - error_ratio.min(1.0)) as f32
     }
 
     /// Average POI quality (0.0-1.0), or 0.0 if no POIs
-    fn avg_poi_quality(route: &Route, hidden_gems: bool) -> f32 {
+    pub(super) fn avg_poi_quality(route: &Route, hidden_gems: bool) -> f32 {
         if route.pois.is_empty() {
             return 0.0;
         }
@@ -126,6 +245,30 @@ impl RouteScorer {
         (unique.len() as f32 / 3.0).min(1.0)
     }
 
+    /// Green coverage score (0.0-1.0): share of waypoint and snapped POIs in green categories
+    fn green_coverage(route: &Route) -> f32 {
+        let categories: Vec<_> = route
+            .pois
+            .iter()
+            .map(|rp| &rp.poi.category)
+            .chain(route.snapped_pois.iter().map(|sp| &sp.poi.category))
+            .collect();
+        if categories.is_empty() {
+            return 0.0;
+        }
+        categories.iter().filter(|c| c.is_green()).count() as f32 / categories.len() as f32
+    }
+
+    /// Variety component: green coverage when the user prefers green routes,
+    /// category diversity otherwise
+    fn variety(route: &Route, preferences: &RoutePreferences) -> f32 {
+        if preferences.prefer_green {
+            Self::green_coverage(route)
+        } else {
+            Self::category_diversity(route)
+        }
+    }
+
     /// V1 scoring: original algorithm (0-10)
     fn calculate_route_score_v1(
         &self,
@@ -136,7 +279,7 @@ impl RouteScorer {
         let score = 3.0 * Self::distance_accuracy(route, target_distance_km)
             + (route.pois.len() as f32).min(3.0)
             + 2.0 * Self::avg_poi_quality(route, preferences.hidden_gems)
-            + 2.0 * Self::category_diversity(route);
+            + 2.0 * Self::variety(route, preferences);
 
         score.clamp(0.0, 10.0)
     }
@@ -155,7 +298,7 @@ impl RouteScorer {
         let mut score = 2.5 * Self::distance_accuracy(route, target_distance_km)
             + 2.0 * poi_count_normalized
             + 1.5 * Self::avg_poi_quality(route, preferences.hidden_gems)
-            + Self::category_diversity(route);
+            + Self::variety(route, preferences);
 
         if let Some(ref metrics) = route.metrics {
             let shape_score = (metrics.circularity + metrics.convexity) / 2.0;
@@ -359,4 +502,130 @@ mod tests {
         // 2.5 + 2.0*1.0 (shape) + 1.0*0.5 (path diversity) = 5.0
         assert!((score - 5.0).abs() < 0.01, "score={score}");
     }
+
+    #[tokio::test]
+    async fn quality_tier_thresholds() {
+        let scorer = scorer_v1();
+        let mut route = make_route(5.0, vec![]);
+        route.metrics = Some(RouteMetrics {
+            circularity: 0.5,
+            convexity: 0.9,
+            path_overlap_pct: 0.1,
+            poi_density_per_km: 0.0,
+            category_entropy: 0.0,
+            landmark_coverage: 0.0,
+            poi_density_context: super::super::route_metrics::PoiDensityContext::Dense,
+        });
+
+        route.score = 8.5;
+        assert_eq!(
+            scorer.classify_quality_tier(&route),
+            Some(QualityTier::Gold)
+        );
+        route.score = 7.0;
+        assert_eq!(
+            scorer.classify_quality_tier(&route),
+            Some(QualityTier::Silver)
+        );
+        route.score = 5.5;
+        assert_eq!(
+            scorer.classify_quality_tier(&route),
+            Some(QualityTier::Bronze)
+        );
+        route.score = 4.0;
+        assert_eq!(scorer.classify_quality_tier(&route), None);
+    }
+
+    #[tokio::test]
+    async fn quality_tier_requires_metrics_for_gold_and_silver() {
+        let scorer = scorer_v1();
+        let mut route = make_route(5.0, vec![]);
+        route.score = 9.5;
+        assert_eq!(
+            scorer.classify_quality_tier(&route),
+            Some(QualityTier::Bronze)
+        );
+
+        route.metrics = Some(RouteMetrics {
+            circularity: 0.9,
+            convexity: 0.9,
+            path_overlap_pct: 0.6, // Heavy backtracking caps the tier
+            poi_density_per_km: 0.0,
+            category_entropy: 0.0,
+            landmark_coverage: 0.0,
+            poi_density_context: super::super::route_metrics::PoiDensityContext::Dense,
+        });
+        assert_eq!(
+            scorer.classify_quality_tier(&route),
+            Some(QualityTier::Bronze)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exposure_penalty_only_when_requested() {
+        let scorer = scorer_v1();
+        let mut route = make_route(
+            5.0,
+            vec![
+                make_route_poi("A", PoiCategory::Park, 80.0, 1),
+                make_route_poi("B", PoiCategory::Museum, 80.0, 2),
+            ],
+        );
+        route.environmental_exposure = Some(0.5);
+
+        let prefs = RoutePreferences::default();
+        let baseline = scorer.calculate_route_score(&route, 5.0, &prefs);
+
+        let minimize = RoutePreferences {
+            minimize_exposure: true,
+            ..Default::default()
+        };
+        let penalized = scorer.calculate_route_score(&route, 5.0, &minimize);
+        assert!((baseline - penalized - ENV_EXPOSURE_SCORE_PENALTY * 0.5).abs() < 1e-5);
+
+        // No layer coverage: nothing to penalize
+        route.environmental_exposure = None;
+        assert_eq!(
+            scorer.calculate_route_score(&route, 5.0, &minimize),
+            baseline
+        );
+    }
+
+    #[tokio::test]
+    async fn test_elevation_penalties() {
+        let scorer = scorer_v1();
+        let mut route = make_route(
+            5.0,
+            vec![
+                make_route_poi("A", PoiCategory::Park, 80.0, 1),
+                make_route_poi("B", PoiCategory::Museum, 80.0, 2),
+            ],
+        );
+        route.elevation_gain_m = Some(150.0);
+        let baseline = scorer.calculate_route_score(&route, 5.0, &RoutePreferences::default());
+
+        // 30 m/km is past the hilly threshold: full penalty
+        let flat = RoutePreferences {
+            prefer_flat: true,
+            ..Default::default()
+        };
+        let penalized = scorer.calculate_route_score(&route, 5.0, &flat);
+        assert!((baseline - penalized - ELEVATION_FLAT_SCORE_PENALTY).abs() < 1e-5);
+
+        // 50% over the limit: half the over-limit penalty; under it: none
+        let capped = |max| RoutePreferences {
+            max_elevation_gain_m: Some(max),
+            ..Default::default()
+        };
+        let over = scorer.calculate_route_score(&route, 5.0, &capped(100.0));
+        assert!((baseline - over - ELEVATION_OVER_LIMIT_SCORE_PENALTY * 0.5).abs() < 1e-5);
+        assert_eq!(
+            scorer.calculate_route_score(&route, 5.0, &capped(200.0)),
+            baseline
+        );
+
+        // No elevation data: nothing to penalize
+        route.elevation_gain_m = None;
+        assert_eq!(scorer.calculate_route_score(&route, 5.0, &flat), baseline);
+    }
 }
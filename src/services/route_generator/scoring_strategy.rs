@@ -1,23 +1,32 @@
-use super::geometry::{angle_from_start, convex_hull_area};
+use super::candidates::CandidateIndex;
+use super::geometry::convex_hull_area;
 use crate::config::RouteGeneratorConfig;
 use crate::constants::*;
-use crate::models::{Coordinates, Poi, RoutePreferences};
+use crate::models::{Coordinates, RoutePreferences};
+use rand::rngs::SmallRng;
+use rand::Rng;
 
 /// Context passed to scoring strategies
 pub struct ScoringContext<'a> {
-    pub start: &'a Coordinates,
     pub target_waypoint_distance: f64,
     pub target_distance_km: f64,
-    pub attempt_seed: usize,
+    /// Source of the per-candidate variation bonus, seeded per attempt
+    pub rng: &'a mut SmallRng,
     pub preferences: &'a RoutePreferences,
-    pub already_selected: &'a [Poi],
+    /// Indices into the candidate set, in selection order
+    pub already_selected: &'a [usize],
 }
 
 /// Trait for POI scoring strategies
 pub trait PoiScoringStrategy: Send + Sync {
-    /// Score POIs for loop route selection
-    /// Returns a vector of (score, POI) tuples
-    fn score_pois<'a>(&self, pois: &'a [Poi], context: &ScoringContext) -> Vec<(f32, &'a Poi)>;
+    /// Score the `remaining` candidates (indices into `candidates`) for loop
+    /// route selection. Returns (score, candidate index) pairs.
+    fn score_pois(
+        &self,
+        candidates: &CandidateIndex,
+        remaining: &[usize],
+        context: &mut ScoringContext,
+    ) -> Vec<(f32, usize)>;
 }
 
 /// Calculate score based on distance from ideal waypoint distance.
@@ -45,11 +54,9 @@ fn calculate_distance_score(
     }
 }
 
-/// Calculate variation offset to ensure different POIs are selected on each attempt
-fn calculate_variation_offset(poi_index: usize, attempt_seed: usize) -> f32 {
-    ((poi_index * VARIATION_MULTIPLIER + attempt_seed * VARIATION_OFFSET_BASE) % VARIATION_MOD)
-        as f32
-        * VARIATION_SCORE_FACTOR
+/// Random score bonus so different POIs are selected on each attempt
+fn variation_bonus(rng: &mut SmallRng) -> f32 {
+    rng.gen_range(0.0..VARIATION_MAX_BONUS)
 }
 
 /// Compute adaptive max distance filter based on target distance and config.
@@ -75,16 +82,21 @@ impl SimpleStrategy {
 }
 
 impl PoiScoringStrategy for SimpleStrategy {
-    fn score_pois<'a>(&self, pois: &'a [Poi], context: &ScoringContext) -> Vec<(f32, &'a Poi)> {
+    fn score_pois(
+        &self,
+        candidates: &CandidateIndex,
+        remaining: &[usize],
+        context: &mut ScoringContext,
+    ) -> Vec<(f32, usize)> {
         let max_dist = max_reasonable_distance(
             context.target_distance_km,
             self.config.max_poi_distance_multiplier,
         );
 
-        pois.iter()
-            .enumerate()
-            .filter_map(|(idx, poi)| {
-                let dist = context.start.distance_to(&poi.coordinates);
+        remaining
+            .iter()
+            .filter_map(|&candidate| {
+                let dist = candidates.distances_km[candidate];
 
                 if dist < self.config.min_poi_distance_km || dist > max_dist {
                     return None;
@@ -98,9 +110,9 @@ impl PoiScoringStrategy for SimpleStrategy {
                     12.0,
                     3.0,
                 );
-                let variation = calculate_variation_offset(idx, context.attempt_seed);
+                let variation = variation_bonus(context.rng);
 
-                Some((distance_score + variation, poi))
+                Some((distance_score + variation, candidate))
             })
             .collect()
     }
@@ -138,15 +150,19 @@ impl AdvancedStrategy {
     }
 
     /// Calculate penalty for POIs that cluster together
-    fn cluster_penalty(candidate: &Poi, selected: &[Poi], min_separation_km: f64) -> f32 {
+    fn cluster_penalty(
+        candidate: &Coordinates,
+        selected: &[Coordinates],
+        min_separation_km: f64,
+    ) -> f32 {
         if selected.is_empty() {
             return 0.0;
         }
 
         let mut max_penalty: f32 = 0.0;
 
-        for poi in selected {
-            let dist = candidate.coordinates.distance_to(&poi.coordinates);
+        for point in selected {
+            let dist = candidate.distance_to(point);
             if dist < min_separation_km {
                 let penalty = (1.0 - dist / min_separation_km) * 100.0;
                 max_penalty = max_penalty.max(penalty as f32);
@@ -155,35 +171,50 @@ impl AdvancedStrategy {
 
         max_penalty
     }
+}
 
-    /// Loop shape predictor: estimate convex hull area of [start, selected..., candidate]
-    /// Returns a 0-1 score where higher = better loop coverage
-    fn loop_shape_score(start: &Coordinates, candidate: &Poi, already_selected: &[Poi]) -> f32 {
-        if already_selected.is_empty() {
-            return 0.5; // Neutral for first selection
-        }
+/// Loop shape predictor: how much a candidate grows the convex hull of
+/// [start, selected...]. The hull without the candidate is computed once per
+/// selection step rather than once per candidate.
+struct LoopShape {
+    /// start + selected, with one spare slot for the candidate
+    points: Vec<Coordinates>,
+    area_without: f64,
+}
 
-        // Build point set: start + selected + candidate
-        let mut points: Vec<Coordinates> = Vec::with_capacity(already_selected.len() + 2);
+impl LoopShape {
+    fn new(start: &Coordinates, selected: &[Coordinates]) -> Self {
+        let mut points = Vec::with_capacity(selected.len() + 2);
         points.push(*start);
-        for poi in already_selected {
-            points.push(poi.coordinates);
+        points.extend_from_slice(selected);
+        let area_without = if selected.is_empty() {
+            0.0
+        } else {
+            convex_hull_area(&points)
+        };
+        LoopShape {
+            points,
+            area_without,
         }
-        points.push(candidate.coordinates);
+    }
 
-        let area_with = convex_hull_area(&points);
+    /// 0-1 score where higher = better loop coverage
+    fn score(&mut self, candidate: &Coordinates) -> f32 {
+        if self.points.len() == 1 {
+            return 0.5; // Neutral for first selection
+        }
 
-        // Compare to area without candidate
-        points.pop();
-        let area_without = convex_hull_area(&points);
+        self.points.push(*candidate);
+        let area_with = convex_hull_area(&self.points);
+        self.points.pop();
 
         // Score based on how much the candidate increases the hull area
-        if area_without < 1e-15 {
+        if self.area_without < 1e-15 {
             // All points collinear without candidate - any area increase is good
             return if area_with > 1e-15 { 1.0 } else { 0.0 };
         }
 
-        let area_ratio = area_with / area_without;
+        let area_ratio = area_with / self.area_without;
         // Ratio > 1 means candidate expands the hull (good for round loops)
         // Normalize: ratio of 1.0 = no expansion (0.0), ratio of 2.0+ = max (1.0)
         ((area_ratio - 1.0) as f32).clamp(0.0, 1.0)
@@ -191,7 +222,12 @@ impl AdvancedStrategy {
 }
 
 impl PoiScoringStrategy for AdvancedStrategy {
-    fn score_pois<'a>(&self, pois: &'a [Poi], context: &ScoringContext) -> Vec<(f32, &'a Poi)> {
+    fn score_pois(
+        &self,
+        candidates: &CandidateIndex,
+        remaining: &[usize],
+        context: &mut ScoringContext,
+    ) -> Vec<(f32, usize)> {
         let max_dist = max_reasonable_distance(
             context.target_distance_km,
             self.config.max_poi_distance_multiplier,
@@ -200,13 +236,25 @@ impl PoiScoringStrategy for AdvancedStrategy {
         let selected_angles: Vec<f64> = context
             .already_selected
             .iter()
-            .map(|poi| angle_from_start(context.start, &poi.coordinates))
+            .map(|&i| candidates.angles[i])
+            .collect();
+        let selected_points: Vec<Coordinates> = context
+            .already_selected
+            .iter()
+            .map(|&i| *candidates.coordinates(i))
             .collect();
+        let mut loop_shape = LoopShape::new(&candidates.start, &selected_points);
+        let angular_half = self.config.poi_score_weight_angular / 2.0;
+
+        let min_separation_km = context
+            .preferences
+            .poi_min_separation_km
+            .unwrap_or(self.config.poi_min_separation_km);
 
-        pois.iter()
-            .enumerate()
-            .filter_map(|(idx, poi)| {
-                let dist = context.start.distance_to(&poi.coordinates);
+        remaining
+            .iter()
+            .filter_map(|&candidate| {
+                let dist = candidates.distances_km[candidate];
 
                 if dist < self.config.min_poi_distance_km || dist > max_dist {
                     return None;
@@ -221,21 +269,16 @@ impl PoiScoringStrategy for AdvancedStrategy {
                     7.0,
                 );
 
-                let quality_score = poi.quality_score(context.preferences.hidden_gems) / 100.0;
+                let quality_score = candidates.quality[candidate];
 
-                let angle = angle_from_start(context.start, &poi.coordinates);
-                let angular_score = Self::angular_diversity_score(angle, &selected_angles);
-                let shape_score =
-                    Self::loop_shape_score(context.start, poi, context.already_selected);
-                let angular_half = self.config.poi_score_weight_angular / 2.0;
+                let angular_score =
+                    Self::angular_diversity_score(candidates.angles[candidate], &selected_angles);
+                let point = candidates.coordinates(candidate);
+                let shape_score = loop_shape.score(point);
 
-                let cluster_pen = Self::cluster_penalty(
-                    poi,
-                    context.already_selected,
-                    self.config.poi_min_separation_km,
-                );
+                let cluster_pen = Self::cluster_penalty(point, &selected_points, min_separation_km);
 
-                let variation = calculate_variation_offset(idx, context.attempt_seed);
+                let variation = variation_bonus(context.rng);
 
                 let score = dist_score * self.config.poi_score_weight_distance
                     + quality_score * self.config.poi_score_weight_quality
@@ -244,7 +287,7 @@ impl PoiScoringStrategy for AdvancedStrategy {
                     - cluster_pen * self.config.poi_score_weight_clustering
                     + variation * self.config.poi_score_weight_variation;
 
-                Some((score, poi))
+                Some((score, candidate))
             })
             .collect()
     }
@@ -253,7 +296,8 @@ impl PoiScoringStrategy for AdvancedStrategy {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::PoiCategory;
+    use crate::models::{Poi, PoiCategory};
+    use crate::services::route_generator::variation;
 
     #[test]
     fn test_simple_strategy_distance_scoring_short_route() {
@@ -345,11 +389,74 @@ mod tests {
             70.0,
         );
 
-        let selected = vec![poi1];
+        let selected = vec![poi1.coordinates];
         let min_separation = 0.3; // 300m
 
         // POI closer than min separation should get penalty
-        let penalty = AdvancedStrategy::cluster_penalty(&poi2, &selected, min_separation);
+        let penalty =
+            AdvancedStrategy::cluster_penalty(&poi2.coordinates, &selected, min_separation);
         assert!(penalty > 0.0);
     }
+
+    fn ring_candidates(n: usize) -> Vec<Poi> {
+        // POIs on rings 0.5-2.5 km around Paris centre
+        (0..n)
+            .map(|i| {
+                let angle = i as f64 * 2.399; // golden angle spread
+                let radius_deg = 0.005 + 0.02 * (i % 5) as f64 / 4.0;
+                Poi::new(
+                    format!("P{i}"),
+                    PoiCategory::Park,
+                    Coordinates::new(
+                        48.8566 + radius_deg * angle.sin(),
+                        2.3522 + radius_deg * angle.cos(),
+                    )
+                    .unwrap(),
+                    (i % 100) as f32,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_advanced_strategy_scores_remaining_candidates_only() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let pois = ring_candidates(40);
+        let preferences = RoutePreferences::default();
+        let candidates = CandidateIndex::new(&start, &pois, &preferences);
+        let strategy = AdvancedStrategy::new(RouteGeneratorConfig::default());
+
+        let selected = [3usize];
+        let remaining: Vec<usize> = (0..pois.len()).filter(|i| *i != 3).collect();
+        let mut rng = variation::seeded_rng(None, 0);
+        let mut context = ScoringContext {
+            target_waypoint_distance: 1.5,
+            target_distance_km: 5.0,
+            rng: &mut rng,
+            preferences: &preferences,
+            already_selected: &selected,
+        };
+
+        let scored = strategy.score_pois(&candidates, &remaining, &mut context);
+        assert!(!scored.is_empty());
+        assert!(scored.iter().all(|(_, i)| *i != 3 && *i < pois.len()));
+        assert!(scored.iter().all(|(score, _)| score.is_finite()));
+    }
+
+    #[test]
+    fn test_loop_shape_rewards_hull_expansion() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let selected = [
+            Coordinates::new(48.8666, 2.3522).unwrap(),
+            Coordinates::new(48.8566, 2.3722).unwrap(),
+        ];
+        let mut shape = LoopShape::new(&start, &selected);
+        // Opposite side of the start expands the hull; a point inside does not
+        let outside = shape.score(&Coordinates::new(48.8466, 2.3322).unwrap());
+        let inside = shape.score(&Coordinates::new(48.8586, 2.3552).unwrap());
+        assert!(outside > 0.9);
+        assert_eq!(inside, 0.0);
+
+        assert_eq!(LoopShape::new(&start, &[]).score(&selected[0]), 0.5);
+    }
 }
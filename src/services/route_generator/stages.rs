@@ -0,0 +1,367 @@
+//! Multi-day routes: a long target distance (a 60km hike over three days)
+//! split into daily stages that start and end at accommodation. Loops go
+//! out and back to one base; segments hop from stay to stay, visiting a few
+//! points of interest on the way.
+
+use super::RouteGenerator;
+use crate::constants::*;
+use crate::error::{AppError, Result};
+use crate::models::coordinates::lng_delta;
+use crate::models::{
+    Coordinates, Poi, PoiCategory, Route, RoutePoi, RoutePreferences, RouteStage, StageLayout,
+    StagedRoute, TransportMode,
+};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+impl RouteGenerator {
+    /// Split `total_distance_km` into `days` stages of roughly equal length,
+    /// laid out as daily loops from a base or as a point-to-point trek
+    pub async fn generate_staged_route(
+        &self,
+        start: Coordinates,
+        total_distance_km: f64,
+        days: u32,
+        layout: StageLayout,
+        mode: &TransportMode,
+        preferences: &RoutePreferences,
+    ) -> Result<StagedRoute> {
+        if !(1..=STAGED_ROUTE_MAX_DAYS).contains(&days) {
+            return Err(AppError::InvalidRequest(format!(
+                "days must be between 1 and {STAGED_ROUTE_MAX_DAYS}"
+            )));
+        }
+        if total_distance_km <= 0.0 {
+            return Err(AppError::InvalidRequest(
+                "Total distance must be positive".to_string(),
+            ));
+        }
+
+        let daily_km = total_distance_km / days as f64;
+        tracing::info!(
+            days,
+            ?layout,
+            "Generating staged route, {:.1}km per day",
+            daily_km
+        );
+        let stages = match layout {
+            StageLayout::Loops => {
+                self.daily_loops(start, daily_km, days, mode, preferences)
+                    .await?
+            }
+            StageLayout::Segments => {
+                self.daily_segments(start, daily_km, days, mode, preferences)
+                    .await?
+            }
+        };
+        Ok(StagedRoute::new(layout, stages))
+    }
+
+    /// One loop per day from the stay nearest the start (or the start
+    /// itself), each with its own variation seed so the days differ
+    async fn daily_loops(
+        &self,
+        start: Coordinates,
+        daily_km: f64,
+        days: u32,
+        mode: &TransportMode,
+        preferences: &RoutePreferences,
+    ) -> Result<Vec<RouteStage>> {
+        let base = self
+            .find_accommodation(&start, STAGE_BASE_SEARCH_RADIUS_KM)
+            .await?
+            .into_iter()
+            .min_by(|a, b| {
+                start
+                    .distance_to(&a.coordinates)
+                    .total_cmp(&start.distance_to(&b.coordinates))
+            });
+        let anchor = base.as_ref().map_or(start, |stay| stay.coordinates);
+        let base_seed = preferences.seed.unwrap_or(DEFAULT_VARIATION_SEED);
+
+        let mut stages = Vec::with_capacity(days as usize);
+        for day in 1..=days {
+            let day_preferences = RoutePreferences {
+                seed: Some(base_seed.wrapping_add(u64::from(day))),
+                ..preferences.clone()
+            };
+            let route = self
+                .generate_loop_route(
+                    anchor,
+                    daily_km,
+                    daily_km * STAGE_DISTANCE_TOLERANCE,
+                    mode,
+                    &day_preferences,
+                )
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| AppError::RouteGeneration(format!("No loop found for day {day}")))?;
+            stages.push(RouteStage {
+                day,
+                distance_km: route.distance_km,
+                accommodation: base.clone(),
+                route,
+            });
+        }
+        Ok(stages)
+    }
+
+    /// A trek: each day ends at a stay about a day's distance further on,
+    /// keeping roughly the same heading, via up to `STAGE_MAX_VIA_POIS`
+    /// points of interest near the way
+    async fn daily_segments(
+        &self,
+        start: Coordinates,
+        daily_km: f64,
+        days: u32,
+        mode: &TransportMode,
+        preferences: &RoutePreferences,
+    ) -> Result<Vec<RouteStage>> {
+        let target_straight_km = daily_km / STAGE_ROUTING_DETOUR_FACTOR;
+        let mut anchor = start;
+        let mut heading: Option<(f64, f64)> = None;
+        let mut used: HashSet<Uuid> = HashSet::new();
+
+        let mut stages = Vec::with_capacity(days as usize);
+        for day in 1..=days {
+            let candidates = self
+                .find_accommodation(
+                    &anchor,
+                    target_straight_km * (1.0 + STAGE_DISTANCE_TOLERANCE),
+                )
+                .await?;
+            let stay = choose_stay(&anchor, &candidates, target_straight_km, heading, &used)
+                .cloned()
+                .ok_or_else(|| {
+                    AppError::RouteGeneration(format!(
+                        "No accommodation about {daily_km:.1}km away for the end of day {day}"
+                    ))
+                })?;
+
+            let vias = self
+                .via_pois(&anchor, &stay.coordinates, daily_km, preferences)
+                .await?;
+            let mut waypoints = vec![anchor];
+            waypoints.extend(vias.iter().map(|poi| poi.coordinates));
+            waypoints.push(stay.coordinates);
+            let directions = self
+                .directions
+                .directions(&waypoints, mode, &preferences.costing, preferences.surface)
+                .await?;
+
+            let route_pois = vias
+                .into_iter()
+                .enumerate()
+                .map(|(i, poi)| {
+                    let along_km = anchor.distance_to(&poi.coordinates);
+                    RoutePoi::new(poi, i as u32 + 1, along_km)
+                })
+                .collect();
+            let route = Route::new(
+                directions.distance_km(),
+                directions.duration_minutes(),
+                directions.to_coordinates(),
+                route_pois,
+            )
+            .with_duration_estimates(mode, &self.config.speed_models());
+
+            heading = Some(direction(&anchor, &stay.coordinates));
+            anchor = stay.coordinates;
+            used.insert(stay.id);
+            stages.push(RouteStage {
+                day,
+                distance_km: route.distance_km,
+                accommodation: Some(stay),
+                route,
+            });
+        }
+        Ok(stages)
+    }
+
+    /// Stays within `radius_km`; none is an empty list, not an error
+    async fn find_accommodation(&self, center: &Coordinates, radius_km: f64) -> Result<Vec<Poi>> {
+        match self
+            .poi_service
+            .find_pois(
+                center,
+                radius_km,
+                Some(&[PoiCategory::Accommodation]),
+                STAGE_ACCOMMODATION_LIMIT,
+            )
+            .await
+        {
+            Err(AppError::NoPoisFound(_)) => Ok(Vec::new()),
+            result => result,
+        }
+    }
+
+    /// The most popular points of interest whose detour between `from` and
+    /// `to` fits the day's slack, in the order they're passed
+    async fn via_pois(
+        &self,
+        from: &Coordinates,
+        to: &Coordinates,
+        daily_km: f64,
+        preferences: &RoutePreferences,
+    ) -> Result<Vec<Poi>> {
+        let straight_km = from.distance_to(to);
+        let midpoint = Coordinates {
+            lat: (from.lat + to.lat) / 2.0,
+            lng: from.lng + lng_delta(from.lng, to.lng) / 2.0,
+        };
+        // Slack between the stay's routed estimate and the day's share
+        let slack_km = (daily_km - straight_km * STAGE_ROUTING_DETOUR_FACTOR)
+            .max(daily_km * STAGE_DISTANCE_TOLERANCE);
+        let pois = match self
+            .poi_service
+            .find_pois(
+                &midpoint,
+                straight_km / 2.0 + slack_km / 2.0,
+                preferences.poi_categories.as_deref(),
+                STAGE_VIA_CANDIDATE_LIMIT,
+            )
+            .await
+        {
+            Err(AppError::NoPoisFound(_)) => return Ok(Vec::new()),
+            result => result?,
+        };
+        Ok(select_vias(
+            from,
+            to,
+            pois,
+            slack_km,
+            preferences.hidden_gems,
+        ))
+    }
+}
+
+/// Unit vector from `from` to `to` in (east, north), on a local flat
+/// approximation
+fn direction(from: &Coordinates, to: &Coordinates) -> (f64, f64) {
+    let east = lng_delta(from.lng, to.lng) * from.lat.to_radians().cos();
+    let north = to.lat - from.lat;
+    let length = east.hypot(north);
+    if length == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (east / length, north / length)
+    }
+}
+
+/// The unused stay whose straight-line distance best matches a day's
+/// walk, penalizing ones that double back on the previous day's heading
+fn choose_stay<'a>(
+    anchor: &Coordinates,
+    candidates: &'a [Poi],
+    target_straight_km: f64,
+    heading: Option<(f64, f64)>,
+    used: &HashSet<Uuid>,
+) -> Option<&'a Poi> {
+    let min_km = target_straight_km * (1.0 - STAGE_DISTANCE_TOLERANCE * 2.0);
+    let cost = |stay: &Poi| {
+        let distance_km = anchor.distance_to(&stay.coordinates);
+        let mismatch = (distance_km - target_straight_km).abs() / target_straight_km;
+        let turn = heading.map_or(0.0, |(east, north)| {
+            let (e, n) = direction(anchor, &stay.coordinates);
+            // 0 straight on, 1 straight back
+            (1.0 - (east * e + north * n)) / 2.0
+        });
+        mismatch + STAGE_HEADING_WEIGHT * turn
+    };
+    candidates
+        .iter()
+        .filter(|stay| !used.contains(&stay.id))
+        .filter(|stay| anchor.distance_to(&stay.coordinates) >= min_km)
+        .min_by(|a, b| cost(a).total_cmp(&cost(b)))
+}
+
+/// Up to `STAGE_MAX_VIA_POIS` points of interest (not amenities or other
+/// stays) whose detour fits `slack_km`, best first, then ordered from
+/// `from` to `to`
+fn select_vias(
+    from: &Coordinates,
+    to: &Coordinates,
+    pois: Vec<Poi>,
+    slack_km: f64,
+    hidden_gems: bool,
+) -> Vec<Poi> {
+    let straight_km = from.distance_to(to);
+    let mut vias: Vec<Poi> = pois
+        .into_iter()
+        .filter(|poi| poi.category.is_point_of_interest())
+        .filter(|poi| {
+            let detour =
+                from.distance_to(&poi.coordinates) + poi.coordinates.distance_to(to) - straight_km;
+            detour * STAGE_ROUTING_DETOUR_FACTOR <= slack_km
+        })
+        .collect();
+    vias.sort_by(|a, b| {
+        b.quality_score(hidden_gems)
+            .total_cmp(&a.quality_score(hidden_gems))
+    });
+    vias.truncate(STAGE_MAX_VIA_POIS);
+    vias.sort_by(|a, b| {
+        from.distance_to(&a.coordinates)
+            .total_cmp(&from.distance_to(&b.coordinates))
+    });
+    vias
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poi(name: &str, category: PoiCategory, lat: f64, lng: f64, popularity: f32) -> Poi {
+        Poi::new(
+            name.to_string(),
+            category,
+            Coordinates::new(lat, lng).unwrap(),
+            popularity,
+        )
+    }
+
+    #[test]
+    fn test_choose_stay_keeps_heading_and_skips_used() {
+        let anchor = Coordinates::new(45.0, 6.0).unwrap();
+        // ~15km north and ~15km south
+        let north = poi("Refuge nord", PoiCategory::Accommodation, 45.135, 6.0, 50.0);
+        let south = poi("Refuge sud", PoiCategory::Accommodation, 44.866, 6.0, 50.0);
+        let near = poi("Gîte", PoiCategory::Accommodation, 45.01, 6.0, 50.0);
+        let candidates = vec![south.clone(), north.clone(), near];
+
+        // Walking north: the south stay would mean turning back
+        let heading = Some((0.0, 1.0));
+        let chosen = choose_stay(&anchor, &candidates, 15.0, heading, &HashSet::new()).unwrap();
+        assert_eq!(chosen.name, "Refuge nord");
+
+        // Already slept there: only the stay behind remains (the one
+        // barely out of town is too close for a day)
+        let used = HashSet::from([north.id]);
+        let chosen = choose_stay(&anchor, &candidates, 15.0, heading, &used).unwrap();
+        assert_eq!(chosen.name, "Refuge sud");
+
+        let used = HashSet::from([north.id, south.id]);
+        assert!(choose_stay(&anchor, &candidates, 15.0, heading, &used).is_none());
+    }
+
+    #[test]
+    fn test_select_vias_orders_along_the_way() {
+        let from = Coordinates::new(45.0, 6.0).unwrap();
+        let to = Coordinates::new(45.1, 6.0).unwrap();
+        let pois = vec![
+            poi("Cascade", PoiCategory::Waterfall, 45.07, 6.001, 70.0),
+            poi("Chapelle", PoiCategory::Church, 45.03, 6.001, 90.0),
+            poi("Lac lointain", PoiCategory::Park, 45.05, 6.3, 100.0),
+            poi("Fontaine", PoiCategory::DrinkingWater, 45.05, 6.0, 100.0),
+            poi("Hôtel", PoiCategory::Accommodation, 45.05, 6.0, 100.0),
+            poi("Belvédère", PoiCategory::Viewpoint, 45.05, 6.002, 10.0),
+        ];
+
+        let vias = select_vias(&from, &to, pois, 2.0, false);
+        let names: Vec<&str> = vias.iter().map(|p| p.name.as_str()).collect();
+        // Off-course, amenity and stay excluded; the two most popular kept,
+        // in walking order
+        assert_eq!(names, vec!["Chapelle", "Cascade"]);
+    }
+}
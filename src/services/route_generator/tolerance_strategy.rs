@@ -1,26 +1,56 @@
+use super::candidates::CandidateIndex;
+use super::failure::{self, FailureTally};
+use super::leg_repair;
+use super::pareto;
+use super::progress::{GenerationEvent, ProgressSink};
+use super::road_constraints;
 use super::route_scoring::RouteScorer;
+use super::tsp;
+use super::variation;
 use super::waypoint_selection::WaypointSelector;
 use crate::config::RouteGeneratorConfig;
 use crate::constants::*;
-use crate::error::{AppError, Result};
-use crate::models::{Coordinates, Poi, Route, RoutePreferences, TransportMode};
-use crate::services::mapbox::MapboxClient;
+use crate::error::{AppError, GenerationFailure, Result};
+use crate::models::{
+    Coordinates, CostingOptions, Departure, Poi, QualityTier, Route, RoutePreferences, SpeedModel,
+    SurfacePreference, TransportMode,
+};
+use crate::services::directions::DirectionsProvider;
+use crate::services::elevation::ElevationProvider;
+use crate::services::environment::EnvironmentalLayer;
+use crate::services::mapbox::DirectionsResponse;
+use crate::services::solar;
+use std::sync::Arc;
+
+/// Generation attempts per tolerance level: one per requested alternative,
+/// plus an extra budget when a Pareto route set is requested
+pub fn attempts_per_level(preferences: &RoutePreferences) -> usize {
+    let alternatives = preferences
+        .max_alternatives
+        .clamp(MIN_ALTERNATIVES_FOR_SUCCESS, MAX_ALTERNATIVES_CLAMP)
+        as usize;
+    if preferences.pareto {
+        alternatives + PARETO_EXTRA_ATTEMPTS
+    } else {
+        alternatives
+    }
+}
 
 /// Handles adaptive tolerance and retry strategies for route generation
 pub struct ToleranceStrategy {
     config: RouteGeneratorConfig,
-    mapbox_client: MapboxClient,
+    directions: Arc<dyn DirectionsProvider>,
     waypoint_selector: WaypointSelector,
     route_scorer: RouteScorer,
 }
 
 /// Parameters for loop route generation attempt
 pub struct LoopRouteParams<'a> {
-    pub start: &'a Coordinates,
     pub target_distance_km: f64,
     pub distance_tolerance: f64,
     pub mode: &'a TransportMode,
-    pub candidate_pois: &'a [Poi],
+    /// Candidate POIs and the start point, shared by all levels and attempts
+    pub candidates: &'a CandidateIndex<'a>,
     pub attempt_seed: usize,
     pub preferences: &'a RoutePreferences,
 }
@@ -28,13 +58,13 @@ pub struct LoopRouteParams<'a> {
 impl ToleranceStrategy {
     pub fn new(
         config: RouteGeneratorConfig,
-        mapbox_client: MapboxClient,
+        directions: Arc<dyn DirectionsProvider>,
         waypoint_selector: WaypointSelector,
         route_scorer: RouteScorer,
     ) -> Self {
         Self {
             config,
-            mapbox_client,
+            directions,
             waypoint_selector,
             route_scorer,
         }
@@ -51,43 +81,70 @@ impl ToleranceStrategy {
             .calculate_route_score(route, target_distance_km, preferences)
     }
 
-    /// Try to generate routes with a specific tolerance level
+    pub fn set_environmental_layer(&mut self, layer: Arc<dyn EnvironmentalLayer>) {
+        self.route_scorer.set_environmental_layer(layer);
+    }
+
+    pub fn set_elevation_provider(&mut self, provider: Arc<dyn ElevationProvider>) {
+        self.route_scorer.set_elevation_provider(provider);
+    }
+
+    pub fn set_directions_provider(&mut self, provider: Arc<dyn DirectionsProvider>) {
+        self.directions = provider;
+    }
+
+    /// Classify a scored route (public delegation for geometric fallback paths).
+    pub fn quality_tier(&self, route: &Route) -> Option<QualityTier> {
+        self.route_scorer.classify_quality_tier(route)
+    }
+
+    /// Try to generate routes with a specific tolerance level. Failed
+    /// attempts are recorded in `tally`.
     #[allow(clippy::too_many_arguments)]
     pub async fn try_generate_routes_with_tolerance(
         &self,
-        start: &Coordinates,
+        candidates: &CandidateIndex<'_>,
         target_distance_km: f64,
         distance_tolerance: f64,
         mode: &TransportMode,
-        candidate_pois: &[Poi],
         preferences: &RoutePreferences,
         seed_offset: usize,
+        tally: &mut FailureTally,
+        progress: &ProgressSink,
     ) -> Vec<Route> {
         let max_alternatives = preferences
             .max_alternatives
             .clamp(MIN_ALTERNATIVES_FOR_SUCCESS, MAX_ALTERNATIVES_CLAMP)
             as usize;
+        let attempts = attempts_per_level(preferences);
         let mut routes = Vec::new();
 
-        for attempt in 0..max_alternatives {
+        for attempt in 0..attempts {
             let params = LoopRouteParams {
-                start,
                 target_distance_km,
                 distance_tolerance,
                 mode,
-                candidate_pois,
+                candidates,
                 attempt_seed: attempt + seed_offset,
                 preferences,
             };
 
             match self.try_generate_loop(params).await {
-                Ok(route) => routes.push(route),
+                Ok(route) => {
+                    progress.emit(GenerationEvent::RouteCandidate {
+                        route_id: route.id,
+                        distance_km: route.distance_km,
+                        poi_count: route.pois.len(),
+                    });
+                    routes.push(route)
+                }
                 Err(e) => {
                     tracing::debug!(
                         "Failed to generate route alternative {}: {}",
                         attempt + 1,
                         e
                     );
+                    tally.record(&e, target_distance_km);
                 }
             }
         }
@@ -95,9 +152,9 @@ impl ToleranceStrategy {
         if routes.is_empty() {
             tracing::warn!(
                 tolerance_km = %format!("{:.2}", distance_tolerance),
-                attempts = max_alternatives,
+                attempts = attempts,
                 "Tolerance level exhausted: 0/{} attempts produced valid routes (target: {:.1}km ± {:.2}km)",
-                max_alternatives, target_distance_km, distance_tolerance
+                attempts, target_distance_km, distance_tolerance
             );
             return routes;
         }
@@ -106,6 +163,7 @@ impl ToleranceStrategy {
             route.score =
                 self.route_scorer
                     .calculate_route_score(route, target_distance_km, preferences);
+            route.quality_tier = self.route_scorer.classify_quality_tier(route);
         }
 
         routes.sort_by(|a, b| {
@@ -114,6 +172,11 @@ impl ToleranceStrategy {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
+        if preferences.pareto {
+            routes =
+                pareto::pareto_front(routes, target_distance_km, preferences, max_alternatives);
+        }
+
         tracing::info!("Generated {} route alternatives", routes.len());
         routes
     }
@@ -126,18 +189,21 @@ impl ToleranceStrategy {
         params: &LoopRouteParams<'_>,
         corrected_target: f64,
         retry: usize,
-    ) -> Result<Option<(crate::services::mapbox::DirectionsResponse, Vec<Poi>)>> {
+    ) -> Result<Option<(DirectionsResponse, Vec<Poi>)>> {
+        let attempt_seed = params.attempt_seed * self.config.max_route_generation_retries + retry;
+        let mut rng = variation::seeded_rng(params.preferences.seed, attempt_seed as u64);
         let selected_pois = self.waypoint_selector.select_loop_waypoints(
-            params.start,
+            params.candidates,
             corrected_target,
-            params.candidate_pois,
-            params.attempt_seed * self.config.max_route_generation_retries + retry,
+            attempt_seed,
+            &mut rng,
             params.preferences,
         )?;
 
-        let ordered_pois = WaypointSelector::order_pois_clockwise(params.start, &selected_pois);
+        let mut ordered_pois =
+            WaypointSelector::order_pois_clockwise(&params.candidates.start, &selected_pois);
 
-        if !WaypointSelector::verify_loop_shape(params.start, &ordered_pois, retry) {
+        if !WaypointSelector::verify_loop_shape(&params.candidates.start, &ordered_pois, retry) {
             tracing::info!(
                 retry = retry + 1,
                 waypoint_count = ordered_pois.len(),
@@ -148,10 +214,32 @@ impl ToleranceStrategy {
             return Ok(None);
         }
 
-        let waypoints = Self::build_loop_waypoints(params.start, &ordered_pois);
+        // The shape check needs the angular order; only then reorder by tour length
+        ordered_pois = tsp::shortest_loop_order(
+            &params.candidates.start,
+            &ordered_pois,
+            self.config.tsp_min_waypoints,
+        );
+
+        let speed = self.config.speed_models().for_mode(params.mode);
+        if let Some(sunset_fraction) = golden_hour_sunset_fraction(params, corrected_target, &speed)
+        {
+            ordered_pois = WaypointSelector::order_for_golden_hour(
+                &params.candidates.start,
+                &ordered_pois,
+                sunset_fraction,
+            );
+        }
+
+        let waypoints = Self::build_loop_waypoints(&params.candidates.start, &ordered_pois);
         let directions = match self
-            .mapbox_client
-            .get_directions(&waypoints, params.mode)
+            .directions
+            .directions(
+                &waypoints,
+                params.mode,
+                &params.preferences.costing,
+                params.preferences.surface,
+            )
             .await
         {
             Ok(d) => d,
@@ -176,13 +264,31 @@ impl ToleranceStrategy {
     async fn evaluate_route_distance(
         &self,
         params: &LoopRouteParams<'_>,
-        directions: crate::services::mapbox::DirectionsResponse,
+        directions: DirectionsResponse,
         ordered_pois: Vec<Poi>,
         min_distance: f64,
         max_distance: f64,
         distance_correction: &mut f64,
         retry: usize,
     ) -> Result<Option<Route>> {
+        let (directions, ordered_pois) =
+            if directions.distance_km() > max_distance && self.config.leg_repair_enabled {
+                match self
+                    .repair_worst_leg(
+                        params,
+                        &directions,
+                        &ordered_pois,
+                        min_distance,
+                        max_distance,
+                    )
+                    .await
+                {
+                    Some(repaired) => repaired,
+                    None => (directions, ordered_pois),
+                }
+            } else {
+                (directions, ordered_pois)
+            };
         let distance_km = directions.distance_km();
 
         if Self::is_distance_within_tolerance(distance_km, min_distance, max_distance) {
@@ -194,15 +300,41 @@ impl ToleranceStrategy {
                 params.distance_tolerance,
                 *distance_correction
             );
-            let route = self
+            let mut route = self
                 .route_scorer
                 .build_route(
                     directions,
                     ordered_pois,
+                    params.mode,
                     params.preferences,
-                    params.candidate_pois.len(),
+                    params.candidates.pois.len(),
                 )
-                .await?;
+                .await?
+                .with_duration_estimates(params.mode, &self.config.speed_models());
+            if let Some(reason) =
+                road_constraints::road_profile_violation(&route, params.preferences)
+            {
+                tracing::info!(
+                    retry = retry + 1,
+                    "Retry {}: rejected — {}",
+                    retry + 1,
+                    reason
+                );
+                return Ok(None);
+            }
+            if *params.mode == TransportMode::Walk && self.config.duration_requery_cycling {
+                let cycling_minutes = self
+                    .requery_cycling_minutes(
+                        &params.candidates.start,
+                        &route,
+                        &params.preferences.costing,
+                        params.preferences.surface,
+                    )
+                    .await;
+                route.duration_estimates = route
+                    .duration_estimates
+                    .map(|estimates| estimates.with_cycling_minutes(cycling_minutes));
+            }
             return Ok(Some(route));
         }
 
@@ -228,6 +360,67 @@ impl ToleranceStrategy {
         Ok(None)
     }
 
+    /// Re-route the leg with the worst detour through an extra POI and splice it
+    /// back in. Returns the repaired directions and waypoints only if the result
+    /// lands within tolerance.
+    async fn repair_worst_leg(
+        &self,
+        params: &LoopRouteParams<'_>,
+        directions: &DirectionsResponse,
+        ordered_pois: &[Poi],
+        min_distance: f64,
+        max_distance: f64,
+    ) -> Option<(DirectionsResponse, Vec<Poi>)> {
+        let waypoints = Self::build_loop_waypoints(&params.candidates.start, ordered_pois);
+        let leg = leg_repair::worst_detour_leg(&waypoints, directions)?;
+        let (from, to) = (waypoints[leg], waypoints[leg + 1]);
+        let sub_waypoint =
+            leg_repair::pick_sub_waypoint(&from, &to, params.candidates.pois, ordered_pois)?;
+
+        let replacement = match self
+            .directions
+            .directions(
+                &[from, sub_waypoint.coordinates, to],
+                params.mode,
+                &params.preferences.costing,
+                params.preferences.surface,
+            )
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => {
+                tracing::debug!(error = %e, leg, "Leg repair query failed: {}", e);
+                return None;
+            }
+        };
+        let repaired = leg_repair::splice_leg(directions, leg, &replacement)?;
+        let repaired_km = repaired.distance_km();
+
+        if !Self::is_distance_within_tolerance(repaired_km, min_distance, max_distance) {
+            tracing::debug!(
+                leg,
+                original_km = %format!("{:.2}", directions.distance_km()),
+                repaired_km = %format!("{:.2}", repaired_km),
+                "Leg repair via '{}' still outside tolerance",
+                sub_waypoint.name
+            );
+            return None;
+        }
+
+        tracing::info!(
+            leg,
+            original_km = %format!("{:.2}", directions.distance_km()),
+            repaired_km = %format!("{:.2}", repaired_km),
+            "Repaired leg {} via '{}': {:.2}km -> {:.2}km",
+            leg, sub_waypoint.name, directions.distance_km(), repaired_km
+        );
+
+        // Waypoint `leg` in `waypoints` is POI `leg - 1`, so the new POI goes at index `leg`
+        let mut pois = ordered_pois.to_vec();
+        pois.insert(leg, sub_waypoint.clone());
+        Some((repaired, pois))
+    }
+
     /// Try to generate a single loop route with selected waypoints.
     /// Uses feedback-based distance correction: after each out-of-tolerance result,
     /// adjusts the target distance passed to waypoint selection based on the ratio
@@ -236,6 +429,8 @@ impl ToleranceStrategy {
         let min_distance = params.target_distance_km - params.distance_tolerance;
         let max_distance = params.target_distance_km + params.distance_tolerance;
         let mut distance_correction: f64 = 1.0;
+        // Achieved distance closest to the target among out-of-tolerance retries
+        let mut best_km: Option<f64> = None;
 
         for retry in 0..self.config.max_route_generation_retries {
             let corrected_target = params.target_distance_km * distance_correction;
@@ -247,6 +442,15 @@ impl ToleranceStrategy {
                 continue;
             };
 
+            let achieved_km = directions.distance_km();
+            if !Self::is_distance_within_tolerance(achieved_km, min_distance, max_distance) {
+                best_km = Some(failure::closest_km(
+                    best_km,
+                    achieved_km,
+                    params.target_distance_km,
+                ));
+            }
+
             if let Some(route) = self
                 .evaluate_route_distance(
                     &params,
@@ -263,10 +467,56 @@ impl ToleranceStrategy {
             }
         }
 
-        Err(AppError::RouteGeneration(format!(
+        tracing::debug!(
             "Could not achieve target distance after {} attempts with {} candidate POIs (wanted {}km ± {}km)",
-            self.config.max_route_generation_retries, params.candidate_pois.len(), params.target_distance_km, params.distance_tolerance
-        )))
+            self.config.max_route_generation_retries, params.candidates.pois.len(), params.target_distance_km, params.distance_tolerance
+        );
+        // No distance to report means every retry was rejected before or
+        // after routing for its shape or road profile
+        Err(AppError::GenerationFailed(match best_km {
+            Some(best_km) => GenerationFailure::AllAttemptsOutOfTolerance { best_km },
+            None => GenerationFailure::ShapeRejected,
+        }))
+    }
+
+    /// Ask the backend for a cycling route through a walking route's waypoints.
+    /// Returns the cycling duration only if bikes can follow roughly the same
+    /// geometry; failures and large detours yield `None`.
+    async fn requery_cycling_minutes(
+        &self,
+        start: &Coordinates,
+        route: &Route,
+        costing: &CostingOptions,
+        surface: SurfacePreference,
+    ) -> Option<u32> {
+        let pois: Vec<Poi> = route.pois.iter().map(|rp| rp.poi.clone()).collect();
+        let waypoints = Self::build_loop_waypoints(start, &pois);
+
+        match self
+            .directions
+            .directions(&waypoints, &TransportMode::Bike, costing, surface)
+            .await
+        {
+            Ok(cycling) => {
+                let deviation =
+                    (cycling.distance_km() - route.distance_km).abs() / route.distance_km;
+                if deviation <= PROFILE_REQUERY_MAX_DISTANCE_DEVIATION {
+                    Some(cycling.duration_minutes())
+                } else {
+                    tracing::debug!(
+                        walking_km = %format!("{:.2}", route.distance_km),
+                        cycling_km = %format!("{:.2}", cycling.distance_km()),
+                        "Cycling geometry deviates {:.0}% from walking route, omitting cycling duration",
+                        deviation * 100.0
+                    );
+                    None
+                }
+            }
+            Err(e) => {
+                tracing::debug!(error = %e, "Cycling re-query failed, omitting cycling duration");
+                None
+            }
+        }
     }
 
     /// Check if a distance is within the acceptable tolerance range
@@ -288,9 +538,76 @@ impl ToleranceStrategy {
     }
 }
 
+/// Where sunset falls along a golden-hour route, as a fraction of its
+/// estimated duration (may lie outside 0..1). `None` unless golden-hour
+/// routing was requested and the sun sets at the start that day. The
+/// duration comes from `speed` on flat ground, since the route isn't known yet.
+fn golden_hour_sunset_fraction(
+    params: &LoopRouteParams<'_>,
+    distance_km: f64,
+    speed: &SpeedModel,
+) -> Option<f64> {
+    let preferences = params.preferences;
+    if !preferences.golden_hour {
+        return None;
+    }
+    let start = &params.candidates.start;
+    let duration = time::Duration::seconds_f64(speed.minutes(distance_km, None) * 60.0);
+    let depart_at = match preferences.departure {
+        // Planned to end at sunset
+        None => return Some(1.0),
+        Some(Departure::DepartAt(at)) => at,
+        Some(Departure::ArriveBy(by)) => by - duration,
+    };
+    // The sunset nearest the walk, even one that falls just before it starts
+    let sunset = solar::next_sunset(depart_at - duration, start)?;
+    Some((sunset - depart_at) / duration)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::SpeedModels;
+    use time::{Date, Month};
+
+    #[test]
+    fn test_golden_hour_sunset_fraction() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let pois = Vec::new();
+        let fraction = |preferences: &RoutePreferences| {
+            let candidates = CandidateIndex::new(&start, &pois, preferences);
+            let params = LoopRouteParams {
+                target_distance_km: 10.0,
+                distance_tolerance: 1.0,
+                mode: &TransportMode::Walk,
+                candidates: &candidates,
+                attempt_seed: 0,
+                preferences,
+            };
+            golden_hour_sunset_fraction(&params, 10.0, &SpeedModels::default().walking)
+        };
+        // Paris sunset on 2024-06-21 is at 19:58 UTC; a 10 km walk takes 2 h
+        let at = |hour: u8, minute: u8| {
+            Date::from_calendar_date(2024, Month::June, 21)
+                .unwrap()
+                .with_hms(hour, minute, 0)
+                .unwrap()
+                .assume_utc()
+        };
+        let golden = |departure| RoutePreferences {
+            golden_hour: true,
+            departure,
+            ..Default::default()
+        };
+
+        assert_eq!(fraction(&RoutePreferences::default()), None);
+        assert_eq!(fraction(&golden(None)), Some(1.0));
+        let half = fraction(&golden(Some(Departure::DepartAt(at(19, 0))))).unwrap();
+        assert!((half - 0.5).abs() < 0.05, "{half}");
+        // Back at 20:00, so out from 18:00: sunset comes near the end
+        let near_end = fraction(&golden(Some(Departure::ArriveBy(at(20, 0))))).unwrap();
+        assert!((near_end - 0.98).abs() < 0.03, "{near_end}");
+    }
 
     #[test]
     fn test_feedback_correction_undershoot() {
@@ -0,0 +1,244 @@
+//! Shortest closed tour through a loop's waypoints, on haversine distances.
+//! The tour starts and ends at the start point, which is not reordered.
+
+use crate::constants::TSP_EXACT_MAX_WAYPOINTS;
+use crate::models::{Coordinates, Poi};
+
+/// Reorder clockwise waypoints into the shortest loop from `start`, or keep
+/// them as they are below `min_waypoints`. The clockwise order is kept
+/// unless a tour is strictly shorter, so ties stay deterministic.
+pub fn shortest_loop_order(
+    start: &Coordinates,
+    clockwise: &[Poi],
+    min_waypoints: usize,
+) -> Vec<Poi> {
+    if clockwise.len() < min_waypoints {
+        return clockwise.to_vec();
+    }
+
+    let dist = distance_matrix(start, clockwise);
+    let initial: Vec<usize> = (1..=clockwise.len()).collect();
+    let tour = if clockwise.len() <= TSP_EXACT_MAX_WAYPOINTS {
+        held_karp(&dist)
+    } else {
+        two_opt(&dist, initial.clone())
+    };
+
+    let tour = if tour_length(&dist, &tour) < tour_length(&dist, &initial) - 1e-9 {
+        tour
+    } else {
+        initial
+    };
+    tour.into_iter().map(|i| clockwise[i - 1].clone()).collect()
+}
+
+/// Node 0 is the start; node `i` is `pois[i - 1]`
+fn distance_matrix(start: &Coordinates, pois: &[Poi]) -> Vec<Vec<f64>> {
+    let points: Vec<&Coordinates> = std::iter::once(start)
+        .chain(pois.iter().map(|p| &p.coordinates))
+        .collect();
+    points
+        .iter()
+        .map(|a| points.iter().map(|b| a.distance_to(b)).collect())
+        .collect()
+}
+
+/// Length of start -> `tour` -> start
+fn tour_length(dist: &[Vec<f64>], tour: &[usize]) -> f64 {
+    let mut length = 0.0;
+    let mut previous = 0;
+    for &node in tour.iter().chain(std::iter::once(&0)) {
+        length += dist[previous][node];
+        previous = node;
+    }
+    length
+}
+
+/// Exact shortest tour over nodes 1..n (dynamic programming over subsets)
+fn held_karp(dist: &[Vec<f64>]) -> Vec<usize> {
+    let n = dist.len() - 1;
+    let full = (1usize << n) - 1;
+    // cost[mask][j]: shortest path from the start through `mask`, ending at node j + 1
+    let mut cost = vec![vec![f64::INFINITY; n]; full + 1];
+    let mut parent = vec![vec![usize::MAX; n]; full + 1];
+    for j in 0..n {
+        cost[1 << j][j] = dist[0][j + 1];
+    }
+    for mask in 1..=full {
+        for last in 0..n {
+            if mask & (1 << last) == 0 || !cost[mask][last].is_finite() {
+                continue;
+            }
+            for next in 0..n {
+                if mask & (1 << next) != 0 {
+                    continue;
+                }
+                let extended = mask | (1 << next);
+                let candidate = cost[mask][last] + dist[last + 1][next + 1];
+                if candidate < cost[extended][next] {
+                    cost[extended][next] = candidate;
+                    parent[extended][next] = last;
+                }
+            }
+        }
+    }
+
+    let mut last = (0..n)
+        .min_by(|&a, &b| {
+            (cost[full][a] + dist[a + 1][0])
+                .partial_cmp(&(cost[full][b] + dist[b + 1][0]))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(0);
+    let mut mask = full;
+    let mut tour = Vec::with_capacity(n);
+    while last != usize::MAX {
+        tour.push(last + 1);
+        let previous = parent[mask][last];
+        mask &= !(1 << last);
+        last = previous;
+    }
+    tour.reverse();
+    tour
+}
+
+/// Reverse segments while any reversal shortens the tour
+fn two_opt(dist: &[Vec<f64>], mut tour: Vec<usize>) -> Vec<usize> {
+    // Node at position i of the closed tour, with the start at both ends
+    let node = |tour: &[usize], i: usize| {
+        if i == 0 || i > tour.len() {
+            0
+        } else {
+            tour[i - 1]
+        }
+    };
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 1..tour.len() {
+            for j in i + 1..=tour.len() {
+                let (a, b) = (node(&tour, i - 1), node(&tour, i));
+                let (c, d) = (node(&tour, j), node(&tour, j + 1));
+                let delta = dist[a][c] + dist[b][d] - dist[a][b] - dist[c][d];
+                if delta < -1e-9 {
+                    tour[i - 1..j].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+    tour
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::TSP_MIN_WAYPOINTS;
+    use crate::models::PoiCategory;
+    use crate::services::route_generator::waypoint_selection::WaypointSelector;
+
+    fn poi(name: &str, lat: f64, lng: f64) -> Poi {
+        Poi::new(
+            name.to_string(),
+            PoiCategory::Park,
+            Coordinates::new(lat, lng).unwrap(),
+            50.0,
+        )
+    }
+
+    fn names(pois: &[Poi]) -> Vec<&str> {
+        pois.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    fn length(start: &Coordinates, pois: &[Poi]) -> f64 {
+        let dist = distance_matrix(start, pois);
+        tour_length(&dist, &(1..=pois.len()).collect::<Vec<_>>())
+    }
+
+    /// Waypoints at alternating near/far radii: sorting by angle zig-zags
+    /// between the two rings
+    fn zigzag(count: usize) -> (Coordinates, Vec<Poi>) {
+        let start = Coordinates::new(48.85, 2.35).unwrap();
+        let pois = (0..count)
+            .map(|i| {
+                let angle = i as f64 / count as f64 * std::f64::consts::TAU;
+                let radius_m = if i % 2 == 0 { 3000.0 } else { 300.0 };
+                let point = start.offset_by(radius_m * angle.sin(), radius_m * angle.cos());
+                poi(&format!("P{}", i), point.lat, point.lng)
+            })
+            .collect();
+        (start, pois)
+    }
+
+    #[test]
+    fn test_few_waypoints_keep_clockwise_order() {
+        let (start, pois) = zigzag(4);
+        let clockwise = WaypointSelector::order_pois_clockwise(&start, &pois);
+        assert_eq!(
+            names(&shortest_loop_order(&start, &clockwise, TSP_MIN_WAYPOINTS)),
+            names(&clockwise)
+        );
+    }
+
+    #[test]
+    fn test_min_waypoints_setting_controls_reordering() {
+        let (start, pois) = zigzag(6);
+        let clockwise = WaypointSelector::order_pois_clockwise(&start, &pois);
+        assert_eq!(
+            names(&shortest_loop_order(&start, &clockwise, 7)),
+            names(&clockwise)
+        );
+        let ordered = shortest_loop_order(&start, &clockwise, 6);
+        assert!(length(&start, &ordered) < length(&start, &clockwise) - 0.1);
+    }
+
+    #[test]
+    fn test_exact_tour_is_never_longer_than_clockwise() {
+        let (start, pois) = zigzag(8);
+        let clockwise = WaypointSelector::order_pois_clockwise(&start, &pois);
+        let ordered = shortest_loop_order(&start, &clockwise, TSP_MIN_WAYPOINTS);
+        assert_eq!(ordered.len(), clockwise.len());
+        assert!(length(&start, &ordered) < length(&start, &clockwise) - 0.1);
+    }
+
+    #[test]
+    fn test_held_karp_matches_brute_force() {
+        let (start, pois) = zigzag(6);
+        let dist = distance_matrix(&start, &pois);
+        let exact = tour_length(&dist, &held_karp(&dist));
+
+        fn permutations(items: Vec<usize>) -> Vec<Vec<usize>> {
+            if items.len() <= 1 {
+                return vec![items];
+            }
+            let mut all = Vec::new();
+            for i in 0..items.len() {
+                let mut rest = items.clone();
+                let first = rest.remove(i);
+                for mut tail in permutations(rest) {
+                    tail.insert(0, first);
+                    all.push(tail);
+                }
+            }
+            all
+        }
+        let brute = permutations((1..=6).collect())
+            .iter()
+            .map(|tour| tour_length(&dist, tour))
+            .fold(f64::INFINITY, f64::min);
+        assert!((exact - brute).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_two_opt_removes_crossings_for_large_loops() {
+        let (start, pois) = zigzag(14);
+        let clockwise = WaypointSelector::order_pois_clockwise(&start, &pois);
+        let ordered = shortest_loop_order(&start, &clockwise, TSP_MIN_WAYPOINTS);
+        let mut sorted = names(&ordered);
+        sorted.sort();
+        let mut expected = names(&pois);
+        expected.sort();
+        assert_eq!(sorted, expected);
+        assert!(length(&start, &ordered) < length(&start, &clockwise));
+    }
+}
@@ -1,9 +1,10 @@
 use crate::config::{RouteGeneratorConfig, ScoringStrategy};
 use crate::constants::*;
-use crate::error::{AppError, Result};
+use crate::error::{AppError, GenerationFailure, Result};
 use crate::models::{Coordinates, Poi, RoutePreferences};
-use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use rand::{rngs::SmallRng, seq::SliceRandom};
 
+use super::candidates::CandidateIndex;
 use super::geometry::angle_from_start;
 use super::scoring_strategy::{
     AdvancedStrategy, PoiScoringStrategy, ScoringContext, SimpleStrategy,
@@ -35,24 +36,26 @@ impl WaypointSelector {
         }
     }
 
-    /// Select waypoints for a loop route
+    /// Select waypoints for a loop route around `candidates.start`
     /// Strategy: Choose 2-4 POIs that are spatially distributed to form a loop
     pub fn select_loop_waypoints(
         &self,
-        start: &Coordinates,
+        candidates: &CandidateIndex,
         target_distance_km: f64,
-        pois: &[Poi],
         attempt_seed: usize,
+        rng: &mut SmallRng,
         preferences: &RoutePreferences,
     ) -> Result<Vec<Poi>> {
-        if pois.len() < 2 {
-            return Err(AppError::RouteGeneration(
-                "Not enough POIs to create route".to_string(),
-            ));
+        let start = &candidates.start;
+        if candidates.len() < 2 {
+            tracing::debug!("Not enough POIs to create route");
+            return Err(AppError::GenerationFailed(GenerationFailure::NoPoisInArea));
         }
 
-        let num_waypoints =
-            self.calculate_waypoint_count(target_distance_km, pois.len(), attempt_seed);
+        // Pinned places always take a slot; the rest are filled around them
+        let num_waypoints = self
+            .calculate_waypoint_count(target_distance_km, candidates.len(), attempt_seed)
+            .max(candidates.pinned);
         let multiplier = self.get_waypoint_distance_multiplier(num_waypoints, target_distance_km);
         let target_waypoint_distance = target_distance_km * multiplier;
 
@@ -66,12 +69,11 @@ impl WaypointSelector {
 
         // Use iterative selection with strategy pattern
         let selected = self.select_pois_iteratively(
-            start,
-            pois,
+            candidates,
             target_waypoint_distance,
             target_distance_km,
             num_waypoints,
-            attempt_seed,
+            rng,
             preferences,
         )?;
 
@@ -96,37 +98,37 @@ impl WaypointSelector {
 
     /// Iteratively select POIs using the scoring strategy
     /// This allows the strategy to consider already-selected POIs for clustering/angular diversity
-    #[allow(clippy::too_many_arguments)]
     fn select_pois_iteratively(
         &self,
-        start: &Coordinates,
-        pois: &[Poi],
+        candidates: &CandidateIndex,
         target_waypoint_distance: f64,
         target_distance_km: f64,
         num_waypoints: usize,
-        attempt_seed: usize,
+        rng: &mut SmallRng,
         preferences: &RoutePreferences,
     ) -> Result<Vec<Poi>> {
-        let mut selected: Vec<Poi> = Vec::new();
-        let mut remaining_pois: Vec<Poi> = pois.to_vec();
+        let mut selected: Vec<usize> = Vec::with_capacity(num_waypoints);
+        selected.extend(0..candidates.pinned);
+        let mut remaining_pois: Vec<usize> = (candidates.pinned..candidates.len()).collect();
 
-        for iteration in 0..num_waypoints {
+        for iteration in selected.len()..num_waypoints {
             if remaining_pois.is_empty() {
                 break;
             }
 
             // Create scoring context
-            let context = ScoringContext {
-                start,
+            let mut context = ScoringContext {
                 target_waypoint_distance,
                 target_distance_km,
-                attempt_seed,
+                rng: &mut *rng,
                 preferences,
                 already_selected: &selected,
             };
 
             // Score remaining POIs using strategy
-            let mut scored_pois = self.scoring_strategy.score_pois(&remaining_pois, &context);
+            let mut scored_pois =
+                self.scoring_strategy
+                    .score_pois(candidates, &remaining_pois, &mut context);
 
             // Fallback if no POIs scored
             if scored_pois.is_empty() {
@@ -138,7 +140,7 @@ impl WaypointSelector {
                     "No POIs scored in iteration {} ({} remaining, filter: min {:.2}km, max mult {:.1}x), using fallback",
                     iteration, remaining_pois.len(), self.config.min_poi_distance_km, self.config.max_poi_distance_multiplier
                 );
-                scored_pois = self.fallback_score_closest_pois(start, &remaining_pois, 1)?;
+                scored_pois = self.fallback_score_closest_pois(candidates, &remaining_pois, 1)?;
             }
 
             // If still no POIs after fallback, break the loop
@@ -158,26 +160,30 @@ impl WaypointSelector {
 
             // Select top POI with some randomization
             let pool_size = (scored_pois.len() / 3).max(1).min(scored_pois.len());
-            let mut rng = StdRng::seed_from_u64((attempt_seed + selected.len()) as u64);
             let selected_poi = scored_pois[..pool_size]
-                .choose(&mut rng)
-                .map(|(_, poi)| (*poi).clone())
-                .unwrap_or_else(|| scored_pois[0].1.clone());
+                .choose(rng)
+                .map(|(_, candidate)| *candidate)
+                .unwrap_or(scored_pois[0].1);
 
             // Remove selected POI from remaining
-            remaining_pois.retain(|poi| poi.id != selected_poi.id);
+            let selected_id = candidates.pois[selected_poi].id;
+            remaining_pois.retain(|&candidate| candidates.pois[candidate].id != selected_id);
 
             selected.push(selected_poi);
         }
 
         if selected.len() < 2 {
-            return Err(AppError::RouteGeneration(format!(
+            tracing::debug!(
                 "Could only select {} POI(s), need at least 2",
                 selected.len()
-            )));
+            );
+            return Err(AppError::GenerationFailed(GenerationFailure::NoPoisInArea));
         }
 
-        Ok(selected)
+        Ok(selected
+            .into_iter()
+            .map(|candidate| candidates.pois[candidate].clone())
+            .collect())
     }
 
     /// Order POIs in clockwise direction around start point for efficient loop routing
@@ -200,6 +206,69 @@ impl WaypointSelector {
         pois_with_angles.into_iter().map(|(_, poi)| poi).collect()
     }
 
+    /// Re-order clockwise waypoints so golden-hour spots (viewpoints,
+    /// waterfronts) fall closest to `sunset_fraction` of the way round.
+    /// Only rotations and the reverse of the angular order are considered,
+    /// so the loop keeps the shape `verify_loop_shape` accepted.
+    pub fn order_for_golden_hour(
+        start: &Coordinates,
+        clockwise: &[Poi],
+        sunset_fraction: f64,
+    ) -> Vec<Poi> {
+        if clockwise.len() < 2 || !clockwise.iter().any(|p| p.category.is_golden_hour_spot()) {
+            return clockwise.to_vec();
+        }
+
+        let n = clockwise.len();
+        let reversed: Vec<Poi> = clockwise.iter().rev().cloned().collect();
+        let mut best = clockwise.to_vec();
+        let mut best_cost = Self::golden_hour_cost(start, &best, sunset_fraction);
+        for order in [clockwise, reversed.as_slice()] {
+            for rotation in 0..n {
+                let candidate: Vec<Poi> = order[rotation..]
+                    .iter()
+                    .chain(&order[..rotation])
+                    .cloned()
+                    .collect();
+                let cost = Self::golden_hour_cost(start, &candidate, sunset_fraction);
+                if cost < best_cost - 1e-9 {
+                    best = candidate;
+                    best_cost = cost;
+                }
+            }
+        }
+        best
+    }
+
+    /// Squared distance, in fractions of the loop, between each golden-hour
+    /// spot and the sunset position (straight legs approximate the path)
+    fn golden_hour_cost(start: &Coordinates, ordered: &[Poi], sunset_fraction: f64) -> f64 {
+        let mut legs = Vec::with_capacity(ordered.len() + 1);
+        let mut previous = start;
+        for poi in ordered {
+            legs.push(previous.distance_to(&poi.coordinates));
+            previous = &poi.coordinates;
+        }
+        let total = legs.iter().sum::<f64>() + previous.distance_to(start);
+        if total <= 0.0 {
+            return 0.0;
+        }
+
+        let mut along = 0.0;
+        ordered
+            .iter()
+            .zip(&legs)
+            .map(|(poi, leg)| {
+                along += leg;
+                if poi.category.is_golden_hour_spot() {
+                    (along / total - sunset_fraction).powi(2)
+                } else {
+                    0.0
+                }
+            })
+            .sum()
+    }
+
     /// Calculate the optimal number of waypoints based on distance, available POIs, and attempt seed
     /// Uses alternating pattern: even seeds → fewer waypoints, odd seeds → more waypoints
     fn calculate_waypoint_count(
@@ -274,38 +343,35 @@ impl WaypointSelector {
     }
 
     /// Fallback strategy: score POIs by proximity when filtering yields too few results
-    fn fallback_score_closest_pois<'a, P>(
+    fn fallback_score_closest_pois(
         &self,
-        start: &Coordinates,
-        pois: &'a [P],
+        candidates: &CandidateIndex,
+        remaining: &[usize],
         num_waypoints: usize,
-    ) -> Result<Vec<(f32, &'a Poi)>>
-    where
-        P: std::borrow::Borrow<Poi>,
-    {
+    ) -> Result<Vec<(f32, usize)>> {
         tracing::warn!(
             "Using fallback scoring - relaxing constraints for {} waypoints",
             num_waypoints
         );
 
-        if pois.len() < 2 {
-            return Err(AppError::RouteGeneration(format!(
+        if remaining.len() < 2 {
+            tracing::debug!(
                 "Not enough POIs in area (found {}, need at least 2)",
-                pois.len()
-            )));
+                remaining.len()
+            );
+            return Err(AppError::GenerationFailed(GenerationFailure::NoPoisInArea));
         }
 
-        Ok(pois
+        Ok(remaining
             .iter()
             .enumerate()
-            .filter_map(|(idx, poi_ref)| {
-                let poi = poi_ref.borrow();
-                let dist = start.distance_to(&poi.coordinates);
+            .filter_map(|(idx, &candidate)| {
+                let dist = candidates.distances_km[candidate];
                 if dist < self.config.min_poi_distance_km {
                     return None;
                 }
                 let score = 1.0 / (dist as f32 + 1.0); // Closer = better
-                Some((score + (idx as f32 * 0.01), poi))
+                Some((score + (idx as f32 * 0.01), candidate))
             })
             .collect())
     }
@@ -613,6 +679,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_golden_hour_moves_viewpoint_towards_sunset() {
+        use crate::models::PoiCategory;
+
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let poi = |name: &str, category: PoiCategory, lat: f64, lng: f64| {
+            Poi::new(
+                name.to_string(),
+                category,
+                Coordinates::new(lat, lng).unwrap(),
+                70.0,
+            )
+        };
+        let clockwise = WaypointSelector::order_pois_clockwise(
+            &start,
+            &[
+                poi("View", PoiCategory::Viewpoint, 48.87, 2.35),
+                poi("Park", PoiCategory::Park, 48.85, 2.37),
+                poi("Museum", PoiCategory::Museum, 48.85, 2.33),
+            ],
+        );
+        let names = |order: &[Poi]| order.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+
+        // Ending at sunset: the viewpoint is the last stop
+        let late = WaypointSelector::order_for_golden_hour(&start, &clockwise, 1.0);
+        assert_eq!(late.last().unwrap().name, "View");
+        // Sunset early in the outing: the viewpoint comes first
+        let early = WaypointSelector::order_for_golden_hour(&start, &clockwise, 0.1);
+        assert_eq!(early[0].name, "View");
+
+        // Still the same cycle, so the loop shape is unchanged
+        for order in [&late, &early] {
+            let mut cycle = names(order);
+            while cycle[0] != names(&clockwise)[0] {
+                cycle.rotate_left(1);
+            }
+            let mut reversed = cycle.clone();
+            reversed[1..].reverse();
+            assert!(cycle == names(&clockwise) || reversed == names(&clockwise));
+        }
+
+        // Nothing to watch the sunset from: order untouched
+        let plain: Vec<Poi> = clockwise
+            .iter()
+            .filter(|p| !p.category.is_golden_hour_spot())
+            .cloned()
+            .collect();
+        assert_eq!(
+            names(&WaypointSelector::order_for_golden_hour(
+                &start, &plain, 0.0
+            )),
+            names(&plain)
+        );
+    }
+
     #[test]
     fn test_verify_loop_shape_clustered() {
         use crate::models::PoiCategory;
@@ -0,0 +1,137 @@
+//! Snap radius suggestions from client feedback.
+//!
+//! Clients report, for each snapped POI they showed, how far it was from the
+//! path and whether the user tapped or kept it. POIs right on the path set
+//! the baseline engagement rate; the suggested radius reaches out as far as
+//! engagement stays within [`SNAP_TUNING_RELATIVE_ENGAGEMENT`] of that
+//! baseline. Suggestions are advisory and only show up in the admin report;
+//! `SNAP_RADIUS_M` still decides the radius in use.
+
+use crate::constants::{
+    SNAP_FEEDBACK_BUCKET_M, SNAP_FEEDBACK_MAX_DISTANCE_M, SNAP_TUNING_MIN_BUCKET_SAMPLES,
+    SNAP_TUNING_MIN_SAMPLES, SNAP_TUNING_RELATIVE_ENGAGEMENT,
+};
+use crate::models::snap_feedback::{SnapDistanceBucket, SnapRadiusRecommendation};
+
+/// Dense buckets from sparse `(bucket index, shown, engaged)` counts
+pub fn buckets_from_counts(counts: &[(i32, i64, i64)]) -> Vec<SnapDistanceBucket> {
+    let bucket_count = (SNAP_FEEDBACK_MAX_DISTANCE_M / SNAP_FEEDBACK_BUCKET_M).ceil() as usize;
+    let mut buckets: Vec<SnapDistanceBucket> = (0..bucket_count)
+        .map(|i| SnapDistanceBucket {
+            max_distance_m: ((i + 1) as f64 * SNAP_FEEDBACK_BUCKET_M)
+                .min(SNAP_FEEDBACK_MAX_DISTANCE_M),
+            shown: 0,
+            engaged: 0,
+        })
+        .collect();
+    for &(index, shown, engaged) in counts {
+        if let Some(bucket) = usize::try_from(index).ok().and_then(|i| buckets.get_mut(i)) {
+            bucket.shown += shown;
+            bucket.engaged += engaged;
+        }
+    }
+    buckets
+}
+
+/// Furthest distance at which snapped POIs still engage users about as well
+/// as those on the path, or `None` without enough feedback
+pub fn recommend_radius(buckets: &[SnapDistanceBucket]) -> Option<f64> {
+    let total: i64 = buckets.iter().map(|b| b.shown).sum();
+    if total < SNAP_TUNING_MIN_SAMPLES {
+        return None;
+    }
+
+    // Sparse buckets are skipped rather than ending the radius
+    let mut trusted = buckets
+        .iter()
+        .filter(|b| b.shown >= SNAP_TUNING_MIN_BUCKET_SAMPLES);
+    let nearest = trusted.next()?;
+    let baseline = nearest.engagement_rate();
+    if baseline <= 0.0 {
+        return None;
+    }
+
+    let mut radius = nearest.max_distance_m;
+    for bucket in trusted {
+        if bucket.engagement_rate() < baseline * SNAP_TUNING_RELATIVE_ENGAGEMENT {
+            break;
+        }
+        radius = bucket.max_distance_m;
+    }
+    Some(radius)
+}
+
+/// Suggestion for one transport mode from its bucketed feedback
+pub fn recommend(
+    transport_mode: &str,
+    current_radius_m: f64,
+    counts: &[(i32, i64, i64)],
+) -> SnapRadiusRecommendation {
+    let buckets = buckets_from_counts(counts);
+    SnapRadiusRecommendation {
+        transport_mode: transport_mode.to_string(),
+        current_radius_m,
+        recommended_radius_m: recommend_radius(&buckets),
+        samples: buckets.iter().map(|b| b.shown).sum(),
+        buckets,
+        computed_at: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 100 POIs shown per bucket with the given engagement percentages
+    fn counts(engagement_pct: &[i64]) -> Vec<(i32, i64, i64)> {
+        engagement_pct
+            .iter()
+            .enumerate()
+            .map(|(i, pct)| (i as i32, 100, *pct))
+            .collect()
+    }
+
+    #[test]
+    fn test_radius_extends_while_engagement_holds() {
+        // 0-25 m: 60%, ..., 75-100 m: 35%, 100-125 m: 20% (< half of 60%)
+        let rec = recommend("walk", 100.0, &counts(&[60, 55, 40, 35, 20, 50]));
+        assert_eq!(rec.recommended_radius_m, Some(100.0));
+        assert_eq!(rec.samples, 600);
+        assert_eq!(rec.buckets[0].engagement_rate(), 0.6);
+    }
+
+    #[test]
+    fn test_sparse_buckets_are_skipped() {
+        let mut feedback = counts(&[60, 50]);
+        // 50-75 m barely seen, then 75-100 m still engaging
+        feedback.push((2, 3, 0));
+        feedback.push((3, 100, 45));
+        assert_eq!(
+            recommend_radius(&buckets_from_counts(&feedback)),
+            Some(100.0)
+        );
+    }
+
+    #[test]
+    fn test_no_recommendation_without_enough_feedback() {
+        assert_eq!(
+            recommend("bike", 100.0, &counts(&[60])).recommended_radius_m,
+            None
+        );
+        // Plenty of feedback, nobody ever engages
+        assert_eq!(
+            recommend_radius(&buckets_from_counts(&counts(&[0, 0, 0]))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_buckets_are_ignored() {
+        let buckets = buckets_from_counts(&[(-1, 10, 1), (1000, 10, 1), (0, 5, 2)]);
+        assert_eq!(buckets.iter().map(|b| b.shown).sum::<i64>(), 5);
+        assert_eq!(
+            buckets.last().unwrap().max_distance_m,
+            SNAP_FEEDBACK_MAX_DISTANCE_M
+        );
+    }
+}
@@ -1,10 +1,14 @@
 use crate::db::PoiRepository;
+use crate::models::road_profile::RoadProfile;
 use crate::models::route::{RoutePoi, SnappedPoi};
 use crate::models::{BoundingBox, Coordinates, PoiCategory};
 use std::collections::HashSet;
 use std::sync::Arc;
 use tracing::{debug, instrument};
 
+/// Reasonable limit for POIs near one route
+const SNAP_CANDIDATE_LIMIT: i64 = 500;
+
 #[derive(Clone)]
 pub struct SnappingService {
     repo: Arc<dyn PoiRepository>,
@@ -15,7 +19,9 @@ impl SnappingService {
         SnappingService { repo }
     }
 
-    /// Find POIs that are near the route path but not used as waypoints
+    /// Find POIs that are near the route path but not used as waypoints.
+    /// POIs in `excluded_categories` are never snapped; amenities are only
+    /// snapped when `categories` asks for them explicitly.
     #[instrument(skip(self, route_path, waypoint_pois))]
     pub async fn find_snapped_pois(
         &self,
@@ -23,20 +29,64 @@ impl SnappingService {
         waypoint_pois: &[RoutePoi],
         snap_radius_m: f64,
         categories: Option<&[PoiCategory]>,
+        excluded_categories: Option<&[PoiCategory]>,
     ) -> Result<Vec<SnappedPoi>, Box<dyn std::error::Error>> {
         if route_path.len() < 2 {
             debug!("Route path too short for snapping");
             return Ok(Vec::new());
         }
 
-        // Step 1: Calculate bounding box of the route with buffer
+        // Step 1: POIs near the path, ordered along it: in one query when the
+        // repository supports it, otherwise from a bounding box search
+        let candidates = match self
+            .repo
+            .find_near_path(route_path, snap_radius_m, categories, SNAP_CANDIDATE_LIMIT)
+            .await?
+        {
+            Some(candidates) => candidates,
+            None => {
+                self.snap_in_bbox(route_path, snap_radius_m, categories)
+                    .await?
+            }
+        };
+        let candidates_count = candidates.len();
+
+        // Step 2: Drop waypoints, excluded categories and unrequested amenities
+        let waypoint_ids: HashSet<_> = waypoint_pois.iter().map(|rp| rp.poi.id).collect();
+        let snapped: Vec<SnappedPoi> = candidates
+            .into_iter()
+            .filter(|snapped| {
+                let poi = &snapped.poi;
+                !waypoint_ids.contains(&poi.id)
+                    && !excluded_categories.is_some_and(|excluded| excluded.contains(&poi.category))
+                    && (poi.category.is_point_of_interest()
+                        || categories.is_some_and(|requested| requested.contains(&poi.category)))
+            })
+            .collect();
+
+        debug!(
+            "Snapped {} POIs to route (from {} candidates)",
+            snapped.len(),
+            candidates_count
+        );
+
+        Ok(snapped)
+    }
+
+    /// POIs within `snap_radius_m` of the path, from a search of its buffered
+    /// bounding box, ordered along the path
+    async fn snap_in_bbox(
+        &self,
+        route_path: &[Coordinates],
+        snap_radius_m: f64,
+        categories: Option<&[PoiCategory]>,
+    ) -> Result<Vec<SnappedPoi>, Box<dyn std::error::Error>> {
         let bbox = BoundingBox::from_path_with_buffer(route_path, snap_radius_m);
         debug!(
             "Calculated bbox: lat [{}, {}], lng [{}, {}]",
             bbox.min_lat, bbox.max_lat, bbox.min_lng, bbox.max_lng
         );
 
-        // Step 2: Query POIs in bounding box
         let nearby_pois = self
             .repo
             .find_in_bbox(
@@ -45,25 +95,14 @@ impl SnappingService {
                 bbox.min_lng,
                 bbox.max_lng,
                 categories,
-                500, // Reasonable limit for POIs in bbox
+                SNAP_CANDIDATE_LIMIT,
             )
             .await?;
 
         debug!("Found {} POIs in bounding box", nearby_pois.len());
 
-        // Step 3: Create set of waypoint POI IDs for deduplication
-        let waypoint_ids: HashSet<_> = waypoint_pois.iter().map(|rp| rp.poi.id).collect();
-
-        // Step 4: Filter POIs by distance to path
         let mut snapped = Vec::new();
-        let nearby_pois_count = nearby_pois.len();
-
         for poi in nearby_pois {
-            // Skip if this POI is already a waypoint
-            if waypoint_ids.contains(&poi.id) {
-                continue;
-            }
-
             // Calculate distance from POI to route path
             if let Some((dist_km, _segment, dist_along_km)) =
                 poi.coordinates.distance_to_linestring(route_path)
@@ -76,13 +115,7 @@ impl SnappingService {
             }
         }
 
-        debug!(
-            "Snapped {} POIs to route (from {} candidates)",
-            snapped.len(),
-            nearby_pois_count
-        );
-
-        // Step 5: Sort by distance along path
+        // Sort by distance along path
         snapped.sort_by(|a, b| {
             a.distance_from_start_km
                 .partial_cmp(&b.distance_from_start_km)
@@ -91,4 +124,29 @@ impl SnappingService {
 
         Ok(snapped)
     }
+
+    /// Find drinking water and toilets along a route path
+    pub async fn find_amenities(
+        &self,
+        route_path: &[Coordinates],
+        snap_radius_m: f64,
+    ) -> Result<Vec<SnappedPoi>, Box<dyn std::error::Error>> {
+        self.find_snapped_pois(
+            route_path,
+            &[],
+            snap_radius_m,
+            Some(PoiCategory::AMENITIES),
+            None,
+        )
+        .await
+    }
+
+    /// Summarize the roads under a route path from way data.
+    /// Returns `None` when the repository has no way data for the area.
+    pub async fn road_profile(
+        &self,
+        route_path: &[Coordinates],
+    ) -> Result<Option<RoadProfile>, Box<dyn std::error::Error>> {
+        Ok(self.repo.road_profile(route_path).await?)
+    }
 }
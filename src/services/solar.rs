@@ -0,0 +1,124 @@
+//! Sunrise and sunset times (NOAA sunrise equation, ~1-2 min accuracy away
+//! from the poles).
+
+use crate::models::Coordinates;
+use time::{Date, Duration, OffsetDateTime, Time};
+
+/// Sun altitude at rise/set: refraction plus the solar disc radius
+const HORIZON_ALTITUDE_DEG: f64 = -0.833;
+const AXIAL_TILT_DEG: f64 = 23.4397;
+const J2000: f64 = 2451545.0;
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2440587.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SunTimes {
+    pub sunrise: OffsetDateTime,
+    pub sunset: OffsetDateTime,
+}
+
+/// Sunrise and sunset around local solar noon on `date` at `point`, or
+/// `None` during polar day or night
+pub fn sun_times(date: Date, point: &Coordinates) -> Option<SunTimes> {
+    let noon_utc = date.with_time(Time::MIDNIGHT).assume_utc() + Duration::hours(12);
+    let julian_day = noon_utc.unix_timestamp() as f64 / 86400.0 + UNIX_EPOCH_JULIAN_DAY;
+    let day = (julian_day - J2000 + 0.0008).round();
+
+    // Mean solar noon at this longitude, in days since J2000
+    let mean_noon = day - point.lng / 360.0;
+    let anomaly = (357.5291 + 0.98560028 * mean_noon)
+        .rem_euclid(360.0)
+        .to_radians();
+    let center =
+        1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+    let ecliptic_lng = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = J2000 + mean_noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic_lng).sin();
+
+    let declination = (ecliptic_lng.sin() * AXIAL_TILT_DEG.to_radians().sin()).asin();
+    let lat = point.lat.to_radians();
+    let cos_hour_angle = (HORIZON_ALTITUDE_DEG.to_radians().sin() - lat.sin() * declination.sin())
+        / (lat.cos() * declination.cos());
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+
+    Some(SunTimes {
+        sunrise: from_julian_day(transit - half_day),
+        sunset: from_julian_day(transit + half_day),
+    })
+}
+
+/// First sunset at `point` after `after`, looking up to two days ahead
+pub fn next_sunset(after: OffsetDateTime, point: &Coordinates) -> Option<OffsetDateTime> {
+    let date = after.date();
+    (-1..=2)
+        .filter_map(|offset| sun_times(date + Duration::days(offset), point))
+        .map(|times| times.sunset)
+        .find(|sunset| *sunset > after)
+}
+
+fn from_julian_day(julian_day: f64) -> OffsetDateTime {
+    let unix_seconds = ((julian_day - UNIX_EPOCH_JULIAN_DAY) * 86400.0).round() as i64;
+    OffsetDateTime::from_unix_timestamp(unix_seconds).unwrap_or(OffsetDateTime::UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    fn utc(date: Date, hour: u8, minute: u8) -> OffsetDateTime {
+        date.with_hms(hour, minute, 0).unwrap().assume_utc()
+    }
+
+    fn assert_close(actual: OffsetDateTime, expected: OffsetDateTime) {
+        assert!(
+            (actual - expected).abs() <= Duration::minutes(3),
+            "{} vs {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_paris_summer_solstice() {
+        let date = Date::from_calendar_date(2024, Month::June, 21).unwrap();
+        let paris = Coordinates::new(48.8566, 2.3522).unwrap();
+        let times = sun_times(date, &paris).unwrap();
+        // 05:47 and 21:58 CEST
+        assert_close(times.sunrise, utc(date, 3, 47));
+        assert_close(times.sunset, utc(date, 19, 58));
+    }
+
+    #[test]
+    fn test_far_east_longitude() {
+        // Sydney, 2024-12-21: 05:41 and 20:05 AEDT (UTC+11)
+        let date = Date::from_calendar_date(2024, Month::December, 21).unwrap();
+        let sydney = Coordinates::new(-33.8688, 151.2093).unwrap();
+        let times = sun_times(date, &sydney).unwrap();
+        assert_close(times.sunrise, utc(date, 18, 41) - Duration::days(1));
+        assert_close(times.sunset, utc(date, 9, 5));
+    }
+
+    #[test]
+    fn test_polar_day_has_no_sunset() {
+        let date = Date::from_calendar_date(2024, Month::June, 21).unwrap();
+        let tromso = Coordinates::new(69.6492, 18.9553).unwrap();
+        assert_eq!(sun_times(date, &tromso), None);
+        assert_eq!(next_sunset(utc(date, 12, 0), &tromso), None);
+    }
+
+    #[test]
+    fn test_next_sunset_rolls_over_to_tomorrow() {
+        let date = Date::from_calendar_date(2024, Month::June, 21).unwrap();
+        let paris = Coordinates::new(48.8566, 2.3522).unwrap();
+        assert_close(
+            next_sunset(utc(date, 12, 0), &paris).unwrap(),
+            utc(date, 19, 58),
+        );
+        let tomorrow = next_sunset(utc(date, 21, 0), &paris).unwrap();
+        assert_eq!(tomorrow.date(), date.next_day().unwrap());
+    }
+}
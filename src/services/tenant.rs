@@ -0,0 +1,280 @@
+//! Tenant namespaces for white-label deployments.
+//!
+//! Each API key belongs to a tenant. The tenant id scopes per-tenant state:
+//! route cache entries and usage accounting here, saved routes and share
+//! links in Postgres, and anything else stored per tenant later. Keys come
+//! from `TENANT_API_KEYS`, or with `API_KEY_AUTH` from the `api_keys` table,
+//! whose rows carry their tenant. Without either every request belongs to
+//! the default tenant and no key is required.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::FromRequestParts;
+use axum::http::{header, request::Parts, HeaderMap};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::config::TenantConfig;
+use crate::error::AppError;
+use crate::models::api_key::ApiKey;
+use crate::AppState;
+
+/// Header carrying the API key (`Authorization: Bearer <key>` also works)
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+const DEFAULT_TENANT: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TenantId(String);
+
+impl TenantId {
+    pub fn new(id: impl Into<String>) -> Self {
+        TenantId(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_TENANT
+    }
+
+    /// Tenant ids are non-empty `[A-Za-z0-9_-]`
+    pub fn is_valid_id(id: &str) -> bool {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+    }
+}
+
+impl Default for TenantId {
+    fn default() -> Self {
+        TenantId::new(DEFAULT_TENANT)
+    }
+}
+
+impl std::fmt::Display for TenantId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Loop route requests served for one tenant since startup
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TenantUsage {
+    pub route_requests: u64,
+    pub cache_hits: u64,
+    pub routes_generated: u64,
+    pub failed_requests: u64,
+}
+
+/// API key lookup plus in-memory usage counters
+pub struct TenantRegistry {
+    keys: HashMap<String, TenantId>,
+    usage: Mutex<HashMap<TenantId, TenantUsage>>,
+}
+
+impl TenantRegistry {
+    /// `api_keys` are (tenant, key) pairs
+    pub fn new(api_keys: &[(String, String)]) -> Self {
+        TenantRegistry {
+            keys: api_keys
+                .iter()
+                .map(|(tenant, key)| (key.clone(), TenantId::new(tenant.clone())))
+                .collect(),
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &TenantConfig) -> Self {
+        TenantRegistry::new(&config.api_keys)
+    }
+
+    pub fn resolve(&self, api_key: &str) -> Option<&TenantId> {
+        self.keys.get(api_key)
+    }
+
+    /// Number of distinct tenants
+    pub fn tenant_count(&self) -> usize {
+        let mut tenants: Vec<_> = self.keys.values().collect();
+        tenants.sort_by(|a, b| a.0.cmp(&b.0));
+        tenants.dedup();
+        tenants.len()
+    }
+
+    pub fn record(&self, tenant: &TenantId, update: impl FnOnce(&mut TenantUsage)) {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        update(usage.entry(tenant.clone()).or_default());
+    }
+
+    pub fn usage(&self, tenant: &TenantId) -> TenantUsage {
+        let usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.get(tenant).cloned().unwrap_or_default()
+    }
+}
+
+/// Scope a cache key to a tenant. The default tenant keeps the bare key, so
+/// single-tenant deployments keep their existing cache entries.
+pub fn tenant_cache_key(tenant: &TenantId, key: &str) -> String {
+    if tenant.is_default() {
+        key.to_string()
+    } else {
+        format!("tenant:{}:{}", tenant, key)
+    }
+}
+
+/// The API key a request carries, from `x-api-key` or a bearer token
+pub(crate) fn api_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get(API_KEY_HEADER) {
+        return key.to_str().ok();
+    }
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// The tenant of the request's API key: the tenant stored with a key
+/// authenticated by `API_KEY_AUTH`, else the registry's, else the default
+/// tenant. Requests without a known key are rejected with 401 once tenants
+/// are configured.
+fn resolve_tenant(registry: Option<&TenantRegistry>, parts: &Parts) -> Result<TenantId, AppError> {
+    if let Some(key) = parts.extensions.get::<ApiKey>() {
+        return Ok(key.tenant.clone());
+    }
+    let Some(registry) = registry else {
+        return Ok(TenantId::default());
+    };
+    let key = api_key(&parts.headers)
+        .ok_or_else(|| AppError::Unauthorized("Missing API key".to_string()))?;
+    registry
+        .resolve(key)
+        .cloned()
+        .ok_or_else(|| AppError::Unauthorized("Invalid API key".to_string()))
+}
+
+/// Resolves the caller's tenant from its API key
+impl FromRequestParts<Arc<AppState>> for TenantId {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        resolve_tenant(state.tenants.as_deref(), parts)
+    }
+}
+
+/// Resolves the caller's tenant for PostgreSQL-only routes, whose registry
+/// is added to the router as an `Arc<TenantRegistry>` extension
+impl FromRequestParts<PgPool> for TenantId {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &PgPool,
+    ) -> Result<Self, Self::Rejection> {
+        let registry = parts.extensions.get::<Arc<TenantRegistry>>().cloned();
+        resolve_tenant(registry.as_deref(), parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn registry() -> TenantRegistry {
+        TenantRegistry::new(&[
+            ("acme".to_string(), "key-a1".to_string()),
+            ("acme".to_string(), "key-a2".to_string()),
+            ("globex".to_string(), "key-g".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_resolve_keys() {
+        let registry = registry();
+        assert_eq!(registry.resolve("key-a2"), Some(&TenantId::new("acme")));
+        assert_eq!(registry.resolve("key-g"), Some(&TenantId::new("globex")));
+        assert_eq!(registry.resolve("nope"), None);
+        assert_eq!(registry.tenant_count(), 2);
+    }
+
+    #[test]
+    fn test_stored_key_tenant_wins() {
+        let request = |key: Option<ApiKey>| {
+            let mut request = axum::http::Request::builder()
+                .header(API_KEY_HEADER, "key-g")
+                .body(())
+                .unwrap();
+            if let Some(key) = key {
+                request.extensions_mut().insert(key);
+            }
+            request.into_parts().0
+        };
+        let key = ApiKey {
+            id: uuid::Uuid::new_v4(),
+            user_id: "alice".to_string(),
+            tenant: TenantId::new("acme"),
+            scopes: vec![],
+        };
+
+        let parts = request(Some(key));
+        assert_eq!(resolve_tenant(None, &parts).unwrap(), TenantId::new("acme"));
+        let parts = request(None);
+        assert_eq!(resolve_tenant(None, &parts).unwrap(), TenantId::default());
+        assert_eq!(
+            resolve_tenant(Some(&registry()), &parts).unwrap(),
+            TenantId::new("globex")
+        );
+        assert!(TenantId::is_valid_id("acme_2-b"));
+        assert!(!TenantId::is_valid_id("white label"));
+        assert!(!TenantId::is_valid_id(""));
+    }
+
+    #[test]
+    fn test_usage_is_per_tenant() {
+        let registry = registry();
+        let acme = TenantId::new("acme");
+        registry.record(&acme, |u| u.route_requests += 1);
+        registry.record(&acme, |u| u.cache_hits += 1);
+
+        assert_eq!(registry.usage(&acme).route_requests, 1);
+        assert_eq!(registry.usage(&acme).cache_hits, 1);
+        assert_eq!(
+            registry.usage(&TenantId::new("globex")),
+            TenantUsage::default()
+        );
+    }
+
+    #[test]
+    fn test_tenant_cache_key() {
+        assert_eq!(
+            tenant_cache_key(&TenantId::default(), "route:loop:ab"),
+            "route:loop:ab"
+        );
+        assert_eq!(
+            tenant_cache_key(&TenantId::new("acme"), "route:loop:ab"),
+            "tenant:acme:route:loop:ab"
+        );
+    }
+
+    #[test]
+    fn test_api_key_header_or_bearer() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(api_key(&headers), None);
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer key-g"),
+        );
+        assert_eq!(api_key(&headers), Some("key-g"));
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("key-a1"));
+        assert_eq!(api_key(&headers), Some("key-a1"));
+    }
+}
@@ -0,0 +1,182 @@
+//! Time budgets for outbound calls, so a stuck dependency fails the call
+//! with [`AppError::Timeout`] instead of hanging the request.
+
+use crate::db::PoiRepository;
+use crate::error::{AppError, Result};
+use crate::models::road_profile::RoadProfile;
+use crate::models::route::SnappedPoi;
+use crate::models::{Coordinates, Poi, PoiCategory};
+use crate::services::dependency_guard::Dependency;
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// `call`, failing with [`AppError::Timeout`] once it runs past `budget`
+pub async fn with_timeout<T>(
+    dependency: Dependency,
+    budget: Duration,
+    call: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(budget, call)
+        .await
+        .unwrap_or_else(|_| {
+            Err(AppError::Timeout {
+                dependency,
+                after_ms: budget.as_millis() as u64,
+            })
+        })
+}
+
+/// POI repository decorator giving every query a Postgres time budget
+pub struct TimeoutPoiRepository {
+    inner: Arc<dyn PoiRepository>,
+    budget: Duration,
+}
+
+impl TimeoutPoiRepository {
+    pub fn new(inner: Arc<dyn PoiRepository>, budget: Duration) -> Self {
+        TimeoutPoiRepository { inner, budget }
+    }
+
+    async fn timed<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        with_timeout(Dependency::Postgres, self.budget, call).await
+    }
+}
+
+#[async_trait]
+impl PoiRepository for TimeoutPoiRepository {
+    async fn find_within_radius(
+        &self,
+        center: &Coordinates,
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        self.timed(
+            self.inner
+                .find_within_radius(center, radius_meters, categories, limit),
+        )
+        .await
+    }
+
+    async fn find_in_bbox(
+        &self,
+        min_lat: f64,
+        max_lat: f64,
+        min_lng: f64,
+        max_lng: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        self.timed(
+            self.inner
+                .find_in_bbox(min_lat, max_lat, min_lng, max_lng, categories, limit),
+        )
+        .await
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Poi>> {
+        self.timed(self.inner.find_by_ids(ids)).await
+    }
+
+    async fn find_by_osm_ids(&self, osm_ids: &[i64]) -> Result<Vec<Poi>> {
+        self.timed(self.inner.find_by_osm_ids(osm_ids)).await
+    }
+
+    async fn insert(&self, poi: &Poi) -> Result<Uuid> {
+        self.timed(self.inner.insert(poi)).await
+    }
+
+    async fn count(&self) -> Result<i64> {
+        self.timed(self.inner.count()).await
+    }
+
+    async fn road_profile(&self, path: &[Coordinates]) -> Result<Option<RoadProfile>> {
+        self.timed(self.inner.road_profile(path)).await
+    }
+
+    async fn find_near_path(
+        &self,
+        path: &[Coordinates],
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Option<Vec<SnappedPoi>>> {
+        self.timed(
+            self.inner
+                .find_near_path(path, radius_meters, categories, limit),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StuckRepository;
+
+    #[async_trait]
+    impl PoiRepository for StuckRepository {
+        async fn find_within_radius(
+            &self,
+            _center: &Coordinates,
+            _radius_meters: f64,
+            _categories: Option<&[PoiCategory]>,
+            _limit: i64,
+        ) -> Result<Vec<Poi>> {
+            std::future::pending().await
+        }
+
+        async fn find_in_bbox(
+            &self,
+            _min_lat: f64,
+            _max_lat: f64,
+            _min_lng: f64,
+            _max_lng: f64,
+            _categories: Option<&[PoiCategory]>,
+            _limit: i64,
+        ) -> Result<Vec<Poi>> {
+            std::future::pending().await
+        }
+
+        async fn find_by_ids(&self, _ids: &[Uuid]) -> Result<Vec<Poi>> {
+            Ok(Vec::new())
+        }
+
+        async fn find_by_osm_ids(&self, _osm_ids: &[i64]) -> Result<Vec<Poi>> {
+            Ok(Vec::new())
+        }
+
+        async fn insert(&self, _poi: &Poi) -> Result<Uuid> {
+            std::future::pending().await
+        }
+
+        async fn count(&self) -> Result<i64> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stuck_queries_time_out() {
+        let repo = TimeoutPoiRepository::new(Arc::new(StuckRepository), Duration::from_millis(10));
+        let center = Coordinates::new(48.8566, 2.3522).unwrap();
+
+        let err = repo
+            .find_within_radius(&center, 1000.0, None, 10)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::Timeout {
+                dependency: Dependency::Postgres,
+                after_ms: 10
+            }
+        ));
+        assert_eq!(Dependency::of_error(&err), Some(Dependency::Postgres));
+        // Calls finishing within budget pass through
+        assert!(repo.find_by_ids(&[]).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,345 @@
+//! Client for a Valhalla routing server (`POST /route`), an alternative to
+//! Mapbox that honours the request's [`CostingOptions`].
+
+use crate::constants::VALHALLA_REQUEST_TIMEOUT_SECS;
+use crate::error::{AppError, GenerationFailure, Result};
+use crate::models::{polyline, Coordinates, CostingOptions, SurfacePreference, TransportMode};
+use crate::services::dependency_guard::Dependency;
+use crate::services::directions::DirectionsProvider;
+use crate::services::mapbox::{DirectionsLeg, DirectionsResponse};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+/// Valhalla encodes shapes as polylines with 6 decimal places
+const SHAPE_PRECISION: f64 = 1e6;
+
+/// `error_code`s for waypoints Valhalla can't route between: unconnected
+/// regions, no usable edge near a location, no path found
+const UNROUTABLE_ERROR_CODES: [u32; 3] = [170, 171, 442];
+
+pub struct ValhallaClient {
+    client: Client,
+    base_url: String,
+}
+
+#[derive(Deserialize)]
+struct RouteResponse {
+    trip: Trip,
+}
+
+#[derive(Deserialize)]
+struct Trip {
+    legs: Vec<TripLeg>,
+    summary: Summary,
+}
+
+#[derive(Deserialize)]
+struct TripLeg {
+    shape: String,
+    summary: Summary,
+}
+
+/// Body of a 4xx answer
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error_code: u32,
+    #[serde(default)]
+    error: String,
+}
+
+#[derive(Deserialize)]
+struct Summary {
+    /// Kilometers, as requested in `directions_options.units`
+    length: f64,
+    /// Seconds
+    time: f64,
+}
+
+impl ValhallaClient {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(VALHALLA_REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+        ValhallaClient {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+/// Request body for `mode` with `costing` and `surface` applied over the
+/// mode's defaults
+fn route_request(
+    waypoints: &[Coordinates],
+    mode: &TransportMode,
+    costing: &CostingOptions,
+    surface: SurfacePreference,
+) -> Value {
+    let mut options = Map::new();
+    let model = if mode.is_cycling() {
+        let bicycle_type = match mode {
+            TransportMode::RoadBike => "Road",
+            TransportMode::Gravel => "Cross",
+            _ => "Hybrid",
+        };
+        options.insert("bicycle_type".into(), json!(bicycle_type));
+        if costing.avoid_highways {
+            options.insert("use_roads".into(), json!(0.0));
+        }
+        match surface {
+            SurfacePreference::Paved => {
+                options.insert("avoid_bad_surfaces".into(), json!(1.0));
+            }
+            SurfacePreference::Trail => {
+                options.insert("avoid_bad_surfaces".into(), json!(0.0));
+            }
+            SurfacePreference::Mixed => {}
+        }
+        "bicycle"
+    } else {
+        if let Some(factor) = costing.walkway_factor {
+            options.insert("walkway_factor".into(), json!(factor));
+        }
+        match surface {
+            SurfacePreference::Paved => {
+                options.insert("use_tracks".into(), json!(0.0));
+            }
+            SurfacePreference::Trail => {
+                options.insert("use_tracks".into(), json!(1.0));
+            }
+            SurfacePreference::Mixed => {}
+        }
+        "pedestrian"
+    };
+    // Road cyclists skip ferries unless asked, as with Mapbox's `exclude`
+    let use_ferry = costing
+        .use_ferries
+        .or_else(|| (*mode == TransportMode::RoadBike).then_some(0.0));
+    if let Some(use_ferry) = use_ferry {
+        options.insert("use_ferry".into(), json!(use_ferry));
+    }
+
+    json!({
+        "locations": waypoints
+            .iter()
+            .map(|c| json!({"lat": c.lat, "lon": c.lng}))
+            .collect::<Vec<_>>(),
+        "costing": model,
+        "costing_options": { (model): options },
+        "directions_options": { "units": "kilometers" },
+        "directions_type": "none",
+    })
+}
+
+fn backend_error(message: String) -> AppError {
+    AppError::RoutingBackend {
+        dependency: Dependency::Valhalla,
+        message,
+    }
+}
+
+/// A routing failure for the attempt when Valhalla found no path, an
+/// outage otherwise
+fn error_for(status: reqwest::StatusCode, body: &str) -> AppError {
+    match serde_json::from_str::<ErrorResponse>(body) {
+        Ok(error) if UNROUTABLE_ERROR_CODES.contains(&error.error_code) => {
+            tracing::debug!(
+                error_code = error.error_code,
+                "Valhalla found no route: {}",
+                error.error
+            );
+            AppError::GenerationFailed(GenerationFailure::Unroutable)
+        }
+        _ => {
+            tracing::warn!(status = %status, "Valhalla HTTP error {}: {}", status, body);
+            backend_error(format!("HTTP {}: {}", status, body))
+        }
+    }
+}
+
+fn to_directions(trip: Trip) -> std::result::Result<DirectionsResponse, String> {
+    let mut geometry = Vec::new();
+    for leg in &trip.legs {
+        geometry.extend(polyline::decode(&leg.shape, SHAPE_PRECISION)?);
+    }
+    DirectionsResponse {
+        distance_meters: trip.summary.length * 1000.0,
+        duration_seconds: trip.summary.time,
+        geometry,
+        legs: trip
+            .legs
+            .iter()
+            .map(|leg| DirectionsLeg {
+                distance_meters: leg.summary.length * 1000.0,
+                duration_seconds: leg.summary.time,
+            })
+            .collect(),
+    }
+    .sanitized()
+}
+
+#[async_trait]
+impl DirectionsProvider for ValhallaClient {
+    fn name(&self) -> &str {
+        "valhalla"
+    }
+
+    fn dependency(&self) -> Dependency {
+        Dependency::Valhalla
+    }
+
+    async fn directions(
+        &self,
+        waypoints: &[Coordinates],
+        mode: &TransportMode,
+        costing: &CostingOptions,
+        surface: SurfacePreference,
+    ) -> Result<DirectionsResponse> {
+        if waypoints.len() < 2 {
+            return Err(AppError::InvalidRequest(
+                "At least 2 waypoints required".to_string(),
+            ));
+        }
+
+        let unavailable = |e: reqwest::Error| backend_error(format!("Request failed: {}", e));
+        let response = self
+            .client
+            .post(format!("{}/route", self.base_url))
+            .json(&route_request(waypoints, mode, costing, surface))
+            .send()
+            .await
+            .map_err(unavailable)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(error_for(status, &error_text));
+        }
+
+        let route: RouteResponse = response.json().await.map_err(unavailable)?;
+        to_directions(route.trip).map_err(|e| {
+            tracing::warn!(
+                error = %e,
+                waypoints = waypoints.len(),
+                "Rejected Valhalla route geometry: {}",
+                e
+            );
+            backend_error(format!("Degenerate route: {}", e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_request_costing() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let end = Coordinates::new(48.86, 2.36).unwrap();
+        let costing = CostingOptions {
+            avoid_highways: true,
+            use_ferries: None,
+            walkway_factor: Some(0.5),
+        };
+        let mixed = SurfacePreference::Mixed;
+
+        let walk = route_request(&[start, end], &TransportMode::Walk, &costing, mixed);
+        assert_eq!(walk["costing"], "pedestrian");
+        assert_eq!(walk["costing_options"]["pedestrian"]["walkway_factor"], 0.5);
+        assert_eq!(walk["locations"][1]["lon"], 2.36);
+
+        let road = route_request(&[start, end], &TransportMode::RoadBike, &costing, mixed);
+        let options = &road["costing_options"]["bicycle"];
+        assert_eq!(options["bicycle_type"], "Road");
+        assert_eq!(options["use_roads"], 0.0);
+        assert_eq!(options["use_ferry"], 0.0);
+        assert!(options.get("walkway_factor").is_none());
+        assert!(options.get("avoid_bad_surfaces").is_none());
+    }
+
+    #[test]
+    fn test_route_request_surface() {
+        let start = Coordinates::new(48.8566, 2.3522).unwrap();
+        let end = Coordinates::new(48.86, 2.36).unwrap();
+        let costing = CostingOptions::default();
+
+        let paved = route_request(
+            &[start, end],
+            &TransportMode::Bike,
+            &costing,
+            SurfacePreference::Paved,
+        );
+        assert_eq!(
+            paved["costing_options"]["bicycle"]["avoid_bad_surfaces"],
+            1.0
+        );
+        let trail = route_request(
+            &[start, end],
+            &TransportMode::Walk,
+            &costing,
+            SurfacePreference::Trail,
+        );
+        assert_eq!(trail["costing_options"]["pedestrian"]["use_tracks"], 1.0);
+        let mixed = route_request(
+            &[start, end],
+            &TransportMode::Walk,
+            &costing,
+            SurfacePreference::Mixed,
+        );
+        assert!(mixed["costing_options"]["pedestrian"]
+            .get("use_tracks")
+            .is_none());
+    }
+
+    #[test]
+    fn test_no_path_is_a_routing_failure() {
+        let no_path = r#"{"error_code":442,"error":"No path could be found for input",
+            "status_code":400,"status":"Bad Request"}"#;
+        assert!(matches!(
+            error_for(reqwest::StatusCode::BAD_REQUEST, no_path),
+            AppError::GenerationFailed(GenerationFailure::Unroutable)
+        ));
+
+        let bad_costing = r#"{"error_code":125,"error":"No costing method found"}"#;
+        for (status, body) in [
+            (reqwest::StatusCode::BAD_REQUEST, bad_costing),
+            (reqwest::StatusCode::BAD_GATEWAY, "<html>502</html>"),
+        ] {
+            assert!(matches!(
+                error_for(status, body),
+                AppError::RoutingBackend {
+                    dependency: Dependency::Valhalla,
+                    ..
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_route_response_conversion() {
+        let body = r#"{"trip": {"status": 0, "units": "kilometers",
+            "legs": [
+                {"shape": "o`~d|AocqnCo}@o}@", "summary": {"length": 0.15, "time": 108.0}},
+                {"shape": "__`e|A_bsnCo}@n}@", "summary": {"length": 0.15, "time": 108.0}}
+            ],
+            "summary": {"length": 0.3, "time": 216.0}}}"#;
+        let route: RouteResponse = serde_json::from_str(body).unwrap();
+        let directions = to_directions(route.trip).unwrap();
+        assert_eq!(directions.distance_meters, 300.0);
+        assert_eq!(directions.duration_minutes(), 4);
+        assert_eq!(directions.legs.len(), 2);
+        // The point shared by both legs appears once
+        assert_eq!(
+            directions.geometry,
+            vec![[2.3522, 48.8566], [2.3532, 48.8576], [2.3522, 48.8586]]
+        );
+    }
+}
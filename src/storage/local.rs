@@ -0,0 +1,201 @@
+use super::{unix_now, validate_key, ObjectStore};
+use crate::error::{AppError, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Artifacts stored as files under a directory. Signed URLs point at this
+/// server's `/api/v1/artifacts/{key}` endpoint and carry an HMAC over the key
+/// and expiry.
+pub struct LocalDirStore {
+    root: PathBuf,
+    public_base_url: String,
+    signing_key: Vec<u8>,
+}
+
+impl LocalDirStore {
+    pub fn new(
+        root: impl AsRef<Path>,
+        public_base_url: &str,
+        signing_key: Vec<u8>,
+    ) -> Result<Self> {
+        if signing_key.is_empty() {
+            return Err(AppError::Storage(
+                "artifact signing key must not be empty".to_string(),
+            ));
+        }
+        Ok(LocalDirStore {
+            root: root.as_ref().to_path_buf(),
+            public_base_url: public_base_url.trim_end_matches('/').to_string(),
+            signing_key,
+        })
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+
+    fn mac(&self, key: &str, expires: u64) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.signing_key).expect("HMAC accepts any key length");
+        mac.update(key.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+}
+
+fn io_error(action: &str, key: &str, e: std::io::Error) -> AppError {
+    AppError::Storage(format!("failed to {} artifact '{}': {}", action, key, e))
+}
+
+#[async_trait]
+impl ObjectStore for LocalDirStore {
+    async fn put(&self, key: &str, data: Vec<u8>, _content_type: &str) -> Result<()> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error("store", key, e))?;
+        }
+        // Write then rename so readers never see a partial file
+        let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&tmp, data)
+            .await
+            .map_err(|e| io_error("store", key, e))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| io_error("store", key, e))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path_for(key)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error("read", key, e)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(io_error("delete", key, e)),
+        }
+    }
+
+    fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String> {
+        validate_key(key)?;
+        let expires = unix_now() + expires_in.as_secs();
+        let signature = hex::encode(self.mac(key, expires).finalize().into_bytes());
+        Ok(format!(
+            "{}/api/v1/artifacts/{}?expires={}&signature={}",
+            self.public_base_url, key, expires, signature
+        ))
+    }
+
+    fn verify_signature(&self, key: &str, expires: u64, signature: &str) -> bool {
+        if expires < unix_now() {
+            return false;
+        }
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        // Constant-time comparison
+        self.mac(key, expires).verify_slice(&signature).is_ok()
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "local"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(dir: &Path) -> LocalDirStore {
+        LocalDirStore::new(dir, "http://localhost:3000/", b"test-secret".to_vec()).unwrap()
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("easyroute-artifacts-{}", uuid::Uuid::new_v4()))
+    }
+
+    /// (key, expires, signature) from a signed URL
+    fn parse_signed_url(url: &str) -> (String, u64, String) {
+        let rest = url
+            .strip_prefix("http://localhost:3000/api/v1/artifacts/")
+            .unwrap();
+        let (key, query) = rest.split_once('?').unwrap();
+        let mut expires = 0;
+        let mut signature = String::new();
+        for pair in query.split('&') {
+            match pair.split_once('=').unwrap() {
+                ("expires", v) => expires = v.parse().unwrap(),
+                ("signature", v) => signature = v.to_string(),
+                _ => {}
+            }
+        }
+        (key.to_string(), expires, signature)
+    }
+
+    #[tokio::test]
+    async fn test_put_get_delete_roundtrip() {
+        let dir = temp_dir();
+        let store = store(&dir);
+
+        assert_eq!(store.get("traces/a.json").await.unwrap(), None);
+        store
+            .put("traces/a.json", b"{}".to_vec(), "application/json")
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get("traces/a.json").await.unwrap(),
+            Some(b"{}".to_vec())
+        );
+
+        store.delete("traces/a.json").await.unwrap();
+        assert_eq!(store.get("traces/a.json").await.unwrap(), None);
+        // Deleting twice is fine
+        store.delete("traces/a.json").await.unwrap();
+
+        assert!(store.put("../escape", vec![], "text/plain").await.is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_signed_url_verifies_key_and_expiry() {
+        let store = store(&temp_dir());
+        let url = store
+            .signed_url("gpx/route.gpx", Duration::from_secs(60))
+            .unwrap();
+        let (key, expires, signature) = parse_signed_url(&url);
+        assert_eq!(key, "gpx/route.gpx");
+        assert!(store.verify_signature(&key, expires, &signature));
+
+        // Signature is bound to the key and the expiry
+        assert!(!store.verify_signature("gpx/other.gpx", expires, &signature));
+        assert!(!store.verify_signature(&key, expires + 60, &signature));
+        assert!(!store.verify_signature(&key, expires, "not-hex"));
+
+        // A different signing key rejects it
+        let other =
+            LocalDirStore::new(temp_dir(), "http://localhost:3000", b"other".to_vec()).unwrap();
+        assert!(!other.verify_signature(&key, expires, &signature));
+    }
+
+    #[test]
+    fn test_expired_signature_rejected() {
+        let store = store(&temp_dir());
+        let expired = unix_now() - 1;
+        let signature = hex::encode(store.mac("a.json", expired).finalize().into_bytes());
+        assert!(!store.verify_signature("a.json", expired, &signature));
+    }
+}
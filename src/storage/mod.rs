@@ -0,0 +1,136 @@
+//! Object storage for large artifacts (generation traces, GPX exports, map
+//! previews) that should not live in Postgres.
+//!
+//! Clients never fetch artifacts through the API with credentials: a backend
+//! hands out a time-limited signed URL instead. S3-compatible stores sign
+//! with SigV4 and serve the object themselves; the local directory store
+//! signs with HMAC and objects are served by `GET /artifacts/{*key}`.
+
+pub(crate) mod local;
+pub(crate) mod s3;
+
+pub use local::LocalDirStore;
+pub use s3::S3Store;
+
+use crate::config::ArtifactStoreConfig;
+use crate::error::{AppError, Result};
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Trait for artifact storage backends. All methods take `&self`.
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<()>;
+    /// `None` when no object is stored under `key`
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    /// Deleting a missing object is not an error
+    async fn delete(&self, key: &str) -> Result<()>;
+    /// URL that fetches the object without credentials until `expires_in` elapses
+    fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String>;
+    /// Check a signature issued by [`signed_url`](Self::signed_url). Only
+    /// backends whose URLs point back at this server verify anything.
+    fn verify_signature(&self, _key: &str, _expires: u64, _signature: &str) -> bool {
+        false
+    }
+    fn backend_name(&self) -> &'static str;
+}
+
+/// Build the configured backend
+pub fn from_config(config: &ArtifactStoreConfig) -> Result<Arc<dyn ObjectStore>> {
+    Ok(match config {
+        ArtifactStoreConfig::Local {
+            dir,
+            public_base_url,
+            signing_key,
+        } => {
+            let signing_key = match signing_key {
+                Some(key) => key.as_bytes().to_vec(),
+                None => {
+                    tracing::warn!(
+                        "ARTIFACT_SIGNING_KEY not set: using a random key, signed artifact URLs will not survive a restart"
+                    );
+                    rand::random::<[u8; 32]>().to_vec()
+                }
+            };
+            Arc::new(LocalDirStore::new(dir, public_base_url, signing_key)?)
+        }
+        ArtifactStoreConfig::S3(s3_config) => Arc::new(S3Store::new(s3_config.clone())),
+    })
+}
+
+/// Keys are relative, `/`-separated paths of `[A-Za-z0-9._-]` segments, so
+/// they are safe both as file paths and unencoded URL paths.
+pub fn validate_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && key.len() <= 512
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidRequest(format!(
+            "Invalid artifact key '{}'",
+            key
+        )))
+    }
+}
+
+/// Content type served for a key, from its extension
+pub fn content_type_for_key(key: &str) -> &'static str {
+    match key.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("json") => "application/json",
+        Some("geojson") => "application/geo+json",
+        Some("gpx") => "application/gpx+xml",
+        Some("ndjson") => "application/x-ndjson",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("svg") => "image/svg+xml",
+        Some("gz") => "application/gzip",
+        _ => "application/octet-stream",
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_key() {
+        for key in ["traces/2024/abc.json", "a", "gpx/route-1_v2.gpx"] {
+            assert!(validate_key(key).is_ok(), "{key}");
+        }
+        for key in [
+            "",
+            "/abs",
+            "a//b",
+            "../etc/passwd",
+            "a/./b",
+            "a b",
+            "a?b",
+            "trail/",
+        ] {
+            assert!(validate_key(key).is_err(), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_content_type_for_key() {
+        assert_eq!(content_type_for_key("x/route.gpx"), "application/gpx+xml");
+        assert_eq!(content_type_for_key("preview.png"), "image/png");
+        assert_eq!(content_type_for_key("blob"), "application/octet-stream");
+    }
+}
@@ -16,11 +16,13 @@ use tower::ServiceExt;
 
 mod common;
 
-async fn setup_test_app() -> axum::Router {
-    let pool = common::setup_test_db().await;
+/// The app over its own test schema; call `db.cleanup()` when done
+async fn setup_test_app() -> (axum::Router, common::TestDb) {
+    let db = common::TestDb::new().await;
     let config = common::get_test_config();
 
-    let poi_repo: Arc<dyn easyroute::db::PoiRepository> = Arc::new(PgPoiRepository::new(pool));
+    let poi_repo: Arc<dyn easyroute::db::PoiRepository> =
+        Arc::new(PgPoiRepository::new(db.pool.clone()));
     let mapbox_client = MapboxClient::new(config.mapbox_api_key.clone());
     let poi_service = PoiService::new(poi_repo.clone());
     let snapping_service = SnappingService::new(poi_repo.clone());
@@ -36,15 +38,22 @@ async fn setup_test_app() -> axum::Router {
         poi_repo,
         route_generator,
         cache: None, // No Redis cache in tests
+        shadow: None,
+        request_log: None,
+        privacy: None,
+        tenants: None,
+        guard: Default::default(),
+        events: Default::default(),
+        artifacts: None,
     });
 
-    easyroute::routes::create_router(state)
+    (easyroute::routes::create_router(state), db)
 }
 
 #[tokio::test]
-#[ignore]
+#[cfg_attr(not(feature = "integration"), ignore)]
 async fn test_health_check_endpoint() {
-    let app = setup_test_app().await;
+    let (app, db) = setup_test_app().await;
 
     let request = Request::builder()
         .uri("/debug/health")
@@ -62,12 +71,13 @@ async fn test_health_check_endpoint() {
 
     assert_eq!(json["status"], "ok");
     assert!(json["checks"]["database"].is_string() || json["checks"]["database"].is_object());
+    db.cleanup().await;
 }
 
 #[tokio::test]
-#[ignore]
+#[cfg_attr(not(feature = "integration"), ignore)]
 async fn test_loop_route_endpoint_validation() {
-    let app = setup_test_app().await;
+    let (app, db) = setup_test_app().await;
 
     // Test with invalid distance (too small)
     let invalid_request = json!({
@@ -91,6 +101,36 @@ async fn test_loop_route_endpoint_validation() {
         StatusCode::BAD_REQUEST,
         "Should reject invalid distance"
     );
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_loop_route_stream_rejects_before_streaming() {
+    let (app, db) = setup_test_app().await;
+
+    let invalid_request = json!({
+        "start_point": {"lat": 48.8566, "lng": 2.3522},
+        "distance_km": 0.1,
+        "mode": "walk"
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/routes/loop/stream")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&invalid_request).unwrap()))
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    // Validation errors are plain JSON, not an event stream
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/json",
+        "Should not open a stream for an invalid request"
+    );
+    db.cleanup().await;
 }
 
 #[tokio::test]
@@ -110,7 +150,7 @@ async fn test_loop_route_request_deserialization() {
     let request: LoopRouteRequest = serde_json::from_value(json_data).unwrap();
 
     assert_eq!(request.start_point.lat, 48.8566);
-    assert_eq!(request.distance_km, 5.0);
+    assert_eq!(request.distance_km, Some(5.0));
     assert_eq!(request.mode, TransportMode::Walk);
     assert_eq!(request.preferences.max_alternatives, 2);
 }
@@ -153,8 +193,26 @@ async fn test_route_preferences_serialization() {
             easyroute::models::PoiCategory::Monument,
             easyroute::models::PoiCategory::Park,
         ]),
+        excluded_poi_categories: None,
         hidden_gems: true,
         max_alternatives: 5,
+        poi_min_separation_km: None,
+        pace_min_per_km: None,
+        min_paved_fraction: None,
+        max_busy_road_fraction: None,
+        surface: Default::default(),
+        prefer_green: false,
+        step_free: false,
+        minimize_exposure: false,
+        max_elevation_gain_m: None,
+        prefer_flat: false,
+        costing: Default::default(),
+        include_visit_time: false,
+        golden_hour: false,
+        departure: None,
+        pareto: false,
+        seed: None,
+        must_include: Vec::new(),
     };
 
     let json = serde_json::to_value(&prefs).unwrap();
@@ -0,0 +1,220 @@
+//! Fallback ladder under injected faults. Runs offline: directions come from a
+//! local mock server and POIs from an in-memory repository.
+
+use axum::extract::{Path, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use easyroute::config::{ChaosConfig, DegradationConfig, FallbackPolicy, FaultRates};
+use easyroute::db::PoiRepository;
+use easyroute::models::{Coordinates, RoutePreferences, TransportMode};
+use easyroute::services::chaos::{FaultInjector, FaultyPoiRepository};
+use easyroute::services::dependency_guard::{Dependency, DependencyGuard};
+use easyroute::services::mapbox::{AuthMode, MapboxClient};
+use easyroute::services::poi_service::PoiService;
+use easyroute::services::route_generator::RouteGenerator;
+use easyroute::services::snapping_service::SnappingService;
+use easyroute::{AppError, GenerationFailure, Result};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+mod common;
+
+use common::mock::{ring_of_pois, start, straight_line_directions, MemoryPoiRepository};
+
+/// Mapbox-shaped straight-line directions
+async fn mock_directions(
+    State(hits): State<Arc<AtomicUsize>>,
+    Path((_profile, coordinates)): Path<(String, String)>,
+) -> Json<Value> {
+    hits.fetch_add(1, Ordering::SeqCst);
+    let waypoints: Vec<Coordinates> = coordinates
+        .split(';')
+        .map(|pair| {
+            let (lng, lat) = pair.split_once(',').unwrap();
+            Coordinates::new(lat.parse().unwrap(), lng.parse().unwrap()).unwrap()
+        })
+        .collect();
+
+    let directions = straight_line_directions(&waypoints);
+    let legs: Vec<Value> = directions
+        .legs
+        .iter()
+        .map(|leg| json!({ "distance": leg.distance_meters, "duration": leg.duration_seconds }))
+        .collect();
+
+    Json(json!({
+        "code": "Ok",
+        "routes": [{
+            "distance": directions.distance_meters,
+            "duration": directions.duration_seconds,
+            "geometry": { "type": "LineString", "coordinates": directions.geometry },
+            "legs": legs,
+        }],
+    }))
+}
+
+/// Start the mock server; returns its base URL and request counter
+async fn start_mock_mapbox() -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/{profile}/{coordinates}", get(mock_directions))
+        .with_state(Arc::clone(&hits));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", addr), hits)
+}
+
+fn generator(
+    base_url: &str,
+    chaos: ChaosConfig,
+    degradation: DegradationConfig,
+) -> (RouteGenerator, Arc<FaultInjector>) {
+    let faults = Arc::new(FaultInjector::new(chaos));
+    let memory: Arc<dyn PoiRepository> = Arc::new(MemoryPoiRepository {
+        pois: ring_of_pois(),
+    });
+    let repo: Arc<dyn PoiRepository> =
+        Arc::new(FaultyPoiRepository::new(memory, Arc::clone(&faults)));
+    let mapbox = MapboxClient::with_config(
+        "test-token".to_string(),
+        base_url.to_string(),
+        AuthMode::BearerHeader,
+    )
+    .with_fault_injector(Arc::clone(&faults));
+    let generator = RouteGenerator::new(
+        mapbox,
+        PoiService::new(repo.clone()),
+        SnappingService::new(repo),
+        100.0,
+        Default::default(),
+    )
+    .with_dependency_guard(Arc::new(DependencyGuard::new(degradation)));
+    (generator, faults)
+}
+
+async fn generate(generator: &RouteGenerator) -> Result<Vec<easyroute::models::Route>> {
+    generator
+        .generate_loop_route(
+            start(),
+            5.0,
+            0.5,
+            &TransportMode::Walk,
+            &RoutePreferences::default(),
+        )
+        .await
+}
+
+fn mapbox_faults(mapbox: FaultRates) -> ChaosConfig {
+    ChaosConfig {
+        seed: 7,
+        timeout_ms: 1,
+        mapbox,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_ladder_falls_through_to_geometric_loop() {
+    let (base_url, hits) = start_mock_mapbox().await;
+
+    // Every Mapbox call fails: the whole ladder is tried, then the request
+    // fails with the backend as the reason
+    let (always_failing, faults) = generator(
+        &base_url,
+        mapbox_faults(FaultRates {
+            error_rate: 1.0,
+            ..Default::default()
+        }),
+        DegradationConfig::default(),
+    );
+    assert!(matches!(
+        generate(&always_failing).await,
+        Err(AppError::GenerationFailed(
+            GenerationFailure::BackendUnavailable
+        ))
+    ));
+    let ladder_calls = faults.calls(Dependency::Mapbox);
+    assert!(ladder_calls > 1, "only {} Mapbox calls", ladder_calls);
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+    // Every call but the last fails: tolerance levels and extreme tolerance
+    // come up empty and the geometric loop (one call) succeeds
+    let (geometric_only, faults) = generator(
+        &base_url,
+        mapbox_faults(FaultRates {
+            fail_first: ladder_calls - 1,
+            ..Default::default()
+        }),
+        DegradationConfig::default(),
+    );
+    let routes = generate(&geometric_only).await.unwrap();
+    assert_eq!(routes.len(), 1);
+    assert_eq!(faults.calls(Dependency::Mapbox), ladder_calls);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_postgres_faults_follow_fallback_policy() {
+    let (base_url, hits) = start_mock_mapbox().await;
+    let postgres_down = ChaosConfig {
+        postgres: FaultRates {
+            error_rate: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let (failing, _) = generator(
+        &base_url,
+        postgres_down.clone(),
+        DegradationConfig::default(),
+    );
+    assert!(matches!(
+        generate(&failing).await,
+        Err(AppError::Database(_))
+    ));
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+    let (degrading, faults) = generator(
+        &base_url,
+        postgres_down,
+        DegradationConfig {
+            postgres: FallbackPolicy::Degrade,
+            ..Default::default()
+        },
+    );
+    let routes = generate(&degrading).await.unwrap();
+    // Straight to the geometric loop
+    assert_eq!(routes.len(), 1);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    assert!(faults.injected(Dependency::Postgres) >= 1);
+}
+
+#[tokio::test]
+async fn test_latency_and_timeouts_are_injected() {
+    let (base_url, _) = start_mock_mapbox().await;
+    let faults = Arc::new(FaultInjector::new(ChaosConfig {
+        timeout_ms: 50,
+        mapbox: FaultRates {
+            latency_ms: 20,
+            timeout_rate: 1.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    }));
+    let mapbox =
+        MapboxClient::with_config("test-token".to_string(), base_url, AuthMode::BearerHeader)
+            .with_fault_injector(faults);
+    let started = std::time::Instant::now();
+    let err = mapbox
+        .get_directions(
+            &[start(), Coordinates::new(48.86, 2.36).unwrap()],
+            &TransportMode::Walk,
+        )
+        .await
+        .unwrap_err();
+    assert!(started.elapsed() >= std::time::Duration::from_millis(70));
+    assert!(err.to_string().contains("timed out"));
+}
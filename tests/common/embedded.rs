@@ -0,0 +1,144 @@
+//! A throwaway Postgres cluster for `cargo test --features integration`, so
+//! the database suite runs without Docker or a hand-made test database.
+//!
+//! The first test needing a database starts one cluster for the whole test
+//! binary: `initdb` into a temp dir, `pg_ctl start` on a free local port,
+//! `createdb easyroute_test`. Binaries come from `PG_BIN_DIR`, else `PATH`,
+//! and PostGIS must be installed for that Postgres (e.g. the
+//! `postgresql-15-postgis-3` package). A watchdog stops the cluster and
+//! deletes its directory once the test process exits.
+//!
+//! Postgres refuses to run as root, so run the suite as a regular user.
+
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+const DATABASE: &str = "easyroute_test";
+const SUPERUSER: &str = "postgres";
+
+struct Cluster {
+    url: String,
+}
+
+static CLUSTER: OnceLock<Cluster> = OnceLock::new();
+
+/// URL of the per-run test database, starting the cluster on first use
+pub fn database_url() -> String {
+    CLUSTER.get_or_init(start).url.clone()
+}
+
+fn bin(name: &str) -> PathBuf {
+    match std::env::var_os("PG_BIN_DIR") {
+        Some(dir) => Path::new(&dir).join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+/// Run `command`, panicking with its output if it fails
+fn run(command: &mut Command, what: &str) -> String {
+    let output = command
+        .output()
+        .unwrap_or_else(|e| panic!("{what}: could not run {:?}: {e}", command.get_program()));
+    if !output.status.success() {
+        panic!(
+            "{what} failed ({}):\n{}{}",
+            output.status,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("No free local port for the test cluster")
+        .port()
+}
+
+fn start() -> Cluster {
+    let share_dir = run(
+        Command::new(bin("pg_config")).arg("--sharedir"),
+        "pg_config",
+    );
+    let postgis = Path::new(&share_dir).join("extension/postgis.control");
+    assert!(
+        postgis.exists(),
+        "PostGIS is not installed for this Postgres ({} missing); install it or set PG_BIN_DIR",
+        postgis.display()
+    );
+
+    let root = std::env::temp_dir().join(format!("easyroute-pg-{}", uuid::Uuid::new_v4()));
+    let data = root.join("data");
+    std::fs::create_dir_all(&root).expect("Failed to create the test cluster directory");
+    run(
+        Command::new(bin("initdb")).arg("-D").arg(&data).args([
+            "-U",
+            SUPERUSER,
+            "--auth=trust",
+            "-E",
+            "UTF8",
+            "--no-sync",
+        ]),
+        "initdb",
+    );
+
+    let port = free_port();
+    // Durability is pointless for a database deleted after the run
+    let options = format!(
+        "-p {port} -k {} -c listen_addresses=127.0.0.1 -c fsync=off -c synchronous_commit=off -c full_page_writes=off",
+        root.display()
+    );
+    run(
+        Command::new(bin("pg_ctl"))
+            .arg("-D")
+            .arg(&data)
+            .arg("-l")
+            .arg(root.join("postgres.log"))
+            .args(["-o", &options, "-w", "start"]),
+        "pg_ctl start",
+    );
+    spawn_watchdog(&root, &data);
+
+    run(
+        Command::new(bin("createdb")).args([
+            "-h",
+            "127.0.0.1",
+            "-p",
+            &port.to_string(),
+            "-U",
+            SUPERUSER,
+            DATABASE,
+        ]),
+        "createdb",
+    );
+
+    Cluster {
+        url: format!("postgres://{SUPERUSER}@127.0.0.1:{port}/{DATABASE}"),
+    }
+}
+
+/// Statics are never dropped, so a detached shell waits for this process
+/// to exit, then stops the cluster and removes it. It must outlive us, so
+/// it is never waited on.
+#[allow(clippy::zombie_processes)]
+fn spawn_watchdog(root: &Path, data: &Path) {
+    let script = format!(
+        "while kill -0 {pid} 2>/dev/null; do sleep 1; done; \
+         '{pg_ctl}' -D '{data}' -m immediate stop >/dev/null 2>&1; rm -rf '{root}'",
+        pid = std::process::id(),
+        pg_ctl = bin("pg_ctl").display(),
+        data = data.display(),
+        root = root.display(),
+    );
+    Command::new("sh")
+        .args(["-c", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start the test cluster watchdog");
+}
@@ -0,0 +1,343 @@
+//! In-process stand-ins for the database and the directions API, so tests can
+//! run the generator (or the whole router) offline.
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use easyroute::cache::memory::MemoryCacheService;
+use easyroute::cache::RouteCache;
+use easyroute::db::PoiRepository;
+use easyroute::models::{
+    Coordinates, CostingOptions, Poi, PoiCategory, SurfacePreference, TransportMode,
+};
+use easyroute::services::directions::DirectionsProvider;
+use easyroute::services::mapbox::{DirectionsLeg, DirectionsResponse};
+use easyroute::services::request_log::{RequestLogSink, RequestLogger};
+use easyroute::services::route_generator::RouteGenerator;
+use easyroute::AppState;
+use easyroute::{AppError, Result};
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Walking speed of the straight-line directions, in m/s
+const MOCK_SPEED_MPS: f64 = 1.4;
+/// Longest segment of the straight-line geometry, below the jump check
+const MOCK_STEP_M: f64 = 200.0;
+
+/// Read-only POIs held in memory
+pub struct MemoryPoiRepository {
+    pub pois: Vec<Poi>,
+}
+
+impl MemoryPoiRepository {
+    fn matching<'a>(
+        &'a self,
+        categories: Option<&'a [PoiCategory]>,
+    ) -> impl Iterator<Item = &'a Poi> + 'a {
+        self.pois
+            .iter()
+            .filter(move |p| categories.map_or(true, |c| c.contains(&p.category)))
+    }
+}
+
+#[async_trait]
+impl PoiRepository for MemoryPoiRepository {
+    async fn find_within_radius(
+        &self,
+        center: &Coordinates,
+        radius_meters: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        Ok(self
+            .matching(categories)
+            .filter(|p| center.distance_to(&p.coordinates) * 1000.0 <= radius_meters)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_in_bbox(
+        &self,
+        min_lat: f64,
+        max_lat: f64,
+        min_lng: f64,
+        max_lng: f64,
+        categories: Option<&[PoiCategory]>,
+        limit: i64,
+    ) -> Result<Vec<Poi>> {
+        Ok(self
+            .matching(categories)
+            .filter(|p| {
+                (min_lat..=max_lat).contains(&p.coordinates.lat)
+                    && (min_lng..=max_lng).contains(&p.coordinates.lng)
+            })
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Poi>> {
+        Ok(self
+            .pois
+            .iter()
+            .filter(|p| ids.contains(&p.id))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_osm_ids(&self, osm_ids: &[i64]) -> Result<Vec<Poi>> {
+        Ok(self
+            .pois
+            .iter()
+            .filter(|p| p.osm_id.is_some_and(|id| osm_ids.contains(&id)))
+            .cloned()
+            .collect())
+    }
+
+    async fn insert(&self, _poi: &Poi) -> Result<Uuid> {
+        Err(AppError::Internal("read-only test repository".to_string()))
+    }
+
+    async fn count(&self) -> Result<i64> {
+        Ok(self.pois.len() as i64)
+    }
+}
+
+/// Straight lines between the waypoints, densified so no segment trips the
+/// jump check
+pub fn straight_line_directions(waypoints: &[Coordinates]) -> DirectionsResponse {
+    let mut geometry = vec![[waypoints[0].lng, waypoints[0].lat]];
+    let mut legs = Vec::new();
+    for pair in waypoints.windows(2) {
+        let leg_m = pair[0].distance_to(&pair[1]) * 1000.0;
+        let steps = (leg_m / MOCK_STEP_M).ceil().max(1.0) as usize;
+        for i in 1..=steps {
+            let t = i as f64 / steps as f64;
+            geometry.push([
+                pair[0].lng + (pair[1].lng - pair[0].lng) * t,
+                pair[0].lat + (pair[1].lat - pair[0].lat) * t,
+            ]);
+        }
+        legs.push(DirectionsLeg {
+            distance_meters: leg_m,
+            duration_seconds: leg_m / MOCK_SPEED_MPS,
+        });
+    }
+    let distance_meters: f64 = legs.iter().map(|l| l.distance_meters).sum();
+    DirectionsResponse {
+        distance_meters,
+        duration_seconds: distance_meters / MOCK_SPEED_MPS,
+        geometry,
+        legs,
+    }
+}
+
+/// Directions provider answering with [`straight_line_directions`], failing
+/// every call, or never answering; counts the calls either way
+#[derive(Default)]
+pub struct MockDirections {
+    failing: bool,
+    hanging: bool,
+    calls: AtomicUsize,
+    in_flight: AtomicUsize,
+}
+
+impl MockDirections {
+    /// A backend that is down
+    pub fn failing() -> Self {
+        MockDirections {
+            failing: true,
+            ..Default::default()
+        }
+    }
+
+    /// A backend that never answers
+    pub fn hanging() -> Self {
+        MockDirections {
+            hanging: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// Calls started and neither answered nor dropped yet
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+}
+
+/// Counts a call in flight until it returns or is dropped
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl<'a> InFlight<'a> {
+    fn start(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        InFlight(count)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl DirectionsProvider for MockDirections {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn directions(
+        &self,
+        waypoints: &[Coordinates],
+        _mode: &TransportMode,
+        _costing: &CostingOptions,
+        _surface: SurfacePreference,
+    ) -> Result<DirectionsResponse> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let _in_flight = InFlight::start(&self.in_flight);
+        if self.hanging {
+            std::future::pending::<()>().await;
+        }
+        if self.failing {
+            return Err(AppError::MapboxApi("mock backend down".to_string()));
+        }
+        Ok(straight_line_directions(waypoints))
+    }
+}
+
+/// Central Paris, where the mock POIs are
+pub fn start() -> Coordinates {
+    Coordinates::new(48.8566, 2.3522).unwrap()
+}
+
+/// A ring of POIs around [`start`]
+pub fn ring_of_pois() -> Vec<Poi> {
+    let categories = [
+        PoiCategory::Park,
+        PoiCategory::Museum,
+        PoiCategory::Monument,
+    ];
+    (0..12)
+        .map(|i| {
+            let angle = i as f64 / 12.0 * std::f64::consts::TAU;
+            super::create_test_poi(
+                &format!("POI {}", i),
+                categories[i % categories.len()].clone(),
+                48.8566 + 0.008 * angle.cos(),
+                2.3522 + 0.012 * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+/// Router over `pois` and `directions`, with an in-memory route cache
+pub fn app(pois: Vec<Poi>, directions: Arc<MockDirections>) -> Router {
+    easyroute::routes::create_router(Arc::new(state(pois, directions)))
+}
+
+/// [`app`] logging every request to `sink`
+pub fn logging_app(
+    pois: Vec<Poi>,
+    directions: Arc<MockDirections>,
+    sink: Arc<dyn RequestLogSink>,
+) -> Router {
+    let state = AppState {
+        request_log: Some(RequestLogger::new(sink, 1.0)),
+        ..state(pois, directions)
+    };
+    easyroute::routes::create_router(Arc::new(state))
+}
+
+fn state(pois: Vec<Poi>, directions: Arc<MockDirections>) -> AppState {
+    let poi_repo: Arc<dyn PoiRepository> = Arc::new(MemoryPoiRepository { pois });
+    let route_generator =
+        RouteGenerator::from_parts(directions, Arc::clone(&poi_repo), Default::default());
+    let cache: Arc<dyn RouteCache> = Arc::new(MemoryCacheService::new(3600, 100));
+
+    AppState {
+        poi_repo,
+        route_generator,
+        cache: Some(cache),
+        shadow: None,
+        request_log: None,
+        privacy: None,
+        tenants: None,
+        guard: Default::default(),
+        events: Default::default(),
+        artifacts: None,
+    }
+}
+
+/// POST `body` to `uri`; returns the status, content type and JSON body
+pub async fn post(app: &Router, uri: &str, body: String) -> (StatusCode, String, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    send(app, request).await
+}
+
+/// GET `uri`; returns the status, content type and JSON body
+pub async fn get(app: &Router, uri: &str) -> (StatusCode, String, Value) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    send(app, request).await
+}
+
+/// DELETE `uri` with `token` as the API key; returns the status, content type and JSON body
+pub async fn delete(app: &Router, uri: &str, token: Option<&str>) -> (StatusCode, String, Value) {
+    let mut request = Request::builder().method("DELETE").uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+    send(app, request.body(Body::empty()).unwrap()).await
+}
+
+/// `method` `uri` with `key` as the API key and an optional JSON `body`;
+/// returns the status, content type and JSON body
+pub async fn send_with_key(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    key: &str,
+    body: Option<&Value>,
+) -> (StatusCode, String, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {key}"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+        .unwrap();
+    send(app, request).await
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, String, Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, content_type, json)
+}
+
+pub async fn post_json(app: &Router, uri: &str, body: &Value) -> (StatusCode, String, Value) {
+    post(app, uri, body.to_string()).await
+}
@@ -1,15 +1,16 @@
+use axum::http::StatusCode;
 use easyroute::db::queries;
-use easyroute::models::{Coordinates, PoiCategory};
-use serial_test::serial;
+use easyroute::models::poi_anomaly::RepairPlan;
+use easyroute::models::{BoundingBox, Coordinates, PoiCategory};
+use easyroute::services::tenant::TenantId;
 
 mod common;
 
 #[tokio::test]
-#[ignore]
-#[serial]
+#[cfg_attr(not(feature = "integration"), ignore)]
 async fn test_insert_and_find_pois() {
-    let pool = common::setup_test_db().await;
-    common::cleanup_test_db(&pool).await;
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
 
     // Create test POIs
     let poi1 = common::create_test_poi("Eiffel Tower", PoiCategory::Monument, 48.8584, 2.2945);
@@ -53,15 +54,14 @@ async fn test_insert_and_find_pois() {
         "Expected to find 'Luxembourg Gardens'"
     );
 
-    common::cleanup_test_db(&pool).await;
+    db.cleanup().await;
 }
 
 #[tokio::test]
-#[ignore]
-#[serial]
+#[cfg_attr(not(feature = "integration"), ignore)]
 async fn test_find_pois_by_category() {
-    let pool = common::setup_test_db().await;
-    common::cleanup_test_db(&pool).await;
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
 
     // Create POIs of different categories
     let monument =
@@ -100,15 +100,14 @@ async fn test_find_pois_by_category() {
         "Expected to find 'Arc de Triomphe'"
     );
 
-    common::cleanup_test_db(&pool).await;
+    db.cleanup().await;
 }
 
 #[tokio::test]
-#[ignore]
-#[serial]
+#[cfg_attr(not(feature = "integration"), ignore)]
 async fn test_spatial_distance_ordering() {
-    let pool = common::setup_test_db().await;
-    common::cleanup_test_db(&pool).await;
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
 
     let center = Coordinates::new(48.8566, 2.3522).unwrap();
 
@@ -135,15 +134,14 @@ async fn test_spatial_distance_ordering() {
         );
     }
 
-    common::cleanup_test_db(&pool).await;
+    db.cleanup().await;
 }
 
 #[tokio::test]
-#[ignore]
-#[serial]
+#[cfg_attr(not(feature = "integration"), ignore)]
 async fn test_duplicate_osm_id_handling() {
-    let pool = common::setup_test_db().await;
-    common::cleanup_test_db(&pool).await;
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
 
     let mut poi1 = common::create_test_poi("Test POI", PoiCategory::Monument, 48.8566, 2.3522);
     poi1.osm_id = Some(12345);
@@ -158,5 +156,543 @@ async fn test_duplicate_osm_id_handling() {
     let result = queries::insert_poi(&pool, &poi2).await;
     assert!(result.is_err(), "Duplicate OSM ID should be rejected");
 
-    common::cleanup_test_db(&pool).await;
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_partitioned_pois_accept_append_imports() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+
+    /// An osm2pgsql row, keyed by (osm_type, osm_id)
+    async fn insert_osm(
+        pool: &sqlx::PgPool,
+        osm_type: &str,
+        osm_id: i64,
+        lat: f64,
+        lng: f64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO pois (name, category, location, osm_type, osm_id)
+             VALUES ('OSM POI', 'monument', ST_MakePoint($4, $3)::geography, $1, $2)",
+        )
+        .bind(osm_type)
+        .bind(osm_id)
+        .bind(lat)
+        .bind(lng)
+        .execute(pool)
+        .await
+        .map(|_| ())
+    }
+
+    // As osm2pgsql's `ids = { type = 'any' }` creates it
+    sqlx::query("ALTER TABLE pois ADD COLUMN osm_type CHAR(1)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    insert_osm(&pool, "N", 42, 48.8566, 2.3522).await.unwrap();
+
+    sqlx::raw_sql(include_str!("../migrations/optional/partition_pois.sql"))
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(queries::pois_partitioned(&pool).await.unwrap());
+    let kept: String = sqlx::query_scalar("SELECT osm_type::text FROM pois WHERE osm_id = 42")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(kept, "N");
+
+    // `--append`: a way sharing the node's id, then the node moving to
+    // another partition (osm2pgsql deletes the old row first)
+    insert_osm(&pool, "W", 42, 48.8570, 2.3525).await.unwrap();
+    sqlx::query("DELETE FROM pois WHERE osm_type = 'N' AND osm_id = ANY($1)")
+        .bind(vec![42i64])
+        .execute(&pool)
+        .await
+        .unwrap();
+    insert_osm(&pool, "N", 42, 40.7128, -74.0060).await.unwrap();
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pois WHERE osm_id = 42")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+
+    // The same object twice in one partition is still rejected
+    assert!(insert_osm(&pool, "W", 42, 48.8571, 2.3526).await.is_err());
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_find_pois_near_path() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+
+    // An east-west path along 48.8566, ~1.5km long
+    let path = vec![
+        Coordinates::new(48.8566, 2.3400).unwrap(),
+        Coordinates::new(48.8566, 2.3600).unwrap(),
+    ];
+    let late = common::create_test_poi("Late POI", PoiCategory::Monument, 48.8568, 2.3550);
+    let early = common::create_test_poi("Early POI", PoiCategory::Park, 48.8565, 2.3420);
+    let off_path = common::create_test_poi("Off Path", PoiCategory::Museum, 48.8650, 2.3500);
+    for poi in [&late, &early, &off_path] {
+        queries::insert_poi(&pool, poi).await.unwrap();
+    }
+
+    let pois = queries::find_pois_near_path_with_hints(
+        &pool,
+        &path,
+        100.0,
+        None,
+        &queries::PoiQueryHints::default(),
+        10,
+    )
+    .await
+    .unwrap();
+
+    let names: Vec<&str> = pois.iter().map(|(poi, _, _)| poi.name.as_str()).collect();
+    assert_eq!(names, vec!["Early POI", "Late POI"]);
+    let (_, distance_m, along_km) = &pois[1];
+    assert!(*distance_m < 50.0, "{distance_m}m from the path");
+    let expected_km = path[0].distance_to(&late.coordinates);
+    assert!((along_km - expected_km).abs() < 0.05, "{along_km}km along");
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_poi_doctor_checks_and_repairs() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+
+    let fine = common::create_test_poi("Louvre", PoiCategory::Museum, 48.8606, 2.3376);
+    let far = common::create_test_poi("Far Away", PoiCategory::Museum, 43.7384, 7.4246);
+    let unnamed = common::create_test_poi(" ", PoiCategory::Toilets, 48.8600, 2.3400);
+    let cafe = common::create_test_poi("Café", PoiCategory::Cafe, 48.8610, 2.3410);
+    for poi in [&fine, &far, &unnamed, &cafe] {
+        queries::insert_poi(&pool, poi).await.unwrap();
+    }
+
+    let paris = BoundingBox {
+        min_lat: 48.8,
+        max_lat: 48.9,
+        min_lng: 2.2,
+        max_lng: 2.4,
+    };
+    let supported = vec!["museum".to_string(), "toilets".to_string()];
+    let mut flagged = queries::find_pois_outside_bbox(&pool, &paris)
+        .await
+        .unwrap();
+    flagged.extend(queries::find_pois_with_empty_names(&pool).await.unwrap());
+    flagged.extend(
+        queries::find_pois_outside_categories(&pool, &supported)
+            .await
+            .unwrap(),
+    );
+    let ids: Vec<_> = flagged.iter().map(|f| f.id).collect();
+    assert_eq!(ids.len(), 3);
+    assert!(!ids.contains(&fine.id));
+
+    let plan = RepairPlan::new(&flagged);
+    assert_eq!(
+        queries::apply_poi_repairs(&pool, &plan).await.unwrap(),
+        (2, 1)
+    );
+    let remaining = queries::find_pois_by_ids(&pool, &[fine.id, far.id, unnamed.id, cafe.id])
+        .await
+        .unwrap();
+    let mut names: Vec<&str> = remaining.iter().map(|p| p.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["Louvre", "Toilets"]);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_saving_a_regenerated_route_is_idempotent() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+
+    let path = vec![
+        Coordinates::new(48.8566, 2.3522).unwrap(),
+        Coordinates::new(48.8600, 2.3600).unwrap(),
+    ];
+    let route = easyroute::models::Route::builder()
+        .path(path.clone())
+        .build();
+    let regenerated = easyroute::models::Route::builder().path(path).build();
+    assert_ne!(route.id, regenerated.id);
+
+    let first = queries::save_route(
+        &pool,
+        &TenantId::default(),
+        "alice",
+        Some("Morning"),
+        &route,
+    )
+    .await
+    .unwrap();
+    let again = queries::save_route(
+        &pool,
+        &TenantId::default(),
+        "alice",
+        Some("Favorite"),
+        &regenerated,
+    )
+    .await
+    .unwrap();
+    assert_eq!(again.route_id, first.route_id);
+    assert_eq!(again.saved_at, first.saved_at);
+    assert_eq!(again.name.as_deref(), Some("Favorite"));
+
+    // Other users get their own bookmark
+    let other = queries::save_route(&pool, &TenantId::default(), "bob", None, &regenerated)
+        .await
+        .unwrap();
+    assert_eq!(other.route_id, regenerated.id);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_users_only_read_their_own_saved_copy() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+
+    let start = Coordinates::new(48.8566, 2.3522).unwrap();
+    let route = easyroute::models::Route::builder()
+        .path(vec![start, Coordinates::new(48.8600, 2.3600).unwrap()])
+        .build();
+    // Mallory saves an edited copy under the same id first
+    let mut forged = route.clone();
+    forged
+        .path
+        .insert(1, Coordinates::new(48.90, 2.30).unwrap());
+    queries::save_route(&pool, &TenantId::default(), "mallory", None, &forged)
+        .await
+        .unwrap();
+    queries::save_route(&pool, &TenantId::default(), "alice", None, &route)
+        .await
+        .unwrap();
+
+    let saved = queries::get_saved_route(&pool, &TenantId::default(), "alice", route.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(saved.path, route.path);
+    assert!(
+        queries::get_saved_route(&pool, &TenantId::default(), "bob", route.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Alice's share link serves her copy, not the earlier forged one
+    queries::create_route_share(
+        &pool,
+        "aliceShare",
+        &TenantId::default(),
+        "alice",
+        route.id,
+        1,
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let shared = queries::get_shared_route(&pool, "aliceShare")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(shared.path, route.path);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_saved_routes_are_per_tenant() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+    let (acme, globex) = (TenantId::new("acme"), TenantId::new("globex"));
+
+    let route = easyroute::models::Route::builder()
+        .path(vec![
+            Coordinates::new(48.8566, 2.3522).unwrap(),
+            Coordinates::new(48.8600, 2.3600).unwrap(),
+        ])
+        .build();
+    queries::save_route(&pool, &acme, "alice", Some("Acme"), &route)
+        .await
+        .unwrap();
+    // The same user string under another tenant is another user
+    let other = queries::save_route(&pool, &globex, "alice", Some("Globex"), &route)
+        .await
+        .unwrap();
+    assert_eq!(other.name.as_deref(), Some("Globex"));
+
+    let listed = queries::list_saved_routes(&pool, &acme, "alice", 10, None)
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].name.as_deref(), Some("Acme"));
+    assert!(
+        queries::get_saved_route(&pool, &TenantId::default(), "alice", route.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_saved_routes_carry_consistency_findings() {
+    use easyroute::models::saved_route::StaleReason;
+
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+
+    let path = vec![
+        Coordinates::new(48.8566, 2.3522).unwrap(),
+        Coordinates::new(48.8600, 2.3600).unwrap(),
+    ];
+    let route = easyroute::models::Route::builder().path(path).build();
+    queries::save_route(&pool, &TenantId::default(), "alice", None, &route)
+        .await
+        .unwrap();
+
+    let to_check = queries::list_saved_routes_to_check(&pool, 10)
+        .await
+        .unwrap();
+    assert_eq!(to_check.len(), 1);
+    let reasons = vec![StaleReason::PoiRemoved {
+        poi_id: uuid::Uuid::new_v4(),
+        name: "Louvre".to_string(),
+    }];
+    queries::mark_saved_route_checked(&pool, &TenantId::default(), "alice", route.id, &reasons)
+        .await
+        .unwrap();
+    let saved = queries::get_saved_route(&pool, &TenantId::default(), "alice", route.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(saved.stale);
+    assert_eq!(saved.stale_reasons, reasons);
+
+    // A clean check clears the flag
+    queries::mark_saved_route_checked(&pool, &TenantId::default(), "alice", route.id, &[])
+        .await
+        .unwrap();
+    let listed = queries::list_saved_routes(&pool, &TenantId::default(), "alice", 10, None)
+        .await
+        .unwrap();
+    assert!(!listed[0].route.stale);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_geometry_backfill_skips_saved_copies_that_dont_match() {
+    use easyroute::models::evaluation::EvaluatedRoute;
+    use easyroute::models::{Poi, Route, TransportMode};
+
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+
+    let start = Coordinates::new(48.8566, 2.3522).unwrap();
+    let louvre = Poi::builder()
+        .name("Louvre")
+        .coordinates(Coordinates::new(48.8606, 2.3376).unwrap())
+        .build();
+    let path = vec![start, louvre.coordinates, start];
+    let length_km: f64 = path.windows(2).map(|w| w[0].distance_to(&w[1])).sum();
+    let route = Route::builder()
+        .path(path)
+        .distance_km(length_km)
+        .waypoint(louvre)
+        .build();
+    let mut evaluated =
+        EvaluatedRoute::from_route(&route, &start, length_km, &TransportMode::Walk, "simple");
+    evaluated.geometry = None;
+    queries::insert_evaluated_route(&pool, &evaluated)
+        .await
+        .unwrap();
+
+    // A client-edited copy with a detour the generator never produced
+    let mut forged = route.clone();
+    forged
+        .path
+        .insert(1, Coordinates::new(48.90, 2.30).unwrap());
+    queries::save_route(&pool, &TenantId::default(), "mallory", None, &forged)
+        .await
+        .unwrap();
+    assert!(queries::list_geometry_backfill_sources(&pool, 10)
+        .await
+        .unwrap()
+        .is_empty());
+
+    queries::save_route(&pool, &TenantId::default(), "alice", None, &route)
+        .await
+        .unwrap();
+    let sources = queries::list_geometry_backfill_sources(&pool, 10)
+        .await
+        .unwrap();
+    assert_eq!(sources.len(), 1);
+    assert_eq!(sources[0].1.path, route.path);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_metric_recompute_records_unreadable_geometry() {
+    use easyroute::constants::ROUTE_METRICS_VERSION;
+    use easyroute::models::evaluation::EvaluatedRoute;
+    use easyroute::models::{Route, TransportMode};
+    use easyroute::scheduler::tasks::MetricRecomputeTask;
+    use easyroute::scheduler::ScheduledTask;
+
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+
+    let start = Coordinates::new(48.8566, 2.3522).unwrap();
+    let route = Route::builder()
+        .path(vec![
+            start,
+            Coordinates::new(48.8606, 2.3376).unwrap(),
+            start,
+        ])
+        .distance_km(2.2)
+        .build();
+    let evaluated = EvaluatedRoute::from_route(&route, &start, 2.2, &TransportMode::Walk, "simple");
+    queries::insert_evaluated_route(&pool, &evaluated)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE evaluated_routes SET path = '{\"not\": \"geometry\"}' WHERE id = $1")
+        .bind(evaluated.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let task = MetricRecomputeTask::new(pool.clone());
+    let summary = task.run().await.unwrap();
+    assert!(
+        summary.contains("1 with unreadable geometry"),
+        "{}",
+        summary
+    );
+    assert!(
+        queries::list_routes_for_recompute(&pool, ROUTE_METRICS_VERSION, 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let summary = task.run().await.unwrap();
+    assert!(
+        summary.contains("0 with unreadable geometry"),
+        "{}",
+        summary
+    );
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_api_keys_only_act_for_their_own_user() {
+    use common::mock::send_with_key;
+    use easyroute::models::api_key::{generate_key, hash_key, ApiScope};
+    use easyroute::models::Route;
+    use serde_json::json;
+
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+    let scopes = [ApiScope::RoutesRead, ApiScope::RoutesWrite];
+    let mut keys = Vec::new();
+    for user in ["alice", "bob"] {
+        let key = generate_key();
+        queries::create_api_key(
+            &pool,
+            &TenantId::default(),
+            user,
+            &hash_key(&key),
+            &key[..8],
+            &scopes,
+        )
+        .await
+        .unwrap();
+        keys.push(key);
+    }
+    let (alice, bob) = (&keys[0], &keys[1]);
+    let app = easyroute::routes::create_pg_router(pool.clone()).layer(
+        axum::middleware::from_fn_with_state(
+            pool.clone(),
+            easyroute::routes::auth::require_api_key,
+        ),
+    );
+
+    let start = Coordinates::new(48.8566, 2.3522).unwrap();
+    let route = Route::builder()
+        .path(vec![
+            start,
+            Coordinates::new(48.8606, 2.3376).unwrap(),
+            start,
+        ])
+        .distance_km(2.2)
+        .build();
+    let save = |user: &str| json!({ "user": user, "route": route });
+    let save_uri = format!("/routes/{}/save", route.id);
+
+    let (status, _, _) = send_with_key(&app, "POST", &save_uri, alice, Some(&save("alice"))).await;
+    assert_eq!(status, StatusCode::OK);
+    // Bob can't save as, list or act on Alice's routes; he only ever reads
+    // his own copy, which he hasn't saved
+    let (status, _, _) = send_with_key(&app, "POST", &save_uri, bob, Some(&save("alice"))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _, _) = send_with_key(&app, "GET", "/routes?user=alice", bob, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let route_uri = format!("/routes/{}", route.id);
+    let (status, _, _) = send_with_key(&app, "GET", &route_uri, bob, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) =
+        send_with_key(&app, "GET", &format!("{}?user=alice", route_uri), bob, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let share_uri = format!("/routes/{}/share", route.id);
+    let (status, _, _) = send_with_key(&app, "POST", &share_uri, bob, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _, body) = send_with_key(&app, "GET", "/routes?user=alice", alice, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["routes"].as_array().unwrap().len(), 1);
+    let (status, _, _) = send_with_key(&app, "POST", &share_uri, alice, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The same user under another tenant's key is another user
+    let acme = generate_key();
+    queries::create_api_key(
+        &pool,
+        &TenantId::new("acme"),
+        "alice",
+        &hash_key(&acme),
+        &acme[..8],
+        &scopes,
+    )
+    .await
+    .unwrap();
+    let (status, _, _) = send_with_key(&app, "GET", &route_uri, &acme, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, body) = send_with_key(&app, "GET", "/routes?user=alice", &acme, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["routes"].as_array().unwrap().is_empty());
+
+    db.cleanup().await;
 }
@@ -0,0 +1,445 @@
+//! `/routes/loop` end to end: the full router over an in-memory POI
+//! repository and straight-line directions, so status codes, error bodies,
+//! caching and the response schema are checked offline.
+
+use axum::http::StatusCode;
+use easyroute::services::events::EventBus;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+mod common;
+
+use common::mock::{app, delete, get, post, post_json, ring_of_pois, MockDirections};
+
+fn loop_request(distance_km: f64) -> Value {
+    json!({
+        "start_point": {"lat": 48.8566, "lng": 2.3522},
+        "distance_km": distance_km,
+        "mode": "walk"
+    })
+}
+
+fn route_ids(body: &Value) -> Vec<String> {
+    body["routes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_loop_route_response_schema() {
+    let directions = Arc::new(MockDirections::default());
+    let app = app(ring_of_pois(), Arc::clone(&directions));
+
+    let (status, content_type, body) = post_json(&app, "/routes/loop", &loop_request(5.0)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(content_type, "application/json");
+
+    let routes = body["routes"].as_array().unwrap();
+    assert!(!routes.is_empty());
+    for route in routes {
+        assert!(uuid::Uuid::parse_str(route["id"].as_str().unwrap()).is_ok());
+        let distance_km = route["distance_km"].as_f64().unwrap();
+        assert!(
+            (2.5..=7.5).contains(&distance_km),
+            "{distance_km}km for a 5km request"
+        );
+        assert!(route["estimated_duration_minutes"].as_u64().unwrap() > 0);
+        assert!(route["score"].is_number());
+        assert!(route["metrics"].is_object());
+
+        let path = route["path"].as_array().unwrap();
+        assert!(path.len() >= 2);
+        assert!(path[0]["lat"].is_f64() && path[0]["lng"].is_f64());
+        // A loop ends where it starts
+        assert_eq!(path.first(), path.last());
+
+        for (i, poi) in route["pois"].as_array().unwrap().iter().enumerate() {
+            assert_eq!(poi["order_in_route"].as_u64().unwrap(), i as u64 + 1);
+            assert!(poi["name"].is_string());
+            assert!(poi["category"].is_string());
+            assert!(poi["distance_from_start_km"].is_number());
+        }
+    }
+    assert!(directions.calls() > 0);
+}
+
+#[tokio::test]
+async fn test_loop_route_as_geojson() {
+    let app = app(ring_of_pois(), Arc::new(MockDirections::default()));
+
+    let (status, content_type, body) =
+        post_json(&app, "/routes/loop?format=geojson", &loop_request(5.0)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/geo+json");
+    assert_eq!(body["type"], "FeatureCollection");
+    assert!(!body["features"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_loop_route_with_compact_coordinates() {
+    let app = app(ring_of_pois(), Arc::new(MockDirections::default()));
+
+    let (status, _, body) = post_json(
+        &app,
+        "/routes/loop?coords=compact&precision=3",
+        &loop_request(5.0),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    let route = &body["routes"][0];
+    let point = route["path"][0].as_array().unwrap();
+    assert_eq!(point.len(), 2);
+    for value in point
+        .iter()
+        .chain(route["pois"][0]["coordinates"].as_array().unwrap())
+    {
+        let value = value.as_f64().unwrap();
+        assert_eq!((value * 1e3).round() / 1e3, value);
+    }
+
+    let (status, _, _) = post_json(&app, "/routes/loop?precision=9", &loop_request(5.0)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_invalid_requests_are_rejected_with_error_bodies() {
+    let directions = Arc::new(MockDirections::default());
+    let app = app(ring_of_pois(), Arc::clone(&directions));
+
+    // Validation errors: 400 with the usual error body
+    let (status, _, body) = post_json(&app, "/routes/loop", &loop_request(0.1)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Bad Request");
+    assert!(body["message"].as_str().unwrap().contains("distance"));
+
+    let (status, _, body) = post_json(
+        &app,
+        "/routes/loop",
+        &json!({"start_point": {"lat": 48.8566, "lng": 2.3522}, "mode": "walk"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].is_string());
+
+    // Bodies that don't parse are rejected by the extractor
+    let (status, _, _) = post(&app, "/routes/loop", "{not json".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _, _) = post_json(
+        &app,
+        "/routes/loop",
+        &json!({"start_point": {"lat": 48.8566, "lng": 2.3522}, "distance_km": 5.0, "mode": "teleport"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Nothing reached the directions backend
+    assert_eq!(directions.calls(), 0);
+}
+
+#[tokio::test]
+async fn test_backend_failure_reports_reason() {
+    let app = app(ring_of_pois(), Arc::new(MockDirections::failing()));
+
+    let (status, _, body) = post_json(&app, "/routes/loop", &loop_request(5.0)).await;
+    assert!(status.is_server_error(), "{status}");
+    assert_eq!(body["reason"], "backend_unavailable");
+    assert!(body["message"].is_string());
+    assert!(body["hint"].is_string());
+}
+
+#[tokio::test]
+async fn test_repeat_request_is_served_from_cache() {
+    let directions = Arc::new(MockDirections::default());
+    let app = app(ring_of_pois(), Arc::clone(&directions));
+
+    let (status, _, first) = post_json(&app, "/routes/loop", &loop_request(5.0)).await;
+    assert_eq!(status, StatusCode::OK);
+    let calls = directions.calls();
+
+    // Same request: the cached routes, without generating again
+    let (status, _, second) = post_json(&app, "/routes/loop", &loop_request(5.0)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(route_ids(&first), route_ids(&second));
+    assert_eq!(directions.calls(), calls);
+
+    // A different distance is a different key
+    let (status, _, third) = post_json(&app, "/routes/loop", &loop_request(8.0)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(directions.calls() > calls);
+    assert_ne!(route_ids(&first), route_ids(&third));
+}
+
+#[tokio::test]
+async fn test_must_include_places_are_on_every_route() {
+    let pois = ring_of_pois();
+    let pinned = pois[3].clone();
+    let app = app(pois, Arc::new(MockDirections::default()));
+
+    let mut request = loop_request(5.0);
+    request["preferences"] = json!({"must_include": [pinned.id]});
+    let (status, _, body) = post_json(&app, "/routes/loop", &request).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    for route in body["routes"].as_array().unwrap() {
+        let names: Vec<&str> = route["pois"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&pinned.name.as_str()), "{names:?}");
+    }
+
+    // A point ~20km away can't fit a 5km loop
+    request["preferences"] = json!({"must_include": [{"lat": 49.03, "lng": 2.3522}]});
+    let (status, _, body) = post_json(&app, "/routes/loop", &request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains("must_include"));
+
+    request["preferences"] = json!({"must_include": [uuid::Uuid::new_v4()]});
+    let (status, _, body) = post_json(&app, "/routes/loop", &request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains("unknown POI"));
+}
+
+#[tokio::test]
+async fn test_area_without_pois_is_not_found() {
+    let directions = Arc::new(MockDirections::default());
+    let app = app(Vec::new(), Arc::clone(&directions));
+
+    let (status, _, body) = post_json(&app, "/routes/loop", &loop_request(5.0)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "Not Found");
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("No POIs found"));
+    assert_eq!(directions.calls(), 0);
+}
+
+#[tokio::test]
+async fn test_heatmap_grid_over_bbox() {
+    let app = app(ring_of_pois(), Arc::new(MockDirections::default()));
+
+    let (status, content_type, body) =
+        get(&app, "/heatmap?bbox=2.33,48.84,2.37,48.87&cell_m=500").await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(content_type, "application/geo+json");
+    assert_eq!(body["type"], "FeatureCollection");
+    assert_eq!(body["truncated"], false);
+    let cells = body["features"].as_array().unwrap();
+    let poi_count: u64 = cells
+        .iter()
+        .map(|c| c["properties"]["poi_count"].as_u64().unwrap())
+        .sum();
+    assert_eq!(poi_count, 12);
+    let best = cells
+        .iter()
+        .map(|c| c["properties"]["score"].as_f64().unwrap())
+        .fold(0.0, f64::max);
+    assert_eq!(best, 1.0);
+    assert_eq!(cells[0]["geometry"]["type"], "Polygon");
+
+    let (status, _, body) = get(&app, "/heatmap?bbox=-5,42,8,51").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains("cell"));
+}
+
+#[tokio::test]
+async fn test_admin_cache_invalidation() {
+    use easyroute::cache::{
+        loop_route_cache_key, poi_region_cache_key, CacheKeyPrecision, CacheTtl,
+        MemoryCacheService, RouteCache, RoutePreferencesHash,
+    };
+    use easyroute::models::Coordinates;
+    use easyroute::routes::admin::create_admin_router;
+
+    let cache = Arc::new(MemoryCacheService::new(3600, 100));
+    let paris = Coordinates::new(48.8566, 2.3522).unwrap();
+    let lyon = Coordinates::new(45.764, 4.8357).unwrap();
+    let prefs = RoutePreferencesHash::new(None, false);
+    let keys = [
+        loop_route_cache_key(&paris, 5.0, "walk", &prefs, &CacheKeyPrecision::DEFAULT),
+        poi_region_cache_key(&paris, 5.0, None),
+        poi_region_cache_key(&lyon, 5.0, None),
+    ];
+    for key in &keys {
+        cache.cache_routes(key, &[], CacheTtl::Standard).await;
+    }
+    let app = create_admin_router(
+        Arc::clone(&cache) as Arc<dyn RouteCache>,
+        EventBus::default(),
+        "secret".to_string(),
+    );
+
+    let (status, _, _) = delete(&app, "/admin/cache?prefix=poi:region", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = delete(&app, "/admin/cache?prefix=poi:region", Some("wrong")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _, _) = delete(&app, "/admin/cache", Some("secret")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _, body) = delete(
+        &app,
+        "/admin/cache?prefix=poi:region&bbox=2.2,48.8,2.5,48.9",
+        Some("secret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["invalidated"], 1);
+    assert!(cache.get_cached_routes(&keys[0]).await.is_some());
+    assert!(cache.get_cached_routes(&keys[1]).await.is_none());
+    assert!(cache.get_cached_routes(&keys[2]).await.is_some());
+
+    let (_, _, body) = delete(&app, "/admin/cache?prefix=poi:region", Some("secret")).await;
+    assert_eq!(body["invalidated"], 1);
+}
+
+#[tokio::test]
+async fn test_admin_cache_invalidation_reaches_other_instances() {
+    use easyroute::cache::{
+        poi_region_cache_key, CacheTtl, MemoryCacheService, RouteCache, TieredCacheService,
+    };
+    use easyroute::models::Coordinates;
+    use easyroute::routes::admin::{create_admin_router, spawn_invalidation_listener};
+    use std::time::Duration;
+
+    // Two instances with their own in-process tier over one shared tier,
+    // on one event bus standing in for the Redis fanout
+    let shared = Arc::new(MemoryCacheService::new(3600, 100));
+    let replica = |shared: &Arc<MemoryCacheService>| {
+        Arc::new(TieredCacheService::new(
+            MemoryCacheService::new(3600, 100),
+            Arc::clone(shared) as Arc<dyn RouteCache>,
+        ))
+    };
+    let (first, second) = (replica(&shared), replica(&shared));
+    let events = EventBus::default();
+    spawn_invalidation_listener(Arc::clone(&second) as Arc<dyn RouteCache>, &events);
+
+    let key = poi_region_cache_key(&Coordinates::new(48.8566, 2.3522).unwrap(), 5.0, None);
+    first.cache_routes(&key, &[], CacheTtl::Standard).await;
+    assert!(second.get_cached_routes(&key).await.is_some());
+
+    let app = create_admin_router(first, events, "secret".to_string());
+    let (status, _, body) =
+        delete(&app, "/admin/cache?bbox=2.2,48.8,2.5,48.9", Some("secret")).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert!(shared.get_cached_routes(&key).await.is_none());
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while second.get_cached_routes(&key).await.is_some() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the other instance kept its local copy");
+}
+
+#[tokio::test]
+async fn test_admin_pg_routes_require_admin_token() {
+    use easyroute::routes::admin::create_admin_pg_router;
+
+    // Never connected: the token check rejects the request first
+    let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+    let app = create_admin_pg_router(pool, "secret".to_string());
+
+    let (status, _, body) = get(&app, "/admin/evaluations/export?format=csv").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "body: {body}");
+    let (status, _, body) = get(&app, "/admin/snap-radius").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "body: {body}");
+}
+
+#[tokio::test]
+async fn test_stream_disconnect_stops_generation() {
+    use axum::body::Body;
+    use axum::http::{header, Request};
+    use futures::StreamExt;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    let directions = Arc::new(MockDirections::hanging());
+    let app = app(ring_of_pois(), Arc::clone(&directions));
+    let request = Request::builder()
+        .method("POST")
+        .uri("/routes/loop/stream")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(loop_request(5.0).to_string()))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Read until generation is waiting on the backend, then hang up
+    let mut body = response.into_body().into_data_stream();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while directions.in_flight() == 0 {
+            let _ = tokio::time::timeout(Duration::from_millis(10), body.next()).await;
+        }
+    })
+    .await
+    .expect("generation never reached the backend");
+    drop(body);
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while directions.in_flight() > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("generation kept running after the client disconnected");
+}
+
+#[tokio::test]
+async fn test_request_log_records_the_endpoint() {
+    use async_trait::async_trait;
+    use common::mock::logging_app;
+    use easyroute::services::request_log::{RequestLogRecord, RequestLogSink};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct MemorySink {
+        lines: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl RequestLogSink for MemorySink {
+        async fn write_lines(&self, lines: &[String]) -> std::io::Result<()> {
+            self.lines.lock().unwrap().extend_from_slice(lines);
+            Ok(())
+        }
+    }
+
+    let sink = Arc::new(MemorySink::default());
+    let app = logging_app(
+        ring_of_pois(),
+        Arc::new(MockDirections::default()),
+        sink.clone(),
+    );
+    post_json(&app, "/routes/loop", &loop_request(5.0)).await;
+    post_json(&app, "/routes/loop/stream", &loop_request(4.0)).await;
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while sink.lines.lock().unwrap().len() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("requests were not logged");
+    let mut endpoints: Vec<String> = sink
+        .lines
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|line| {
+            serde_json::from_str::<RequestLogRecord>(line)
+                .unwrap()
+                .endpoint
+        })
+        .collect();
+    endpoints.sort();
+    assert_eq!(endpoints, ["/routes/loop", "/routes/loop/stream"]);
+}
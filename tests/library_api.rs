@@ -0,0 +1,77 @@
+//! The crate-root API embedders rely on: everything here is reached through
+//! root re-exports only, so moving an item without re-exporting it breaks
+//! this file before it breaks a downstream crate.
+
+use easyroute::{
+    Coordinates, DirectionsProvider, MetricsExplained, Poi, PoiCategory, PoiRepository, Route,
+    RouteCache, RouteGenerator, RouteGeneratorConfig, RouteMetrics, RoutePreferences,
+    TransportMode,
+};
+use std::sync::Arc;
+
+mod common;
+
+use common::mock::{ring_of_pois, start, MemoryPoiRepository, MockDirections};
+
+#[allow(dead_code)]
+struct DataSources {
+    pois: Arc<dyn PoiRepository>,
+    directions: Arc<dyn DirectionsProvider>,
+    cache: Option<Arc<dyn RouteCache>>,
+}
+
+#[test]
+fn test_models_and_metrics_through_root_exports() {
+    let start = Coordinates::new(48.8566, 2.3522).unwrap();
+    let pois: Vec<Poi> = [(48.8606, 2.3376), (48.8530, 2.3499), (48.8584, 2.3700)]
+        .iter()
+        .map(|&(lat, lng)| {
+            Poi::builder()
+                .category(PoiCategory::Monument)
+                .coordinates(Coordinates::new(lat, lng).unwrap())
+                .popularity(80.0)
+                .build()
+        })
+        .collect();
+
+    let mut path = vec![start];
+    path.extend(pois.iter().map(|p| p.coordinates));
+    path.push(start);
+    let mut route = pois
+        .into_iter()
+        .fold(Route::builder().path(path), |b, poi| b.waypoint(poi))
+        .mode(TransportMode::Walk)
+        .build();
+
+    let metrics = RouteMetrics::compute(&route, 3);
+    route.metrics_explained = Some(MetricsExplained::new(&route, &metrics));
+    route.metrics = Some(metrics);
+    assert!(route.metrics.as_ref().unwrap().circularity > 0.0);
+}
+
+#[tokio::test]
+async fn test_generator_from_parts_without_postgres_or_mapbox() {
+    let directions = Arc::new(MockDirections::default());
+    let pois: Arc<dyn PoiRepository> = Arc::new(MemoryPoiRepository {
+        pois: ring_of_pois(),
+    });
+    let generator = RouteGenerator::from_parts(
+        Arc::clone(&directions) as Arc<dyn DirectionsProvider>,
+        pois,
+        RouteGeneratorConfig::default(),
+    );
+
+    let routes = generator
+        .generate_loop_route(
+            start(),
+            5.0,
+            1.0,
+            &TransportMode::Walk,
+            &RoutePreferences::default(),
+        )
+        .await
+        .unwrap();
+    assert!(!routes.is_empty());
+    assert!(routes.iter().all(|r| r.metrics.is_some()));
+    assert!(directions.calls() > 0);
+}
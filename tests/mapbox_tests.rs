@@ -129,6 +129,7 @@ fn test_mapbox_response_conversions() {
         distance_meters: 5240.0,
         duration_seconds: 3720.0,
         geometry: vec![[2.3522, 48.8566], [2.2945, 48.8584]],
+        legs: vec![],
     };
 
     assert_eq!(response.distance_km(), 5.24);
@@ -0,0 +1,266 @@
+//! Query plan regression tests for the hot POI queries.
+//!
+//! Each query is run through `EXPLAIN (FORMAT JSON)` against a seeded table and
+//! must (a) avoid a sequential scan on `pois`, (b) use the expected spatial
+//! index and (c) cost at most a fraction of the same query with index scans
+//! disabled. The relative budget keeps the tests stable across PostGIS
+//! versions and table sizes while still failing on an accidental seq scan.
+
+use easyroute::constants::DEFAULT_SNAP_RADIUS_METERS;
+use easyroute::db::queries::{self, QueryShape};
+use easyroute::models::{geohash, BoundingBox, Coordinates, PoiCategory};
+use serde_json::Value;
+use sqlx::PgPool;
+
+mod common;
+
+/// Seeded grid: GRID_SIZE x GRID_SIZE points, GRID_STEP_DEG apart (~370 m)
+const GRID_SIZE: i32 = 150;
+const GRID_STEP_DEG: f64 = 0.005;
+const GRID_ORIGIN: (f64, f64) = (48.5, 2.0);
+
+/// Indexed plan must cost at most this share of the forced sequential plan
+const MAX_COST_RATIO: f64 = 0.2;
+
+async fn seed_grid(pool: &PgPool) {
+    sqlx::query(
+        "INSERT INTO pois (name, category, location, popularity_score)
+         SELECT 'plan-test ' || i,
+                (ARRAY['park', 'monument', 'cafe', 'museum'])[1 + i % 4],
+                ST_SetSRID(ST_MakePoint($2 + (i % $3) * $4, $1 + (i / $3) * $4), 4326)::geography,
+                50.0
+         FROM generate_series(0, $3 * $3 - 1) AS i",
+    )
+    .bind(GRID_ORIGIN.0)
+    .bind(GRID_ORIGIN.1)
+    .bind(GRID_SIZE)
+    .bind(GRID_STEP_DEG)
+    .execute(pool)
+    .await
+    .expect("Failed to seed POI grid");
+    sqlx::query("ANALYZE pois").execute(pool).await.unwrap();
+}
+
+/// Query parameters, bound in order
+enum Param {
+    Text(String),
+    Float(f64),
+    Int(i64),
+    TextArray(Vec<String>),
+}
+
+/// Plan of `sql` with the given parameters; with `indexes_enabled = false`
+/// index and bitmap scans are disabled for the transaction.
+async fn explain(pool: &PgPool, sql: &str, params: &[Param], indexes_enabled: bool) -> Value {
+    let mut tx = pool.begin().await.unwrap();
+    if !indexes_enabled {
+        sqlx::query("SET LOCAL enable_indexscan = off")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("SET LOCAL enable_bitmapscan = off")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+    }
+
+    let explain_sql = format!("EXPLAIN (FORMAT JSON) {}", sql);
+    let mut query = sqlx::query_scalar::<_, Value>(&explain_sql);
+    for param in params {
+        query = match param {
+            Param::Text(v) => query.bind(v.clone()),
+            Param::Float(v) => query.bind(*v),
+            Param::Int(v) => query.bind(*v),
+            Param::TextArray(v) => query.bind(v.clone()),
+        };
+    }
+    let output = query.fetch_one(&mut *tx).await.unwrap();
+    tx.rollback().await.unwrap();
+
+    output[0]["Plan"].clone()
+}
+
+fn plan_nodes(plan: &Value) -> Vec<&Value> {
+    let mut nodes = vec![plan];
+    if let Some(children) = plan["Plans"].as_array() {
+        for child in children {
+            nodes.extend(plan_nodes(child));
+        }
+    }
+    nodes
+}
+
+fn total_cost(plan: &Value) -> f64 {
+    plan["Total Cost"].as_f64().expect("plan has no Total Cost")
+}
+
+/// Assert index usage and the relative cost budget for one query
+async fn assert_plan_within_budget(
+    pool: &PgPool,
+    label: &str,
+    sql: &str,
+    params: &[Param],
+    expected_indexes: &[&str],
+) {
+    let plan = explain(pool, sql, params, true).await;
+    let nodes = plan_nodes(&plan);
+
+    let seq_scan = nodes
+        .iter()
+        .any(|n| n["Node Type"] == "Seq Scan" && n["Relation Name"] == "pois");
+    assert!(!seq_scan, "{label}: sequential scan on pois\n{plan:#}");
+
+    let uses_index = nodes.iter().any(|n| {
+        expected_indexes
+            .iter()
+            .any(|index| n["Index Name"] == *index)
+    });
+    assert!(
+        uses_index,
+        "{label}: none of {expected_indexes:?} used\n{plan:#}"
+    );
+
+    let sequential_cost = total_cost(&explain(pool, sql, params, false).await);
+    let cost = total_cost(&plan);
+    assert!(
+        cost <= sequential_cost * MAX_COST_RATIO,
+        "{label}: cost {cost:.1} exceeds budget ({:.0}% of sequential cost {sequential_cost:.1})",
+        MAX_COST_RATIO * 100.0
+    );
+}
+
+fn grid_center() -> Coordinates {
+    let offset = GRID_SIZE as f64 * GRID_STEP_DEG / 2.0;
+    Coordinates::new(GRID_ORIGIN.0 + offset, GRID_ORIGIN.1 + offset).unwrap()
+}
+
+fn grid_center_wkt() -> String {
+    let center = grid_center();
+    format!("POINT({} {})", center.lng, center.lat)
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_radius_query_plan() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+    seed_grid(&pool).await;
+
+    assert_plan_within_budget(
+        &pool,
+        "radius",
+        &queries::radius_query_sql(QueryShape::default()),
+        &[
+            Param::Text(grid_center_wkt()),
+            Param::Float(2000.0),
+            Param::Int(100),
+        ],
+        &["idx_pois_location"],
+    )
+    .await;
+
+    let categories = vec![PoiCategory::Park.to_string(), PoiCategory::Cafe.to_string()];
+    assert_plan_within_budget(
+        &pool,
+        "radius with categories",
+        &queries::radius_query_sql(QueryShape {
+            categories: true,
+            ..Default::default()
+        }),
+        &[
+            Param::Text(grid_center_wkt()),
+            Param::Float(2000.0),
+            Param::TextArray(categories),
+            Param::Int(100),
+        ],
+        &["idx_pois_location"],
+    )
+    .await;
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_snapping_query_plan() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+    seed_grid(&pool).await;
+
+    // Snapping looks up a few candidates in a small radius around each waypoint
+    assert_plan_within_budget(
+        &pool,
+        "snapping",
+        &queries::radius_query_sql(QueryShape::default()),
+        &[
+            Param::Text(grid_center_wkt()),
+            Param::Float(DEFAULT_SNAP_RADIUS_METERS),
+            Param::Int(5),
+        ],
+        &["idx_pois_location"],
+    )
+    .await;
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_bbox_query_plan() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+    seed_grid(&pool).await;
+
+    let (lat, lng) = (GRID_ORIGIN.0 + 0.3, GRID_ORIGIN.1 + 0.3);
+    assert_plan_within_budget(
+        &pool,
+        "bbox",
+        &queries::bbox_query_sql(QueryShape::default()),
+        &[
+            Param::Float(lat),
+            Param::Float(lat + 0.02),
+            Param::Float(lng),
+            Param::Float(lng + 0.03),
+            Param::Int(100),
+        ],
+        &["idx_pois_location_geometry"],
+    )
+    .await;
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "integration"), ignore)]
+async fn test_geohash_prefilter_query_plan() {
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
+    seed_grid(&pool).await;
+
+    let radius_m = 2000.0;
+    let bbox = BoundingBox::from_center_radius(&grid_center(), radius_m);
+    let cells = geohash::covering_cells(&bbox, 5, 9).expect("2 km fits in 9 precision-5 cells");
+    let (lower, upper) = queries::geohash_ranges(&cells);
+
+    // Either index keeps the query off a sequential scan; which one wins is
+    // the planner's call
+    assert_plan_within_budget(
+        &pool,
+        "radius with geohash prefilter",
+        &queries::radius_query_sql(QueryShape {
+            geohash_cells: true,
+            ..Default::default()
+        }),
+        &[
+            Param::Text(grid_center_wkt()),
+            Param::Float(radius_m),
+            Param::TextArray(lower),
+            Param::TextArray(upper),
+            Param::Int(100),
+        ],
+        &["idx_pois_geohash", "idx_pois_location"],
+    )
+    .await;
+
+    db.cleanup().await;
+}
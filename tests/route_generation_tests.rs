@@ -4,17 +4,15 @@ use easyroute::services::mapbox::MapboxClient;
 use easyroute::services::poi_service::PoiService;
 use easyroute::services::route_generator::RouteGenerator;
 use easyroute::services::snapping_service::SnappingService;
-use serial_test::serial;
 use std::sync::Arc;
 
 mod common;
 
 #[tokio::test]
 #[ignore]
-#[serial]
 async fn test_route_generation_with_database_pois() {
-    let pool = common::setup_test_db().await;
-    common::cleanup_test_db(&pool).await;
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
 
     // Insert test POIs in a loop-friendly arrangement
     let center = Coordinates::new(48.8566, 2.3522).unwrap();
@@ -51,8 +49,26 @@ async fn test_route_generation_with_database_pois() {
     // Generate route
     let preferences = RoutePreferences {
         poi_categories: None,
+        excluded_poi_categories: None,
         hidden_gems: false,
         max_alternatives: 1,
+        poi_min_separation_km: None,
+        pace_min_per_km: None,
+        min_paved_fraction: None,
+        max_busy_road_fraction: None,
+        surface: Default::default(),
+        prefer_green: false,
+        step_free: false,
+        minimize_exposure: false,
+        max_elevation_gain_m: None,
+        prefer_flat: false,
+        costing: Default::default(),
+        include_visit_time: false,
+        golden_hour: false,
+        departure: None,
+        pareto: false,
+        seed: None,
+        must_include: Vec::new(),
     };
 
     let result = route_generator
@@ -83,15 +99,14 @@ async fn test_route_generation_with_database_pois() {
         route.score
     );
 
-    common::cleanup_test_db(&pool).await;
+    db.cleanup().await;
 }
 
 #[tokio::test]
 #[ignore]
-#[serial]
 async fn test_route_generation_distance_validation() {
-    let pool = common::setup_test_db().await;
-    common::cleanup_test_db(&pool).await;
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
 
     // Insert POIs at specific distances to test tolerance
     let center = Coordinates::new(48.8566, 2.3522).unwrap();
@@ -147,15 +162,14 @@ async fn test_route_generation_distance_validation() {
         }
     }
 
-    common::cleanup_test_db(&pool).await;
+    db.cleanup().await;
 }
 
 #[tokio::test]
 #[ignore]
-#[serial]
 async fn test_route_poi_ordering() {
-    let pool = common::setup_test_db().await;
-    common::cleanup_test_db(&pool).await;
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
 
     // Insert POIs
     let center = Coordinates::new(48.8566, 2.3522).unwrap();
@@ -213,15 +227,14 @@ async fn test_route_poi_ordering() {
         }
     }
 
-    common::cleanup_test_db(&pool).await;
+    db.cleanup().await;
 }
 
 #[tokio::test]
 #[ignore]
-#[serial]
 async fn test_route_scoring_different_preferences() {
-    let pool = common::setup_test_db().await;
-    common::cleanup_test_db(&pool).await;
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
 
     // Insert POIs with different popularity scores
     let center = Coordinates::new(48.8566, 2.3522).unwrap();
@@ -257,8 +270,26 @@ async fn test_route_scoring_different_preferences() {
     // Test with popular preference
     let popular_pref = RoutePreferences {
         poi_categories: None,
+        excluded_poi_categories: None,
         hidden_gems: false,
         max_alternatives: 1,
+        poi_min_separation_km: None,
+        pace_min_per_km: None,
+        min_paved_fraction: None,
+        max_busy_road_fraction: None,
+        surface: Default::default(),
+        prefer_green: false,
+        step_free: false,
+        minimize_exposure: false,
+        max_elevation_gain_m: None,
+        prefer_flat: false,
+        costing: Default::default(),
+        include_visit_time: false,
+        golden_hour: false,
+        departure: None,
+        pareto: false,
+        seed: None,
+        must_include: Vec::new(),
     };
 
     let result = route_generator
@@ -271,15 +302,14 @@ async fn test_route_scoring_different_preferences() {
         assert!(route.score > 0.0);
     }
 
-    common::cleanup_test_db(&pool).await;
+    db.cleanup().await;
 }
 
 #[tokio::test]
 #[ignore]
-#[serial]
 async fn test_route_alternatives_use_different_waypoint_counts() {
-    let pool = common::setup_test_db().await;
-    common::cleanup_test_db(&pool).await;
+    let db = common::TestDb::new().await;
+    let pool = db.pool.clone();
 
     // Insert multiple POIs in a diverse arrangement to allow for 2, 3, and 4 waypoint routes
     let center = Coordinates::new(48.8566, 2.3522).unwrap();
@@ -322,8 +352,26 @@ async fn test_route_alternatives_use_different_waypoint_counts() {
     // Request 5 alternatives to get diverse waypoint counts
     let preferences = RoutePreferences {
         poi_categories: None,
+        excluded_poi_categories: None,
         hidden_gems: false,
         max_alternatives: 5,
+        poi_min_separation_km: None,
+        pace_min_per_km: None,
+        min_paved_fraction: None,
+        max_busy_road_fraction: None,
+        surface: Default::default(),
+        prefer_green: false,
+        step_free: false,
+        minimize_exposure: false,
+        max_elevation_gain_m: None,
+        prefer_flat: false,
+        costing: Default::default(),
+        include_visit_time: false,
+        golden_hour: false,
+        departure: None,
+        pareto: false,
+        seed: None,
+        must_include: Vec::new(),
     };
 
     // Use 2km tolerance so both 2-waypoint (~4-5km) and 3-waypoint (~5-6km) routes
@@ -367,5 +415,5 @@ async fn test_route_alternatives_use_different_waypoint_counts() {
         );
     }
 
-    common::cleanup_test_db(&pool).await;
+    db.cleanup().await;
 }
@@ -0,0 +1,147 @@
+//! Snapshots of the JSON the API serves, reduced to its shape (field names
+//! and value types), so a renamed, removed or retyped field — which breaks
+//! the mobile clients — shows up as a snapshot diff in review. Values are
+//! left out, so algorithm changes don't churn the snapshots.
+//!
+//! After an intended schema change: `cargo insta review` (or
+//! `INSTA_UPDATE=always cargo test --test schema_snapshots`) and commit the
+//! updated `tests/snapshots/`.
+
+use axum::http::StatusCode;
+use easyroute::models::saved_route::StaleReason;
+use easyroute::models::{
+    Departure, QualityTier, RoadProfile, Route, RoutePoi, RouteStrength, SnappedPoi, SpeedModels,
+    TransportMode,
+};
+use easyroute::services::route_generator::metrics_explanation::MetricsExplained;
+use easyroute::services::route_generator::route_metrics::RouteMetrics;
+use insta::assert_json_snapshot;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+mod common;
+
+use common::mock::{app, post_json, ring_of_pois, start, MockDirections};
+
+/// `value` with every leaf replaced by its type; arrays by the shape of
+/// their first element
+fn shape(value: &Value) -> Value {
+    match value {
+        Value::Null => json!("null"),
+        Value::Bool(_) => json!("bool"),
+        Value::Number(n) if n.is_f64() => json!("float"),
+        Value::Number(_) => json!("integer"),
+        Value::String(_) => json!("string"),
+        Value::Array(items) => Value::Array(items.first().map(shape).into_iter().collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(k, v)| (k.clone(), shape(v)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+/// A route with every optional part filled in
+fn full_route() -> Route {
+    let pois = ring_of_pois();
+    let mut path = vec![start()];
+    path.extend(pois[..4].iter().map(|p| p.coordinates));
+    path.push(start());
+    let waypoints = pois[..3]
+        .iter()
+        .enumerate()
+        .map(|(i, poi)| RoutePoi::new(poi.clone(), i as u32 + 1, i as f64 + 1.0))
+        .collect();
+
+    let mut route = Route::new(5.0, 60, path, waypoints)
+        .with_duration_estimates(&TransportMode::Walk, &SpeedModels::default())
+        .with_road_profile(Some(RoadProfile {
+            paved_fraction: 0.8,
+            busy_road_fraction: 0.1,
+            smooth_fraction: 0.7,
+            steps_count: 0,
+            max_incline_pct: Some(4.0),
+        }))
+        .with_visit_time();
+    route.snapped_pois = vec![SnappedPoi::new(pois[5].clone(), 2.5, 40.0)];
+    route.amenities = vec![SnappedPoi::new(pois[6].clone(), 3.0, 25.0)];
+    route.elevation_gain_m = Some(35.0);
+    route.environmental_exposure = Some(0.2);
+    route.score = 7.5;
+    route.quality_tier = Some(QualityTier::Silver);
+    route.strength = Some(RouteStrength::Shape);
+    let metrics = RouteMetrics::compute(&route, 12);
+    route.metrics_explained = Some(MetricsExplained::new(&route, &metrics));
+    route.metrics = Some(metrics);
+    route.mark_stale(vec![StaleReason::PoiMoved {
+        poi_id: pois[0].id,
+        name: pois[0].name.clone(),
+        moved_m: 140.0,
+    }]);
+    // 2026-06-21 18:00 UTC
+    let depart_at = OffsetDateTime::from_unix_timestamp(1_782_064_800).unwrap();
+    route.with_timeline(Departure::DepartAt(depart_at))
+}
+
+#[test]
+fn test_route_schema() {
+    assert_json_snapshot!(shape(&serde_json::to_value(full_route()).unwrap()));
+}
+
+#[test]
+fn test_route_metrics_schema() {
+    let metrics = full_route().metrics.unwrap();
+    assert_json_snapshot!(shape(&serde_json::to_value(metrics).unwrap()));
+}
+
+#[tokio::test]
+async fn test_loop_response_envelope_schema() {
+    let app = app(ring_of_pois(), Arc::new(MockDirections::default()));
+    let request = json!({
+        "start_point": {"lat": 48.8566, "lng": 2.3522},
+        "distance_km": 5.0,
+        "mode": "walk",
+        "preferences": {"seed": 7}
+    });
+
+    let (status, _, body) = post_json(&app, "/routes/loop", &request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_json_snapshot!(shape(&body));
+}
+
+#[tokio::test]
+async fn test_error_body_schemas() {
+    let ok = app(ring_of_pois(), Arc::new(MockDirections::default()));
+    let request = |distance_km: f64| {
+        json!({
+            "start_point": {"lat": 48.8566, "lng": 2.3522},
+            "distance_km": distance_km,
+            "mode": "walk"
+        })
+    };
+
+    let (status, _, bad_request) = post_json(&ok, "/routes/loop", &request(0.1)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_json_snapshot!("bad_request", shape(&bad_request));
+
+    let empty = app(Vec::new(), Arc::new(MockDirections::default()));
+    let (status, _, not_found) = post_json(&empty, "/routes/loop", &request(5.0)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_json_snapshot!("not_found", shape(&not_found));
+
+    let down = app(ring_of_pois(), Arc::new(MockDirections::failing()));
+    let (status, _, unavailable) = post_json(&down, "/routes/loop", &request(5.0)).await;
+    assert!(status.is_server_error());
+    assert_json_snapshot!("backend_unavailable", shape(&unavailable));
+}
+
+#[test]
+fn test_shape_keeps_names_and_types_only() {
+    let value = json!({"a": 1, "b": [{"c": 1.5}, {"c": 2.5}], "d": null, "e": []});
+    assert_eq!(
+        shape(&value),
+        json!({"a": "integer", "b": [{"c": "float"}], "d": "null", "e": []})
+    );
+}